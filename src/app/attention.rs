@@ -0,0 +1,95 @@
+use super::is_fetch_stale;
+use crate::workspace::RepositoryState;
+use std::time::SystemTime;
+
+/// Narrows the repository list to repositories that need attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AttentionFilter {
+    #[default]
+    All,
+    HasChanges,
+    Behind,
+    /// The current branch tracks a deleted remote branch.
+    GoneUpstream,
+    /// Not fetched within `STALE_FETCH_AGE`, or never.
+    NotFetched,
+}
+
+impl AttentionFilter {
+    pub const ALL: [AttentionFilter; 5] = [
+        AttentionFilter::All,
+        AttentionFilter::HasChanges,
+        AttentionFilter::Behind,
+        AttentionFilter::GoneUpstream,
+        AttentionFilter::NotFetched,
+    ];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            AttentionFilter::All => "attention_all",
+            AttentionFilter::HasChanges => "attention_has_changes",
+            AttentionFilter::Behind => "attention_behind",
+            AttentionFilter::GoneUpstream => "attention_gone_upstream",
+            AttentionFilter::NotFetched => "attention_not_fetched",
+        }
+    }
+
+    pub fn matches(self, repo: &RepositoryState) -> bool {
+        self.matches_at(repo, SystemTime::now())
+    }
+
+    pub fn matches_at(self, repo: &RepositoryState, now: SystemTime) -> bool {
+        let git_info = &repo.git_info;
+        match self {
+            AttentionFilter::All => true,
+            AttentionFilter::HasChanges => git_info.has_changes,
+            AttentionFilter::Behind => git_info.behind > 0,
+            AttentionFilter::GoneUpstream => git_info.upstream_gone,
+            AttentionFilter::NotFetched => is_fetch_stale(repo.last_fetch, now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitInfo;
+    use std::time::Duration;
+
+    fn repo(git_info: GitInfo) -> RepositoryState {
+        RepositoryState {
+            git_info,
+            ..RepositoryState::default()
+        }
+    }
+
+    #[test]
+    fn gone_upstream_filter_keeps_only_gone_branches() {
+        let gone = repo(GitInfo {
+            upstream_gone: true,
+            ..GitInfo::default()
+        });
+        let tracking = repo(GitInfo {
+            has_upstream: true,
+            ..GitInfo::default()
+        });
+
+        assert!(AttentionFilter::GoneUpstream.matches(&gone));
+        assert!(!AttentionFilter::GoneUpstream.matches(&tracking));
+        assert!(AttentionFilter::All.matches(&tracking));
+        assert!(!AttentionFilter::Behind.matches(&gone));
+    }
+
+    #[test]
+    fn not_fetched_filter_keeps_stale_and_never_fetched() {
+        let now = SystemTime::now();
+        let fetched_at = |hours_ago: u64| RepositoryState {
+            last_fetch: Some(now - Duration::from_secs(hours_ago * 60 * 60)),
+            ..RepositoryState::default()
+        };
+
+        assert!(AttentionFilter::NotFetched.matches_at(&RepositoryState::default(), now));
+        assert!(AttentionFilter::NotFetched.matches_at(&fetched_at(48), now));
+        assert!(!AttentionFilter::NotFetched.matches_at(&fetched_at(2), now));
+    }
+}
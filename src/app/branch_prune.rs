@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A merged branch offered for deletion; checked by default.
+pub struct PruneBranch {
+    pub name: String,
+    pub selected: bool,
+}
+
+/// Merged branches of one repository in the prune preview.
+pub struct PruneRepo {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub default_branch: String,
+    pub branches: Vec<PruneBranch>,
+}
+
+impl PruneRepo {
+    pub fn selected(&self) -> Vec<String> {
+        self.branches
+            .iter()
+            .filter(|branch| branch.selected)
+            .map(|branch| branch.name.clone())
+            .collect()
+    }
+}
+
+/// "Prune merged branches" preview for the active workspace, filled in as
+/// every repository reports its merged branches.
+pub struct PruneDialog {
+    pub repos: Vec<PruneRepo>,
+    /// Repositories with uncommitted changes, left out of the scan.
+    pub skipped_dirty: usize,
+    /// `(repository name, error)` of every scan that failed.
+    pub scan_failures: Vec<(String, String)>,
+    pending: HashSet<PathBuf>,
+}
+
+impl PruneDialog {
+    pub fn new(scanning: Vec<PathBuf>, skipped_dirty: usize) -> Self {
+        Self {
+            repos: Vec::new(),
+            skipped_dirty,
+            scan_failures: Vec::new(),
+            pending: scanning.into_iter().collect(),
+        }
+    }
+
+    /// Adds the merged branches of a scanned repository. Repositories with
+    /// nothing to delete are not listed.
+    pub fn found(
+        &mut self,
+        repo_path: &Path,
+        repo_name: String,
+        default_branch: String,
+        branches: Vec<String>,
+    ) {
+        if !self.pending.remove(repo_path) || branches.is_empty() {
+            return;
+        }
+        self.repos.push(PruneRepo {
+            repo_path: repo_path.to_path_buf(),
+            repo_name,
+            default_branch,
+            branches: branches
+                .into_iter()
+                .map(|name| PruneBranch {
+                    name,
+                    selected: true,
+                })
+                .collect(),
+        });
+        self.repos.sort_by(|a, b| a.repo_name.cmp(&b.repo_name));
+    }
+
+    /// The scan of `repo_path` failed; other repositories are ignored.
+    pub fn scan_failed(&mut self, repo_path: &Path, repo_name: String, error: String) {
+        if self.pending.remove(repo_path) {
+            self.scan_failures.push((repo_name, error));
+        }
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.repos
+            .iter()
+            .flat_map(|repo| &repo.branches)
+            .filter(|branch| branch.selected)
+            .count()
+    }
+}
+
+/// Deletion progress of a confirmed prune, summarised once every
+/// repository has reported back.
+pub struct PruneBatch {
+    pub total: usize,
+    pub deleted: usize,
+    /// Repositories where at least one branch was deleted.
+    pub repos_pruned: usize,
+    /// `(repository, branch, error)` for every branch that was not deleted.
+    pub failures: Vec<(PathBuf, String, String)>,
+    pending: HashSet<PathBuf>,
+}
+
+impl PruneBatch {
+    pub fn new(running: Vec<PathBuf>) -> Self {
+        let pending: HashSet<PathBuf> = running.into_iter().collect();
+        Self {
+            total: pending.len(),
+            deleted: 0,
+            repos_pruned: 0,
+            failures: Vec::new(),
+            pending,
+        }
+    }
+
+    pub fn complete(&mut self, repo_path: &Path, deleted: usize, failed: &[(String, String)]) {
+        if !self.pending.remove(repo_path) {
+            return;
+        }
+        self.deleted += deleted;
+        if deleted > 0 {
+            self.repos_pruned += 1;
+        }
+        self.failures.extend(
+            failed
+                .iter()
+                .map(|(branch, error)| (repo_path.to_path_buf(), branch.clone(), error.clone())),
+        );
+    }
+
+    /// The repository never ran, e.g. its queued operation was cancelled.
+    pub fn abandon(&mut self, repo_path: &Path) {
+        self.pending.remove(repo_path);
+    }
+
+    pub fn done(&self) -> usize {
+        self.total - self.pending.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_lists_only_repos_with_merged_branches() {
+        let mut dialog = PruneDialog::new(
+            vec![
+                PathBuf::from("/w/b"),
+                PathBuf::from("/w/a"),
+                PathBuf::from("/w/c"),
+            ],
+            2,
+        );
+
+        dialog.found(
+            Path::new("/w/b"),
+            "b".to_string(),
+            "main".to_string(),
+            vec!["feature/x".to_string(), "fix/y".to_string()],
+        );
+        dialog.found(
+            Path::new("/w/c"),
+            "c".to_string(),
+            "main".to_string(),
+            vec![],
+        );
+        assert!(dialog.is_scanning());
+
+        dialog.scan_failed(Path::new("/w/a"), "a".to_string(), "no HEAD".to_string());
+        dialog.scan_failed(
+            Path::new("/w/unknown"),
+            "unknown".to_string(),
+            "no HEAD".to_string(),
+        );
+
+        assert!(!dialog.is_scanning());
+        assert_eq!(dialog.repos.len(), 1);
+        assert_eq!(
+            dialog.scan_failures,
+            [("a".to_string(), "no HEAD".to_string())]
+        );
+        assert_eq!(dialog.skipped_dirty, 2);
+        assert_eq!(dialog.selected_count(), 2);
+
+        dialog.repos[0].branches[0].selected = false;
+        assert_eq!(dialog.repos[0].selected(), vec!["fix/y".to_string()]);
+    }
+
+    #[test]
+    fn batch_keeps_per_branch_failures_without_stopping() {
+        let mut batch = PruneBatch::new(vec![
+            PathBuf::from("/a"),
+            PathBuf::from("/b"),
+            PathBuf::from("/c"),
+        ]);
+
+        batch.complete(Path::new("/a"), 3, &[]);
+        batch.complete(
+            Path::new("/b"),
+            1,
+            &[(
+                "feature/wt".to_string(),
+                "checked out at '/tmp/wt'".to_string(),
+            )],
+        );
+        batch.complete(Path::new("/elsewhere"), 5, &[]);
+        assert_eq!(batch.done(), 2);
+
+        batch.abandon(Path::new("/c"));
+        assert!(batch.is_finished());
+        assert_eq!(batch.deleted, 4);
+        assert_eq!(batch.repos_pruned, 2);
+        assert_eq!(
+            batch.failures,
+            vec![(
+                PathBuf::from("/b"),
+                "feature/wt".to_string(),
+                "checked out at '/tmp/wt'".to_string()
+            )]
+        );
+    }
+}
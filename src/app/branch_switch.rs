@@ -0,0 +1,318 @@
+use crate::git::GitInfo;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// What "switch every repository to branch X" does in one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchPlan {
+    AlreadyOn,
+    CheckoutLocal,
+    /// Creates a local branch tracking `remote_branch` (`remotes/origin/x`).
+    TrackRemote {
+        remote_branch: String,
+    },
+    CreateFromDefault,
+    SkipMissing,
+    SkipDirty,
+    /// The status holds only the most recent branches; the full list is
+    /// being loaded before the repository can be planned.
+    LoadingBranches,
+}
+
+impl SwitchPlan {
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            SwitchPlan::AlreadyOn => "switch_plan_already_on",
+            SwitchPlan::CheckoutLocal => "switch_plan_checkout",
+            SwitchPlan::TrackRemote { .. } => "switch_plan_track",
+            SwitchPlan::CreateFromDefault => "switch_plan_create",
+            SwitchPlan::SkipMissing => "switch_plan_missing",
+            SwitchPlan::SkipDirty => "switch_plan_dirty",
+            SwitchPlan::LoadingBranches => "switch_plan_loading",
+        }
+    }
+
+    /// Runs a git operation when executed.
+    pub fn runs(&self) -> bool {
+        matches!(
+            self,
+            SwitchPlan::CheckoutLocal
+                | SwitchPlan::TrackRemote { .. }
+                | SwitchPlan::CreateFromDefault
+        )
+    }
+}
+
+/// Plans the switch from `all_branches`, the full branch list when it is
+/// loaded, or else the branch list in `GitInfo`. A repository with
+/// uncommitted changes is skipped unless it is already on `target`.
+pub fn plan_branch_switch(
+    info: &GitInfo,
+    all_branches: Option<&[String]>,
+    target: &str,
+    create_missing: bool,
+) -> SwitchPlan {
+    if info.current_branch.as_deref() == Some(target) {
+        return SwitchPlan::AlreadyOn;
+    }
+    if info.has_changes {
+        return SwitchPlan::SkipDirty;
+    }
+    plan_ignoring_changes(info, all_branches, target, create_missing)
+}
+
+/// The plan for a dirty repository the user explicitly chose to switch.
+pub fn plan_ignoring_changes(
+    info: &GitInfo,
+    all_branches: Option<&[String]>,
+    target: &str,
+    create_missing: bool,
+) -> SwitchPlan {
+    if info.current_branch.as_deref() == Some(target) {
+        return SwitchPlan::AlreadyOn;
+    }
+    // Урезанный список не доказывает, что ветки нет
+    let branches = match all_branches {
+        Some(branches) => branches,
+        None if info.branch_count_total > info.branches.len() => {
+            return SwitchPlan::LoadingBranches
+        }
+        None => &info.branches,
+    };
+    if branches.iter().any(|branch| branch == target) {
+        return SwitchPlan::CheckoutLocal;
+    }
+
+    // origin первым, затем любой другой remote
+    let remote_branch = std::iter::once("origin")
+        .chain(info.remotes.iter().map(String::as_str))
+        .map(|remote| format!("remotes/{}/{}", remote, target))
+        .find(|candidate| branches.contains(candidate));
+    if let Some(remote_branch) = remote_branch {
+        return SwitchPlan::TrackRemote { remote_branch };
+    }
+
+    if create_missing {
+        SwitchPlan::CreateFromDefault
+    } else {
+        SwitchPlan::SkipMissing
+    }
+}
+
+/// A repository in the bulk switch dialog.
+pub struct BranchSwitchRow {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub plan: SwitchPlan,
+    /// Switch even though the repository has uncommitted changes.
+    pub override_dirty: bool,
+}
+
+/// "Switch branch across repos" dialog for the active workspace.
+pub struct BranchSwitchDialog {
+    pub target: String,
+    pub create_missing: bool,
+    pub rows: Vec<BranchSwitchRow>,
+}
+
+/// Progress of an executed bulk switch, summarised once every repository
+/// has reported back.
+pub struct BranchSwitchBatch {
+    pub target: String,
+    pub total: usize,
+    pub skipped: usize,
+    pub succeeded: usize,
+    pub failed: Vec<PathBuf>,
+    pending: HashSet<PathBuf>,
+}
+
+impl BranchSwitchBatch {
+    pub fn new(target: String, running: Vec<PathBuf>, skipped: usize) -> Self {
+        let pending: HashSet<PathBuf> = running.into_iter().collect();
+        Self {
+            target,
+            total: pending.len(),
+            skipped,
+            succeeded: 0,
+            failed: Vec::new(),
+            pending,
+        }
+    }
+
+    /// Records the result of one repository; other repositories are ignored.
+    pub fn complete(&mut self, repo_path: &Path, success: bool) {
+        if !self.pending.remove(repo_path) {
+            return;
+        }
+        if success {
+            self.succeeded += 1;
+        } else {
+            self.failed.push(repo_path.to_path_buf());
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.total - self.pending.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(current: &str, branches: &[&str], has_changes: bool) -> GitInfo {
+        GitInfo {
+            current_branch: Some(current.to_string()),
+            branches: branches.iter().map(|b| b.to_string()).collect(),
+            remotes: vec!["origin".to_string(), "upstream".to_string()],
+            has_changes,
+            ..GitInfo::default()
+        }
+    }
+
+    const TARGET: &str = "release/2024.06";
+
+    #[test]
+    fn local_branch_is_checked_out() {
+        let info = info("main", &["main", TARGET, "remotes/origin/dev"], false);
+
+        assert_eq!(
+            plan_branch_switch(&info, None, TARGET, false),
+            SwitchPlan::CheckoutLocal
+        );
+    }
+
+    #[test]
+    fn remote_branch_gets_a_tracking_branch_preferring_origin() {
+        let info = info(
+            "main",
+            &[
+                "main",
+                "remotes/upstream/release/2024.06",
+                "remotes/origin/release/2024.06",
+            ],
+            false,
+        );
+
+        assert_eq!(
+            plan_branch_switch(&info, None, TARGET, false),
+            SwitchPlan::TrackRemote {
+                remote_branch: "remotes/origin/release/2024.06".to_string()
+            }
+        );
+
+        let upstream_only =
+            self::info("main", &["main", "remotes/upstream/release/2024.06"], false);
+        assert_eq!(
+            plan_branch_switch(&upstream_only, None, TARGET, false),
+            SwitchPlan::TrackRemote {
+                remote_branch: "remotes/upstream/release/2024.06".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn missing_branch_is_skipped_or_created() {
+        let info = info("main", &["main", "remotes/origin/main"], false);
+
+        assert_eq!(
+            plan_branch_switch(&info, None, TARGET, false),
+            SwitchPlan::SkipMissing
+        );
+        assert_eq!(
+            plan_branch_switch(&info, None, TARGET, true),
+            SwitchPlan::CreateFromDefault
+        );
+    }
+
+    #[test]
+    fn dirty_repos_are_skipped_unless_already_there() {
+        let dirty = info("main", &["main", TARGET], true);
+        assert_eq!(
+            plan_branch_switch(&dirty, None, TARGET, true),
+            SwitchPlan::SkipDirty
+        );
+        assert_eq!(
+            plan_ignoring_changes(&dirty, None, TARGET, true),
+            SwitchPlan::CheckoutLocal
+        );
+
+        let on_target = info(TARGET, &["main", TARGET], true);
+        assert_eq!(
+            plan_branch_switch(&on_target, None, TARGET, false),
+            SwitchPlan::AlreadyOn
+        );
+    }
+
+    #[test]
+    fn branch_names_must_match_exactly() {
+        // Префикс или похожее имя — не та ветка
+        let info = info(
+            "main",
+            &["release/2024.06-rc", "remotes/origin/release/2024"],
+            false,
+        );
+
+        assert_eq!(
+            plan_branch_switch(&info, None, TARGET, false),
+            SwitchPlan::SkipMissing
+        );
+        assert!(!SwitchPlan::SkipMissing.runs());
+        assert!(SwitchPlan::CreateFromDefault.runs());
+    }
+
+    #[test]
+    fn capped_branch_list_waits_for_the_full_one() {
+        // В статусе только последние ветки, нужная в него не попала
+        let capped = GitInfo {
+            branch_count_total: 3000,
+            ..info("main", &["main", "remotes/origin/main"], false)
+        };
+
+        assert_eq!(
+            plan_branch_switch(&capped, None, TARGET, true),
+            SwitchPlan::LoadingBranches
+        );
+        assert!(!SwitchPlan::LoadingBranches.runs());
+
+        let all = vec![
+            "main".to_string(),
+            "remotes/origin/main".to_string(),
+            "remotes/origin/release/2024.06".to_string(),
+        ];
+        assert_eq!(
+            plan_branch_switch(&capped, Some(&all), TARGET, true),
+            SwitchPlan::TrackRemote {
+                remote_branch: "remotes/origin/release/2024.06".to_string()
+            }
+        );
+        assert_eq!(
+            plan_branch_switch(&capped, Some(&all[..2]), TARGET, false),
+            SwitchPlan::SkipMissing
+        );
+    }
+
+    #[test]
+    fn batch_counts_results_of_its_own_repos() {
+        let mut batch = BranchSwitchBatch::new(
+            TARGET.to_string(),
+            vec![PathBuf::from("/a"), PathBuf::from("/b")],
+            3,
+        );
+
+        batch.complete(Path::new("/a"), true);
+        batch.complete(Path::new("/elsewhere"), false);
+        assert_eq!(batch.done(), 1);
+        assert!(!batch.is_finished());
+
+        batch.complete(Path::new("/b"), false);
+        assert!(batch.is_finished());
+        assert_eq!(batch.succeeded, 1);
+        assert_eq!(batch.failed, vec![PathBuf::from("/b")]);
+        assert_eq!(batch.skipped, 3);
+    }
+}
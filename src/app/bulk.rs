@@ -0,0 +1,410 @@
+use crate::workspace::{RepositoryState, WorkspaceId};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Fetch,
+    Pull,
+    Refresh,
+}
+
+impl BulkAction {
+    pub const ALL: [BulkAction; 3] = [BulkAction::Fetch, BulkAction::Pull, BulkAction::Refresh];
+
+    pub fn folder_menu_key(self) -> &'static str {
+        match self {
+            BulkAction::Fetch => "fetch_folder",
+            BulkAction::Pull => "pull_folder",
+            BulkAction::Refresh => "refresh_folder",
+        }
+    }
+
+    /// `{0}` folder.
+    pub fn confirm_key(self) -> &'static str {
+        match self {
+            BulkAction::Fetch => "confirm_fetch_folder",
+            BulkAction::Pull => "confirm_pull_folder",
+            BulkAction::Refresh => "confirm_refresh_folder",
+        }
+    }
+
+    /// `{0}` folder, `{1}` done, `{2}` total.
+    pub fn progress_key(self) -> &'static str {
+        match self {
+            BulkAction::Fetch => "folder_fetching",
+            BulkAction::Pull => "folder_pulling",
+            BulkAction::Refresh => "folder_refreshing",
+        }
+    }
+}
+
+/// A bulk operation, checked against the environments of the workspaces it
+/// touches before it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkRequest {
+    /// Fetch All of one workspace.
+    FetchWorkspace(WorkspaceId),
+    /// One fetch per repository across every workspace.
+    FetchAllWorkspaces,
+    /// `action` on the repositories under a tree folder or status group.
+    Folder {
+        folder: String,
+        repos: Vec<PathBuf>,
+        action: BulkAction,
+    },
+    /// Pull of one repository from its row.
+    Pull {
+        repo_path: PathBuf,
+        repo_name: String,
+    },
+    /// The previewed plan of the branch switch dialog.
+    SwitchBranch,
+}
+
+impl BulkRequest {
+    /// Refreshing only reads the status and never needs a confirmation.
+    pub fn mutates(&self) -> bool {
+        !matches!(
+            self,
+            BulkRequest::Folder {
+                action: BulkAction::Refresh,
+                ..
+            }
+        )
+    }
+
+    /// A single repository is not a bulk action for `confirm_bulk_actions`;
+    /// only a Prod label asks before it.
+    pub fn is_bulk(&self) -> bool {
+        !matches!(self, BulkRequest::Pull { .. })
+    }
+}
+
+/// Progress of a bulk action started from a tree folder node.
+pub struct FolderOperation {
+    pub action: BulkAction,
+    pub folder: String,
+    pub total: usize,
+    pending: HashSet<PathBuf>,
+}
+
+impl FolderOperation {
+    pub fn new(action: BulkAction, folder: String, repos: &[PathBuf]) -> Self {
+        let pending: HashSet<PathBuf> = repos.iter().cloned().collect();
+        Self {
+            action,
+            folder,
+            total: pending.len(),
+            pending,
+        }
+    }
+
+    pub fn complete(&mut self, repo_path: &Path) {
+        self.pending.remove(repo_path);
+    }
+
+    pub fn done(&self) -> usize {
+        self.total - self.pending.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// One `git fetch` of a Fetch All batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchJob {
+    pub repo_path: PathBuf,
+    /// Fetch with `--recurse-submodules=on-demand`.
+    pub recurse_submodules: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FetchPlan {
+    pub jobs: Vec<FetchJob>,
+    /// `(submodule, parent)`: skipped because the parent's fetch covers it.
+    pub covered: Vec<(PathBuf, PathBuf)>,
+    /// `(entry, job)`: skipped by `dedupe_by` because the job runs on the
+    /// same `.git`, e.g. a monorepo service and its root.
+    pub merged: Vec<(PathBuf, PathBuf)>,
+}
+
+impl FetchPlan {
+    /// One job per repository, without submodule handling.
+    pub fn plain(repos: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            jobs: repos
+                .into_iter()
+                .map(|repo_path| FetchJob {
+                    repo_path,
+                    recurse_submodules: false,
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Keeps one job per `key`, so a folder registered twice in a workspace
+    /// is fetched once; the kept job recurses if any of them did.
+    pub fn dedupe_by(mut self, key: impl Fn(&Path) -> PathBuf) -> Self {
+        let mut jobs: Vec<FetchJob> = Vec::with_capacity(self.jobs.len());
+        let mut seen: HashMap<PathBuf, usize> = HashMap::with_capacity(self.jobs.len());
+        for job in self.jobs {
+            let job_key = key(&job.repo_path);
+            match seen.get(&job_key) {
+                Some(&index) => {
+                    let kept = &mut jobs[index];
+                    kept.recurse_submodules |= job.recurse_submodules;
+                    if kept.repo_path != job.repo_path {
+                        self.merged.push((job.repo_path, kept.repo_path.clone()));
+                    }
+                }
+                None => {
+                    seen.insert(job_key, jobs.len());
+                    jobs.push(job);
+                }
+            }
+        }
+        self.jobs = jobs;
+        self
+    }
+
+    /// Adds the jobs of `other`. A repository covered by a parent's fetch
+    /// in one plan is not fetched again as a job of another.
+    pub fn merge(mut self, other: FetchPlan) -> Self {
+        self.jobs.extend(other.jobs);
+        self.covered.extend(other.covered);
+        self.merged.extend(other.merged);
+        let covered: HashSet<&PathBuf> = self.covered.iter().map(|(path, _)| path).collect();
+        self.jobs.retain(|job| !covered.contains(&job.repo_path));
+        self
+    }
+}
+
+/// Registered repository the submodule at `index` is checked out in,
+/// following nested submodules up to the outermost one.
+fn covering_parent(repos: &[RepositoryState], index: usize) -> Option<usize> {
+    let mut current = index;
+    let mut parent = None;
+    while repos[current].git_info.is_submodule {
+        let path = &repos[current].path;
+        let Some(next) = repos
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.path != *path && path.starts_with(&r.path))
+            .max_by_key(|(_, r)| r.path.components().count())
+            .map(|(i, _)| i)
+        else {
+            break;
+        };
+        parent = Some(next);
+        current = next;
+    }
+    parent
+}
+
+/// Splits a Fetch All batch so that a parent repository fetches its
+/// submodules and submodules registered alongside it are not fetched twice.
+pub fn plan_fetch_batch(repos: &[RepositoryState]) -> FetchPlan {
+    let parents: Vec<Option<usize>> = (0..repos.len())
+        .map(|index| covering_parent(repos, index))
+        .collect();
+
+    let mut plan = FetchPlan::default();
+    for (index, repo) in repos.iter().enumerate() {
+        match parents[index] {
+            Some(parent) => plan
+                .covered
+                .push((repo.path.clone(), repos[parent].path.clone())),
+            None => plan.jobs.push(FetchJob {
+                repo_path: repo.path.clone(),
+                recurse_submodules: parents.contains(&Some(index)),
+            }),
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::duplicate_key;
+
+    fn repo(path: &str, is_submodule: bool) -> RepositoryState {
+        let mut repo = RepositoryState::new(PathBuf::from(path));
+        repo.git_info.is_submodule = is_submodule;
+        repo
+    }
+
+    fn job(path: &str, recurse_submodules: bool) -> FetchJob {
+        FetchJob {
+            repo_path: PathBuf::from(path),
+            recurse_submodules,
+        }
+    }
+
+    #[test]
+    fn registered_submodules_are_fetched_through_their_parent() {
+        let repos = vec![
+            repo("/src/app", false),
+            repo("/src/app/libs/core", true),
+            repo("/src/app/libs/core/vendor/zlib", true),
+            repo("/src/other", false),
+        ];
+
+        let plan = plan_fetch_batch(&repos);
+
+        assert_eq!(
+            plan.jobs,
+            vec![job("/src/app", true), job("/src/other", false)]
+        );
+        assert_eq!(
+            plan.covered,
+            vec![
+                (
+                    PathBuf::from("/src/app/libs/core"),
+                    PathBuf::from("/src/app")
+                ),
+                (
+                    PathBuf::from("/src/app/libs/core/vendor/zlib"),
+                    PathBuf::from("/src/app")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn submodule_without_its_parent_is_fetched_on_its_own() {
+        let repos = vec![
+            repo("/src/app/libs/core", true),
+            repo("/src/application", false),
+        ];
+
+        let plan = plan_fetch_batch(&repos);
+
+        assert_eq!(
+            plan.jobs,
+            vec![
+                job("/src/app/libs/core", false),
+                job("/src/application", false)
+            ]
+        );
+        assert!(plan.covered.is_empty());
+    }
+
+    #[test]
+    fn parent_without_registered_submodules_fetches_plainly() {
+        let repos = vec![repo("/src/app", false), repo("/src/app-docs", false)];
+
+        let plan = plan_fetch_batch(&repos);
+
+        assert_eq!(
+            plan.jobs,
+            vec![job("/src/app", false), job("/src/app-docs", false)]
+        );
+        assert!(plan.covered.is_empty());
+    }
+
+    #[test]
+    fn fetch_all_schedules_each_folder_once() {
+        // Одна и та же папка дважды в одной области: со слешем и без
+        let repos = vec![
+            repo("/src/app", false),
+            repo("/src/app/", false),
+            repo("/src/app/./libs/core", true),
+            repo("/src/app/libs/core", true),
+            repo("/src/web", false),
+        ];
+
+        let plan = plan_fetch_batch(&repos).dedupe_by(duplicate_key);
+
+        assert_eq!(
+            plan.jobs,
+            vec![job("/src/app", true), job("/src/web", false)]
+        );
+
+        let plain = FetchPlan::plain(repos.iter().map(|r| r.path.clone())).dedupe_by(duplicate_key);
+        assert_eq!(
+            plain.jobs,
+            vec![
+                job("/src/app", false),
+                job("/src/app/./libs/core", false),
+                job("/src/web", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn monorepo_services_share_the_fetch_of_their_root() {
+        let root = PathBuf::from("/src/mono");
+        let key = |path: &Path| {
+            if path.starts_with(&root) {
+                root.clone()
+            } else {
+                path.to_path_buf()
+            }
+        };
+
+        let plan = FetchPlan::plain(
+            ["/src/mono", "/src/mono/api", "/src/mono/web", "/src/other"].map(PathBuf::from),
+        )
+        .dedupe_by(key);
+
+        assert_eq!(
+            plan.jobs,
+            vec![job("/src/mono", false), job("/src/other", false)]
+        );
+        assert_eq!(
+            plan.merged,
+            vec![
+                (PathBuf::from("/src/mono/api"), root.clone()),
+                (PathBuf::from("/src/mono/web"), root.clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn workspaces_share_one_plan() {
+        // app с подмодулем в одной области, тот же app и подмодуль отдельно в другой
+        let dev = plan_fetch_batch(&[repo("/src/app", false), repo("/src/app/libs/core", true)]);
+        let prod = plan_fetch_batch(&[
+            repo("/src/app/", false),
+            repo("/src/app/libs/core", true),
+            repo("/src/billing", false),
+        ]);
+        let ops = plan_fetch_batch(&[repo("/src/app/libs/core", true)]);
+
+        let plan = FetchPlan::default()
+            .merge(dev)
+            .merge(prod)
+            .merge(ops)
+            .dedupe_by(duplicate_key);
+
+        assert_eq!(
+            plan.jobs,
+            vec![job("/src/app", true), job("/src/billing", false)]
+        );
+    }
+
+    #[test]
+    fn only_mutating_bulk_requests_are_guarded() {
+        let folder = |action| BulkRequest::Folder {
+            folder: "/src".to_string(),
+            repos: vec![PathBuf::from("/src/app")],
+            action,
+        };
+        let pull = BulkRequest::Pull {
+            repo_path: PathBuf::from("/src/app"),
+            repo_name: "app".to_string(),
+        };
+
+        assert!(folder(BulkAction::Pull).mutates());
+        assert!(folder(BulkAction::Fetch).mutates());
+        assert!(!folder(BulkAction::Refresh).mutates());
+        assert!(pull.mutates());
+        assert!(!pull.is_bulk());
+        assert!(BulkRequest::SwitchBranch.is_bulk());
+    }
+}
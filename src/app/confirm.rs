@@ -0,0 +1,284 @@
+use super::BulkRequest;
+use crate::workspace::{Workspace, WorkspaceId};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Destructive actions that wait for an explicit confirmation dialog.
+#[derive(Debug, Clone)]
+pub enum ConfirmAction {
+    PushNoVerify {
+        repo_path: PathBuf,
+        repo_name: String,
+    },
+    /// A bulk operation on a Prod workspace, or on any workspace while
+    /// `confirm_bulk_actions` is on.
+    Bulk {
+        request: BulkRequest,
+        /// Workspace, folder or repository name; the repository count across
+        /// all workspaces; the target branch of a switch.
+        subject: String,
+    },
+    /// Global `safe.directory` change, affects every git client.
+    MarkSafeDirectory {
+        repo_path: PathBuf,
+        repo_name: String,
+    },
+    DeleteBranches {
+        repo_path: PathBuf,
+        repo_name: String,
+        branches: Vec<String>,
+        /// `git branch -D`: the branches were checked against the default
+        /// branch rather than HEAD.
+        force: bool,
+    },
+    /// Switches to `default_branch`, then force-deletes the checked-out
+    /// `branch` whose upstream is gone.
+    DeleteGoneBranch {
+        repo_path: PathBuf,
+        repo_name: String,
+        branch: String,
+        default_branch: String,
+        /// Commits found only on `branch` when the dialog opened.
+        local_commits: usize,
+    },
+    /// `git push --mirror`; also needs `required_phrase` typed in.
+    PushMirror {
+        repo_path: PathBuf,
+        repo_name: String,
+        remote: String,
+    },
+    /// `git stash drop`: the entry is gone for good.
+    DropStash {
+        repo_path: PathBuf,
+        repo_name: String,
+        index: usize,
+        /// The entry as listed in the stash window.
+        stash: String,
+    },
+    /// Removes a workspace that still lists repositories; large ones also
+    /// need the workspace name typed in, or Shift held.
+    /// Found again by id on confirm: the list may change while it is open.
+    RemoveWorkspace {
+        workspace_id: WorkspaceId,
+        workspace_name: String,
+        repo_count: usize,
+        typed: bool,
+    },
+}
+
+/// How hard it is to remove a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalConfirmation {
+    None,
+    Simple,
+    /// The workspace name must be typed before Confirm unlocks.
+    Typed,
+}
+
+/// An empty workspace goes without asking; one with `typed_threshold` or
+/// more repositories needs its name typed. A threshold of 0 never asks for
+/// typing.
+pub fn workspace_removal_confirmation(
+    repo_count: usize,
+    typed_threshold: usize,
+) -> RemovalConfirmation {
+    if repo_count == 0 {
+        RemovalConfirmation::None
+    } else if typed_threshold > 0 && repo_count >= typed_threshold {
+        RemovalConfirmation::Typed
+    } else {
+        RemovalConfirmation::Simple
+    }
+}
+
+/// How long the Undo toast stays after a workspace is removed.
+pub const WORKSPACE_UNDO_WINDOW: Duration = Duration::from_secs(15);
+
+/// A removed workspace kept for this session so it can be put back where
+/// it was.
+pub struct RemovedWorkspace {
+    pub workspace: Workspace,
+    pub index: usize,
+    pub removed_at: Instant,
+}
+
+impl RemovedWorkspace {
+    pub fn can_undo(&self, now: Instant) -> bool {
+        now.duration_since(self.removed_at) < WORKSPACE_UNDO_WINDOW
+    }
+}
+
+/// Phrase typed to confirm a mirror push.
+pub const MIRROR_CONFIRMATION_PHRASE: &str = "MIRROR";
+
+impl ConfirmAction {
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            ConfirmAction::PushNoVerify { .. } => "confirm_push_no_verify",
+            ConfirmAction::Bulk { request, .. } => match request {
+                BulkRequest::FetchWorkspace(_) => "confirm_fetch_all",
+                BulkRequest::FetchAllWorkspaces => "confirm_fetch_all_workspaces",
+                BulkRequest::Folder { action, .. } => action.confirm_key(),
+                BulkRequest::Pull { .. } => "confirm_pull",
+                BulkRequest::SwitchBranch => "confirm_branch_switch",
+            },
+            ConfirmAction::MarkSafeDirectory { .. } => "confirm_mark_safe_directory",
+            ConfirmAction::DeleteBranches { .. } => "confirm_delete_branches",
+            ConfirmAction::DeleteGoneBranch {
+                local_commits: 0, ..
+            } => "confirm_delete_gone_branch",
+            ConfirmAction::DeleteGoneBranch { .. } => "confirm_delete_gone_branch_commits",
+            ConfirmAction::PushMirror { .. } => "confirm_push_mirror",
+            ConfirmAction::DropStash { .. } => "confirm_drop_stash",
+            ConfirmAction::RemoveWorkspace { .. } => "confirm_remove_workspace",
+        }
+    }
+
+    /// Repository or workspace name shown in the confirmation message.
+    pub fn subject(&self) -> &str {
+        match self {
+            ConfirmAction::PushNoVerify { repo_name, .. } => repo_name,
+            ConfirmAction::Bulk { subject, .. } => subject,
+            ConfirmAction::MarkSafeDirectory { repo_name, .. } => repo_name,
+            ConfirmAction::DeleteBranches { repo_name, .. } => repo_name,
+            ConfirmAction::DeleteGoneBranch { repo_name, .. } => repo_name,
+            ConfirmAction::PushMirror { repo_name, .. } => repo_name,
+            ConfirmAction::DropStash { repo_name, .. } => repo_name,
+            ConfirmAction::RemoveWorkspace { workspace_name, .. } => workspace_name,
+        }
+    }
+
+    /// Arguments of the confirmation message: the subject, then whatever
+    /// else the message mentions.
+    pub fn message_args(&self) -> Vec<String> {
+        let mut args = vec![self.subject().to_string()];
+        match self {
+            ConfirmAction::RemoveWorkspace { repo_count, .. } => args.push(repo_count.to_string()),
+            ConfirmAction::DeleteGoneBranch { local_commits, .. } => {
+                args.push(local_commits.to_string())
+            }
+            _ => {}
+        }
+        args
+    }
+
+    /// Exactly what the action will remove, listed under the message.
+    pub fn items(&self) -> &[String] {
+        match self {
+            ConfirmAction::DeleteBranches { branches, .. } => branches,
+            ConfirmAction::DeleteGoneBranch { branch, .. } => std::slice::from_ref(branch),
+            ConfirmAction::PushMirror { remote, .. } => std::slice::from_ref(remote),
+            ConfirmAction::DropStash { stash, .. } => std::slice::from_ref(stash),
+            _ => &[],
+        }
+    }
+
+    /// Text that must be typed before the Confirm button unlocks.
+    pub fn required_phrase(&self) -> Option<&str> {
+        match self {
+            ConfirmAction::PushMirror { .. } => Some(MIRROR_CONFIRMATION_PHRASE),
+            ConfirmAction::RemoveWorkspace {
+                workspace_name,
+                typed: true,
+                ..
+            } => Some(workspace_name),
+            _ => None,
+        }
+    }
+
+    /// Holding Shift unlocks Confirm instead of typing the phrase.
+    pub fn shift_unlocks(&self) -> bool {
+        matches!(self, ConfirmAction::RemoveWorkspace { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removal_confirmation_grows_with_workspace_size() {
+        assert_eq!(
+            workspace_removal_confirmation(0, 20),
+            RemovalConfirmation::None
+        );
+        assert_eq!(
+            workspace_removal_confirmation(1, 20),
+            RemovalConfirmation::Simple
+        );
+        assert_eq!(
+            workspace_removal_confirmation(19, 20),
+            RemovalConfirmation::Simple
+        );
+        assert_eq!(
+            workspace_removal_confirmation(20, 20),
+            RemovalConfirmation::Typed
+        );
+        // 0 отключает ввод имени, но не само подтверждение
+        assert_eq!(
+            workspace_removal_confirmation(500, 0),
+            RemovalConfirmation::Simple
+        );
+    }
+
+    #[test]
+    fn typed_removal_requires_the_workspace_name() {
+        let action = ConfirmAction::RemoveWorkspace {
+            workspace_id: WorkspaceId(2),
+            workspace_name: "Monolith".to_string(),
+            repo_count: 50,
+            typed: true,
+        };
+
+        assert_eq!(action.required_phrase(), Some("Monolith"));
+        assert_eq!(action.message_args(), ["Monolith", "50"]);
+    }
+
+    #[test]
+    fn gone_branch_with_local_commits_warns_about_them() {
+        let action = |local_commits| ConfirmAction::DeleteGoneBranch {
+            repo_path: PathBuf::from("/src/api"),
+            repo_name: "api".to_string(),
+            branch: "feature".to_string(),
+            default_branch: "main".to_string(),
+            local_commits,
+        };
+
+        assert_eq!(action(0).message_key(), "confirm_delete_gone_branch");
+        assert_eq!(
+            action(3).message_key(),
+            "confirm_delete_gone_branch_commits"
+        );
+        assert_eq!(action(3).message_args(), ["api", "3"]);
+    }
+
+    #[test]
+    fn undo_expires_after_the_window() {
+        let removed_at = Instant::now();
+        let removed = RemovedWorkspace {
+            workspace: Workspace::new("Old"),
+            index: 0,
+            removed_at,
+        };
+
+        assert!(removed.can_undo(removed_at + Duration::from_secs(14)));
+        assert!(!removed.can_undo(removed_at + WORKSPACE_UNDO_WINDOW));
+    }
+
+    #[test]
+    fn stash_drop_names_the_entry() {
+        let action = ConfirmAction::DropStash {
+            repo_path: PathBuf::from("/src/api"),
+            repo_name: "api".to_string(),
+            index: 1,
+            stash: "stash@{1}  WIP on main: 1a2b3c4 Fix login".to_string(),
+        };
+
+        assert_eq!(action.message_key(), "confirm_drop_stash");
+        assert_eq!(
+            action.items(),
+            ["stash@{1}  WIP on main: 1a2b3c4 Fix login"]
+        );
+        assert_eq!(action.required_phrase(), None);
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStage {
+    /// Unmerged files remain (or have not been re-checked yet).
+    Conflicted,
+    /// A re-check is running.
+    Checking,
+    /// The last re-check found no unmerged files.
+    Resolved,
+    /// Abort or continue is running.
+    Finishing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictAction {
+    OpenFile,
+    Recheck,
+    Abort,
+    Continue,
+}
+
+/// Conflict panel shown after a pull stopped with merge conflicts.
+pub struct ConflictPanel {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub files: Vec<String>,
+    pub stage: ConflictStage,
+}
+
+impl ConflictPanel {
+    pub fn new(repo_path: PathBuf, repo_name: String, files: Vec<String>) -> Self {
+        Self {
+            repo_path,
+            repo_name,
+            files,
+            stage: ConflictStage::Conflicted,
+        }
+    }
+
+    /// "Mark resolved & continue" waits for a re-check that came back empty,
+    /// so a merge is never committed with conflict markers still in it.
+    pub fn is_enabled(&self, action: ConflictAction) -> bool {
+        match action {
+            ConflictAction::OpenFile => !self.files.is_empty(),
+            ConflictAction::Recheck => {
+                matches!(
+                    self.stage,
+                    ConflictStage::Conflicted | ConflictStage::Resolved
+                )
+            }
+            ConflictAction::Abort => {
+                matches!(
+                    self.stage,
+                    ConflictStage::Conflicted | ConflictStage::Resolved
+                )
+            }
+            ConflictAction::Continue => self.stage == ConflictStage::Resolved,
+        }
+    }
+
+    pub fn start_check(&mut self) {
+        self.stage = ConflictStage::Checking;
+    }
+
+    pub fn checked(&mut self, files: Vec<String>) {
+        self.stage = if files.is_empty() {
+            ConflictStage::Resolved
+        } else {
+            ConflictStage::Conflicted
+        };
+        self.files = files;
+    }
+
+    pub fn start_finish(&mut self) {
+        self.stage = ConflictStage::Finishing;
+    }
+
+    /// Abort or continue failed; the merge is still there to act on.
+    pub fn failed(&mut self) {
+        self.stage = ConflictStage::Conflicted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel() -> ConflictPanel {
+        ConflictPanel::new(
+            PathBuf::from("/r/api"),
+            "api".to_string(),
+            vec!["src/main.rs".to_string()],
+        )
+    }
+
+    #[test]
+    fn continue_needs_an_empty_recheck() {
+        let mut panel = panel();
+        assert!(!panel.is_enabled(ConflictAction::Continue));
+        assert!(panel.is_enabled(ConflictAction::Abort));
+        assert!(panel.is_enabled(ConflictAction::OpenFile));
+
+        panel.start_check();
+        assert!(!panel.is_enabled(ConflictAction::Recheck));
+        assert!(!panel.is_enabled(ConflictAction::Continue));
+
+        panel.checked(vec!["src/main.rs".to_string()]);
+        assert_eq!(panel.stage, ConflictStage::Conflicted);
+        assert!(!panel.is_enabled(ConflictAction::Continue));
+
+        panel.start_check();
+        panel.checked(Vec::new());
+        assert_eq!(panel.stage, ConflictStage::Resolved);
+        assert!(panel.is_enabled(ConflictAction::Continue));
+        assert!(!panel.is_enabled(ConflictAction::OpenFile));
+    }
+
+    #[test]
+    fn nothing_runs_while_finishing() {
+        let mut panel = panel();
+        panel.checked(Vec::new());
+        panel.start_finish();
+
+        for action in [
+            ConflictAction::Recheck,
+            ConflictAction::Abort,
+            ConflictAction::Continue,
+        ] {
+            assert!(!panel.is_enabled(action));
+        }
+
+        panel.failed();
+        assert!(panel.is_enabled(ConflictAction::Abort));
+        assert!(!panel.is_enabled(ConflictAction::Continue));
+    }
+}
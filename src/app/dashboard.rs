@@ -0,0 +1,167 @@
+use crate::workspace::Workspace;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Bar of the workspace size chart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSize {
+    pub name: String,
+    pub repos: usize,
+    pub color: Option<[u8; 3]>,
+}
+
+/// Totals across every workspace for the dashboard window. A repository
+/// listed in several workspaces is counted once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlobalStats {
+    pub total_repos: usize,
+    pub total_behind: usize,
+    pub total_ahead: usize,
+    pub with_conflicts: usize,
+    pub with_errors: usize,
+    /// On a branch that tracks nothing; detached or not yet loaded
+    /// repositories are left out.
+    pub without_upstream: usize,
+    /// Oldest last commit, as a unix time.
+    pub most_stale: Option<(PathBuf, i64)>,
+    /// Most commits in the cached activity sparkline.
+    pub most_active: Option<(PathBuf, u32)>,
+    pub workspace_sizes: Vec<WorkspaceSize>,
+}
+
+impl GlobalStats {
+    pub fn compute(
+        workspaces: &[Workspace],
+        has_conflicts: impl Fn(&Path) -> bool,
+        has_error: impl Fn(&Path) -> bool,
+        activity: impl Fn(&Path) -> Option<u32>,
+    ) -> Self {
+        let mut stats = GlobalStats {
+            workspace_sizes: workspaces
+                .iter()
+                .map(|workspace| WorkspaceSize {
+                    name: workspace.name.clone(),
+                    repos: workspace.repository_count(),
+                    color: workspace.color,
+                })
+                .collect(),
+            ..Self::default()
+        };
+
+        let mut seen = HashSet::new();
+        for repo in workspaces.iter().flat_map(|w| &w.repositories) {
+            if !seen.insert(&repo.path) {
+                continue;
+            }
+            let info = &repo.git_info;
+            stats.total_repos += 1;
+            stats.total_ahead += info.ahead;
+            stats.total_behind += info.behind;
+            if has_conflicts(&repo.path) {
+                stats.with_conflicts += 1;
+            }
+            if has_error(&repo.path) {
+                stats.with_errors += 1;
+            }
+            if info.current_branch.is_some() && !info.has_upstream {
+                stats.without_upstream += 1;
+            }
+            if let Some(time) = info.last_commit_time {
+                if stats
+                    .most_stale
+                    .as_ref()
+                    .is_none_or(|(_, oldest)| time < *oldest)
+                {
+                    stats.most_stale = Some((repo.path.clone(), time));
+                }
+            }
+            if let Some(commits) = activity(&repo.path).filter(|commits| *commits > 0) {
+                if stats
+                    .most_active
+                    .as_ref()
+                    .is_none_or(|(_, most)| commits > *most)
+                {
+                    stats.most_active = Some((repo.path.clone(), commits));
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn largest_workspace(&self) -> usize {
+        self.workspace_sizes
+            .iter()
+            .map(|size| size.repos)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::RepositoryState;
+
+    fn repo(path: &str, ahead: usize, behind: usize, last_commit: i64) -> RepositoryState {
+        let mut repo = RepositoryState {
+            path: PathBuf::from(path),
+            ..RepositoryState::default()
+        };
+        repo.git_info.current_branch = Some("main".to_string());
+        repo.git_info.has_upstream = true;
+        repo.git_info.ahead = ahead;
+        repo.git_info.behind = behind;
+        repo.git_info.last_commit_time = Some(last_commit);
+        repo
+    }
+
+    #[test]
+    fn totals_count_shared_repositories_once() {
+        let mut work = Workspace::new("work");
+        work.repositories = vec![repo("/src/api", 2, 5, 300), repo("/src/web", 0, 1, 100)];
+        let mut local = repo("/src/tool", 1, 0, 200);
+        local.git_info.has_upstream = false;
+        let mut oss = Workspace::new("oss");
+        oss.repositories = vec![repo("/src/api", 2, 5, 300), local];
+
+        let stats = GlobalStats::compute(
+            &[work, oss],
+            |path| path == Path::new("/src/web"),
+            |_| false,
+            |path| (path == Path::new("/src/api")).then_some(12),
+        );
+
+        assert_eq!(stats.total_repos, 3);
+        assert_eq!((stats.total_ahead, stats.total_behind), (3, 6));
+        assert_eq!(stats.with_conflicts, 1);
+        assert_eq!(stats.without_upstream, 1);
+        assert_eq!(stats.most_stale, Some((PathBuf::from("/src/web"), 100)));
+        assert_eq!(stats.most_active, Some((PathBuf::from("/src/api"), 12)));
+        assert_eq!(
+            stats
+                .workspace_sizes
+                .iter()
+                .map(|size| size.repos)
+                .collect::<Vec<_>>(),
+            [2, 2]
+        );
+        assert_eq!(stats.largest_workspace(), 2);
+    }
+
+    #[test]
+    fn unloaded_repositories_do_not_count_as_missing_upstream() {
+        let mut workspace = Workspace::new("new");
+        workspace.repositories = vec![RepositoryState {
+            path: PathBuf::from("/src/fresh"),
+            ..RepositoryState::default()
+        }];
+
+        let stats = GlobalStats::compute(&[workspace], |_| false, |_| true, |_| None);
+
+        assert_eq!(stats.total_repos, 1);
+        assert_eq!(stats.with_errors, 1);
+        assert_eq!(stats.without_upstream, 0);
+        assert_eq!(stats.most_stale, None);
+        assert_eq!(stats.most_active, None);
+    }
+}
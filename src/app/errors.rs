@@ -0,0 +1,145 @@
+use crate::git::{classify_git_error, GitErrorKind};
+use crate::ui::ellipsize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone)]
+pub struct RepoError {
+    pub message: String,
+    pub kind: GitErrorKind,
+    pub occurred_at: SystemTime,
+    pub retrying: bool,
+}
+
+impl RepoError {
+    /// First `max_chars` characters of the message, cut on a char boundary.
+    pub fn summary(&self, max_chars: usize) -> String {
+        ellipsize(&self.message, max_chars).into_owned()
+    }
+}
+
+/// Most recent error per repository. An entry stays until a status update
+/// for that repository succeeds, whichever action triggered it.
+#[derive(Debug, Default)]
+pub struct RepoErrors {
+    errors: HashMap<PathBuf, RepoError>,
+}
+
+impl RepoErrors {
+    pub fn record<T: Into<String>>(&mut self, path: PathBuf, message: T) {
+        let message = message.into();
+        self.errors.insert(
+            path,
+            RepoError {
+                kind: classify_git_error(&message),
+                message,
+                occurred_at: SystemTime::now(),
+                retrying: false,
+            },
+        );
+    }
+
+    pub fn begin_retry(&mut self, path: &Path) -> bool {
+        match self.errors.get_mut(path) {
+            Some(error) => {
+                error.retrying = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn resolve(&mut self, path: &Path) -> Option<RepoError> {
+        self.errors.remove(path)
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&RepoError> {
+        self.errors.get(path)
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.errors.contains_key(path)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.errors.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path() -> PathBuf {
+        PathBuf::from("/repos/api")
+    }
+
+    #[test]
+    fn recorded_error_is_visible_until_resolved() {
+        let mut errors = RepoErrors::default();
+        errors.record(path(), "fatal: not a git repository");
+
+        assert!(errors.contains(&path()));
+        assert_eq!(
+            errors.get(&path()).unwrap().message,
+            "fatal: not a git repository"
+        );
+
+        assert!(errors.resolve(&path()).is_some());
+        assert!(!errors.contains(&path()));
+    }
+
+    #[test]
+    fn retry_keeps_entry_until_success() {
+        let mut errors = RepoErrors::default();
+        errors.record(path(), "timeout");
+
+        assert!(errors.begin_retry(&path()));
+        let error = errors.get(&path()).unwrap();
+        assert!(error.retrying);
+        assert_eq!(error.message, "timeout");
+
+        errors.resolve(&path());
+        assert!(!errors.contains(&path()));
+    }
+
+    #[test]
+    fn failed_retry_replaces_message_and_clears_retrying() {
+        let mut errors = RepoErrors::default();
+        errors.record(path(), "timeout");
+        errors.begin_retry(&path());
+        errors.record(path(), "connection refused");
+
+        let error = errors.get(&path()).unwrap();
+        assert!(!error.retrying);
+        assert_eq!(error.message, "connection refused");
+    }
+
+    #[test]
+    fn retry_without_error_is_a_no_op() {
+        let mut errors = RepoErrors::default();
+        assert!(!errors.begin_retry(&path()));
+        assert!(errors.resolve(&path()).is_none());
+    }
+
+    #[test]
+    fn summary_truncates_on_char_boundary() {
+        let error = RepoError {
+            message: "ошибка".repeat(50),
+            kind: GitErrorKind::Other,
+            occurred_at: SystemTime::now(),
+            retrying: false,
+        };
+
+        let summary = error.summary(200);
+        assert_eq!(summary.chars().count(), 203);
+        assert!(summary.ends_with("..."));
+
+        let short = RepoError {
+            message: "short".to_string(),
+            ..error
+        };
+        assert_eq!(short.summary(200), "short");
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Runs in the repository; which tool opens is decided by git's `diff.tool`.
+pub const DEFAULT_DIFFTOOL_COMMAND: &str = "git difftool --dir-diff";
+
+/// A user-configured external command, ready to spawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl LaunchCommand {
+    /// Command line as shown in logs.
+    pub fn display(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|part| {
+                if part.contains(' ') {
+                    format!("\"{}\"", part)
+                } else {
+                    part.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Starts the command in `cwd` without waiting for it. No console window
+    /// is opened on Windows and stdin is closed, so a prompting tool exits
+    /// instead of hanging.
+    pub fn spawn_detached(&self, cwd: &Path) -> std::io::Result<()> {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args)
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(0x08000000);
+        }
+
+        let mut child = cmd.spawn()?;
+        // Забираем код завершения, чтобы не оставлять зомби-процессов
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+/// Splits `template` into words (double quotes keep spaces together) and
+/// replaces `{path}` with `path`. `None` for an empty template.
+pub fn build_command(template: &str, path: &Path) -> Option<LaunchCommand> {
+    let path = path.to_string_lossy();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_word = false;
+
+    for c in template.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_word = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_word {
+                    words.push(std::mem::take(&mut current));
+                    has_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_word = true;
+            }
+        }
+    }
+    if has_word {
+        words.push(current);
+    }
+
+    let mut words = words.into_iter().map(|word| word.replace("{path}", &path));
+    Some(LaunchCommand {
+        program: words.next().filter(|program| !program.is_empty())?,
+        args: words.collect(),
+    })
+}
+
+fn is_git_difftool(command: &LaunchCommand) -> bool {
+    command.program == "git" && command.args.first().is_some_and(|arg| arg == "difftool")
+}
+
+/// Difftool command for `repo_path`. `git difftool` gets `--no-prompt` so
+/// it never waits on stdin; `against_upstream` compares HEAD to
+/// `@{upstream}`, falling back to `git difftool` for tools that cannot
+/// compare revisions themselves.
+pub fn difftool_command(
+    template: &str,
+    repo_path: &Path,
+    against_upstream: bool,
+) -> Option<LaunchCommand> {
+    let mut command = build_command(template, repo_path)?;
+    if against_upstream && !is_git_difftool(&command) {
+        command = build_command(DEFAULT_DIFFTOOL_COMMAND, repo_path)?;
+    }
+
+    if is_git_difftool(&command)
+        && !command
+            .args
+            .iter()
+            .any(|arg| arg == "--no-prompt" || arg == "-y")
+    {
+        command.args.insert(1, "--no-prompt".to_string());
+    }
+    if against_upstream {
+        command.args.push("HEAD".to_string());
+        command.args.push("@{upstream}".to_string());
+    }
+
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &LaunchCommand) -> Vec<&str> {
+        command.args.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn placeholder_is_substituted_inside_quoted_words() {
+        let command = build_command(
+            r#""C:\Program Files\Meld\meld.exe" --label "{path} (work)" {path}"#,
+            Path::new("/r/my repo"),
+        )
+        .unwrap();
+
+        assert_eq!(command.program, r"C:\Program Files\Meld\meld.exe");
+        assert_eq!(
+            args(&command),
+            vec!["--label", "/r/my repo (work)", "/r/my repo"]
+        );
+    }
+
+    #[test]
+    fn empty_template_builds_nothing() {
+        assert_eq!(build_command("   ", Path::new("/r/api")), None);
+        assert_eq!(build_command(r#""" --flag"#, Path::new("/r/api")), None);
+    }
+
+    #[test]
+    fn git_difftool_never_prompts() {
+        let command =
+            difftool_command(DEFAULT_DIFFTOOL_COMMAND, Path::new("/r/api"), false).unwrap();
+        assert_eq!(
+            args(&command),
+            vec!["difftool", "--no-prompt", "--dir-diff"]
+        );
+
+        let command = difftool_command("git difftool -y", Path::new("/r/api"), false).unwrap();
+        assert_eq!(args(&command), vec!["difftool", "-y"]);
+    }
+
+    #[test]
+    fn upstream_diff_compares_head_with_upstream() {
+        let command =
+            difftool_command("git difftool --tool=meld", Path::new("/r/api"), true).unwrap();
+        assert_eq!(
+            args(&command),
+            vec![
+                "difftool",
+                "--no-prompt",
+                "--tool=meld",
+                "HEAD",
+                "@{upstream}"
+            ]
+        );
+
+        let command = difftool_command("meld {path}", Path::new("/r/api"), true).unwrap();
+        assert_eq!(command.program, "git");
+        assert_eq!(
+            args(&command),
+            vec![
+                "difftool",
+                "--no-prompt",
+                "--dir-diff",
+                "HEAD",
+                "@{upstream}"
+            ]
+        );
+    }
+}
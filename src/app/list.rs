@@ -0,0 +1,213 @@
+use super::{by_staleness, RepoErrors, RepoFilter};
+use crate::config::SortMode;
+use crate::workspace::RepositoryState;
+use std::cmp::Ordering;
+
+/// Header columns of the list view that can be clicked to sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    Name,
+    Behind,
+    Ahead,
+}
+
+impl ListColumn {
+    pub fn label_key(self) -> &'static str {
+        match self {
+            ListColumn::Name => "list_column_name",
+            ListColumn::Behind => "list_column_behind",
+            ListColumn::Ahead => "list_column_ahead",
+        }
+    }
+
+    /// Names read A→Z first; counters show the biggest first.
+    fn starts_descending(self) -> bool {
+        !matches!(self, ListColumn::Name)
+    }
+}
+
+/// Column sort picked in the list header; overrides the sort mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListSort {
+    pub column: ListColumn,
+    pub descending: bool,
+}
+
+impl ListSort {
+    /// Header click: a new column starts in its natural direction, a second
+    /// click reverses it and a third goes back to the sort mode.
+    pub fn clicked(current: Option<ListSort>, column: ListColumn) -> Option<ListSort> {
+        match current {
+            Some(sort) if sort.column == column => (sort.descending == column.starts_descending())
+                .then_some(ListSort {
+                    column,
+                    descending: !sort.descending,
+                }),
+            _ => Some(ListSort {
+                column,
+                descending: column.starts_descending(),
+            }),
+        }
+    }
+}
+
+fn by_name(a: &RepositoryState, b: &RepositoryState) -> Ordering {
+    a.name
+        .to_lowercase()
+        .cmp(&b.name.to_lowercase())
+        .then_with(|| a.path.cmp(&b.path))
+}
+
+/// Indices of the repositories shown in the list view, in display order.
+/// Filtering matches the tree; ties fall back to the name.
+pub fn list_order(
+    repositories: &[RepositoryState],
+    filter: RepoFilter,
+    errors: &RepoErrors,
+    sort_mode: SortMode,
+    list_sort: Option<ListSort>,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = repositories
+        .iter()
+        .enumerate()
+        .filter(|(_, repo)| filter.matches(repo, errors))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&repositories[a], &repositories[b]);
+        match list_sort {
+            Some(sort) => {
+                let ordering = match sort.column {
+                    ListColumn::Name => by_name(a, b),
+                    ListColumn::Behind => a.git_info.behind.cmp(&b.git_info.behind),
+                    ListColumn::Ahead => a.git_info.ahead.cmp(&b.git_info.ahead),
+                };
+                let ordering = if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+                ordering.then_with(|| by_name(a, b))
+            }
+            None => match sort_mode {
+                SortMode::Path => a.path.cmp(&b.path),
+                SortMode::Name => by_name(a, b),
+                SortMode::LastCommit => b
+                    .git_info
+                    .last_commit_time
+                    .cmp(&a.git_info.last_commit_time)
+                    .then_with(|| by_name(a, b)),
+                SortMode::Staleness => {
+                    by_staleness(a.last_fetch, b.last_fetch).then_with(|| by_name(a, b))
+                }
+            },
+        }
+    });
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::AttentionFilter;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn repo(path: &str, ahead: usize, behind: usize, time: i64) -> RepositoryState {
+        let mut repo = RepositoryState::new(PathBuf::from(path));
+        repo.git_info.ahead = ahead;
+        repo.git_info.behind = behind;
+        repo.git_info.last_commit_time = Some(time);
+        repo
+    }
+
+    fn sample() -> Vec<RepositoryState> {
+        vec![
+            repo("/w/zeta/web", 0, 5, 300),
+            repo("/w/alpha/Api", 3, 0, 100),
+            repo("/w/mid/cli", 1, 2, 200),
+        ]
+    }
+
+    fn sorted(sort_mode: SortMode, list_sort: Option<ListSort>) -> Vec<usize> {
+        list_order(
+            &sample(),
+            RepoFilter::new("", AttentionFilter::All, &HashSet::new()),
+            &RepoErrors::default(),
+            sort_mode,
+            list_sort,
+        )
+    }
+
+    #[test]
+    fn sort_mode_orders_the_list_without_a_column() {
+        assert_eq!(sorted(SortMode::Path, None), vec![1, 2, 0]);
+        assert_eq!(sorted(SortMode::Name, None), vec![1, 2, 0]);
+        assert_eq!(sorted(SortMode::LastCommit, None), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn name_column_sorts_case_insensitively_both_ways() {
+        let ascending = ListSort::clicked(None, ListColumn::Name);
+        let descending = ListSort::clicked(ascending, ListColumn::Name);
+
+        assert_eq!(sorted(SortMode::LastCommit, ascending), vec![1, 2, 0]);
+        assert_eq!(sorted(SortMode::LastCommit, descending), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn behind_and_ahead_columns_put_the_biggest_first() {
+        let behind = ListSort::clicked(None, ListColumn::Behind);
+        let ahead = ListSort::clicked(behind, ListColumn::Ahead);
+
+        assert_eq!(sorted(SortMode::Path, behind), vec![0, 2, 1]);
+        assert_eq!(sorted(SortMode::Path, ahead), vec![1, 2, 0]);
+        assert_eq!(
+            sorted(SortMode::Path, ListSort::clicked(ahead, ListColumn::Ahead)),
+            vec![0, 2, 1]
+        );
+    }
+
+    #[test]
+    fn third_click_returns_to_the_sort_mode() {
+        let first = ListSort::clicked(None, ListColumn::Behind);
+        let second = ListSort::clicked(first, ListColumn::Behind);
+
+        assert_eq!(ListSort::clicked(second, ListColumn::Behind), None);
+    }
+
+    #[test]
+    fn staleness_puts_never_fetched_first() {
+        let now = std::time::SystemTime::now();
+        let mut repos = sample();
+        repos[0].last_fetch = Some(now);
+        repos[1].last_fetch = Some(now - std::time::Duration::from_secs(3600));
+
+        assert_eq!(
+            list_order(
+                &repos,
+                RepoFilter::new("", AttentionFilter::All, &HashSet::new()),
+                &RepoErrors::default(),
+                SortMode::Staleness,
+                None
+            ),
+            vec![2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn list_honours_the_search() {
+        assert_eq!(
+            list_order(
+                &sample(),
+                RepoFilter::new("api", AttentionFilter::All, &HashSet::new()),
+                &RepoErrors::default(),
+                SortMode::Path,
+                None
+            ),
+            vec![1]
+        );
+    }
+}
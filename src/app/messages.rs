@@ -1,11 +1,24 @@
 use crate::git::GitMessage;
+use crate::workspace::ProjectType;
 use std::path::PathBuf;
 
 #[derive(Debug)]
 pub enum AppMessage {
     Git(GitMessage),
-    ReposFound { repos: Vec<PathBuf> },
-    SearchComplete { total_found: usize },
+    ReposFound {
+        /// Folder the scan started from.
+        root: PathBuf,
+        repos: Vec<PathBuf>,
+        /// Measured scan cost per top-level folder, see `measured_ms_per_dir`.
+        ms_per_dir: Option<f32>,
+    },
+    SearchComplete {
+        total_found: usize,
+    },
+    ProjectTypeDetected {
+        repo_path: PathBuf,
+        project_type: ProjectType,
+    },
 }
 
 impl From<GitMessage> for AppMessage {
@@ -1,46 +1,452 @@
+pub mod attention;
+pub mod branch_prune;
+pub mod branch_switch;
+pub mod bulk;
+pub mod confirm;
+pub mod conflicts;
+pub mod dashboard;
+pub mod errors;
+pub mod launcher;
+pub mod list;
 pub mod messages;
+pub mod power;
+pub mod quick_filter;
+pub mod recent;
+pub mod runtime;
+pub mod scan_preview;
+pub mod scheduler;
 pub mod search;
+pub mod shutdown;
+pub mod staleness;
+pub mod tray;
 pub mod tree;
 
 use crossbeam_channel::{Receiver, Sender};
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 
-use crate::config::{Config, ConfigManager};
-use crate::git::refresh_repo_status_async;
+use crate::config::{
+    export_settings, home_dir, preview_import, read_settings_export, Config, ConfigManager,
+    ConfigSaver, ForcePushRecord, ImportPreview, StatusCache,
+};
+use crate::git::{
+    build_digest_async, cancel_queued, check_conflicts_async, check_dirty_noise_async,
+    check_git_executable, compare_snapshot_async, configure_credential_helper, contributor_since,
+    create_branch_from_default_async, describe_env_overrides, find_merged_branches_async,
+    git_am_in_progress, git_am_resolve, git_apply, git_apply_3way, git_bisect_mark_async,
+    git_bisect_reset, git_branch_overview, git_cat_file_async, git_default_branch,
+    git_delete_branch, git_fetch_fast_async_with_retry, git_finish_merge_async, git_list_remotes,
+    git_local_only_commits, git_log_async, git_log_graph_async, git_merged_branches,
+    git_pull_fast_async, git_push_fast_async, git_push_force_with_lease_async,
+    git_push_mirror_async, git_rename_remote, git_set_note_async, git_set_upstream,
+    git_shortlog_async, git_show_async, git_show_ignored, git_stash_action, git_stash_list,
+    git_stash_show_async, git_unset_upstream, global_credential_helper, is_queued,
+    is_valid_remote_name, list_snapshots, load_all_branches_async, load_commit_activity_async,
+    mark_safe_directory, parse_remote_url, pool_status, prune_branches_async,
+    refresh_repo_status_async, save_snapshot, set_branch_list_limit, set_fetch_depth,
+    set_git_executable, set_provider_hosts, set_repo_env_overrides, set_repo_identities,
+    switch_branch, switch_branch_async, take_snapshot_async, watch_bisect_async, ApplyResult,
+    BisectState, BisectVerdict, BranchOverview, CherryEntry, CommitActivity, CommitSummary,
+    ContributorEntry, DigestRequest, DirtyNoise, GitDirCheck, GitInfo, GitMessage, GraphLogLine,
+    IgnoredFile, NoteEntry, RemoteEntry, RepoDelta, SnapshotChange, SnapshotRequest, StashAction,
+    StashEntry, SystemGit, WorkspaceSnapshot, CONTRIBUTOR_RANGES,
+};
 use crate::localization::Localizer;
-use crate::logging::Logger;
-use crate::ui::IconManager;
-use crate::workspace::Workspace;
+use crate::logging::{start_audit_log, AuditEntry, AuditFile, AuditOperation, Logger};
+use crate::ui::{IconManager, Terminal, Tray, WorkspacePanel, WorkspacePanelAction};
+use crate::workspace::{
+    description_mtimes, description_sources, detect_project_type, duplicate_key,
+    ensure_unique_workspace_ids, find_duplicate_registrations, find_missing_prefixes,
+    read_description, requires_bulk_confirmation, rewrite_repo_paths, verify_repath,
+    workspace_index, CachedDescription, DuplicateRepo, MissingPrefix, RepositoryState, Workspace,
+    WorkspaceId, MISSING_PREFIX_THRESHOLD,
+};
 
+pub use attention::*;
+pub use branch_prune::*;
+pub use branch_switch::*;
+pub use bulk::*;
+pub use confirm::*;
+pub use conflicts::*;
+pub use dashboard::*;
+pub use errors::*;
+pub use launcher::*;
+pub use list::*;
 pub use messages::*;
+pub use power::*;
+pub use quick_filter::*;
+pub use recent::*;
+pub use runtime::*;
+pub use scan_preview::*;
+pub use scheduler::*;
 pub use search::*;
+pub use shutdown::*;
+pub use staleness::*;
+pub use tray::*;
 pub use tree::*;
 
+/// Asks where to write a workspace archive, or which one to restore when
+/// `workspace_idx` is `None`.
+pub struct ArchiveDialog {
+    pub workspace_idx: Option<usize>,
+    pub path: String,
+}
+
+/// Offered after a large scan: keep everything in the active workspace or
+/// make one workspace per top-level folder.
+pub struct ScanSplitDialog {
+    pub repos: Vec<PathBuf>,
+    /// Folder name and its repositories, sorted by name.
+    pub groups: Vec<(String, Vec<PathBuf>)>,
+}
+
+impl ScanSplitDialog {
+    /// `clients: 24, oss: 41` for the dialog button.
+    pub fn summary(&self) -> String {
+        self.groups
+            .iter()
+            .map(|(name, repos)| format!("{}: {}", name, repos.len()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Asks why a branch is being force-pushed before the push starts.
+pub struct ForcePushDialog {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub branch: String,
+    pub reason: String,
+}
+
+pub struct RepathDialog {
+    pub old_prefix: PathBuf,
+    pub new_prefix: String,
+}
+
+pub struct StashWindow {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub entries: Vec<StashEntry>,
+    pub selected: Option<usize>,
+    /// `git stash show -p` output per stash index, loaded on first hover;
+    /// the error is shown in place of the diff.
+    pub diffs: HashMap<usize, Result<String, String>>,
+    pub loading: HashSet<usize>,
+}
+
+pub struct PatchDialog {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub patch_path: String,
+    pub create_commits: bool,
+    /// `git am` stopped on a conflicting patch.
+    pub am_stopped: bool,
+}
+
+/// Number of commit diffs kept in `commit_diff_cache`.
+const COMMIT_DIFF_CACHE_SIZE: usize = 5;
+
+/// How often the description files of one repository are looked at again.
+const DESCRIPTION_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+pub struct CommitLogView {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub commits: Option<Result<Vec<CommitSummary>, String>>,
+    /// `git log --graph` rows, loaded the first time the graph is shown.
+    pub graph: Option<Result<Vec<GraphLogLine>, String>>,
+    pub show_graph: bool,
+    pub selected: Option<String>,
+    /// `git show` failures by commit hash; selecting the commit retries.
+    pub diff_errors: HashMap<String, String>,
+    pub notes: Vec<NoteEntry>,
+    /// Commit hash and text of the note open for editing.
+    pub editing_note: Option<(String, String)>,
+    /// Path typed into "View file at commit…", kept between commits.
+    pub cat_file_path: String,
+}
+
+impl CommitLogView {
+    pub fn note_for(&self, commit_hash: &str) -> Option<&NoteEntry> {
+        self.notes
+            .iter()
+            .find(|note| note.object_hash == commit_hash)
+    }
+}
+
+/// One file as of a commit, opened from the commit log.
+pub struct FileAtCommitView {
+    pub repo_path: PathBuf,
+    pub hash: String,
+    pub file_path: String,
+    /// `None` while `git cat-file` runs; saved byte for byte.
+    pub content: Option<Result<Vec<u8>, String>>,
+    /// `content` as shown and copied; invalid UTF-8 is replaced.
+    pub text: String,
+}
+
+impl FileAtCommitView {
+    pub fn file_name(&self) -> &str {
+        self.file_path
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(&self.file_path)
+    }
+
+    pub fn short_hash(&self) -> &str {
+        self.hash.get(..7).unwrap_or(&self.hash)
+    }
+}
+
+/// Result of `git remote prune --dry-run`, shown before pruning.
+pub struct StaleRefsView {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub refs: Vec<String>,
+}
+
+/// `git cherry -v @{u}` entries: what the next push would include.
+pub struct CherryView {
+    pub repo_name: String,
+    pub entries: Vec<CherryEntry>,
+}
+
+/// "Branches overview" window: every local branch of one repository.
+pub struct BranchesView {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub default_branch: Option<String>,
+    pub branches: Vec<BranchOverview>,
+    /// Comma separated `Config::protected_branches` being edited.
+    pub protected_input: String,
+}
+
+/// Remotes of one repository; a name being edited in place is `editing`.
+pub struct RemotesView {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub remotes: Vec<RemoteEntry>,
+    /// Index into `remotes` and the new name typed so far.
+    pub editing: Option<(usize, String)>,
+}
+
+/// Service folders found inside a monorepo, offered as sub-entries.
+pub struct MonorepoServicesView {
+    /// Folder holding the shared `.git`.
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    /// Candidate service roots and whether they are ticked.
+    pub services: Vec<(PathBuf, bool)>,
+}
+
+/// Commit counts per author of one repository over `days`.
+pub struct ContributorsView {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub days: u32,
+}
+
+/// Files behind a "changes" indicator that is only line-ending or
+/// filename-case noise.
+pub struct DirtyNoiseView {
+    pub repo_name: String,
+    pub noise: DirtyNoise,
+}
+
+/// Ignored paths of one repository with the rule that matched each.
+pub struct IgnoredFilesView {
+    pub repo_name: String,
+    pub files: Vec<IgnoredFile>,
+}
+
+/// Environment overrides of one repository being edited.
+pub struct EnvOverridesView {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    /// `(name, value)` rows; rows without a name are dropped on save.
+    pub rows: Vec<(String, String)>,
+}
+
+/// Result of comparing the workspace with a stored snapshot; `changes` is
+/// `None` while the comparison runs.
+pub struct SnapshotDiffView {
+    pub taken_at: u64,
+    pub changes: Option<Vec<SnapshotChange>>,
+}
+
+/// Read-only "Operation history" window over the audit file.
+pub struct AuditHistoryView {
+    pub entries: Vec<AuditEntry>,
+    pub repo_filter: Option<PathBuf>,
+    pub operation_filter: Option<AuditOperation>,
+    pub export_path: String,
+}
+
+impl AuditHistoryView {
+    /// Entries matching the filters, newest first.
+    pub fn filtered(&self) -> Vec<&AuditEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|e| {
+                self.repo_filter.is_none() || self.repo_filter.as_ref() == Some(&e.repo_path)
+            })
+            .filter(|e| {
+                self.operation_filter.is_none() || self.operation_filter == Some(e.operation)
+            })
+            .collect()
+    }
+
+    /// Distinct repositories in the history, for the filter dropdown.
+    pub fn repo_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.entries.iter().map(|e| e.repo_path.clone()).collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+}
+
+pub fn audit_file() -> AuditFile {
+    AuditFile::new(ConfigManager::get_config_file_path().with_file_name("audit.jsonl"))
+}
+
 pub struct MyApp {
     pub config: Config,
+    pub config_saver: ConfigSaver,
     pub logger: Logger,
     pub icon_manager: IconManager,
     pub localizer: Localizer,
 
-    pub active_workspace_idx: usize,
-    pub editing_workspace: Option<usize>,
+    /// `None` until a workspace is picked; the first workspace is shown then.
+    pub active_workspace_id: Option<WorkspaceId>,
+    pub editing_workspace: Option<WorkspaceId>,
+    pub workspace_panel: WorkspacePanel,
     pub new_workspace_name: String,
 
     pub app_receiver: Option<Receiver<AppMessage>>,
     pub app_sender: Option<Sender<AppMessage>>,
 
     pub search_query: String,
+    pub attention_filter: AttentionFilter,
+    /// Header column sort of the list view; `None` follows the sort mode.
+    pub list_sort: Option<ListSort>,
     pub collapsed_paths: HashSet<String>,
     pub show_logs: bool,
+    /// Logs panel "Filter by repo"; `None` shows every entry.
+    pub log_repo_filter: Option<PathBuf>,
+    /// Embedded shell in the bottom panel; dropping it kills the shell.
+    pub terminal_pane: Option<Terminal>,
+    pub show_settings: bool,
+    pub settings_transfer_path: String,
+    pub import_preview: Option<ImportPreview>,
+    pub tag_window_repo: Option<PathBuf>,
+    pub commit_log: Option<CommitLogView>,
+    pub file_at_commit: Option<FileAtCommitView>,
+    pub stale_refs: Option<StaleRefsView>,
+    pub cherry: Option<CherryView>,
+    pub remotes: Option<RemotesView>,
+    pub monorepo_services: Option<MonorepoServicesView>,
+    pub env_overrides: Option<EnvOverridesView>,
+    pub ignored_files: Option<IgnoredFilesView>,
+    pub contributors: Option<ContributorsView>,
+    /// Shortlog per repository and `--since` value.
+    pub contributor_cache: HashMap<(PathBuf, String), Vec<ContributorEntry>>,
+    /// Text of the git executable setting, `config.git_executable` once non-empty.
+    pub git_executable_input: String,
+    pub branches_view: Option<BranchesView>,
+    pub audit_history: Option<AuditHistoryView>,
+    pub patch_dialog: Option<PatchDialog>,
+    pub stash_window: Option<StashWindow>,
+    /// Uncapped branch lists, kept outside `GitInfo` so repo clones stay light.
+    pub all_branches: HashMap<PathBuf, Vec<String>>,
+    /// Repositories with a running `git bisect`, reported by their watcher.
+    pub bisect_states: HashMap<PathBuf, BisectState>,
+    pub bisect_watchers: HashSet<PathBuf>,
+    /// Repositories whose last pull stopped with merge conflicts.
+    pub conflict_panels: HashMap<PathBuf, ConflictPanel>,
+    /// `(branch, new pull request URL)` after a branch was pushed.
+    pub pr_links: HashMap<PathBuf, (String, String)>,
+    pub loading_branches: HashSet<PathBuf>,
+    pub folder_operation: Option<FolderOperation>,
+    /// Entries skipped by a bulk fetch or pull (submodules fetched by their
+    /// parent, entries sharing a `.git`), refreshed once the job that
+    /// covered them reports back.
+    pub refresh_after_job: HashMap<PathBuf, Vec<PathBuf>>,
+    pub commit_diff_cache: HashMap<String, String>,
+    pub commit_diff_order: VecDeque<String>,
+    pub status_cache: StatusCache,
+    pub digest: Option<Vec<RepoDelta>>,
+    pub digest_loading: bool,
+    /// Sparklines being loaded, and those that failed this session.
+    pub activity_loading: HashSet<PathBuf>,
+    pub activity_failed: HashSet<PathBuf>,
+    /// Result of the hover-triggered dirty-noise check; `None` while it runs.
+    /// Dropped when a new status arrives.
+    pub dirty_noise: HashMap<PathBuf, Option<DirtyNoise>>,
+    pub dirty_noise_view: Option<DirtyNoiseView>,
+    /// When `refresh_description` last compared a repository's mtimes.
+    description_checked_at: HashMap<PathBuf, std::time::Instant>,
+    pub snapshot_loading: bool,
+    pub snapshot_diff: Option<SnapshotDiffView>,
+    /// Stored snapshots of the named workspace, read once per workspace.
+    pub snapshot_list: Option<(String, Vec<WorkspaceSnapshot>)>,
+    /// Set by the first Fetch All of the session when `auto_show_digest` is on.
+    pub digest_after_fetch: bool,
+    pub power: PowerState,
+    /// `None` until the first frame, then `Some(None)` where there is no tray.
+    pub tray: Option<Option<Tray>>,
+    pub tray_status: Option<TrayStatus>,
+    /// The window was closed to the tray and is invisible.
+    pub hidden_to_tray: bool,
+    /// "Quit" was picked in the tray; the next close request exits.
+    pub quitting_from_tray: bool,
+    pub digest_auto_checked: bool,
+    pub locale_reload_timer: std::time::Instant,
+    pub last_reload_time: HashMap<String, std::time::SystemTime>,
+    /// Cached `credential.helper` lookup; `None` until the settings window asks.
+    pub global_credential_helper: Option<Option<String>>,
+    pub repath_dialog: Option<RepathDialog>,
+    pub force_push_dialog: Option<ForcePushDialog>,
+    /// Name, branch and reason of force pushes in flight, recorded in the
+    /// history once the push succeeds.
+    pub force_push_reasons: HashMap<PathBuf, (String, String, String)>,
+    pub scan_split_dialog: Option<ScanSplitDialog>,
+    /// Found repositories waiting for "Add selected".
+    pub scan_preview: Option<ScanPreview>,
+    /// Repositories registered in several workspaces, refreshed on every
+    /// config save.
+    pub repo_duplicates: Vec<DuplicateRepo>,
+    pub show_duplicates_report: bool,
+    /// Open dashboard window and the figures it shows.
+    pub dashboard: Option<GlobalStats>,
+    pub branch_switch_dialog: Option<BranchSwitchDialog>,
+    pub branch_switch_batch: Option<BranchSwitchBatch>,
+    pub prune_dialog: Option<PruneDialog>,
+    pub prune_batch: Option<PruneBatch>,
+    pub archive_dialog: Option<ArchiveDialog>,
+    pub missing_prefixes: Vec<MissingPrefix>,
+    pub logged_tree_truncations: HashSet<PathBuf>,
+    pub pending_confirmation: Option<ConfirmAction>,
+    /// The last removed workspace, offered back by the Undo toast.
+    pub removed_workspace: Option<RemovedWorkspace>,
+    pub shutdown: AppShutdownState,
+    /// What was typed for `ConfirmAction::required_phrase`.
+    pub confirmation_input: String,
+    pub show_workspace_switcher: bool,
+    /// The virtual "Recent" workspace is shown instead of the active one;
+    /// `active_workspace_id` keeps pointing at a real workspace.
+    pub show_recent: bool,
+    pub workspace_switcher_selection: usize,
     pub search_status: Option<String>,
     pub search_status_timer: Option<std::time::Instant>,
 
     pub is_searching: bool,
-    pub is_loading_on_startup: bool,
-    pub startup_loaded_repos: usize,
-    pub syncing_repos: HashSet<PathBuf>,
-    pub error_repos: HashSet<PathBuf>,
+    pub load_scheduler: LoadScheduler,
+    /// Spinners and error flags of every repository, in any workspace.
+    pub runtime: RepoRuntimeState,
+    /// Branch picked in a dropdown that waits for "Switch".
+    pub pending_branch_switch: Option<(PathBuf, String)>,
     pub pending_git_loads: usize,
     pub first_startup: bool,
 }
@@ -49,28 +455,112 @@ impl Default for MyApp {
     fn default() -> Self {
         Self {
             config: Config::default(),
+            config_saver: ConfigSaver::default(),
             logger: Logger::default(),
             icon_manager: IconManager::new(),
             localizer: Localizer::new("en"),
 
-            active_workspace_idx: 0,
+            active_workspace_id: None,
             editing_workspace: None,
+            workspace_panel: WorkspacePanel::default(),
             new_workspace_name: String::new(),
 
             app_receiver: None,
             app_sender: None,
 
             search_query: String::new(),
+            attention_filter: AttentionFilter::All,
+            list_sort: None,
             collapsed_paths: HashSet::new(),
             show_logs: false,
+            log_repo_filter: None,
+            terminal_pane: None,
+            show_settings: false,
+            settings_transfer_path: home_dir()
+                .unwrap_or_default()
+                .join("repo_manager_settings.json")
+                .to_string_lossy()
+                .to_string(),
+            import_preview: None,
+            tag_window_repo: None,
+            commit_log: None,
+            file_at_commit: None,
+            stale_refs: None,
+            cherry: None,
+            remotes: None,
+            monorepo_services: None,
+            env_overrides: None,
+            ignored_files: None,
+            contributors: None,
+            contributor_cache: HashMap::new(),
+            git_executable_input: String::new(),
+            branches_view: None,
+            audit_history: None,
+            patch_dialog: None,
+            stash_window: None,
+            all_branches: HashMap::new(),
+            bisect_states: HashMap::new(),
+            bisect_watchers: HashSet::new(),
+            conflict_panels: HashMap::new(),
+            pr_links: HashMap::new(),
+            loading_branches: HashSet::new(),
+            folder_operation: None,
+            refresh_after_job: HashMap::new(),
+            commit_diff_cache: HashMap::new(),
+            commit_diff_order: VecDeque::new(),
+            status_cache: StatusCache::default(),
+            digest: None,
+            digest_loading: false,
+            activity_loading: HashSet::new(),
+            activity_failed: HashSet::new(),
+            dirty_noise: HashMap::new(),
+            description_checked_at: HashMap::new(),
+            dirty_noise_view: None,
+            snapshot_loading: false,
+            snapshot_diff: None,
+            snapshot_list: None,
+            digest_after_fetch: false,
+            tray: None,
+            tray_status: None,
+            hidden_to_tray: false,
+            quitting_from_tray: false,
+            power: PowerState::new(
+                std::time::Duration::from_secs(15 * 60),
+                std::time::Instant::now(),
+            ),
+            digest_auto_checked: false,
+            locale_reload_timer: std::time::Instant::now(),
+            last_reload_time: HashMap::new(),
+            global_credential_helper: None,
+            repath_dialog: None,
+            force_push_dialog: None,
+            force_push_reasons: HashMap::new(),
+            scan_split_dialog: None,
+            scan_preview: None,
+            repo_duplicates: Vec::new(),
+            show_duplicates_report: false,
+            dashboard: None,
+            branch_switch_dialog: None,
+            branch_switch_batch: None,
+            prune_dialog: None,
+            prune_batch: None,
+            archive_dialog: None,
+            missing_prefixes: Vec::new(),
+            logged_tree_truncations: HashSet::new(),
+            pending_confirmation: None,
+            removed_workspace: None,
+            shutdown: AppShutdownState::Running,
+            confirmation_input: String::new(),
+            show_workspace_switcher: false,
+            show_recent: false,
+            workspace_switcher_selection: 0,
             search_status: None,
             search_status_timer: None,
 
             is_searching: false,
-            is_loading_on_startup: false,
-            startup_loaded_repos: 0,
-            syncing_repos: HashSet::new(),
-            error_repos: HashSet::new(),
+            load_scheduler: LoadScheduler::default(),
+            runtime: RepoRuntimeState::default(),
+            pending_branch_switch: None,
             pending_git_loads: 0,
             first_startup: true,
         }
@@ -83,12 +573,27 @@ impl MyApp {
         let mut app = Self {
             localizer: Localizer::new(&config.language),
             config,
+            status_cache: StatusCache::load(),
             ..Default::default()
         };
+        set_branch_list_limit(app.config.branch_list_limit);
+        set_provider_hosts(app.config.provider_hosts.clone());
+        set_fetch_depth(app.config.default_fetch_depth);
+        app.logger.set_capacity(app.config.log_capacity);
+        start_audit_log(audit_file());
+        app.git_executable_input = app
+            .config
+            .git_executable
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        app.apply_git_command_settings();
+        app.check_git_environment();
 
+        ensure_unique_workspace_ids(&mut app.config.workspaces);
         if let Some(last_index) = app.config.last_active_workspace_index {
             if last_index < app.config.workspaces.len() {
-                app.active_workspace_idx = last_index;
+                app.set_active_workspace(last_index);
             }
         }
 
@@ -100,19 +605,183 @@ impl MyApp {
                     .unwrap_or_default()
                     .to_string_lossy()
                     .to_string();
+                repo.detect_symlink();
+                repo.validate_git_dir();
+                repo.last_fetch = app.status_cache.last_fetch.get(&repo.path).copied();
+                repo.description = app
+                    .status_cache
+                    .descriptions
+                    .get(&repo.path)
+                    .and_then(|cached| cached.description.clone());
             }
         }
 
+        app.report_invalid_repositories();
+        app.refresh_repo_duplicates();
+        app.refresh_missing_prefixes();
         app.first_startup = true;
         app
     }
 
-    pub fn save_config(&self) {
-        if let Err(e) = ConfigManager::save(&self.config) {
-            eprintln!("Failed to save config: {}", e);
+    /// Hands the git executable, every repository's environment overrides
+    /// and the workspace identities to the command layer. A repository
+    /// listed in several workspaces takes the first identity set.
+    pub fn apply_git_command_settings(&self) {
+        set_git_executable(self.config.git_executable.clone());
+        let mut identities = HashMap::new();
+        for workspace in &self.config.workspaces {
+            let identity = workspace.git_env();
+            for repo in &workspace.repositories {
+                set_repo_env_overrides(&repo.path, repo.env_overrides.clone());
+                if let Some(identity) = &identity {
+                    identities
+                        .entry(repo.path.clone())
+                        .or_insert_with(|| identity.clone());
+                }
+            }
+        }
+        set_repo_identities(identities);
+    }
+
+    /// Environment check: logs the version of the git that will be used.
+    pub fn check_git_environment(&mut self) {
+        let executable = self
+            .config
+            .git_executable
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "git".to_string());
+        match check_git_executable() {
+            Ok(version) => self.logger.info(
+                self.localizer
+                    .tf("git_executable_ok", &[&version, &executable]),
+            ),
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("git_executable_invalid", &[&executable, &e.to_string()]),
+            ),
         }
     }
 
+    /// Applies the typed git executable; an empty field goes back to PATH.
+    pub fn set_git_executable_input(&mut self) {
+        let input = self.git_executable_input.trim();
+        let git_executable = (!input.is_empty()).then(|| PathBuf::from(input));
+        if git_executable == self.config.git_executable {
+            return;
+        }
+        self.config.git_executable = git_executable;
+        set_git_executable(self.config.git_executable.clone());
+        self.mark_config_dirty();
+    }
+
+    /// Window title; marks portable mode so it is clear which config is in use.
+    pub fn window_title(&self) -> String {
+        let title = self.localizer.t("app_title");
+        if ConfigManager::is_portable() {
+            self.localizer.tf("title_portable", &[&title])
+        } else {
+            title
+        }
+    }
+
+    pub fn save_config(&mut self) {
+        self.prune_runtime_state();
+        self.config_saver.mark_dirty();
+        self.config_saver.flush(&self.config);
+    }
+
+    /// Defers the write to `ConfigSaver`, for changes that happen every frame.
+    pub fn mark_config_dirty(&mut self) {
+        self.prune_runtime_state();
+        self.config_saver.mark_dirty();
+    }
+
+    /// Every config change may remove or relocate repositories; their
+    /// spinners and errors go with them.
+    fn prune_runtime_state(&mut self) {
+        let known: HashSet<PathBuf> = self.all_repo_paths().into_iter().collect();
+        self.runtime.retain_known(&known);
+    }
+
+    /// Repositories listed in several workspaces, minus the ones kept
+    /// everywhere on purpose. Runs when repositories are loaded, added or
+    /// removed, not on every config change.
+    pub fn refresh_repo_duplicates(&mut self) {
+        let dismissed = &self.config.dismissed_duplicates;
+        self.repo_duplicates =
+            find_duplicate_registrations(&self.config.workspaces, RepositoryState::duplicate_key)
+                .into_iter()
+                .filter(|duplicate| !dismissed.contains(&duplicate.key))
+                .collect();
+    }
+
+    /// The duplicate the row `path` of `workspace_id` belongs to.
+    pub fn duplicate_of(&self, workspace_id: WorkspaceId, path: &Path) -> Option<&DuplicateRepo> {
+        self.repo_duplicates
+            .iter()
+            .find(|duplicate| duplicate.is_at(workspace_id, path))
+    }
+
+    /// "Keep only here": removes the repository from every other workspace.
+    pub fn keep_duplicate_only_in(&mut self, key: &Path, workspace_id: WorkspaceId) {
+        let Some(duplicate) = self
+            .repo_duplicates
+            .iter()
+            .find(|duplicate| duplicate.key == key)
+            .cloned()
+        else {
+            return;
+        };
+
+        let mut removed = 0;
+        for location in duplicate
+            .locations
+            .iter()
+            .filter(|location| location.workspace_id != workspace_id)
+        {
+            if let Some(workspace) = self
+                .config
+                .workspaces
+                .iter_mut()
+                .find(|workspace| workspace.id == location.workspace_id)
+            {
+                let before = workspace.repositories.len();
+                workspace
+                    .repositories
+                    .retain(|repo| repo.path != location.path);
+                removed += before - workspace.repositories.len();
+            }
+        }
+
+        let kept_in = duplicate
+            .locations
+            .iter()
+            .find(|location| location.workspace_id == workspace_id)
+            .map(|location| location.workspace_name.clone())
+            .unwrap_or_default();
+        self.logger.info(self.localizer.tf(
+            "duplicate_kept_only_in",
+            &[&key.display().to_string(), &kept_in, &removed.to_string()],
+        ));
+        self.refresh_repo_duplicates();
+        self.save_config();
+    }
+
+    /// "Keep everywhere": stops reporting the repository as a duplicate.
+    pub fn keep_duplicate_everywhere(&mut self, key: PathBuf) {
+        self.config.dismissed_duplicates.insert(key);
+        self.refresh_repo_duplicates();
+        self.save_config();
+    }
+
+    pub fn is_known_repo(&self, path: &Path) -> bool {
+        self.config
+            .workspaces
+            .iter()
+            .any(|workspace| workspace.repositories.iter().any(|repo| repo.path == path))
+    }
+
     pub fn switch_to_workspace(&mut self, workspace_idx: usize) {
         if workspace_idx >= self.config.workspaces.len() {
             self.logger.info(format!(
@@ -128,7 +797,8 @@ impl MyApp {
             workspace_name, workspace_idx
         ));
 
-        self.active_workspace_idx = workspace_idx;
+        self.set_active_workspace(workspace_idx);
+        self.show_recent = false;
         self.config.last_active_workspace_index = Some(workspace_idx);
 
         self.load_workspace(workspace_idx);
@@ -136,91 +806,3080 @@ impl MyApp {
         self.save_config();
     }
 
-    pub fn setup_git_communication(&mut self) {
-        let (tx, rx) = crossbeam_channel::unbounded::<AppMessage>();
-        self.app_sender = Some(tx);
-        self.app_receiver = Some(rx);
+    /// Scan results: large scans over several folders ask first whether to
+    /// split them into workspaces.
+    pub fn handle_found_repositories(&mut self, root: PathBuf, repos: Vec<PathBuf>) {
+        let groups = partition_by_top_folder(&root, &repos);
+        if should_offer_split(&groups, self.config.workspace_split_threshold) {
+            self.scan_split_dialog = Some(ScanSplitDialog {
+                repos,
+                groups: groups.into_iter().collect(),
+            });
+            return;
+        }
+        self.review_found_repositories(repos);
     }
 
-    pub fn refresh_all_repos(&self) {
+    pub fn compute_global_stats(&self) -> GlobalStats {
+        GlobalStats::compute(
+            &self.config.workspaces,
+            |path| self.conflict_panels.contains_key(path),
+            |path| self.runtime.errors().contains(path),
+            |path| self.status_cache.activity.get(path).map(|a| a.total()),
+        )
+    }
+
+    pub fn open_dashboard(&mut self) {
+        self.dashboard = Some(self.compute_global_stats());
+    }
+
+    /// Recomputes the figures of an open dashboard, e.g. after a bulk
+    /// operation.
+    pub fn refresh_dashboard(&mut self) {
+        if self.dashboard.is_some() {
+            self.dashboard = Some(self.compute_global_stats());
+        }
+    }
+
+    /// One plan for every workspace, so a repository registered in several
+    /// of them is fetched once.
+    fn fetch_all_workspaces(&mut self) {
+        let repo_count = self
+            .config
+            .workspaces
+            .iter()
+            .map(|workspace| workspace.repository_count())
+            .sum();
+        let plan = self
+            .config
+            .workspaces
+            .iter()
+            .map(|workspace| self.workspace_fetch_plan(&workspace.repositories))
+            .fold(FetchPlan::default(), FetchPlan::merge);
+        self.run_fetch_plan(plan, repo_count);
+    }
+
+    /// Lists large scan results for review; small ones are added directly.
+    pub fn review_found_repositories(&mut self, repos: Vec<PathBuf>) {
+        if !self.config.confirm_scan_results || repos.len() <= SCAN_PREVIEW_MIN_REPOS {
+            self.add_found_repositories(repos);
+            return;
+        }
+        let existing: HashSet<PathBuf> = self
+            .get_active_workspace()
+            .map(|workspace| {
+                workspace
+                    .repositories
+                    .iter()
+                    .map(|repo| repo.path.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.scan_preview = Some(ScanPreview::new(repos, &existing));
+    }
+
+    /// Adds scanned repositories to the active workspace.
+    pub fn add_found_repositories(&mut self, repos: Vec<PathBuf>) {
+        let mut repos_to_refresh = Vec::new();
+        if let Some(workspace) = self.get_active_workspace_mut() {
+            for repo_path in repos {
+                if workspace.add_repository(repo_path.clone()) {
+                    repos_to_refresh.push(repo_path);
+                }
+            }
+        }
+
+        let added_count = repos_to_refresh.len();
+        self.apply_git_command_settings();
         if let Some(tx) = &self.app_sender {
-            if let Some(workspace) = self.config.workspaces.get(self.active_workspace_idx) {
-                for repo in &workspace.repositories {
-                    refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+            for repo_path in repos_to_refresh {
+                refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+            }
+        }
+
+        if added_count > 0 {
+            self.refresh_repo_duplicates();
+            self.save_config();
+            self.logger.info(
+                self.localizer
+                    .tf("added_repos_log", &[&added_count.to_string()]),
+            );
+            self.search_status = Some(
+                self.localizer
+                    .tf("added_repos", &[&added_count.to_string()]),
+            );
+        } else {
+            self.logger.warning(self.localizer.t("no_new_repos_log"));
+            self.search_status = Some(self.localizer.t("no_repos_found"));
+        }
+        self.search_status_timer = Some(std::time::Instant::now());
+    }
+
+    /// Puts each folder's repositories into the workspace of the same name,
+    /// creating missing ones, and opens the largest.
+    pub fn split_into_workspaces(&mut self, groups: Vec<(String, Vec<PathBuf>)>) {
+        let mut largest: Option<(usize, usize)> = None;
+        let mut added_count = 0;
+        let mut repos_to_refresh = Vec::new();
+
+        for (name, repos) in groups {
+            let idx = match self.config.workspaces.iter().position(|w| w.name == name) {
+                Some(idx) => idx,
+                None => {
+                    self.config.workspaces.push(Workspace::new(name));
+                    self.config.workspaces.len() - 1
+                }
+            };
+
+            let workspace = &mut self.config.workspaces[idx];
+            for repo_path in repos {
+                if workspace.add_repository(repo_path.clone()) {
+                    added_count += 1;
+                    // Незагруженные области прочитают статус при открытии
+                    if workspace.is_loaded {
+                        repos_to_refresh.push(repo_path);
+                    }
                 }
             }
+
+            let size = workspace.repositories.len();
+            if !matches!(largest, Some((_, max)) if max >= size) {
+                largest = Some((idx, size));
+            }
+        }
+
+        self.apply_git_command_settings();
+        self.refresh_repo_duplicates();
+        if let Some(tx) = &self.app_sender {
+            for repo_path in repos_to_refresh {
+                refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+            }
+        }
+
+        self.logger.info(
+            self.localizer
+                .tf("added_repos_log", &[&added_count.to_string()]),
+        );
+        self.search_status = Some(
+            self.localizer
+                .tf("added_repos", &[&added_count.to_string()]),
+        );
+        self.search_status_timer = Some(std::time::Instant::now());
+
+        match largest {
+            Some((idx, _)) => self.switch_to_workspace(idx),
+            None => self.save_config(),
         }
     }
 
-    pub fn load_workspace(&mut self, workspace_idx: usize) {
-        if workspace_idx >= self.config.workspaces.len() {
-            self.logger.info(format!(
-                "Load workspace {} failed: index out of bounds",
-                workspace_idx
-            ));
+    /// Applies what the user did in the sidebar workspace list.
+    pub fn apply_workspace_action(&mut self, action: WorkspacePanelAction) {
+        match action {
+            WorkspacePanelAction::Switch(idx) => {
+                self.logger
+                    .info(self.localizer.tf("switch_workspace", &[&idx.to_string()]));
+                self.switch_to_workspace(idx);
+            }
+            WorkspacePanelAction::Rename(id, new_name) => {
+                if let Some(idx) = workspace_index(&self.config.workspaces, id) {
+                    self.config.workspaces[idx].name = new_name;
+                    self.save_config();
+                }
+                self.editing_workspace = None;
+            }
+            WorkspacePanelAction::Remove(idx) => self.request_workspace_removal(idx),
+            WorkspacePanelAction::Add => {
+                let new_workspace =
+                    Workspace::new(format!("Workspace {}", self.config.workspaces.len() + 1));
+                self.config.workspaces.push(new_workspace);
+                self.save_config();
+            }
+            WorkspacePanelAction::Duplicate(idx) => {
+                let Some(workspace) = self.config.workspaces.get(idx) else {
+                    return;
+                };
+                let name = self.localizer.tf("workspace_copy_name", &[&workspace.name]);
+                let copy = workspace.duplicate(name);
+                // Копия встаёт сразу под оригиналом
+                self.config.workspaces.insert(idx + 1, copy);
+                self.refresh_repo_duplicates();
+                self.save_config();
+            }
+            WorkspacePanelAction::Archive(idx) => self.open_archive_dialog(Some(idx)),
+            WorkspacePanelAction::RemoveDuplicates(idx) => {
+                let Some(workspace) = self.config.workspaces.get_mut(idx) else {
+                    return;
+                };
+                let removed = workspace.remove_duplicates();
+                self.logger.info(
+                    self.localizer
+                        .tf("duplicates_removed", &[&removed.to_string()]),
+                );
+                if removed > 0 {
+                    self.refresh_repo_duplicates();
+                    self.save_config();
+                }
+            }
+            WorkspacePanelAction::RestoreFromArchive => self.open_archive_dialog(None),
+            WorkspacePanelAction::Reorder { from, to } => self.reorder_workspace(from, to),
+            WorkspacePanelAction::SetIdentity(idx, name, email) => {
+                if let Some(workspace) = self.config.workspaces.get_mut(idx) {
+                    workspace.git_author_name = name;
+                    workspace.git_author_email = email;
+                    self.apply_git_command_settings();
+                    self.mark_config_dirty();
+                }
+            }
+            WorkspacePanelAction::SetStyle(idx, color, environment) => {
+                if let Some(workspace) = self.config.workspaces.get_mut(idx) {
+                    workspace.color = color;
+                    workspace.environment = environment;
+                    self.mark_config_dirty();
+                }
+            }
+        }
+    }
+
+    /// Removes at once when the workspace is empty, otherwise asks first.
+    pub fn request_workspace_removal(&mut self, idx: usize) {
+        let Some(workspace) = self.config.workspaces.get(idx) else {
+            return;
+        };
+        let repo_count = workspace.repositories.len();
+        let severity = workspace_removal_confirmation(
+            repo_count,
+            self.config.workspace_removal_typed_threshold,
+        );
+        if severity == RemovalConfirmation::None {
+            self.remove_workspace(idx);
             return;
         }
 
-        let workspace = &mut self.config.workspaces[workspace_idx];
-        if workspace.is_loaded {
-            self.logger
-                .info(format!("Workspace '{}' already loaded", workspace.name));
+        self.confirmation_input.clear();
+        self.pending_confirmation = Some(ConfirmAction::RemoveWorkspace {
+            workspace_id: workspace.id,
+            workspace_name: workspace.name.clone(),
+            repo_count,
+            typed: severity == RemovalConfirmation::Typed,
+        });
+    }
+
+    pub fn remove_workspace(&mut self, idx: usize) {
+        if idx >= self.config.workspaces.len() {
             return;
         }
+        let removed = self.config.workspaces.remove(idx);
+        if self.editing_workspace == Some(removed.id) {
+            self.editing_workspace = None;
+        }
+        self.logger
+            .info(self.localizer.tf("workspace_removed", &[&removed.name]));
+        self.removed_workspace = Some(RemovedWorkspace {
+            workspace: removed,
+            index: idx,
+            removed_at: std::time::Instant::now(),
+        });
+        self.config.last_active_workspace_index = Some(self.active_workspace_idx());
+        self.apply_git_command_settings();
+        self.refresh_repo_duplicates();
+        self.save_config();
+    }
 
-        let repo_count = workspace.repositories.len();
-        self.logger.info(format!(
-            "Loading workspace '{}' with {} repositories",
-            workspace.name, repo_count
+    /// Puts the last removed workspace back at its old position.
+    pub fn undo_workspace_removal(&mut self) {
+        let Some(removed) = self.removed_workspace.take() else {
+            return;
+        };
+        let index = removed.index.min(self.config.workspaces.len());
+        self.logger.info(self.localizer.tf(
+            "workspace_restored",
+            &[
+                &removed.workspace.name,
+                &removed.workspace.repositories.len().to_string(),
+            ],
         ));
+        self.config.workspaces.insert(index, removed.workspace);
+        // Порядок сдвинулся — активный снова ищем по id
+        self.config.last_active_workspace_index = Some(self.active_workspace_idx());
+        self.apply_git_command_settings();
+        self.refresh_repo_duplicates();
+        self.save_config();
+    }
 
-        if let Some(tx) = &self.app_sender {
-            self.pending_git_loads += repo_count;
+    pub fn reorder_workspace(&mut self, from: usize, to: usize) {
+        let count = self.config.workspaces.len();
+        if from == to || from >= count || to >= count {
+            return;
+        }
 
-            for repo in &workspace.repositories {
-                self.logger.info(format!(
-                    "Starting async load for repo: {}",
-                    repo.path.display()
-                ));
-                refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+        self.config.workspaces.swap(from, to);
+        // Активный определяется по id и переезжает вместе с workspace
+        self.config.last_active_workspace_index = Some(self.active_workspace_idx());
+
+        self.save_config();
+    }
+
+    /// Fetch All for the active workspace.
+    pub fn request_fetch_all(&mut self) {
+        if let Some(workspace) = self.get_active_workspace() {
+            self.request_bulk(BulkRequest::FetchWorkspace(workspace.id));
+        }
+    }
+
+    /// Starts `request`, or opens the confirmation dialog first when one of
+    /// the workspaces it touches is labeled Prod or the global setting asks
+    /// for it.
+    pub fn request_bulk(&mut self, request: BulkRequest) {
+        let active_id = self.get_active_workspace().map(|workspace| workspace.id);
+        let workspaces: Vec<&Workspace> = match &request {
+            BulkRequest::FetchWorkspace(id) => self
+                .config
+                .workspaces
+                .iter()
+                .filter(|workspace| workspace.id == *id)
+                .collect(),
+            BulkRequest::FetchAllWorkspaces => self
+                .config
+                .workspaces
+                .iter()
+                .filter(|workspace| workspace.repository_count() > 0)
+                .collect(),
+            BulkRequest::Folder { repos, .. } => self.workspaces_holding(repos),
+            BulkRequest::Pull { repo_path, .. } => {
+                self.workspaces_holding(std::slice::from_ref(repo_path))
+            }
+            BulkRequest::SwitchBranch => self
+                .config
+                .workspaces
+                .iter()
+                .filter(|workspace| Some(workspace.id) == active_id)
+                .collect(),
+        };
+        // Решает самое строгое окружение среди затронутых
+        let confirm_bulk_actions = self.config.confirm_bulk_actions && request.is_bulk();
+        let needs_confirmation = request.mutates()
+            && workspaces.iter().any(|workspace| {
+                requires_bulk_confirmation(&workspace.environment, confirm_bulk_actions)
+            });
+        if !needs_confirmation {
+            self.run_bulk(request);
+            return;
+        }
+
+        let subject = match &request {
+            BulkRequest::FetchWorkspace(_) => workspaces
+                .first()
+                .map(|workspace| workspace.name.clone())
+                .unwrap_or_default(),
+            BulkRequest::FetchAllWorkspaces => workspaces
+                .iter()
+                .map(|workspace| workspace.repository_count())
+                .sum::<usize>()
+                .to_string(),
+            BulkRequest::Folder { folder, .. } => folder.clone(),
+            BulkRequest::Pull { repo_name, .. } => repo_name.clone(),
+            BulkRequest::SwitchBranch => self
+                .branch_switch_dialog
+                .as_ref()
+                .map(|dialog| dialog.target.trim().to_string())
+                .unwrap_or_default(),
+        };
+        self.pending_confirmation = Some(ConfirmAction::Bulk { request, subject });
+    }
+
+    /// Workspaces listing at least one of `repos`.
+    fn workspaces_holding(&self, repos: &[PathBuf]) -> Vec<&Workspace> {
+        let repos: HashSet<&PathBuf> = repos.iter().collect();
+        self.config
+            .workspaces
+            .iter()
+            .filter(|workspace| {
+                workspace
+                    .repositories
+                    .iter()
+                    .any(|repo| repos.contains(&repo.path))
+            })
+            .collect()
+    }
+
+    fn run_bulk(&mut self, request: BulkRequest) {
+        match request {
+            BulkRequest::FetchWorkspace(id) => {
+                if let Some(workspace_idx) = workspace_index(&self.config.workspaces, id) {
+                    self.fetch_all(workspace_idx);
+                }
+            }
+            BulkRequest::FetchAllWorkspaces => self.fetch_all_workspaces(),
+            BulkRequest::Folder {
+                folder,
+                repos,
+                action,
+            } => self.run_folder_action(folder, repos, action),
+            BulkRequest::Pull {
+                repo_path,
+                repo_name,
+            } => {
+                self.logger
+                    .info(self.localizer.tf("starting_pull", &[&repo_name]));
+                self.runtime.start_op(repo_path.clone(), RepoOp::Fetch);
+                if let Some(tx) = &self.app_sender {
+                    git_pull_fast_async::<AppMessage>(repo_path, tx.clone());
+                }
             }
+            BulkRequest::SwitchBranch => self.run_branch_switch(),
+        }
+    }
+
+    fn fetch_all(&mut self, workspace_idx: usize) {
+        let Some(workspace) = self.config.workspaces.get(workspace_idx) else {
+            return;
+        };
+        let repo_count = workspace.repository_count();
+        let plan = self.workspace_fetch_plan(&workspace.repositories);
+        self.run_fetch_plan(plan, repo_count);
+    }
+
+    fn workspace_fetch_plan(&self, repos: &[RepositoryState]) -> FetchPlan {
+        if self.config.fetch_submodules_with_parent {
+            plan_fetch_batch(repos)
         } else {
-            self.logger
-                .info("No app_sender available for loading repositories");
+            FetchPlan::plain(repos.iter().map(|r| r.path.clone()))
         }
+    }
 
-        workspace.mark_as_loaded();
-        self.logger
-            .info(format!("Workspace '{}' marked as loaded", workspace.name));
+    /// One job per `.git`: a folder registered twice, or monorepo services
+    /// and their root, get a single fetch or pull. Skipped entries are
+    /// refreshed when their job completes.
+    fn dedupe_plan(&mut self, plan: FetchPlan) -> FetchPlan {
+        // Ключи берём из закэшированных canonical_path, а не с диска
+        let repos = || {
+            self.config
+                .workspaces
+                .iter()
+                .flat_map(|workspace| &workspace.repositories)
+        };
+        let keys: HashMap<&Path, PathBuf> = repos()
+            .map(|repo| (repo.path.as_path(), repo.duplicate_key()))
+            .collect();
+        let key = |path: &Path| {
+            keys.get(path)
+                .cloned()
+                .unwrap_or_else(|| duplicate_key(path))
+        };
+        let project_keys: HashMap<&Path, PathBuf> = repos()
+            .map(|repo| (repo.path.as_path(), key(repo.infer_project_root())))
+            .collect();
+        let plan =
+            plan.dedupe_by(|path| project_keys.get(path).cloned().unwrap_or_else(|| key(path)));
+
+        for (entry, job) in &plan.merged {
+            self.refresh_after_job
+                .entry(job.clone())
+                .or_default()
+                .push(entry.clone());
+        }
+        plan
     }
 
-    pub fn refresh_all_loaded_repos(&mut self) {
+    /// Queues a status refresh for the entries that shared the finished job.
+    pub fn advance_refresh_after_job(&mut self, msg: &GitMessage) {
+        let Some(repo_path) = completed_repo_path(msg) else {
+            return;
+        };
+        let Some(entries) = self.refresh_after_job.remove(&repo_path) else {
+            return;
+        };
         if let Some(tx) = &self.app_sender {
-            let mut total_repos = 0;
-            for workspace in &self.config.workspaces {
-                for repo in &workspace.repositories {
-                    refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
-                    total_repos += 1;
-                }
+            for entry in entries {
+                refresh_repo_status_async::<AppMessage>(entry, tx.clone());
             }
+        }
+    }
 
-            if total_repos > 0 {
-                self.is_loading_on_startup = true;
-                self.startup_loaded_repos = 0;
-                self.search_status = Some(format!(
-                    "Загрузка информации о {} репозиториях...",
-                    total_repos
-                ));
-                self.search_status_timer = Some(std::time::Instant::now());
+    fn run_fetch_plan(&mut self, plan: FetchPlan, repo_count: usize) {
+        let plan = self.dedupe_plan(plan);
+
+        self.logger.info(
+            self.localizer
+                .tf("starting_fetch_all", &[&repo_count.to_string()]),
+        );
+        // Родитель мог слиться с другой записью той же папки
+        let merged_into: HashMap<&PathBuf, &PathBuf> = plan
+            .merged
+            .iter()
+            .map(|(entry, job)| (entry, job))
+            .collect();
+        for (submodule, parent) in &plan.covered {
+            self.logger.info(self.localizer.tf(
+                "fetch_covered_by_parent",
+                &[
+                    &submodule.display().to_string(),
+                    &parent.display().to_string(),
+                ],
+            ));
+            let job = merged_into.get(parent).copied().unwrap_or(parent);
+            self.refresh_after_job
+                .entry(job.clone())
+                .or_default()
+                .push(submodule.clone());
+        }
+
+        if self.config.auto_show_digest && !self.digest_auto_checked {
+            self.digest_auto_checked = true;
+            self.digest_after_fetch = true;
+        }
+
+        let recurse_submodules = plan
+            .jobs
+            .iter()
+            .filter(|job| job.recurse_submodules)
+            .map(|job| job.repo_path.clone())
+            .collect();
+        let repos = plan.jobs.into_iter().map(|job| job.repo_path).collect();
+        self.run_staggered(repos, BulkAction::Fetch, recurse_submodules);
+    }
+
+    /// Starts `action` on every repository, 200 ms apart, through the git pool.
+    /// Fetches of `recurse_submodules` repositories include their submodules.
+    fn run_staggered(
+        &mut self,
+        repos: Vec<PathBuf>,
+        action: BulkAction,
+        recurse_submodules: HashSet<PathBuf>,
+    ) {
+        for (index, repo_path) in repos.into_iter().enumerate() {
+            let recurse = recurse_submodules.contains(&repo_path);
+            if action != BulkAction::Refresh {
+                self.runtime.start_op(repo_path.clone(), RepoOp::Fetch);
+            }
+
+            let delay_ms = index as u64 * 200;
+
+            if let Some(tx) = &self.app_sender {
+                let tx_clone = tx.clone();
+                std::thread::spawn(move || {
+                    if delay_ms > 0 {
+                        std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+                    }
+                    match action {
+                        BulkAction::Fetch => git_fetch_fast_async_with_retry::<AppMessage>(
+                            repo_path, recurse, tx_clone,
+                        ),
+                        BulkAction::Pull => git_pull_fast_async::<AppMessage>(repo_path, tx_clone),
+                        BulkAction::Refresh => {
+                            refresh_repo_status_async::<AppMessage>(repo_path, tx_clone)
+                        }
+                    }
+                });
             }
         }
     }
 
+    /// Runs `action` on the repositories under a tree folder node.
+    fn run_folder_action(&mut self, folder: String, repos: Vec<PathBuf>, action: BulkAction) {
+        if repos.is_empty() {
+            return;
+        }
+        // Статус читается по каждой записи, а fetch и pull — по одному на .git
+        let repos = if action == BulkAction::Refresh {
+            repos
+        } else {
+            let plan = self.dedupe_plan(FetchPlan::plain(repos));
+            plan.jobs.into_iter().map(|job| job.repo_path).collect()
+        };
+
+        let operation = FolderOperation::new(action, folder, &repos);
+        self.logger.info(self.localizer.tf(
+            action.progress_key(),
+            &[&operation.folder, "0", &operation.total.to_string()],
+        ));
+        self.folder_operation = Some(operation);
+        self.update_folder_progress_status();
+        self.run_staggered(repos, action, HashSet::new());
+    }
+
+    pub fn advance_folder_operation(&mut self, msg: &GitMessage) {
+        let Some(operation) = &mut self.folder_operation else {
+            return;
+        };
+        let Some(repo_path) = completed_repo_path(msg) else {
+            return;
+        };
+
+        operation.complete(&repo_path);
+        self.update_folder_progress_status();
+        if self
+            .folder_operation
+            .as_ref()
+            .is_some_and(|operation| operation.is_finished())
+        {
+            self.folder_operation = None;
+        }
+    }
+
+    fn update_folder_progress_status(&mut self) {
+        if let Some(operation) = &self.folder_operation {
+            self.search_status = Some(self.localizer.tf(
+                operation.action.progress_key(),
+                &[
+                    &operation.folder,
+                    &operation.done().to_string(),
+                    &operation.total.to_string(),
+                ],
+            ));
+            self.search_status_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    pub fn open_branch_switch_dialog(&mut self) {
+        self.branch_switch_dialog = Some(BranchSwitchDialog {
+            target: String::new(),
+            create_missing: false,
+            rows: Vec::new(),
+        });
+    }
+
+    /// Recomputes the preview from the current status of every repository
+    /// in the active workspace, keeping the per-repo dirty overrides.
+    pub fn refresh_branch_switch_plan(&mut self) {
+        let workspace_idx = self.active_workspace_idx();
+        let Some(dialog) = &mut self.branch_switch_dialog else {
+            return;
+        };
+        let Some(workspace) = self.config.workspaces.get(workspace_idx) else {
+            return;
+        };
+
+        let target = dialog.target.trim().to_string();
+        let overridden: HashSet<PathBuf> = dialog
+            .rows
+            .iter()
+            .filter(|row| row.override_dirty)
+            .map(|row| row.repo_path.clone())
+            .collect();
+        dialog.rows = if target.is_empty() {
+            Vec::new()
+        } else {
+            workspace
+                .repositories
+                .iter()
+                .map(|repo| {
+                    let override_dirty =
+                        repo.git_info.has_changes && overridden.contains(&repo.path);
+                    let all_branches = self.all_branches.get(&repo.path).map(Vec::as_slice);
+                    let plan = if override_dirty {
+                        plan_ignoring_changes(
+                            &repo.git_info,
+                            all_branches,
+                            &target,
+                            dialog.create_missing,
+                        )
+                    } else {
+                        plan_branch_switch(
+                            &repo.git_info,
+                            all_branches,
+                            &target,
+                            dialog.create_missing,
+                        )
+                    };
+                    BranchSwitchRow {
+                        repo_path: repo.path.clone(),
+                        repo_name: repo.name.clone(),
+                        plan,
+                        override_dirty,
+                    }
+                })
+                .collect()
+        };
+
+        // План обновится, когда придёт полный список веток
+        let to_load: Vec<PathBuf> = dialog
+            .rows
+            .iter()
+            .filter(|row| row.plan == SwitchPlan::LoadingBranches)
+            .map(|row| row.repo_path.clone())
+            .collect();
+        for repo_path in to_load {
+            self.load_all_branches(repo_path);
+        }
+    }
+
+    /// Executes the previewed plan through the git pool.
+    fn run_branch_switch(&mut self) {
+        let Some(dialog) = self.branch_switch_dialog.take() else {
+            return;
+        };
+        let target = dialog.target.trim().to_string();
+        let (running, skipped): (Vec<_>, Vec<_>) =
+            dialog.rows.into_iter().partition(|row| row.plan.runs());
+
+        self.logger.info(self.localizer.tf(
+            "branch_switch_started",
+            &[
+                &target,
+                &running.len().to_string(),
+                &skipped.len().to_string(),
+            ],
+        ));
+        let batch = BranchSwitchBatch::new(
+            target.clone(),
+            running.iter().map(|row| row.repo_path.clone()).collect(),
+            skipped.len(),
+        );
+        if batch.is_finished() {
+            self.finish_branch_switch(batch);
+            return;
+        }
+        self.branch_switch_batch = Some(batch);
+        self.update_branch_switch_status();
+
+        let Some(tx) = &self.app_sender else {
+            return;
+        };
+        for row in running {
+            self.runtime.start_op(row.repo_path.clone(), RepoOp::Sync);
+            match row.plan {
+                SwitchPlan::CheckoutLocal => {
+                    switch_branch_async::<AppMessage>(row.repo_path, target.clone(), tx.clone())
+                }
+                SwitchPlan::TrackRemote { remote_branch } => {
+                    switch_branch_async::<AppMessage>(row.repo_path, remote_branch, tx.clone())
+                }
+                SwitchPlan::CreateFromDefault => create_branch_from_default_async::<AppMessage>(
+                    row.repo_path,
+                    target.clone(),
+                    tx.clone(),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn advance_branch_switch(&mut self, msg: &GitMessage) {
+        let Some(batch) = &mut self.branch_switch_batch else {
+            return;
+        };
+        let Some(repo_path) = completed_repo_path(msg) else {
+            return;
+        };
+
+        // Успех — только если репозиторий действительно на целевой ветке
+        let on_target = matches!(
+            msg,
+            GitMessage::RepoStatusUpdated { git_info, .. }
+                if git_info.current_branch.as_deref() == Some(batch.target.as_str())
+        );
+        batch.complete(&repo_path, on_target);
+        if batch.is_finished() {
+            if let Some(batch) = self.branch_switch_batch.take() {
+                self.finish_branch_switch(batch);
+            }
+        } else {
+            self.update_branch_switch_status();
+        }
+    }
+
+    fn update_branch_switch_status(&mut self) {
+        if let Some(batch) = &self.branch_switch_batch {
+            self.search_status = Some(self.localizer.tf(
+                "branch_switch_progress",
+                &[
+                    &batch.target,
+                    &batch.done().to_string(),
+                    &batch.total.to_string(),
+                ],
+            ));
+            self.search_status_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    fn finish_branch_switch(&mut self, batch: BranchSwitchBatch) {
+        let summary = self.localizer.tf(
+            "branch_switch_summary",
+            &[
+                &batch.target,
+                &batch.succeeded.to_string(),
+                &batch.skipped.to_string(),
+                &batch.failed.len().to_string(),
+            ],
+        );
+        if batch.failed.is_empty() {
+            self.logger.info(summary.clone());
+        } else {
+            self.logger.warning(summary.clone());
+        }
+        self.search_status = Some(summary);
+        self.search_status_timer = Some(std::time::Instant::now());
+    }
+
+    /// Scans every clean repository of the active workspace for local
+    /// branches merged into its default branch.
+    pub fn open_prune_dialog(&mut self) {
+        let Some(workspace) = self.config.workspaces.get(self.active_workspace_idx()) else {
+            return;
+        };
+        let (dirty, clean): (Vec<_>, Vec<_>) = workspace
+            .repositories
+            .iter()
+            .map(|repo| (repo.path.clone(), repo.git_info.has_changes))
+            .partition(|(_, has_changes)| *has_changes);
+        let scanning: Vec<PathBuf> = clean.into_iter().map(|(path, _)| path).collect();
+
+        self.prune_dialog = Some(PruneDialog::new(scanning.clone(), dirty.len()));
+        let Some(tx) = &self.app_sender else {
+            return;
+        };
+        for repo_path in scanning {
+            find_merged_branches_async::<AppMessage>(
+                repo_path,
+                self.config.protected_branches.clone(),
+                tx.clone(),
+            );
+        }
+    }
+
+    /// Feeds scan results into the preview and deletion results into the
+    /// running batch.
+    pub fn advance_branch_prune(&mut self, msg: &GitMessage) {
+        if let Some(dialog) = &mut self.prune_dialog {
+            match msg {
+                GitMessage::MergedBranchesFound {
+                    repo_path,
+                    default_branch,
+                    branches,
+                } => {
+                    let repo_name = repo_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    dialog.found(
+                        repo_path,
+                        repo_name,
+                        default_branch.clone(),
+                        branches.clone(),
+                    );
+                }
+                GitMessage::MergedBranchesScanFailed { repo_path, error } => {
+                    let repo_name = repo_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    dialog.scan_failed(repo_path, repo_name, error.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let Some(batch) = &mut self.prune_batch else {
+            return;
+        };
+        match msg {
+            GitMessage::BranchesPruned {
+                repo_path,
+                deleted,
+                failed,
+            } => {
+                for (branch, error) in failed {
+                    self.logger.warning_for(
+                        self.localizer
+                            .tf("branch_delete_error", &[branch, error.trim()]),
+                        repo_path,
+                    );
+                }
+                batch.complete(repo_path, deleted.len(), failed);
+            }
+            GitMessage::OperationCancelled { repo_path } => batch.abandon(repo_path),
+            _ => return,
+        }
+
+        if batch.is_finished() {
+            if let Some(batch) = self.prune_batch.take() {
+                self.finish_branch_prune(batch);
+            }
+        } else {
+            self.update_branch_prune_status();
+        }
+    }
+
+    /// Deletes the branches still checked in the preview through the git pool.
+    pub fn run_branch_prune(&mut self) {
+        let Some(dialog) = self.prune_dialog.take() else {
+            return;
+        };
+        let running: Vec<(PathBuf, String, Vec<String>)> = dialog
+            .repos
+            .iter()
+            .map(|repo| {
+                (
+                    repo.repo_path.clone(),
+                    repo.default_branch.clone(),
+                    repo.selected(),
+                )
+            })
+            .filter(|(_, _, branches)| !branches.is_empty())
+            .collect();
+        if running.is_empty() {
+            return;
+        }
+
+        self.prune_batch = Some(PruneBatch::new(
+            running.iter().map(|(path, _, _)| path.clone()).collect(),
+        ));
+        self.update_branch_prune_status();
+
+        let Some(tx) = &self.app_sender else {
+            return;
+        };
+        for (repo_path, default_branch, branches) in running {
+            prune_branches_async::<AppMessage>(repo_path, default_branch, branches, tx.clone());
+        }
+    }
+
+    fn update_branch_prune_status(&mut self) {
+        if let Some(batch) = &self.prune_batch {
+            self.search_status = Some(self.localizer.tf(
+                "prune_progress",
+                &[&batch.done().to_string(), &batch.total.to_string()],
+            ));
+            self.search_status_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    fn finish_branch_prune(&mut self, batch: PruneBatch) {
+        let summary = self.localizer.tf(
+            "prune_summary",
+            &[
+                &batch.deleted.to_string(),
+                &batch.repos_pruned.to_string(),
+                &batch.failures.len().to_string(),
+            ],
+        );
+        if batch.failures.is_empty() {
+            self.logger.info(summary.clone());
+        } else {
+            self.logger.warning(summary.clone());
+        }
+        self.search_status = Some(summary);
+        self.search_status_timer = Some(std::time::Instant::now());
+    }
+
+    /// After a push that left nothing ahead, links the page that opens a
+    /// pull request for the branch. Protected branches get no link.
+    pub fn offer_pull_request(&mut self, repo_path: &Path, git_info: &GitInfo) {
+        let Some(branch) = git_info.current_branch.as_deref() else {
+            return;
+        };
+        if git_info.ahead > 0 || self.config.protected_branches.iter().any(|b| b == branch) {
+            return;
+        }
+        let Some(url) = git_info
+            .remote_url
+            .as_deref()
+            .and_then(parse_remote_url)
+            .map(|hosted| hosted.new_pull_request_url(branch))
+        else {
+            return;
+        };
+
+        self.logger.info_with_link(
+            self.localizer.tf("branch_pushed_open_pr", &[branch]),
+            url.clone(),
+        );
+        self.pr_links
+            .insert(repo_path.to_path_buf(), (branch.to_string(), url));
+    }
+
+    pub fn start_push(&mut self, repo_path: PathBuf, repo_name: &str, no_verify: bool) {
+        let key = if no_verify {
+            "starting_push_no_verify"
+        } else {
+            "starting_push"
+        };
+        self.logger.info(self.localizer.tf(key, &[repo_name]));
+        self.runtime.start_op(repo_path.clone(), RepoOp::Push);
+        if let Some(tx) = &self.app_sender {
+            git_push_fast_async::<AppMessage>(repo_path, no_verify, tx.clone());
+        }
+    }
+
+    pub fn confirm_pending_action(&mut self) {
+        match self.pending_confirmation.take() {
+            Some(ConfirmAction::PushNoVerify {
+                repo_path,
+                repo_name,
+            }) => self.start_push(repo_path, &repo_name, true),
+            Some(ConfirmAction::Bulk { request, .. }) => self.run_bulk(request),
+            Some(ConfirmAction::MarkSafeDirectory {
+                repo_path,
+                repo_name,
+            }) => self.trust_repo_directory(repo_path, &repo_name),
+            Some(ConfirmAction::DeleteBranches {
+                repo_path,
+                repo_name,
+                branches,
+                force,
+            }) => self.delete_branches(repo_path, &repo_name, &branches, force),
+            Some(ConfirmAction::DeleteGoneBranch {
+                repo_path,
+                repo_name,
+                branch,
+                default_branch,
+                local_commits,
+            }) => self.delete_gone_branch(
+                repo_path,
+                &repo_name,
+                &branch,
+                &default_branch,
+                local_commits,
+            ),
+            Some(ConfirmAction::PushMirror {
+                repo_path,
+                repo_name,
+                remote,
+            }) => self.push_mirror(repo_path, &repo_name, remote),
+            // Индексы относятся к окну, открытому для этого репозитория
+            Some(ConfirmAction::DropStash {
+                repo_path, index, ..
+            }) if self
+                .stash_window
+                .as_ref()
+                .is_some_and(|window| window.repo_path == repo_path) =>
+            {
+                self.run_stash_action(StashAction::Drop, index)
+            }
+            Some(ConfirmAction::DropStash { .. }) => {}
+            Some(ConfirmAction::RemoveWorkspace { workspace_id, .. }) => {
+                if let Some(idx) = workspace_index(&self.config.workspaces, workspace_id) {
+                    self.remove_workspace(idx);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Git operations are running or waiting for a pool slot.
+    pub fn is_busy(&self) -> bool {
+        let (running, queued) = pool_status();
+        !self.runtime.is_idle() || running > 0 || queued > 0
+    }
+
+    /// Repository name and state key of every operation in flight, for the
+    /// close dialog.
+    pub fn in_flight_operations(&self) -> Vec<(String, &'static str)> {
+        let mut operations: Vec<(String, &'static str)> = self
+            .runtime
+            .syncing()
+            .map(|path| {
+                let name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let state = if is_queued(path) {
+                    "shutdown_op_queued"
+                } else if self.runtime.is_pushing(path) {
+                    "shutdown_op_pushing"
+                } else {
+                    "shutdown_op_running"
+                };
+                (name, state)
+            })
+            .collect();
+        operations.sort();
+        operations
+    }
+
+    pub fn choose_shutdown(&mut self, choice: ShutdownChoice) {
+        if choice == ShutdownChoice::CancelAndClose {
+            for path in self.runtime.syncing() {
+                cancel_queued(path);
+            }
+        }
+        self.shutdown.choose(
+            choice,
+            std::time::Duration::from_secs(self.config.shutdown_wait_secs as u64),
+            std::time::Instant::now(),
+        );
+    }
+
+    /// Force-pushes the current branch, asking for a reason first when
+    /// `force_push_require_reason` is on.
+    pub fn request_force_push(&mut self, repo_path: PathBuf, repo_name: String, branch: String) {
+        if self.config.force_push_require_reason {
+            self.force_push_dialog = Some(ForcePushDialog {
+                repo_path,
+                repo_name,
+                branch,
+                reason: String::new(),
+            });
+        } else {
+            self.start_force_push(repo_path, repo_name, branch, String::new());
+        }
+    }
+
+    pub fn start_force_push(
+        &mut self,
+        repo_path: PathBuf,
+        repo_name: String,
+        branch: String,
+        reason: String,
+    ) {
+        self.force_push_dialog = None;
+        self.logger.warning(
+            self.localizer
+                .tf("force_push_started", &[&repo_name, &branch]),
+        );
+        self.runtime.start_op(repo_path.clone(), RepoOp::Sync);
+        self.force_push_reasons
+            .insert(repo_path.clone(), (repo_name, branch, reason));
+        if let Some(tx) = &self.app_sender {
+            git_push_force_with_lease_async::<AppMessage>(repo_path, tx.clone());
+        }
+    }
+
+    /// Adds a finished force push to the persisted history.
+    pub fn finish_force_push(
+        &mut self,
+        repo_path: &Path,
+        commit_before: Option<String>,
+        commit_after: String,
+    ) {
+        let Some((repo_name, branch, reason)) = self.force_push_reasons.remove(repo_path) else {
+            return;
+        };
+
+        self.logger.warning(
+            self.localizer
+                .tf("force_push_finished", &[&repo_name, &branch, &reason]),
+        );
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.config.record_force_push(ForcePushRecord {
+            repo_name,
+            branch,
+            reason,
+            timestamp,
+            commit_before,
+            commit_after,
+        });
+        self.save_config();
+    }
+
+    fn push_mirror(&mut self, repo_path: PathBuf, repo_name: &str, remote: String) {
+        self.logger.warning(
+            self.localizer
+                .tf("mirror_push_started", &[repo_name, &remote]),
+        );
+        self.runtime.start_op(repo_path.clone(), RepoOp::Sync);
+        if let Some(tx) = &self.app_sender {
+            git_push_mirror_async::<AppMessage>(repo_path, remote, tx.clone());
+        }
+    }
+
+    /// Adds the repository to the global `safe.directory` list and retries
+    /// the refresh that failed on dubious ownership.
+    fn trust_repo_directory(&mut self, repo_path: PathBuf, repo_name: &str) {
+        match mark_safe_directory(&SystemGit, &repo_path) {
+            Ok(_) => {
+                self.logger
+                    .info(self.localizer.tf("safe_directory_added", &[repo_name]));
+                self.runtime.begin_retry(&repo_path);
+                if let Some(tx) = &self.app_sender {
+                    refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                }
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("safe_directory_error", &[repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    pub fn open_commit_log(&mut self, repo_path: PathBuf, repo_name: String) {
+        if let Some(tx) = &self.app_sender {
+            git_log_async::<AppMessage>(repo_path.clone(), tx.clone());
+        }
+        self.commit_log = Some(CommitLogView {
+            repo_path,
+            repo_name,
+            commits: None,
+            graph: None,
+            show_graph: false,
+            selected: None,
+            diff_errors: HashMap::new(),
+            notes: Vec::new(),
+            editing_note: None,
+            cat_file_path: String::new(),
+        });
+    }
+
+    /// Switches the commit log between the plain list and the graph.
+    pub fn toggle_commit_graph(&mut self) {
+        let Some(view) = &mut self.commit_log else {
+            return;
+        };
+        view.show_graph = !view.show_graph;
+        // После ошибки граф запрашивается заново
+        if view.show_graph && !matches!(view.graph, Some(Ok(_))) {
+            view.graph = None;
+            if let Some(tx) = &self.app_sender {
+                git_log_graph_async::<AppMessage>(view.repo_path.clone(), tx.clone());
+            }
+        }
+    }
+
+    /// Opens the inline note editor of a commit with its current note.
+    pub fn edit_commit_note(&mut self, commit_hash: String) {
+        let Some(view) = &mut self.commit_log else {
+            return;
+        };
+        let text = view
+            .note_for(&commit_hash)
+            .map(|note| note.content.trim_end().to_string())
+            .unwrap_or_default();
+        view.editing_note = Some((commit_hash, text));
+    }
+
+    /// Saves the edited note; an empty text removes it.
+    pub fn save_commit_note(&mut self, remove: bool) {
+        let Some(view) = &mut self.commit_log else {
+            return;
+        };
+        let Some((commit_hash, text)) = view.editing_note.take() else {
+            return;
+        };
+
+        let message = (!remove && !text.trim().is_empty()).then_some(text);
+        if message.is_none() && view.note_for(&commit_hash).is_none() {
+            return;
+        }
+        if let Some(tx) = &self.app_sender {
+            git_set_note_async::<AppMessage>(
+                view.repo_path.clone(),
+                commit_hash,
+                message,
+                tx.clone(),
+            );
+        }
+    }
+
+    pub fn select_commit(&mut self, hash: String) {
+        let Some(view) = &mut self.commit_log else {
+            return;
+        };
+        view.selected = Some(hash.clone());
+        view.diff_errors.remove(&hash);
+
+        if !self.commit_diff_cache.contains_key(&hash) {
+            if let Some(tx) = &self.app_sender {
+                git_show_async::<AppMessage>(view.repo_path.clone(), hash, tx.clone());
+            }
+        }
+    }
+
+    pub fn view_file_at_commit(&mut self, hash: String, file_path: String) {
+        let Some(view) = &self.commit_log else {
+            return;
+        };
+        let repo_path = view.repo_path.clone();
+        if let Some(tx) = &self.app_sender {
+            git_cat_file_async::<AppMessage>(
+                repo_path.clone(),
+                hash.clone(),
+                file_path.clone(),
+                tx.clone(),
+            );
+        }
+
+        self.file_at_commit = Some(FileAtCommitView {
+            repo_path,
+            hash,
+            file_path,
+            content: None,
+            text: String::new(),
+        });
+    }
+
+    /// Asks where to save the file; the system dialog confirms replacing an
+    /// existing one.
+    pub fn save_file_at_commit(&mut self) {
+        let Some(view) = &self.file_at_commit else {
+            return;
+        };
+        let Some(Ok(content)) = &view.content else {
+            return;
+        };
+        let mut dialog = rfd::FileDialog::new().set_file_name(format!(
+            "{}-{}",
+            view.short_hash(),
+            view.file_name()
+        ));
+        if let Some(home) = home_dir() {
+            dialog = dialog.set_directory(home);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+
+        match std::fs::write(&path, content) {
+            Ok(_) => self.logger.info(
+                self.localizer
+                    .tf("file_at_commit_saved", &[&path.display().to_string()]),
+            ),
+            Err(e) => self.logger.error(self.localizer.tf(
+                "file_at_commit_save_error",
+                &[&path.display().to_string(), &e.to_string()],
+            )),
+        }
+    }
+
+    pub fn cache_commit_diff(&mut self, hash: String, diff_text: String) {
+        self.commit_diff_order.retain(|h| *h != hash);
+        self.commit_diff_order.push_back(hash.clone());
+        self.commit_diff_cache.insert(hash, diff_text);
+
+        while self.commit_diff_order.len() > COMMIT_DIFF_CACHE_SIZE {
+            if let Some(oldest) = self.commit_diff_order.pop_front() {
+                self.commit_diff_cache.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn generate_digest(&mut self, auto: bool) {
+        let Some(workspace) = self.get_active_workspace() else {
+            return;
+        };
+        let requests = workspace
+            .repositories
+            .iter()
+            .map(|repo| DigestRequest {
+                repo_path: repo.path.clone(),
+                repo_name: repo.name.clone(),
+                last_seen_tip: self.status_cache.upstream_tips.get(&repo.path).cloned(),
+            })
+            .collect();
+
+        if let Some(tx) = &self.app_sender {
+            self.digest_loading = true;
+            build_digest_async::<AppMessage>(requests, auto, tx.clone());
+        }
+    }
+
+    /// Folder the workspace snapshots are stored in, next to the config.
+    fn snapshot_dir() -> PathBuf {
+        ConfigManager::get_config_file_path().with_file_name("snapshots")
+    }
+
+    /// Name and current status data of the active workspace's repositories.
+    fn snapshot_requests(&self) -> Option<(String, Vec<SnapshotRequest>)> {
+        let workspace = self.get_active_workspace()?;
+        let requests = workspace
+            .repositories
+            .iter()
+            .map(|repo| SnapshotRequest {
+                repo_path: repo.path.clone(),
+                repo_name: repo.name.clone(),
+                branch: repo.git_info.current_branch.clone(),
+                ahead: repo.git_info.ahead,
+                behind: repo.git_info.behind,
+            })
+            .collect();
+        Some((workspace.name.clone(), requests))
+    }
+
+    pub fn take_snapshot(&mut self) {
+        let Some((workspace, requests)) = self.snapshot_requests() else {
+            return;
+        };
+        if let Some(tx) = &self.app_sender {
+            self.snapshot_loading = true;
+            take_snapshot_async::<AppMessage>(workspace, requests, tx.clone());
+        }
+    }
+
+    pub fn snapshot_taken(&mut self, snapshot: WorkspaceSnapshot) {
+        self.snapshot_loading = false;
+        match save_snapshot(&Self::snapshot_dir(), &snapshot) {
+            Ok(path) => {
+                self.snapshot_list = None;
+                self.logger.info(self.localizer.tf(
+                    "snapshot_saved",
+                    &[
+                        &snapshot.repos.len().to_string(),
+                        &path.display().to_string(),
+                    ],
+                ));
+            }
+            Err(e) => self
+                .logger
+                .error(self.localizer.tf("snapshot_save_failed", &[&e.to_string()])),
+        }
+    }
+
+    /// Stored snapshots of the active workspace, newest first.
+    pub fn stored_snapshots(&mut self) -> Vec<WorkspaceSnapshot> {
+        let Some(name) = self.get_active_workspace().map(|w| w.name.clone()) else {
+            return Vec::new();
+        };
+        match &self.snapshot_list {
+            Some((listed, snapshots)) if *listed == name => snapshots.clone(),
+            _ => {
+                let snapshots = list_snapshots(&Self::snapshot_dir(), &name);
+                self.snapshot_list = Some((name, snapshots.clone()));
+                snapshots
+            }
+        }
+    }
+
+    pub fn compare_with_snapshot(&mut self, snapshot: WorkspaceSnapshot) {
+        let Some((_, requests)) = self.snapshot_requests() else {
+            return;
+        };
+        if let Some(tx) = &self.app_sender {
+            self.snapshot_diff = Some(SnapshotDiffView {
+                taken_at: snapshot.taken_at,
+                changes: None,
+            });
+            compare_snapshot_async::<AppMessage>(snapshot, requests, tx.clone());
+        }
+    }
+
+    pub fn snapshot_compared(&mut self, taken_at: u64, changes: Vec<SnapshotChange>) {
+        // Окно могли закрыть или открыть для другого снимка
+        if let Some(view) = &mut self.snapshot_diff {
+            if view.taken_at == taken_at {
+                view.changes = Some(changes);
+            }
+        }
+    }
+
+    /// Moves between normal and low-power mode from the viewport state.
+    /// Returns the repaint heartbeat to request while in low-power mode.
+    pub fn update_power_state(
+        &mut self,
+        focused: bool,
+        minimized: bool,
+    ) -> Option<std::time::Duration> {
+        self.power
+            .set_catch_up_after(std::time::Duration::from_secs(
+                self.config.catch_up_minutes as u64 * 60,
+            ));
+
+        match self
+            .power
+            .update(focused, minimized, std::time::Instant::now())
+        {
+            PowerTransition::Entered => self.logger.info(self.localizer.t("low_power_entered")),
+            PowerTransition::Exited { catch_up } => {
+                self.logger.info(self.localizer.t("low_power_exited"));
+                if catch_up {
+                    self.refresh_all_repos();
+                }
+            }
+            PowerTransition::None => {}
+        }
+
+        self.power
+            .is_low_power()
+            .then_some(LOW_POWER_REPAINT_INTERVAL)
+    }
+
+    /// Starts the automatic digest once the first Fetch All of the session
+    /// has finished.
+    pub fn maybe_start_auto_digest(&mut self) {
+        if self.digest_after_fetch && self.runtime.is_idle() {
+            self.digest_after_fetch = false;
+            self.generate_digest(true);
+        }
+    }
+
+    /// Starts the dirty-noise check of a repository unless it ran since the
+    /// last status update.
+    pub fn request_dirty_noise(&mut self, repo_path: &Path) {
+        if self.dirty_noise.contains_key(repo_path) {
+            return;
+        }
+        let Some(tx) = &self.app_sender else {
+            return;
+        };
+        self.dirty_noise.insert(repo_path.to_path_buf(), None);
+        check_dirty_noise_async::<AppMessage>(repo_path.to_path_buf(), tx.clone());
+    }
+
+    pub fn open_dirty_noise(&mut self, repo_path: &Path, repo_name: String) {
+        if let Some(Some(noise)) = self.dirty_noise.get(repo_path) {
+            self.dirty_noise_view = Some(DirtyNoiseView {
+                repo_name,
+                noise: noise.clone(),
+            });
+        }
+    }
+
+    /// Detects the project type from the files in the repository root, off
+    /// the UI thread.
+    pub fn request_project_type(&self, repo_path: PathBuf) {
+        let Some(tx) = &self.app_sender else {
+            return;
+        };
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let project_type = detect_project_type(&repo_path);
+            let _ = tx.send(AppMessage::ProjectTypeDetected {
+                repo_path,
+                project_type,
+            });
+        });
+    }
+
+    /// Starts loading the sparkline of a visible row when it is missing or
+    /// from an earlier day.
+    pub fn request_commit_activity(&mut self, repo_path: &Path, now: i64) {
+        if self.activity_loading.contains(repo_path) || self.activity_failed.contains(repo_path) {
+            return;
+        }
+        if matches!(self.status_cache.activity.get(repo_path), Some(a) if a.is_current(now)) {
+            return;
+        }
+        let Some(tx) = &self.app_sender else {
+            return;
+        };
+        self.activity_loading.insert(repo_path.to_path_buf());
+        load_commit_activity_async::<AppMessage>(repo_path.to_path_buf(), tx.clone());
+    }
+
+    /// Stores a loaded sparkline; the cache is written once the batch of
+    /// visible rows has finished.
+    pub fn commit_activity_loaded(&mut self, repo_path: PathBuf, activity: Option<CommitActivity>) {
+        self.activity_loading.remove(&repo_path);
+        match activity {
+            Some(activity) => {
+                self.status_cache.activity.insert(repo_path, activity);
+            }
+            None => {
+                self.activity_failed.insert(repo_path);
+            }
+        }
+
+        if self.activity_loading.is_empty() {
+            if let Err(e) = self.status_cache.save() {
+                self.logger
+                    .error(format!("Failed to save status cache: {}", e));
+            }
+        }
+    }
+
+    pub fn apply_digest(
+        &mut self,
+        tips: Vec<(PathBuf, String)>,
+        deltas: Vec<RepoDelta>,
+        auto: bool,
+    ) {
+        self.digest_loading = false;
+        self.status_cache.upstream_tips.extend(tips);
+        if let Err(e) = self.status_cache.save() {
+            self.logger
+                .error(format!("Failed to save status cache: {}", e));
+        }
+
+        if !auto || !deltas.is_empty() {
+            self.digest = Some(deltas);
+        }
+    }
+
+    /// Picks up edits to `assets/locales/*.json` without a rebuild.
+    #[cfg(debug_assertions)]
+    pub fn hot_reload_locales(&mut self) {
+        if self.locale_reload_timer.elapsed() < std::time::Duration::from_secs(5) {
+            return;
+        }
+        self.locale_reload_timer = std::time::Instant::now();
+
+        let dir = Localizer::locales_dir();
+        let first_check = self.last_reload_time.is_empty();
+        if Localizer::locale_files_changed(&dir, &mut self.last_reload_time)
+            && !first_check
+            && self.localizer.reload()
+        {
+            self.localizer.set_language(&self.config.language);
+            self.logger
+                .info(format!("Locales reloaded from {}", dir.display()));
+        }
+    }
+
+    pub fn global_credential_helper(&mut self) -> Option<&str> {
+        self.global_credential_helper
+            .get_or_insert_with(global_credential_helper)
+            .as_deref()
+    }
+
+    pub fn apply_credential_helper(&mut self) {
+        let mut applied = 0;
+        for path in &self.all_repo_paths() {
+            match configure_credential_helper(path, &self.config.credential_helper) {
+                Ok(_) => applied += 1,
+                Err(e) => self.logger.error(format!(
+                    "Failed to configure credential helper for {:?}: {}",
+                    path, e
+                )),
+            }
+        }
+
+        self.logger.info(
+            self.localizer
+                .tf("credential_helper_applied", &[&applied.to_string()]),
+        );
+        self.save_config();
+    }
+
+    fn all_repo_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .config
+            .workspaces
+            .iter()
+            .flat_map(|w| w.repositories.iter().map(|r| r.path.clone()))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    pub fn refresh_missing_prefixes(&mut self) {
+        self.missing_prefixes = find_missing_prefixes(
+            &self.all_repo_paths(),
+            |path| path.exists(),
+            MISSING_PREFIX_THRESHOLD,
+        );
+    }
+
+    pub fn open_repath_dialog(&mut self, old_prefix: PathBuf) {
+        self.repath_dialog = Some(RepathDialog {
+            new_prefix: old_prefix.to_string_lossy().to_string(),
+            old_prefix,
+        });
+    }
+
+    pub fn load_all_branches(&mut self, repo_path: PathBuf) {
+        if !self.loading_branches.insert(repo_path.clone()) {
+            return;
+        }
+        if let Some(tx) = &self.app_sender {
+            load_all_branches_async::<AppMessage>(repo_path, tx.clone());
+        }
+    }
+
+    /// Checks out `branch` in the background; the row shows a spinner until
+    /// the refreshed status arrives.
+    pub fn start_branch_switch(&mut self, repo_path: PathBuf, branch: String) {
+        self.pending_branch_switch = None;
+        self.logger.info(self.localizer.tf(
+            "starting_branch_switch",
+            &[&branch, &repo_path.display().to_string()],
+        ));
+        self.runtime.start_op(repo_path.clone(), RepoOp::Sync);
+        if let Some(tx) = &self.app_sender {
+            switch_branch_async::<AppMessage>(repo_path, branch, tx.clone());
+        }
+    }
+
+    pub fn open_stash_window(&mut self, repo_path: PathBuf, repo_name: String) {
+        match git_stash_list(&repo_path) {
+            Ok(entries) => {
+                self.stash_window = Some(StashWindow {
+                    repo_path,
+                    repo_name,
+                    entries,
+                    selected: None,
+                    diffs: HashMap::new(),
+                    loading: HashSet::new(),
+                });
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("stash_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    /// Requests the diff of a stash entry unless it is cached or in flight.
+    pub fn load_stash_diff(&mut self, index: usize) {
+        let Some(window) = &mut self.stash_window else {
+            return;
+        };
+        if window.diffs.contains_key(&index) || !window.loading.insert(index) {
+            return;
+        }
+        if let Some(tx) = &self.app_sender {
+            git_stash_show_async::<AppMessage>(window.repo_path.clone(), index, tx.clone());
+        }
+    }
+
+    /// Drop asks first; pop and apply keep the changes and run at once.
+    pub fn request_stash_action(&mut self, action: StashAction, index: usize) {
+        let Some(window) = &self.stash_window else {
+            return;
+        };
+        if action != StashAction::Drop {
+            self.run_stash_action(action, index);
+            return;
+        }
+
+        let stash = window
+            .entries
+            .iter()
+            .find(|entry| entry.index == index)
+            .map(|entry| format!("stash@{{{}}}  {}", entry.index, entry.message))
+            .unwrap_or_else(|| format!("stash@{{{}}}", index));
+        self.pending_confirmation = Some(ConfirmAction::DropStash {
+            repo_path: window.repo_path.clone(),
+            repo_name: window.repo_name.clone(),
+            index,
+            stash,
+        });
+    }
+
+    fn run_stash_action(&mut self, action: StashAction, index: usize) {
+        let Some(window) = &self.stash_window else {
+            return;
+        };
+        let repo_path = window.repo_path.clone();
+        let repo_name = window.repo_name.clone();
+
+        match git_stash_action(&repo_path, action, index) {
+            Ok(_) => {
+                self.logger.info(self.localizer.tf(
+                    "stash_action_done",
+                    &[
+                        &self.localizer.t(action.label_key()),
+                        &format!("stash@{{{}}}", index),
+                        &repo_name,
+                    ],
+                ));
+                // Индексы сдвигаются после pop/drop, список перечитывается целиком
+                self.open_stash_window(repo_path.clone(), repo_name);
+                if let Some(tx) = &self.app_sender {
+                    refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                }
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("stash_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    /// Starts polling the bisect of `repo_path` unless a watcher already
+    /// runs for it.
+    pub fn watch_bisect(&mut self, repo_path: &Path) {
+        if !self.bisect_watchers.insert(repo_path.to_path_buf()) {
+            return;
+        }
+        if let Some(tx) = &self.app_sender {
+            watch_bisect_async::<AppMessage>(repo_path.to_path_buf(), tx.clone());
+        }
+    }
+
+    pub fn mark_bisect(&mut self, repo_path: PathBuf, verdict: BisectVerdict) {
+        self.runtime.start_op(repo_path.clone(), RepoOp::Sync);
+        if let Some(tx) = &self.app_sender {
+            git_bisect_mark_async::<AppMessage>(repo_path, verdict, tx.clone());
+        }
+    }
+
+    pub fn reset_bisect(&mut self, repo_path: PathBuf) {
+        match git_bisect_reset(&repo_path) {
+            Ok(()) => {
+                self.bisect_states.remove(&repo_path);
+                if let Some(tx) = &self.app_sender {
+                    refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                }
+            }
+            Err(e) => self.logger.error(self.localizer.tf(
+                "bisect_error",
+                &[&repo_path.display().to_string(), &e.to_string()],
+            )),
+        }
+    }
+
+    pub fn show_pull_conflicts(&mut self, repo_path: PathBuf, files: Vec<String>) {
+        let repo_name = repo_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        self.logger.warning(
+            self.localizer
+                .tf("pull_conflicts", &[&repo_name, &files.len().to_string()]),
+        );
+        self.conflict_panels.insert(
+            repo_path.clone(),
+            ConflictPanel::new(repo_path, repo_name, files),
+        );
+    }
+
+    pub fn recheck_conflicts(&mut self, repo_path: &Path) {
+        let Some(panel) = self.conflict_panels.get_mut(repo_path) else {
+            return;
+        };
+        panel.start_check();
+        if let Some(tx) = &self.app_sender {
+            check_conflicts_async::<AppMessage>(repo_path.to_path_buf(), tx.clone());
+        }
+    }
+
+    /// Aborts the merge or rebase in progress when `abort`, otherwise
+    /// "Mark resolved & continue".
+    pub fn finish_merge(&mut self, repo_path: &Path, abort: bool) {
+        let Some(panel) = self.conflict_panels.get_mut(repo_path) else {
+            return;
+        };
+        panel.start_finish();
+        self.runtime.start_op(repo_path.to_path_buf(), RepoOp::Sync);
+        if let Some(tx) = &self.app_sender {
+            git_finish_merge_async::<AppMessage>(repo_path.to_path_buf(), abort, tx.clone());
+        }
+    }
+
+    /// Abort or continue finished: a status update closes the panel, an
+    /// error leaves it open for another attempt.
+    pub fn conflict_operation_done(&mut self, repo_path: &Path, succeeded: bool) {
+        let Some(panel) = self.conflict_panels.get_mut(repo_path) else {
+            return;
+        };
+        match (panel.stage, succeeded) {
+            (ConflictStage::Finishing, true) => {
+                self.conflict_panels.remove(repo_path);
+            }
+            (ConflictStage::Finishing | ConflictStage::Checking, false) => panel.failed(),
+            _ => {}
+        }
+    }
+
+    pub fn open_branches_view(&mut self, repo_path: PathBuf, repo_name: String) {
+        match git_branch_overview(&repo_path) {
+            Ok(branches) => {
+                self.branches_view = Some(BranchesView {
+                    default_branch: git_default_branch(&repo_path),
+                    repo_path,
+                    repo_name,
+                    branches,
+                    protected_input: self.config.protected_branches.join(", "),
+                });
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("branches_overview_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    /// Re-reads the overview after a switch or delete in its repository.
+    pub fn reload_branches_view(&mut self, repo_path: &Path) {
+        let Some(view) = &mut self.branches_view else {
+            return;
+        };
+        if view.repo_path != repo_path {
+            return;
+        }
+        if let Ok(branches) = git_branch_overview(&view.repo_path) {
+            view.branches = branches;
+        }
+    }
+
+    pub fn apply_protected_branches_input(&mut self) {
+        let Some(view) = &self.branches_view else {
+            return;
+        };
+        let protected: Vec<String> = view
+            .protected_input
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        if protected != self.config.protected_branches {
+            self.config.protected_branches = protected;
+            self.save_config();
+        }
+    }
+
+    pub fn request_delete_branch(&mut self, branch: String) {
+        let Some(view) = &self.branches_view else {
+            return;
+        };
+        self.pending_confirmation = Some(ConfirmAction::DeleteBranches {
+            repo_path: view.repo_path.clone(),
+            repo_name: view.repo_name.clone(),
+            branches: vec![branch],
+            force: false,
+        });
+    }
+
+    /// Asks to delete every branch merged into the default branch, listing
+    /// them in the confirmation.
+    pub fn request_delete_merged_branches(&mut self) {
+        self.apply_protected_branches_input();
+        let Some(view) = &self.branches_view else {
+            return;
+        };
+        let Some(default_branch) = view.default_branch.clone() else {
+            return;
+        };
+
+        match git_merged_branches(
+            &view.repo_path,
+            &default_branch,
+            &self.config.protected_branches,
+        ) {
+            Ok(branches) if branches.is_empty() => self.logger.info(
+                self.localizer
+                    .tf("no_merged_branches", &[&default_branch, &view.repo_name]),
+            ),
+            Ok(branches) => {
+                self.pending_confirmation = Some(ConfirmAction::DeleteBranches {
+                    repo_path: view.repo_path.clone(),
+                    repo_name: view.repo_name.clone(),
+                    branches,
+                    force: true,
+                });
+            }
+            Err(e) => self.logger.error(self.localizer.tf(
+                "branches_overview_error",
+                &[&view.repo_name, &e.to_string()],
+            )),
+        }
+    }
+
+    fn delete_branches(
+        &mut self,
+        repo_path: PathBuf,
+        repo_name: &str,
+        branches: &[String],
+        force: bool,
+    ) {
+        for branch in branches {
+            match git_delete_branch(&repo_path, branch, force) {
+                Ok(()) => self.logger.info_for(
+                    self.localizer.tf("branch_deleted", &[branch, repo_name]),
+                    &repo_path,
+                ),
+                Err(e) => self.logger.error_for(
+                    self.localizer
+                        .tf("branch_delete_error", &[branch, &e.to_string()]),
+                    &repo_path,
+                ),
+            }
+        }
+
+        self.reload_branches_view(&repo_path);
+        if let Some(tx) = &self.app_sender {
+            refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+        }
+    }
+
+    pub fn unset_upstream(&mut self, repo_path: PathBuf, repo_name: &str) {
+        match git_unset_upstream(&repo_path) {
+            Ok(()) => {
+                self.logger.info_for(
+                    self.localizer.tf("upstream_unset", &[repo_name]),
+                    &repo_path,
+                );
+                if let Some(tx) = &self.app_sender {
+                    refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                }
+            }
+            Err(e) => self.logger.error_for(
+                self.localizer
+                    .tf("upstream_error", &[repo_name, &e.to_string()]),
+                &repo_path,
+            ),
+        }
+    }
+
+    /// Tracks `origin/<default branch>` instead of the deleted upstream.
+    pub fn repoint_upstream(&mut self, repo_path: PathBuf, repo_name: &str) {
+        let Some(default_branch) = git_default_branch(&repo_path) else {
+            return;
+        };
+        let upstream = format!("origin/{}", default_branch);
+        match git_set_upstream(&repo_path, &upstream) {
+            Ok(()) => {
+                self.logger.info_for(
+                    self.localizer.tf("upstream_set", &[repo_name, &upstream]),
+                    &repo_path,
+                );
+                if let Some(tx) = &self.app_sender {
+                    refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                }
+            }
+            Err(e) => self.logger.error_for(
+                self.localizer
+                    .tf("upstream_error", &[repo_name, &e.to_string()]),
+                &repo_path,
+            ),
+        }
+    }
+
+    /// Asks before deleting the checked-out branch whose upstream is gone.
+    /// Only offered for a clean working tree, so nothing uncommitted is
+    /// carried over to the default branch.
+    pub fn request_delete_gone_branch(&mut self, repo: &RepositoryState) {
+        let Some(branch) = repo.git_info.current_branch.clone() else {
+            return;
+        };
+        if repo.git_info.has_changes {
+            return;
+        }
+        let Some(default_branch) = git_default_branch(&repo.path) else {
+            return;
+        };
+        if default_branch == branch {
+            return;
+        }
+        // Upstream удалён, поэтому ahead ничего не говорит о неотправленных коммитах
+        let local_commits = match git_local_only_commits(&repo.path, &branch, &default_branch) {
+            Ok(count) => count,
+            Err(e) => {
+                self.logger.error_for(
+                    self.localizer
+                        .tf("branch_delete_error", &[&branch, &e.to_string()]),
+                    &repo.path,
+                );
+                return;
+            }
+        };
+
+        self.pending_confirmation = Some(ConfirmAction::DeleteGoneBranch {
+            repo_path: repo.path.clone(),
+            repo_name: repo.name.clone(),
+            branch,
+            default_branch,
+            local_commits,
+        });
+    }
+
+    fn delete_gone_branch(
+        &mut self,
+        repo_path: PathBuf,
+        repo_name: &str,
+        branch: &str,
+        default_branch: &str,
+        confirmed_commits: usize,
+    ) {
+        // Пока диалог был открыт, на ветке могли появиться новые коммиты
+        match git_local_only_commits(&repo_path, branch, default_branch) {
+            Ok(count) if count <= confirmed_commits => {}
+            Ok(count) => {
+                self.logger.error_for(
+                    self.localizer
+                        .tf("gone_branch_gained_commits", &[branch, &count.to_string()]),
+                    &repo_path,
+                );
+                return;
+            }
+            Err(e) => {
+                self.logger.error_for(
+                    self.localizer
+                        .tf("branch_delete_error", &[branch, &e.to_string()]),
+                    &repo_path,
+                );
+                return;
+            }
+        }
+        if let Err(e) = switch_branch(&repo_path, default_branch) {
+            self.logger.error(
+                self.localizer
+                    .tf("branch_delete_error", &[branch, &e.to_string()]),
+            );
+            return;
+        }
+        // Ветка уже слита и удалена на сервере, -d отказал бы для squash-слияний
+        self.delete_branches(repo_path, repo_name, &[branch.to_string()], true);
+    }
+
+    pub fn open_remotes_window(&mut self, repo_path: PathBuf, repo_name: String) {
+        match git_list_remotes(&repo_path) {
+            Ok(remotes) => {
+                self.remotes = Some(RemotesView {
+                    repo_path,
+                    repo_name,
+                    remotes,
+                    editing: None,
+                });
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("remotes_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    /// Looks for service folders below `repo_path` that are not in the
+    /// active workspace yet and opens the checklist for them.
+    pub fn open_monorepo_services(&mut self, repo_path: PathBuf, repo_name: String) {
+        let known: HashSet<PathBuf> = self
+            .get_active_workspace()
+            .map(|workspace| {
+                workspace
+                    .repositories
+                    .iter()
+                    .map(|r| r.path.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let services: Vec<(PathBuf, bool)> = Workspace::detect_monorepo_services(&repo_path)
+            .into_iter()
+            .filter(|service| !known.contains(service))
+            .map(|service| (service, true))
+            .collect();
+
+        if services.is_empty() {
+            self.logger
+                .info(self.localizer.tf("no_monorepo_services", &[&repo_name]));
+            return;
+        }
+        self.monorepo_services = Some(MonorepoServicesView {
+            repo_path,
+            repo_name,
+            services,
+        });
+    }
+
+    pub fn open_ignored_files(&mut self, repo_path: PathBuf, repo_name: String) {
+        match git_show_ignored(&repo_path) {
+            Ok(files) => {
+                self.ignored_files = Some(IgnoredFilesView { repo_name, files });
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("ignored_files_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    pub fn open_contributors(&mut self, repo_path: PathBuf, repo_name: String) {
+        let days = CONTRIBUTOR_RANGES[0];
+        self.request_contributors(&repo_path, days);
+        self.contributors = Some(ContributorsView {
+            repo_path,
+            repo_name,
+            days,
+        });
+    }
+
+    pub fn set_contributors_range(&mut self, days: u32) {
+        let Some(view) = &mut self.contributors else {
+            return;
+        };
+        view.days = days;
+        let repo_path = view.repo_path.clone();
+        self.request_contributors(&repo_path, days);
+    }
+
+    /// Loads the shortlog unless it is cached.
+    fn request_contributors(&mut self, repo_path: &Path, days: u32) {
+        let since = contributor_since(days);
+        if self
+            .contributor_cache
+            .contains_key(&(repo_path.to_path_buf(), since.clone()))
+        {
+            return;
+        }
+        if let Some(tx) = &self.app_sender {
+            git_shortlog_async::<AppMessage>(repo_path.to_path_buf(), since, tx.clone());
+        }
+    }
+
+    pub fn open_env_overrides(&mut self, repo_path: PathBuf, repo_name: String) {
+        let rows = self
+            .config
+            .workspaces
+            .iter()
+            .flat_map(|workspace| workspace.repositories.iter())
+            .find(|repo| repo.path == repo_path)
+            .map(|repo| repo.env_overrides.clone())
+            .unwrap_or_default();
+        self.env_overrides = Some(EnvOverridesView {
+            repo_path,
+            repo_name,
+            rows,
+        });
+    }
+
+    /// Stores the edited overrides on every entry of the repository and
+    /// applies them to the commands spawned from now on.
+    pub fn save_env_overrides(&mut self) {
+        let Some(view) = self.env_overrides.take() else {
+            return;
+        };
+        let overrides: Vec<(String, String)> = view
+            .rows
+            .into_iter()
+            .map(|(name, value)| (name.trim().to_string(), value))
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+
+        for workspace in &mut self.config.workspaces {
+            for repo in &mut workspace.repositories {
+                if repo.path == view.repo_path {
+                    repo.env_overrides = overrides.clone();
+                }
+            }
+        }
+        set_repo_env_overrides(&view.repo_path, overrides.clone());
+        self.save_config();
+
+        if overrides.is_empty() {
+            self.logger.info(
+                self.localizer
+                    .tf("env_overrides_cleared", &[&view.repo_name]),
+            );
+        } else {
+            self.logger.info(self.localizer.tf(
+                "env_overrides_saved",
+                &[&view.repo_name, &describe_env_overrides(&overrides)],
+            ));
+        }
+    }
+
+    /// Adds the ticked services to the active workspace.
+    pub fn add_monorepo_services(&mut self) {
+        let Some(view) = self.monorepo_services.take() else {
+            return;
+        };
+
+        let mut added = Vec::new();
+        if let Some(workspace) = self.get_active_workspace_mut() {
+            for (service, checked) in view.services {
+                if checked && workspace.add_monorepo_service(&view.repo_path, service.clone()) {
+                    added.push(service);
+                }
+            }
+        }
+        if added.is_empty() {
+            return;
+        }
+
+        self.refresh_repo_duplicates();
+        self.save_config();
+        self.logger.info(self.localizer.tf(
+            "monorepo_services_added",
+            &[&added.len().to_string(), &view.repo_name],
+        ));
+        if let Some(tx) = &self.app_sender {
+            for service in added {
+                refresh_repo_status_async::<AppMessage>(service, tx.clone());
+            }
+        }
+    }
+
+    /// Applies the name typed in the remotes window. Invalid names keep the
+    /// field open so they can be corrected.
+    pub fn confirm_remote_rename(&mut self) {
+        let Some(view) = &mut self.remotes else {
+            return;
+        };
+        let Some((index, new_name)) = view.editing.take() else {
+            return;
+        };
+        let Some(old_name) = view.remotes.get(index).map(|r| r.name.clone()) else {
+            return;
+        };
+        let new_name = new_name.trim().to_string();
+        if new_name == old_name {
+            return;
+        }
+        if !is_valid_remote_name(&new_name) {
+            self.logger
+                .error(self.localizer.tf("invalid_remote_name", &[&new_name]));
+            view.editing = Some((index, new_name));
+            return;
+        }
+
+        let repo_path = view.repo_path.clone();
+        let repo_name = view.repo_name.clone();
+        match git_rename_remote(&repo_path, &old_name, &new_name) {
+            Ok(()) => {
+                self.logger.info(
+                    self.localizer
+                        .tf("remote_renamed", &[&old_name, &new_name, &repo_name]),
+                );
+                self.open_remotes_window(repo_path.clone(), repo_name);
+                // Ветки отслеживания переименованы git, список веток нужно перечитать
+                if let Some(tx) = &self.app_sender {
+                    refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                }
+            }
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("remotes_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    pub fn open_patch_dialog(&mut self, repo_path: PathBuf, repo_name: String) {
+        self.patch_dialog = Some(PatchDialog {
+            am_stopped: git_am_in_progress(&repo_path),
+            repo_path,
+            repo_name,
+            patch_path: String::new(),
+            create_commits: false,
+        });
+    }
+
+    pub fn apply_patch(&mut self) {
+        let Some(dialog) = &mut self.patch_dialog else {
+            return;
+        };
+        let patch_path = PathBuf::from(dialog.patch_path.trim());
+
+        match git_apply(&dialog.repo_path, &patch_path, dialog.create_commits) {
+            Ok(_) => {
+                self.logger.info(self.localizer.tf(
+                    "patch_applied",
+                    &[&patch_path.display().to_string(), &dialog.repo_name],
+                ));
+                self.finish_patch_dialog();
+            }
+            // Обычный apply отказал — пробуем слияние с индексом как базой
+            Err(e) if !dialog.create_commits => {
+                self.logger.warning(
+                    self.localizer
+                        .tf("patch_retry_3way", &[&dialog.repo_name, &e.to_string()]),
+                );
+                self.apply_patch_3way(patch_path);
+            }
+            Err(e) => {
+                self.logger.error(
+                    self.localizer
+                        .tf("patch_error", &[&dialog.repo_name, &e.to_string()]),
+                );
+                dialog.am_stopped = dialog.create_commits && git_am_in_progress(&dialog.repo_path);
+            }
+        }
+    }
+
+    fn apply_patch_3way(&mut self, patch_path: PathBuf) {
+        let Some(dialog) = &self.patch_dialog else {
+            return;
+        };
+        let repo_path = dialog.repo_path.clone();
+        let repo_name = dialog.repo_name.clone();
+
+        match git_apply_3way(&repo_path, &patch_path) {
+            Ok(ApplyResult::Success) => {
+                self.logger.info(self.localizer.tf(
+                    "patch_applied",
+                    &[&patch_path.display().to_string(), &repo_name],
+                ));
+                self.finish_patch_dialog();
+            }
+            Ok(ApplyResult::ConflictsCreated { conflicted_files }) => {
+                self.logger.warning(self.localizer.tf(
+                    "patch_applied_with_conflicts",
+                    &[&conflicted_files.len().to_string()],
+                ));
+                self.logger.warning(self.localizer.tf(
+                    "patch_conflicted_files",
+                    &[&repo_name, &conflicted_files.join(", ")],
+                ));
+                self.conflict_panels.insert(
+                    repo_path.clone(),
+                    ConflictPanel::new(repo_path, repo_name, conflicted_files),
+                );
+                self.finish_patch_dialog();
+            }
+            Ok(ApplyResult::Failed(stderr)) => self
+                .logger
+                .error(self.localizer.tf("patch_error", &[&repo_name, &stderr])),
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("patch_error", &[&repo_name, &e.to_string()]),
+            ),
+        }
+    }
+
+    /// `git am --continue` / `--abort` after a stopped `git am`.
+    pub fn resolve_patch_am(&mut self, resume: bool) {
+        let Some(dialog) = &mut self.patch_dialog else {
+            return;
+        };
+
+        match git_am_resolve(&dialog.repo_path, resume) {
+            Ok(_) => {
+                let key = if resume { "am_continued" } else { "am_aborted" };
+                self.logger
+                    .info(self.localizer.tf(key, &[&dialog.repo_name]));
+                self.finish_patch_dialog();
+            }
+            Err(e) => {
+                self.logger.error(
+                    self.localizer
+                        .tf("patch_error", &[&dialog.repo_name, &e.to_string()]),
+                );
+                dialog.am_stopped = git_am_in_progress(&dialog.repo_path);
+            }
+        }
+    }
+
+    fn finish_patch_dialog(&mut self) {
+        let Some(dialog) = self.patch_dialog.take() else {
+            return;
+        };
+        if let Some(tx) = &self.app_sender {
+            refresh_repo_status_async::<AppMessage>(dialog.repo_path, tx.clone());
+        }
+    }
+
+    pub fn open_archive_dialog(&mut self, workspace_idx: Option<usize>) {
+        let file_name = match workspace_idx.and_then(|idx| self.config.workspaces.get(idx)) {
+            Some(workspace) => format!("{}.workspace.json", workspace.name),
+            None => String::new(),
+        };
+        self.archive_dialog = Some(ArchiveDialog {
+            workspace_idx,
+            path: home_dir()
+                .unwrap_or_default()
+                .join(file_name)
+                .display()
+                .to_string(),
+        });
+    }
+
+    /// Archives or restores according to the open dialog. On failure the
+    /// dialog stays open with the path for another try.
+    pub fn apply_archive_dialog(&mut self) {
+        let Some(dialog) = &self.archive_dialog else {
+            return;
+        };
+        let path = PathBuf::from(dialog.path.trim());
+
+        let result = match dialog.workspace_idx {
+            Some(idx) => self.archive_workspace(idx, &path),
+            None => self.restore_workspace(&path),
+        };
+        match result {
+            Ok(()) => self.archive_dialog = None,
+            Err(e) => self.logger.error(self.localizer.tf(
+                "workspace_archive_error",
+                &[&path.display().to_string(), &e.to_string()],
+            )),
+        }
+    }
+
+    fn archive_workspace(
+        &mut self,
+        idx: usize,
+        path: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(workspace) = self.config.workspaces.get(idx) else {
+            return Ok(());
+        };
+        workspace.archive_to_file(path)?;
+        self.logger.info(self.localizer.tf(
+            "workspace_archived",
+            &[&workspace.name, &path.display().to_string()],
+        ));
+
+        self.config.workspaces.remove(idx);
+        self.refresh_repo_duplicates();
+        self.save_config();
+        Ok(())
+    }
+
+    fn restore_workspace(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let workspace = Workspace::restore_from_archive(path)?;
+        // Архив уже восстановлен — не создаём дубликат
+        if let Some((index, _)) = Workspace::find_by_name(&self.config.workspaces, &workspace.name)
+        {
+            self.logger.info(
+                self.localizer
+                    .tf("workspace_already_restored", &[&workspace.name]),
+            );
+            self.switch_to_workspace(index);
+            return Ok(());
+        }
+
+        self.logger.info(self.localizer.tf(
+            "workspace_restored",
+            &[&workspace.name, &workspace.repository_count().to_string()],
+        ));
+
+        self.config.workspaces.push(workspace);
+        self.refresh_repo_duplicates();
+        self.save_config();
+        self.switch_to_workspace(self.config.workspaces.len() - 1);
+        Ok(())
+    }
+
+    pub fn apply_repath(&mut self) {
+        let Some(dialog) = self.repath_dialog.take() else {
+            return;
+        };
+        let new_prefix = PathBuf::from(dialog.new_prefix.trim());
+
+        let (found, absent) = verify_repath(
+            &self.all_repo_paths(),
+            &dialog.old_prefix,
+            &new_prefix,
+            |path| path.exists(),
+        );
+
+        if found.is_empty() {
+            self.logger.error(
+                self.localizer
+                    .tf("repath_nothing_found", &[&new_prefix.display().to_string()]),
+            );
+            return;
+        }
+
+        for path in &absent {
+            self.logger.warning(
+                self.localizer
+                    .tf("repath_repo_not_found", &[&path.display().to_string()]),
+            );
+        }
+
+        let updated =
+            rewrite_repo_paths(&mut self.config.workspaces, &dialog.old_prefix, &new_prefix);
+        self.logger
+            .info(self.localizer.tf("repath_updated", &[&updated.to_string()]));
+
+        for workspace in &mut self.config.workspaces {
+            workspace.mark_as_unloaded();
+        }
+        self.load_workspace(self.active_workspace_idx());
+        self.refresh_missing_prefixes();
+        self.refresh_repo_duplicates();
+        self.save_config();
+    }
+
+    pub fn export_settings(&mut self) {
+        let path = PathBuf::from(&self.settings_transfer_path);
+        match export_settings(&self.config, &path, home_dir().as_deref()) {
+            Ok(_) => self.logger.info(
+                self.localizer
+                    .tf("settings_exported", &[&path.display().to_string()]),
+            ),
+            Err(e) => self.logger.error(
+                self.localizer
+                    .tf("settings_export_error", &[&e.to_string()]),
+            ),
+        }
+    }
+
+    pub fn preview_settings_import(&mut self) {
+        let path = PathBuf::from(&self.settings_transfer_path);
+        match read_settings_export(&path, home_dir().as_deref()) {
+            Ok(imported) => {
+                self.import_preview = Some(preview_import(&self.config, &imported));
+            }
+            Err(e) => {
+                self.import_preview = None;
+                self.logger.error(
+                    self.localizer
+                        .tf("settings_import_error", &[&e.to_string()]),
+                );
+            }
+        }
+    }
+
+    pub fn apply_settings_import(&mut self) {
+        let Some(preview) = self.import_preview.take() else {
+            return;
+        };
+
+        match ConfigManager::backup_config_file() {
+            Ok(Some(backup)) => self.logger.info(
+                self.localizer
+                    .tf("config_backup_created", &[&backup.display().to_string()]),
+            ),
+            Ok(None) => {}
+            Err(e) => {
+                self.logger.error(
+                    self.localizer
+                        .tf("settings_import_error", &[&e.to_string()]),
+                );
+                return;
+            }
+        }
+
+        self.config = preview.merged;
+        self.localizer.set_language(&self.config.language);
+        for workspace in &mut self.config.workspaces {
+            workspace.mark_as_unloaded();
+            for repo in &mut workspace.repositories {
+                repo.detect_symlink();
+            }
+        }
+        self.refresh_repo_duplicates();
+        // Identity импортированных workspace действует сразу
+        self.apply_git_command_settings();
+        self.load_workspace(self.active_workspace_idx());
+        self.save_config();
+
+        self.logger.info(self.localizer.tf(
+            "settings_imported",
+            &[
+                &preview.workspaces_added.to_string(),
+                &preview.settings_overwritten.to_string(),
+            ],
+        ));
+    }
+
+    pub fn setup_git_communication(&mut self) {
+        let (tx, rx) = crossbeam_channel::unbounded::<AppMessage>();
+        self.app_sender = Some(tx);
+        self.app_receiver = Some(rx);
+    }
+
+    pub fn refresh_all_repos(&self) {
+        if let Some(tx) = &self.app_sender {
+            if let Some(workspace) = self.get_active_workspace() {
+                for repo in &workspace.repositories {
+                    if repo.git_dir_check.is_valid() {
+                        refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn load_workspace(&mut self, workspace_idx: usize) {
+        if workspace_idx >= self.config.workspaces.len() {
+            self.logger.info(format!(
+                "Load workspace {} failed: index out of bounds",
+                workspace_idx
+            ));
+            return;
+        }
+
+        let workspace = &mut self.config.workspaces[workspace_idx];
+        if workspace.is_loaded {
+            self.logger
+                .info(format!("Workspace '{}' already loaded", workspace.name));
+            return;
+        }
+
+        let repo_count = workspace.repositories.len();
+        self.logger.info(format!(
+            "Loading workspace '{}' with {} repositories",
+            workspace.name, repo_count
+        ));
+
+        if let Some(tx) = &self.app_sender {
+            let valid: Vec<&RepositoryState> = workspace
+                .repositories
+                .iter()
+                .filter(|repo| repo.git_dir_check.is_valid())
+                .collect();
+            self.pending_git_loads += valid.len();
+
+            for repo in valid {
+                self.logger.info(format!(
+                    "Starting async load for repo: {}",
+                    repo.path.display()
+                ));
+                refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+            }
+        } else {
+            self.logger
+                .info("No app_sender available for loading repositories");
+        }
+
+        workspace.mark_as_loaded();
+        self.logger
+            .info(format!("Workspace '{}' marked as loaded", workspace.name));
+    }
+
+    /// Logs every entry whose folder exists but is not a usable checkout;
+    /// missing folders are left to the relocation prompt.
+    fn report_invalid_repositories(&mut self) {
+        let invalid: Vec<(PathBuf, String)> = self
+            .config
+            .workspaces
+            .iter()
+            .flat_map(|workspace| &workspace.repositories)
+            .filter(|repo| repo.git_dir_check.is_invalid())
+            .map(|repo| (repo.path.clone(), self.git_dir_tooltip(&repo.git_dir_check)))
+            .collect();
+        for (path, reason) in invalid {
+            let message = self.localizer.tf(
+                "repo_invalid_git_dir",
+                &[&path.display().to_string(), &reason],
+            );
+            self.logger.warning_for(message, &path);
+        }
+    }
+
+    pub fn git_dir_tooltip(&self, check: &GitDirCheck) -> String {
+        let target = match check {
+            GitDirCheck::BrokenGitdir(gitdir) => gitdir.display().to_string(),
+            _ => String::new(),
+        };
+        self.localizer.tf(check.tooltip_key(), &[&target])
+    }
+
+    pub fn refresh_all_loaded_repos(&mut self) {
+        self.start_prioritized_load();
+    }
+
+    /// Loads the active workspace first; the other workspaces are queued
+    /// behind it and only start once every active repository has reported.
+    pub fn start_prioritized_load(&mut self) {
+        let mut active = Vec::new();
+        let mut background = Vec::new();
+        let active_idx = self.active_workspace_idx();
+        for (idx, workspace) in self.config.workspaces.iter_mut().enumerate() {
+            let target = if idx == active_idx {
+                &mut active
+            } else {
+                &mut background
+            };
+            // Исправленный вручную репозиторий снова загружается
+            for repo in &mut workspace.repositories {
+                repo.validate_git_dir();
+            }
+            target.extend(
+                workspace
+                    .repositories
+                    .iter()
+                    .filter(|r| r.git_dir_check.is_valid())
+                    .map(|r| r.path.clone()),
+            );
+        }
+
+        let batch = self.load_scheduler.start(active, background);
+        if let Some(workspace) = self.get_active_workspace_mut() {
+            workspace.mark_as_loaded();
+        }
+        self.dispatch_load_batch(batch);
+        self.update_load_progress_status(false);
+    }
+
+    fn dispatch_load_batch(&mut self, batch: Vec<PathBuf>) {
+        if batch.is_empty() {
+            return;
+        }
+
+        if self.load_scheduler.phase() == LoadPhase::Background {
+            self.logger.info(format!(
+                "Active workspace loaded, starting background load of {} repositories",
+                batch.len()
+            ));
+            for workspace in &mut self.config.workspaces {
+                workspace.mark_as_loaded();
+            }
+        }
+
+        if let Some(tx) = &self.app_sender {
+            self.pending_git_loads += batch.len();
+            for repo_path in batch {
+                refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+            }
+        }
+    }
+
+    /// Feeds a git message to the load scheduler; starts the background
+    /// phase once the active workspace is done.
+    pub fn advance_load_scheduler(&mut self, msg: &GitMessage) {
+        if !self.load_scheduler.is_running() {
+            return;
+        }
+
+        let batch = self.load_scheduler.on_message(msg);
+        self.dispatch_load_batch(batch);
+        self.update_load_progress_status(matches!(msg, GitMessage::Error(_)));
+    }
+
+    fn update_load_progress_status(&mut self, last_was_error: bool) {
+        if self.load_scheduler.is_running() {
+            let (loaded, total, background) = self.load_scheduler.progress();
+            self.search_status = Some(self.localizer.tf(
+                "startup_progress",
+                &[
+                    &loaded.to_string(),
+                    &total.to_string(),
+                    &background.to_string(),
+                ],
+            ));
+        } else if last_was_error {
+            self.search_status = Some(self.localizer.t("loading_complete_errors"));
+        } else {
+            self.search_status = Some(self.localizer.t("all_repos_loaded"));
+        }
+        self.search_status_timer = Some(std::time::Instant::now());
+    }
+
+    /// Bookmarks or unbookmarks a repository, whatever workspace holds it.
+    pub fn toggle_bookmark(&mut self, repo_path: &Path) {
+        if !self.config.bookmarks.remove(repo_path) {
+            self.config.bookmarks.insert(repo_path.to_path_buf());
+        }
+        self.save_config();
+    }
+
+    /// Bookmarked repositories by name, with the state of the first
+    /// workspace holding them; bookmarks outside every workspace get a bare
+    /// entry.
+    pub fn bookmarked_repositories(&self) -> Vec<RepositoryState> {
+        let mut repos: Vec<RepositoryState> = self
+            .config
+            .bookmarks
+            .iter()
+            .map(|path| {
+                self.config
+                    .workspaces
+                    .iter()
+                    .flat_map(|workspace| workspace.repositories.iter())
+                    .find(|repo| &repo.path == path)
+                    .cloned()
+                    .unwrap_or_else(|| RepositoryState::new(path.clone()))
+            })
+            .collect();
+        repos.sort_by_key(|repo| repo.name.to_lowercase());
+        repos
+    }
+
+    /// Repositories of the "Recent" view. Every workspace gets loaded so that
+    /// activity times are known.
+    pub fn recent_repositories(&self) -> Vec<RepositoryState> {
+        let window = std::time::Duration::from_secs(self.config.recent_days as u64 * 24 * 60 * 60);
+        recent_repos(&self.config, window)
+            .into_iter()
+            .filter_map(|(workspace_idx, repo_idx)| {
+                self.config
+                    .workspaces
+                    .get(workspace_idx)?
+                    .repositories
+                    .get(repo_idx)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    pub fn show_recent_workspace(&mut self) {
+        self.show_recent = true;
+        for idx in 0..self.config.workspaces.len() {
+            if !self.config.workspaces[idx].is_loaded {
+                self.load_workspace(idx);
+            }
+        }
+    }
+
+    pub fn open_audit_history(&mut self) {
+        self.audit_history = Some(AuditHistoryView {
+            entries: audit_file().read_all(),
+            repo_filter: None,
+            operation_filter: None,
+            export_path: home_dir()
+                .unwrap_or_default()
+                .join("repo-manager-audit.jsonl")
+                .display()
+                .to_string(),
+        });
+    }
+
+    /// Writes the filtered history as JSON lines to the export path.
+    pub fn export_audit_history(&mut self) {
+        let Some(view) = &self.audit_history else {
+            return;
+        };
+        let lines: Vec<String> = view
+            .filtered()
+            .into_iter()
+            .rev()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .collect();
+        let path = PathBuf::from(&view.export_path);
+
+        match std::fs::write(&path, lines.join("\n") + "\n") {
+            Ok(_) => self.logger.info(self.localizer.tf(
+                "audit_export_success",
+                &[&lines.len().to_string(), &path.display().to_string()],
+            )),
+            Err(e) => self.logger.error(self.localizer.tf(
+                "audit_export_error",
+                &[&path.display().to_string(), &e.to_string()],
+            )),
+        }
+    }
+
+    /// Starts a shell in `cwd` in the terminal pane, replacing a running one.
+    pub fn open_terminal(&mut self, cwd: &Path, ctx: &egui::Context) {
+        self.terminal_pane = None;
+        match Terminal::spawn(cwd, ctx.clone()) {
+            Ok(terminal) => self.terminal_pane = Some(terminal),
+            Err(e) => self.logger.error(self.localizer.tf(
+                "terminal_error",
+                &[&cwd.display().to_string(), &e.to_string()],
+            )),
+        }
+    }
+
+    /// Toolbar toggle: closes the pane or opens it in the workspace root.
+    pub fn toggle_terminal(&mut self, ctx: &egui::Context) {
+        if self.terminal_pane.take().is_some() {
+            return;
+        }
+        let cwd = self
+            .get_active_workspace()
+            .filter(|_| !self.show_recent)
+            .and_then(|workspace| workspace.root_path())
+            .or_else(home_dir)
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.open_terminal(&cwd, ctx);
+    }
+
+    /// Opens the working tree changes (or HEAD against upstream) in the
+    /// configured external diff tool.
+    pub fn open_difftool(&mut self, repo_path: &Path, repo_name: &str, against_upstream: bool) {
+        let Some(command) =
+            difftool_command(&self.config.difftool_command, repo_path, against_upstream)
+        else {
+            self.logger
+                .error(self.localizer.tf("difftool_not_configured", &[repo_name]));
+            return;
+        };
+
+        if let Err(e) = command.spawn_detached(repo_path) {
+            self.logger.error(self.localizer.tf(
+                "difftool_error",
+                &[repo_name, &command.display(), &e.to_string()],
+            ));
+        }
+    }
+
+    /// Stamps every workspace containing `repo_path` as just modified. The
+    /// config is written later by `ConfigSaver`, not once per operation.
+    pub fn mark_workspace_modified(&mut self, repo_path: &Path) {
+        let now = std::time::SystemTime::now();
+        let mut changed = false;
+        for workspace in &mut self.config.workspaces {
+            if workspace.repositories.iter().any(|r| r.path == *repo_path) {
+                workspace.last_modified = Some(now);
+                changed = true;
+            }
+        }
+        if changed {
+            self.mark_config_dirty();
+        }
+    }
+
+    /// Reads the description again when its files changed since it was
+    /// cached. Their mtimes are compared at most once a minute per
+    /// repository, however often the status updates.
+    pub fn refresh_description(&mut self, repo_path: &Path) {
+        // Статус приходит после каждой операции, а описание меняется редко
+        let now = std::time::Instant::now();
+        if self
+            .description_checked_at
+            .get(repo_path)
+            .is_some_and(|checked| now.duration_since(*checked) < DESCRIPTION_RECHECK_INTERVAL)
+        {
+            return;
+        }
+        self.description_checked_at
+            .insert(repo_path.to_path_buf(), now);
+
+        let Some(git_root) = self.config.workspaces.iter().find_map(|workspace| {
+            workspace
+                .repositories
+                .iter()
+                .find(|repo| repo.path == repo_path)
+                .map(|repo| repo.infer_project_root().to_path_buf())
+        }) else {
+            return;
+        };
+
+        let sources = description_sources(repo_path, &git_root);
+        let mtimes = description_mtimes(&sources);
+        if matches!(self.status_cache.descriptions.get(repo_path), Some(cached) if cached.mtimes == mtimes)
+        {
+            return;
+        }
+
+        let description = read_description(&sources);
+        for workspace in &mut self.config.workspaces {
+            for repo in &mut workspace.repositories {
+                if repo.path == repo_path {
+                    repo.description = description.clone();
+                }
+            }
+        }
+        self.status_cache.descriptions.insert(
+            repo_path.to_path_buf(),
+            CachedDescription {
+                description,
+                mtimes,
+            },
+        );
+        if let Err(e) = self.status_cache.save() {
+            self.logger
+                .error(format!("Failed to save status cache: {}", e));
+        }
+    }
+
+    /// Stamps a finished fetch on every entry of `repo_path` and in the
+    /// status cache.
+    pub fn record_fetch(&mut self, repo_path: &Path) {
+        let now = std::time::SystemTime::now();
+        for workspace in &mut self.config.workspaces {
+            for repo in &mut workspace.repositories {
+                if repo.path == repo_path {
+                    repo.last_fetch = Some(now);
+                }
+            }
+        }
+        self.status_cache
+            .last_fetch
+            .insert(repo_path.to_path_buf(), now);
+        if let Err(e) = self.status_cache.save() {
+            self.logger
+                .error(format!("Failed to save status cache: {}", e));
+        }
+    }
+
+    /// Position of the active workspace; the first one when the active
+    /// workspace was removed.
+    pub fn active_workspace_idx(&self) -> usize {
+        self.active_workspace_id
+            .and_then(|id| workspace_index(&self.config.workspaces, id))
+            .unwrap_or(0)
+    }
+
+    /// Quick filter chips of the active workspace.
+    pub fn active_quick_filters(&self) -> HashSet<RepoPredicate> {
+        self.active_workspace_id
+            .map(|id| self.config.ui_state.quick_filters(id))
+            .unwrap_or_default()
+    }
+
+    pub fn toggle_quick_filter(&mut self, chip: RepoPredicate) {
+        let Some(id) = self.active_workspace_id else {
+            return;
+        };
+        let mut chips = self.config.ui_state.quick_filters(id);
+        if !chips.remove(&chip) {
+            chips.insert(chip);
+        }
+        self.config.ui_state.set_quick_filters(id, &chips);
+        self.mark_config_dirty();
+    }
+
+    pub fn set_active_workspace(&mut self, workspace_idx: usize) {
+        self.active_workspace_id = self.config.workspaces.get(workspace_idx).map(|w| w.id);
+    }
+
     pub fn get_active_workspace(&self) -> Option<&Workspace> {
-        self.config.workspaces.get(self.active_workspace_idx)
+        self.config.workspaces.get(self.active_workspace_idx())
     }
 
     pub fn get_active_workspace_mut(&mut self) -> Option<&mut Workspace> {
-        self.config.workspaces.get_mut(self.active_workspace_idx)
+        let idx = self.active_workspace_idx();
+        self.config.workspaces.get_mut(idx)
     }
 }
@@ -0,0 +1,154 @@
+use std::time::{Duration, Instant};
+
+/// Repaint heartbeat while in low-power mode; still drains the message
+/// channel so finished operations are not lost.
+pub const LOW_POWER_REPAINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// An unfocused but visible window stays in normal mode this long, so
+/// glancing at another window does not pause anything.
+pub const UNFOCUSED_GRACE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerTransition {
+    None,
+    Entered,
+    /// `catch_up`: the window was hidden for longer than the catch-up
+    /// interval and the active workspace should be refreshed once.
+    Exited {
+        catch_up: bool,
+    },
+}
+
+/// Switches between normal and low-power mode from the window's focus and
+/// minimized state.
+pub struct PowerState {
+    low_power_since: Option<Instant>,
+    last_focused: Instant,
+    catch_up_after: Duration,
+}
+
+impl PowerState {
+    pub fn new(catch_up_after: Duration, now: Instant) -> Self {
+        Self {
+            low_power_since: None,
+            last_focused: now,
+            catch_up_after,
+        }
+    }
+
+    pub fn is_low_power(&self) -> bool {
+        self.low_power_since.is_some()
+    }
+
+    pub fn set_catch_up_after(&mut self, catch_up_after: Duration) {
+        self.catch_up_after = catch_up_after;
+    }
+
+    /// Minimized windows go low-power at once, unfocused ones after
+    /// `UNFOCUSED_GRACE`.
+    pub fn wants_low_power(&self, focused: bool, minimized: bool, now: Instant) -> bool {
+        minimized || (!focused && now.duration_since(self.last_focused) >= UNFOCUSED_GRACE)
+    }
+
+    pub fn enter_low_power(&mut self, now: Instant) {
+        if self.low_power_since.is_none() {
+            self.low_power_since = Some(now);
+        }
+    }
+
+    /// Back to normal; `true` when the window was away long enough to
+    /// warrant a catch-up refresh.
+    pub fn exit_low_power(&mut self, now: Instant) -> bool {
+        self.last_focused = now;
+        self.low_power_since
+            .take()
+            .is_some_and(|since| now.duration_since(since) >= self.catch_up_after)
+    }
+
+    /// Called once per frame with the current viewport state.
+    pub fn update(&mut self, focused: bool, minimized: bool, now: Instant) -> PowerTransition {
+        let wants_low_power = self.wants_low_power(focused, minimized, now);
+        if focused && !minimized && !self.is_low_power() {
+            self.last_focused = now;
+        }
+
+        match (self.is_low_power(), wants_low_power) {
+            (false, true) => {
+                self.enter_low_power(now);
+                PowerTransition::Entered
+            }
+            (true, false) => PowerTransition::Exited {
+                catch_up: self.exit_low_power(now),
+            },
+            _ => PowerTransition::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn focused_window_stays_in_normal_mode() {
+        let start = Instant::now();
+        let mut power = PowerState::new(Duration::from_secs(15 * 60), start);
+
+        assert_eq!(power.update(true, false, start), PowerTransition::None);
+        assert_eq!(
+            power.update(true, false, start + HOUR),
+            PowerTransition::None
+        );
+        assert!(!power.is_low_power());
+    }
+
+    #[test]
+    fn unfocused_window_waits_for_the_grace_period() {
+        let start = Instant::now();
+        let mut power = PowerState::new(Duration::from_secs(15 * 60), start);
+
+        assert_eq!(
+            power.update(false, false, start + Duration::from_secs(5)),
+            PowerTransition::None
+        );
+        assert_eq!(
+            power.update(false, false, start + UNFOCUSED_GRACE),
+            PowerTransition::Entered
+        );
+        assert!(power.is_low_power());
+    }
+
+    #[test]
+    fn hidden_for_an_hour_triggers_one_catch_up_refresh() {
+        let start = Instant::now();
+        let mut power = PowerState::new(Duration::from_secs(15 * 60), start);
+
+        assert_eq!(power.update(true, true, start), PowerTransition::Entered);
+        assert_eq!(
+            power.update(true, true, start + HOUR / 2),
+            PowerTransition::None
+        );
+        assert_eq!(
+            power.update(true, false, start + HOUR),
+            PowerTransition::Exited { catch_up: true }
+        );
+        assert_eq!(
+            power.update(true, false, start + HOUR),
+            PowerTransition::None
+        );
+    }
+
+    #[test]
+    fn short_minimize_needs_no_catch_up() {
+        let start = Instant::now();
+        let mut power = PowerState::new(Duration::from_secs(15 * 60), start);
+
+        power.update(false, true, start);
+        assert_eq!(
+            power.update(true, false, start + Duration::from_secs(30)),
+            PowerTransition::Exited { catch_up: false }
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use super::{AttentionFilter, RepoErrors};
+use crate::workspace::RepositoryState;
+use std::collections::HashSet;
+
+/// Quick filter chip under the search box. Active chips are combined with
+/// each other and with the search text, all of which must match.
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+pub enum RepoPredicate {
+    Behind,
+    Ahead,
+    Dirty,
+    Errors,
+    /// On a branch other than the one `origin/HEAD` (or `main`/`master`)
+    /// names; repositories without a known default branch never match.
+    OffDefaultBranch,
+    /// On a branch that tracks nothing.
+    NoUpstream,
+}
+
+impl RepoPredicate {
+    pub const ALL: [RepoPredicate; 6] = [
+        RepoPredicate::Behind,
+        RepoPredicate::Ahead,
+        RepoPredicate::Dirty,
+        RepoPredicate::Errors,
+        RepoPredicate::OffDefaultBranch,
+        RepoPredicate::NoUpstream,
+    ];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            RepoPredicate::Behind => "chip_behind",
+            RepoPredicate::Ahead => "chip_ahead",
+            RepoPredicate::Dirty => "chip_dirty",
+            RepoPredicate::Errors => "chip_errors",
+            RepoPredicate::OffDefaultBranch => "chip_off_default_branch",
+            RepoPredicate::NoUpstream => "chip_no_upstream",
+        }
+    }
+
+    pub fn matches(self, repo: &RepositoryState, errors: &RepoErrors) -> bool {
+        let info = &repo.git_info;
+        match self {
+            RepoPredicate::Behind => info.behind > 0,
+            RepoPredicate::Ahead => info.ahead > 0,
+            RepoPredicate::Dirty => info.has_changes,
+            RepoPredicate::Errors => errors.contains(&repo.path),
+            RepoPredicate::OffDefaultBranch => matches!(
+                (&info.current_branch, &info.default_branch),
+                (Some(current), Some(default)) if current != default
+            ),
+            RepoPredicate::NoUpstream => info.current_branch.is_some() && !info.has_upstream,
+        }
+    }
+
+    /// Repositories of `repos` the chip alone would keep, for its label.
+    pub fn count(self, repos: &[RepositoryState], errors: &RepoErrors) -> usize {
+        repos
+            .iter()
+            .filter(|repo| self.matches(repo, errors))
+            .count()
+    }
+}
+
+/// Case-insensitive search over name, description and path, narrowed by
+/// every active chip.
+pub fn repo_matches(
+    repo: &RepositoryState,
+    query: &str,
+    chips: &HashSet<RepoPredicate>,
+    errors: &RepoErrors,
+) -> bool {
+    let matches_search = if query.is_empty() {
+        true
+    } else {
+        let query_lower = query.to_lowercase();
+        repo.name.to_lowercase().contains(&query_lower)
+            || repo
+                .description
+                .as_ref()
+                .is_some_and(|description| description.to_lowercase().contains(&query_lower))
+            || repo
+                .path
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&query_lower)
+    };
+
+    matches_search && chips.iter().all(|chip| chip.matches(repo, errors))
+}
+
+/// Everything that narrows the repository list, shared by the tree and
+/// the flat list.
+#[derive(Debug, Clone, Copy)]
+pub struct RepoFilter<'a> {
+    pub query: &'a str,
+    pub attention: AttentionFilter,
+    pub chips: &'a HashSet<RepoPredicate>,
+}
+
+impl<'a> RepoFilter<'a> {
+    pub fn new(
+        query: &'a str,
+        attention: AttentionFilter,
+        chips: &'a HashSet<RepoPredicate>,
+    ) -> Self {
+        Self {
+            query,
+            attention,
+            chips,
+        }
+    }
+
+    pub fn matches(&self, repo: &RepositoryState, errors: &RepoErrors) -> bool {
+        repo_matches(repo, self.query, self.chips, errors) && self.attention.matches(repo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn repo(path: &str) -> RepositoryState {
+        let mut repo = RepositoryState {
+            path: PathBuf::from(path),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            ..RepositoryState::default()
+        };
+        repo.git_info.current_branch = Some("main".to_string());
+        repo.git_info.default_branch = Some("main".to_string());
+        repo.git_info.has_upstream = true;
+        repo
+    }
+
+    fn chips(chips: &[RepoPredicate]) -> HashSet<RepoPredicate> {
+        chips.iter().copied().collect()
+    }
+
+    fn visible(
+        repos: &[RepositoryState],
+        query: &str,
+        active: &[RepoPredicate],
+        errors: &RepoErrors,
+    ) -> Vec<String> {
+        repos
+            .iter()
+            .filter(|repo| repo_matches(repo, query, &chips(active), errors))
+            .map(|repo| repo.name.clone())
+            .collect()
+    }
+
+    fn sample() -> (Vec<RepositoryState>, RepoErrors) {
+        let mut api = repo("/src/api");
+        api.git_info.behind = 3;
+        api.git_info.has_changes = true;
+        let mut web = repo("/src/web");
+        web.git_info.has_changes = true;
+        web.git_info.current_branch = Some("feature/login".to_string());
+        web.git_info.has_upstream = false;
+        let mut cli = repo("/tools/cli");
+        cli.git_info.ahead = 1;
+        cli.git_info.behind = 1;
+        let mut errors = RepoErrors::default();
+        errors.record(PathBuf::from("/tools/cli"), "timeout");
+        (vec![api, web, cli], errors)
+    }
+
+    #[test]
+    fn chips_are_combined_with_and() {
+        let (repos, errors) = sample();
+
+        assert_eq!(
+            visible(&repos, "", &[RepoPredicate::Dirty], &errors),
+            ["api", "web"]
+        );
+        assert_eq!(
+            visible(
+                &repos,
+                "",
+                &[RepoPredicate::Dirty, RepoPredicate::Behind],
+                &errors
+            ),
+            ["api"]
+        );
+        assert_eq!(
+            visible(
+                &repos,
+                "",
+                &[RepoPredicate::Behind, RepoPredicate::Errors],
+                &errors
+            ),
+            ["cli"]
+        );
+        assert!(visible(
+            &repos,
+            "",
+            &[RepoPredicate::Ahead, RepoPredicate::Dirty],
+            &errors
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn chips_narrow_the_text_search() {
+        let (repos, errors) = sample();
+
+        assert_eq!(visible(&repos, "SRC", &[], &errors), ["api", "web"]);
+        assert_eq!(
+            visible(&repos, "src", &[RepoPredicate::OffDefaultBranch], &errors),
+            ["web"]
+        );
+        assert_eq!(
+            visible(&repos, "src", &[RepoPredicate::NoUpstream], &errors),
+            ["web"]
+        );
+        assert!(visible(&repos, "tools", &[RepoPredicate::Dirty], &errors).is_empty());
+    }
+
+    #[test]
+    fn counts_ignore_other_chips_and_unknown_default_branch() {
+        let (mut repos, errors) = sample();
+        // Ветка по умолчанию неизвестна — не считаем репозиторий «не на ней»
+        repos[0].git_info.current_branch = Some("develop".to_string());
+        repos[0].git_info.default_branch = None;
+
+        assert_eq!(RepoPredicate::Dirty.count(&repos, &errors), 2);
+        assert_eq!(RepoPredicate::Behind.count(&repos, &errors), 2);
+        assert_eq!(RepoPredicate::Errors.count(&repos, &errors), 1);
+        assert_eq!(RepoPredicate::OffDefaultBranch.count(&repos, &errors), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::config::Config;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// `(workspace index, repository index)` of repositories whose HEAD or index
+/// changed within `window`, most recent first. A repository listed in several
+/// workspaces appears once.
+pub fn recent_repos(config: &Config, window: Duration) -> Vec<(usize, usize)> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let cutoff = now - window.as_secs() as i64;
+
+    let mut seen = HashSet::new();
+    let mut recent = Vec::new();
+    for (workspace_idx, workspace) in config.workspaces.iter().enumerate() {
+        for (repo_idx, repo) in workspace.repositories.iter().enumerate() {
+            let activity = repo
+                .git_info
+                .last_commit_time
+                .max(repo.git_info.index_mtime);
+            if let Some(activity) = activity.filter(|time| *time >= cutoff) {
+                if seen.insert(repo.path.clone()) {
+                    recent.push((activity, workspace_idx, repo_idx));
+                }
+            }
+        }
+    }
+
+    recent.sort_by_key(|(activity, _, _)| std::cmp::Reverse(*activity));
+    recent
+        .into_iter()
+        .map(|(_, workspace_idx, repo_idx)| (workspace_idx, repo_idx))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{RepositoryState, Workspace};
+    use std::path::PathBuf;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn repo(
+        path: &str,
+        commit_days_ago: Option<i64>,
+        index_days_ago: Option<i64>,
+    ) -> RepositoryState {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let mut repo = RepositoryState::new(PathBuf::from(path));
+        repo.git_info.last_commit_time = commit_days_ago.map(|days| now - days * DAY);
+        repo.git_info.index_mtime = index_days_ago.map(|days| now - days * DAY);
+        repo
+    }
+
+    fn workspace(name: &str, repos: Vec<RepositoryState>) -> Workspace {
+        let mut workspace = Workspace::new(name);
+        workspace.repositories = repos;
+        workspace
+    }
+
+    #[test]
+    fn lists_repos_touched_within_window_newest_first() {
+        let mut config = Config::default();
+        config.workspaces.push(workspace(
+            "Work",
+            vec![
+                repo("/w/old", Some(30), Some(20)),
+                repo("/w/committed", Some(3), None),
+                repo("/w/edited", Some(40), Some(1)),
+            ],
+        ));
+        config.workspaces.push(workspace(
+            "Side",
+            vec![
+                repo("/s/fresh", Some(0), None),
+                repo("/s/unknown", None, None),
+            ],
+        ));
+
+        let recent = recent_repos(&config, Duration::from_secs(7 * DAY as u64));
+
+        assert_eq!(recent, vec![(1, 0), (0, 2), (0, 1)]);
+    }
+
+    #[test]
+    fn repo_shared_by_workspaces_is_listed_once() {
+        let mut config = Config::default();
+        config
+            .workspaces
+            .push(workspace("A", vec![repo("/shared", Some(1), None)]));
+        config
+            .workspaces
+            .push(workspace("B", vec![repo("/shared", Some(1), None)]));
+
+        let recent = recent_repos(&config, Duration::from_secs(7 * DAY as u64));
+
+        assert_eq!(recent, vec![(0, 0)]);
+    }
+}
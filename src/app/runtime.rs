@@ -0,0 +1,189 @@
+use super::RepoErrors;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Kind of user operation started on a repository. Every kind shows the
+/// spinner; pushes and fetches are also remembered for what ends them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoOp {
+    Sync,
+    Push,
+    /// Fetch, pull or deepen: the status update that ends it records the
+    /// fetch time.
+    Fetch,
+}
+
+/// What was running on a repository when its operation ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FinishedOp {
+    pub was_syncing: bool,
+    pub was_pushing: bool,
+    pub was_fetching: bool,
+}
+
+/// Spinners and error flags of repositories, keyed by path so they do not
+/// depend on which workspace is shown. Entries are cleared by the message
+/// that ends the operation, or once the path is no longer in any workspace.
+#[derive(Debug, Default)]
+pub struct RepoRuntimeState {
+    syncing: HashSet<PathBuf>,
+    pushing: HashSet<PathBuf>,
+    fetching: HashSet<PathBuf>,
+    errors: RepoErrors,
+}
+
+impl RepoRuntimeState {
+    pub fn start_op(&mut self, path: PathBuf, op: RepoOp) {
+        match op {
+            RepoOp::Sync => {}
+            RepoOp::Push => {
+                self.pushing.insert(path.clone());
+            }
+            RepoOp::Fetch => {
+                self.fetching.insert(path.clone());
+            }
+        }
+        self.syncing.insert(path);
+    }
+
+    /// Ends whatever ran on `path`, whether it succeeded or not.
+    pub fn finish_op(&mut self, path: &Path) -> FinishedOp {
+        FinishedOp {
+            was_syncing: self.syncing.remove(path),
+            was_pushing: self.pushing.remove(path),
+            was_fetching: self.fetching.remove(path),
+        }
+    }
+
+    pub fn set_error<T: Into<String>>(&mut self, path: PathBuf, message: T) {
+        self.errors.record(path, message);
+    }
+
+    /// A status update succeeded, so the last error no longer applies.
+    pub fn resolve_error(&mut self, path: &Path) {
+        self.errors.resolve(path);
+    }
+
+    pub fn begin_retry(&mut self, path: &Path) -> bool {
+        self.errors.begin_retry(path)
+    }
+
+    /// Forgets everything about a repository that left its last workspace;
+    /// a late message for it then has nothing to clear.
+    pub fn clear_for_removed_repo(&mut self, path: &Path) {
+        self.finish_op(path);
+        self.errors.resolve(path);
+    }
+
+    /// Drops entries of paths that are in none of `known`, e.g. after a
+    /// repository was removed or its folder relocated.
+    pub fn retain_known(&mut self, known: &HashSet<PathBuf>) {
+        let stale: Vec<PathBuf> = self
+            .syncing
+            .iter()
+            .chain(&self.pushing)
+            .chain(&self.fetching)
+            .chain(self.errors.paths())
+            .filter(|path| !known.contains(*path))
+            .cloned()
+            .collect();
+        for path in stale {
+            self.clear_for_removed_repo(&path);
+        }
+    }
+
+    pub fn is_syncing(&self, path: &Path) -> bool {
+        self.syncing.contains(path)
+    }
+
+    pub fn is_pushing(&self, path: &Path) -> bool {
+        self.pushing.contains(path)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.syncing.is_empty()
+    }
+
+    pub fn syncing(&self) -> impl Iterator<Item = &PathBuf> {
+        self.syncing.iter()
+    }
+
+    pub fn errors(&self) -> &RepoErrors {
+        &self.errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known(paths: &[&str]) -> HashSet<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn operation_finished_while_another_workspace_is_shown_clears_its_spinner() {
+        let mut state = RepoRuntimeState::default();
+        let api = PathBuf::from("/a/api");
+        let web = PathBuf::from("/b/web");
+
+        // Область A: fetch api; переключились на B: push web
+        state.start_op(api.clone(), RepoOp::Fetch);
+        state.start_op(web.clone(), RepoOp::Push);
+        // api завершился, пока показана B
+        let finished = state.finish_op(&api);
+        assert_eq!(
+            finished,
+            FinishedOp {
+                was_syncing: true,
+                was_pushing: false,
+                was_fetching: true,
+            }
+        );
+        assert!(!state.is_syncing(&api));
+        assert!(state.is_pushing(&web));
+
+        // web упал уже после возврата в A
+        state.finish_op(&web);
+        state.set_error(web.clone(), "rejected");
+        assert!(state.is_idle());
+        assert!(state.errors().contains(&web));
+
+        // Повторное сообщение ничего не меняет
+        assert_eq!(state.finish_op(&api), FinishedOp::default());
+    }
+
+    #[test]
+    fn removed_repo_loses_its_spinner_and_error() {
+        let mut state = RepoRuntimeState::default();
+        let api = PathBuf::from("/a/api");
+        state.start_op(api.clone(), RepoOp::Sync);
+        state.set_error(api.clone(), "timeout");
+
+        state.clear_for_removed_repo(&api);
+
+        assert!(state.is_idle());
+        assert!(!state.errors().contains(&api));
+    }
+
+    #[test]
+    fn relocated_paths_are_pruned_and_listed_ones_kept() {
+        let mut state = RepoRuntimeState::default();
+        state.start_op(PathBuf::from("/old/api"), RepoOp::Fetch);
+        state.set_error(PathBuf::from("/old/web"), "not a git repository");
+        state.set_error(PathBuf::from("/b/lib"), "timeout");
+        state.start_op(PathBuf::from("/b/lib"), RepoOp::Sync);
+
+        // /old → /new: старых путей больше нет ни в одной области
+        state.retain_known(&known(&["/new/api", "/new/web", "/b/lib"]));
+
+        assert_eq!(state.syncing().collect::<Vec<_>>(), [Path::new("/b/lib")]);
+        assert!(!state.errors().contains(Path::new("/old/web")));
+        assert!(state.errors().contains(Path::new("/b/lib")));
+        // Поздний статус старого пути не возвращает спиннер
+        assert_eq!(
+            state.finish_op(Path::new("/old/api")),
+            FinishedOp::default()
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Scans finding more repositories than this are shown for review before
+/// anything is added, when `Config::confirm_scan_results` is on.
+pub const SCAN_PREVIEW_MIN_REPOS: usize = 10;
+
+/// Branch named by the contents of `.git/HEAD`; `None` for a detached HEAD.
+pub fn parse_head_ref(head: &str) -> Option<String> {
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .filter(|branch| !branch.is_empty())
+        .map(str::to_string)
+}
+
+/// Current branch read straight from `.git/HEAD`, without running git.
+/// Worktrees and submodules, whose `.git` is a file, get `None`.
+fn read_head_branch(repo_path: &Path) -> Option<String> {
+    std::fs::read_to_string(repo_path.join(".git").join("HEAD"))
+        .ok()
+        .and_then(|head| parse_head_ref(&head))
+}
+
+fn path_matches(path: &Path, filter_lower: &str) -> bool {
+    path.to_string_lossy().to_lowercase().contains(filter_lower)
+}
+
+/// A found repository in the scan results window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanPreviewEntry {
+    pub path: PathBuf,
+    pub branch: Option<String>,
+    /// Already in the active workspace: shown greyed out, never added again.
+    pub already_added: bool,
+    pub selected: bool,
+}
+
+/// Scan results waiting for the user to pick what gets added.
+pub struct ScanPreview {
+    pub entries: Vec<ScanPreviewEntry>,
+    pub filter: String,
+}
+
+impl ScanPreview {
+    /// New repositories start checked; ones the workspace already has are
+    /// unchecked and locked.
+    pub fn new(found: Vec<PathBuf>, existing: &HashSet<PathBuf>) -> Self {
+        let entries = found
+            .into_iter()
+            .map(|path| {
+                let already_added = existing.contains(&path);
+                ScanPreviewEntry {
+                    branch: read_head_branch(&path),
+                    already_added,
+                    selected: !already_added,
+                    path,
+                }
+            })
+            .collect();
+        Self {
+            entries,
+            filter: String::new(),
+        }
+    }
+
+    /// Case-insensitive match of the filter box against the path.
+    pub fn is_visible(&self, entry: &ScanPreviewEntry) -> bool {
+        path_matches(&entry.path, &self.filter.to_lowercase())
+    }
+
+    /// Checks or unchecks every visible repository that can be added.
+    pub fn select_visible(&mut self, selected: bool) {
+        let filter = self.filter.to_lowercase();
+        for entry in &mut self.entries {
+            if !entry.already_added && path_matches(&entry.path, &filter) {
+                entry.selected = selected;
+            }
+        }
+    }
+
+    /// Checked repositories, hidden ones included.
+    pub fn selected_paths(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.selected && !entry.already_added)
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.selected && !entry.already_added)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(found: &[&str], existing: &[&str]) -> ScanPreview {
+        ScanPreview::new(
+            found.iter().map(PathBuf::from).collect(),
+            &existing.iter().map(PathBuf::from).collect(),
+        )
+    }
+
+    #[test]
+    fn new_repos_are_checked_and_known_ones_greyed_out() {
+        let preview = preview(&["/src/api", "/src/web", "/src/old"], &["/src/web"]);
+
+        let states: Vec<(bool, bool)> = preview
+            .entries
+            .iter()
+            .map(|entry| (entry.selected, entry.already_added))
+            .collect();
+        assert_eq!(states, [(true, false), (false, true), (true, false)]);
+        assert_eq!(
+            preview.selected_paths(),
+            [PathBuf::from("/src/api"), PathBuf::from("/src/old")]
+        );
+    }
+
+    #[test]
+    fn select_all_and_none_follow_the_filter() {
+        let mut preview = preview(
+            &["/src/api", "/src/vendor/lib", "/src/vendor/known"],
+            &["/src/vendor/known"],
+        );
+
+        preview.filter = "VENDOR".to_string();
+        preview.select_visible(false);
+        assert_eq!(preview.selected_paths(), [PathBuf::from("/src/api")]);
+
+        preview.filter.clear();
+        preview.select_visible(true);
+        assert_eq!(preview.selected_count(), 2);
+        // Уже добавленный остаётся неотмеченным
+        assert!(!preview.entries[2].selected);
+    }
+
+    #[test]
+    fn head_file_names_the_branch() {
+        assert_eq!(
+            parse_head_ref("ref: refs/heads/feature/login\n"),
+            Some("feature/login".to_string())
+        );
+        assert_eq!(
+            parse_head_ref("3f2c1a9b8e7d6c5b4a3f2c1a9b8e7d6c5b4a3f2c\n"),
+            None
+        );
+    }
+}
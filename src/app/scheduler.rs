@@ -0,0 +1,191 @@
+use crate::git::{error_repo_path, GitMessage};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Repository whose operation `msg` finishes: status updates and errors
+/// both end a load, fetch or pull.
+pub fn completed_repo_path(msg: &GitMessage) -> Option<PathBuf> {
+    match msg {
+        GitMessage::RepoStatusUpdated { repo_path, .. }
+        | GitMessage::FetchComplete { repo_path, .. }
+        | GitMessage::PullConflicts { repo_path, .. }
+        | GitMessage::OperationCancelled { repo_path } => Some(repo_path.clone()),
+        GitMessage::Error(err) => error_repo_path(err),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadPhase {
+    Idle,
+    Active,
+    Background,
+}
+
+/// Two-phase status loading: repositories of the active workspace are
+/// enqueued first, the rest only once every active one has reported back,
+/// so they do not compete for pool slots with what the user is looking at.
+pub struct LoadScheduler {
+    phase: LoadPhase,
+    active_total: usize,
+    active_pending: HashSet<PathBuf>,
+    background_queue: Vec<PathBuf>,
+    background_pending: HashSet<PathBuf>,
+}
+
+impl Default for LoadScheduler {
+    fn default() -> Self {
+        Self {
+            phase: LoadPhase::Idle,
+            active_total: 0,
+            active_pending: HashSet::new(),
+            background_queue: Vec::new(),
+            background_pending: HashSet::new(),
+        }
+    }
+}
+
+impl LoadScheduler {
+    /// Returns the repositories to enqueue right away.
+    pub fn start(&mut self, active: Vec<PathBuf>, background: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        let active: Vec<PathBuf> = active
+            .into_iter()
+            .filter(|p| seen.insert(p.clone()))
+            .collect();
+        let background = background
+            .into_iter()
+            .filter(|p| seen.insert(p.clone()))
+            .collect();
+
+        self.active_total = active.len();
+        self.active_pending = active.iter().cloned().collect();
+        self.background_queue = background;
+        self.background_pending.clear();
+        self.phase = LoadPhase::Active;
+
+        if active.is_empty() {
+            return self.start_background();
+        }
+        active
+    }
+
+    fn start_background(&mut self) -> Vec<PathBuf> {
+        let batch = std::mem::take(&mut self.background_queue);
+        self.background_pending = batch.iter().cloned().collect();
+        self.phase = if batch.is_empty() {
+            LoadPhase::Idle
+        } else {
+            LoadPhase::Background
+        };
+        batch
+    }
+
+    /// Marks a repository as loaded (successfully or not). Returns the
+    /// background batch when this completes the active phase.
+    pub fn complete(&mut self, repo_path: &Path) -> Vec<PathBuf> {
+        match self.phase {
+            LoadPhase::Active => {
+                if self.active_pending.remove(repo_path) && self.active_pending.is_empty() {
+                    return self.start_background();
+                }
+            }
+            LoadPhase::Background => {
+                if self.background_pending.remove(repo_path) && self.background_pending.is_empty() {
+                    self.phase = LoadPhase::Idle;
+                }
+            }
+            LoadPhase::Idle => {}
+        }
+        Vec::new()
+    }
+
+    pub fn on_message(&mut self, msg: &GitMessage) -> Vec<PathBuf> {
+        match completed_repo_path(msg) {
+            Some(repo_path) => self.complete(&repo_path),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn phase(&self) -> LoadPhase {
+        self.phase
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.phase != LoadPhase::Idle
+    }
+
+    /// `(active loaded, active total, background pending)`.
+    pub fn progress(&self) -> (usize, usize, usize) {
+        (
+            self.active_total - self.active_pending.len(),
+            self.active_total,
+            self.background_pending.len() + self.background_queue.len(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loaded(path: &str) -> GitMessage {
+        GitMessage::RepoStatusUpdated {
+            repo_path: PathBuf::from(path),
+            git_info: Box::default(),
+        }
+    }
+
+    fn failed(path: &str) -> GitMessage {
+        GitMessage::Error(format!(
+            "Failed to get git info for {:?}: boom",
+            PathBuf::from(path)
+        ))
+    }
+
+    fn paths(items: &[&str]) -> Vec<PathBuf> {
+        items.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn background_waits_for_every_active_repo() {
+        let mut scheduler = LoadScheduler::default();
+        let first = scheduler.start(paths(&["/a", "/b"]), paths(&["/c", "/d", "/a"]));
+
+        assert_eq!(first, paths(&["/a", "/b"]));
+        assert_eq!(scheduler.progress(), (0, 2, 2));
+
+        assert!(scheduler.on_message(&loaded("/a")).is_empty());
+        assert!(scheduler.on_message(&loaded("/c")).is_empty());
+        assert!(scheduler.on_message(&loaded("/a")).is_empty());
+        assert_eq!(scheduler.phase(), LoadPhase::Active);
+        assert_eq!(scheduler.progress(), (1, 2, 2));
+
+        let second = scheduler.on_message(&failed("/b"));
+        assert_eq!(second, paths(&["/c", "/d"]));
+        assert_eq!(scheduler.phase(), LoadPhase::Background);
+        assert_eq!(scheduler.progress(), (2, 2, 2));
+
+        scheduler.on_message(&loaded("/c"));
+        scheduler.on_message(&failed("/d"));
+        assert_eq!(scheduler.phase(), LoadPhase::Idle);
+        assert!(!scheduler.is_running());
+    }
+
+    #[test]
+    fn empty_active_workspace_starts_background_immediately() {
+        let mut scheduler = LoadScheduler::default();
+        let first = scheduler.start(Vec::new(), paths(&["/c"]));
+
+        assert_eq!(first, paths(&["/c"]));
+        assert_eq!(scheduler.phase(), LoadPhase::Background);
+    }
+
+    #[test]
+    fn nothing_to_load_stays_idle() {
+        let mut scheduler = LoadScheduler::default();
+
+        assert!(scheduler.start(Vec::new(), Vec::new()).is_empty());
+        assert!(!scheduler.is_running());
+    }
+}
@@ -1,47 +1,326 @@
-use std::path::PathBuf;
+use crate::git::check_git_dir;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 
 pub struct RepositorySearcher;
 
-impl RepositorySearcher {
-    pub fn find_git_repositories(path: &PathBuf) -> Vec<PathBuf> {
-        let mut repositories = Vec::new();
+/// Scan cost of one top-level folder before the first real scan
+/// recalibrates it, measured on an SSD with a few hundred repositories.
+pub const DEFAULT_MS_PER_DIR: f32 = 5.0;
+
+/// State shared by the scan threads.
+struct Scan {
+    repositories: Mutex<Vec<PathBuf>>,
+    follow_symlinks: bool,
+    /// Resolved folders already entered, so a link back up the tree does
+    /// not loop forever.
+    visited: Mutex<HashSet<PathBuf>>,
+}
 
+impl RepositorySearcher {
+    /// Symlinked folders are entered only with `follow_symlinks`; a symlink
+    /// pointing straight at a repository is always found.
+    pub fn find_git_repositories(
+        path: &PathBuf,
+        scan_threads: u8,
+        follow_symlinks: bool,
+    ) -> Vec<PathBuf> {
         if Self::is_git_repository(path) {
-            repositories.push(path.clone());
-            return repositories;
+            return vec![path.clone()];
         }
 
-        Self::scan_for_repositories(path, &mut repositories);
+        let scan = Scan {
+            repositories: Mutex::new(Vec::new()),
+            follow_symlinks,
+            visited: Mutex::new(std::fs::canonicalize(path).into_iter().collect()),
+        };
 
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(scan_threads.max(1) as usize)
+            .build()
+        {
+            Ok(pool) => pool.install(|| Self::scan_for_repositories(path, &scan)),
+            Err(e) => {
+                eprintln!("Failed to create scan thread pool: {}", e);
+                Self::scan_for_repositories(path, &scan);
+            }
+        }
+
+        let mut repositories = scan.repositories.into_inner().unwrap_or_default();
+        // Порядок обхода в параллельном режиме не детерминирован
+        repositories.sort();
+        repositories.dedup();
         repositories
     }
 
-    fn is_git_repository(path: &PathBuf) -> bool {
-        path.join(".git").exists()
-    }
-
-    fn scan_for_repositories(dir: &PathBuf, repositories: &mut Vec<PathBuf>) {
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-
-                if path.is_dir() {
-                    if Self::is_git_repository(&path) {
-                        repositories.push(path);
-                    } else {
-                        if let Some(name) = path.file_name() {
-                            let name_str = name.to_string_lossy();
-                            if !name_str.starts_with('.')
-                                && !name_str.eq_ignore_ascii_case("node_modules")
-                                && !name_str.eq_ignore_ascii_case("target")
-                                && !name_str.eq_ignore_ascii_case("build")
-                            {
-                                Self::scan_for_repositories(&path, repositories);
-                            }
-                        }
-                    }
+    /// Rough duration of `find_git_repositories(path, ..)` at `ms_per_dir`
+    /// per folder; only the first level is read.
+    pub fn estimate_scan_time(path: &Path, ms_per_dir: f32) -> Duration {
+        scan_time_for(Self::count_scan_dirs(path), ms_per_dir)
+    }
+
+    /// Direct child folders the scan will enter or report; hidden and
+    /// skipped folders (`node_modules`, `target`, ...) cost nothing.
+    pub fn count_scan_dirs(path: &Path) -> usize {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|child| {
+                child.is_dir() && (Self::should_descend(child) || Self::is_git_repository(child))
+            })
+            .count()
+    }
+
+    /// A stray `.git` without HEAD, or a `.git` file pointing nowhere, is
+    /// not a repository.
+    fn is_git_repository(path: &Path) -> bool {
+        check_git_dir(path).is_valid()
+    }
+
+    fn should_descend(path: &Path) -> bool {
+        match path.file_name() {
+            Some(name) => {
+                let name_str = name.to_string_lossy();
+                !name_str.starts_with('.')
+                    && !name_str.eq_ignore_ascii_case("node_modules")
+                    && !name_str.eq_ignore_ascii_case("target")
+                    && !name_str.eq_ignore_ascii_case("build")
+            }
+            None => false,
+        }
+    }
+
+    fn should_follow(path: &Path, scan: &Scan) -> bool {
+        let is_symlink = std::fs::symlink_metadata(path)
+            .map(|meta| meta.file_type().is_symlink())
+            .unwrap_or(false);
+        if !is_symlink {
+            return true;
+        }
+        if !scan.follow_symlinks {
+            return false;
+        }
+        match (std::fs::canonicalize(path), scan.visited.lock()) {
+            (Ok(target), Ok(mut visited)) => visited.insert(target),
+            _ => false,
+        }
+    }
+
+    fn scan_for_repositories(dir: &Path, scan: &Scan) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+
+        entries.par_iter().for_each(|path| {
+            if !path.is_dir() {
+                return;
+            }
+
+            if Self::is_git_repository(path) {
+                if let Ok(mut found) = scan.repositories.lock() {
+                    found.push(path.clone());
                 }
+            } else if Self::should_descend(path) && Self::should_follow(path, scan) {
+                Self::scan_for_repositories(path, scan);
+            }
+        });
+    }
+}
+
+/// Scan results grouped by the top-level folder of `root` they are in.
+/// Repositories directly in `root`, and `root` itself, go under the name of
+/// `root`.
+pub fn partition_by_top_folder(root: &Path, repos: &[PathBuf]) -> BTreeMap<String, Vec<PathBuf>> {
+    let root_name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| root.display().to_string());
+
+    let mut groups: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for repo in repos {
+        let mut components = repo
+            .strip_prefix(root)
+            .map(|relative| relative.components())
+            .ok();
+        let top = components.as_mut().and_then(|c| c.next());
+        let nested = components.as_mut().and_then(|c| c.next()).is_some();
+
+        let name = match top {
+            Some(top) if nested => top.as_os_str().to_string_lossy().to_string(),
+            _ => root_name.clone(),
+        };
+        groups.entry(name).or_default().push(repo.clone());
+    }
+    groups
+}
+
+/// A scan is worth splitting when it is large and spans several folders;
+/// a `threshold` of 0 never offers it.
+pub fn should_offer_split(groups: &BTreeMap<String, Vec<PathBuf>>, threshold: usize) -> bool {
+    let total: usize = groups.values().map(Vec::len).sum();
+    threshold > 0 && total > threshold && groups.len() > 1
+}
+
+/// `dirs` top-level folders at `ms_per_dir` each.
+pub fn scan_time_for(dirs: usize, ms_per_dir: f32) -> Duration {
+    Duration::from_micros((dirs as f32 * ms_per_dir.max(0.0) * 1000.0).round() as u64)
+}
+
+/// Measured cost per top-level folder of a finished scan; `None` when the
+/// scan had nothing to measure by.
+pub fn measured_ms_per_dir(elapsed: Duration, dirs: usize) -> Option<f32> {
+    (dirs > 0).then(|| elapsed.as_millis() as f32 / dirs as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_repo(path: &Path) {
+        std::fs::create_dir_all(path.join(".git")).unwrap();
+        std::fs::write(path.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+    }
+
+    #[test]
+    fn parallel_scan_finds_each_repository_once() {
+        let root = std::env::temp_dir().join("repo_manager_parallel_scan");
+        let _ = std::fs::remove_dir_all(&root);
+
+        let mut expected = Vec::new();
+        for group in 0..5 {
+            for repo in 0..10 {
+                let path = root
+                    .join(format!("group{}", group))
+                    .join(format!("repo{}", repo));
+                make_repo(&path);
+                expected.push(path);
             }
         }
+        // Репозитории внутри репозитория и в пропускаемых папках не ищем
+        make_repo(&root.join("group0/repo0/vendor/inner"));
+        make_repo(&root.join("group1/node_modules/dep"));
+        make_repo(&root.join(".cache/hidden"));
+        expected.sort();
+
+        let found = RepositorySearcher::find_git_repositories(&root, 4, false);
+        let sequential = RepositorySearcher::find_git_repositories(&root, 1, false);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(found, expected);
+        assert_eq!(sequential, expected);
+    }
+
+    #[test]
+    fn estimate_counts_only_scanned_top_level_folders() {
+        let root = std::env::temp_dir().join("repo_manager_scan_estimate");
+        let _ = std::fs::remove_dir_all(&root);
+        make_repo(&root.join("api"));
+        std::fs::create_dir_all(root.join("clients/acme")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        std::fs::create_dir_all(root.join(".cache")).unwrap();
+        std::fs::write(root.join("README.md"), "notes").unwrap();
+
+        let dirs = RepositorySearcher::count_scan_dirs(&root);
+        let estimate = RepositorySearcher::estimate_scan_time(&root, DEFAULT_MS_PER_DIR);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(dirs, 2);
+        assert_eq!(estimate, Duration::from_millis(10));
+        assert_eq!(scan_time_for(3_000, 5.0), Duration::from_secs(15));
+        assert_eq!(
+            measured_ms_per_dir(Duration::from_millis(120), 40),
+            Some(3.0)
+        );
+        assert_eq!(measured_ms_per_dir(Duration::from_secs(1), 0), None);
+    }
+
+    #[test]
+    fn repos_are_grouped_by_top_level_folder() {
+        let root = Path::new("/home/me/src");
+        let repos = vec![
+            root.join("clients/acme/api"),
+            root.join("clients/globex"),
+            root.join("oss/ripgrep"),
+            root.join("scratch"),
+        ];
+
+        let groups = partition_by_top_folder(root, &repos);
+
+        assert_eq!(
+            groups.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["clients", "oss", "src"]
+        );
+        assert_eq!(groups["clients"].len(), 2);
+        assert_eq!(groups["oss"], vec![root.join("oss/ripgrep")]);
+        // Репозиторий прямо в корне не становится отдельной папкой
+        assert_eq!(groups["src"], vec![root.join("scratch")]);
+    }
+
+    #[test]
+    fn root_repository_goes_under_the_root_name() {
+        let root = Path::new("/home/me/project");
+        let groups = partition_by_top_folder(root, &[root.to_path_buf()]);
+
+        assert_eq!(groups["project"], vec![root.to_path_buf()]);
+    }
+
+    #[test]
+    fn split_is_offered_only_for_large_multi_folder_scans() {
+        let root = Path::new("/src");
+        let spread: Vec<PathBuf> = (0..31)
+            .map(|i| root.join(format!("group{}/repo{}", i % 3, i)))
+            .collect();
+        let single: Vec<PathBuf> = (0..31)
+            .map(|i| root.join(format!("clients/repo{}", i)))
+            .collect();
+
+        let spread = partition_by_top_folder(root, &spread);
+        assert!(should_offer_split(&spread, 30));
+        assert!(!should_offer_split(&spread, 31));
+        assert!(!should_offer_split(&spread, 0));
+        assert!(!should_offer_split(
+            &partition_by_top_folder(root, &single),
+            30
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_folders_are_entered_only_when_following() {
+        let root = std::env::temp_dir().join("repo_manager_scan_symlinks");
+        let outside = std::env::temp_dir().join("repo_manager_scan_symlinks_target");
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+        make_repo(&outside.join("linked"));
+        std::fs::create_dir_all(&root).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("shared")).unwrap();
+        // Ссылка на предка не должна зациклить обход
+        std::os::unix::fs::symlink(&root, root.join("loop")).unwrap();
+
+        let skipped = RepositorySearcher::find_git_repositories(&root, 4, false);
+        let followed = RepositorySearcher::find_git_repositories(&root, 4, true);
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+
+        assert!(skipped.is_empty());
+        assert_eq!(followed, vec![root.join("shared/linked")]);
+    }
+
+    #[test]
+    fn repository_root_is_returned_as_is() {
+        let root = std::env::temp_dir().join("repo_manager_scan_root_repo");
+        make_repo(&root);
+
+        let found = RepositorySearcher::find_git_repositories(&root, 4, false);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(found, vec![root]);
     }
 }
@@ -0,0 +1,163 @@
+use std::time::{Duration, Instant};
+
+/// What the user picked in the "operations still running" dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownChoice {
+    /// Keep the window open and close it once everything has finished.
+    WaitAndClose,
+    /// Drop the queued operations, then wait for the running ones only.
+    CancelAndClose,
+    CloseAnyway,
+    /// Back to work, nothing is closed.
+    Stay,
+}
+
+/// Closing the window while git operations are in flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppShutdownState {
+    Running,
+    /// A close was requested while busy; the dialog is open.
+    Confirming,
+    /// Closes by itself when idle or once `max_wait` has passed.
+    Waiting {
+        since: Instant,
+        max_wait: Duration,
+    },
+    /// The next close request is let through.
+    Closing,
+}
+
+impl AppShutdownState {
+    /// Handles a close request from the window; `true` lets it through.
+    /// A second request while waiting closes right away.
+    pub fn close_requested(&mut self, busy: bool) -> bool {
+        match self {
+            AppShutdownState::Running if busy => {
+                *self = AppShutdownState::Confirming;
+                false
+            }
+            AppShutdownState::Confirming => false,
+            _ => {
+                *self = AppShutdownState::Closing;
+                true
+            }
+        }
+    }
+
+    /// Applies the dialog choice. Only meaningful while confirming.
+    pub fn choose(&mut self, choice: ShutdownChoice, max_wait: Duration, now: Instant) {
+        if *self != AppShutdownState::Confirming {
+            return;
+        }
+        *self = match choice {
+            ShutdownChoice::WaitAndClose | ShutdownChoice::CancelAndClose => {
+                AppShutdownState::Waiting {
+                    since: now,
+                    max_wait,
+                }
+            }
+            ShutdownChoice::CloseAnyway => AppShutdownState::Closing,
+            ShutdownChoice::Stay => AppShutdownState::Running,
+        };
+    }
+
+    /// Called every frame; `true` when the window should be closed now.
+    pub fn tick(&mut self, busy: bool, now: Instant) -> bool {
+        match *self {
+            AppShutdownState::Waiting { since, max_wait }
+                if !busy || now.duration_since(since) >= max_wait =>
+            {
+                *self = AppShutdownState::Closing;
+                true
+            }
+            AppShutdownState::Confirming if !busy => {
+                // Всё успело завершиться, пока диалог был открыт
+                *self = AppShutdownState::Closing;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Time left before waiting gives up.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        match *self {
+            AppShutdownState::Waiting { since, max_wait } => {
+                Some(max_wait.saturating_sub(now.duration_since(since)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_WAIT: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn idle_app_closes_at_once() {
+        let mut state = AppShutdownState::Running;
+
+        assert!(state.close_requested(false));
+        assert_eq!(state, AppShutdownState::Closing);
+    }
+
+    #[test]
+    fn busy_app_asks_and_can_stay_open() {
+        let mut state = AppShutdownState::Running;
+
+        assert!(!state.close_requested(true));
+        assert_eq!(state, AppShutdownState::Confirming);
+        assert!(!state.close_requested(true));
+
+        state.choose(ShutdownChoice::Stay, MAX_WAIT, Instant::now());
+        assert_eq!(state, AppShutdownState::Running);
+    }
+
+    #[test]
+    fn waiting_closes_when_operations_finish() {
+        let start = Instant::now();
+        let mut state = AppShutdownState::Confirming;
+
+        state.choose(ShutdownChoice::WaitAndClose, MAX_WAIT, start);
+        assert!(!state.tick(true, start + Duration::from_secs(5)));
+        assert_eq!(
+            state.remaining(start + Duration::from_secs(5)),
+            Some(Duration::from_secs(55))
+        );
+        assert!(state.tick(false, start + Duration::from_secs(6)));
+        assert_eq!(state, AppShutdownState::Closing);
+    }
+
+    #[test]
+    fn waiting_gives_up_after_the_maximum() {
+        let start = Instant::now();
+        let mut state = AppShutdownState::Confirming;
+
+        state.choose(ShutdownChoice::CancelAndClose, MAX_WAIT, start);
+        assert!(!state.tick(true, start + MAX_WAIT - Duration::from_secs(1)));
+        assert!(state.tick(true, start + MAX_WAIT));
+    }
+
+    #[test]
+    fn close_anyway_and_second_request_while_waiting_close() {
+        let mut state = AppShutdownState::Confirming;
+        state.choose(ShutdownChoice::CloseAnyway, MAX_WAIT, Instant::now());
+        assert_eq!(state, AppShutdownState::Closing);
+        assert!(state.close_requested(true));
+
+        let mut waiting = AppShutdownState::Confirming;
+        waiting.choose(ShutdownChoice::WaitAndClose, MAX_WAIT, Instant::now());
+        assert!(waiting.close_requested(true));
+    }
+
+    #[test]
+    fn dialog_goes_away_when_work_finishes_first() {
+        let mut state = AppShutdownState::Confirming;
+
+        assert!(state.tick(false, Instant::now()));
+        assert!(!AppShutdownState::Running.tick(false, Instant::now()));
+    }
+}
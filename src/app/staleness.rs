@@ -0,0 +1,58 @@
+use crate::workspace::RepositoryState;
+use std::cmp::Ordering;
+use std::time::{Duration, SystemTime};
+
+/// A repository not fetched for this long is counted in the header hint.
+pub const STALE_FETCH_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Never fetched, or last fetched more than `STALE_FETCH_AGE` before `now`.
+pub fn is_fetch_stale(last_fetch: Option<SystemTime>, now: SystemTime) -> bool {
+    match last_fetch {
+        Some(time) => now.duration_since(time).unwrap_or(Duration::ZERO) > STALE_FETCH_AGE,
+        None => true,
+    }
+}
+
+/// Staleness order: never fetched first, then the oldest fetch.
+pub fn by_staleness(a: Option<SystemTime>, b: Option<SystemTime>) -> Ordering {
+    // None < Some: ровно тот порядок, что нужен
+    a.cmp(&b)
+}
+
+pub fn stale_fetch_count(repositories: &[RepositoryState], now: SystemTime) -> usize {
+    repositories
+        .iter()
+        .filter(|repo| is_fetch_stale(repo.last_fetch, now))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn never_fetched_comes_first_then_the_oldest() {
+        let now = SystemTime::now();
+        let mut fetches = vec![Some(now - HOUR), None, Some(now - 30 * HOUR), None];
+
+        fetches.sort_by(|a, b| by_staleness(*a, *b));
+
+        assert_eq!(
+            fetches,
+            vec![None, None, Some(now - 30 * HOUR), Some(now - HOUR)]
+        );
+    }
+
+    #[test]
+    fn stale_means_never_or_over_a_day_ago() {
+        let now = SystemTime::now();
+
+        assert!(is_fetch_stale(None, now));
+        assert!(is_fetch_stale(Some(now - 25 * HOUR), now));
+        assert!(!is_fetch_stale(Some(now - 23 * HOUR), now));
+        // Часы могли уйти назад — это не повод считать fetch старым
+        assert!(!is_fetch_stale(Some(now + HOUR), now));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::workspace::RepositoryState;
+
+/// Aggregate state of the active workspace shown in the tray menu.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrayStatus {
+    pub behind: usize,
+    pub dirty: usize,
+}
+
+impl TrayStatus {
+    pub fn of(repositories: &[RepositoryState]) -> Self {
+        Self {
+            behind: repositories
+                .iter()
+                .filter(|repo| repo.git_info.behind > 0)
+                .count(),
+            dirty: repositories
+                .iter()
+                .filter(|repo| repo.git_info.has_changes)
+                .count(),
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.behind == 0 && self.dirty == 0
+    }
+}
+
+/// Actions offered by the tray menu.
+#[cfg_attr(
+    not(all(feature = "tray", any(windows, target_os = "macos"))),
+    allow(dead_code)
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayCommand {
+    FetchAll,
+    OpenWindow,
+    Quit,
+}
+
+/// What the window close button does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAction {
+    HideToTray,
+    /// The usual close, with the "operations still running" check.
+    Close,
+}
+
+/// Hides to the tray only when the user asked for it and a tray icon is
+/// actually there to bring the window back; "Quit" in the tray always closes.
+pub fn close_action(close_to_tray: bool, tray_available: bool, quitting: bool) -> CloseAction {
+    if close_to_tray && tray_available && !quitting {
+        CloseAction::HideToTray
+    } else {
+        CloseAction::Close
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitInfo;
+    use std::path::PathBuf;
+
+    fn repo(behind: usize, has_changes: bool) -> RepositoryState {
+        RepositoryState {
+            path: PathBuf::from("/tmp/repo"),
+            git_info: GitInfo {
+                behind,
+                has_changes,
+                ..GitInfo::default()
+            },
+            ..RepositoryState::default()
+        }
+    }
+
+    #[test]
+    fn status_counts_repositories_not_commits() {
+        let repos = [repo(5, false), repo(1, true), repo(0, true), repo(0, false)];
+
+        assert_eq!(
+            TrayStatus::of(&repos),
+            TrayStatus {
+                behind: 2,
+                dirty: 2
+            }
+        );
+        assert!(!TrayStatus::of(&repos).is_clean());
+        assert!(TrayStatus::of(&[repo(0, false)]).is_clean());
+    }
+
+    #[test]
+    fn close_hides_only_with_a_tray_to_come_back_from() {
+        assert_eq!(close_action(true, true, false), CloseAction::HideToTray);
+        // Без значка в трее окно было бы не вернуть
+        assert_eq!(close_action(true, false, false), CloseAction::Close);
+        assert_eq!(close_action(false, true, false), CloseAction::Close);
+        assert_eq!(close_action(true, true, true), CloseAction::Close);
+    }
+}
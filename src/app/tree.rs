@@ -1,5 +1,54 @@
+use super::{RepoErrors, RepoFilter};
+use crate::config::{GroupMode, SortMode};
 use crate::workspace::RepositoryState;
 use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Status groups of `GroupMode::ByStatus`, in display order. The names are
+/// localization keys; a repository can be listed in several groups.
+pub const STATUS_GROUPS: [StatusGroup; 5] = [
+    StatusGroup::Behind,
+    StatusGroup::Ahead,
+    StatusGroup::Dirty,
+    StatusGroup::Clean,
+    StatusGroup::Error,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusGroup {
+    Behind,
+    Ahead,
+    Dirty,
+    Clean,
+    Error,
+}
+
+impl StatusGroup {
+    pub fn label_key(self) -> &'static str {
+        match self {
+            StatusGroup::Behind => "status_group_behind",
+            StatusGroup::Ahead => "status_group_ahead",
+            StatusGroup::Dirty => "status_group_dirty",
+            StatusGroup::Clean => "status_group_clean",
+            StatusGroup::Error => "status_group_error",
+        }
+    }
+
+    /// Clean means none of the other groups apply.
+    pub fn contains(self, repo: &RepositoryState, errors: &RepoErrors) -> bool {
+        let info = &repo.git_info;
+        let has_error = errors.get(&repo.path).is_some();
+        match self {
+            StatusGroup::Behind => info.behind > 0,
+            StatusGroup::Ahead => info.ahead > 0,
+            StatusGroup::Dirty => info.has_changes,
+            StatusGroup::Clean => {
+                info.behind == 0 && info.ahead == 0 && !info.has_changes && !has_error
+            }
+            StatusGroup::Error => has_error,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct TreeNode {
@@ -8,6 +57,10 @@ pub struct TreeNode {
     pub children: Vec<TreeNode>,
     pub repositories: Vec<(usize, PathBuf)>,
     pub is_expanded: bool,
+    /// Deeper folders were flattened into this node by `max_tree_depth`.
+    pub truncated: bool,
+    /// A status group rather than a folder; `name` is a localization key.
+    pub is_group: bool,
 }
 
 impl TreeNode {
@@ -18,7 +71,76 @@ impl TreeNode {
             children: Vec::new(),
             repositories: Vec::new(),
             is_expanded: true,
+            truncated: false,
+            is_group: false,
+        }
+    }
+
+    /// Depth of the deepest descendant, `0` for a node without children.
+    pub fn depth(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| child.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn truncated_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if self.truncated {
+            paths.push(self.path.clone());
+        }
+        for child in &self.children {
+            paths.extend(child.truncated_paths());
+        }
+        paths
+    }
+
+    /// Paths of every repository under this node, nested folders included.
+    /// Paths stay valid under a search filter, unlike the `original_idx`
+    /// indices.
+    pub fn all_repository_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .repositories
+            .iter()
+            .map(|(_, path)| path.clone())
+            .collect();
+        for child in &self.children {
+            paths.extend(child.all_repository_paths());
+        }
+        paths
+    }
+
+    fn take_nested_repositories(&mut self) -> Vec<(usize, PathBuf)> {
+        let mut repositories = Vec::new();
+        for mut child in std::mem::take(&mut self.children) {
+            repositories.extend(child.take_nested_repositories());
+            repositories.append(&mut child.repositories);
         }
+        repositories
+    }
+
+    fn first_repository_path(&self) -> Option<&PathBuf> {
+        self.repositories
+            .first()
+            .map(|(_, path)| path)
+            .or_else(|| self.children.iter().find_map(|c| c.first_repository_path()))
+    }
+
+    /// Absolute folder path of this node. `path` only keeps normal components,
+    /// so the root and drive prefix are recovered from a contained repository.
+    pub fn folder_path(&self) -> Option<PathBuf> {
+        let depth = self.path.components().count();
+        self.first_repository_path()?
+            .ancestors()
+            .find(|ancestor| {
+                ancestor
+                    .components()
+                    .filter(|c| matches!(c, std::path::Component::Normal(_)))
+                    .count()
+                    == depth
+            })
+            .map(PathBuf::from)
     }
 
     pub fn find_child_mut(&mut self, name: &str) -> Option<&mut TreeNode> {
@@ -42,25 +164,20 @@ pub struct TreeBuilder;
 impl TreeBuilder {
     pub fn build_tree(
         repositories: &[RepositoryState],
-        search_query: &str,
-        sort_by_name: bool,
+        filter: RepoFilter,
+        effective_sort: SortMode,
+        max_depth: usize,
+        group: GroupMode,
+        errors: &RepoErrors,
     ) -> TreeNode {
+        if group == GroupMode::ByStatus {
+            return Self::build_status_tree(repositories, filter, effective_sort, errors);
+        }
+
         let mut root = TreeNode::new("Root".to_string(), PathBuf::new());
 
         for (idx, repo) in repositories.iter().enumerate() {
-            let matches_search = if search_query.is_empty() {
-                true
-            } else {
-                let query_lower = search_query.to_lowercase();
-                repo.name.to_lowercase().contains(&query_lower)
-                    || repo
-                        .path
-                        .to_string_lossy()
-                        .to_lowercase()
-                        .contains(&query_lower)
-            };
-
-            if !matches_search {
+            if !filter.matches(repo, errors) {
                 continue;
             }
 
@@ -91,13 +208,133 @@ impl TreeBuilder {
             current_node.repositories.push((idx, repo.path.clone()));
         }
 
-        if sort_by_name {
-            Self::sort_tree_node(&mut root, repositories);
+        Self::flatten_deep_nodes(&mut root, 0, max_depth);
+
+        match effective_sort {
+            SortMode::Path => {}
+            SortMode::Name => Self::sort_tree_node(&mut root, repositories),
+            SortMode::LastCommit => {
+                Self::sort_tree_node_by_last_commit(&mut root, repositories);
+            }
+            SortMode::Staleness => Self::sort_tree_node_by_staleness(&mut root, repositories),
         }
 
         root
     }
 
+    /// One top-level node per non-empty status group. Groups keep their
+    /// fixed order; the sort mode only orders repositories inside a group.
+    fn build_status_tree(
+        repositories: &[RepositoryState],
+        filter: RepoFilter,
+        effective_sort: SortMode,
+        errors: &RepoErrors,
+    ) -> TreeNode {
+        let mut root = TreeNode::new("Root".to_string(), PathBuf::new());
+
+        for status in STATUS_GROUPS {
+            // Путь-заглушка, чтобы свёрнутые группы не путались с папками
+            let mut node = TreeNode::new(
+                status.label_key().to_string(),
+                PathBuf::from(format!("::{}", status.label_key())),
+            );
+            node.is_group = true;
+            node.repositories = repositories
+                .iter()
+                .enumerate()
+                .filter(|(_, repo)| filter.matches(repo, errors))
+                .filter(|(_, repo)| status.contains(repo, errors))
+                .map(|(idx, repo)| (idx, repo.path.clone()))
+                .collect();
+
+            match effective_sort {
+                SortMode::Path => {}
+                SortMode::Name => Self::sort_tree_node(&mut node, repositories),
+                SortMode::LastCommit => {
+                    Self::sort_tree_node_by_last_commit(&mut node, repositories);
+                }
+                SortMode::Staleness => Self::sort_tree_node_by_staleness(&mut node, repositories),
+            }
+
+            if !node.repositories.is_empty() {
+                root.children.push(node);
+            }
+        }
+
+        root
+    }
+
+    /// Folds everything below `max_depth` into the node at that depth.
+    fn flatten_deep_nodes(node: &mut TreeNode, level: usize, max_depth: usize) {
+        if level >= max_depth && node.depth() > 0 {
+            let mut nested = node.take_nested_repositories();
+            node.repositories.append(&mut nested);
+            node.truncated = true;
+            return;
+        }
+
+        for child in &mut node.children {
+            Self::flatten_deep_nodes(child, level + 1, max_depth);
+        }
+    }
+
+    fn latest_commit_time(node: &TreeNode, repositories: &[RepositoryState]) -> Option<i64> {
+        let own = node
+            .repositories
+            .iter()
+            .filter_map(|(idx, _)| repositories.get(*idx)?.git_info.last_commit_time);
+        let nested = node
+            .children
+            .iter()
+            .filter_map(|child| Self::latest_commit_time(child, repositories));
+
+        own.chain(nested).max()
+    }
+
+    fn sort_tree_node_by_last_commit(node: &mut TreeNode, repositories: &[RepositoryState]) {
+        for child in &mut node.children {
+            Self::sort_tree_node_by_last_commit(child, repositories);
+        }
+
+        node.children
+            .sort_by_key(|child| std::cmp::Reverse(Self::latest_commit_time(child, repositories)));
+
+        node.repositories.sort_by_key(|(idx, _)| {
+            std::cmp::Reverse(
+                repositories
+                    .get(*idx)
+                    .and_then(|repo| repo.git_info.last_commit_time),
+            )
+        });
+    }
+
+    /// The stalest fetch in the folder; `None` when anything in it was
+    /// never fetched.
+    fn stalest_fetch(node: &TreeNode, repositories: &[RepositoryState]) -> Option<SystemTime> {
+        let own = node
+            .repositories
+            .iter()
+            .filter_map(|(idx, _)| repositories.get(*idx))
+            .map(|repo| repo.last_fetch);
+        let nested = node
+            .children
+            .iter()
+            .map(|child| Self::stalest_fetch(child, repositories));
+
+        own.chain(nested).min().flatten()
+    }
+
+    fn sort_tree_node_by_staleness(node: &mut TreeNode, repositories: &[RepositoryState]) {
+        for child in &mut node.children {
+            Self::sort_tree_node_by_staleness(child, repositories);
+        }
+
+        node.children
+            .sort_by_key(|child| Self::stalest_fetch(child, repositories));
+        node.repositories
+            .sort_by_key(|(idx, _)| repositories.get(*idx).and_then(|repo| repo.last_fetch));
+    }
+
     fn sort_tree_node(node: &mut TreeNode, repositories: &[RepositoryState]) {
         node.children.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -116,3 +353,154 @@ impl TreeBuilder {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::AttentionFilter;
+    use std::collections::HashSet;
+
+    fn repos(paths: &[&str]) -> Vec<RepositoryState> {
+        paths
+            .iter()
+            .map(|p| RepositoryState::new(PathBuf::from(p)))
+            .collect()
+    }
+
+    #[test]
+    fn depth_counts_deepest_branch() {
+        let tree = TreeBuilder::build_tree(
+            &repos(&["/a/b/c/r1", "/a/r2"]),
+            RepoFilter::new("", AttentionFilter::All, &HashSet::new()),
+            SortMode::Path,
+            8,
+            GroupMode::ByPath,
+            &RepoErrors::default(),
+        );
+
+        assert_eq!(tree.depth(), 3);
+        assert!(tree.truncated_paths().is_empty());
+    }
+
+    #[test]
+    fn nodes_below_max_depth_are_flattened() {
+        let repositories = repos(&["/a/b/c/d/r1", "/a/b/x/r2", "/a/b/r3", "/a/r4"]);
+        let tree = TreeBuilder::build_tree(
+            &repositories,
+            RepoFilter::new("", AttentionFilter::All, &HashSet::new()),
+            SortMode::Path,
+            2,
+            GroupMode::ByPath,
+            &RepoErrors::default(),
+        );
+
+        let b = &tree.children[0].children[0];
+        assert_eq!(b.name, "b");
+        assert!(b.truncated);
+        assert!(b.children.is_empty());
+        assert_eq!(b.repositories.len(), 3);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.truncated_paths(), vec![PathBuf::from("a/b")]);
+    }
+
+    #[test]
+    fn all_repository_paths_collects_nested_folders() {
+        let repositories = repos(&["/src/services/api", "/src/services/auth/core", "/src/web"]);
+        let tree = TreeBuilder::build_tree(
+            &repositories,
+            RepoFilter::new("", AttentionFilter::All, &HashSet::new()),
+            SortMode::Path,
+            8,
+            GroupMode::ByPath,
+            &RepoErrors::default(),
+        );
+
+        let services = &tree.children[0].children[0];
+        assert_eq!(services.name, "services");
+        let mut paths = services.all_repository_paths();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/src/services/api"),
+                PathBuf::from("/src/services/auth/core"),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_repository_paths_respects_search_filter() {
+        let repositories = repos(&["/src/services/api", "/src/services/auth", "/src/web"]);
+        let tree = TreeBuilder::build_tree(
+            &repositories,
+            RepoFilter::new("auth", AttentionFilter::All, &HashSet::new()),
+            SortMode::Path,
+            8,
+            GroupMode::ByPath,
+            &RepoErrors::default(),
+        );
+
+        assert_eq!(
+            tree.all_repository_paths(),
+            vec![PathBuf::from("/src/services/auth")]
+        );
+    }
+
+    #[test]
+    fn status_groups_list_a_repository_in_every_matching_group() {
+        let mut repositories = repos(&["/w/api", "/w/web", "/w/cli", "/w/broken"]);
+        repositories[0].git_info.ahead = 2;
+        repositories[0].git_info.has_changes = true;
+        repositories[1].git_info.behind = 1;
+        let mut errors = RepoErrors::default();
+        errors.record(PathBuf::from("/w/broken"), "fatal: not a git repository");
+
+        let tree = TreeBuilder::build_tree(
+            &repositories,
+            RepoFilter::new("", AttentionFilter::All, &HashSet::new()),
+            SortMode::Path,
+            8,
+            GroupMode::ByStatus,
+            &errors,
+        );
+
+        let groups: Vec<(&str, Vec<usize>)> = tree
+            .children
+            .iter()
+            .map(|node| {
+                assert!(node.is_group);
+                let indices = node.repositories.iter().map(|(idx, _)| *idx).collect();
+                (node.name.as_str(), indices)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("status_group_behind", vec![1]),
+                ("status_group_ahead", vec![0]),
+                ("status_group_dirty", vec![0]),
+                ("status_group_clean", vec![2]),
+                ("status_group_error", vec![3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_status_groups_are_left_out() {
+        let tree = TreeBuilder::build_tree(
+            &repos(&["/w/api", "/w/web"]),
+            RepoFilter::new("web", AttentionFilter::All, &HashSet::new()),
+            SortMode::Name,
+            8,
+            GroupMode::ByStatus,
+            &RepoErrors::default(),
+        );
+
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].name, "status_group_clean");
+        assert_eq!(
+            tree.children[0].all_repository_paths(),
+            vec![PathBuf::from("/w/web")]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IdentityRule {
+    pub path_prefix: String,
+    pub email: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentityMismatch {
+    pub rule: IdentityRule,
+    pub actual_email: String,
+}
+
+impl IdentityRule {
+    pub fn resolved_prefix(&self, home: Option<&Path>) -> PathBuf {
+        match (self.path_prefix.strip_prefix('~'), home) {
+            (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+            _ => PathBuf::from(&self.path_prefix),
+        }
+    }
+}
+
+/// Finds the most specific rule covering `repo_path` and reports it when the
+/// repository's effective email differs from the expected one. Without an
+/// identity (status not loaded yet, or none configured) nothing is checked.
+pub fn find_identity_mismatch(
+    rules: &[IdentityRule],
+    repo_path: &Path,
+    identity: Option<&(String, String)>,
+    home: Option<&Path>,
+) -> Option<IdentityMismatch> {
+    let rule = rules
+        .iter()
+        .filter(|rule| !rule.path_prefix.trim().is_empty() && !rule.email.trim().is_empty())
+        .map(|rule| (rule, rule.resolved_prefix(home)))
+        .filter(|(_, prefix)| repo_path.starts_with(prefix))
+        .max_by_key(|(_, prefix)| prefix.components().count())
+        .map(|(rule, _)| rule)?;
+
+    let (_, actual_email) = identity?;
+    if actual_email.eq_ignore_ascii_case(rule.email.trim()) {
+        None
+    } else {
+        Some(IdentityMismatch {
+            rule: rule.clone(),
+            actual_email: actual_email.clone(),
+        })
+    }
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(prefix: &str, email: &str) -> IdentityRule {
+        IdentityRule {
+            path_prefix: prefix.to_string(),
+            email: email.to_string(),
+            name: None,
+        }
+    }
+
+    fn identity(email: &str) -> (String, String) {
+        ("Jane".to_string(), email.to_string())
+    }
+
+    #[test]
+    fn repo_outside_any_prefix_is_not_checked() {
+        let rules = vec![rule("/home/jane/work", "jane@corp.example")];
+        let id = identity("jane@home.example");
+
+        assert_eq!(
+            find_identity_mismatch(&rules, Path::new("/home/jane/oss/repo"), Some(&id), None),
+            None
+        );
+    }
+
+    #[test]
+    fn wrong_email_under_prefix_is_reported() {
+        let rules = vec![rule("/home/jane/work", "jane@corp.example")];
+        let id = identity("jane@home.example");
+
+        let mismatch =
+            find_identity_mismatch(&rules, Path::new("/home/jane/work/api"), Some(&id), None)
+                .unwrap();
+        assert_eq!(mismatch.rule.email, "jane@corp.example");
+        assert_eq!(mismatch.actual_email, "jane@home.example");
+    }
+
+    #[test]
+    fn email_comparison_ignores_case() {
+        let rules = vec![rule("/work", "Jane@Corp.example")];
+        let id = identity("jane@corp.example");
+
+        assert_eq!(
+            find_identity_mismatch(&rules, Path::new("/work/api"), Some(&id), None),
+            None
+        );
+    }
+
+    #[test]
+    fn missing_identity_is_not_checked() {
+        let rules = vec![rule("/work", "jane@corp.example")];
+
+        assert_eq!(
+            find_identity_mismatch(&rules, Path::new("/work/api"), None, None),
+            None
+        );
+    }
+
+    #[test]
+    fn most_specific_prefix_wins() {
+        let rules = vec![
+            rule("/work", "jane@corp.example"),
+            rule("/work/client", "jane@client.example"),
+        ];
+        let id = identity("jane@client.example");
+
+        assert_eq!(
+            find_identity_mismatch(&rules, Path::new("/work/client/app"), Some(&id), None),
+            None
+        );
+        assert!(
+            find_identity_mismatch(&rules, Path::new("/work/internal"), Some(&id), None).is_some()
+        );
+    }
+
+    #[test]
+    fn prefix_must_match_whole_components() {
+        let rules = vec![rule("/work", "jane@corp.example")];
+        let id = identity("jane@home.example");
+
+        assert_eq!(
+            find_identity_mismatch(&rules, Path::new("/workshop/app"), Some(&id), None),
+            None
+        );
+    }
+
+    #[test]
+    fn tilde_expands_to_home() {
+        let rules = vec![rule("~/work", "jane@corp.example")];
+        let id = identity("jane@home.example");
+
+        assert!(find_identity_mismatch(
+            &rules,
+            Path::new("/home/jane/work/api"),
+            Some(&id),
+            Some(Path::new("/home/jane")),
+        )
+        .is_some());
+    }
+}
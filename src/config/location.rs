@@ -0,0 +1,198 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Config file in the working directory used by early versions.
+pub const LEGACY_CONFIG_FILE: &str = "repo_manager_config.json";
+
+/// Files and folders kept next to the config, moved along with it.
+pub const CONFIG_SIDE_FILES: [&str; 3] = ["status_cache.json", "audit.jsonl", "snapshots"];
+
+/// Where the config lives on Linux and other XDG systems.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLocation {
+    pub path: PathBuf,
+    /// An older config to copy to `path`, which does not exist yet.
+    pub migrate_from: Option<PathBuf>,
+}
+
+impl ConfigLocation {
+    /// Old and new path of every side file to copy with the config.
+    pub fn side_files(&self) -> Vec<(PathBuf, PathBuf)> {
+        let Some(old) = &self.migrate_from else {
+            return Vec::new();
+        };
+        CONFIG_SIDE_FILES
+            .iter()
+            .map(|name| (old.with_file_name(name), self.path.with_file_name(name)))
+            .collect()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/repo-manager/<file_name>`, or `~/.config/...` when the
+/// variable is unset, empty or relative (as the XDG spec requires). Until
+/// that file exists, the legacy `legacy` file and then the `~/.config` one
+/// (left behind after setting `XDG_CONFIG_HOME`) are migrated into it.
+/// `None` without `HOME` or `XDG_CONFIG_HOME`.
+pub fn resolve_xdg_config_path(
+    env: impl Fn(&str) -> Option<OsString>,
+    exists: impl Fn(&Path) -> bool,
+    legacy: &Path,
+    file_name: &str,
+) -> Option<ConfigLocation> {
+    let fallback = env("HOME")
+        .filter(|home| !home.is_empty())
+        .map(|home| PathBuf::from(home).join(".config"));
+    let config_home = env("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| fallback.clone())?;
+
+    let path = config_home.join("repo-manager").join(file_name);
+    if exists(&path) {
+        return Some(ConfigLocation {
+            path,
+            migrate_from: None,
+        });
+    }
+
+    let old_locations = [
+        Some(legacy.to_path_buf()),
+        fallback.map(|dir| dir.join("repo-manager").join(file_name)),
+    ];
+    let migrate_from = old_locations
+        .into_iter()
+        .flatten()
+        .find(|old| *old != path && exists(old));
+    Some(ConfigLocation { path, migrate_from })
+}
+
+/// Copies a file, or a folder with everything in it.
+pub fn copy_recursively(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return std::fs::copy(from, to).map(|_| ());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(vars: &[(&str, &str)], existing: &[&str]) -> Option<ConfigLocation> {
+        let vars: HashMap<&str, OsString> = vars
+            .iter()
+            .map(|(name, value)| (*name, OsString::from(value)))
+            .collect();
+        resolve_xdg_config_path(
+            |name| vars.get(name).cloned(),
+            |path| existing.iter().any(|existing| Path::new(existing) == path),
+            Path::new(LEGACY_CONFIG_FILE),
+            "config.json",
+        )
+    }
+
+    fn location(path: &str, migrate_from: Option<&str>) -> Option<ConfigLocation> {
+        Some(ConfigLocation {
+            path: PathBuf::from(path),
+            migrate_from: migrate_from.map(PathBuf::from),
+        })
+    }
+
+    #[test]
+    fn xdg_config_home_wins_over_home() {
+        let vars = [("HOME", "/home/me"), ("XDG_CONFIG_HOME", "/home/me/.cfg")];
+
+        assert_eq!(
+            resolve(&vars, &[]),
+            location("/home/me/.cfg/repo-manager/config.json", None)
+        );
+        assert_eq!(
+            resolve(&[("HOME", "/home/me")], &[]),
+            location("/home/me/.config/repo-manager/config.json", None)
+        );
+        // Относительный XDG_CONFIG_HOME спецификация велит игнорировать
+        assert_eq!(
+            resolve(&[("HOME", "/home/me"), ("XDG_CONFIG_HOME", "cfg")], &[]),
+            location("/home/me/.config/repo-manager/config.json", None)
+        );
+        assert_eq!(resolve(&[], &[]), None);
+    }
+
+    #[test]
+    fn old_configs_are_migrated_until_the_new_one_exists() {
+        let vars = [("HOME", "/home/me"), ("XDG_CONFIG_HOME", "/home/me/.cfg")];
+        let new = "/home/me/.cfg/repo-manager/config.json";
+        let home_config = "/home/me/.config/repo-manager/config.json";
+
+        assert_eq!(
+            resolve(&vars, &[LEGACY_CONFIG_FILE, home_config]),
+            location(new, Some(LEGACY_CONFIG_FILE))
+        );
+        assert_eq!(
+            resolve(&vars, &[home_config]),
+            location(new, Some(home_config))
+        );
+        assert_eq!(
+            resolve(&vars, &[new, LEGACY_CONFIG_FILE]),
+            location(new, None)
+        );
+        assert_eq!(
+            resolve(&[("HOME", "/home/me")], &[home_config]),
+            location(home_config, None)
+        );
+    }
+
+    #[test]
+    fn toml_config_is_looked_up_and_migrated_under_its_own_name() {
+        let existing = ["/home/me/.config/repo-manager/config.json"];
+        let exists = |path: &Path| existing.iter().any(|existing| Path::new(existing) == path);
+        let vars = |name: &str| (name == "HOME").then(|| OsString::from("/home/me"));
+
+        // JSON-конфиг не переносится в config.toml
+        assert_eq!(
+            resolve_xdg_config_path(
+                vars,
+                exists,
+                Path::new("repo_manager_config.toml"),
+                "config.toml"
+            ),
+            location("/home/me/.config/repo-manager/config.toml", None)
+        );
+    }
+
+    #[test]
+    fn side_files_move_with_the_config() {
+        let moved = ConfigLocation {
+            path: PathBuf::from("/home/me/.cfg/repo-manager/config.json"),
+            migrate_from: Some(PathBuf::from(LEGACY_CONFIG_FILE)),
+        };
+
+        assert_eq!(
+            moved.side_files(),
+            [
+                (
+                    PathBuf::from("status_cache.json"),
+                    PathBuf::from("/home/me/.cfg/repo-manager/status_cache.json")
+                ),
+                (
+                    PathBuf::from("audit.jsonl"),
+                    PathBuf::from("/home/me/.cfg/repo-manager/audit.jsonl")
+                ),
+                (
+                    PathBuf::from("snapshots"),
+                    PathBuf::from("/home/me/.cfg/repo-manager/snapshots")
+                ),
+            ]
+        );
+        assert!(location("/a/config.json", None)
+            .unwrap()
+            .side_files()
+            .is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use super::Config;
+use crate::workspace::{ensure_unique_workspace_ids, Workspace};
+
+/// Combines a machine-local `primary` config with a shared `secondary` one.
+///
+/// Every setting comes from `primary`. Workspaces from both are kept; for a
+/// name present in both, `primary`'s workspace wins and only gains the
+/// repositories of `secondary`'s workspace it does not list yet.
+pub fn merge_configs(primary: Config, secondary: Config) -> Config {
+    let mut merged = primary;
+
+    for mut shared in secondary.workspaces {
+        shared.from_extra = true;
+        for repo in &mut shared.repositories {
+            repo.from_extra = true;
+        }
+        match Workspace::find_by_name_mut(&mut merged.workspaces, &shared.name) {
+            Some((_, existing)) => {
+                for repo in shared.repositories {
+                    if !existing.repositories.iter().any(|r| r.path == repo.path) {
+                        existing.repositories.push(repo);
+                    }
+                }
+            }
+            None => merged.workspaces.push(shared),
+        }
+    }
+
+    // Один и тот же файл мог быть скопирован на обе стороны
+    ensure_unique_workspace_ids(&mut merged.workspaces);
+    merged
+}
+
+/// `config` without what `merge_configs` took from the secondary file, as
+/// it is written back to the primary one. A workspace from the secondary
+/// file stays only for repositories added to it since. `None` when nothing
+/// came from the secondary file.
+pub fn without_extra_entries(config: &Config) -> Option<Config> {
+    let has_extra = config.workspaces.iter().any(|workspace| {
+        workspace.from_extra || workspace.repositories.iter().any(|repo| repo.from_extra)
+    });
+    if !has_extra {
+        return None;
+    }
+
+    let mut own = config.clone();
+    for workspace in &mut own.workspaces {
+        workspace.repositories.retain(|repo| !repo.from_extra);
+    }
+    own.workspaces
+        .retain(|workspace| !workspace.from_extra || !workspace.repositories.is_empty());
+    Some(own)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn workspace(name: &str, repos: &[&str]) -> Workspace {
+        let mut workspace = Workspace::new(name);
+        for repo in repos {
+            workspace.add_repository(PathBuf::from(repo));
+        }
+        workspace
+    }
+
+    fn repo_paths(workspace: &Workspace) -> Vec<PathBuf> {
+        workspace
+            .repositories
+            .iter()
+            .map(|repo| repo.path.clone())
+            .collect()
+    }
+
+    #[test]
+    fn settings_come_from_primary_and_workspaces_from_both() {
+        let primary = Config {
+            window_width: Some(1440.0),
+            language: "ru".to_string(),
+            workspaces: vec![workspace("Local", &["/home/me/scratch"])],
+            ..Config::default()
+        };
+        let secondary = Config {
+            window_width: Some(800.0),
+            language: "en".to_string(),
+            workspaces: vec![workspace("Shared", &["/srv/api"])],
+            ..Config::default()
+        };
+
+        let merged = merge_configs(primary, secondary);
+
+        assert_eq!(merged.window_width, Some(1440.0));
+        assert_eq!(merged.language, "ru");
+        let names: Vec<&str> = merged.workspaces.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["Local", "Shared"]);
+    }
+
+    #[test]
+    fn same_named_workspace_keeps_primary_and_gains_missing_repos() {
+        let mut primary = Config::default();
+        let mut work = workspace("Work", &["/repos/api", "/repos/web"]);
+        work.color = Some([200, 60, 60]);
+        primary.workspaces.push(work);
+
+        let mut secondary = Config::default();
+        secondary
+            .workspaces
+            .push(workspace("Work", &["/repos/web", "/repos/docs"]));
+
+        let merged = merge_configs(primary, secondary);
+
+        assert_eq!(merged.workspaces.len(), 1);
+        assert_eq!(merged.workspaces[0].color, Some([200, 60, 60]));
+        assert_eq!(
+            repo_paths(&merged.workspaces[0]),
+            [
+                PathBuf::from("/repos/api"),
+                PathBuf::from("/repos/web"),
+                PathBuf::from("/repos/docs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn copied_workspace_ids_are_made_unique() {
+        let shared = workspace("Shared", &["/srv/api"]);
+        let mut primary = Config::default();
+        primary.workspaces.push(shared.clone());
+        let mut renamed = shared;
+        renamed.name = "Shared (old)".to_string();
+        let mut secondary = Config::default();
+        secondary.workspaces.push(renamed);
+
+        let merged = merge_configs(primary, secondary);
+
+        assert_eq!(merged.workspaces.len(), 2);
+        assert_ne!(merged.workspaces[0].id, merged.workspaces[1].id);
+    }
+
+    #[test]
+    fn secondary_entries_are_not_written_to_the_primary_file() {
+        let mut primary = Config::default();
+        primary.workspaces.push(workspace("Work", &["/repos/api"]));
+        let mut secondary = Config::default();
+        secondary
+            .workspaces
+            .push(workspace("Work", &["/repos/api", "/repos/docs"]));
+        secondary
+            .workspaces
+            .push(workspace("Shared", &["/srv/api"]));
+        secondary.workspaces.push(workspace("Team", &["/srv/web"]));
+
+        let mut merged = merge_configs(primary.clone(), Config::default());
+        assert!(without_extra_entries(&merged).is_none());
+
+        merged = merge_configs(primary, secondary);
+        // Репозиторий, добавленный в общую область после загрузки
+        merged.workspaces[2].add_repository(PathBuf::from("/home/me/web-fork"));
+
+        let own = without_extra_entries(&merged).unwrap();
+        let names: Vec<&str> = own.workspaces.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["Work", "Team"]);
+        assert_eq!(
+            repo_paths(&own.workspaces[0]),
+            [PathBuf::from("/repos/api")]
+        );
+        assert_eq!(
+            repo_paths(&own.workspaces[1]),
+            [PathBuf::from("/home/me/web-fork")]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use super::Config;
+use serde_json::{Map, Value};
+
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` upgrades a config from version `n + 1` to `n + 2`.
+const MIGRATIONS: &[Migration] = &[migrate_v1_sort_by_name];
+
+/// Layout of a config file on disk, see `ConfigManager::detect_version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigVersion {
+    /// Written by the old single-file app: workspaces without the fields
+    /// added since (`last_active_workspace_index`, per-workspace sorting,
+    /// repository aliases). Missing fields are filled with their serde
+    /// defaults on load.
+    V1,
+    V2,
+}
+
+/// `V1` when the file is from the old app and should be written back in
+/// the current format. Unparsable input counts as `V2`: there is nothing
+/// to migrate and the regular parse reports the error.
+pub fn detect_config_version(json: &str) -> ConfigVersion {
+    let Ok(Value::Object(config)) = serde_json::from_str::<Value>(json) else {
+        return ConfigVersion::V2;
+    };
+
+    let outdated = config
+        .get("config_version")
+        .and_then(Value::as_u64)
+        .is_none_or(|version| version < CURRENT_CONFIG_VERSION as u64);
+    let legacy_layout =
+        config.contains_key("workspaces") && !config.contains_key("last_active_workspace_index");
+
+    if outdated || legacy_layout {
+        ConfigVersion::V1
+    } else {
+        ConfigVersion::V2
+    }
+}
+
+pub fn default_config_version() -> u32 {
+    1
+}
+
+/// v1 stored sorting as `sort_by_name: bool`, v2 uses `sort_mode`.
+fn migrate_v1_sort_by_name(config: &mut Map<String, Value>) {
+    let sort_by_name = config
+        .remove("sort_by_name")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    if sort_by_name && !config.contains_key("sort_mode") {
+        config.insert("sort_mode".to_string(), Value::String("Name".to_string()));
+    }
+}
+
+pub fn migrate_config_value(mut value: Value) -> Result<Value, String> {
+    let config = value
+        .as_object_mut()
+        .ok_or_else(|| "Config root must be an object".to_string())?;
+
+    let version = match config.get("config_version") {
+        None => default_config_version(),
+        Some(raw) => raw
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| format!("Invalid config_version: {}", raw))?,
+    };
+
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(format!(
+            "Config version {} is newer than supported version {}",
+            version, CURRENT_CONFIG_VERSION
+        ));
+    }
+
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(config);
+    }
+
+    config.insert(
+        "config_version".to_string(),
+        Value::from(CURRENT_CONFIG_VERSION),
+    );
+
+    Ok(value)
+}
+
+pub fn config_from_value(value: Value) -> Result<Config, Box<dyn std::error::Error>> {
+    let migrated = migrate_config_value(value)?;
+    Ok(serde_json::from_value(migrated)?)
+}
+
+pub fn parse_config_json(content: &str) -> Result<Config, Box<dyn std::error::Error>> {
+    config_from_value(serde_json::from_str(content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SortMode;
+    use serde_json::json;
+
+    #[test]
+    fn migration_table_reaches_current_version() {
+        assert_eq!(MIGRATIONS.len() as u32 + 1, CURRENT_CONFIG_VERSION);
+    }
+
+    #[test]
+    fn unversioned_sort_by_name_becomes_sort_mode() {
+        let config = parse_config_json(r#"{"sort_by_name": true, "workspaces": []}"#).unwrap();
+
+        assert_eq!(config.sort_mode, SortMode::Name);
+        assert_eq!(config.config_version, CURRENT_CONFIG_VERSION);
+    }
+
+    #[test]
+    fn v1_without_sorting_keeps_default_mode() {
+        let migrated =
+            migrate_config_value(json!({"config_version": 1, "sort_by_name": false})).unwrap();
+
+        assert_eq!(migrated.get("sort_by_name"), None);
+        assert_eq!(migrated.get("sort_mode"), None);
+        assert_eq!(migrated["config_version"], json!(CURRENT_CONFIG_VERSION));
+    }
+
+    #[test]
+    fn current_version_is_left_untouched() {
+        let input = json!({"config_version": CURRENT_CONFIG_VERSION, "sort_mode": "LastCommit"});
+        let config = config_from_value(input).unwrap();
+
+        assert_eq!(config.sort_mode, SortMode::LastCommit);
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let err = migrate_config_value(json!({"config_version": CURRENT_CONFIG_VERSION + 1}))
+            .unwrap_err();
+        assert!(err.contains("newer"));
+    }
+
+    #[test]
+    fn legacy_workspaces_are_detected_as_v1() {
+        let legacy = r#"{"workspaces": [{"name": "Work", "repositories": [{"path": "/r/api"}]}]}"#;
+        assert_eq!(detect_config_version(legacy), ConfigVersion::V1);
+
+        let config = parse_config_json(legacy).unwrap();
+        assert_eq!(config.workspaces[0].sort_mode, None);
+        assert_eq!(config.last_active_workspace_index, None);
+
+        let saved = serde_json::to_string(&config).unwrap();
+        assert_eq!(detect_config_version(&saved), ConfigVersion::V2);
+    }
+
+    #[test]
+    fn old_version_number_is_detected_as_v1() {
+        let json = r#"{"config_version": 1, "last_active_workspace_index": 0, "workspaces": []}"#;
+        assert_eq!(detect_config_version(json), ConfigVersion::V1);
+        assert_eq!(detect_config_version("not json"), ConfigVersion::V2);
+    }
+
+    #[test]
+    fn invalid_version_is_rejected() {
+        assert!(migrate_config_value(json!({"config_version": 0})).is_err());
+        assert!(migrate_config_value(json!({"config_version": "two"})).is_err());
+        assert!(migrate_config_value(json!([1, 2, 3])).is_err());
+    }
+}
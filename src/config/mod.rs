@@ -1,7 +1,365 @@
-use std::path::PathBuf;
+pub mod identity;
+pub mod location;
+pub mod merge;
+pub mod migration;
+pub mod portable;
+pub mod saver;
+pub mod status_cache;
+pub mod transfer;
+
+pub use identity::*;
+pub use location::*;
+pub use merge::*;
+pub use migration::*;
+pub use portable::*;
+pub use saver::*;
+pub use status_cache::*;
+pub use transfer::*;
+
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        Self::from_extension(path).unwrap_or(ConfigFormat::Json)
+    }
+
+    /// `None` unless the file ends in `.json` or `.toml`.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        Self::from_name(path.extension()?.to_str()?)
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigOverride {
+    pub path: Option<PathBuf>,
+    pub format: ConfigFormat,
+    /// `--portable`: same as a `portable.marker` next to the executable.
+    pub portable: bool,
+    /// `--config-extra`: shared config merged into the main one on load.
+    pub extra: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIG_OVERRIDE: Mutex<Option<ConfigOverride>> = Mutex::new(None);
+    static ref PORTABLE_DIR: Option<PathBuf> =
+        executable_dir().filter(|dir| has_portable_marker(dir));
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortMode {
+    #[default]
+    Path,
+    Name,
+    LastCommit,
+    /// Never-fetched repositories first, then the longest since a fetch.
+    Staleness,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 4] = [
+        SortMode::Path,
+        SortMode::Name,
+        SortMode::LastCommit,
+        SortMode::Staleness,
+    ];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            SortMode::Path => "sort_path",
+            SortMode::Name => "sort_name",
+            SortMode::LastCommit => "sort_last_commit",
+            SortMode::Staleness => "sort_staleness",
+        }
+    }
+}
+
+/// Repositories shown as the folder tree or as a flat table.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ViewMode {
+    #[default]
+    Tree,
+    List,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 2] = [ViewMode::Tree, ViewMode::List];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            ViewMode::Tree => "view_tree",
+            ViewMode::List => "view_list",
+        }
+    }
+}
+
+/// Row height of the repository list; Comfortable adds the description
+/// line under the name.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RowDensity {
+    Compact,
+    #[default]
+    Comfortable,
+}
+
+impl RowDensity {
+    pub const ALL: [RowDensity; 2] = [RowDensity::Compact, RowDensity::Comfortable];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            RowDensity::Compact => "density_compact",
+            RowDensity::Comfortable => "density_comfortable",
+        }
+    }
+}
+
+/// How the tree groups repositories: by folder or into status groups.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GroupMode {
+    #[default]
+    ByPath,
+    ByStatus,
+}
+
+impl GroupMode {
+    pub const ALL: [GroupMode; 2] = [GroupMode::ByPath, GroupMode::ByStatus];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            GroupMode::ByPath => "group_by_path",
+            GroupMode::ByStatus => "group_by_status",
+        }
+    }
+
+    /// The mode after this one, for the toolbar button that cycles them.
+    pub fn next(self) -> GroupMode {
+        let position = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(position + 1) % Self::ALL.len()]
+    }
+}
+
+/// How picking an entry in the branch dropdown switches branches.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BranchSwitchConfirmation {
+    /// Checkout right away, unless the working tree has changes.
+    #[default]
+    Click,
+    /// Picking only highlights the branch; "Switch" in the dropdown footer
+    /// does the checkout.
+    Confirm,
+}
+
+impl BranchSwitchConfirmation {
+    pub const ALL: [BranchSwitchConfirmation; 2] = [
+        BranchSwitchConfirmation::Click,
+        BranchSwitchConfirmation::Confirm,
+    ];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            BranchSwitchConfirmation::Click => "branch_switch_click",
+            BranchSwitchConfirmation::Confirm => "branch_switch_confirm",
+        }
+    }
+
+    /// Uncommitted changes always ask first: a checkout may carry them over
+    /// to the other branch or fail halfway.
+    pub fn requires_confirmation(self, has_changes: bool) -> bool {
+        self == BranchSwitchConfirmation::Confirm || has_changes
+    }
+}
+
+/// One element of the repository row's status cell.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowElement {
+    SyncSpinner,
+    PullBadge,
+    PushBadge,
+    ErrorIndicator,
+    ChangesIndicator,
+    BranchDelta,
+    LastCommitAge,
+    /// Latest tag, with the `git describe` string in its tooltip.
+    LatestTag,
+}
+
+impl RowElement {
+    pub const ALL: [RowElement; 8] = [
+        RowElement::SyncSpinner,
+        RowElement::PullBadge,
+        RowElement::PushBadge,
+        RowElement::ErrorIndicator,
+        RowElement::ChangesIndicator,
+        RowElement::BranchDelta,
+        RowElement::LastCommitAge,
+        RowElement::LatestTag,
+    ];
+
+    /// The status cell as it looked before it became configurable.
+    pub fn default_row() -> Vec<RowElement> {
+        vec![
+            RowElement::SyncSpinner,
+            RowElement::PullBadge,
+            RowElement::PushBadge,
+            RowElement::ErrorIndicator,
+            RowElement::ChangesIndicator,
+        ]
+    }
+
+    /// Elements not present in `row`, in `ALL` order.
+    pub fn hidden(row: &[RowElement]) -> Vec<RowElement> {
+        Self::ALL
+            .into_iter()
+            .filter(|element| !row.contains(element))
+            .collect()
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            RowElement::SyncSpinner => "row_sync_spinner",
+            RowElement::PullBadge => "row_pull_badge",
+            RowElement::PushBadge => "row_push_badge",
+            RowElement::ErrorIndicator => "row_error_indicator",
+            RowElement::ChangesIndicator => "row_changes_indicator",
+            RowElement::BranchDelta => "row_branch_delta",
+            RowElement::LastCommitAge => "row_last_commit_age",
+            RowElement::LatestTag => "row_latest_tag",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CredentialHelper {
+    /// Leave `credential.helper` to the user's git configuration.
+    #[default]
+    Default,
+    /// Plaintext `~/.git-credentials`; discouraged.
+    Store,
+    Cache {
+        timeout_secs: u64,
+    },
+    /// git-credential-manager.
+    Manager,
+    Custom(String),
+}
+
+impl CredentialHelper {
+    pub fn presets() -> [CredentialHelper; 5] {
+        [
+            CredentialHelper::Default,
+            CredentialHelper::Store,
+            CredentialHelper::Cache { timeout_secs: 900 },
+            CredentialHelper::Manager,
+            CredentialHelper::Custom(String::new()),
+        ]
+    }
+
+    pub fn same_kind(&self, other: &CredentialHelper) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            CredentialHelper::Default => "credential_default",
+            CredentialHelper::Store => "credential_store",
+            CredentialHelper::Cache { .. } => "credential_cache",
+            CredentialHelper::Manager => "credential_manager",
+            CredentialHelper::Custom(_) => "credential_custom",
+        }
+    }
+
+    /// Value for `git config credential.helper`, `None` for `Default`.
+    pub fn config_value(&self) -> Option<String> {
+        match self {
+            CredentialHelper::Default => None,
+            CredentialHelper::Store => Some("store".to_string()),
+            CredentialHelper::Cache { timeout_secs } => {
+                Some(format!("cache --timeout={}", timeout_secs))
+            }
+            CredentialHelper::Manager => Some("manager".to_string()),
+            CredentialHelper::Custom(value) => Some(value.trim().to_string()),
+        }
+    }
+}
+
+/// Force pushes kept in `Config::force_push_history`.
+pub const FORCE_PUSH_HISTORY_LIMIT: usize = 200;
+
+/// Audit trail entry of one force push.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ForcePushRecord {
+    pub repo_name: String,
+    pub branch: String,
+    pub reason: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Upstream commit that was overwritten; `None` for a new remote branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_before: Option<String>,
+    pub commit_after: String,
+}
+
+/// View state remembered between runs that is not a setting.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UiState {
+    /// Active quick filter chips, keyed by the workspace id as a string so
+    /// the TOML format can store it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub quick_filters: BTreeMap<String, BTreeSet<crate::app::RepoPredicate>>,
+}
+
+impl UiState {
+    pub fn quick_filters(
+        &self,
+        workspace: crate::workspace::WorkspaceId,
+    ) -> HashSet<crate::app::RepoPredicate> {
+        self.quick_filters
+            .get(&workspace.0.to_string())
+            .map(|chips| chips.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_quick_filters(
+        &mut self,
+        workspace: crate::workspace::WorkspaceId,
+        chips: &HashSet<crate::app::RepoPredicate>,
+    ) {
+        let key = workspace.0.to_string();
+        if chips.is_empty() {
+            self.quick_filters.remove(&key);
+        } else {
+            self.quick_filters
+                .insert(key, chips.iter().copied().collect());
+        }
+    }
+}
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct Config {
+    #[serde(default = "default_config_version")]
+    pub config_version: u32,
     #[serde(default)]
     pub workspaces: Vec<crate::workspace::Workspace>,
     #[serde(default)]
@@ -11,17 +369,197 @@ pub struct Config {
     #[serde(default = "default_sidebar_width")]
     pub sidebar_width: f32,
     #[serde(default)]
-    pub sort_by_name: bool,
+    pub sort_mode: SortMode,
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    #[serde(default)]
+    pub group_mode: GroupMode,
+    #[serde(default)]
+    pub row_density: RowDensity,
     #[serde(default)]
     pub last_active_workspace_index: Option<usize>,
     #[serde(default = "default_language")]
     pub language: String,
+    #[serde(default)]
+    pub expected_identities: Vec<IdentityRule>,
+    #[serde(default)]
+    pub credential_helper: CredentialHelper,
+    #[serde(default = "default_max_tree_depth")]
+    pub max_tree_depth: u8,
+    #[serde(default)]
+    pub auto_show_digest: bool,
+    /// 30-day commit-activity sparkline next to repository names.
+    #[serde(default = "default_true")]
+    pub show_activity_sparkline: bool,
+    #[serde(default)]
+    pub confirm_bulk_actions: bool,
+    #[serde(default = "default_scan_threads")]
+    pub scan_threads: u8,
+    /// Scans enter symlinked folders; off by default since links often
+    /// point back into the tree being scanned.
+    #[serde(default)]
+    pub follow_symlinks_in_scan: bool,
+    /// Scan cost per top-level folder measured by the first scan; the
+    /// scan time estimate uses `DEFAULT_MS_PER_DIR` until then.
+    #[serde(default)]
+    pub actual_ms_per_dir: Option<f32>,
+    /// Scans finding more than `SCAN_PREVIEW_MIN_REPOS` repositories list
+    /// them for review instead of adding everything.
+    #[serde(default = "default_true")]
+    pub confirm_scan_results: bool,
+    /// Scans with more repositories than this, spread over several
+    /// folders, offer one workspace per folder; 0 turns the offer off.
+    #[serde(default = "default_workspace_split_threshold")]
+    pub workspace_split_threshold: usize,
+    /// Removing a workspace with this many repositories requires typing
+    /// its name; 0 only asks for a plain confirmation.
+    #[serde(default = "default_workspace_removal_typed_threshold")]
+    pub workspace_removal_typed_threshold: usize,
+    /// Visible status-cell elements, in display order.
+    #[serde(default = "RowElement::default_row")]
+    pub row_elements: Vec<RowElement>,
+    #[serde(default = "default_branch_list_limit")]
+    pub branch_list_limit: usize,
+    /// Log panel entries kept in memory.
+    #[serde(default = "default_log_capacity")]
+    pub log_capacity: usize,
+    /// Repositories shown above every workspace's tree, wherever they live.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub bookmarks: HashSet<PathBuf>,
+    /// Window of the virtual "Recent" workspace, in days.
+    #[serde(default = "default_recent_days")]
+    pub recent_days: u32,
+    /// External diff command run in the repository; `{path}` is replaced
+    /// with the repository path.
+    #[serde(default = "default_difftool_command")]
+    pub difftool_command: String,
+    /// Portable mode: repository paths under this directory are stored
+    /// relative to it. Empty means the root of the executable's drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path_root: String,
+    #[serde(default)]
+    pub branch_switch_confirmation: BranchSwitchConfirmation,
+    /// Never offered by "Delete all merged branches".
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+    /// After being minimized or in the background this long, the active
+    /// workspace is refreshed once when the window comes back.
+    #[serde(default = "default_catch_up_minutes")]
+    pub catch_up_minutes: u32,
+    /// Fetch All fetches a parent repository with its submodules and skips
+    /// the submodules registered separately in the same workspace.
+    #[serde(default = "default_true")]
+    pub fetch_submodules_with_parent: bool,
+    /// `--depth` for fetches of shallow clones; `None` fetches full history.
+    #[serde(default)]
+    pub default_fetch_depth: Option<u32>,
+    /// "Wait and close when done" gives up after this many seconds.
+    #[serde(default = "default_shutdown_wait_secs")]
+    pub shutdown_wait_secs: u32,
+    /// Hover time before a tooltip appears.
+    #[serde(default = "default_tooltip_delay_ms")]
+    pub tooltip_delay_ms: u64,
+    /// Pointer movement within one frame, in points, above which tooltips
+    /// are hidden until the pointer stops; smaller jitter keeps them.
+    #[serde(default = "default_tooltip_threshold_px")]
+    pub tooltip_on_hover_threshold_px: f32,
+    /// Git binary to run instead of the one found on PATH.
+    #[serde(default)]
+    pub git_executable: Option<PathBuf>,
+    /// Self-hosted or enterprise hosts and the provider they run, e.g.
+    /// `"ghe.mycorp.com" = "github"`; public hosts are recognised by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_hosts: BTreeMap<String, crate::git::HostingProvider>,
+    /// The close button hides the window to the tray; Quit in the tray
+    /// menu exits. Ignored where there is no tray icon.
+    #[serde(default)]
+    pub close_to_tray: bool,
+    /// Ask for a reason before every force push.
+    #[serde(default = "default_true")]
+    pub force_push_require_reason: bool,
+    /// Newest last, capped at `FORCE_PUSH_HISTORY_LIMIT`.
+    #[serde(default, skip_serializing_if = "VecDeque::is_empty")]
+    pub force_push_history: VecDeque<ForcePushRecord>,
+    #[serde(default)]
+    pub ui_state: UiState,
+    /// Repositories the user chose to keep in several workspaces, by
+    /// `duplicate_key`; they are left out of the duplicates report.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub dismissed_duplicates: BTreeSet<PathBuf>,
+}
+
+impl Config {
+    pub fn record_force_push(&mut self, record: ForcePushRecord) {
+        self.force_push_history.push_back(record);
+        while self.force_push_history.len() > FORCE_PUSH_HISTORY_LIMIT {
+            self.force_push_history.pop_front();
+        }
+    }
 }
 
 fn default_sidebar_width() -> f32 {
     250.0
 }
 
+fn default_scan_threads() -> u8 {
+    4
+}
+
+fn default_workspace_split_threshold() -> usize {
+    30
+}
+
+fn default_workspace_removal_typed_threshold() -> usize {
+    20
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_catch_up_minutes() -> u32 {
+    15
+}
+
+fn default_shutdown_wait_secs() -> u32 {
+    120
+}
+
+fn default_tooltip_delay_ms() -> u64 {
+    500
+}
+
+fn default_tooltip_threshold_px() -> f32 {
+    2.0
+}
+
+fn default_protected_branches() -> Vec<String> {
+    crate::git::DEFAULT_PROTECTED_BRANCHES
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn default_difftool_command() -> String {
+    crate::app::DEFAULT_DIFFTOOL_COMMAND.to_string()
+}
+
+fn default_recent_days() -> u32 {
+    7
+}
+
+fn default_log_capacity() -> usize {
+    crate::logging::DEFAULT_LOG_CAPACITY
+}
+
+fn default_branch_list_limit() -> usize {
+    crate::git::DEFAULT_BRANCH_LIST_LIMIT
+}
+
+fn default_max_tree_depth() -> u8 {
+    8
+}
+
 fn default_language() -> String {
     "en".to_string()
 }
@@ -29,13 +567,51 @@ fn default_language() -> String {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            config_version: CURRENT_CONFIG_VERSION,
             workspaces: Vec::new(),
             window_width: None,
             window_height: None,
             sidebar_width: 250.0,
-            sort_by_name: false,
+            sort_mode: SortMode::Path,
+            view_mode: ViewMode::Tree,
+            row_density: RowDensity::Comfortable,
+            group_mode: GroupMode::ByPath,
             last_active_workspace_index: None,
             language: "en".to_string(),
+            expected_identities: Vec::new(),
+            credential_helper: CredentialHelper::Default,
+            max_tree_depth: 8,
+            auto_show_digest: false,
+            show_activity_sparkline: true,
+            confirm_bulk_actions: false,
+            scan_threads: 4,
+            follow_symlinks_in_scan: false,
+            actual_ms_per_dir: None,
+            confirm_scan_results: true,
+            workspace_split_threshold: 30,
+            workspace_removal_typed_threshold: 20,
+            row_elements: RowElement::default_row(),
+            branch_list_limit: crate::git::DEFAULT_BRANCH_LIST_LIMIT,
+            log_capacity: crate::logging::DEFAULT_LOG_CAPACITY,
+            bookmarks: HashSet::new(),
+            recent_days: 7,
+            difftool_command: default_difftool_command(),
+            path_root: String::new(),
+            branch_switch_confirmation: BranchSwitchConfirmation::Click,
+            protected_branches: default_protected_branches(),
+            catch_up_minutes: 15,
+            fetch_submodules_with_parent: true,
+            default_fetch_depth: None,
+            shutdown_wait_secs: 120,
+            tooltip_delay_ms: 500,
+            tooltip_on_hover_threshold_px: 2.0,
+            git_executable: None,
+            provider_hosts: BTreeMap::new(),
+            close_to_tray: false,
+            force_push_require_reason: true,
+            force_push_history: VecDeque::new(),
+            ui_state: UiState::default(),
+            dismissed_duplicates: BTreeSet::new(),
         }
     }
 }
@@ -43,8 +619,100 @@ impl Default for Config {
 pub struct ConfigManager;
 
 impl ConfigManager {
+    /// Redirects all load/save operations, e.g. from `--config-file` / `--config-format`.
+    pub fn set_override(config_override: ConfigOverride) {
+        if let Ok(mut current) = CONFIG_OVERRIDE.lock() {
+            *current = Some(config_override);
+        }
+    }
+
+    pub fn detect_version(json: &str) -> ConfigVersion {
+        detect_config_version(json)
+    }
+
+    fn current_override() -> Option<ConfigOverride> {
+        CONFIG_OVERRIDE
+            .lock()
+            .ok()
+            .and_then(|current| current.clone())
+    }
+
+    pub fn config_format() -> ConfigFormat {
+        Self::current_override()
+            .map(|o| o.format)
+            .unwrap_or(ConfigFormat::Json)
+    }
+
+    /// Directory holding the config in portable mode, i.e. the one with the
+    /// executable.
+    pub fn portable_dir() -> Option<PathBuf> {
+        if let Some(dir) = PORTABLE_DIR.as_ref() {
+            return Some(dir.clone());
+        }
+        Self::current_override()
+            .filter(|o| o.portable)
+            .and_then(|_| executable_dir())
+    }
+
+    pub fn is_portable() -> bool {
+        Self::portable_dir().is_some()
+    }
+
     pub fn get_config_file_path() -> PathBuf {
-        let legacy_config = PathBuf::from("repo_manager_config.json");
+        let base_path = match Self::portable_dir() {
+            Some(dir) => dir.join("config.json"),
+            None => Self::default_config_file_path(),
+        };
+
+        if let Some(config_override) = Self::current_override() {
+            if let Some(path) = config_override.path {
+                return path;
+            }
+            if config_override.format == ConfigFormat::Toml {
+                return base_path.with_extension("toml");
+            }
+        }
+
+        base_path
+    }
+
+    fn default_config_file_path() -> PathBuf {
+        let legacy_config = PathBuf::from(LEGACY_CONFIG_FILE);
+
+        // Рабочий каталог зависит от способа запуска — на Linux только XDG
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            // --config-format toml ищет и переносит config.toml, а не JSON
+            let extension = Self::config_format().extension();
+            if let Some(location) = resolve_xdg_config_path(
+                |name| std::env::var_os(name),
+                |path| path.exists(),
+                &legacy_config.with_extension(extension),
+                &format!("config.{}", extension),
+            ) {
+                if let Some(dir) = location.path.parent() {
+                    let _ = std::fs::create_dir_all(dir);
+                }
+                if let Some(old) = &location.migrate_from {
+                    match std::fs::copy(old, &location.path) {
+                        Ok(_) => println!("Config copied from {:?} to {:?}", old, location.path),
+                        Err(e) => {
+                            eprintln!("Failed to copy config from {:?}: {}", old, e);
+                            return old.clone();
+                        }
+                    }
+                }
+                for (old, new) in location.side_files() {
+                    if old.exists() && !new.exists() {
+                        if let Err(e) = copy_recursively(&old, &new) {
+                            eprintln!("Failed to copy {:?} to {:?}: {}", old, new, e);
+                        }
+                    }
+                }
+                return location.path;
+            }
+        }
+
         if legacy_config.exists() {
             println!("Using legacy config location: {:?}", legacy_config);
             return legacy_config;
@@ -84,13 +752,63 @@ impl ConfigManager {
     }
 
     pub fn load() -> Config {
+        // Дополнительный файл только читается: сохранение пишет в основной
+        let mut config = match Self::current_override().and_then(|o| o.extra) {
+            Some(extra) => merge_configs(Self::load_file(), Self::load_extra(&extra)),
+            None => Self::load_file(),
+        };
+        if let Some(dir) = Self::portable_dir() {
+            let root = effective_path_root(&config, &dir);
+            resolve_repository_paths(&mut config, &root);
+        }
+        for workspace in &mut config.workspaces {
+            let removed = workspace.remove_duplicates();
+            if removed > 0 {
+                println!(
+                    "Removed {} duplicate entries from workspace {:?}",
+                    removed, workspace.name
+                );
+            }
+        }
+        config
+    }
+
+    /// The `--config-extra` file; one that cannot be read counts as the
+    /// default config. A V1 file is read as is and never rewritten.
+    fn load_extra(path: &Path) -> Config {
+        Self::read_file(path).unwrap_or_else(|e| {
+            eprintln!("Config {:?} not loaded: {}", path, e);
+            Config::default()
+        })
+    }
+
+    fn load_file() -> Config {
         let config_path = Self::get_config_file_path();
         println!("Looking for config at: {:?}", config_path);
 
+        if Self::config_format() == ConfigFormat::Toml {
+            return match Self::load_from_toml(&config_path) {
+                Ok(config) => {
+                    println!("Config loaded successfully from: {:?}", config_path);
+                    config
+                }
+                Err(e) => {
+                    println!("TOML config not loaded ({}), using defaults", e);
+                    Config::default()
+                }
+            };
+        }
+
         if let Ok(content) = std::fs::read_to_string(&config_path) {
             println!("Config loaded successfully from: {:?}", config_path);
-            if let Ok(config) = serde_json::from_str::<Config>(&content) {
-                return config;
+            match parse_config_json(&content) {
+                Ok(config) => {
+                    if Self::detect_version(&content) == ConfigVersion::V1 {
+                        Self::write_migrated(&config);
+                    }
+                    return config;
+                }
+                Err(e) => eprintln!("Failed to parse config: {}", e),
             }
         } else {
             println!("Config file not found, using defaults");
@@ -99,13 +817,192 @@ impl ConfigManager {
         Config::default()
     }
 
+    /// Rewrites a V1 file in the current format, keeping the original as a
+    /// backup next to it.
+    fn write_migrated(config: &Config) {
+        if let Err(e) = Self::backup_config_file() {
+            eprintln!("Failed to back up config before migration: {}", e);
+            return;
+        }
+        match Self::save(config) {
+            Ok(()) => println!("Migrated config from V1 to V2"),
+            Err(e) => eprintln!("Failed to write migrated config: {}", e),
+        }
+    }
+
     pub fn save(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-        let content = serde_json::to_string_pretty(config)?;
+        let portable_config;
+        let config = match Self::portable_dir() {
+            Some(dir) => {
+                let mut relative = config.clone();
+                relativize_repository_paths(&mut relative, &effective_path_root(config, &dir));
+                portable_config = relative;
+                &portable_config
+            }
+            None => config,
+        };
+
+        // Записи из --config-extra остаются только в своём файле
+        let own_config = without_extra_entries(config);
+        let config = own_config.as_ref().unwrap_or(config);
+
         let config_path = Self::get_config_file_path();
+        if Self::config_format() == ConfigFormat::Toml {
+            return Self::save_as_toml(config, &config_path);
+        }
+
+        let content = serde_json::to_string_pretty(config)?;
 
         std::fs::write(&config_path, content)?;
         println!("Config saved to: {:?}", config_path);
 
         Ok(())
     }
+
+    pub fn save_as_toml(config: &Config, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = toml::to_string_pretty(config)?;
+        std::fs::write(path, content)?;
+        println!("Config saved to: {:?}", path);
+
+        Ok(())
+    }
+
+    /// Reads a config file in the format its extension names; without a
+    /// `.json` or `.toml` extension, in the `--config-format` one.
+    pub fn read_file(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        match ConfigFormat::from_extension(path).unwrap_or_else(Self::config_format) {
+            ConfigFormat::Toml => Self::load_from_toml(path),
+            ConfigFormat::Json => parse_config_json(&std::fs::read_to_string(path)?),
+        }
+    }
+
+    pub fn load_from_toml(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let value = serde_json::to_value(toml::from_str::<toml::Value>(&content)?)?;
+        config_from_value(value)
+    }
+
+    /// Copies the current config file aside before it gets replaced.
+    pub fn backup_config_file() -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+        let config_path = Self::get_config_file_path();
+        if !config_path.exists() {
+            return Ok(None);
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let mut backup_name = config_path.file_name().unwrap_or_default().to_os_string();
+        backup_name.push(format!(".bak-{}", timestamp));
+        let backup_path = config_path.with_file_name(backup_name);
+
+        std::fs::copy(&config_path, &backup_path)?;
+        println!("Config backed up to: {:?}", backup_path);
+
+        Ok(Some(backup_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::Workspace;
+
+    #[test]
+    fn dirty_repositories_always_confirm_branch_switch() {
+        let click = BranchSwitchConfirmation::Click;
+        let confirm = BranchSwitchConfirmation::Confirm;
+
+        assert!(!click.requires_confirmation(false));
+        assert!(click.requires_confirmation(true));
+        assert!(confirm.requires_confirmation(false));
+        assert_eq!(Config::default().branch_switch_confirmation, click);
+    }
+
+    #[test]
+    fn force_push_history_keeps_the_newest_records() {
+        let mut config = Config::default();
+        assert!(config.force_push_require_reason);
+
+        for i in 0..FORCE_PUSH_HISTORY_LIMIT + 5 {
+            config.record_force_push(ForcePushRecord {
+                repo_name: "api".to_string(),
+                branch: "feature".to_string(),
+                reason: format!("rebase #{}", i),
+                timestamp: i as u64,
+                commit_before: Some("aaa".to_string()),
+                commit_after: "bbb".to_string(),
+            });
+        }
+
+        assert_eq!(config.force_push_history.len(), FORCE_PUSH_HISTORY_LIMIT);
+        assert_eq!(config.force_push_history.front().unwrap().timestamp, 5);
+        assert_eq!(
+            config.force_push_history.back().unwrap().reason,
+            format!("rebase #{}", FORCE_PUSH_HISTORY_LIMIT + 4)
+        );
+
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.force_push_history, config.force_push_history);
+    }
+
+    #[test]
+    fn default_row_matches_fixed_status_cell() {
+        assert_eq!(
+            Config::default().row_elements,
+            vec![
+                RowElement::SyncSpinner,
+                RowElement::PullBadge,
+                RowElement::PushBadge,
+                RowElement::ErrorIndicator,
+                RowElement::ChangesIndicator,
+            ]
+        );
+
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.row_elements, RowElement::default_row());
+    }
+
+    #[test]
+    fn hidden_elements_are_the_ones_missing_from_the_row() {
+        let row = vec![RowElement::ChangesIndicator, RowElement::PullBadge];
+
+        assert_eq!(
+            RowElement::hidden(&row),
+            vec![
+                RowElement::SyncSpinner,
+                RowElement::PushBadge,
+                RowElement::ErrorIndicator,
+                RowElement::BranchDelta,
+                RowElement::LastCommitAge,
+                RowElement::LatestTag,
+            ]
+        );
+    }
+
+    #[test]
+    fn toml_round_trip_preserves_workspaces() {
+        let mut config = Config::default();
+        let mut workspace = Workspace::new("Work");
+        workspace.add_repository(PathBuf::from("/tmp/repos/alpha"));
+        config.workspaces.push(workspace);
+        config.window_width = Some(1024.0);
+        config.language = "ru".to_string();
+
+        let path = std::env::temp_dir().join("repo_manager_toml_round_trip.toml");
+        ConfigManager::save_as_toml(&config, &path).unwrap();
+        let loaded = ConfigManager::load_from_toml(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.workspaces.len(), 1);
+        assert_eq!(loaded.workspaces[0].name, "Work");
+        assert_eq!(
+            loaded.workspaces[0].repositories[0].path,
+            PathBuf::from("/tmp/repos/alpha")
+        );
+        assert_eq!(loaded.window_width, Some(1024.0));
+        assert_eq!(loaded.window_height, None);
+        assert_eq!(loaded.language, "ru");
+    }
 }
@@ -0,0 +1,265 @@
+use super::Config;
+use std::path::{Path, PathBuf};
+
+/// A file with this name next to the executable turns on portable mode.
+pub const PORTABLE_MARKER: &str = "portable.marker";
+
+/// Directory of the running executable.
+pub fn executable_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+pub fn has_portable_marker(dir: &Path) -> bool {
+    dir.join(PORTABLE_MARKER).is_file()
+}
+
+/// A path taken apart without asking the host OS, so Windows paths are
+/// handled the same way on every platform.
+struct PathParts<'a> {
+    /// Lowercased drive letter of `C:\...`.
+    drive: Option<char>,
+    absolute: bool,
+    parts: Vec<&'a str>,
+}
+
+fn split_path(path: &str) -> PathParts<'_> {
+    // Путь вида \\?\C:\... возвращает current_exe на Windows
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+
+    let mut chars = path.chars();
+    let (drive, rest) = match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => {
+            (Some(letter.to_ascii_lowercase()), &path[2..])
+        }
+        _ => (None, path),
+    };
+
+    PathParts {
+        drive,
+        absolute: rest.starts_with(['/', '\\']),
+        parts: rest
+            .split(['/', '\\'])
+            .filter(|part| !part.is_empty() && *part != ".")
+            .collect(),
+    }
+}
+
+fn same_part(a: &str, b: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        a.to_lowercase() == b.to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+/// Root of the drive holding `exe_dir`: `E:\` on Windows, `/` elsewhere.
+pub fn default_path_root(exe_dir: &Path) -> PathBuf {
+    match split_path(&exe_dir.to_string_lossy()).drive {
+        Some(drive) => PathBuf::from(format!("{}:\\", drive.to_ascii_uppercase())),
+        None => PathBuf::from("/"),
+    }
+}
+
+/// `path` relative to `root`, with `/` separators so it resolves on any OS.
+/// `None` when `path` is not under `root`. Drive letters and, on drives,
+/// the rest of the path compare case-insensitively like Windows does.
+pub fn relativize_path(path: &Path, root: &Path) -> Option<PathBuf> {
+    let path_text = path.to_string_lossy();
+    let root_text = root.to_string_lossy();
+    let path = split_path(&path_text);
+    let root = split_path(&root_text);
+
+    if !path.absolute || !root.absolute || path.drive != root.drive {
+        return None;
+    }
+    if path.parts.len() < root.parts.len() {
+        return None;
+    }
+
+    let case_insensitive = root.drive.is_some();
+    let under_root = root
+        .parts
+        .iter()
+        .zip(&path.parts)
+        .all(|(a, b)| same_part(a, b, case_insensitive));
+    if !under_root {
+        return None;
+    }
+
+    let relative = path.parts[root.parts.len()..].join("/");
+    Some(PathBuf::from(if relative.is_empty() {
+        ".".to_string()
+    } else {
+        relative
+    }))
+}
+
+/// Inverse of `relativize_path`. Absolute paths are returned unchanged;
+/// relative ones are joined onto `root` using the root's separator style.
+pub fn resolve_path(stored: &Path, root: &Path) -> PathBuf {
+    let stored_text = stored.to_string_lossy();
+    let parts = split_path(&stored_text);
+    if parts.absolute || parts.drive.is_some() {
+        return stored.to_path_buf();
+    }
+
+    let root_text = root.to_string_lossy();
+    let windows_style = split_path(&root_text).drive.is_some() || root_text.contains('\\');
+    let separator = if windows_style { "\\" } else { "/" };
+
+    let mut resolved = root_text.trim_end_matches(['/', '\\']).to_string();
+    if parts.parts.is_empty() {
+        if resolved.is_empty() || resolved.ends_with(':') {
+            resolved.push_str(separator);
+        }
+        return PathBuf::from(resolved);
+    }
+    for part in parts.parts {
+        resolved.push_str(separator);
+        resolved.push_str(part);
+    }
+    PathBuf::from(resolved)
+}
+
+/// Root used for relative repository paths: `config.path_root` or the drive
+/// of the executable.
+pub fn effective_path_root(config: &Config, exe_dir: &Path) -> PathBuf {
+    if config.path_root.trim().is_empty() {
+        default_path_root(exe_dir)
+    } else {
+        PathBuf::from(config.path_root.trim())
+    }
+}
+
+/// Turns stored relative repository paths into absolute ones after loading.
+pub fn resolve_repository_paths(config: &mut Config, root: &Path) {
+    for workspace in &mut config.workspaces {
+        for repo in &mut workspace.repositories {
+            repo.path = resolve_path(&repo.path, root);
+            if let Some(monorepo_root) = &mut repo.monorepo_root {
+                *monorepo_root = resolve_path(monorepo_root, root);
+            }
+        }
+    }
+    config.bookmarks = config
+        .bookmarks
+        .iter()
+        .map(|path| resolve_path(path, root))
+        .collect();
+}
+
+/// Stores repository paths under `root` relative to it before saving; paths
+/// elsewhere stay absolute.
+pub fn relativize_repository_paths(config: &mut Config, root: &Path) {
+    for workspace in &mut config.workspaces {
+        for repo in &mut workspace.repositories {
+            if let Some(relative) = relativize_path(&repo.path, root) {
+                repo.path = relative;
+            }
+            if let Some(monorepo_root) = &mut repo.monorepo_root {
+                if let Some(relative) = relativize_path(monorepo_root, root) {
+                    *monorepo_root = relative;
+                }
+            }
+        }
+    }
+    config.bookmarks = config
+        .bookmarks
+        .iter()
+        .map(|path| relativize_path(path, root).unwrap_or_else(|| path.clone()))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_paths_relativize_across_drive_letter_case() {
+        assert_eq!(
+            relativize_path(Path::new(r"d:\Repos\api"), Path::new(r"D:\repos")),
+            Some(PathBuf::from("api"))
+        );
+        assert_eq!(
+            relativize_path(Path::new(r"E:\repos\web\app"), Path::new(r"E:\")),
+            Some(PathBuf::from("repos/web/app"))
+        );
+        assert_eq!(
+            relativize_path(Path::new(r"E:\repos"), Path::new(r"E:\repos\")),
+            Some(PathBuf::from("."))
+        );
+    }
+
+    #[test]
+    fn paths_outside_the_root_stay_absolute() {
+        assert_eq!(
+            relativize_path(Path::new(r"C:\repos\api"), Path::new(r"E:\")),
+            None
+        );
+        assert_eq!(
+            relativize_path(Path::new(r"E:\repository"), Path::new(r"E:\repo")),
+            None
+        );
+        assert_eq!(
+            relativize_path(Path::new("/home/u/Repos"), Path::new("/home/u/repos")),
+            None
+        );
+        assert_eq!(
+            relativize_path(Path::new("repos/api"), Path::new("/")),
+            None
+        );
+    }
+
+    #[test]
+    fn relative_paths_resolve_on_another_drive() {
+        let stored = relativize_path(Path::new(r"D:\repos\api"), Path::new(r"D:\")).unwrap();
+
+        assert_eq!(
+            resolve_path(&stored, Path::new(r"E:\")),
+            PathBuf::from(r"E:\repos\api")
+        );
+        assert_eq!(
+            resolve_path(Path::new("."), Path::new(r"E:\")),
+            PathBuf::from(r"E:\")
+        );
+        assert_eq!(
+            resolve_path(Path::new("repos/api"), Path::new("/media/usb")),
+            PathBuf::from("/media/usb/repos/api")
+        );
+        assert_eq!(
+            resolve_path(Path::new(r"C:\elsewhere"), Path::new(r"E:\")),
+            PathBuf::from(r"C:\elsewhere")
+        );
+    }
+
+    #[test]
+    fn bookmarks_follow_the_portable_root() {
+        let mut config = Config::default();
+        config
+            .bookmarks
+            .insert(PathBuf::from("/media/usb/repos/api"));
+        config.bookmarks.insert(PathBuf::from("/home/u/other"));
+
+        relativize_repository_paths(&mut config, Path::new("/media/usb"));
+        assert!(config.bookmarks.contains(Path::new("repos/api")));
+        assert!(config.bookmarks.contains(Path::new("/home/u/other")));
+
+        resolve_repository_paths(&mut config, Path::new("/mnt/stick"));
+        assert!(config.bookmarks.contains(Path::new("/mnt/stick/repos/api")));
+        assert!(config.bookmarks.contains(Path::new("/home/u/other")));
+    }
+
+    #[test]
+    fn default_root_is_the_executable_drive() {
+        assert_eq!(
+            default_path_root(Path::new(r"\\?\e:\tools\repo-manager")),
+            PathBuf::from(r"E:\")
+        );
+        assert_eq!(
+            default_path_root(Path::new("/media/usb/tools")),
+            PathBuf::from("/")
+        );
+    }
+}
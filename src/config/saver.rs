@@ -0,0 +1,182 @@
+use super::{Config, ConfigManager};
+use std::time::{Duration, Instant};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+type SaveFn = fn(&Config) -> Result<(), Box<dyn std::error::Error>>;
+
+/// Coalesces config writes: mutations only mark the config dirty and the
+/// actual write happens at most once per `interval`.
+pub struct ConfigSaver<C: Clock = SystemClock> {
+    clock: C,
+    interval: Duration,
+    dirty_since: Option<Instant>,
+    save_fn: SaveFn,
+}
+
+impl Default for ConfigSaver {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(2))
+    }
+}
+
+impl ConfigSaver {
+    pub fn new(interval: Duration) -> Self {
+        Self::with_clock(SystemClock, interval, ConfigManager::save)
+    }
+}
+
+impl<C: Clock> ConfigSaver<C> {
+    pub fn with_clock(clock: C, interval: Duration, save_fn: SaveFn) -> Self {
+        Self {
+            clock,
+            interval,
+            dirty_since: None,
+            save_fn,
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        if self.dirty_since.is_none() {
+            self.dirty_since = Some(self.clock.now());
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_since.is_some()
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Writes the config if it has been dirty for at least `interval`.
+    /// Returns `true` when a write happened.
+    pub fn maybe_save(&mut self, config: &Config) -> bool {
+        match self.dirty_since {
+            Some(since) if self.clock.now().duration_since(since) >= self.interval => {
+                self.flush(config)
+            }
+            _ => false,
+        }
+    }
+
+    /// Writes immediately if there are pending changes (used on shutdown).
+    pub fn flush(&mut self, config: &Config) -> bool {
+        if self.dirty_since.is_none() {
+            return false;
+        }
+
+        self.dirty_since = None;
+        if let Err(e) = (self.save_fn)(config) {
+            eprintln!("Failed to save config: {}", e);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone)]
+    struct MockClock {
+        start: Instant,
+        offset: Rc<Cell<Duration>>,
+    }
+
+    impl MockClock {
+        fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                offset: Rc::new(Cell::new(Duration::ZERO)),
+            }
+        }
+
+        fn advance(&self, by: Duration) {
+            self.offset.set(self.offset.get() + by);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + self.offset.get()
+        }
+    }
+
+    fn no_op_save(_config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn saver(clock: &MockClock) -> ConfigSaver<MockClock> {
+        ConfigSaver::with_clock(clock.clone(), Duration::from_secs(2), no_op_save)
+    }
+
+    #[test]
+    fn clean_config_is_never_written() {
+        let clock = MockClock::new();
+        let mut saver = saver(&clock);
+
+        clock.advance(Duration::from_secs(10));
+        assert!(!saver.maybe_save(&Config::default()));
+        assert!(!saver.flush(&Config::default()));
+    }
+
+    #[test]
+    fn burst_of_mutations_is_coalesced_into_one_write() {
+        let clock = MockClock::new();
+        let mut saver = saver(&clock);
+        let config = Config::default();
+
+        for _ in 0..100 {
+            saver.mark_dirty();
+            assert!(!saver.maybe_save(&config));
+            clock.advance(Duration::from_millis(16));
+        }
+
+        clock.advance(Duration::from_secs(1));
+        assert!(saver.maybe_save(&config));
+        assert!(!saver.maybe_save(&config));
+    }
+
+    #[test]
+    fn interval_is_measured_from_first_mutation() {
+        let clock = MockClock::new();
+        let mut saver = saver(&clock);
+        let config = Config::default();
+
+        saver.mark_dirty();
+        clock.advance(Duration::from_millis(1500));
+        saver.mark_dirty();
+        assert!(!saver.maybe_save(&config));
+
+        clock.advance(Duration::from_millis(500));
+        assert!(saver.maybe_save(&config));
+        assert!(!saver.is_dirty());
+    }
+
+    #[test]
+    fn flush_writes_pending_changes_immediately() {
+        let clock = MockClock::new();
+        let mut saver = saver(&clock);
+        let config = Config::default();
+
+        saver.mark_dirty();
+        assert!(saver.flush(&config));
+
+        clock.advance(Duration::from_secs(5));
+        assert!(!saver.maybe_save(&config));
+    }
+}
@@ -0,0 +1,43 @@
+use super::ConfigManager;
+use crate::git::CommitActivity;
+use crate::workspace::CachedDescription;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Per-repository state remembered between sessions, kept next to the
+/// config file so that the config itself stays hand-editable.
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct StatusCache {
+    /// Last upstream tip the daily digest reported for each repository.
+    #[serde(default)]
+    pub upstream_tips: HashMap<PathBuf, String>,
+    /// Commit-activity sparklines, recomputed once a day.
+    #[serde(default)]
+    pub activity: HashMap<PathBuf, CommitActivity>,
+    /// When each repository last talked to its remote: the end of its last
+    /// successful fetch or pull.
+    #[serde(default)]
+    pub last_fetch: HashMap<PathBuf, SystemTime>,
+    #[serde(default)]
+    pub descriptions: HashMap<PathBuf, CachedDescription>,
+}
+
+impl StatusCache {
+    pub fn file_path() -> PathBuf {
+        ConfigManager::get_config_file_path().with_file_name("status_cache.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::file_path(), content)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use super::{config_from_value, Config};
+use crate::workspace::Workspace;
+use std::path::{Path, PathBuf};
+
+const HOME_MARKER: &str = "~";
+
+pub struct ImportPreview {
+    pub merged: Config,
+    pub workspaces_added: usize,
+    pub workspaces_merged: usize,
+    pub settings_overwritten: usize,
+}
+
+fn to_portable_path(path: &Path, home: Option<&Path>) -> PathBuf {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) => PathBuf::from(HOME_MARKER).join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+fn from_portable_path(path: &Path, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix(HOME_MARKER), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Copy of the config without machine-specific values and credential
+/// settings; repository paths under the home directory are stored relative
+/// to `~`.
+pub fn portable_config(config: &Config, home: Option<&Path>) -> Config {
+    let mut portable = config.clone();
+    portable.window_width = None;
+    portable.window_height = None;
+    portable.last_active_workspace_index = None;
+    portable.credential_helper = Default::default();
+
+    for workspace in &mut portable.workspaces {
+        for repo in &mut workspace.repositories {
+            repo.path = to_portable_path(&repo.path, home);
+        }
+    }
+
+    portable
+}
+
+pub fn export_settings(
+    config: &Config,
+    path: &Path,
+    home: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = serde_json::to_string_pretty(&portable_config(config, home))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+pub fn read_settings_export(
+    path: &Path,
+    home: Option<&Path>,
+) -> Result<Config, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut config = config_from_value(serde_json::from_str(&content)?)?;
+
+    for workspace in &mut config.workspaces {
+        for repo in &mut workspace.repositories {
+            repo.path = from_portable_path(&repo.path, home);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Merges `imported` into `current`: new workspaces are appended, workspaces
+/// with the same name receive the repositories they are missing, and shared
+/// settings are taken from the import. Machine-specific values stay local.
+pub fn preview_import(current: &Config, imported: &Config) -> ImportPreview {
+    let mut merged = current.clone();
+    let mut workspaces_added = 0;
+    let mut workspaces_merged = 0;
+
+    for imported_ws in &imported.workspaces {
+        match Workspace::find_by_name_mut(&mut merged.workspaces, &imported_ws.name) {
+            Some((_, existing)) => {
+                let mut added_any = false;
+                for repo in &imported_ws.repositories {
+                    added_any |= existing.add_repository(repo.path.clone());
+                }
+                if added_any {
+                    workspaces_merged += 1;
+                }
+            }
+            None => {
+                // Метка окружения, цвет и identity переносятся вместе с workspace
+                let mut workspace = imported_ws.duplicate(imported_ws.name.clone());
+                workspace.repositories.clear();
+                for repo in &imported_ws.repositories {
+                    workspace.add_repository(repo.path.clone());
+                }
+                merged.workspaces.push(workspace);
+                workspaces_added += 1;
+            }
+        }
+    }
+
+    let mut settings_overwritten = 0;
+    if merged.sidebar_width != imported.sidebar_width {
+        merged.sidebar_width = imported.sidebar_width;
+        settings_overwritten += 1;
+    }
+    if merged.sort_mode != imported.sort_mode {
+        merged.sort_mode = imported.sort_mode;
+        settings_overwritten += 1;
+    }
+    if merged.language != imported.language {
+        merged.language = imported.language.clone();
+        settings_overwritten += 1;
+    }
+    if merged.expected_identities != imported.expected_identities {
+        merged.expected_identities = imported.expected_identities.clone();
+        settings_overwritten += 1;
+    }
+
+    ImportPreview {
+        merged,
+        workspaces_added,
+        workspaces_merged,
+        settings_overwritten,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CredentialHelper, SortMode};
+    use crate::workspace::{EnvironmentLabel, Workspace};
+
+    fn workspace(name: &str, repos: &[&str]) -> Workspace {
+        let mut ws = Workspace::new(name);
+        for repo in repos {
+            ws.add_repository(PathBuf::from(repo));
+        }
+        ws
+    }
+
+    #[test]
+    fn portable_config_drops_machine_values() {
+        let mut config = Config {
+            window_width: Some(800.0),
+            window_height: Some(600.0),
+            last_active_workspace_index: Some(2),
+            credential_helper: CredentialHelper::Store,
+            ..Default::default()
+        };
+        config.workspaces.push(workspace(
+            "Work",
+            &["/home/jane/work/api", "/srv/shared/tools"],
+        ));
+
+        let portable = portable_config(&config, Some(Path::new("/home/jane")));
+
+        assert_eq!(portable.window_width, None);
+        assert_eq!(portable.window_height, None);
+        assert_eq!(portable.last_active_workspace_index, None);
+        assert_eq!(portable.credential_helper, CredentialHelper::Default);
+        assert_eq!(
+            portable.workspaces[0].repositories[0].path,
+            PathBuf::from("~/work/api")
+        );
+        assert_eq!(
+            portable.workspaces[0].repositories[1].path,
+            PathBuf::from("/srv/shared/tools")
+        );
+    }
+
+    #[test]
+    fn export_and_read_round_trip_expands_home() {
+        let mut config = Config::default();
+        config
+            .workspaces
+            .push(workspace("Work", &["/home/jane/work/api"]));
+
+        let path = std::env::temp_dir().join("repo_manager_export_round_trip.json");
+        export_settings(&config, &path, Some(Path::new("/home/jane"))).unwrap();
+        let imported = read_settings_export(&path, Some(Path::new("/Users/jane"))).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            imported.workspaces[0].repositories[0].path,
+            PathBuf::from("/Users/jane/work/api")
+        );
+    }
+
+    #[test]
+    fn environment_and_identity_survive_a_round_trip() {
+        let mut prod = workspace("Prod", &["/home/jane/prod/api"]);
+        prod.environment = EnvironmentLabel::Prod;
+        prod.color = Some([200, 40, 40]);
+        prod.git_author_name = Some("Jane Ops".to_string());
+        prod.git_author_email = Some("ops@corp.example".to_string());
+        let mut config = Config::default();
+        config.workspaces.push(prod);
+
+        let path = std::env::temp_dir().join("repo_manager_export_environment.json");
+        export_settings(&config, &path, Some(Path::new("/home/jane"))).unwrap();
+        let imported = read_settings_export(&path, Some(Path::new("/home/jane"))).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let preview = preview_import(&Config::default(), &imported);
+        let workspace = &preview.merged.workspaces[0];
+        assert_eq!(workspace.environment, EnvironmentLabel::Prod);
+        assert_eq!(workspace.color, Some([200, 40, 40]));
+        assert_eq!(workspace.git_author_name.as_deref(), Some("Jane Ops"));
+        assert_eq!(
+            workspace.git_author_email.as_deref(),
+            Some("ops@corp.example")
+        );
+        assert_eq!(
+            workspace.repositories[0].path,
+            PathBuf::from("/home/jane/prod/api")
+        );
+        assert_ne!(workspace.id, config.workspaces[0].id);
+    }
+
+    #[test]
+    fn preview_counts_added_merged_and_overwritten() {
+        let mut current = Config::default();
+        current.workspaces.push(workspace("Work", &["/a"]));
+        current.workspaces.push(workspace("Home", &["/b"]));
+        current.window_width = Some(1000.0);
+
+        let mut imported = Config::default();
+        imported.workspaces.push(workspace("Work", &["/a", "/c"]));
+        imported.workspaces.push(workspace("Home", &["/b"]));
+        imported.workspaces.push(workspace("Clients", &["/d"]));
+        imported.sort_mode = SortMode::Name;
+        imported.language = "ru".to_string();
+
+        let preview = preview_import(&current, &imported);
+
+        assert_eq!(preview.workspaces_added, 1);
+        assert_eq!(preview.workspaces_merged, 1);
+        assert_eq!(preview.settings_overwritten, 2);
+        assert_eq!(preview.merged.workspaces.len(), 3);
+        assert_eq!(preview.merged.workspaces[0].repositories.len(), 2);
+        assert_eq!(preview.merged.window_width, Some(1000.0));
+        assert_eq!(preview.merged.workspaces[2].repositories[0].name, "d");
+    }
+}
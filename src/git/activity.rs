@@ -0,0 +1,221 @@
+use super::{git_command, GitMessage, PoolGuard};
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Days covered by the activity sparkline.
+pub const ACTIVITY_DAYS: usize = 30;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Commits per local day over the last `ACTIVITY_DAYS` days.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitActivity {
+    /// Local day (days since the epoch) of the last bucket.
+    pub day: i64,
+    /// Offset from UTC in seconds the days were cut with.
+    pub utc_offset: i64,
+    /// Oldest day first; the last bucket is `day` itself.
+    pub counts: [u16; ACTIVITY_DAYS],
+}
+
+impl CommitActivity {
+    pub fn total(&self) -> u32 {
+        self.counts.iter().map(|&count| u32::from(count)).sum()
+    }
+
+    /// Computed today; older histograms are refreshed once a day.
+    pub fn is_current(&self, now: i64) -> bool {
+        local_day(now, self.utc_offset) == self.day
+    }
+}
+
+/// Day number of `timestamp` in a timezone `utc_offset` seconds from UTC.
+pub fn local_day(timestamp: i64, utc_offset: i64) -> i64 {
+    (timestamp + utc_offset).div_euclid(SECONDS_PER_DAY)
+}
+
+/// Buckets commit times into local days ending today. Commits older than the
+/// window or dated in the future (clock skew) are left out.
+pub fn activity_histogram(commit_times: &[i64], now: i64, utc_offset: i64) -> [u16; ACTIVITY_DAYS] {
+    let today = local_day(now, utc_offset);
+    let mut counts = [0u16; ACTIVITY_DAYS];
+    for &time in commit_times {
+        let age = today - local_day(time, utc_offset);
+        if (0..ACTIVITY_DAYS as i64).contains(&age) {
+            let bucket = &mut counts[ACTIVITY_DAYS - 1 - age as usize];
+            *bucket = bucket.saturating_add(1);
+        }
+    }
+    counts
+}
+
+/// Timestamp and UTC offset from an ident line such as
+/// `Jane <jane@example.com> 1700000000 +0530`.
+pub fn parse_ident_time(ident: &str) -> Option<(i64, i64)> {
+    let mut parts = ident.trim().rsplitn(3, ' ');
+    let zone = parts.next()?;
+    let time = parts.next()?.parse().ok()?;
+
+    let sign = match zone.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = zone.get(1..)?;
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some((time, sign * (hours * 3600 + minutes * 60)))
+}
+
+/// Current time and local UTC offset as git sees them; falls back to UTC.
+fn local_now(repo_path: &Path) -> (i64, i64) {
+    // Подставное имя: без настроенной identity git var завершается ошибкой
+    let ident = git_command(repo_path)
+        .args(["var", "GIT_COMMITTER_IDENT"])
+        .env("GIT_COMMITTER_NAME", "repo-manager")
+        .env("GIT_COMMITTER_EMAIL", "repo-manager@localhost")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| parse_ident_time(&String::from_utf8_lossy(&output.stdout)));
+
+    ident.unwrap_or_else(|| (unix_now(), 0))
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn load_commit_activity(
+    repo_path: &Path,
+) -> Result<CommitActivity, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["log", "--since=30.days", "--format=%ct"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git log failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    let times: Vec<i64> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect();
+    let (now, utc_offset) = local_now(repo_path);
+
+    Ok(CommitActivity {
+        day: local_day(now, utc_offset),
+        utc_offset,
+        counts: activity_histogram(&times, now, utc_offset),
+    })
+}
+
+/// Sparkline data for one row; failures (e.g. no commits yet) come back as
+/// `None` instead of an error so a cosmetic cell does not flag the repo.
+pub fn load_commit_activity_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let activity = load_commit_activity(&repo_path).ok();
+        let _ = tx.send(T::from(GitMessage::CommitActivityLoaded {
+            repo_path,
+            activity,
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-03-10 12:00:00 UTC
+    const NOW: i64 = 1_710_072_000;
+    const HOUR: i64 = 3600;
+
+    #[test]
+    fn commits_land_in_their_day_with_today_last() {
+        let times = [
+            NOW,
+            NOW - HOUR,
+            NOW - SECONDS_PER_DAY,
+            NOW - 29 * SECONDS_PER_DAY,
+        ];
+        let counts = activity_histogram(&times, NOW, 0);
+
+        assert_eq!(counts[ACTIVITY_DAYS - 1], 2);
+        assert_eq!(counts[ACTIVITY_DAYS - 2], 1);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts.iter().map(|&c| u32::from(c)).sum::<u32>(), 4);
+    }
+
+    #[test]
+    fn old_and_future_commits_are_ignored() {
+        let times = [NOW - 30 * SECONDS_PER_DAY, NOW + 2 * SECONDS_PER_DAY];
+
+        assert_eq!(activity_histogram(&times, NOW, 0), [0; ACTIVITY_DAYS]);
+    }
+
+    #[test]
+    fn day_boundary_follows_the_local_timezone() {
+        // 23:30 UTC накануне — это уже сегодня в UTC+3
+        let late_evening_utc = NOW - 12 * HOUR - 30 * 60;
+
+        let utc = activity_histogram(&[late_evening_utc], NOW, 0);
+        assert_eq!(utc[ACTIVITY_DAYS - 2], 1);
+
+        let moscow = activity_histogram(&[late_evening_utc], NOW, 3 * HOUR);
+        assert_eq!(moscow[ACTIVITY_DAYS - 1], 1);
+
+        // 02:00 UTC сегодня — ещё вчера в UTC-5
+        let early_utc = NOW - 10 * HOUR;
+        let new_york = activity_histogram(&[early_utc], NOW, -5 * HOUR);
+        assert_eq!(new_york[ACTIVITY_DAYS - 2], 1);
+    }
+
+    #[test]
+    fn local_day_rounds_down_before_the_epoch() {
+        assert_eq!(local_day(-1, 0), -1);
+        assert_eq!(local_day(0, 0), 0);
+        assert_eq!(local_day(SECONDS_PER_DAY - 1, 0), 0);
+    }
+
+    #[test]
+    fn histogram_is_current_only_on_the_same_local_day() {
+        let activity = CommitActivity {
+            day: local_day(NOW, 3 * HOUR),
+            utc_offset: 3 * HOUR,
+            counts: [0; ACTIVITY_DAYS],
+        };
+
+        assert!(activity.is_current(NOW + 8 * HOUR));
+        assert!(!activity.is_current(NOW + 9 * HOUR));
+    }
+
+    #[test]
+    fn ident_time_and_offset_are_parsed() {
+        assert_eq!(
+            parse_ident_time("Jane Doe <jane@example.com> 1700000000 +0530\n"),
+            Some((1_700_000_000, 5 * HOUR + 30 * 60))
+        );
+        assert_eq!(
+            parse_ident_time("x <y> 1700000000 -0800"),
+            Some((1_700_000_000, -8 * HOUR))
+        );
+        assert_eq!(parse_ident_time("x <y> 1700000000 UTC"), None);
+    }
+}
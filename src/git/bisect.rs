@@ -0,0 +1,140 @@
+use super::{git_command, GitMessage};
+use crossbeam_channel::Sender;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often a running bisect is checked for verdicts given outside the app.
+pub const BISECT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisectVerdict {
+    Good,
+    Bad,
+}
+
+impl BisectVerdict {
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            BisectVerdict::Good => "good",
+            BisectVerdict::Bad => "bad",
+        }
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            BisectVerdict::Good => "bisect_good",
+            BisectVerdict::Bad => "bisect_bad",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BisectState {
+    /// Commit checked out for testing.
+    pub current_hash: String,
+    pub remaining_steps: usize,
+}
+
+/// `BISECT_START` is written by every `git bisect start`; `BISECT_HEAD`
+/// only by `--no-checkout` sessions.
+pub fn bisect_in_progress(repo_path: &Path) -> bool {
+    let git_dir = repo_path.join(".git");
+    git_dir.join("BISECT_START").exists() || git_dir.join("BISECT_HEAD").exists()
+}
+
+/// `bisect_steps` from `git rev-list --bisect-vars` output.
+pub fn parse_bisect_steps(vars: &str) -> Option<usize> {
+    vars.lines()
+        .find_map(|line| line.trim().strip_prefix("bisect_steps="))
+        .and_then(|steps| steps.trim_matches('\'').parse().ok())
+}
+
+/// Current commit and the estimated steps left. The estimate is the one
+/// `git bisect` prints ("roughly N steps") and stays 0 until a bad commit
+/// has been marked.
+pub fn read_bisect_state(repo_path: &Path) -> Option<BisectState> {
+    let head = git_command(repo_path)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let current_hash = String::from_utf8_lossy(&head.stdout).trim().to_string();
+
+    let remaining_steps = git_command(repo_path)
+        .args([
+            "rev-list",
+            "--bisect-vars",
+            "refs/bisect/bad",
+            "--not",
+            "--glob=refs/bisect/good-*",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| parse_bisect_steps(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or(0);
+
+    Some(BisectState {
+        current_hash,
+        remaining_steps,
+    })
+}
+
+/// Polls a repository with a running bisect and reports every change of
+/// its state; sends `BisectEnded` and stops once the bisect is reset.
+pub fn watch_bisect_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut last_state = None;
+        while bisect_in_progress(&repo_path) {
+            let state = read_bisect_state(&repo_path);
+            if state != last_state {
+                if let Some(state) = &state {
+                    let msg = GitMessage::BisectStateChanged {
+                        repo_path: repo_path.clone(),
+                        current_hash: state.current_hash.clone(),
+                        remaining_steps: state.remaining_steps,
+                    };
+                    if tx.send(T::from(msg)).is_err() {
+                        return;
+                    }
+                }
+                last_state = state;
+            }
+            std::thread::sleep(BISECT_POLL_INTERVAL);
+        }
+
+        let _ = tx.send(T::from(GitMessage::BisectEnded { repo_path }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_read_from_bisect_vars() {
+        let vars = "bisect_rev='1a2b3c4d'\nbisect_nr=5\nbisect_good=6\nbisect_bad=5\n\
+                    bisect_all=12\nbisect_steps=3\n";
+
+        assert_eq!(parse_bisect_steps(vars), Some(3));
+        assert_eq!(parse_bisect_steps(""), None);
+    }
+
+    #[test]
+    fn bisect_is_detected_by_its_state_files() {
+        let dir = std::env::temp_dir().join("repo_manager_bisect_detect");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+
+        let before = bisect_in_progress(&dir);
+        std::fs::write(dir.join(".git").join("BISECT_START"), "main\n").unwrap();
+        let during = bisect_in_progress(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(!before);
+        assert!(during);
+    }
+}
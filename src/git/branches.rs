@@ -0,0 +1,154 @@
+/// `git for-each-ref refs/heads` format read by `parse_branch_overview`:
+/// HEAD marker, name, upstream, tracking state and commit time, separated
+/// by NUL bytes.
+pub const BRANCH_OVERVIEW_FORMAT: &str =
+    "%(HEAD)%00%(refname:short)%00%(upstream:short)%00%(upstream:track,nobracket)%00%(committerdate:unix)";
+
+/// Branch names never offered for bulk deletion unless configured otherwise.
+pub const DEFAULT_PROTECTED_BRANCHES: [&str; 3] = ["main", "master", "develop"];
+
+/// One local branch in the "Branches overview" window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchOverview {
+    pub name: String,
+    pub is_current: bool,
+    pub upstream: Option<String>,
+    /// The upstream is configured but its remote branch was deleted.
+    pub upstream_gone: bool,
+    pub ahead: usize,
+    pub behind: usize,
+    /// Committer time of the tip, Unix seconds.
+    pub last_commit_time: Option<i64>,
+}
+
+/// `ahead 2, behind 3`, `ahead 1`, `gone` or empty.
+fn parse_track(track: &str) -> (usize, usize, bool) {
+    let mut ahead = 0;
+    let mut behind = 0;
+    let mut gone = false;
+    for part in track.split(',').map(str::trim) {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        } else if part == "gone" {
+            gone = true;
+        }
+    }
+    (ahead, behind, gone)
+}
+
+/// Lines of `git for-each-ref --format=BRANCH_OVERVIEW_FORMAT refs/heads`.
+pub fn parse_branch_overview(output: &str) -> Vec<BranchOverview> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\0').collect();
+            let [head, name, upstream, track, time] = fields[..] else {
+                return None;
+            };
+            if name.is_empty() {
+                return None;
+            }
+            let (ahead, behind, upstream_gone) = parse_track(track);
+            Some(BranchOverview {
+                name: name.to_string(),
+                is_current: head == "*",
+                upstream: (!upstream.is_empty()).then(|| upstream.to_string()),
+                upstream_gone,
+                ahead,
+                behind,
+                last_commit_time: time.trim().parse().ok(),
+            })
+        })
+        .collect()
+}
+
+/// Branches from `git branch --merged <default>` output that can be deleted:
+/// not the default branch itself, not checked out here or in another
+/// worktree (`*` / `+` markers) and not protected.
+pub fn deletable_merged_branches(
+    merged_output: &str,
+    default_branch: &str,
+    protected: &[String],
+) -> Vec<String> {
+    merged_output
+        .lines()
+        .filter(|line| !line.starts_with(['*', '+']))
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.starts_with('('))
+        .filter(|name| *name != default_branch)
+        .filter(|name| !protected.iter().any(|p| p == name))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Branch name from `git symbolic-ref --short refs/remotes/<remote>/HEAD`
+/// output such as `origin/main`.
+pub fn parse_remote_head(output: &str) -> Option<String> {
+    let head = output.trim();
+    let (_, branch) = head.split_once('/')?;
+    (!branch.is_empty()).then(|| branch.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overview_lines_carry_tracking_state() {
+        let output = "*\x00main\x00origin/main\x00\x001700000000\n \
+                      \x00feature/login\x00origin/feature/login\x00ahead 2, behind 3\x001700000100\n \
+                      \x00old\x00origin/old\x00gone\x001600000000\n \
+                      \x00local-only\x00\x00\x001700000200\n";
+
+        let branches = parse_branch_overview(output);
+
+        assert_eq!(branches.len(), 4);
+        assert!(branches[0].is_current);
+        assert_eq!(branches[0].upstream.as_deref(), Some("origin/main"));
+        assert_eq!((branches[1].ahead, branches[1].behind), (2, 3));
+        assert!(!branches[1].is_current);
+        assert!(branches[2].upstream_gone);
+        assert_eq!(branches[3].upstream, None);
+        assert_eq!(branches[3].last_commit_time, Some(1_700_000_200));
+    }
+
+    #[test]
+    fn malformed_overview_lines_are_skipped() {
+        assert!(parse_branch_overview("garbage\n\n").is_empty());
+        assert_eq!(parse_track("ahead 1"), (1, 0, false));
+        assert_eq!(parse_track(""), (0, 0, false));
+    }
+
+    #[test]
+    fn merged_branches_exclude_default_current_and_protected() {
+        let merged = "  develop\n* feature/current\n  feature/done\n+ feature/in-worktree\n  \
+                      main\n  release/1.0\n";
+        let protected = vec!["develop".to_string(), "release/1.0".to_string()];
+
+        assert_eq!(
+            deletable_merged_branches(merged, "main", &protected),
+            vec!["feature/done".to_string()]
+        );
+    }
+
+    #[test]
+    fn detached_head_line_is_not_a_branch() {
+        let merged = "* (HEAD detached at 1a2b3c)\n  fix\n";
+        assert_eq!(
+            deletable_merged_branches(merged, "master", &[]),
+            vec!["fix".to_string()]
+        );
+    }
+
+    #[test]
+    fn remote_head_names_the_default_branch() {
+        assert_eq!(parse_remote_head("origin/main\n"), Some("main".to_string()));
+        assert_eq!(
+            parse_remote_head("upstream/release/2.x"),
+            Some("release/2.x".to_string())
+        );
+        assert_eq!(parse_remote_head(""), None);
+    }
+}
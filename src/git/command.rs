@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Commit identity set on a workspace, used instead of the one from
+/// `.gitconfig` for its repositories.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceGitEnv {
+    pub author_name: Option<String>,
+    pub author_email: Option<String>,
+}
+
+impl WorkspaceGitEnv {
+    /// Author and committer variables for the fields that are set.
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let mut vars = Vec::new();
+        if let Some(name) = &self.author_name {
+            vars.push(("GIT_AUTHOR_NAME".to_string(), name.clone()));
+            vars.push(("GIT_COMMITTER_NAME".to_string(), name.clone()));
+        }
+        if let Some(email) = &self.author_email {
+            vars.push(("GIT_AUTHOR_EMAIL".to_string(), email.clone()));
+            vars.push(("GIT_COMMITTER_EMAIL".to_string(), email.clone()));
+        }
+        vars
+    }
+}
+
+/// Executable and per-repository environment applied to every git command.
+#[derive(Debug, Default)]
+struct GitCommandSettings {
+    executable: Option<PathBuf>,
+    env_overrides: HashMap<PathBuf, Vec<(String, String)>>,
+    identities: HashMap<PathBuf, WorkspaceGitEnv>,
+}
+
+lazy_static::lazy_static! {
+    static ref GIT_COMMAND_SETTINGS: Mutex<GitCommandSettings> =
+        Mutex::new(GitCommandSettings::default());
+}
+
+/// Git binary used for all commands; `None` resolves `git` from PATH.
+pub fn set_git_executable(executable: Option<PathBuf>) {
+    if let Ok(mut settings) = GIT_COMMAND_SETTINGS.lock() {
+        settings.executable = executable.filter(|path| !path.as_os_str().is_empty());
+    }
+}
+
+/// Replaces the environment overrides of one repository.
+pub fn set_repo_env_overrides(repo_path: &Path, overrides: Vec<(String, String)>) {
+    if let Ok(mut settings) = GIT_COMMAND_SETTINGS.lock() {
+        if overrides.is_empty() {
+            settings.env_overrides.remove(repo_path);
+        } else {
+            settings
+                .env_overrides
+                .insert(repo_path.to_path_buf(), overrides);
+        }
+    }
+}
+
+/// Replaces the workspace identities of all repositories at once, so
+/// repositories no longer in an overriding workspace lose theirs.
+pub fn set_repo_identities(identities: HashMap<PathBuf, WorkspaceGitEnv>) {
+    if let Ok(mut settings) = GIT_COMMAND_SETTINGS.lock() {
+        settings.identities = identities;
+    }
+}
+
+/// Builds a git command from explicit settings; the global helpers below
+/// only look the settings up.
+pub fn build_git_command(
+    executable: Option<&Path>,
+    repo_path: Option<&Path>,
+    env_overrides: &[(String, String)],
+) -> Command {
+    let mut cmd = match executable {
+        Some(executable) => Command::new(executable),
+        None => Command::new("git"),
+    };
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+
+    if let Some(repo_path) = repo_path {
+        cmd.current_dir(repo_path);
+    }
+    for (name, value) in env_overrides {
+        cmd.env(name, value);
+    }
+
+    cmd
+}
+
+/// Git command not tied to a repository, e.g. clone or `--version`,
+/// optionally committing as a workspace identity.
+pub(crate) fn create_git_command(identity: Option<&WorkspaceGitEnv>) -> Command {
+    let settings = GIT_COMMAND_SETTINGS.lock();
+    let executable = settings
+        .as_ref()
+        .ok()
+        .and_then(|settings| settings.executable.clone());
+    let env = identity.map(WorkspaceGitEnv::env_vars).unwrap_or_default();
+    build_git_command(executable.as_deref(), None, &env)
+}
+
+/// Git command running inside `repo_path` with its workspace identity and
+/// that repository's environment overrides.
+pub(crate) fn git_command(repo_path: impl AsRef<Path>) -> Command {
+    let repo_path = repo_path.as_ref();
+    let (executable, env) = match GIT_COMMAND_SETTINGS.lock() {
+        Ok(settings) => {
+            let mut env = settings
+                .identities
+                .get(repo_path)
+                .map(WorkspaceGitEnv::env_vars)
+                .unwrap_or_default();
+            // Переменные самого репозитория точнее workspace и идут последними
+            env.extend(
+                settings
+                    .env_overrides
+                    .get(repo_path)
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+            (settings.executable.clone(), env)
+        }
+        Err(_) => (None, Vec::new()),
+    };
+    build_git_command(executable.as_deref(), Some(repo_path), &env)
+}
+
+/// Runs `git --version` with the configured executable.
+pub fn check_git_executable() -> Result<String, Box<dyn std::error::Error>> {
+    let output = create_git_command(None).arg("--version").output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Git version check failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Variable names that probably hold a secret.
+pub fn is_sensitive_env_name(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["KEY", "TOKEN", "PASSWORD"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+/// `NAME=value` pairs for the log, with secret values replaced by `***`.
+pub fn describe_env_overrides(overrides: &[(String, String)]) -> String {
+    overrides
+        .iter()
+        .map(|(name, value)| {
+            if is_sensitive_env_name(name) {
+                format!("{}=***", name)
+            } else {
+                format!("{}={}", name, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn executable_defaults_to_git_on_path() {
+        let cmd = build_git_command(None, None, &[]);
+
+        assert_eq!(cmd.get_program(), OsStr::new("git"));
+        assert_eq!(cmd.get_current_dir(), None);
+    }
+
+    #[test]
+    fn executable_and_overrides_land_on_the_command() {
+        let overrides = vec![
+            (
+                "GIT_SSH_COMMAND".to_string(),
+                "ssh -i ~/.ssh/deploy".to_string(),
+            ),
+            ("GIT_TRACE".to_string(), "1".to_string()),
+        ];
+        let cmd = build_git_command(
+            Some(Path::new("/opt/git/bin/git")),
+            Some(Path::new("/work/api")),
+            &overrides,
+        );
+
+        assert_eq!(cmd.get_program(), OsStr::new("/opt/git/bin/git"));
+        assert_eq!(cmd.get_current_dir(), Some(Path::new("/work/api")));
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(
+            OsStr::new("GIT_SSH_COMMAND"),
+            Some(OsStr::new("ssh -i ~/.ssh/deploy"))
+        )));
+        assert!(envs.contains(&(OsStr::new("GIT_TRACE"), Some(OsStr::new("1")))));
+    }
+
+    #[test]
+    fn repo_command_picks_up_only_its_own_overrides() {
+        let repo = Path::new("/tmp/command-test/with-env");
+        set_repo_env_overrides(repo, vec![("GIT_TRACE".to_string(), "1".to_string())]);
+
+        let with_env = git_command(repo);
+        let other = git_command("/tmp/command-test/other");
+
+        assert_eq!(with_env.get_current_dir(), Some(repo));
+        assert_eq!(with_env.get_envs().count(), 1);
+        assert_eq!(other.get_envs().count(), 0);
+
+        set_repo_env_overrides(repo, Vec::new());
+        assert_eq!(git_command(repo).get_envs().count(), 0);
+    }
+
+    #[test]
+    fn workspace_identity_sets_author_and_committer() {
+        let repo = Path::new("/tmp/command-test/with-identity");
+        let identity = WorkspaceGitEnv {
+            author_name: Some("Jane Work".to_string()),
+            author_email: None,
+        };
+        set_repo_identities(HashMap::from([(repo.to_path_buf(), identity.clone())]));
+        set_repo_env_overrides(
+            repo,
+            vec![("GIT_COMMITTER_NAME".to_string(), "CI".to_string())],
+        );
+
+        let cmd = git_command(repo);
+        let envs: Vec<_> = cmd.get_envs().collect();
+
+        set_repo_identities(HashMap::new());
+        set_repo_env_overrides(repo, Vec::new());
+        assert!(envs.contains(&(OsStr::new("GIT_AUTHOR_NAME"), Some(OsStr::new("Jane Work")))));
+        // Переменная репозитория перекрывает identity workspace
+        assert!(envs.contains(&(OsStr::new("GIT_COMMITTER_NAME"), Some(OsStr::new("CI")))));
+        assert!(!envs.iter().any(|(name, _)| *name == "GIT_AUTHOR_EMAIL"));
+        assert_eq!(create_git_command(Some(&identity)).get_envs().count(), 2);
+    }
+
+    #[test]
+    fn secrets_are_masked_in_descriptions() {
+        let overrides = vec![
+            ("GIT_SSH_COMMAND".to_string(), "ssh -i key".to_string()),
+            ("api_token".to_string(), "abc".to_string()),
+            ("DB_PASSWORD".to_string(), "hunter2".to_string()),
+        ];
+
+        assert_eq!(
+            describe_env_overrides(&overrides),
+            "GIT_SSH_COMMAND=ssh -i key, api_token=***, DB_PASSWORD=***"
+        );
+        assert!(is_sensitive_env_name("SSH_KEY_PATH"));
+        assert!(!is_sensitive_env_name("GIT_TRACE"));
+    }
+}
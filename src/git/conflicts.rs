@@ -0,0 +1,150 @@
+use super::git_command;
+use std::path::{Path, PathBuf};
+
+/// Conflict lines git prints during a merge, e.g.
+/// `CONFLICT (content): Merge conflict in src/main.rs`.
+pub fn has_conflict_markers(output: &str) -> bool {
+    output
+        .lines()
+        .any(|line| line.trim_start().starts_with("CONFLICT ("))
+}
+
+/// Operation stopped on conflicts and waiting for `--continue` or `--abort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+}
+
+impl PendingOperation {
+    /// Git subcommand that aborts or continues the operation.
+    pub fn command(self) -> &'static str {
+        match self {
+            PendingOperation::Merge => "merge",
+            PendingOperation::Rebase => "rebase",
+            PendingOperation::CherryPick => "cherry-pick",
+            PendingOperation::Revert => "revert",
+        }
+    }
+}
+
+/// The operation whose state files exist in the git dir; `exists` checks a
+/// path relative to it. A rebase is checked first: it may stop on a
+/// cherry-picked commit and leave `CHERRY_PICK_HEAD` behind.
+pub fn pending_operation_from(exists: impl Fn(&str) -> bool) -> Option<PendingOperation> {
+    // rebase-apply с файлом applying оставляет `git am`, а не rebase
+    if exists("rebase-merge") || (exists("rebase-apply") && !exists("rebase-apply/applying")) {
+        Some(PendingOperation::Rebase)
+    } else if exists("MERGE_HEAD") {
+        Some(PendingOperation::Merge)
+    } else if exists("CHERRY_PICK_HEAD") {
+        Some(PendingOperation::CherryPick)
+    } else if exists("REVERT_HEAD") {
+        Some(PendingOperation::Revert)
+    } else {
+        None
+    }
+}
+
+/// The operation in progress in `repo_path`. Looks in the real git dir, so
+/// worktrees and submodules with a `.git` file work too.
+pub fn pending_operation(repo_path: &Path) -> Option<PendingOperation> {
+    let output = git_command(repo_path)
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let git_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    pending_operation_from(|name| git_dir.join(name).exists())
+}
+
+/// File names from `git diff --name-only --diff-filter=U` output.
+pub fn parse_conflicted_files(output: &str) -> Vec<String> {
+    let mut files: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    // Файл с несколькими стадиями может встретиться дважды
+    files.dedup();
+    files
+}
+
+/// Files with unresolved conflicts.
+pub fn read_conflicted_files(repo_path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(parse_conflicted_files(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflict_markers_are_found_in_pull_output() {
+        let output = "Auto-merging src/main.rs\n\
+                      CONFLICT (content): Merge conflict in src/main.rs\n\
+                      Automatic merge failed; fix conflicts and then commit the result.\n";
+
+        assert!(has_conflict_markers(output));
+        assert!(has_conflict_markers(
+            "CONFLICT (modify/delete): README.md deleted in HEAD"
+        ));
+        assert!(!has_conflict_markers(
+            "fatal: Not possible to fast-forward, aborting."
+        ));
+    }
+
+    #[test]
+    fn pending_operation_is_read_from_state_files() {
+        let detect = |files: &[&str]| pending_operation_from(|name| files.contains(&name));
+
+        assert_eq!(detect(&["MERGE_HEAD"]), Some(PendingOperation::Merge));
+        assert_eq!(detect(&["rebase-merge"]), Some(PendingOperation::Rebase));
+        assert_eq!(detect(&["rebase-apply"]), Some(PendingOperation::Rebase));
+        assert_eq!(
+            detect(&["CHERRY_PICK_HEAD"]),
+            Some(PendingOperation::CherryPick)
+        );
+        assert_eq!(detect(&["REVERT_HEAD"]), Some(PendingOperation::Revert));
+        // Интерактивный rebase, остановившийся на pick
+        assert_eq!(
+            detect(&["rebase-merge", "CHERRY_PICK_HEAD"]),
+            Some(PendingOperation::Rebase)
+        );
+        // `git am` — не наша операция
+        assert_eq!(detect(&["rebase-apply", "rebase-apply/applying"]), None);
+        assert_eq!(detect(&[]), None);
+        assert_eq!(PendingOperation::CherryPick.command(), "cherry-pick");
+    }
+
+    #[test]
+    fn conflicted_files_are_listed_once() {
+        let output = "src/main.rs\nsrc/main.rs\n\ndocs/guide with spaces.md\n";
+
+        assert_eq!(
+            parse_conflicted_files(output),
+            vec![
+                "src/main.rs".to_string(),
+                "docs/guide with spaces.md".to_string()
+            ]
+        );
+        assert!(parse_conflicted_files("").is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use super::{git_command, GitMessage, PoolGuard};
+use crossbeam_channel::Sender;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestCommit {
+    pub short_hash: String,
+    pub author: String,
+    pub subject: String,
+}
+
+/// New upstream commits of one repository since the last seen tip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoDelta {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub commits: Vec<DigestCommit>,
+    /// The old tip is gone from the history (force-push, rebase).
+    pub history_rewritten: bool,
+}
+
+pub struct DigestRequest {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub last_seen_tip: Option<String>,
+}
+
+/// `None` when there is nothing to report: the repository is seen for the
+/// first time or its upstream tip did not move. `commits` is `None` when the
+/// old tip could not be resolved.
+pub fn assemble_delta(
+    repo_path: PathBuf,
+    repo_name: String,
+    old_tip: Option<&str>,
+    new_tip: &str,
+    commits: Option<Vec<DigestCommit>>,
+) -> Option<RepoDelta> {
+    let old_tip = old_tip?;
+    if old_tip == new_tip {
+        return None;
+    }
+
+    Some(RepoDelta {
+        repo_path,
+        repo_name,
+        history_rewritten: commits.is_none(),
+        commits: commits.unwrap_or_default(),
+    })
+}
+
+pub fn group_by_author(commits: &[DigestCommit]) -> BTreeMap<&str, Vec<&DigestCommit>> {
+    let mut groups: BTreeMap<&str, Vec<&DigestCommit>> = BTreeMap::new();
+    for commit in commits {
+        groups
+            .entry(commit.author.as_str())
+            .or_default()
+            .push(commit);
+    }
+    groups
+}
+
+pub fn digest_to_markdown(deltas: &[RepoDelta], title: &str, rewritten_note: &str) -> String {
+    let mut markdown = format!("# {}\n", title);
+
+    for delta in deltas {
+        markdown.push_str(&format!("\n## {}\n", delta.repo_name));
+        if delta.history_rewritten {
+            markdown.push_str(&format!("\n_{}_\n", rewritten_note));
+            continue;
+        }
+
+        for (author, commits) in group_by_author(&delta.commits) {
+            markdown.push_str(&format!("\n**{}**\n\n", author));
+            for commit in commits {
+                markdown.push_str(&format!("- `{}` {}\n", commit.short_hash, commit.subject));
+            }
+        }
+    }
+
+    markdown
+}
+
+fn git_stdout(repo_path: &Path, args: &[&str]) -> Option<String> {
+    git_command(repo_path)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn upstream_tip(repo_path: &Path) -> Option<String> {
+    git_stdout(repo_path, &["rev-parse", "@{u}"]).filter(|tip| !tip.is_empty())
+}
+
+fn commits_between(repo_path: &Path, old_tip: &str, new_tip: &str) -> Option<Vec<DigestCommit>> {
+    git_stdout(
+        repo_path,
+        &["cat-file", "-e", &format!("{}^{{commit}}", old_tip)],
+    )?;
+
+    let log = git_stdout(
+        repo_path,
+        &[
+            "log",
+            "--format=%h%x1f%an%x1f%s",
+            &format!("{}..{}", old_tip, new_tip),
+        ],
+    )?;
+
+    Some(
+        log.lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\x1f');
+                Some(DigestCommit {
+                    short_hash: fields.next()?.to_string(),
+                    author: fields.next()?.to_string(),
+                    subject: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Resolves current upstream tips and the commits that arrived since
+/// `last_seen_tip`, one pooled thread per repository, then sends a single
+/// `GitMessage::DigestReady`.
+pub fn build_digest_async<T>(requests: Vec<DigestRequest>, auto: bool, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let workers: Vec<_> = requests
+            .into_iter()
+            .map(|request| {
+                std::thread::spawn(move || {
+                    let _guard = PoolGuard::try_acquire_with_timeout(60_000);
+
+                    let new_tip = upstream_tip(&request.repo_path)?;
+                    let commits = request
+                        .last_seen_tip
+                        .as_deref()
+                        .filter(|old_tip| *old_tip != new_tip)
+                        .and_then(|old_tip| commits_between(&request.repo_path, old_tip, &new_tip));
+                    let delta = assemble_delta(
+                        request.repo_path.clone(),
+                        request.repo_name,
+                        request.last_seen_tip.as_deref(),
+                        &new_tip,
+                        commits,
+                    );
+
+                    Some(((request.repo_path, new_tip), delta))
+                })
+            })
+            .collect();
+
+        let mut tips = Vec::new();
+        let mut deltas = Vec::new();
+        for worker in workers {
+            if let Ok(Some((tip, delta))) = worker.join() {
+                tips.push(tip);
+                deltas.extend(delta);
+            }
+        }
+        deltas.sort_by(|a, b| a.repo_name.cmp(&b.repo_name));
+
+        let _ = tx.send(T::from(GitMessage::DigestReady { tips, deltas, auto }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str, author: &str, subject: &str) -> DigestCommit {
+        DigestCommit {
+            short_hash: hash.to_string(),
+            author: author.to_string(),
+            subject: subject.to_string(),
+        }
+    }
+
+    fn delta(commits: Option<Vec<DigestCommit>>) -> Option<RepoDelta> {
+        assemble_delta(
+            PathBuf::from("/r/api"),
+            "api".to_string(),
+            Some("aaa"),
+            "bbb",
+            commits,
+        )
+    }
+
+    #[test]
+    fn first_seen_or_unchanged_repo_has_no_delta() {
+        let path = PathBuf::from("/r/api");
+        assert_eq!(
+            assemble_delta(path.clone(), "api".into(), None, "bbb", Some(vec![])),
+            None
+        );
+        assert_eq!(
+            assemble_delta(path, "api".into(), Some("bbb"), "bbb", Some(vec![])),
+            None
+        );
+    }
+
+    #[test]
+    fn moved_tip_lists_new_commits() {
+        let delta = delta(Some(vec![commit("1", "Ann", "Fix login")])).unwrap();
+
+        assert!(!delta.history_rewritten);
+        assert_eq!(delta.commits.len(), 1);
+    }
+
+    #[test]
+    fn missing_old_tip_is_reported_as_rewritten_history() {
+        let delta = delta(None).unwrap();
+
+        assert!(delta.history_rewritten);
+        assert!(delta.commits.is_empty());
+    }
+
+    #[test]
+    fn markdown_groups_commits_by_author() {
+        let deltas = vec![
+            delta(Some(vec![
+                commit("1a", "Bob", "Add cache"),
+                commit("2b", "Ann", "Fix login"),
+                commit("3c", "Bob", "Tune cache"),
+            ]))
+            .unwrap(),
+            RepoDelta {
+                repo_name: "web".to_string(),
+                ..delta(None).unwrap()
+            },
+        ];
+
+        let markdown = digest_to_markdown(&deltas, "Digest", "history rewritten");
+
+        assert_eq!(
+            markdown,
+            "# Digest\n\
+             \n## api\n\
+             \n**Ann**\n\n- `2b` Fix login\n\
+             \n**Bob**\n\n- `1a` Add cache\n- `3c` Tune cache\n\
+             \n## web\n\
+             \n_history rewritten_\n"
+        );
+    }
+}
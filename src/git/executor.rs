@@ -0,0 +1,277 @@
+use super::git_command;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitErrorKind {
+    /// `detected dubious ownership`: the repository belongs to another user
+    /// and is not listed in `safe.directory`. Every command fails until the
+    /// user marks it as safe.
+    DubiousOwnership,
+    /// The server rejected a push to a protected branch.
+    ProtectedBranch,
+    Other,
+}
+
+/// Stderr fragments of known failures; the first match wins.
+const ERROR_PATTERNS: &[(&str, GitErrorKind)] = &[
+    ("detected dubious ownership", GitErrorKind::DubiousOwnership),
+    // GitHub
+    (
+        "GH006: Protected branch update failed",
+        GitErrorKind::ProtectedBranch,
+    ),
+    (
+        "protected branch hook declined",
+        GitErrorKind::ProtectedBranch,
+    ),
+    // GitLab
+    (
+        "You are not allowed to push code to protected branches",
+        GitErrorKind::ProtectedBranch,
+    ),
+    (
+        "You are not allowed to force push code to a protected branch",
+        GitErrorKind::ProtectedBranch,
+    ),
+    // Bitbucket Cloud / Data Center
+    (
+        "Permission denied to update branch",
+        GitErrorKind::ProtectedBranch,
+    ),
+    (
+        "can only be modified through pull requests",
+        GitErrorKind::ProtectedBranch,
+    ),
+];
+
+pub fn classify_git_error(stderr: &str) -> GitErrorKind {
+    ERROR_PATTERNS
+        .iter()
+        .find(|(pattern, _)| stderr.contains(pattern))
+        .map(|(_, kind)| *kind)
+        .unwrap_or(GitErrorKind::Other)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GitOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub trait GitExecutor {
+    fn run(&self, repo_path: &Path, args: &[&str]) -> std::io::Result<GitOutput>;
+}
+
+pub struct SystemGit;
+
+impl GitExecutor for SystemGit {
+    fn run(&self, repo_path: &Path, args: &[&str]) -> std::io::Result<GitOutput> {
+        let output = git_command(repo_path).args(args).output()?;
+
+        Ok(GitOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+fn read_config_value(executor: &dyn GitExecutor, repo_path: &Path, key: &str) -> Option<String> {
+    let output = executor.run(repo_path, &["config", "--get", key]).ok()?;
+    let value = output.stdout.trim();
+    if output.success && !value.is_empty() {
+        Some(value.to_string())
+    } else {
+        None
+    }
+}
+
+/// Effective `user.name` / `user.email`, as git resolves them for this repo.
+pub fn read_identity(executor: &dyn GitExecutor, repo_path: &Path) -> Option<(String, String)> {
+    let name = read_config_value(executor, repo_path, "user.name").unwrap_or_default();
+    let email = read_config_value(executor, repo_path, "user.email").unwrap_or_default();
+
+    if name.is_empty() && email.is_empty() {
+        None
+    } else {
+        Some((name, email))
+    }
+}
+
+/// `safe.directory` entry for `path`. Git for Windows compares forward-slash
+/// paths, so backslashes and the `\\?\` verbatim prefix are normalized.
+pub fn safe_directory_value(path: &Path, windows: bool) -> String {
+    let raw = path.to_string_lossy();
+    if !windows {
+        return raw.to_string();
+    }
+
+    let trimmed = if let Some(unc) = raw.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else {
+        raw.strip_prefix(r"\\?\").unwrap_or(&raw).to_string()
+    };
+    trimmed.replace('\\', "/")
+}
+
+pub fn safe_directory_args(path: &Path, windows: bool) -> Vec<String> {
+    vec![
+        "config".to_string(),
+        "--global".to_string(),
+        "--add".to_string(),
+        "safe.directory".to_string(),
+        safe_directory_value(path, windows),
+    ]
+}
+
+/// Adds the repository to the global `safe.directory` list.
+pub fn mark_safe_directory(
+    executor: &dyn GitExecutor,
+    repo_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let args = safe_directory_args(repo_path, cfg!(windows));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = executor.run(repo_path, &args)?;
+
+    if !output.success {
+        return Err(format!("Git config safe.directory failed: {}", output.stderr.trim()).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    struct FakeGit {
+        responses: HashMap<String, GitOutput>,
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl FakeGit {
+        fn new(responses: &[(&str, &str)]) -> Self {
+            Self {
+                responses: responses
+                    .iter()
+                    .map(|(args, stdout)| {
+                        (
+                            args.to_string(),
+                            GitOutput {
+                                success: true,
+                                stdout: stdout.to_string(),
+                                stderr: String::new(),
+                            },
+                        )
+                    })
+                    .collect(),
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl GitExecutor for FakeGit {
+        fn run(&self, _repo_path: &Path, args: &[&str]) -> std::io::Result<GitOutput> {
+            self.calls.borrow_mut().push(args.join(" "));
+            Ok(self
+                .responses
+                .get(&args.join(" "))
+                .cloned()
+                .unwrap_or_default())
+        }
+    }
+
+    #[test]
+    fn reads_name_and_email() {
+        let git = FakeGit::new(&[
+            ("config --get user.name", "Jane Doe\n"),
+            ("config --get user.email", "jane@corp.example\n"),
+        ]);
+
+        assert_eq!(
+            read_identity(&git, Path::new("/repo")),
+            Some(("Jane Doe".to_string(), "jane@corp.example".to_string()))
+        );
+    }
+
+    #[test]
+    fn unset_identity_is_none() {
+        let git = FakeGit::new(&[]);
+        assert_eq!(read_identity(&git, Path::new("/repo")), None);
+    }
+
+    #[test]
+    fn dubious_ownership_is_classified() {
+        let stderr = "fatal: detected dubious ownership in repository at '/srv/build/api'\n\
+                      To add an exception for this directory, call:\n\n\
+                      \tgit config --global --add safe.directory /srv/build/api\n";
+
+        assert_eq!(classify_git_error(stderr), GitErrorKind::DubiousOwnership);
+        assert_eq!(
+            classify_git_error("fatal: not a git repository"),
+            GitErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn protected_branch_rejections_are_classified() {
+        let github = "remote: error: GH006: Protected branch update failed for refs/heads/main.\n\
+                      remote: error: Changes must be made through a pull request.\n\
+                      To github.com:acme/api.git\n \
+                      ! [remote rejected] main -> main (protected branch hook declined)\n\
+                      error: failed to push some refs to 'github.com:acme/api.git'\n";
+        let gitlab = "remote: GitLab: You are not allowed to push code to protected branches on this project.\n\
+                      To gitlab.com:group/api.git\n \
+                      ! [remote rejected] main -> main (pre-receive hook declined)\n";
+        let bitbucket_cloud = "remote: Permission denied to update branch main.\n\
+                               To bitbucket.org:team/api.git\n \
+                               ! [remote rejected] main -> main (pre-receive hook declined)\n";
+        let bitbucket_server = "remote: Branch refs/heads/main can only be modified through pull requests.\n\
+                                remote: Check your branch permissions configuration with the project administrator.\n\
+                                ! [remote rejected] main -> main (pre-receive hook declined)\n";
+
+        for stderr in [github, gitlab, bitbucket_cloud, bitbucket_server] {
+            assert_eq!(classify_git_error(stderr), GitErrorKind::ProtectedBranch);
+        }
+        assert_eq!(
+            classify_git_error("! [rejected] main -> main (non-fast-forward)"),
+            GitErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn safe_directory_value_normalizes_windows_paths() {
+        assert_eq!(
+            safe_directory_value(Path::new(r"C:\Users\build\My Repos\api"), true),
+            "C:/Users/build/My Repos/api"
+        );
+        assert_eq!(
+            safe_directory_value(Path::new(r"\\?\C:\repos\api"), true),
+            "C:/repos/api"
+        );
+        assert_eq!(
+            safe_directory_value(Path::new(r"\\?\UNC\server\share\api"), true),
+            "//server/share/api"
+        );
+        assert_eq!(
+            safe_directory_value(Path::new("/srv/build/my repo"), false),
+            "/srv/build/my repo"
+        );
+    }
+
+    #[test]
+    fn mark_safe_directory_runs_global_config_add() {
+        let path = Path::new("/srv/build/api");
+        let expected = format!(
+            "config --global --add safe.directory {}",
+            safe_directory_value(path, cfg!(windows))
+        );
+        let git = FakeGit::new(&[(expected.as_str(), "")]);
+
+        assert!(mark_safe_directory(&git, path).is_ok());
+        assert_eq!(git.calls.borrow().as_slice(), [expected]);
+    }
+}
@@ -0,0 +1,192 @@
+/// Transfer totals of a fetch, read from git's progress output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FetchStats {
+    pub objects_received: usize,
+    pub bytes_received: u64,
+    /// KiB/s reported at the end of the last transfer.
+    pub speed_kbps: f32,
+}
+
+impl FetchStats {
+    /// `123 objects, 456 KiB @ 1.2 MiB/s`; small fetches that git reports
+    /// without a size only get the object count.
+    pub fn summary(&self) -> String {
+        if self.bytes_received == 0 {
+            return format!("{} objects", self.objects_received);
+        }
+        format!(
+            "{} objects, {} @ {}/s",
+            self.objects_received,
+            format_bytes(self.bytes_received as f64),
+            format_bytes(self.speed_kbps as f64 * 1024.0)
+        )
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const KIB: f64 = 1024.0;
+    if bytes < KIB {
+        format!("{} B", bytes as u64)
+    } else if bytes < KIB * KIB {
+        format!("{} KiB", (bytes / KIB) as u64)
+    } else if bytes < KIB * KIB * KIB {
+        format!("{:.1} MiB", bytes / KIB / KIB)
+    } else {
+        format!("{:.1} GiB", bytes / KIB / KIB / KIB)
+    }
+}
+
+/// `456.78 KiB` → bytes.
+fn parse_size(size: &str) -> Option<f64> {
+    let (value, unit) = size.trim().split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let scale = match unit {
+        "bytes" | "byte" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * scale)
+}
+
+/// Totals of every finished `Receiving objects` line in the stderr of
+/// `git fetch --progress`. Progress updates are separated by `\r`; only
+/// the final `..., done.` update of each transfer counts, so a fetch with
+/// submodules or several remotes adds up. `None` when nothing was received.
+pub fn parse_fetch_stats(stderr: &str) -> Option<FetchStats> {
+    let mut stats: Option<FetchStats> = None;
+
+    for update in stderr.split(['\r', '\n']) {
+        let Some(rest) = update.trim().strip_prefix("Receiving objects:") else {
+            continue;
+        };
+        let Some(rest) = rest.trim_end().strip_suffix(", done.") else {
+            continue;
+        };
+
+        // ` 100% (123/123), 456.78 KiB | 1.23 MiB/s`
+        let Some((counts, transfer)) = rest
+            .split_once('(')
+            .and_then(|(_, counts)| counts.split_once(')'))
+        else {
+            continue;
+        };
+        let Some(objects) = counts
+            .split_once('/')
+            .and_then(|(_, total)| total.parse::<usize>().ok())
+        else {
+            continue;
+        };
+
+        let transfer = transfer.trim_start_matches(',').trim();
+        let (bytes, speed) = match transfer.split_once('|') {
+            Some((size, speed)) => (
+                parse_size(size).unwrap_or(0.0),
+                speed
+                    .trim()
+                    .strip_suffix("/s")
+                    .and_then(parse_size)
+                    .unwrap_or(0.0),
+            ),
+            None => (0.0, 0.0),
+        };
+
+        let total = stats.get_or_insert(FetchStats {
+            objects_received: 0,
+            bytes_received: 0,
+            speed_kbps: 0.0,
+        });
+        total.objects_received += objects;
+        total.bytes_received += bytes as u64;
+        total.speed_kbps = (speed / 1024.0) as f32;
+    }
+
+    stats
+}
+
+/// Stderr of `git fetch --progress` without the progress updates, for error
+/// messages: of every `\r`-separated line only the last update is kept, and
+/// counters such as `Receiving objects: ...` are dropped entirely.
+pub fn strip_fetch_progress(stderr: &str) -> String {
+    const PROGRESS: [&str; 6] = [
+        "Enumerating objects:",
+        "Counting objects:",
+        "Compressing objects:",
+        "Receiving objects:",
+        "Resolving deltas:",
+        "Total ",
+    ];
+
+    stderr
+        .lines()
+        .map(|line| line.rsplit('\r').next().unwrap_or(line).trim_end())
+        .filter(|line| {
+            let line = line.strip_prefix("remote:").unwrap_or(line).trim_start();
+            !line.is_empty() && !PROGRESS.iter().any(|prefix| line.starts_with(prefix))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_progress_update_gives_the_totals() {
+        let stderr = "remote: Enumerating objects: 130, done.\n\
+                      Receiving objects:  45% (56/123)\r\
+                      Receiving objects: 100% (123/123), 456.78 KiB | 1.23 MiB/s, done.\n\
+                      Resolving deltas: 100% (40/40), done.\n\
+                      From github.com:acme/api\n   3f2c1a9..8e7d6c5  main -> origin/main\n";
+
+        let stats = parse_fetch_stats(stderr).unwrap();
+
+        assert_eq!(stats.objects_received, 123);
+        assert_eq!(stats.bytes_received, 467742);
+        assert!((stats.speed_kbps - 1259.52).abs() < 0.01);
+        assert_eq!(stats.summary(), "123 objects, 456 KiB @ 1.2 MiB/s");
+    }
+
+    #[test]
+    fn small_and_multiple_transfers_add_up() {
+        // Основной репозиторий без размера, затем подмодуль
+        let stderr = "Receiving objects: 100% (3/3), done.\n\
+                      Fetching submodule vendor/lib\n\
+                      Receiving objects: 100% (10/10), 2.00 MiB | 512.00 KiB/s, done.\n";
+
+        let stats = parse_fetch_stats(stderr).unwrap();
+
+        assert_eq!(stats.objects_received, 13);
+        assert_eq!(stats.bytes_received, 2 * 1024 * 1024);
+        assert_eq!(stats.summary(), "13 objects, 2.0 MiB @ 512 KiB/s");
+    }
+
+    #[test]
+    fn up_to_date_fetch_has_no_stats() {
+        assert_eq!(parse_fetch_stats(""), None);
+        assert_eq!(
+            parse_fetch_stats("Receiving objects:  50% (1/2)\rfatal: the remote end hung up\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn progress_is_stripped_from_errors() {
+        let stderr = "remote: Enumerating objects: 5, done.\n\
+                      remote: Counting objects:  50% (1/2)\rremote: Counting objects: 100% (2/2), done.\n\
+                      Receiving objects:  50% (1/2)\r\
+                      fatal: the remote end hung up unexpectedly\n\
+                      fatal: early EOF\n";
+
+        assert_eq!(
+            strip_fetch_progress(stderr),
+            "fatal: the remote end hung up unexpectedly\nfatal: early EOF"
+        );
+        assert_eq!(
+            strip_fetch_progress("fatal: 'origin' does not appear to be a git repository\n"),
+            "fatal: 'origin' does not appear to be a git repository"
+        );
+    }
+}
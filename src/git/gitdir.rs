@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+/// Result of checking that a folder is a usable git checkout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum GitDirCheck {
+    #[default]
+    Valid,
+    /// The folder itself is gone.
+    Missing,
+    /// The folder has no `.git` at all.
+    NotARepository,
+    /// A `.git` file without a `gitdir:` line, e.g. left by a broken clone.
+    EmptyGitFile,
+    /// `gitdir:` points at a folder that does not exist or has no HEAD.
+    BrokenGitdir(PathBuf),
+    /// A `.git` folder without HEAD.
+    MissingHead,
+}
+
+impl GitDirCheck {
+    pub fn is_valid(&self) -> bool {
+        *self == GitDirCheck::Valid
+    }
+
+    /// Present on disk but unusable, as opposed to gone.
+    pub fn is_invalid(&self) -> bool {
+        !matches!(self, GitDirCheck::Valid | GitDirCheck::Missing)
+    }
+
+    pub fn tooltip_key(&self) -> &'static str {
+        match self {
+            GitDirCheck::Valid => "",
+            GitDirCheck::Missing => "repo_missing_tooltip",
+            GitDirCheck::NotARepository => "repo_not_a_repository_tooltip",
+            GitDirCheck::EmptyGitFile => "repo_empty_git_file_tooltip",
+            GitDirCheck::BrokenGitdir(_) => "repo_broken_gitdir_tooltip",
+            GitDirCheck::MissingHead => "repo_missing_head_tooltip",
+        }
+    }
+}
+
+/// `gitdir: <path>` from the contents of a `.git` file; a relative path is
+/// taken from the folder holding that file.
+pub fn parse_gitdir_pointer(git_file: &str, repo_path: &Path) -> Option<PathBuf> {
+    git_file
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("gitdir:"))
+        .map(str::trim)
+        .filter(|gitdir| !gitdir.is_empty())
+        .map(|gitdir| repo_path.join(gitdir))
+}
+
+/// Checks the `.git` of `repo_path` without running git: a `.git` folder
+/// must hold HEAD, a `.git` file (worktrees, submodules, separate git
+/// dirs) must point at a folder that does.
+pub fn check_git_dir(repo_path: &Path) -> GitDirCheck {
+    if !repo_path.is_dir() {
+        return GitDirCheck::Missing;
+    }
+
+    let dot_git = repo_path.join(".git");
+    if dot_git.is_dir() {
+        return if dot_git.join("HEAD").is_file() {
+            GitDirCheck::Valid
+        } else {
+            GitDirCheck::MissingHead
+        };
+    }
+
+    let Ok(git_file) = std::fs::read_to_string(&dot_git) else {
+        return GitDirCheck::NotARepository;
+    };
+    match parse_gitdir_pointer(&git_file, repo_path) {
+        Some(gitdir) if gitdir.join("HEAD").is_file() => GitDirCheck::Valid,
+        Some(gitdir) => GitDirCheck::BrokenGitdir(gitdir),
+        None => GitDirCheck::EmptyGitFile,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("repo_manager_gitdir_{}", name));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn make_git_dir(path: &Path) {
+        std::fs::create_dir_all(path).unwrap();
+        std::fs::write(path.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+    }
+
+    #[test]
+    fn git_folder_needs_head() {
+        let root = fixture("folder");
+        make_git_dir(&root.join("ok/.git"));
+        std::fs::create_dir_all(root.join("broken/.git")).unwrap();
+        std::fs::create_dir_all(root.join("plain")).unwrap();
+
+        let ok = check_git_dir(&root.join("ok"));
+        let broken = check_git_dir(&root.join("broken"));
+        let plain = check_git_dir(&root.join("plain"));
+        let missing = check_git_dir(&root.join("gone"));
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(ok, GitDirCheck::Valid);
+        assert_eq!(broken, GitDirCheck::MissingHead);
+        assert_eq!(plain, GitDirCheck::NotARepository);
+        assert_eq!(missing, GitDirCheck::Missing);
+        assert!(broken.is_invalid());
+        assert!(!missing.is_invalid());
+    }
+
+    #[test]
+    fn git_file_is_followed_to_its_gitdir() {
+        let root = fixture("file");
+        // Отдельный каталог git, как у worktree или --separate-git-dir
+        make_git_dir(&root.join("store/app.git"));
+        std::fs::create_dir_all(root.join("absolute")).unwrap();
+        std::fs::write(
+            root.join("absolute/.git"),
+            format!("gitdir: {}\n", root.join("store/app.git").display()),
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("relative")).unwrap();
+        std::fs::write(root.join("relative/.git"), "gitdir: ../store/app.git\n").unwrap();
+
+        let absolute = check_git_dir(&root.join("absolute"));
+        let relative = check_git_dir(&root.join("relative"));
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(absolute, GitDirCheck::Valid);
+        assert_eq!(relative, GitDirCheck::Valid);
+    }
+
+    #[test]
+    fn stray_or_dangling_git_file_is_invalid() {
+        let root = fixture("stray");
+        // Пустой .git от прерванного клонирования
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::write(root.join("empty/.git"), "").unwrap();
+        std::fs::create_dir_all(root.join("dangling")).unwrap();
+        std::fs::write(root.join("dangling/.git"), "gitdir: ../nowhere.git\n").unwrap();
+
+        let empty = check_git_dir(&root.join("empty"));
+        let dangling = check_git_dir(&root.join("dangling"));
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(empty, GitDirCheck::EmptyGitFile);
+        assert_eq!(
+            dangling,
+            GitDirCheck::BrokenGitdir(root.join("dangling").join("../nowhere.git"))
+        );
+    }
+
+    #[test]
+    fn gitdir_pointer_is_parsed() {
+        let repo = Path::new("/src/app/libs/core");
+        assert_eq!(
+            parse_gitdir_pointer("gitdir: ../../.git/modules/core\n", repo),
+            Some(repo.join("../../.git/modules/core"))
+        );
+        assert_eq!(parse_gitdir_pointer("gitdir:   \n", repo), None);
+        assert_eq!(parse_gitdir_pointer("", repo), None);
+    }
+}
@@ -0,0 +1,120 @@
+use super::{git_command, GitMessage, PoolGuard};
+use crossbeam_channel::Sender;
+use std::path::PathBuf;
+
+/// Every field is preceded by NUL, so the graph drawn by `--graph` ends up
+/// alone in front of the first separator.
+const GRAPH_LOG_FORMAT: &str = "--format=%x00%H%x00%s%x00%an%x00%ai";
+
+/// A row of `git log --graph`. Rows that only continue the graph (`|\`,
+/// `|/`) have empty commit fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphLogLine {
+    pub graph_prefix: String,
+    pub hash: String,
+    pub subject: String,
+    pub author: String,
+    /// `%ai`, e.g. `2024-05-01 12:30:05 +0200`.
+    pub date: String,
+}
+
+impl GraphLogLine {
+    pub fn is_commit(&self) -> bool {
+        !self.hash.is_empty()
+    }
+
+    pub fn short_hash(&self) -> &str {
+        self.hash.get(..7).unwrap_or(&self.hash)
+    }
+}
+
+pub fn parse_log_graph(output: &str) -> Vec<GraphLogLine> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut fields = line.split('\0');
+            let graph_prefix = fields.next().unwrap_or_default().trim_end().to_string();
+            let mut next = || fields.next().unwrap_or_default().to_string();
+            GraphLogLine {
+                graph_prefix,
+                hash: next(),
+                subject: next(),
+                author: next(),
+                date: next(),
+            }
+        })
+        .collect()
+}
+
+pub fn git_log_graph(
+    repo_path: &PathBuf,
+    limit: usize,
+) -> Result<Vec<GraphLogLine>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["log", "--graph", &format!("-n{}", limit), GRAPH_LOG_FORMAT])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git log --graph failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(parse_log_graph(&String::from_utf8_lossy(&output.stdout)))
+}
+
+pub fn git_log_graph_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let lines = git_log_graph(&repo_path, 200).map_err(|e| e.to_string());
+        let _ = tx.send(T::from(GitMessage::CommitGraphLoaded { repo_path, lines }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_prefix_is_split_from_commit_fields() {
+        let output =
+            "*   \x00a1b2c3d4e5f6\x00Merge branch 'feature'\x00Ann\x002024-05-01 12:30:05 +0200\n\
+                      |\\  \n\
+                      | * \x00f6e5d4c3b2a1\x00Add parser\x00Bob\x002024-04-30 09:00:00 +0000\n\
+                      |/  \n";
+
+        let lines = parse_log_graph(output);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            GraphLogLine {
+                graph_prefix: "*".to_string(),
+                hash: "a1b2c3d4e5f6".to_string(),
+                subject: "Merge branch 'feature'".to_string(),
+                author: "Ann".to_string(),
+                date: "2024-05-01 12:30:05 +0200".to_string(),
+            }
+        );
+        assert_eq!(lines[1].graph_prefix, "|\\");
+        assert!(!lines[1].is_commit());
+        assert_eq!(lines[2].graph_prefix, "| *");
+        assert_eq!(lines[2].short_hash(), "f6e5d4c");
+        assert_eq!(lines[3].graph_prefix, "|/");
+    }
+
+    #[test]
+    fn subject_keeps_graph_like_characters() {
+        let lines = parse_log_graph("* \x00abc\x00Fix | and * in table\x00Ann\x002024-01-01\n");
+
+        assert_eq!(lines[0].subject, "Fix | and * in table");
+        assert_eq!(lines[0].short_hash(), "abc");
+    }
+}
@@ -0,0 +1,224 @@
+use crate::logging::encode_query;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HostingProvider {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+}
+
+lazy_static::lazy_static! {
+    /// Hosts of enterprise/self-hosted instances, from `Config::provider_hosts`.
+    static ref PROVIDER_HOSTS: Mutex<BTreeMap<String, HostingProvider>> =
+        Mutex::new(BTreeMap::new());
+}
+
+pub fn set_provider_hosts(hosts: BTreeMap<String, HostingProvider>) {
+    if let Ok(mut current) = PROVIDER_HOSTS.lock() {
+        *current = hosts
+            .into_iter()
+            .map(|(host, provider)| (host.trim().to_ascii_lowercase(), provider))
+            .collect();
+    }
+}
+
+/// Web location of a repository hosted by a known provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostedRepo {
+    pub provider: HostingProvider,
+    /// `https://<host>/<owner>/<repo>`, without `.git`.
+    pub web_url: String,
+}
+
+impl HostedRepo {
+    /// Page that opens a pull/merge request from `branch`.
+    pub fn new_pull_request_url(&self, branch: &str) -> String {
+        // `#`, `?`, `&` и `%` в имени ветки сломали бы адрес; `/` оставляем
+        let branch = branch
+            .split('/')
+            .map(encode_query)
+            .collect::<Vec<_>>()
+            .join("/");
+        match self.provider {
+            HostingProvider::GitHub => format!("{}/compare/{}?expand=1", self.web_url, branch),
+            HostingProvider::GitLab => format!(
+                "{}/-/merge_requests/new?merge_request[source_branch]={}",
+                self.web_url, branch
+            ),
+            HostingProvider::Bitbucket => {
+                format!("{}/pull-requests/new?source={}", self.web_url, branch)
+            }
+            HostingProvider::Gitea => format!("{}/compare/{}", self.web_url, branch),
+        }
+    }
+}
+
+/// Provider of `host`: an entry of `configured` (keys in lower case) first,
+/// then the public hosting names.
+pub fn provider_for_host(
+    host: &str,
+    configured: &BTreeMap<String, HostingProvider>,
+) -> Option<HostingProvider> {
+    let host = host.to_ascii_lowercase();
+    if let Some(provider) = configured.get(&host) {
+        return Some(*provider);
+    }
+
+    if host.contains("github") {
+        Some(HostingProvider::GitHub)
+    } else if host.contains("gitlab") {
+        Some(HostingProvider::GitLab)
+    } else if host.contains("bitbucket") {
+        Some(HostingProvider::Bitbucket)
+    } else if host.contains("gitea") {
+        Some(HostingProvider::Gitea)
+    } else {
+        None
+    }
+}
+
+fn configured_provider_for_host(host: &str) -> Option<HostingProvider> {
+    let configured = PROVIDER_HOSTS.lock().ok()?;
+    provider_for_host(host, &configured)
+}
+
+/// Host and repository path of `https://host/owner/repo.git`,
+/// `ssh://git@host[:port]/owner/repo.git` and scp-like `git@host:owner/repo.git`
+/// remotes. Local paths have no host.
+fn split_remote_url(url: &str) -> Option<(&str, &str)> {
+    let url = url.trim();
+    if let Some((_, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?;
+        Some((host.split(':').next()?, path))
+    } else {
+        let (authority, path) = url.split_once(':')?;
+        // `C:\repos\api` — путь Windows, а не scp-адрес
+        if authority.len() == 1 || authority.contains(['/', '\\']) {
+            return None;
+        }
+        Some((authority.rsplit('@').next()?, path))
+    }
+}
+
+/// Provider hosting a remote; `None` for unknown hosts and local paths.
+pub fn remote_provider(url: &str) -> Option<HostingProvider> {
+    let (host, _) = split_remote_url(url)?;
+    configured_provider_for_host(host)
+}
+
+pub fn parse_remote_url(url: &str) -> Option<HostedRepo> {
+    let (host, path) = split_remote_url(url)?;
+
+    let provider = configured_provider_for_host(host)?;
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || !path.contains('/') {
+        return None;
+    }
+
+    Some(HostedRepo {
+        provider,
+        web_url: format!("https://{}/{}", host, path),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_common_remote_forms() {
+        let expected = Some(HostedRepo {
+            provider: HostingProvider::GitHub,
+            web_url: "https://github.com/acme/api".to_string(),
+        });
+
+        assert_eq!(parse_remote_url("git@github.com:acme/api.git"), expected);
+        assert_eq!(
+            parse_remote_url("https://github.com/acme/api.git"),
+            expected
+        );
+        assert_eq!(
+            parse_remote_url("https://token@github.com/acme/api"),
+            expected
+        );
+        assert_eq!(
+            parse_remote_url("ssh://git@github.com:22/acme/api.git\n"),
+            expected
+        );
+        assert_eq!(
+            parse_remote_url("https://git.internal.example/acme/api.git"),
+            None
+        );
+    }
+
+    #[test]
+    fn configured_hosts_take_precedence() {
+        let configured = BTreeMap::from([
+            ("ghe.mycorp.com".to_string(), HostingProvider::GitHub),
+            ("code.mycorp.com".to_string(), HostingProvider::Gitea),
+            ("gitlab.mycorp.com".to_string(), HostingProvider::Gitea),
+        ]);
+
+        assert_eq!(
+            provider_for_host("GHE.mycorp.com", &configured),
+            Some(HostingProvider::GitHub)
+        );
+        assert_eq!(
+            provider_for_host("code.mycorp.com", &configured),
+            Some(HostingProvider::Gitea)
+        );
+        // Явная настройка важнее имени хоста
+        assert_eq!(
+            provider_for_host("gitlab.mycorp.com", &configured),
+            Some(HostingProvider::Gitea)
+        );
+        assert_eq!(
+            provider_for_host("gitea.example.org", &BTreeMap::new()),
+            Some(HostingProvider::Gitea)
+        );
+        assert_eq!(provider_for_host("git.example.org", &configured), None);
+    }
+
+    #[test]
+    fn local_remotes_have_no_host() {
+        assert_eq!(split_remote_url("/srv/git/api.git"), None);
+        assert_eq!(split_remote_url("C:\\repos\\api"), None);
+        assert_eq!(split_remote_url("../api.git"), None);
+        assert_eq!(
+            split_remote_url("git@bitbucket.org:team/api.git"),
+            Some(("bitbucket.org", "team/api.git"))
+        );
+        assert_eq!(remote_provider("file:///srv/git/api.git"), None);
+    }
+
+    #[test]
+    fn pull_request_urls_per_provider() {
+        let gitlab = parse_remote_url("git@gitlab.com:group/sub/api.git").unwrap();
+        assert_eq!(
+            gitlab.new_pull_request_url("feature/login"),
+            "https://gitlab.com/group/sub/api/-/merge_requests/new?merge_request[source_branch]=feature/login"
+        );
+
+        let bitbucket = parse_remote_url("https://bitbucket.org/team/api.git").unwrap();
+        assert_eq!(
+            bitbucket.new_pull_request_url("fix"),
+            "https://bitbucket.org/team/api/pull-requests/new?source=fix"
+        );
+
+        let github = parse_remote_url("git@github.com:acme/api.git").unwrap();
+        assert_eq!(
+            github.new_pull_request_url("fix"),
+            "https://github.com/acme/api/compare/fix?expand=1"
+        );
+        assert_eq!(
+            github.new_pull_request_url("fix/#12&c++"),
+            "https://github.com/acme/api/compare/fix/%2312%26c%2B%2B?expand=1"
+        );
+    }
+}
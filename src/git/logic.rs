@@ -1,22 +1,96 @@
-use std::path::PathBuf;
+use super::{
+    git_command, read_identity, CommitActivity, ContributorEntry, DirtyNoise, FetchStats,
+    GraphLogLine, NoteEntry, RepoDelta, SnapshotChange, SystemGit, WorkspaceSnapshot,
+};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const DEFAULT_BRANCH_LIST_LIMIT: usize = 100;
+
+/// Branches kept in `GitInfo::branches`; the rest are loaded on demand.
+static BRANCH_LIST_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_BRANCH_LIST_LIMIT);
+
+pub fn set_branch_list_limit(limit: usize) {
+    BRANCH_LIST_LIMIT.store(limit.max(1), Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitSummary {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub time: i64,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StashEntry {
+    /// `N` in `stash@{N}`.
+    pub index: usize,
+    pub message: String,
+}
 
 #[derive(Debug, Clone)]
 pub struct GitInfo {
     pub current_branch: Option<String>,
+    /// Branch `origin/HEAD` points to, otherwise a local `main` or `master`.
+    pub default_branch: Option<String>,
+    /// The most recently committed branches, capped by `set_branch_list_limit`.
     pub branches: Vec<String>,
+    pub branch_count_total: usize,
     pub ahead: usize,
     pub behind: usize,
     pub has_changes: bool,
+    pub identity: Option<(String, String)>,
+    pub last_commit_time: Option<i64>,
+    /// Modification time of `.git/index`, i.e. the last stage/checkout.
+    pub index_mtime: Option<i64>,
+    pub tags: Vec<String>,
+    /// `git describe --tags --always`; only the short hash when no tag is
+    /// reachable.
+    pub describe_string: Option<String>,
+    pub has_upstream: bool,
+    /// The current branch tracks a remote branch that no longer exists.
+    pub upstream_gone: bool,
+    pub has_push_hooks: bool,
+    /// URL of `origin`, used to link to the hosting provider.
+    pub remote_url: Option<String>,
+    /// A `git bisect` session is running.
+    pub bisecting: bool,
+    /// Names of the configured remotes, for the per-remote fetch menu.
+    pub remotes: Vec<String>,
+    /// Checked out as a submodule of another repository.
+    pub is_submodule: bool,
+    /// Commits reachable from HEAD in a shallow clone; `None` for full history.
+    pub shallow_depth: Option<usize>,
+    /// Entries of `git status --ignored`; an ignored folder counts once.
+    pub ignored_count: usize,
 }
 
 impl Default for GitInfo {
     fn default() -> Self {
         Self {
             current_branch: None,
+            default_branch: None,
             branches: vec![],
+            branch_count_total: 0,
             ahead: 0,
             behind: 0,
             has_changes: false,
+            identity: None,
+            last_commit_time: None,
+            index_mtime: None,
+            tags: vec![],
+            describe_string: None,
+            has_upstream: false,
+            upstream_gone: false,
+            has_push_hooks: false,
+            remote_url: None,
+            bisecting: false,
+            remotes: vec![],
+            is_submodule: false,
+            shallow_depth: None,
+            ignored_count: 0,
         }
     }
 }
@@ -25,66 +99,173 @@ impl Default for GitInfo {
 pub enum GitMessage {
     RepoStatusUpdated {
         repo_path: PathBuf,
-        git_info: GitInfo,
+        git_info: Box<GitInfo>,
+    },
+    /// A fetch finished; handled like `RepoStatusUpdated` once its
+    /// transfer statistics are logged.
+    FetchComplete {
+        repo_path: PathBuf,
+        git_info: Box<GitInfo>,
+        stats: Option<FetchStats>,
+    },
+    TagsPushed {
+        repo_path: PathBuf,
+        count: usize,
+    },
+    /// A queued operation was cancelled before it got a pool slot.
+    OperationCancelled {
+        repo_path: PathBuf,
+    },
+    OperationOutput {
+        repo_path: PathBuf,
+        line: String,
+    },
+    /// Errors of the log, graph and diff are shown in the commit log window.
+    CommitLogLoaded {
+        repo_path: PathBuf,
+        commits: Result<Vec<CommitSummary>, String>,
+        notes: Vec<NoteEntry>,
+    },
+    CommitGraphLoaded {
+        repo_path: PathBuf,
+        lines: Result<Vec<GraphLogLine>, String>,
+    },
+    CommitDiffLoaded {
+        repo_path: PathBuf,
+        hash: String,
+        diff: Result<String, String>,
+    },
+    /// Result of `git_cat_file`; the error is shown in the file window.
+    FileAtCommitLoaded {
+        repo_path: PathBuf,
+        hash: String,
+        file_path: String,
+        content: Result<Vec<u8>, String>,
+    },
+    /// Result of `git_stash_show`; the error is shown in the stash window.
+    StashDiffLoaded {
+        repo_path: PathBuf,
+        index: usize,
+        diff: Result<String, String>,
+    },
+    BranchesLoaded {
+        repo_path: PathBuf,
+        branches: Vec<String>,
+    },
+    BisectStateChanged {
+        repo_path: PathBuf,
+        current_hash: String,
+        remaining_steps: usize,
+    },
+    BisectEnded {
+        repo_path: PathBuf,
+    },
+    /// A pull stopped with merge conflicts in `files`.
+    PullConflicts {
+        repo_path: PathBuf,
+        files: Vec<String>,
+    },
+    /// Files still unmerged after the user re-checked a conflicted merge.
+    ConflictsChecked {
+        repo_path: PathBuf,
+        files: Vec<String>,
+    },
+    DigestReady {
+        tips: Vec<(PathBuf, String)>,
+        deltas: Vec<RepoDelta>,
+        auto: bool,
+    },
+    /// `git push --mirror` succeeded; a status update follows.
+    MirrorPushed {
+        repo_path: PathBuf,
+        remote: String,
+    },
+    /// `git push --force-with-lease` succeeded; a status update follows.
+    ForcePushed {
+        repo_path: PathBuf,
+        commit_before: Option<String>,
+        commit_after: String,
+    },
+    SnapshotTaken {
+        snapshot: WorkspaceSnapshot,
+    },
+    SnapshotCompared {
+        /// `taken_at` of the snapshot compared against.
+        taken_at: u64,
+        changes: Vec<SnapshotChange>,
+    },
+    /// `None` when the history could not be read.
+    CommitActivityLoaded {
+        repo_path: PathBuf,
+        activity: Option<CommitActivity>,
+    },
+    DirtyNoiseChecked {
+        repo_path: PathBuf,
+        noise: DirtyNoise,
+    },
+    ContributorsLoaded {
+        repo_path: PathBuf,
+        /// `--since` value the counts were taken with.
+        since: String,
+        contributors: Vec<ContributorEntry>,
+    },
+    /// Branches merged into `default_branch` that the prune preview offers.
+    MergedBranchesFound {
+        repo_path: PathBuf,
+        default_branch: String,
+        branches: Vec<String>,
+    },
+    /// The prune preview could not list merged branches; shown in the
+    /// preview rather than as a repository error.
+    MergedBranchesScanFailed {
+        repo_path: PathBuf,
+        error: String,
+    },
+    /// Result of a prune batch in one repository; `failed` pairs a branch
+    /// with its error. A status update follows.
+    BranchesPruned {
+        repo_path: PathBuf,
+        deleted: Vec<String>,
+        failed: Vec<(String, String)>,
     },
     Error(String),
 }
 
-pub fn get_git_info(repo_path: &PathBuf) -> Result<GitInfo, Box<dyn std::error::Error>> {
-    if !repo_path.join(".git").exists() {
-        return Err(format!("{:?} is not a git repository", repo_path).into());
-    }
-
-    let repo = gix::open(repo_path)?;
-
-    let current_branch = if let Ok(output) = create_git_command()
-        .args(&["branch", "--show-current"])
-        .current_dir(repo_path)
-        .output()
-    {
-        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if branch.is_empty() {
-            None
-        } else {
-            Some(branch)
-        }
-    } else {
-        None
-    };
+/// Repository path carried by a `GitMessage::Error`: errors are formatted
+/// with the path as the first quoted (`{:?}`) value.
+pub fn error_repo_path(err: &str) -> Option<PathBuf> {
+    let start = err.find('"')?;
+    let end = err[start + 1..].find('"')?;
+    Some(PathBuf::from(&err[start + 1..start + 1 + end]))
+}
 
+/// Branch names from `git branch -a` output, local ones first; a remote
+/// branch is skipped when a local branch with the same name exists.
+pub fn parse_branch_list(output: &str, remotes: &[String]) -> Vec<String> {
     let mut branches = Vec::new();
     let mut local_branches = Vec::new();
     let mut remote_branches = Vec::new();
 
-    let remotes = get_remotes(repo_path);
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
 
-    if let Ok(output) = create_git_command()
-        .args(&["branch", "-a", "--sort=-committerdate"])
-        .current_dir(repo_path)
-        .output()
-    {
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        for line in output_str.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
+        if line.starts_with("* ") {
+            let branch_name = line[2..].to_string();
+            if !local_branches.contains(&branch_name) {
+                local_branches.push(branch_name);
             }
-
-            if line.starts_with("* ") {
-                let branch_name = line[2..].to_string();
-                if !local_branches.contains(&branch_name) {
-                    local_branches.push(branch_name);
-                }
-            } else if line.starts_with("remotes/") {
-                let remote_branch = line.to_string();
-                if !remote_branch.contains("HEAD") {
-                    remote_branches.push(remote_branch);
-                }
-            } else if !line.is_empty() {
-                let local_branch = line.to_string();
-                if !local_branches.contains(&local_branch) {
-                    local_branches.push(local_branch);
-                }
+        } else if line.starts_with("remotes/") {
+            let remote_branch = line.to_string();
+            if !remote_branch.contains("HEAD") {
+                remote_branches.push(remote_branch);
+            }
+        } else if !line.is_empty() {
+            let local_branch = line.to_string();
+            if !local_branches.contains(&local_branch) {
+                local_branches.push(local_branch);
             }
         }
     }
@@ -93,7 +274,7 @@ pub fn get_git_info(repo_path: &PathBuf) -> Result<GitInfo, Box<dyn std::error::
 
     for remote_branch in remote_branches {
         let mut found_local = false;
-        for remote_name in &remotes {
+        for remote_name in remotes {
             if let Some(branch_name) =
                 remote_branch.strip_prefix(&format!("remotes/{}/", remote_name))
             {
@@ -109,53 +290,228 @@ pub fn get_git_info(repo_path: &PathBuf) -> Result<GitInfo, Box<dyn std::error::
         }
     }
 
-    let has_changes = if let Ok(output) = create_git_command()
-        .args(&["status", "--porcelain"])
-        .current_dir(repo_path)
+    branches
+}
+
+/// Keeps the first `limit` branches (the list is sorted by commit date),
+/// plus the current branch so the dropdown can always show it.
+pub fn cap_branches(mut branches: Vec<String>, limit: usize, current: Option<&str>) -> Vec<String> {
+    if branches.len() <= limit {
+        return branches;
+    }
+
+    let current_beyond_limit = current
+        .and_then(|current| branches[limit..].iter().position(|b| b == current))
+        .map(|offset| limit + offset);
+    let current = current_beyond_limit.map(|idx| branches.swap_remove(idx));
+    branches.truncate(limit);
+    branches.extend(current);
+    branches
+}
+
+/// Every branch of the repository, most recently committed first.
+pub fn list_branches(repo_path: &PathBuf) -> Vec<String> {
+    list_branches_for_remotes(repo_path, &get_remotes(repo_path))
+}
+
+fn list_branches_for_remotes(repo_path: &PathBuf, remotes: &[String]) -> Vec<String> {
+    git_command(repo_path)
+        .args(["branch", "-a", "--sort=-committerdate"])
+        .output()
+        .map(|output| parse_branch_list(&String::from_utf8_lossy(&output.stdout), remotes))
+        .unwrap_or_default()
+}
+
+pub fn get_git_info(repo_path: &PathBuf) -> Result<GitInfo, Box<dyn std::error::Error>> {
+    if !repo_path.join(".git").exists() {
+        return Err(format!("{:?} is not a git repository", repo_path).into());
+    }
+
+    let repo = gix::open(repo_path)?;
+
+    let current_branch = if let Ok(output) = git_command(repo_path)
+        .args(&["branch", "--show-current"])
         .output()
     {
-        !output.stdout.is_empty()
+        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if branch.is_empty() {
+            None
+        } else {
+            Some(branch)
+        }
     } else {
-        false
+        None
     };
 
-    let (ahead, behind) = get_ahead_behind(&repo, &current_branch).unwrap_or((0, 0));
+    let remotes = get_remotes(repo_path);
+    let all_branches = list_branches_for_remotes(repo_path, &remotes);
+    let branch_count_total = all_branches.len();
+    let default_branch = git_command(repo_path)
+        .args(["symbolic-ref", "--short", "-q", "refs/remotes/origin/HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| super::parse_remote_head(&String::from_utf8_lossy(&output.stdout)))
+        .or_else(|| {
+            ["main", "master"]
+                .into_iter()
+                .find(|name| all_branches.iter().any(|branch| branch == name))
+                .map(str::to_string)
+        });
+    let branches = cap_branches(
+        all_branches,
+        BRANCH_LIST_LIMIT.load(Ordering::Relaxed),
+        current_branch.as_deref(),
+    );
+
+    let (has_changes, ignored_count) = if let Ok(output) = git_command(repo_path)
+        .args(&["status", "--porcelain", "--ignored"])
+        .output()
+    {
+        parse_status_summary(&String::from_utf8_lossy(&output.stdout))
+    } else {
+        (false, 0)
+    };
+
+    let tags = if let Ok(output) = git_command(repo_path)
+        .args(["tag", "--list", "--sort=-creatordate"])
+        .output()
+    {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let has_upstream = git_command(repo_path)
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    let has_push_hooks = hooks_dir(repo_path)
+        .map(|dir| hook_is_active(&dir, "pre-push"))
+        .unwrap_or(false);
+
+    let (ahead, behind, upstream_gone) =
+        get_ahead_behind(&repo, &current_branch).unwrap_or((0, 0, false));
+    let identity = read_identity(&SystemGit, repo_path);
+
+    let last_commit_time = git_command(repo_path)
+        .args(["log", "-1", "--format=%ct"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse::<i64>()
+                .ok()
+        });
+
+    let index_mtime = std::fs::metadata(repo_path.join(".git").join("index"))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64);
+
+    let remote_url = git_command(repo_path)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|url| !url.is_empty());
 
     Ok(GitInfo {
         current_branch,
+        default_branch,
         branches,
+        branch_count_total,
         ahead,
         behind,
         has_changes,
+        ignored_count,
+        identity,
+        last_commit_time,
+        index_mtime,
+        tags,
+        describe_string: super::git_describe_for_ci(repo_path)
+            .ok()
+            .filter(|describe| !describe.is_empty()),
+        has_upstream,
+        upstream_gone,
+        has_push_hooks,
+        remote_url,
+        bisecting: super::bisect_in_progress(repo_path),
+        remotes,
+        is_submodule: is_submodule_checkout(repo_path),
+        shallow_depth: read_shallow_depth(repo_path),
     })
 }
 
+/// `%(upstream:track)` of a branch whose upstream was deleted on the remote
+/// (and pruned locally) is `[gone]`.
+/// Whether `git status --porcelain --ignored` lists any change, and how
+/// many `!!` (ignored) entries it has.
+pub fn parse_status_summary(output: &str) -> (bool, usize) {
+    let ignored = output
+        .lines()
+        .filter(|line| line.starts_with("!! "))
+        .count();
+    let changed = output
+        .lines()
+        .any(|line| !line.is_empty() && !line.starts_with("!! "));
+    (changed, ignored)
+}
+
+pub fn parse_upstream_gone(track: &str) -> bool {
+    track.trim() == "[gone]"
+}
+
+/// `(ahead, behind, upstream_gone)`; a gone upstream has nothing to compare
+/// against, so the remote lookups are skipped.
 fn get_ahead_behind(
     repo: &gix::Repository,
     current_branch: &Option<String>,
-) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+) -> Result<(usize, usize, bool), Box<dyn std::error::Error>> {
     if let Some(branch_name) = current_branch {
         let repo_path = repo.git_dir().parent().unwrap_or(repo.git_dir());
+
+        let upstream_gone = git_command(repo_path)
+            .args([
+                "for-each-ref",
+                "--format=%(upstream:track)",
+                &format!("refs/heads/{}", branch_name),
+            ])
+            .output()
+            .map(|output| parse_upstream_gone(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or(false);
+        if upstream_gone {
+            return Ok((0, 0, true));
+        }
+
         let remotes = get_remotes(&repo_path.to_path_buf());
 
         for remote_name in &remotes {
             let remote_branch = format!("{}/{}", remote_name, branch_name);
 
-            let check_local_remote = create_git_command()
+            let check_local_remote = git_command(repo_path)
                 .args(&["show-branch", &remote_branch])
-                .current_dir(repo_path)
                 .output();
 
             if let Ok(output) = check_local_remote {
                 if output.status.success() {
-                    let rev_list_result = create_git_command()
+                    let rev_list_result = git_command(repo_path)
                         .args(&[
                             "rev-list",
                             "--count",
                             "--left-right",
                             &format!("{}...{}", branch_name, remote_branch),
                         ])
-                        .current_dir(repo_path)
                         .output();
 
                     if let Ok(output) = rev_list_result {
@@ -165,7 +521,7 @@ fn get_ahead_behind(
                             if let Some((ahead_str, behind_str)) = output_str.split_once('\t') {
                                 let ahead = ahead_str.parse::<usize>().unwrap_or(0);
                                 let behind = behind_str.parse::<usize>().unwrap_or(0);
-                                return Ok((ahead, behind));
+                                return Ok((ahead, behind, false));
                             }
                         }
                     }
@@ -174,27 +530,85 @@ fn get_ahead_behind(
         }
     }
 
-    Ok((0, 0))
+    Ok((0, 0, false))
+}
+
+/// Hooks directory of the repository, honoring `core.hooksPath`.
+fn hooks_dir(repo_path: &Path) -> Option<PathBuf> {
+    let read = |args: &[&str]| {
+        git_command(repo_path)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let dir = read(&["config", "--get", "core.hooksPath"])
+        .or_else(|| read(&["rev-parse", "--git-path", "hooks"]))?;
+
+    // Относительные пути считаются от корня рабочей копии
+    Some(repo_path.join(dir))
 }
 
-fn create_git_command() -> std::process::Command {
-    let mut cmd = std::process::Command::new("git");
+/// A hook runs only if the file exists and (on Unix) is executable;
+/// `*.sample` files shipped by `git init` never run.
+pub fn hook_is_active(hooks_dir: &Path, name: &str) -> bool {
+    let Ok(metadata) = std::fs::metadata(hooks_dir.join(name)) else {
+        return false;
+    };
 
-    #[cfg(windows)]
+    #[cfg(unix)]
     {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000);
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
     }
 
-    cmd
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
 }
 
-fn get_remotes(repo_path: &PathBuf) -> Vec<String> {
-    if let Ok(output) = create_git_command()
-        .args(&["remote"])
-        .current_dir(repo_path)
+pub fn is_shallow_repository(repo_path: &Path) -> bool {
+    git_command(repo_path)
+        .args(["rev-parse", "--is-shallow-repository"])
         .output()
-    {
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+        .unwrap_or(false)
+}
+
+/// Depth of a shallow clone, counted as the commits reachable from HEAD.
+fn read_shallow_depth(repo_path: &Path) -> Option<usize> {
+    if !is_shallow_repository(repo_path) {
+        return None;
+    }
+    git_command(repo_path)
+        .args(["rev-list", "--count", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+}
+
+/// Submodules have a `.git` file pointing into the parent's `.git/modules`;
+/// linked worktrees have one too, pointing into `.git/worktrees`.
+pub fn is_submodule_checkout(repo_path: &Path) -> bool {
+    std::fs::read_to_string(repo_path.join(".git"))
+        .map(|text| is_submodule_gitdir(&text))
+        .unwrap_or(false)
+}
+
+fn is_submodule_gitdir(git_file: &str) -> bool {
+    git_file
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("gitdir:"))
+        .is_some_and(|gitdir| gitdir.replace('\\', "/").contains("/modules/"))
+}
+
+fn get_remotes(repo_path: &PathBuf) -> Vec<String> {
+    if let Ok(output) = git_command(repo_path).args(&["remote"]).output() {
         if output.status.success() {
             let output_str = String::from_utf8_lossy(&output.stdout);
             return output_str
@@ -207,3 +621,75 @@ fn get_remotes(repo_path: &PathBuf) -> Vec<String> {
 
     vec!["origin".to_string()]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn status_summary_separates_ignored_entries() {
+        assert_eq!(parse_status_summary(""), (false, 0));
+        assert_eq!(parse_status_summary("!! target/\n!! .env\n"), (false, 2));
+        assert_eq!(
+            parse_status_summary(" M src/lib.rs\n?? notes.md\n!! target/\n"),
+            (true, 1)
+        );
+    }
+
+    #[test]
+    fn branch_list_counts_locals_and_untracked_remotes() {
+        let output = "* main\n  feature/a\n  remotes/origin/HEAD -> origin/main\n  \
+                      remotes/origin/main\n  remotes/origin/feature/b\n";
+
+        let branches = parse_branch_list(output, &names(&["origin"]));
+
+        assert_eq!(
+            branches,
+            names(&["main", "feature/a", "remotes/origin/feature/b"])
+        );
+    }
+
+    #[test]
+    fn branch_list_is_capped_but_keeps_current_branch() {
+        let all: Vec<String> = (0..250).map(|i| format!("branch-{}", i)).collect();
+
+        let capped = cap_branches(all.clone(), 100, Some("branch-3"));
+        assert_eq!(capped.len(), 100);
+        assert_eq!(capped[..], all[..100]);
+
+        let capped = cap_branches(all.clone(), 100, Some("branch-200"));
+        assert_eq!(capped.len(), 101);
+        assert_eq!(capped.last().unwrap(), "branch-200");
+
+        assert_eq!(
+            cap_branches(names(&["a", "b"]), 100, None),
+            names(&["a", "b"])
+        );
+    }
+
+    #[test]
+    fn gone_upstream_is_read_from_the_track_field() {
+        // Вывод `git for-each-ref --format=%(upstream:track)`
+        assert!(parse_upstream_gone("[gone]\n"));
+        assert!(!parse_upstream_gone("[ahead 2, behind 1]\n"));
+        assert!(!parse_upstream_gone("[ahead 1]"));
+        assert!(!parse_upstream_gone("\n"));
+        assert!(!parse_upstream_gone(""));
+    }
+
+    #[test]
+    fn submodules_are_told_apart_from_worktrees() {
+        assert!(is_submodule_gitdir(
+            "gitdir: ../../.git/modules/libs/core\n"
+        ));
+        assert!(is_submodule_gitdir(r"gitdir: C:\src\app\.git\modules\core"));
+        assert!(!is_submodule_gitdir(
+            "gitdir: /src/app/.git/worktrees/feature\n"
+        ));
+        assert!(!is_submodule_gitdir(""));
+    }
+}
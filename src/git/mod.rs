@@ -1,7 +1,37 @@
+pub mod activity;
+pub mod bisect;
+pub mod branches;
+pub mod command;
+pub mod conflicts;
+pub mod digest;
+pub mod executor;
+pub mod fetch_stats;
+pub mod gitdir;
+pub mod graph;
+pub mod hosting;
 pub mod logic;
+pub mod noise;
+pub mod notes;
 pub mod operations;
 pub mod pool;
+pub mod snapshot;
+pub mod stream;
 
+pub use activity::*;
+pub use bisect::*;
+pub use branches::*;
+pub use command::*;
+pub use conflicts::*;
+pub use digest::*;
+pub use executor::*;
+pub use fetch_stats::*;
+pub use gitdir::*;
+pub use graph::*;
+pub use hosting::*;
 pub use logic::*;
+pub use noise::*;
+pub use notes::*;
 pub use operations::*;
 pub use pool::*;
+pub use snapshot::*;
+pub use stream::*;
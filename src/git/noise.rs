@@ -0,0 +1,177 @@
+use super::{git_command, GitMessage, PoolGuard};
+use crossbeam_channel::Sender;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Why a working tree reports changes nobody made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirtyNoise {
+    /// Real edits, or nothing the check recognises.
+    Genuine,
+    /// The dirty files only differ in line endings (`core.autocrlf`).
+    LineEndings(Vec<String>),
+    /// The dirty files collide with another tracked path differing only in
+    /// case, so a case-insensitive checkout keeps one of them modified.
+    CaseCollisions(Vec<String>),
+}
+
+/// Tracked paths reported by `git status --porcelain`; untracked files are
+/// real changes and are left out.
+pub fn parse_dirty_tracked_paths(status: &str) -> Vec<String> {
+    status
+        .lines()
+        .filter(|line| line.len() > 3 && !line.starts_with("??") && !line.starts_with("!!"))
+        .map(|line| {
+            let path = &line[3..];
+            // Переименование: `old -> new`, интересен новый путь
+            path.rsplit(" -> ").next().unwrap_or(path).to_string()
+        })
+        .collect()
+}
+
+/// Tracked paths that clash with another tracked path differing only in case.
+pub fn case_colliding_paths(ls_files: &str) -> Vec<String> {
+    let mut by_folded: HashMap<String, Vec<&str>> = HashMap::new();
+    for path in ls_files.lines().filter(|line| !line.is_empty()) {
+        by_folded.entry(path.to_lowercase()).or_default().push(path);
+    }
+
+    let mut colliding: Vec<String> = by_folded
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flatten()
+        .map(str::to_string)
+        .collect();
+    colliding.sort();
+    colliding
+}
+
+/// Decides between the noise kinds from the outputs of `git status
+/// --porcelain` and `git ls-files`; `differs_ignoring_cr` is whether
+/// `git diff --ignore-cr-at-eol --quiet HEAD` still found changes.
+pub fn classify_dirty_noise(status: &str, ls_files: &str, differs_ignoring_cr: bool) -> DirtyNoise {
+    let dirty = parse_dirty_tracked_paths(status);
+    if dirty.is_empty() || dirty.len() != status.lines().filter(|l| !l.is_empty()).count() {
+        return DirtyNoise::Genuine;
+    }
+
+    let colliding = case_colliding_paths(ls_files);
+    if dirty.iter().all(|path| colliding.contains(path)) {
+        return DirtyNoise::CaseCollisions(colliding);
+    }
+
+    if !differs_ignoring_cr {
+        return DirtyNoise::LineEndings(dirty);
+    }
+
+    DirtyNoise::Genuine
+}
+
+fn run_git(repo_path: &Path, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path).args(args).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn git_check_dirty_noise(repo_path: &Path) -> Result<DirtyNoise, Box<dyn std::error::Error>> {
+    let status = run_git(
+        repo_path,
+        &["-c", "core.quotepath=off", "status", "--porcelain"],
+    )?;
+    let ls_files = run_git(repo_path, &["-c", "core.quotepath=off", "ls-files"])?;
+    // --name-only перечисляет файлы и с одними CRLF, ответ даёт только код выхода
+    let diff = git_command(repo_path)
+        .args(["diff", "--ignore-cr-at-eol", "--quiet", "HEAD"])
+        .output()?;
+    let differs_ignoring_cr = match diff.status.code() {
+        Some(0) => false,
+        Some(1) => true,
+        _ => {
+            return Err(
+                format!("Git diff failed: {}", String::from_utf8_lossy(&diff.stderr)).into(),
+            )
+        }
+    };
+
+    Ok(classify_dirty_noise(
+        &status,
+        &ls_files,
+        differs_ignoring_cr,
+    ))
+}
+
+/// Runs the check when the changes indicator is hovered; a failed check
+/// counts as genuine changes.
+pub fn check_dirty_noise_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let noise = git_check_dirty_noise(&repo_path).unwrap_or(DirtyNoise::Genuine);
+        let _ = tx.send(T::from(GitMessage::DirtyNoiseChecked { repo_path, noise }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LS_FILES: &str = "README.md\nsrc/Main.rs\nsrc/main.rs\nsrc/lib.rs\n";
+
+    #[test]
+    fn crlf_only_changes_are_line_ending_noise() {
+        let status = " M README.md\n M src/lib.rs\n";
+
+        assert_eq!(
+            classify_dirty_noise(status, LS_FILES, false),
+            DirtyNoise::LineEndings(vec!["README.md".to_string(), "src/lib.rs".to_string()])
+        );
+    }
+
+    #[test]
+    fn case_collisions_are_detected_from_ls_files() {
+        let status = " M src/main.rs\n";
+
+        assert_eq!(
+            classify_dirty_noise(status, LS_FILES, true),
+            DirtyNoise::CaseCollisions(vec!["src/Main.rs".to_string(), "src/main.rs".to_string()])
+        );
+    }
+
+    #[test]
+    fn real_edits_and_untracked_files_are_genuine() {
+        // Содержимое отличается не только окончаниями строк
+        assert_eq!(
+            classify_dirty_noise(" M README.md\n", LS_FILES, true),
+            DirtyNoise::Genuine
+        );
+        // Новый файл — настоящее изменение, даже если остальное шум
+        assert_eq!(
+            classify_dirty_noise(" M README.md\n?? notes.txt\n", LS_FILES, false),
+            DirtyNoise::Genuine
+        );
+        assert_eq!(
+            classify_dirty_noise("", LS_FILES, false),
+            DirtyNoise::Genuine
+        );
+    }
+
+    #[test]
+    fn renamed_entries_use_the_new_path() {
+        assert_eq!(
+            parse_dirty_tracked_paths("R  old.txt -> new.txt\nMM src/lib.rs\n"),
+            vec!["new.txt".to_string(), "src/lib.rs".to_string()]
+        );
+    }
+}
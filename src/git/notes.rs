@@ -0,0 +1,227 @@
+use super::{git_command, git_log_async, GitMessage, PoolGuard};
+use crossbeam_channel::Sender;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// A note from `refs/notes/commits` and the object it is attached to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteEntry {
+    pub object_hash: String,
+    pub note_hash: String,
+    pub content: String,
+}
+
+/// `(note_hash, object_hash)` pairs from `git notes list`.
+pub fn parse_notes_list(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (note, object) = line.trim().split_once(' ')?;
+            Some((note.to_string(), object.to_string()))
+        })
+        .collect()
+}
+
+/// Blob contents from `git cat-file --batch`, keyed by hash; missing
+/// objects are skipped.
+pub fn parse_cat_file_batch(output: &[u8]) -> Vec<(String, String)> {
+    let mut blobs = Vec::new();
+    let mut rest = output;
+
+    while let Some(header_end) = rest.iter().position(|&b| b == b'\n') {
+        let header = String::from_utf8_lossy(&rest[..header_end]).to_string();
+        rest = &rest[header_end + 1..];
+
+        let mut fields = header.split(' ');
+        let (Some(hash), Some(_kind), Some(size)) = (fields.next(), fields.next(), fields.next())
+        else {
+            // `<hash> missing` — содержимого нет
+            continue;
+        };
+        let Ok(size) = size.parse::<usize>() else {
+            break;
+        };
+        if rest.len() < size {
+            break;
+        }
+
+        blobs.push((
+            hash.to_string(),
+            String::from_utf8_lossy(&rest[..size]).to_string(),
+        ));
+        // Содержимое завершается переводом строки
+        rest = rest.get(size + 1..).unwrap_or_default();
+    }
+    blobs
+}
+
+fn read_blobs(
+    repo_path: &Path,
+    hashes: &[String],
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let mut child = git_command(repo_path)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Пишем из отдельного потока, чтобы не упереться в заполненный stdout
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or("cat-file stdin is not available")?;
+    let input = hashes.join("\n") + "\n";
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git cat-file failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(parse_cat_file_batch(&output.stdout))
+}
+
+pub fn git_list_notes(repo_path: &Path) -> Result<Vec<NoteEntry>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path).args(["notes", "list"]).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git notes list failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    let pairs = parse_notes_list(&String::from_utf8_lossy(&output.stdout));
+    if pairs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let note_hashes: Vec<String> = pairs.iter().map(|(note, _)| note.clone()).collect();
+    let contents: std::collections::HashMap<String, String> =
+        read_blobs(repo_path, &note_hashes)?.into_iter().collect();
+
+    Ok(pairs
+        .into_iter()
+        .map(|(note_hash, object_hash)| NoteEntry {
+            content: contents.get(&note_hash).cloned().unwrap_or_default(),
+            object_hash,
+            note_hash,
+        })
+        .collect())
+}
+
+/// Attaches `message` to the commit, replacing a note it already has.
+pub fn git_add_note(
+    repo_path: &Path,
+    commit_hash: &str,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // -f: без него git отказывается перезаписать существующую заметку
+    let output = git_command(repo_path)
+        .args(["notes", "add", "-f", "-m", message, commit_hash])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git notes add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+pub fn git_remove_note(
+    repo_path: &Path,
+    commit_hash: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["notes", "remove", commit_hash])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git notes remove failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Writes the note of a commit, or removes it when `message` is `None`,
+/// then reloads the commit log.
+pub fn git_set_note_async<T>(
+    repo_path: PathBuf,
+    commit_hash: String,
+    message: Option<String>,
+    tx: Sender<T>,
+) where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let result = {
+            let _guard = PoolGuard::acquire();
+            match &message {
+                Some(message) => git_add_note(&repo_path, &commit_hash, message),
+                None => git_remove_note(&repo_path, &commit_hash),
+            }
+        };
+
+        if let Err(e) = result {
+            let _ = tx.send(T::from(GitMessage::Error(format!(
+                "Note update failed for {:?}: {}",
+                repo_path, e
+            ))));
+        }
+        git_log_async(repo_path, tx);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_list_pairs_note_with_object() {
+        let output = "a1b2 c3d4\nffff 0000\n\n";
+
+        assert_eq!(
+            parse_notes_list(output),
+            vec![
+                ("a1b2".to_string(), "c3d4".to_string()),
+                ("ffff".to_string(), "0000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_output_is_split_by_declared_size() {
+        let output = b"aaa blob 12\nline1\nline2\n\nbbb missing\nccc blob 3\nok\n\n";
+
+        assert_eq!(
+            parse_cat_file_batch(output),
+            vec![
+                ("aaa".to_string(), "line1\nline2\n".to_string()),
+                ("ccc".to_string(), "ok\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_batch_output_stops_cleanly() {
+        assert!(parse_cat_file_batch(b"aaa blob 50\nshort\n").is_empty());
+        assert!(parse_cat_file_batch(b"").is_empty());
+    }
+}
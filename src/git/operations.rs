@@ -1,22 +1,63 @@
-use super::{get_git_info, GitMessage, PoolGuard};
+use super::{
+    create_git_command, deletable_merged_branches, get_git_info, git_command, git_list_notes,
+    has_conflict_markers, is_shallow_repository, list_branches, parse_branch_overview,
+    parse_fetch_stats, parse_remote_head, pending_operation, read_bisect_state,
+    read_conflicted_files, run_streaming, strip_fetch_progress, upstream_tip, BisectVerdict,
+    BranchOverview, CommitSummary, FetchStats, GitMessage, OutputStream, PendingOperation,
+    PoolGuard, StashEntry, BRANCH_OVERVIEW_FORMAT,
+};
+use crate::config::CredentialHelper;
+use crate::logging::{record_audit, AuditEntry, AuditOperation};
 use crossbeam_channel::Sender;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 
-fn create_git_command() -> std::process::Command {
-    let mut cmd = std::process::Command::new("git");
-
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000);
-    }
+fn current_branch_name(repo_path: &Path) -> Option<String> {
+    let output = git_command(repo_path)
+        .args(["symbolic-ref", "--short", "-q", "HEAD"])
+        .output()
+        .ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !branch.is_empty()).then_some(branch)
+}
 
-    cmd
+/// Runs a mutating operation and appends its outcome to the audit trail.
+/// `branch` defaults to the branch checked out before the operation.
+fn audited<T>(
+    operation: AuditOperation,
+    repo_path: &Path,
+    branch: Option<&str>,
+    run: impl FnOnce() -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let branch = branch
+        .map(str::to_string)
+        .or_else(|| current_branch_name(repo_path));
+    let result = run();
+    record_audit(AuditEntry::now(
+        operation,
+        repo_path,
+        branch,
+        result.as_ref().err().map(|e| e.to_string()),
+    ));
+    result
 }
 
 pub fn switch_branch(
     repo_path: &PathBuf,
     branch_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    audited(
+        AuditOperation::Checkout,
+        repo_path,
+        Some(branch_name),
+        || switch_branch_unaudited(repo_path, branch_name),
+    )
+}
+
+fn switch_branch_unaudited(
+    repo_path: &PathBuf,
+    branch_name: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let _repo = gix::open(repo_path)?;
     if branch_name.starts_with("remotes/") {
@@ -24,20 +65,18 @@ pub fn switch_branch(
         if parts.len() >= 3 {
             let local_branch_name = parts[2..].join("/");
 
-            let check_local = create_git_command()
+            let check_local = git_command(repo_path)
                 .args(&[
                     "show-ref",
                     "--verify",
                     "--quiet",
                     &format!("refs/heads/{}", local_branch_name),
                 ])
-                .current_dir(repo_path)
                 .output()?;
 
             if check_local.status.success() {
-                let output = create_git_command()
+                let output = git_command(repo_path)
                     .args(&["checkout", &local_branch_name])
-                    .current_dir(repo_path)
                     .output()?;
 
                 if !output.status.success() {
@@ -50,9 +89,8 @@ pub fn switch_branch(
 
                 println!("Switched to existing local branch: {}", local_branch_name);
             } else {
-                let output = create_git_command()
+                let output = git_command(repo_path)
                     .args(&["checkout", "-b", &local_branch_name, branch_name])
-                    .current_dir(repo_path)
                     .output()?;
 
                 if !output.status.success() {
@@ -72,9 +110,8 @@ pub fn switch_branch(
             return Err("Invalid remote branch name format".into());
         }
     } else {
-        let output = create_git_command()
+        let output = git_command(repo_path)
             .args(&["checkout", branch_name])
-            .current_dir(repo_path)
             .output()?;
 
         if !output.status.success() {
@@ -91,131 +128,1623 @@ pub fn switch_branch(
     Ok(())
 }
 
-pub fn git_fetch(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let output = create_git_command()
-        .args(&["fetch"])
-        .current_dir(repo_path)
+/// Creates `branch_name` from the default branch and checks it out. Starts
+/// from `origin/<default>` when the default branch has no local copy.
+pub fn git_create_branch_from_default(
+    repo_path: &PathBuf,
+    branch_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    audited(
+        AuditOperation::Checkout,
+        repo_path,
+        Some(branch_name),
+        || {
+            let default_branch =
+                git_default_branch(repo_path).ok_or("Default branch could not be determined")?;
+            let has_local = git_command(repo_path)
+                .args([
+                    "show-ref",
+                    "--verify",
+                    "--quiet",
+                    &format!("refs/heads/{}", default_branch),
+                ])
+                .status()
+                .is_ok_and(|status| status.success());
+            let start_point = if has_local {
+                default_branch
+            } else {
+                format!("origin/{}", default_branch)
+            };
+
+            let output = git_command(repo_path)
+                .args(["checkout", "-b", branch_name, &start_point])
+                .output()?;
+
+            if !output.status.success() {
+                return Err(format!(
+                    "Git checkout failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                )
+                .into());
+            }
+
+            Ok(())
+        },
+    )
+}
+
+/// `--depth` for fetches of shallow clones; 0 fetches full history.
+static FETCH_DEPTH: AtomicU32 = AtomicU32::new(0);
+
+pub fn set_fetch_depth(depth: Option<u32>) {
+    FETCH_DEPTH.store(depth.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn fetch_depth() -> Option<u32> {
+    Some(FETCH_DEPTH.load(Ordering::Relaxed)).filter(|depth| *depth > 0)
+}
+
+/// Shallow clones are fetched with the configured depth; a full clone is
+/// never made shallow by a fetch. `--progress` makes git report transfer
+/// statistics even though stderr is not a terminal.
+pub fn git_fetch(repo_path: &PathBuf) -> Result<Option<FetchStats>, Box<dyn std::error::Error>> {
+    if let Some(depth) = fetch_depth() {
+        if is_shallow_repository(repo_path) {
+            return git_fetch_shallow(repo_path, depth);
+        }
+    }
+
+    let output = git_command(repo_path)
+        .args(["fetch", "--progress"])
+        .output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        return Err(format!("Git fetch failed: {}", strip_fetch_progress(&stderr)).into());
+    }
+
+    println!("Fetched for repo: {:?}", repo_path);
+    Ok(parse_fetch_stats(&stderr))
+}
+
+pub fn git_fetch_shallow(
+    repo_path: &PathBuf,
+    depth: u32,
+) -> Result<Option<FetchStats>, Box<dyn std::error::Error>> {
+    run_fetch(
+        repo_path,
+        &[&format!("--depth={}", depth), "--update-shallow"],
+    )
+}
+
+/// `git fetch --deepen=<n>`: adds `n` commits of history to a shallow clone.
+pub fn git_deepen(repo_path: &PathBuf, n: u32) -> Result<(), Box<dyn std::error::Error>> {
+    run_fetch(repo_path, &[&format!("--deepen={}", n)]).map(|_| ())
+}
+
+pub fn git_unshallow(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    run_fetch(repo_path, &["--unshallow"]).map(|_| ())
+}
+
+fn run_fetch(
+    repo_path: &PathBuf,
+    args: &[&str],
+) -> Result<Option<FetchStats>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["fetch", "--progress"])
+        .args(args)
+        .output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git fetch {} failed: {}",
+            args.join(" "),
+            strip_fetch_progress(&stderr)
+        )
+        .into());
+    }
+
+    println!("Fetched ({}) for repo: {:?}", args.join(" "), repo_path);
+    Ok(parse_fetch_stats(&stderr))
+}
+
+/// `git fetch --recurse-submodules=on-demand`: also fetches submodules whose
+/// recorded commits changed. Parallelism is left to the repository's own
+/// `submodule.fetchJobs` / `fetch.parallel` settings.
+pub fn git_fetch_with_submodules(
+    repo_path: &PathBuf,
+) -> Result<Option<FetchStats>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["fetch", "--progress", "--recurse-submodules=on-demand"])
+        .output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        return Err(format!("Git fetch failed: {}", strip_fetch_progress(&stderr)).into());
+    }
+
+    println!("Fetched with submodules for repo: {:?}", repo_path);
+    Ok(parse_fetch_stats(&stderr))
+}
+
+pub fn git_fetch_all_remotes(
+    repo_path: &PathBuf,
+) -> Result<Option<FetchStats>, Box<dyn std::error::Error>> {
+    run_fetch(repo_path, &["--all"])
+}
+
+pub fn git_fetch_specific_remote(
+    repo_path: &PathBuf,
+    remote_name: &str,
+) -> Result<Option<FetchStats>, Box<dyn std::error::Error>> {
+    run_fetch(repo_path, &[remote_name])
+}
+
+pub fn git_pull(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::Pull, repo_path, None, || {
+        git_pull_unaudited(repo_path)
+    })
+}
+
+fn git_pull_unaudited(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path).args(["pull"]).output()?;
+
+    if !output.status.success() {
+        // Строки CONFLICT git пишет в stdout
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let conflicts = if has_conflict_markers(&stdout) {
+            stdout.trim_end().to_string()
+        } else {
+            String::new()
+        };
+        return Err(format!(
+            "Git pull failed: {}{}",
+            String::from_utf8_lossy(&output.stderr),
+            conflicts
+        )
+        .into());
+    }
+
+    println!("Pulled for repo: {:?}", repo_path);
+    Ok(())
+}
+
+/// Pushes with output streamed line by line, so pre-push hooks show progress
+/// while they run. `no_verify` skips the hooks entirely.
+pub fn git_push(
+    repo_path: &PathBuf,
+    no_verify: bool,
+    on_line: impl FnMut(OutputStream, &str),
+) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::Push, repo_path, None, || {
+        git_push_unaudited(repo_path, no_verify, on_line)
+    })
+}
+
+fn git_push_unaudited(
+    repo_path: &PathBuf,
+    no_verify: bool,
+    on_line: impl FnMut(OutputStream, &str),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = git_command(repo_path);
+    cmd.arg("push");
+    if no_verify {
+        cmd.arg("--no-verify");
+    }
+
+    let output = run_streaming(cmd, on_line)?;
+
+    if !output.status.success() {
+        return Err(format!("Git push failed: {}", output.stderr).into());
+    }
+
+    println!("Pushed for repo: {:?}", repo_path);
+    Ok(())
+}
+
+fn count_remote_tags(repo_path: &PathBuf, remote: &str) -> Option<usize> {
+    let output = git_command(repo_path)
+        .args(["ls-remote", "--tags", remote])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.ends_with("^{}"))
+            .count(),
+    )
+}
+
+/// Pushes all local tags and returns how many new tags appeared on the remote.
+pub fn git_push_tags(repo_path: &PathBuf) -> Result<usize, Box<dyn std::error::Error>> {
+    audited(AuditOperation::PushTags, repo_path, None, || {
+        git_push_tags_unaudited(repo_path)
+    })
+}
+
+fn git_push_tags_unaudited(repo_path: &PathBuf) -> Result<usize, Box<dyn std::error::Error>> {
+    let before = count_remote_tags(repo_path, "origin").unwrap_or(0);
+
+    let output = git_command(repo_path).args(["push", "--tags"]).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git push --tags failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    let after = count_remote_tags(repo_path, "origin").unwrap_or(before);
+    println!("Pushed tags for repo: {:?}", repo_path);
+    Ok(after.saturating_sub(before))
+}
+
+/// `git describe --tags --always`: `v1.2.3-5-gabcdef` for build versions, or
+/// just the short hash when no tag is reachable.
+pub fn git_describe_for_ci(repo_path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["describe", "--tags", "--always"])
         .output()?;
 
     if !output.status.success() {
         return Err(format!(
-            "Git fetch failed: {}",
+            "Git describe failed: {}",
             String::from_utf8_lossy(&output.stderr)
         )
         .into());
     }
 
-    println!("Fetched for repo: {:?}", repo_path);
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `git push --mirror <remote>`: makes every ref on the remote match the
+/// local ones, deleting remote branches and tags that do not exist here.
+pub fn git_push_mirror(
+    repo_path: &PathBuf,
+    remote: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::PushMirror, repo_path, None, || {
+        let output = git_command(repo_path)
+            .args(["push", "--mirror", remote])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Git push --mirror {} failed: {}",
+                remote,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        println!("Mirrored repo {:?} to {}", repo_path, remote);
+        Ok(())
+    })
+}
+
+/// `git push --force-with-lease` of the current branch. Returns the upstream
+/// commit it replaced (`None` without an upstream) and the commit pushed.
+pub fn git_push_force_with_lease(
+    repo_path: &PathBuf,
+) -> Result<(Option<String>, String), Box<dyn std::error::Error>> {
+    audited(AuditOperation::ForcePush, repo_path, None, || {
+        let before = upstream_tip(repo_path);
+        let head = git_command(repo_path)
+            .args(["rev-parse", "HEAD"])
+            .output()?;
+        if !head.status.success() {
+            return Err(format!(
+                "Git rev-parse failed: {}",
+                String::from_utf8_lossy(&head.stderr).trim()
+            )
+            .into());
+        }
+        let after = String::from_utf8_lossy(&head.stdout).trim().to_string();
+
+        let output = git_command(repo_path)
+            .args(["push", "--force-with-lease"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Git push --force-with-lease failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        println!("Force-pushed repo {:?}", repo_path);
+        Ok((before, after))
+    })
+}
+
+pub fn git_push_tag(repo_path: &PathBuf, tag_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::PushTags, repo_path, None, || {
+        git_push_tag_unaudited(repo_path, tag_name)
+    })
+}
+
+fn git_push_tag_unaudited(
+    repo_path: &PathBuf,
+    tag_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["push", "origin", tag_name])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git push {} failed: {}",
+            tag_name,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    println!("Pushed tag {} for repo: {:?}", tag_name, repo_path);
     Ok(())
 }
 
-pub fn git_pull(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let output = create_git_command()
-        .args(&["pull"])
-        .current_dir(repo_path)
+pub fn git_reset_hard(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::Reset, repo_path, None, || {
+        git_reset_hard_unaudited(repo_path)
+    })
+}
+
+fn git_reset_hard_unaudited(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path).args(["reset", "--hard"]).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git reset failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    println!("Reset hard for repo: {:?}", repo_path);
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CherryEntry {
+    /// `+` not yet upstream, `-` an equivalent change is already upstream.
+    pub sign: char,
+    pub hash: String,
+    pub subject: String,
+}
+
+/// Lines of `git cherry -v`: `+ <hash> <subject>`.
+pub fn parse_cherry(output: &str) -> Vec<CherryEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let sign = chars.next().filter(|sign| *sign == '+' || *sign == '-')?;
+            let rest = chars.as_str().trim_start();
+            let (hash, subject) = rest.split_once(' ').unwrap_or((rest, ""));
+            if hash.is_empty() {
+                return None;
+            }
+            Some(CherryEntry {
+                sign,
+                hash: hash.to_string(),
+                subject: subject.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Commits of the current branch compared with `upstream` by patch identity.
+pub fn git_cherry(
+    repo_path: &PathBuf,
+    upstream: &str,
+) -> Result<Vec<CherryEntry>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["cherry", "-v", upstream])
         .output()?;
 
     if !output.status.success() {
         return Err(format!(
-            "Git pull failed: {}",
+            "Git cherry failed: {}",
             String::from_utf8_lossy(&output.stderr)
         )
         .into());
     }
 
-    println!("Pulled for repo: {:?}", repo_path);
+    Ok(parse_cherry(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// An ignored path and the `.gitignore` rule that matched it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoredFile {
+    pub path: String,
+    /// `source:line: pattern`, e.g. `.gitignore:3: target/`.
+    pub rule: Option<String>,
+}
+
+/// Paths of `git status --ignored --porcelain -z`; ignored folders are
+/// reported once, with a trailing `/`.
+pub fn parse_ignored_paths(output: &str) -> Vec<String> {
+    output
+        .split('\0')
+        .filter_map(|entry| entry.strip_prefix("!! "))
+        .map(str::to_string)
+        .collect()
+}
+
+/// `git check-ignore -v -z`: source, line, pattern and path per match.
+pub fn parse_ignore_rules(output: &str) -> HashMap<String, String> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks_exact(4)
+        .filter(|entry| !entry[0].is_empty())
+        .map(|entry| {
+            (
+                entry[3].to_string(),
+                format!("{}:{}: {}", entry[0], entry[1], entry[2]),
+            )
+        })
+        .collect()
+}
+
+/// Ignored paths of the repository with the rule behind each of them.
+pub fn git_show_ignored(
+    repo_path: &PathBuf,
+) -> Result<Vec<IgnoredFile>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["status", "--ignored", "--porcelain", "-z"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git status failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+    let paths = parse_ignored_paths(&String::from_utf8_lossy(&output.stdout));
+
+    let mut rules = HashMap::new();
+    // Пачками, чтобы не упереться в лимит длины командной строки
+    for chunk in paths.chunks(200) {
+        let output = git_command(repo_path)
+            .args(["check-ignore", "-v", "-z", "--"])
+            .args(chunk)
+            .output()?;
+        // 1 — ни один путь не игнорируется, это не ошибка
+        if !output.status.success() && output.status.code() != Some(1) {
+            return Err(format!(
+                "Git check-ignore failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+        rules.extend(parse_ignore_rules(&String::from_utf8_lossy(&output.stdout)));
+    }
+
+    Ok(paths
+        .into_iter()
+        .map(|path| IgnoredFile {
+            rule: rules.remove(&path),
+            path,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub url: String,
+}
+
+/// Fetch lines of `git remote -v`: `origin\thttps://... (fetch)`.
+pub fn parse_remotes(output: &str) -> Vec<RemoteEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, rest) = line.split_once('\t')?;
+            let url = rest.strip_suffix(" (fetch)")?;
+            Some(RemoteEntry {
+                name: name.to_string(),
+                url: url.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn git_list_remotes(
+    repo_path: &PathBuf,
+) -> Result<Vec<RemoteEntry>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path).args(["remote", "-v"]).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git remote failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(parse_remotes(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Letters, digits, `-`, `_` and `.`, not starting with `-` or `.` and
+/// without the sequences git refuses in ref names.
+pub fn is_valid_remote_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with(['-', '.'])
+        && !name.ends_with('.')
+        && !name.ends_with(".lock")
+        && !name.contains("..")
+}
+
+/// `git remote rename`; git moves the remote-tracking branches and the
+/// upstream settings of local branches along with it.
+pub fn git_rename_remote(
+    repo_path: &PathBuf,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_valid_remote_name(new_name) {
+        return Err(format!("Invalid remote name: {}", new_name).into());
+    }
+
+    let output = git_command(repo_path)
+        .args(["remote", "rename", old_name, new_name])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git remote rename failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    println!(
+        "Renamed remote {} to {} in repo: {:?}",
+        old_name, new_name, repo_path
+    );
     Ok(())
 }
 
-pub fn git_push(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let output = create_git_command()
-        .args(&["push"])
-        .current_dir(repo_path)
+pub fn git_unset_upstream(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["branch", "--unset-upstream"])
         .output()?;
 
     if !output.status.success() {
         return Err(format!(
-            "Git push failed: {}",
+            "Git branch --unset-upstream failed: {}",
             String::from_utf8_lossy(&output.stderr)
         )
         .into());
     }
 
-    println!("Pushed for repo: {:?}", repo_path);
-    Ok(())
+    Ok(())
+}
+
+/// Makes the current branch track `upstream`, e.g. `origin/main`.
+pub fn git_set_upstream(
+    repo_path: &PathBuf,
+    upstream: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["branch", &format!("--set-upstream-to={}", upstream)])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git branch --set-upstream-to failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Every local branch with upstream, ahead/behind and tip age, read with a
+/// single `git for-each-ref`.
+pub fn git_branch_overview(
+    repo_path: &PathBuf,
+) -> Result<Vec<BranchOverview>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args([
+            "for-each-ref",
+            &format!("--format={}", BRANCH_OVERVIEW_FORMAT),
+            "refs/heads",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git for-each-ref failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(parse_branch_overview(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// The branch `origin/HEAD` points to; otherwise `main` or `master` when it
+/// exists locally, otherwise the current branch.
+pub fn git_default_branch(repo_path: &PathBuf) -> Option<String> {
+    let remote_head = git_command(repo_path)
+        .args(["symbolic-ref", "--short", "-q", "refs/remotes/origin/HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| parse_remote_head(&String::from_utf8_lossy(&output.stdout)));
+    if remote_head.is_some() {
+        return remote_head;
+    }
+
+    ["main", "master"]
+        .into_iter()
+        .find(|name| {
+            git_command(repo_path)
+                .args([
+                    "show-ref",
+                    "--verify",
+                    "--quiet",
+                    &format!("refs/heads/{}", name),
+                ])
+                .status()
+                .is_ok_and(|status| status.success())
+        })
+        .map(str::to_string)
+        .or_else(|| current_branch_name(repo_path))
+}
+
+/// Local branches fully merged into `default_branch`, minus the default
+/// branch, checked out and `protected` ones.
+pub fn git_merged_branches(
+    repo_path: &PathBuf,
+    default_branch: &str,
+    protected: &[String],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["branch", "--merged", default_branch])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git branch --merged failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(deletable_merged_branches(
+        &String::from_utf8_lossy(&output.stdout),
+        default_branch,
+        protected,
+    ))
+}
+
+/// Number of commits on `branch` that are neither on `default_branch` nor
+/// on any remote-tracking branch: what deleting `branch` would lose.
+pub fn git_local_only_commits(
+    repo_path: &PathBuf,
+    branch: &str,
+    default_branch: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args([
+            "rev-list",
+            "--count",
+            &format!("refs/heads/{}", branch),
+            "--not",
+            &format!("refs/heads/{}", default_branch),
+            "--remotes",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git rev-list failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().parse()?)
+}
+
+/// `git branch -d`, or `-D` with `force` for branches already verified as
+/// merged into another branch than HEAD.
+pub fn git_delete_branch(
+    repo_path: &PathBuf,
+    branch_name: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    audited(
+        AuditOperation::DeleteBranch,
+        repo_path,
+        Some(branch_name),
+        || {
+            let output = git_command(repo_path)
+                .args(["branch", if force { "-D" } else { "-d" }, branch_name])
+                .output()?;
+
+            if !output.status.success() {
+                return Err(format!(
+                    "Git branch delete failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                )
+                .into());
+            }
+
+            println!("Deleted branch {} in repo: {:?}", branch_name, repo_path);
+            Ok(())
+        },
+    )
+}
+
+/// Remote-tracking refs listed by `git remote prune --dry-run` as
+/// `* [would prune] origin/feature/old`.
+pub fn parse_prune_dryrun(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("* [would prune] "))
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// Stale `origin/*` refs. Unlike `fetch --prune` nothing is fetched: the
+/// remote is only asked for its branch list and no refs are deleted.
+pub fn git_remote_prune_dryrun(
+    repo_path: &PathBuf,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["remote", "prune", "--dry-run", "origin"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git remote prune failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(parse_prune_dryrun(&String::from_utf8_lossy(&output.stdout)))
+}
+
+pub fn git_remote_prune(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::PruneBranches, repo_path, None, || {
+        git_remote_prune_unaudited(repo_path)
+    })
+}
+
+fn git_remote_prune_unaudited(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["remote", "prune", "origin"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git remote prune failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    println!("Pruned stale remote refs for repo: {:?}", repo_path);
+    Ok(())
+}
+
+/// `git apply` (working tree only) or `git am` (one commit per patch).
+pub fn git_apply(
+    repo_path: &PathBuf,
+    patch_path: &Path,
+    create_commits: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let command = if create_commits { "am" } else { "apply" };
+    let output = git_command(repo_path)
+        .arg(command)
+        .arg(patch_path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git {} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    println!("Applied patch {:?} to repo: {:?}", patch_path, repo_path);
+    Ok(())
+}
+
+/// Outcome of `git apply --3way`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyResult {
+    Success,
+    /// The patch went in, with conflict markers left in these files.
+    ConflictsCreated {
+        conflicted_files: Vec<String>,
+    },
+    Failed(String),
+}
+
+fn apply_3way_result(success: bool, conflicted_files: Vec<String>, stderr: &str) -> ApplyResult {
+    if success {
+        ApplyResult::Success
+    } else if !conflicted_files.is_empty() {
+        ApplyResult::ConflictsCreated { conflicted_files }
+    } else {
+        ApplyResult::Failed(stderr.trim().to_string())
+    }
+}
+
+/// `git apply --3way`: falls back to a merge with the index as the base,
+/// leaving conflict markers instead of rejecting the patch.
+pub fn git_apply_3way(
+    repo_path: &PathBuf,
+    patch_path: &Path,
+) -> Result<ApplyResult, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["apply", "--3way"])
+        .arg(patch_path)
+        .output()?;
+
+    let conflicted_files = if output.status.success() {
+        Vec::new()
+    } else {
+        read_conflicted_files(repo_path)?
+    };
+
+    Ok(apply_3way_result(
+        output.status.success(),
+        conflicted_files,
+        &String::from_utf8_lossy(&output.stderr),
+    ))
+}
+
+/// `git am` stopped on a patch and waits for `--continue` or `--abort`.
+pub fn git_am_in_progress(repo_path: &PathBuf) -> bool {
+    git_command(repo_path)
+        .args(["rev-parse", "--git-path", "rebase-apply"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+            repo_path.join(dir).join("applying").exists()
+        })
+        .unwrap_or(false)
+}
+
+/// `git am --continue` when `resume` is set, `git am --abort` otherwise.
+pub fn git_am_resolve(repo_path: &PathBuf, resume: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let flag = if resume { "--continue" } else { "--abort" };
+    let output = git_command(repo_path).args(["am", flag]).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git am {} failed: {}",
+            flag,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+pub fn git_set_local_identity(
+    repo_path: &PathBuf,
+    name: Option<&str>,
+    email: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut settings = vec![("user.email", email)];
+    if let Some(name) = name {
+        settings.push(("user.name", name));
+    }
+
+    for (key, value) in settings {
+        let output = git_command(repo_path)
+            .args(["config", "--local", key, value])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Git config {} failed: {}",
+                key,
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+    }
+
+    println!("Set local identity <{}> for repo: {:?}", email, repo_path);
+    Ok(())
+}
+
+pub fn configure_credential_helper(
+    repo_path: &Path,
+    helper: &CredentialHelper,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = match helper.config_value() {
+        Some(value) if value.is_empty() => {
+            return Err("Custom credential helper is empty".into());
+        }
+        Some(value) => git_command(repo_path)
+            .args(["config", "--local", "credential.helper", &value])
+            .output()?,
+        None => git_command(repo_path)
+            .args(["config", "--local", "--unset-all", "credential.helper"])
+            .output()?,
+    };
+
+    // Exit code 5 from --unset-all means the key was not set locally.
+    let not_set = helper.config_value().is_none() && output.status.code() == Some(5);
+    if !output.status.success() && !not_set {
+        return Err(format!(
+            "Git config credential.helper failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    println!("Configured credential helper for repo: {:?}", repo_path);
+    Ok(())
+}
+
+/// The credential helper from the user's global/system git configuration.
+pub fn global_credential_helper() -> Option<String> {
+    let output = create_git_command(None)
+        .args(["config", "--get", "credential.helper"])
+        .output()
+        .ok()?;
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+pub fn git_log(
+    repo_path: &PathBuf,
+    limit: usize,
+) -> Result<Vec<CommitSummary>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args([
+            "log",
+            &format!("-n{}", limit),
+            "--format=%H%x1f%h%x1f%an%x1f%ct%x1f%s",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git log failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    let commits = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(CommitSummary {
+                hash: fields.next()?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                time: fields.next()?.parse().unwrap_or(0),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect();
+
+    Ok(commits)
+}
+
+pub fn git_show(
+    repo_path: &PathBuf,
+    commit_hash: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["show", "--stat", "--patch", commit_hash])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git show failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Raw contents of `file_path` (relative to the repository root) as of
+/// `commit_hash`, without checking anything out.
+pub fn git_cat_file(
+    repo_path: &PathBuf,
+    commit_hash: &str,
+    file_path: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["cat-file", "-p", &format!("{}:{}", commit_hash, file_path)])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git cat-file failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(output.stdout)
+}
+
+/// Date ranges offered by the contributors window, in days.
+pub const CONTRIBUTOR_RANGES: [u32; 3] = [30, 90, 365];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributorEntry {
+    pub author: String,
+    pub commit_count: usize,
+}
+
+/// `--since` value for the last `days` days.
+pub fn contributor_since(days: u32) -> String {
+    format!("{}.days.ago", days)
+}
+
+/// Lines of `git shortlog -sn`: `    42\tJane Doe`.
+pub fn parse_shortlog(output: &str) -> Vec<ContributorEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (count, author) = line.trim().split_once('\t')?;
+            Some(ContributorEntry {
+                author: author.trim().to_string(),
+                commit_count: count.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Most active authors first, at most `limit`; `since` is a `--since` value.
+pub fn git_shortlog(
+    repo_path: &Path,
+    since: &str,
+    limit: usize,
+) -> Result<Vec<ContributorEntry>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["shortlog", "-sn", "--no-merges"])
+        .arg(format!("--since={}", since))
+        .arg("HEAD")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git shortlog failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    let mut contributors = parse_shortlog(&String::from_utf8_lossy(&output.stdout));
+    contributors.truncate(limit);
+    Ok(contributors)
+}
+
+pub fn git_shortlog_async<T>(repo_path: PathBuf, since: String, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let msg = match git_shortlog(&repo_path, &since, 50) {
+            Ok(contributors) => GitMessage::ContributorsLoaded {
+                repo_path,
+                since,
+                contributors,
+            },
+            Err(e) => GitMessage::Error(format!("Shortlog failed for {:?}: {}", repo_path, e)),
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StashAction {
+    Pop,
+    Apply,
+    Drop,
+}
+
+impl StashAction {
+    pub const ALL: [StashAction; 3] = [StashAction::Pop, StashAction::Apply, StashAction::Drop];
+
+    fn command(self) -> &'static str {
+        match self {
+            StashAction::Pop => "pop",
+            StashAction::Apply => "apply",
+            StashAction::Drop => "drop",
+        }
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            StashAction::Pop => "stash_pop",
+            StashAction::Apply => "stash_apply",
+            StashAction::Drop => "stash_drop",
+        }
+    }
+}
+
+pub fn git_stash_list(repo_path: &PathBuf) -> Result<Vec<StashEntry>, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["stash", "list", "--format=%gs"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git stash list failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .enumerate()
+        .map(|(index, message)| StashEntry {
+            index,
+            message: message.to_string(),
+        })
+        .collect())
+}
+
+pub fn git_stash_show(
+    repo_path: &PathBuf,
+    index: usize,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["stash", "show", "-p", &format!("stash@{{{}}}", index)])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git stash show failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn git_stash_action(
+    repo_path: &PathBuf,
+    action: StashAction,
+    index: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::Stash, repo_path, None, || {
+        git_stash_action_unaudited(repo_path, action, index)
+    })
+}
+
+fn git_stash_action_unaudited(
+    repo_path: &PathBuf,
+    action: StashAction,
+    index: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["stash", action.command(), &format!("stash@{{{}}}", index)])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git stash {} failed: {}",
+            action.command(),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+pub fn git_stash_show_async<T>(repo_path: PathBuf, index: usize, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let diff = git_stash_show(&repo_path, index).map_err(|e| e.to_string());
+        let _ = tx.send(T::from(GitMessage::StashDiffLoaded {
+            repo_path,
+            index,
+            diff,
+        }));
+    });
+}
+
+/// Loads the uncapped branch list for the "Load all branches" dropdown entry.
+pub fn load_all_branches_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let branches = list_branches(&repo_path);
+        let _ = tx.send(T::from(GitMessage::BranchesLoaded {
+            repo_path,
+            branches,
+        }));
+    });
+}
+
+pub fn git_log_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let commits = git_log(&repo_path, 200).map_err(|e| e.to_string());
+        // Без заметок лог всё равно показываем
+        let notes = match &commits {
+            Ok(_) => git_list_notes(&repo_path).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        let _ = tx.send(T::from(GitMessage::CommitLogLoaded {
+            repo_path,
+            commits,
+            notes,
+        }));
+    });
+}
+
+pub fn git_show_async<T>(repo_path: PathBuf, commit_hash: String, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let diff = git_show(&repo_path, &commit_hash).map_err(|e| e.to_string());
+        let _ = tx.send(T::from(GitMessage::CommitDiffLoaded {
+            repo_path,
+            hash: commit_hash,
+            diff,
+        }));
+    });
+}
+
+pub fn git_cat_file_async<T>(
+    repo_path: PathBuf,
+    commit_hash: String,
+    file_path: String,
+    tx: Sender<T>,
+) where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = PoolGuard::acquire();
+
+        let content = git_cat_file(&repo_path, &commit_hash, &file_path).map_err(|e| e.to_string());
+        let _ = tx.send(T::from(GitMessage::FileAtCommitLoaded {
+            repo_path,
+            hash: commit_hash,
+            file_path,
+            content,
+        }));
+    });
+}
+
+pub fn refresh_repo_status_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let start_time = std::time::Instant::now();
+        let repo_name = repo_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        match get_git_info(&repo_path) {
+            Ok(git_info) => {
+                let elapsed = start_time.elapsed();
+                println!("Git info loaded for {} in {:?}", repo_name, elapsed);
+
+                let msg = GitMessage::RepoStatusUpdated {
+                    repo_path,
+                    git_info: Box::new(git_info),
+                };
+                if tx.send(T::from(msg)).is_err() {
+                    eprintln!("Failed to send git info update");
+                }
+            }
+            Err(e) => {
+                let elapsed = start_time.elapsed();
+                println!("Git info failed for {} in {:?}: {}", repo_name, elapsed, e);
+
+                let msg = GitMessage::Error(format!("Git error for {:?}: {}", repo_path, e));
+                if tx.send(T::from(msg)).is_err() {
+                    eprintln!("Failed to send error message");
+                }
+            }
+        }
+    });
+}
+
+/// `git bisect good|bad`; returns git's summary line, e.g. "Bisecting: 3
+/// revisions left to test" or "<hash> is the first bad commit".
+pub fn git_bisect_mark(
+    repo_path: &PathBuf,
+    verdict: BisectVerdict,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let output = git_command(repo_path)
+        .args(["bisect", verdict.as_arg()])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git bisect {} failed: {}",
+            verdict.as_arg(),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string())
+}
+
+pub fn git_bisect_reset(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let output = git_command(repo_path).args(["bisect", "reset"]).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git bisect reset failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Marks the current bisect commit and reports the new state right away
+/// instead of waiting for the next poll.
+pub fn git_bisect_mark_async<T>(repo_path: PathBuf, verdict: BisectVerdict, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        match git_bisect_mark(&repo_path, verdict) {
+            Ok(summary) => {
+                if !summary.is_empty() {
+                    let _ = tx.send(T::from(GitMessage::OperationOutput {
+                        repo_path: repo_path.clone(),
+                        line: summary,
+                    }));
+                }
+                if let Some(state) = read_bisect_state(&repo_path) {
+                    let _ = tx.send(T::from(GitMessage::BisectStateChanged {
+                        repo_path: repo_path.clone(),
+                        current_hash: state.current_hash,
+                        remaining_steps: state.remaining_steps,
+                    }));
+                }
+                match get_git_info(&repo_path) {
+                    Ok(git_info) => {
+                        let msg = GitMessage::RepoStatusUpdated {
+                            repo_path,
+                            git_info: Box::new(git_info),
+                        };
+                        let _ = tx.send(T::from(msg));
+                    }
+                    Err(e) => {
+                        let msg = GitMessage::Error(format!(
+                            "Failed to get git info after bisect for {:?}: {}",
+                            repo_path, e
+                        ));
+                        let _ = tx.send(T::from(msg));
+                    }
+                }
+            }
+            Err(e) => {
+                let msg = GitMessage::Error(format!("Bisect failed for {:?}: {}", repo_path, e));
+                let _ = tx.send(T::from(msg));
+            }
+        }
+    });
+}
+
+/// Aborts the merge, rebase, cherry-pick or revert in progress.
+pub fn git_abort_pending(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::AbortOperation, repo_path, None, || {
+        let operation = pending_operation(repo_path).ok_or("Nothing to abort")?;
+        let output = git_command(repo_path)
+            .args([operation.command(), "--abort"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Git {} --abort failed: {}",
+                operation.command(),
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+
+        println!("Aborted {} for repo: {:?}", operation.command(), repo_path);
+        Ok(())
+    })
 }
 
-pub fn git_reset_hard(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let output = create_git_command()
-        .args(&["reset", "--hard"])
-        .current_dir(repo_path)
-        .output()?;
+/// Stages everything and continues the operation in progress: a merge is
+/// committed with the prepared message, the rest run `--continue`.
+pub fn git_continue_pending(repo_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    audited(AuditOperation::ContinueOperation, repo_path, None, || {
+        let operation = pending_operation(repo_path).ok_or("Nothing to continue")?;
+        let finish: &[&str] = match operation {
+            PendingOperation::Merge => &["commit", "--no-edit"],
+            _ => &[operation.command(), "--continue"],
+        };
+        for args in [&["add", "-A"][..], finish] {
+            // Без редактора: сообщение коммита остаётся подготовленным git
+            let output = git_command(repo_path)
+                .env("GIT_EDITOR", "true")
+                .args(args)
+                .output()?;
 
-    if !output.status.success() {
-        return Err(format!(
-            "Git reset failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        )
-        .into());
-    }
+            if !output.status.success() {
+                return Err(format!(
+                    "Git {} failed: {}{}",
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                )
+                .into());
+            }
+        }
 
-    println!("Reset hard for repo: {:?}", repo_path);
-    Ok(())
+        println!(
+            "Continued {} for repo: {:?}",
+            operation.command(),
+            repo_path
+        );
+        Ok(())
+    })
 }
 
-pub fn refresh_repo_status_async<T>(repo_path: PathBuf, tx: Sender<T>)
+/// Re-reads the unmerged files of a conflicted merge.
+pub fn check_conflicts_async<T>(repo_path: PathBuf, tx: Sender<T>)
 where
     T: From<GitMessage> + Send + 'static,
 {
     std::thread::spawn(move || {
-        let start_time = std::time::Instant::now();
-        let repo_name = repo_path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
+        let msg = match read_conflicted_files(&repo_path) {
+            Ok(files) => GitMessage::ConflictsChecked { repo_path, files },
+            Err(e) => {
+                GitMessage::Error(format!("Conflict check failed for {:?}: {}", repo_path, e))
+            }
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
 
-        match get_git_info(&repo_path) {
-            Ok(git_info) => {
-                let elapsed = start_time.elapsed();
-                println!("Git info loaded for {} in {:?}", repo_name, elapsed);
+/// Aborts the operation in progress when `abort`, otherwise continues it.
+/// A rebase may stop on the next commit's conflicts, which are reported
+/// like those of a pull.
+pub fn git_finish_merge_async<T>(repo_path: PathBuf, abort: bool, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
 
-                let msg = GitMessage::RepoStatusUpdated {
-                    repo_path,
-                    git_info,
-                };
-                if tx.send(T::from(msg)).is_err() {
-                    eprintln!("Failed to send git info update");
-                }
-            }
-            Err(e) => {
-                let elapsed = start_time.elapsed();
-                println!("Git info failed for {} in {:?}: {}", repo_name, elapsed, e);
+        let result = if abort {
+            git_abort_pending(&repo_path)
+        } else {
+            git_continue_pending(&repo_path)
+        };
 
-                let msg = GitMessage::Error(format!("Git error for {:?}: {}", repo_path, e));
-                if tx.send(T::from(msg)).is_err() {
-                    eprintln!("Failed to send error message");
+        if result.is_err() && !abort && pending_operation(&repo_path).is_some() {
+            if let Ok(files) = read_conflicted_files(&repo_path) {
+                if !files.is_empty() {
+                    let _ = tx.send(T::from(GitMessage::PullConflicts { repo_path, files }));
+                    return;
                 }
             }
         }
+
+        let msg = match result.and_then(|_| get_git_info(&repo_path)) {
+            Ok(git_info) => GitMessage::RepoStatusUpdated {
+                repo_path,
+                git_info: Box::new(git_info),
+            },
+            Err(e) => GitMessage::Error(format!("Merge failed for {:?}: {}", repo_path, e)),
+        };
+        let _ = tx.send(T::from(msg));
     });
 }
 
+/// Waits in the pool queue; reports `OperationCancelled` when the user
+/// cancels the operation before it starts.
+fn enqueue_or_report<T>(repo_path: &Path, tx: &Sender<T>) -> Option<PoolGuard>
+where
+    T: From<GitMessage>,
+{
+    let guard = PoolGuard::enqueue(repo_path);
+    if guard.is_none() {
+        let _ = tx.send(T::from(GitMessage::OperationCancelled {
+            repo_path: repo_path.to_path_buf(),
+        }));
+    }
+    guard
+}
+
 pub fn git_pull_fast_async<T>(repo_path: PathBuf, tx: Sender<T>)
 where
     T: From<GitMessage> + Send + 'static,
 {
     std::thread::spawn(move || {
-        let _guard = PoolGuard::acquire();
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
 
         let result = git_pull(&repo_path);
 
+        // Конфликт слияния — не просто ошибка: показываем список файлов
+        if let Err(e) = &result {
+            if has_conflict_markers(&e.to_string()) || pending_operation(&repo_path).is_some() {
+                if let Ok(files) = read_conflicted_files(&repo_path) {
+                    if !files.is_empty() {
+                        let _ = tx.send(T::from(GitMessage::PullConflicts { repo_path, files }));
+                        return;
+                    }
+                }
+            }
+        }
+
         match result {
             Ok(_) => match get_git_info(&repo_path) {
                 Ok(git_info) => {
                     let msg = GitMessage::RepoStatusUpdated {
                         repo_path,
-                        git_info,
+                        git_info: Box::new(git_info),
                     };
                     let _ = tx.send(T::from(msg));
                 }
@@ -235,21 +1764,174 @@ where
     });
 }
 
-pub fn git_push_fast_async<T>(repo_path: PathBuf, tx: Sender<T>)
+pub fn switch_branch_async<T>(repo_path: PathBuf, branch_name: String, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        match switch_branch(&repo_path, &branch_name) {
+            Ok(_) => match get_git_info(&repo_path) {
+                Ok(git_info) => {
+                    let msg = GitMessage::RepoStatusUpdated {
+                        repo_path,
+                        git_info: Box::new(git_info),
+                    };
+                    let _ = tx.send(T::from(msg));
+                }
+                Err(e) => {
+                    let msg = GitMessage::Error(format!(
+                        "Failed to get git info after branch switch for {:?}: {}",
+                        repo_path, e
+                    ));
+                    let _ = tx.send(T::from(msg));
+                }
+            },
+            Err(e) => {
+                let msg = GitMessage::Error(format!(
+                    "Branch switch to {} failed for {:?}: {}",
+                    branch_name, repo_path, e
+                ));
+                let _ = tx.send(T::from(msg));
+            }
+        }
+    });
+}
+
+pub fn create_branch_from_default_async<T>(repo_path: PathBuf, branch_name: String, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let msg = match git_create_branch_from_default(&repo_path, &branch_name)
+            .and_then(|_| get_git_info(&repo_path))
+        {
+            Ok(git_info) => GitMessage::RepoStatusUpdated {
+                repo_path,
+                git_info: Box::new(git_info),
+            },
+            Err(e) => GitMessage::Error(format!(
+                "Branch creation of {} failed for {:?}: {}",
+                branch_name, repo_path, e
+            )),
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+/// Merged local branches of one repository for the workspace-wide prune
+/// preview. Reads only, so it does not queue behind user operations.
+pub fn find_merged_branches_async<T>(repo_path: PathBuf, protected: Vec<String>, tx: Sender<T>)
 where
     T: From<GitMessage> + Send + 'static,
 {
     std::thread::spawn(move || {
         let _guard = PoolGuard::acquire();
 
-        let result = git_push(&repo_path);
+        let Some(default_branch) = git_default_branch(&repo_path) else {
+            let _ = tx.send(T::from(GitMessage::MergedBranchesScanFailed {
+                repo_path,
+                error: "Default branch could not be determined".to_string(),
+            }));
+            return;
+        };
+        let msg = match git_merged_branches(&repo_path, &default_branch, &protected) {
+            Ok(branches) => GitMessage::MergedBranchesFound {
+                repo_path,
+                default_branch,
+                branches,
+            },
+            Err(e) => GitMessage::MergedBranchesScanFailed {
+                repo_path,
+                error: e.to_string(),
+            },
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+/// Deletes branches the preview found merged into `default_branch`. The
+/// preview may be minutes old, so each branch is checked again right before
+/// `-D`; one that gained commits since is reported instead of deleted. A
+/// failing branch does not stop the rest.
+pub fn prune_branches_async<T>(
+    repo_path: PathBuf,
+    default_branch: String,
+    branches: Vec<String>,
+    tx: Sender<T>,
+) where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let mut deleted = Vec::new();
+        let mut failed = Vec::new();
+        let still_merged = git_merged_branches(&repo_path, &default_branch, &[]);
+        for branch in branches {
+            match &still_merged {
+                Ok(merged) if merged.contains(&branch) => {}
+                Ok(_) => {
+                    failed.push((branch, format!("no longer merged into {}", default_branch)));
+                    continue;
+                }
+                Err(e) => {
+                    failed.push((branch, e.to_string()));
+                    continue;
+                }
+            }
+            match git_delete_branch(&repo_path, &branch, true) {
+                Ok(()) => deleted.push(branch),
+                Err(e) => failed.push((branch, e.to_string())),
+            }
+        }
+        let _ = tx.send(T::from(GitMessage::BranchesPruned {
+            repo_path: repo_path.clone(),
+            deleted,
+            failed,
+        }));
+
+        if let Ok(git_info) = get_git_info(&repo_path) {
+            let _ = tx.send(T::from(GitMessage::RepoStatusUpdated {
+                repo_path,
+                git_info: Box::new(git_info),
+            }));
+        }
+    });
+}
+
+pub fn git_push_fast_async<T>(repo_path: PathBuf, no_verify: bool, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let result = git_push(&repo_path, no_verify, |_, line| {
+            let msg = GitMessage::OperationOutput {
+                repo_path: repo_path.clone(),
+                line: line.to_string(),
+            };
+            let _ = tx.send(T::from(msg));
+        });
 
         match result {
             Ok(_) => match get_git_info(&repo_path) {
                 Ok(git_info) => {
                     let msg = GitMessage::RepoStatusUpdated {
                         repo_path,
-                        git_info,
+                        git_info: Box::new(git_info),
                     };
                     let _ = tx.send(T::from(msg));
                 }
@@ -269,21 +1951,131 @@ where
     });
 }
 
+/// Runs `git_push_mirror`, reports `GitMessage::MirrorPushed` and then the
+/// full status, since tracking state can change with every ref.
+pub fn git_push_mirror_async<T>(repo_path: PathBuf, remote: String, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let result = git_push_mirror(&repo_path, &remote).and_then(|_| {
+            let _ = tx.send(T::from(GitMessage::MirrorPushed {
+                repo_path: repo_path.clone(),
+                remote: remote.clone(),
+            }));
+            get_git_info(&repo_path)
+        });
+
+        let msg = match result {
+            Ok(git_info) => GitMessage::RepoStatusUpdated {
+                repo_path,
+                git_info: Box::new(git_info),
+            },
+            Err(e) => GitMessage::Error(format!("Mirror push failed for {:?}: {}", repo_path, e)),
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+/// Runs `git_push_force_with_lease`, reports `GitMessage::ForcePushed` and
+/// then the refreshed status.
+pub fn git_push_force_with_lease_async<T>(repo_path: PathBuf, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let result = git_push_force_with_lease(&repo_path).and_then(|(before, after)| {
+            let _ = tx.send(T::from(GitMessage::ForcePushed {
+                repo_path: repo_path.clone(),
+                commit_before: before,
+                commit_after: after,
+            }));
+            get_git_info(&repo_path)
+        });
+
+        let msg = match result {
+            Ok(git_info) => GitMessage::RepoStatusUpdated {
+                repo_path,
+                git_info: Box::new(git_info),
+            },
+            Err(e) => GitMessage::Error(format!("Force push failed for {:?}: {}", repo_path, e)),
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+pub fn git_push_tags_async<T>(repo_path: PathBuf, tag_name: Option<String>, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let result = match &tag_name {
+            Some(tag) => git_push_tag(&repo_path, tag).map(|_| 1),
+            None => git_push_tags(&repo_path),
+        };
+
+        match result {
+            Ok(count) => {
+                let _ = tx.send(T::from(GitMessage::TagsPushed {
+                    repo_path: repo_path.clone(),
+                    count,
+                }));
+
+                match get_git_info(&repo_path) {
+                    Ok(git_info) => {
+                        let msg = GitMessage::RepoStatusUpdated {
+                            repo_path,
+                            git_info: Box::new(git_info),
+                        };
+                        let _ = tx.send(T::from(msg));
+                    }
+                    Err(e) => {
+                        let msg = GitMessage::Error(format!(
+                            "Failed to get git info after pushing tags for {:?}: {}",
+                            repo_path, e
+                        ));
+                        let _ = tx.send(T::from(msg));
+                    }
+                }
+            }
+            Err(e) => {
+                let msg = GitMessage::Error(format!("Push tags failed for {:?}: {}", repo_path, e));
+                let _ = tx.send(T::from(msg));
+            }
+        }
+    });
+}
+
 pub fn git_fetch_fast_async<T>(repo_path: PathBuf, tx: Sender<T>)
 where
     T: From<GitMessage> + Send + 'static,
 {
     std::thread::spawn(move || {
-        let _guard = PoolGuard::acquire();
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
 
         let result = git_fetch(&repo_path);
 
         match result {
-            Ok(_) => match get_git_info(&repo_path) {
+            Ok(stats) => match get_git_info(&repo_path) {
                 Ok(git_info) => {
-                    let msg = GitMessage::RepoStatusUpdated {
+                    let msg = GitMessage::FetchComplete {
                         repo_path,
-                        git_info,
+                        git_info: Box::new(git_info),
+                        stats,
                     };
                     let _ = tx.send(T::from(msg));
                 }
@@ -303,21 +2095,73 @@ where
     });
 }
 
-pub fn git_fetch_fast_async_with_retry<T>(repo_path: PathBuf, tx: Sender<T>)
+/// Deepens a shallow clone by `deepen` commits, or fetches its full history
+/// when `deepen` is `None`.
+pub fn git_deepen_async<T>(repo_path: PathBuf, deepen: Option<u32>, tx: Sender<T>)
 where
     T: From<GitMessage> + Send + 'static,
 {
     std::thread::spawn(move || {
-        let _guard = match PoolGuard::try_acquire_with_timeout(5000) {
-            Some(guard) => guard,
-            None => {
-                let msg = GitMessage::Error(format!(
-                    "Timeout waiting for available slot for {:?}",
-                    repo_path
-                ));
-                let _ = tx.send(T::from(msg));
-                return;
-            }
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let result = match deepen {
+            Some(n) => git_deepen(&repo_path, n),
+            None => git_unshallow(&repo_path),
+        };
+
+        let msg = match result.and_then(|_| get_git_info(&repo_path)) {
+            Ok(git_info) => GitMessage::RepoStatusUpdated {
+                repo_path,
+                git_info: Box::new(git_info),
+            },
+            Err(e) => GitMessage::Error(format!("Fetch failed for {:?}: {}", repo_path, e)),
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+/// Fetches one remote, or every remote when `remote_name` is `None`, and
+/// re-reads the repository so the branch list picks up new remote branches.
+pub fn git_fetch_remote_async<T>(repo_path: PathBuf, remote_name: Option<String>, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
+        };
+
+        let result = match &remote_name {
+            Some(remote_name) => git_fetch_specific_remote(&repo_path, remote_name),
+            None => git_fetch_all_remotes(&repo_path),
+        };
+
+        let msg = match result.and_then(|stats| Ok((stats, get_git_info(&repo_path)?))) {
+            Ok((stats, git_info)) => GitMessage::FetchComplete {
+                repo_path,
+                git_info: Box::new(git_info),
+                stats,
+            },
+            Err(e) => GitMessage::Error(format!("Fetch failed for {:?}: {}", repo_path, e)),
+        };
+        let _ = tx.send(T::from(msg));
+    });
+}
+
+/// `recurse_submodules`: the repository's registered submodules are skipped
+/// in the same batch and fetched through it instead.
+pub fn git_fetch_fast_async_with_retry<T>(
+    repo_path: PathBuf,
+    recurse_submodules: bool,
+    tx: Sender<T>,
+) where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let Some(_guard) = enqueue_or_report(&repo_path, &tx) else {
+            return;
         };
 
         let mut attempt = 0;
@@ -327,15 +2171,20 @@ where
         while attempt < max_attempts {
             attempt += 1;
 
-            let result = git_fetch(&repo_path);
+            let result = if recurse_submodules {
+                git_fetch_with_submodules(&repo_path)
+            } else {
+                git_fetch(&repo_path)
+            };
 
             match result {
-                Ok(_) => {
+                Ok(stats) => {
                     match get_git_info(&repo_path) {
                         Ok(git_info) => {
-                            let msg = GitMessage::RepoStatusUpdated {
+                            let msg = GitMessage::FetchComplete {
                                 repo_path,
-                                git_info,
+                                git_info: Box::new(git_info),
+                                stats,
                             };
                             let _ = tx.send(T::from(msg));
                         }
@@ -384,3 +2233,160 @@ where
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortlog_counts_and_authors_are_parsed() {
+        let output = "    42\tJane Doe\n     7\tИван Петров\n\nbroken line\n";
+
+        assert_eq!(
+            parse_shortlog(output),
+            vec![
+                ContributorEntry {
+                    author: "Jane Doe".to_string(),
+                    commit_count: 42,
+                },
+                ContributorEntry {
+                    author: "Иван Петров".to_string(),
+                    commit_count: 7,
+                },
+            ]
+        );
+        assert_eq!(contributor_since(90), "90.days.ago");
+    }
+
+    #[test]
+    fn ignored_paths_and_their_rules_are_parsed() {
+        let status = "?? new.txt\0!! target/\0!! logs/app.log\0 M src/lib.rs\0";
+        assert_eq!(
+            parse_ignored_paths(status),
+            vec!["target/".to_string(), "logs/app.log".to_string()]
+        );
+
+        let check = [
+            ".gitignore",
+            "3",
+            "target/",
+            "target/",
+            "logs/.gitignore",
+            "1",
+            "*.log",
+            "logs/app.log",
+            "",
+        ]
+        .join("\0");
+        let rules = parse_ignore_rules(&check);
+        assert_eq!(rules["target/"], ".gitignore:3: target/");
+        assert_eq!(rules["logs/app.log"], "logs/.gitignore:1: *.log");
+        assert!(parse_ignore_rules("").is_empty());
+    }
+
+    #[test]
+    fn three_way_apply_reports_conflicts_before_failure() {
+        assert_eq!(
+            apply_3way_result(true, Vec::new(), ""),
+            ApplyResult::Success
+        );
+        assert_eq!(
+            apply_3way_result(false, vec!["src/lib.rs".to_string()], "conflict"),
+            ApplyResult::ConflictsCreated {
+                conflicted_files: vec!["src/lib.rs".to_string()],
+            }
+        );
+        assert_eq!(
+            apply_3way_result(false, Vec::new(), "error: corrupt patch\n"),
+            ApplyResult::Failed("error: corrupt patch".to_string())
+        );
+    }
+
+    #[test]
+    fn cherry_lines_keep_sign_hash_and_subject() {
+        let output = "+ 1a2b3c Add retry to fetch\n\
+                      - 4d5e6f Fix typo\n\
+                      + 7a8b9c\n\
+                      garbage\n";
+
+        assert_eq!(
+            parse_cherry(output),
+            vec![
+                CherryEntry {
+                    sign: '+',
+                    hash: "1a2b3c".to_string(),
+                    subject: "Add retry to fetch".to_string(),
+                },
+                CherryEntry {
+                    sign: '-',
+                    hash: "4d5e6f".to_string(),
+                    subject: "Fix typo".to_string(),
+                },
+                CherryEntry {
+                    sign: '+',
+                    hash: "7a8b9c".to_string(),
+                    subject: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn prune_dryrun_lists_only_would_prune_lines() {
+        let output = "Pruning origin\n\
+                      URL: git@example.com:team/api.git\n \
+                      * [would prune] origin/feature/old\n \
+                      * [would prune] origin/fix/typo\n";
+
+        assert_eq!(
+            parse_prune_dryrun(output),
+            vec![
+                "origin/feature/old".to_string(),
+                "origin/fix/typo".to_string()
+            ]
+        );
+        assert!(parse_prune_dryrun("").is_empty());
+    }
+
+    #[test]
+    fn remotes_are_read_from_fetch_lines() {
+        let output = "origin\tgit@github.com:me/api.git (fetch)\n\
+                      origin\tgit@github.com:me/api.git (push)\n\
+                      upstream\thttps://github.com/org/api.git (fetch)\n\
+                      upstream\thttps://github.com/org/api.git (push)\n";
+
+        assert_eq!(
+            parse_remotes(output),
+            vec![
+                RemoteEntry {
+                    name: "origin".to_string(),
+                    url: "git@github.com:me/api.git".to_string(),
+                },
+                RemoteEntry {
+                    name: "upstream".to_string(),
+                    url: "https://github.com/org/api.git".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn remote_names_with_spaces_or_special_characters_are_rejected() {
+        for name in ["upstream", "my-fork", "team_b", "v2.mirror"] {
+            assert!(is_valid_remote_name(name), "{}", name);
+        }
+        for name in [
+            "",
+            "my fork",
+            "-x",
+            ".hidden",
+            "a..b",
+            "x.lock",
+            "a/b",
+            "a:b",
+            "ветка",
+        ] {
+            assert!(!is_valid_remote_name(name), "{}", name);
+        }
+    }
+}
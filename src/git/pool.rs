@@ -1,25 +1,110 @@
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard};
 
-lazy_static::lazy_static! {
-    static ref GIT_OPERATION_POOL: Arc<Mutex<VecDeque<()>>> = {
-        let mut pool = VecDeque::new();
-        for _ in 0..8 {
-            pool.push_back(());
+/// Git operations allowed to run at the same time.
+pub const POOL_CAPACITY: usize = 8;
+
+pub type JobId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    Running,
+    /// The job is no longer in line: it was cancelled before it started.
+    Cancelled,
+}
+
+/// Slots of the git operation pool and the FIFO of jobs waiting for one.
+/// Jobs start strictly in the order they were enqueued.
+pub struct JobQueue {
+    capacity: usize,
+    running: usize,
+    next_id: JobId,
+    pending: VecDeque<(JobId, PathBuf)>,
+}
+
+impl JobQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            running: 0,
+            next_id: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn enqueue(&mut self, repo_path: PathBuf) -> JobId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.push_back((id, repo_path));
+        id
+    }
+
+    /// Promotes `id` to running when it is first in line and a slot is free.
+    pub fn try_start(&mut self, id: JobId) -> JobState {
+        match self.pending.iter().position(|(job, _)| *job == id) {
+            None => JobState::Cancelled,
+            Some(0) if self.running < self.capacity => {
+                self.pending.pop_front();
+                self.running += 1;
+                JobState::Running
+            }
+            Some(_) => JobState::Queued,
+        }
+    }
+
+    /// Takes a slot without waiting in line; only when nobody is queued.
+    pub fn try_acquire(&mut self) -> bool {
+        if self.running < self.capacity && self.pending.is_empty() {
+            self.running += 1;
+            true
+        } else {
+            false
         }
-        Arc::new(Mutex::new(pool))
-    };
+    }
+
+    pub fn release(&mut self) {
+        self.running = self.running.saturating_sub(1);
+    }
+
+    /// Drops the not yet started jobs of `repo_path`, returns how many.
+    pub fn cancel(&mut self, repo_path: &Path) -> usize {
+        let before = self.pending.len();
+        self.pending.retain(|(_, path)| path != repo_path);
+        before - self.pending.len()
+    }
+
+    pub fn is_queued(&self, repo_path: &Path) -> bool {
+        self.pending.iter().any(|(_, path)| path == repo_path)
+    }
+
+    pub fn running(&self) -> usize {
+        self.running
+    }
+
+    pub fn queued(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref GIT_OPERATION_POOL: (Mutex<JobQueue>, Condvar) =
+        (Mutex::new(JobQueue::new(POOL_CAPACITY)), Condvar::new());
+}
+
+fn lock_pool() -> MutexGuard<'static, JobQueue> {
+    GIT_OPERATION_POOL
+        .0
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 pub struct PoolGuard;
 
 impl PoolGuard {
     pub fn acquire() -> Option<Self> {
-        GIT_OPERATION_POOL
-            .lock()
-            .ok()?
-            .pop_front()
-            .map(|_| PoolGuard)
+        lock_pool().try_acquire().then_some(PoolGuard)
     }
 
     pub fn try_acquire_with_timeout(timeout_ms: u64) -> Option<Self> {
@@ -32,12 +117,105 @@ impl PoolGuard {
         }
         None
     }
+
+    /// Waits in line for a slot. `None` when the job was cancelled with
+    /// `cancel_queued` before it could start.
+    pub fn enqueue(repo_path: &Path) -> Option<Self> {
+        let mut queue = lock_pool();
+        let id = queue.enqueue(repo_path.to_path_buf());
+        loop {
+            match queue.try_start(id) {
+                JobState::Running => return Some(PoolGuard),
+                JobState::Cancelled => return None,
+                JobState::Queued => {
+                    queue = GIT_OPERATION_POOL
+                        .1
+                        .wait(queue)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+            }
+        }
+    }
 }
 
 impl Drop for PoolGuard {
     fn drop(&mut self) {
-        if let Ok(mut pool) = GIT_OPERATION_POOL.lock() {
-            pool.push_back(());
-        }
+        lock_pool().release();
+        GIT_OPERATION_POOL.1.notify_all();
+    }
+}
+
+/// Cancels operations of `repo_path` that are still waiting for a slot.
+pub fn cancel_queued(repo_path: &Path) -> bool {
+    let cancelled = lock_pool().cancel(repo_path) > 0;
+    if cancelled {
+        GIT_OPERATION_POOL.1.notify_all();
+    }
+    cancelled
+}
+
+pub fn is_queued(repo_path: &Path) -> bool {
+    lock_pool().is_queued(repo_path)
+}
+
+/// `(running, queued)` operation counts.
+pub fn pool_status() -> (usize, usize) {
+    let queue = lock_pool();
+    (queue.running(), queue.queued())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_start_in_enqueue_order() {
+        let mut queue = JobQueue::new(1);
+        let first = queue.enqueue(PathBuf::from("/r/a"));
+        let second = queue.enqueue(PathBuf::from("/r/b"));
+
+        assert_eq!(queue.try_start(second), JobState::Queued);
+        assert_eq!(queue.try_start(first), JobState::Running);
+        assert_eq!(queue.try_start(second), JobState::Queued);
+        assert_eq!((queue.running(), queue.queued()), (1, 1));
+    }
+
+    #[test]
+    fn queued_job_is_promoted_when_a_slot_frees() {
+        let mut queue = JobQueue::new(1);
+        let first = queue.enqueue(PathBuf::from("/r/a"));
+        let second = queue.enqueue(PathBuf::from("/r/b"));
+        queue.try_start(first);
+
+        queue.release();
+
+        assert_eq!(queue.try_start(second), JobState::Running);
+        assert_eq!((queue.running(), queue.queued()), (1, 0));
+    }
+
+    #[test]
+    fn cancelled_job_never_starts_and_frees_its_place() {
+        let mut queue = JobQueue::new(1);
+        let first = queue.enqueue(PathBuf::from("/r/a"));
+        let cancelled = queue.enqueue(PathBuf::from("/r/b"));
+        let third = queue.enqueue(PathBuf::from("/r/c"));
+        queue.try_start(first);
+
+        assert!(queue.is_queued(Path::new("/r/b")));
+        assert_eq!(queue.cancel(Path::new("/r/b")), 1);
+        assert!(!queue.is_queued(Path::new("/r/b")));
+        assert_eq!(queue.try_start(cancelled), JobState::Cancelled);
+
+        queue.release();
+        assert_eq!(queue.try_start(third), JobState::Running);
+    }
+
+    #[test]
+    fn direct_acquire_does_not_jump_the_queue() {
+        let mut queue = JobQueue::new(2);
+        assert!(queue.try_acquire());
+        queue.enqueue(PathBuf::from("/r/a"));
+
+        assert!(!queue.try_acquire());
     }
 }
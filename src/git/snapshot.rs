@@ -0,0 +1,377 @@
+use super::{git_command, GitMessage, PoolGuard};
+use crossbeam_channel::Sender;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where one repository stood when a snapshot was taken.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepoSnapshot {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub head: String,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Branch positions of every repository of a workspace at one moment.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSnapshot {
+    pub workspace: String,
+    /// Seconds since the Unix epoch.
+    pub taken_at: u64,
+    pub repos: Vec<RepoSnapshot>,
+}
+
+/// Status data the app already has; only HEAD is resolved when capturing.
+pub struct SnapshotRequest {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub branch: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotChangeKind {
+    Added,
+    Removed,
+    Moved,
+}
+
+/// A repository whose branch or HEAD differs from the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotChange {
+    pub repo_path: PathBuf,
+    pub repo_name: String,
+    pub kind: SnapshotChangeKind,
+    pub old_branch: Option<String>,
+    pub new_branch: Option<String>,
+    pub old_head: Option<String>,
+    pub new_head: Option<String>,
+    /// Commits from the old HEAD to the new one, when the new HEAD descends
+    /// from the old.
+    pub commits: Option<usize>,
+    /// HEAD moved to a commit that does not descend from the old one
+    /// (rebase, reset, force-push) or the old commit is gone.
+    pub history_rewritten: bool,
+}
+
+/// Repositories that changed between `old` and `new`, by name. `commits`
+/// holds the result of `commits_between` for repositories whose HEAD moved;
+/// `None` there, or a missing entry, means the history was rewritten.
+pub fn compare_snapshots(
+    old: &WorkspaceSnapshot,
+    new: &WorkspaceSnapshot,
+    commits: &HashMap<PathBuf, Option<usize>>,
+) -> Vec<SnapshotChange> {
+    let mut changes = Vec::new();
+
+    for before in &old.repos {
+        let Some(after) = new.repos.iter().find(|r| r.repo_path == before.repo_path) else {
+            changes.push(SnapshotChange {
+                repo_path: before.repo_path.clone(),
+                repo_name: before.repo_name.clone(),
+                kind: SnapshotChangeKind::Removed,
+                old_branch: before.branch.clone(),
+                new_branch: None,
+                old_head: Some(before.head.clone()),
+                new_head: None,
+                commits: None,
+                history_rewritten: false,
+            });
+            continue;
+        };
+
+        let head_moved = before.head != after.head;
+        if !head_moved && before.branch == after.branch {
+            continue;
+        }
+        let count = commits.get(&after.repo_path).copied().flatten();
+        changes.push(SnapshotChange {
+            repo_path: after.repo_path.clone(),
+            repo_name: after.repo_name.clone(),
+            kind: SnapshotChangeKind::Moved,
+            old_branch: before.branch.clone(),
+            new_branch: after.branch.clone(),
+            old_head: Some(before.head.clone()),
+            new_head: Some(after.head.clone()),
+            commits: if head_moved { count } else { Some(0) },
+            history_rewritten: head_moved && count.is_none(),
+        });
+    }
+
+    for after in &new.repos {
+        if !old.repos.iter().any(|r| r.repo_path == after.repo_path) {
+            changes.push(SnapshotChange {
+                repo_path: after.repo_path.clone(),
+                repo_name: after.repo_name.clone(),
+                kind: SnapshotChangeKind::Added,
+                old_branch: None,
+                new_branch: after.branch.clone(),
+                old_head: None,
+                new_head: Some(after.head.clone()),
+                commits: None,
+                history_rewritten: false,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.repo_name.cmp(&b.repo_name));
+    changes
+}
+
+fn git_stdout(repo_path: &Path, args: &[&str]) -> Option<String> {
+    git_command(repo_path)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commits in `old..new` when `new` descends from `old`; `None` when it
+/// does not or `old` is no longer in the repository.
+fn commits_between(repo_path: &Path, old: &str, new: &str) -> Option<usize> {
+    git_command(repo_path)
+        .args(["merge-base", "--is-ancestor", old, new])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    git_stdout(
+        repo_path,
+        &["rev-list", "--count", &format!("{}..{}", old, new)],
+    )?
+    .parse()
+    .ok()
+}
+
+/// Resolves HEAD of every repository, one pooled thread each. Repositories
+/// without a commit are left out.
+fn capture(workspace: String, requests: Vec<SnapshotRequest>) -> WorkspaceSnapshot {
+    let workers: Vec<_> = requests
+        .into_iter()
+        .map(|request| {
+            std::thread::spawn(move || {
+                let _guard = PoolGuard::try_acquire_with_timeout(60_000);
+                let head = git_stdout(&request.repo_path, &["rev-parse", "HEAD"])?;
+                Some(RepoSnapshot {
+                    repo_path: request.repo_path,
+                    repo_name: request.repo_name,
+                    branch: request.branch,
+                    head,
+                    ahead: request.ahead,
+                    behind: request.behind,
+                })
+            })
+        })
+        .collect();
+
+    let mut repos: Vec<RepoSnapshot> = workers
+        .into_iter()
+        .filter_map(|worker| worker.join().ok().flatten())
+        .collect();
+    repos.sort_by(|a, b| a.repo_path.cmp(&b.repo_path));
+
+    WorkspaceSnapshot {
+        workspace,
+        taken_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        repos,
+    }
+}
+
+/// Captures the workspace and sends `GitMessage::SnapshotTaken`.
+pub fn take_snapshot_async<T>(workspace: String, requests: Vec<SnapshotRequest>, tx: Sender<T>)
+where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let snapshot = capture(workspace, requests);
+        let _ = tx.send(T::from(GitMessage::SnapshotTaken { snapshot }));
+    });
+}
+
+/// Captures the workspace as it is now, counts the commits each moved HEAD
+/// advanced since `old` and sends `GitMessage::SnapshotCompared`.
+pub fn compare_snapshot_async<T>(
+    old: WorkspaceSnapshot,
+    requests: Vec<SnapshotRequest>,
+    tx: Sender<T>,
+) where
+    T: From<GitMessage> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let current = capture(old.workspace.clone(), requests);
+        let commits = current
+            .repos
+            .iter()
+            .filter_map(|after| {
+                let before = old.repos.iter().find(|r| r.repo_path == after.repo_path)?;
+                (before.head != after.head).then(|| {
+                    (
+                        after.repo_path.clone(),
+                        commits_between(&after.repo_path, &before.head, &after.head),
+                    )
+                })
+            })
+            .collect();
+        let changes = compare_snapshots(&old, &current, &commits);
+
+        let _ = tx.send(T::from(GitMessage::SnapshotCompared {
+            taken_at: old.taken_at,
+            changes,
+        }));
+    });
+}
+
+/// Writes `snapshot` into `dir` as `<taken_at>-<workspace>.json`.
+pub fn save_snapshot(dir: &Path, snapshot: &WorkspaceSnapshot) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let workspace: String = snapshot
+        .workspace
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}-{}.json", snapshot.taken_at, workspace));
+    std::fs::write(&path, serde_json::to_string_pretty(snapshot)?)?;
+    Ok(path)
+}
+
+/// Stored snapshots of `workspace`, newest first. Unreadable files are
+/// skipped.
+pub fn list_snapshots(dir: &Path, workspace: &str) -> Vec<WorkspaceSnapshot> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<WorkspaceSnapshot> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|text| serde_json::from_str::<WorkspaceSnapshot>(&text).ok())
+        .filter(|snapshot| snapshot.workspace == workspace)
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken_at));
+    snapshots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(path: &str, branch: &str, head: &str) -> RepoSnapshot {
+        RepoSnapshot {
+            repo_path: PathBuf::from(path),
+            repo_name: path.rsplit('/').next().unwrap().to_string(),
+            branch: Some(branch.to_string()),
+            head: head.to_string(),
+            ahead: 0,
+            behind: 0,
+        }
+    }
+
+    fn snapshot(taken_at: u64, repos: Vec<RepoSnapshot>) -> WorkspaceSnapshot {
+        WorkspaceSnapshot {
+            workspace: "Work".to_string(),
+            taken_at,
+            repos,
+        }
+    }
+
+    #[test]
+    fn unchanged_repositories_are_not_listed() {
+        let old = snapshot(1, vec![repo("/r/api", "main", "aaa")]);
+        let new = snapshot(2, vec![repo("/r/api", "main", "aaa")]);
+
+        assert!(compare_snapshots(&old, &new, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_repositories_are_reported() {
+        let old = snapshot(
+            1,
+            vec![repo("/r/api", "main", "aaa"), repo("/r/old", "main", "bbb")],
+        );
+        let new = snapshot(
+            2,
+            vec![repo("/r/api", "main", "aaa"), repo("/r/new", "dev", "ccc")],
+        );
+
+        let changes = compare_snapshots(&old, &new, &HashMap::new());
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].repo_name, "new");
+        assert_eq!(changes[0].kind, SnapshotChangeKind::Added);
+        assert_eq!(changes[0].new_head.as_deref(), Some("ccc"));
+        assert_eq!(changes[1].repo_name, "old");
+        assert_eq!(changes[1].kind, SnapshotChangeKind::Removed);
+        assert_eq!(changes[1].old_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn fast_forward_counts_commits_and_rewrite_is_flagged() {
+        let old = snapshot(
+            1,
+            vec![repo("/r/api", "main", "aaa"), repo("/r/web", "main", "ddd")],
+        );
+        let new = snapshot(
+            2,
+            vec![
+                repo("/r/api", "main", "bbb"),
+                repo("/r/web", "release", "eee"),
+            ],
+        );
+        let commits = HashMap::from([
+            (PathBuf::from("/r/api"), Some(3)),
+            (PathBuf::from("/r/web"), None),
+        ]);
+
+        let changes = compare_snapshots(&old, &new, &commits);
+
+        assert_eq!(changes[0].repo_name, "api");
+        assert_eq!(changes[0].commits, Some(3));
+        assert!(!changes[0].history_rewritten);
+        assert_eq!(changes[1].repo_name, "web");
+        assert_eq!(changes[1].new_branch.as_deref(), Some("release"));
+        assert_eq!(changes[1].commits, None);
+        assert!(changes[1].history_rewritten);
+    }
+
+    #[test]
+    fn branch_switch_without_moving_head_is_not_a_rewrite() {
+        let old = snapshot(1, vec![repo("/r/api", "main", "aaa")]);
+        let new = snapshot(2, vec![repo("/r/api", "hotfix", "aaa")]);
+
+        let changes = compare_snapshots(&old, &new, &HashMap::new());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].commits, Some(0));
+        assert!(!changes[0].history_rewritten);
+    }
+
+    #[test]
+    fn snapshots_are_listed_newest_first_per_workspace() {
+        let dir = std::env::temp_dir().join("repo_manager_snapshots");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        save_snapshot(&dir, &snapshot(100, vec![repo("/r/api", "main", "aaa")])).unwrap();
+        save_snapshot(&dir, &snapshot(200, vec![])).unwrap();
+        let mut other = snapshot(300, vec![]);
+        other.workspace = "Home / Blog".to_string();
+        let other_path = save_snapshot(&dir, &other).unwrap();
+
+        let listed: Vec<u64> = list_snapshots(&dir, "Work")
+            .iter()
+            .map(|s| s.taken_at)
+            .collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(listed, vec![200, 100]);
+        assert!(other_path.ends_with("300-Home___Blog.json"));
+    }
+}
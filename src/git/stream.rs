@@ -0,0 +1,120 @@
+use crossbeam_channel::unbounded;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, ExitStatus, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+pub struct StreamedOutput {
+    pub status: ExitStatus,
+    pub stderr: String,
+}
+
+fn forward_lines<R: Read + Send + 'static>(
+    reader: R,
+    stream: OutputStream,
+    tx: crossbeam_channel::Sender<(OutputStream, String)>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(line) => {
+                    if tx.send((stream, line)).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// Runs `cmd` with piped output and calls `on_line` for every line of
+/// stdout/stderr as soon as it is printed, not after the process exits.
+/// Long-running hooks (e.g. pre-push test suites) report progress this way.
+pub fn run_streaming(
+    mut cmd: Command,
+    mut on_line: impl FnMut(OutputStream, &str),
+) -> std::io::Result<StreamedOutput> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let (tx, rx) = unbounded();
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_lines(stdout, OutputStream::Stdout, tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_lines(stderr, OutputStream::Stderr, tx.clone()));
+    }
+    drop(tx);
+
+    let mut stderr = String::new();
+    for (stream, line) in rx {
+        if stream == OutputStream::Stderr {
+            stderr.push_str(&line);
+            stderr.push('\n');
+        }
+        on_line(stream, &line);
+    }
+
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    Ok(StreamedOutput {
+        status: child.wait()?,
+        stderr,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn shell(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script]);
+        cmd
+    }
+
+    #[test]
+    fn lines_arrive_before_the_process_exits() {
+        let start = Instant::now();
+        let mut arrivals = Vec::new();
+
+        let output = run_streaming(shell("echo first; sleep 0.5; echo second"), |_, line| {
+            arrivals.push((line.to_string(), start.elapsed()))
+        })
+        .unwrap();
+        let finished = start.elapsed();
+
+        assert!(output.status.success());
+        assert_eq!(arrivals.len(), 2);
+        assert_eq!(arrivals[0].0, "first");
+        assert!(finished - arrivals[0].1 >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn stderr_is_streamed_and_collected() {
+        let mut lines = Vec::new();
+
+        let output = run_streaming(
+            shell("echo out; echo 'hook failed' >&2; exit 1"),
+            |stream, line| lines.push((stream, line.to_string())),
+        )
+        .unwrap();
+
+        assert!(!output.status.success());
+        assert_eq!(output.stderr, "hook failed\n");
+        assert!(lines.contains(&(OutputStream::Stdout, "out".to_string())));
+        assert!(lines.contains(&(OutputStream::Stderr, "hook failed".to_string())));
+    }
+}
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 #[derive(Clone, Debug)]
 pub struct Localizer {
     current_language: String,
     translations: HashMap<String, HashMap<String, String>>,
+    missing: RefCell<HashMap<String, BTreeSet<String>>>,
 }
 
 impl Localizer {
@@ -12,6 +16,7 @@ impl Localizer {
         Self {
             current_language: language.to_string(),
             translations,
+            missing: RefCell::new(HashMap::new()),
         }
     }
 
@@ -40,6 +45,74 @@ impl Localizer {
         all_translations
     }
 
+    /// Locale sources in the working tree, used for hot reload in debug builds.
+    pub fn locales_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/locales")
+    }
+
+    fn locale_files(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Replaces the embedded translations with `*.json` files from `dir`.
+    /// Returns `false` (and keeps the current translations) if none parsed.
+    pub fn reload_from_dir(&mut self, dir: &Path) -> bool {
+        let mut translations = HashMap::new();
+
+        for path in Self::locale_files(dir) {
+            let Some(language) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let parsed = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<HashMap<String, String>>(&content).ok());
+
+            match parsed {
+                Some(map) => {
+                    translations.insert(language, map);
+                }
+                None => eprintln!("Failed to parse locale file: {:?}", path),
+            }
+        }
+
+        if translations.is_empty() {
+            return false;
+        }
+
+        self.translations = translations;
+        self.missing.borrow_mut().clear();
+        true
+    }
+
+    pub fn reload(&mut self) -> bool {
+        self.reload_from_dir(&Self::locales_dir())
+    }
+
+    /// Records modification times of the locale files in `dir` and reports
+    /// whether any of them changed since the previous call.
+    pub fn locale_files_changed(dir: &Path, last_seen: &mut HashMap<String, SystemTime>) -> bool {
+        let mut changed = false;
+
+        for path in Self::locale_files(dir) {
+            let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            let key = path.to_string_lossy().to_string();
+            if last_seen.insert(key, modified) != Some(modified) {
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
     pub fn set_language(&mut self, language: &str) {
         if self.translations.contains_key(language) {
             self.current_language = language.to_string();
@@ -57,6 +130,12 @@ impl Localizer {
             }
         }
 
+        self.missing
+            .borrow_mut()
+            .entry(self.current_language.clone())
+            .or_default()
+            .insert(key.to_string());
+
         if let Some(en_map) = self.translations.get("en") {
             if let Some(translation) = en_map.get(key) {
                 return translation.clone();
@@ -86,7 +165,83 @@ impl Localizer {
         result
     }
 
+    /// Keys requested this session that the active language has no entry for.
+    pub fn missing_keys(&self) -> Vec<String> {
+        self.missing
+            .borrow()
+            .get(&self.current_language)
+            .map(|keys| keys.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// JSON skeleton of the missing keys, ready to paste into the locale file.
+    pub fn missing_keys_skeleton(&self) -> String {
+        let skeleton: BTreeMap<String, String> = self
+            .missing_keys()
+            .into_iter()
+            .map(|key| (key, String::new()))
+            .collect();
+        serde_json::to_string_pretty(&skeleton).unwrap_or_default()
+    }
+
     pub fn get_available_languages(&self) -> Vec<(&str, String)> {
         vec![("en", self.t("english")), ("ru", self.t("russian"))]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_locale(content: &str) -> HashMap<String, String> {
+        serde_json::from_str(content).expect("locale file must be valid JSON")
+    }
+
+    #[test]
+    fn ru_locale_has_every_en_key() {
+        let en = parse_locale(include_str!("../../assets/locales/en.json"));
+        let ru = parse_locale(include_str!("../../assets/locales/ru.json"));
+
+        let mut missing: Vec<_> = en.keys().filter(|key| !ru.contains_key(*key)).collect();
+        missing.sort();
+
+        assert!(missing.is_empty(), "ru.json is missing keys: {:?}", missing);
+    }
+
+    #[test]
+    fn missing_keys_are_tracked_per_language() {
+        let mut localizer = Localizer::new("ru");
+        assert_eq!(localizer.t("no_such_key"), "no_such_key");
+        localizer.t("workspaces");
+
+        assert_eq!(localizer.missing_keys(), vec!["no_such_key".to_string()]);
+        assert!(localizer
+            .missing_keys_skeleton()
+            .contains("\"no_such_key\": \"\""));
+
+        localizer.set_language("en");
+        assert!(localizer.missing_keys().is_empty());
+    }
+
+    #[test]
+    fn reload_from_dir_replaces_translations() {
+        let dir = std::env::temp_dir().join("repo_manager_locale_reload");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("en.json"), r#"{"fetch": "Fetch now"}"#).unwrap();
+
+        let mut localizer = Localizer::new("en");
+        let mut last_seen = HashMap::new();
+        assert!(Localizer::locale_files_changed(&dir, &mut last_seen));
+        assert!(!Localizer::locale_files_changed(&dir, &mut last_seen));
+
+        assert!(localizer.reload_from_dir(&dir));
+        assert_eq!(localizer.t("fetch"), "Fetch now");
+
+        let empty = dir.join("empty");
+        std::fs::create_dir_all(&empty).unwrap();
+        assert!(!localizer.reload_from_dir(&empty));
+        assert_eq!(localizer.t("fetch"), "Fetch now");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,288 @@
+use crossbeam_channel::{unbounded, Sender};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The audit file is rotated to `<name>.1` once it would grow past this.
+pub const AUDIT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Mutating git operations started from the app.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Pull,
+    Push,
+    PushTags,
+    Reset,
+    Checkout,
+    /// `git remote prune`: deletes stale remote-tracking branches.
+    PruneBranches,
+    Stash,
+    DeleteBranch,
+    /// `git push --mirror`: overwrites and deletes refs on the remote.
+    PushMirror,
+    /// `git push --force-with-lease`.
+    ForcePush,
+    /// `--abort` of a merge, rebase, cherry-pick or revert.
+    AbortOperation,
+    /// Finishing a merge, rebase, cherry-pick or revert after conflicts.
+    ContinueOperation,
+}
+
+impl AuditOperation {
+    pub const ALL: [AuditOperation; 12] = [
+        AuditOperation::Pull,
+        AuditOperation::Push,
+        AuditOperation::PushTags,
+        AuditOperation::Reset,
+        AuditOperation::Checkout,
+        AuditOperation::PruneBranches,
+        AuditOperation::Stash,
+        AuditOperation::DeleteBranch,
+        AuditOperation::PushMirror,
+        AuditOperation::ForcePush,
+        AuditOperation::AbortOperation,
+        AuditOperation::ContinueOperation,
+    ];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            AuditOperation::Pull => "audit_op_pull",
+            AuditOperation::Push => "audit_op_push",
+            AuditOperation::PushTags => "audit_op_push_tags",
+            AuditOperation::Reset => "audit_op_reset",
+            AuditOperation::Checkout => "audit_op_checkout",
+            AuditOperation::PruneBranches => "audit_op_prune_branches",
+            AuditOperation::Stash => "audit_op_stash",
+            AuditOperation::DeleteBranch => "audit_op_delete_branch",
+            AuditOperation::PushMirror => "audit_op_push_mirror",
+            AuditOperation::ForcePush => "audit_op_force_push",
+            AuditOperation::AbortOperation => "audit_op_abort_operation",
+            AuditOperation::ContinueOperation => "audit_op_continue_operation",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditResult {
+    Ok,
+    Failed,
+}
+
+/// One line of the audit file.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub operation: AuditOperation,
+    pub repo_path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub result: AuditResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn now(
+        operation: AuditOperation,
+        repo_path: &Path,
+        branch: Option<String>,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            operation,
+            repo_path: repo_path.to_path_buf(),
+            branch,
+            result: if error.is_some() {
+                AuditResult::Failed
+            } else {
+                AuditResult::Ok
+            },
+            error,
+        }
+    }
+}
+
+/// Append-only JSON lines file with a single rotated predecessor.
+pub struct AuditFile {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl AuditFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_bytes: AUDIT_MAX_BYTES,
+        }
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let current_size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if current_size > 0 && current_size + line.len() as u64 > self.max_bytes {
+            std::fs::rename(&self.path, self.rotated_path())?;
+        }
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// All entries, oldest first, including the rotated file.
+    pub fn read_all(&self) -> Vec<AuditEntry> {
+        [self.rotated_path(), self.path.clone()]
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .flat_map(|text| parse_audit_lines(&text))
+            .collect()
+    }
+}
+
+/// Parses JSON lines, skipping lines that do not parse (e.g. a last line
+/// cut short by a crash).
+pub fn parse_audit_lines(text: &str) -> Vec<AuditEntry> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+lazy_static::lazy_static! {
+    static ref AUDIT_SENDER: Mutex<Option<Sender<AuditEntry>>> = Mutex::new(None);
+    static ref AUDIT_WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+/// Starts the background writer. Until then `record_audit` is a no-op.
+pub fn start_audit_log(file: AuditFile) {
+    let (tx, rx) = unbounded::<AuditEntry>();
+    std::thread::spawn(move || {
+        for entry in rx {
+            if let Err(e) = file.append(&entry) {
+                if let Ok(mut warnings) = AUDIT_WARNINGS.lock() {
+                    warnings.push(format!("{}: {}", file.path.display(), e));
+                }
+            }
+        }
+    });
+
+    if let Ok(mut sender) = AUDIT_SENDER.lock() {
+        *sender = Some(tx);
+    }
+}
+
+/// Queues `entry` for the writer thread; never blocks the operation.
+pub fn record_audit(entry: AuditEntry) {
+    if let Ok(sender) = AUDIT_SENDER.lock() {
+        if let Some(tx) = sender.as_ref() {
+            let _ = tx.send(entry);
+        }
+    }
+}
+
+/// Write failures since the last call, for the UI log.
+pub fn take_audit_warnings() -> Vec<String> {
+    AUDIT_WARNINGS
+        .lock()
+        .map(|mut warnings| std::mem::take(&mut *warnings))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(operation: AuditOperation, error: Option<&str>) -> AuditEntry {
+        AuditEntry {
+            timestamp: 1_700_000_000,
+            operation,
+            repo_path: PathBuf::from("/r/api"),
+            branch: Some("main".to_string()),
+            result: if error.is_some() {
+                AuditResult::Failed
+            } else {
+                AuditResult::Ok
+            },
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn entries_serialize_as_flat_json_lines() {
+        let json = serde_json::to_string(&entry(AuditOperation::PushTags, None)).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"timestamp":1700000000,"operation":"push_tags","repo_path":"/r/api","branch":"main","result":"ok"}"#
+        );
+        assert_eq!(
+            parse_audit_lines(&json),
+            vec![entry(AuditOperation::PushTags, None)]
+        );
+    }
+
+    #[test]
+    fn truncated_last_line_is_skipped() {
+        let good = serde_json::to_string(&entry(AuditOperation::Pull, Some("conflict"))).unwrap();
+        let text = format!("{}\n{}\n{{\"timestamp\":17000", good, good);
+
+        assert_eq!(parse_audit_lines(&text).len(), 2);
+    }
+
+    #[test]
+    fn file_rotates_when_full_and_reads_both_parts() {
+        let dir = std::env::temp_dir().join("repo_manager_audit_rotation");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let line_len = serde_json::to_string(&entry(AuditOperation::Reset, None))
+            .unwrap()
+            .len() as u64
+            + 1;
+        let file = AuditFile {
+            path: dir.join("audit.jsonl"),
+            max_bytes: line_len * 2,
+        };
+
+        for operation in [
+            AuditOperation::Reset,
+            AuditOperation::Pull,
+            AuditOperation::Push,
+        ] {
+            file.append(&entry(operation, None)).unwrap();
+        }
+
+        let current = std::fs::read_to_string(&file.path).unwrap();
+        let rotated = std::fs::read_to_string(file.rotated_path()).unwrap();
+        let operations: Vec<_> = file.read_all().iter().map(|e| e.operation).collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(parse_audit_lines(&current).len(), 1);
+        assert_eq!(parse_audit_lines(&rotated).len(), 2);
+        assert_eq!(
+            operations,
+            vec![
+                AuditOperation::Reset,
+                AuditOperation::Pull,
+                AuditOperation::Push
+            ]
+        );
+    }
+}
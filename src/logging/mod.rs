@@ -1,13 +1,61 @@
-use std::time::SystemTime;
+pub mod audit;
+
+pub use audit::*;
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone)]
 pub struct LogEntry {
     pub timestamp: SystemTime,
     pub level: LogLevel,
     pub message: String,
+    /// URL shown as a clickable link after the message.
+    pub link: Option<String>,
+    /// Repository the entry is about; `None` for app-wide messages.
+    pub context: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone)]
+impl LogEntry {
+    /// Line copied by "timestamp + message": `2024-05-01 12:30:05 UTC [E] ...`.
+    pub fn with_timestamp(&self) -> String {
+        format!(
+            "{} {} {}",
+            format_utc(self.timestamp),
+            self.level.icon(),
+            self.message
+        )
+    }
+
+    /// Web search for the message, prefixed with "git" to keep results on topic.
+    pub fn search_url(&self) -> String {
+        format!(
+            "https://www.google.com/search?q=git+{}",
+            encode_query(&self.message)
+        )
+    }
+
+    /// Whether the entry passes the logs panel repo filter. App-wide entries
+    /// stay visible under any filter.
+    pub fn matches_context(&self, filter: Option<&Path>) -> bool {
+        match (filter, &self.context) {
+            (Some(filter), Some(context)) => context == filter,
+            _ => true,
+        }
+    }
+
+    /// Folder name of the context repository, shown as a badge.
+    pub fn context_name(&self) -> Option<String> {
+        self.context
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string())
+    }
+}
+
+/// Ordered by severity: `Info < Warning < Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
     Info,
     Warning,
@@ -32,21 +80,29 @@ impl LogLevel {
     }
 }
 
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+
+/// Bounded log. When full, the oldest entry of the lowest severity present
+/// is dropped, so info spam cannot push out errors.
 pub struct Logger {
-    logs: Vec<LogEntry>,
+    /// Oldest first; kept contiguous so `logs` can lend it as a slice.
+    logs: VecDeque<LogEntry>,
+    /// Number of kept entries, indexed by `LogLevel as usize`.
+    counts: [usize; 3],
     max_logs: usize,
 }
 
 impl Default for Logger {
     fn default() -> Self {
-        Self::new(1000)
+        Self::new(DEFAULT_LOG_CAPACITY)
     }
 }
 
 impl Logger {
     pub fn new(max_logs: usize) -> Self {
         Self {
-            logs: Vec::new(),
+            logs: VecDeque::new(),
+            counts: [0; 3],
             max_logs,
         }
     }
@@ -63,41 +119,292 @@ impl Logger {
         self.add_log(LogLevel::Error, message.into());
     }
 
+    pub fn info_with_link<T: Into<String>>(&mut self, message: T, link: String) {
+        self.push_entry(LogLevel::Info, message.into(), Some(link), None);
+    }
+
+    pub fn info_for<T: Into<String>>(&mut self, message: T, repo: &Path) {
+        self.with_structured_context(LogLevel::Info, message, repo);
+    }
+
+    pub fn warning_for<T: Into<String>>(&mut self, message: T, repo: &Path) {
+        self.with_structured_context(LogLevel::Warning, message, repo);
+    }
+
+    pub fn error_for<T: Into<String>>(&mut self, message: T, repo: &Path) {
+        self.with_structured_context(LogLevel::Error, message, repo);
+    }
+
+    /// Adds an entry tied to `repo`, so the logs panel can filter by it.
+    pub fn with_structured_context<T: Into<String>>(
+        &mut self,
+        level: LogLevel,
+        message: T,
+        repo: &Path,
+    ) {
+        self.push_entry(level, message.into(), None, Some(repo.to_path_buf()));
+    }
+
     fn add_log(&mut self, level: LogLevel, message: String) {
-        self.logs.push(LogEntry {
+        self.push_entry(level, message, None, None);
+    }
+
+    fn push_entry(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        link: Option<String>,
+        context: Option<PathBuf>,
+    ) {
+        self.logs.push_back(LogEntry {
             timestamp: SystemTime::now(),
             level,
             message,
+            link,
+            context,
         });
+        self.counts[level as usize] += 1;
+        self.evict();
+    }
 
-        if self.logs.len() > self.max_logs {
-            self.logs.remove(0);
+    /// Changes how many entries are kept, dropping extra ones right away.
+    pub fn set_capacity(&mut self, max_logs: usize) {
+        self.max_logs = max_logs;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.logs.len() > self.max_logs {
+            let Some(lowest) = self.counts.iter().position(|&count| count > 0) else {
+                return;
+            };
+            // Самая старая запись наименьшей важности; при потоке info она
+            // обычно в самом начале
+            if let Some(idx) = self
+                .logs
+                .iter()
+                .position(|log| log.level as usize == lowest)
+            {
+                self.logs.remove(idx);
+                self.counts[lowest] -= 1;
+            }
         }
+        // Сдвигает данные, только когда кольцо перешло через край буфера
+        self.logs.make_contiguous();
     }
 
     pub fn logs(&self) -> &[LogEntry] {
-        &self.logs
+        // После make_contiguous всё лежит в первой половине
+        self.logs.as_slices().0
+    }
+
+    /// Repositories that appear as context in the kept entries, sorted.
+    pub fn contexts(&self) -> Vec<PathBuf> {
+        let mut contexts: Vec<PathBuf> = self
+            .logs
+            .iter()
+            .filter_map(|log| log.context.clone())
+            .collect();
+        contexts.sort();
+        contexts.dedup();
+        contexts
     }
 
     pub fn clear(&mut self) {
         self.logs.clear();
+        self.counts = [0; 3];
     }
 
     pub fn error_count(&self) -> usize {
-        self.logs
-            .iter()
-            .filter(|log| matches!(log.level, LogLevel::Error))
-            .count()
+        self.counts[LogLevel::Error as usize]
     }
 
     pub fn warning_count(&self) -> usize {
-        self.logs
-            .iter()
-            .filter(|log| matches!(log.level, LogLevel::Warning))
-            .count()
+        self.counts[LogLevel::Warning as usize]
     }
 
     pub fn total_count(&self) -> usize {
         self.logs.len()
     }
 }
+
+/// Short relative age used by the log panel and error popups: `42s`, `5m`, `3h`.
+pub fn format_elapsed(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else if seconds < 3600 {
+        format!("{}m", seconds / 60)
+    } else {
+        format!("{}h", seconds / 3600)
+    }
+}
+
+/// Absolute UTC time, `YYYY-MM-DD HH:MM:SS UTC`.
+pub fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+
+    // Дни от эпохи в григорианскую дату (алгоритм Howard Hinnant)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+/// Percent-encodes text for a URL query value; spaces become `+`.
+pub fn encode_query(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(logger: &Logger) -> Vec<&str> {
+        logger
+            .logs()
+            .iter()
+            .map(|log| log.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn oldest_info_is_evicted_before_errors() {
+        let mut logger = Logger::new(3);
+        logger.error("e1");
+        logger.info("i1");
+        logger.warning("w1");
+        logger.info("i2");
+
+        assert_eq!(messages(&logger), ["e1", "w1", "i2"]);
+
+        logger.info("i3");
+        assert_eq!(messages(&logger), ["e1", "w1", "i3"]);
+        assert_eq!(logger.error_count(), 1);
+    }
+
+    #[test]
+    fn warnings_go_before_errors_once_info_is_gone() {
+        let mut logger = Logger::new(2);
+        logger.warning("w1");
+        logger.error("e1");
+        logger.error("e2");
+
+        assert_eq!(messages(&logger), ["e1", "e2"]);
+        assert_eq!(logger.warning_count(), 0);
+    }
+
+    #[test]
+    fn full_buffer_of_errors_drops_the_oldest_error() {
+        let mut logger = Logger::new(3);
+        for message in ["e1", "e2", "e3", "e4"] {
+            logger.error(message);
+        }
+
+        assert_eq!(messages(&logger), ["e2", "e3", "e4"]);
+
+        // Новая info-запись при полном буфере ошибок вытесняет саму себя
+        logger.info("i1");
+        assert_eq!(messages(&logger), ["e2", "e3", "e4"]);
+    }
+
+    #[test]
+    fn slice_stays_in_order_after_the_ring_wraps() {
+        let mut logger = Logger::new(4);
+        logger.error("e1");
+        for n in 0..50 {
+            logger.info(format!("i{}", n));
+        }
+
+        assert_eq!(messages(&logger), ["e1", "i47", "i48", "i49"]);
+        assert_eq!((logger.error_count(), logger.total_count()), (1, 4));
+    }
+
+    #[test]
+    fn shrinking_capacity_applies_the_same_policy() {
+        let mut logger = Logger::new(10);
+        logger.info("i1");
+        logger.error("e1");
+        logger.info("i2");
+        logger.warning("w1");
+
+        logger.set_capacity(2);
+
+        assert_eq!(messages(&logger), ["e1", "w1"]);
+        assert_eq!(logger.total_count(), 2);
+    }
+
+    #[test]
+    fn repo_filter_keeps_matching_and_app_wide_entries() {
+        let mut logger = Logger::new(10);
+        logger.info("startup");
+        logger.info_for("fetched", Path::new("/work/svc-a"));
+        logger.error_for("push rejected", Path::new("/work/svc-b"));
+        logger.warning_for("behind", Path::new("/work/svc-a"));
+
+        let filter = Path::new("/work/svc-a");
+        let visible: Vec<&str> = logger
+            .logs()
+            .iter()
+            .filter(|log| log.matches_context(Some(filter)))
+            .map(|log| log.message.as_str())
+            .collect();
+
+        assert_eq!(visible, ["startup", "fetched", "behind"]);
+        assert_eq!(
+            logger.contexts(),
+            [PathBuf::from("/work/svc-a"), PathBuf::from("/work/svc-b")]
+        );
+        assert_eq!(logger.logs()[2].context_name().as_deref(), Some("svc-b"));
+    }
+
+    #[test]
+    fn utc_format_handles_epoch_and_leap_days() {
+        assert_eq!(
+            format_utc(SystemTime::UNIX_EPOCH),
+            "1970-01-01 00:00:00 UTC"
+        );
+        assert_eq!(
+            format_utc(SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29 12:34:56 UTC"
+        );
+    }
+
+    #[test]
+    fn query_encoding_escapes_reserved_and_non_ascii() {
+        assert_eq!(
+            encode_query("fatal: 'origin' & ошибка"),
+            "fatal%3A+%27origin%27+%26+%D0%BE%D1%88%D0%B8%D0%B1%D0%BA%D0%B0"
+        );
+        assert_eq!(encode_query("a-b_c.d~e"), "a-b_c.d~e");
+    }
+}
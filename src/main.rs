@@ -11,20 +11,182 @@ mod logging;
 mod ui;
 mod workspace;
 
-use app::{AppMessage, MyApp, RepositorySearcher, TreeBuilder};
+use app::{
+    close_action, list_order, measured_ms_per_dir, stale_fetch_count, AppMessage, AppShutdownState,
+    AttentionFilter, CloseAction, ConflictAction, ConflictStage, ListColumn, ListSort, MyApp,
+    RepoFilter, RepoOp, RepoPredicate, RepositorySearcher, ShutdownChoice, SwitchPlan, TrayCommand,
+    TrayStatus, TreeBuilder, DEFAULT_MS_PER_DIR, SCAN_PREVIEW_MIN_REPOS,
+};
+use config::{
+    find_identity_mismatch, home_dir, BranchSwitchConfirmation, ConfigFormat, ConfigManager,
+    ConfigOverride, CredentialHelper, IdentityRule, RowDensity, RowElement, SortMode, ViewMode,
+};
 
 use git::{
-    git_fetch_fast_async, git_fetch_fast_async_with_retry, git_pull_fast_async,
-    git_push_fast_async, git_reset_hard, refresh_repo_status_async, switch_branch, GitMessage,
+    cancel_queued, classify_git_error, contributor_since, digest_to_markdown, error_repo_path,
+    git_cherry, git_deepen_async, git_describe_for_ci, git_fetch_fast_async,
+    git_fetch_remote_async, git_push_tags_async, git_remote_prune, git_remote_prune_dryrun,
+    git_reset_hard, git_set_local_identity, group_by_author, is_queued, is_sensitive_env_name,
+    parse_remote_url, pool_status, refresh_repo_status_async, remote_provider,
+    set_branch_list_limit, set_fetch_depth, unix_now, BisectState, BisectVerdict, CommitActivity,
+    DirtyNoise, GitErrorKind, GitInfo, GitMessage, SnapshotChangeKind, StashAction, ACTIVITY_DAYS,
+    CONTRIBUTOR_RANGES,
 };
 
-use logging::LogLevel;
-use ui::{Button, Icon, IconType};
-use workspace::{RepositoryState, Workspace};
+use logging::{format_elapsed, take_audit_warnings, AuditOperation, LogLevel};
+use ui::{
+    ellipsize_to_width, event_bytes, Button, Icon, IconType, Tray, TrayLabels, WorkspacePanelState,
+};
+use workspace::{
+    find_workspace_containing, format_relative_time, EnvironmentLabel, RepositoryState, Workspace,
+    WorkspaceId,
+};
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 
+fn commit_age(time: i64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(time);
+    format_elapsed(std::time::Duration::from_secs((now - time).max(0) as u64))
+}
+
+/// Colored, virtualized diff view; only visible rows are laid out.
+fn render_diff(ui: &mut egui::Ui, diff: &str, scroll_id: &str) {
+    let lines: Vec<&str> = diff.lines().collect();
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .id_source(scroll_id)
+        .auto_shrink([false, false])
+        .show_rows(ui, row_height, lines.len(), |ui, range| {
+            for line in &lines[range] {
+                ui.label(
+                    egui::RichText::new(*line)
+                        .monospace()
+                        .color(diff_line_color(line)),
+                );
+            }
+        });
+}
+
+fn diff_line_color(line: &str) -> egui::Color32 {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+        egui::Color32::WHITE
+    } else if line.starts_with('+') {
+        egui::Color32::LIGHT_GREEN
+    } else if line.starts_with('-') {
+        egui::Color32::LIGHT_RED
+    } else if line.starts_with("@@") {
+        egui::Color32::LIGHT_BLUE
+    } else {
+        egui::Color32::GRAY
+    }
+}
+
+fn parse_config_args() -> Option<ConfigOverride> {
+    let mut config_path: Option<PathBuf> = None;
+    let mut config_format: Option<ConfigFormat> = None;
+    let mut portable = false;
+    let mut extra: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config-file" => config_path = args.next().map(PathBuf::from),
+            "--portable" => portable = true,
+            "--config-extra" => extra = args.next().map(PathBuf::from),
+            "--config-format" => {
+                if let Some(name) = args.next() {
+                    config_format = ConfigFormat::from_name(&name);
+                    if config_format.is_none() {
+                        eprintln!("Unknown config format: {}", name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if config_path.is_none() && config_format.is_none() && !portable && extra.is_none() {
+        return None;
+    }
+
+    let format = config_format.unwrap_or_else(|| {
+        config_path
+            .as_deref()
+            .map(ConfigFormat::from_path)
+            .unwrap_or(ConfigFormat::Json)
+    });
+
+    Some(ConfigOverride {
+        path: config_path,
+        format,
+        portable,
+        extra,
+    })
+}
+
+/// List rows have no folder nesting, only a small margin.
+const LIST_ROW_INDENT: f32 = 8.0;
+
+/// Widths of the name, branch and status columns of a repository row; the
+/// actions take what is left. The list header lines up with the same split.
+fn repo_row_widths(available_width: f32) -> (f32, f32, f32) {
+    let fetch_button_width = 30.0;
+    let menu_width = 35.0;
+    let status_width = 130.0;
+    let branch_width = f32::min(180.0, f32::max(100.0, available_width * 0.2));
+
+    let buttons_width = fetch_button_width + menu_width + 10.0;
+    let min_repo_width = 100.0;
+
+    let repo_width = f32::max(
+        min_repo_width,
+        available_width - branch_width - status_width - buttons_width,
+    );
+    (repo_width, branch_width, status_width)
+}
+
+/// 30-day commit activity drawn as a line in a fixed 60×14 cell; an empty
+/// cell keeps the row aligned while the data loads.
+fn activity_sparkline(ui: &mut egui::Ui, activity: Option<&CommitActivity>) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(60.0, 14.0), egui::Sense::hover());
+    let Some(activity) = activity else {
+        return response;
+    };
+
+    let max = activity.counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let step = rect.width() / (activity.counts.len() - 1) as f32;
+    let points: Vec<egui::Pos2> = activity
+        .counts
+        .iter()
+        .enumerate()
+        .map(|(day, &count)| {
+            egui::pos2(
+                rect.left() + day as f32 * step,
+                rect.bottom() - 1.0 - (rect.height() - 2.0) * count as f32 / max,
+            )
+        })
+        .collect();
+    let color = if activity.total() == 0 {
+        ui.visuals().weak_text_color()
+    } else {
+        egui::Color32::from_rgb(90, 180, 110)
+    };
+    let painter = ui.painter_at(rect);
+    for segment in points.windows(2) {
+        painter.line_segment([segment[0], segment[1]], egui::Stroke::new(1.0, color));
+    }
+    response
+}
+
 fn main() {
+    if let Some(config_override) = parse_config_args() {
+        ConfigManager::set_override(config_override);
+    }
+
     let mut app = MyApp::load_or_default();
     app.setup_git_communication();
 
@@ -36,12 +198,9 @@ fn main() {
         native_options.viewport.inner_size = Some(egui::Vec2::new(1200.0, 800.0));
     }
 
-    eframe::run_native(
-        "Repo Manager",
-        native_options,
-        Box::new(|_cc| Box::new(app)),
-    )
-    .unwrap();
+    let title = app.window_title();
+
+    eframe::run_native(&title, native_options, Box::new(|_cc| Box::new(app))).unwrap();
 }
 
 impl MyApp {
@@ -50,311 +209,4624 @@ impl MyApp {
             self.localizer
                 .tf("searching_in_path", &[&path.display().to_string()]),
         );
+        // Первый уровень читается быстро — оценку показываем сразу
+        let estimate = RepositorySearcher::estimate_scan_time(
+            &path,
+            self.config.actual_ms_per_dir.unwrap_or(DEFAULT_MS_PER_DIR),
+        );
         self.search_status = Some(self.localizer.tf(
             "searching_repos",
-            &[&format!("{:?}", path.file_name().unwrap_or_default())],
+            &[
+                &format!("{:?}", path.file_name().unwrap_or_default()),
+                &format_elapsed(estimate),
+            ],
         ));
         self.search_status_timer = Some(std::time::Instant::now());
         self.is_searching = true;
 
         if let Some(tx) = &self.app_sender {
             let tx_clone = tx.clone();
+            let scan_threads = self.config.scan_threads;
+            let follow_symlinks = self.config.follow_symlinks_in_scan;
             std::thread::spawn(move || {
-                let repos = RepositorySearcher::find_git_repositories(&path);
-                if tx_clone.send(AppMessage::ReposFound { repos }).is_err() {
+                let dirs = RepositorySearcher::count_scan_dirs(&path);
+                let started = std::time::Instant::now();
+                let repos =
+                    RepositorySearcher::find_git_repositories(&path, scan_threads, follow_symlinks);
+                let message = AppMessage::ReposFound {
+                    root: path,
+                    repos,
+                    ms_per_dir: measured_ms_per_dir(started.elapsed(), dirs),
+                };
+                if tx_clone.send(message).is_err() {
                     eprintln!("Failed to send found repositories");
                 }
             });
         }
     }
 
-    fn render_tree_node(
-        &mut self,
-        ui: &mut egui::Ui,
-        node: &app::TreeNode,
-        workspace: &[RepositoryState],
-        depth: usize,
-        to_remove: &std::cell::RefCell<Option<usize>>,
-    ) {
-        if depth > 0 {
-            let indent = (depth as f32) * 20.0;
-            ui.horizontal(|ui| {
-                ui.add_space(indent - 20.0);
+    fn render_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_settings;
 
-                let has_children = !node.children.is_empty();
-                let has_repos = !node.repositories.is_empty();
+        egui::Window::new(self.localizer.t("settings"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let missing_keys = self.localizer.missing_keys();
+                let header = self.localizer.tf(
+                    "translation_coverage",
+                    &[
+                        &missing_keys.len().to_string(),
+                        self.localizer.get_language(),
+                    ],
+                );
 
-                if has_children || has_repos {
-                    let node_path = node.path.to_string_lossy().to_string();
-                    let is_collapsed = self.collapsed_paths.contains(&node_path);
-                    let expand_symbol = if is_collapsed { "+" } else { "-" };
+                egui::CollapsingHeader::new(self.localizer.t("expected_identities"))
+                    .id_source("expected_identities")
+                    .show(ui, |ui| {
+                        let mut rule_to_remove = None;
+                        let mut changed = false;
+
+                        for (idx, rule) in self.config.expected_identities.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut rule.path_prefix)
+                                            .hint_text(self.localizer.t("path_prefix"))
+                                            .desired_width(110.0),
+                                    )
+                                    .changed();
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut rule.email)
+                                            .hint_text(self.localizer.t("email"))
+                                            .desired_width(130.0),
+                                    )
+                                    .changed();
+
+                                let mut name = rule.name.clone().unwrap_or_default();
+                                if ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut name)
+                                            .hint_text(self.localizer.t("name_optional"))
+                                            .desired_width(90.0),
+                                    )
+                                    .changed()
+                                {
+                                    rule.name = (!name.trim().is_empty()).then_some(name);
+                                    changed = true;
+                                }
+
+                                if ui.small_button(self.localizer.t("remove")).clicked() {
+                                    rule_to_remove = Some(idx);
+                                }
+                            });
+                        }
+
+                        if ui.button(self.localizer.t("add_rule")).clicked() {
+                            self.config
+                                .expected_identities
+                                .push(IdentityRule::default());
+                            changed = true;
+                        }
+
+                        if let Some(idx) = rule_to_remove {
+                            self.config.expected_identities.remove(idx);
+                            changed = true;
+                        }
+
+                        if changed {
+                            self.mark_config_dirty();
+                        }
+                    });
 
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("scan_threads"));
                     if ui
-                        .button(format!("{} {}", expand_symbol, node.name))
-                        .clicked()
+                        .add(
+                            egui::DragValue::new(&mut self.config.scan_threads).clamp_range(1..=32),
+                        )
+                        .changed()
                     {
-                        if is_collapsed {
-                            self.collapsed_paths.remove(&node_path);
-                        } else {
-                            self.collapsed_paths.insert(node_path.clone());
-                        }
+                        self.mark_config_dirty();
                     }
+                });
 
-                    let total_items = node.children.len() + node.repositories.len();
-                    if total_items > 0 {
-                        ui.colored_label(
-                            egui::Color32::DARK_GRAY,
-                            self.localizer
-                                .tf("elements_count", &[&total_items.to_string()]),
-                        );
-                    }
-                } else {
-                    ui.horizontal(|ui| {
-                        Button::icon(IconType::Folder).show(ui, &mut self.icon_manager);
-                        ui.label(&node.name);
-                    });
+                if ui
+                    .checkbox(
+                        &mut self.config.follow_symlinks_in_scan,
+                        self.localizer.t("follow_symlinks_in_scan"),
+                    )
+                    .changed()
+                {
+                    self.save_config();
+                }
+                if ui
+                    .checkbox(
+                        &mut self.config.confirm_scan_results,
+                        self.localizer.tf(
+                            "confirm_scan_results",
+                            &[&SCAN_PREVIEW_MIN_REPOS.to_string()],
+                        ),
+                    )
+                    .changed()
+                {
+                    self.save_config();
                 }
-            });
 
-            let node_path = node.path.to_string_lossy().to_string();
-            if self.collapsed_paths.contains(&node_path) && depth > 0 {
-                return;
-            }
-        }
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("workspace_split_threshold"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.workspace_split_threshold)
+                                .clamp_range(0..=1000),
+                        )
+                        .on_hover_text(self.localizer.t("workspace_split_threshold_hint"))
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-        for child in &node.children {
-            self.render_tree_node(ui, child, workspace, depth + 1, to_remove);
-        }
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("workspace_removal_typed_threshold"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(
+                                &mut self.config.workspace_removal_typed_threshold,
+                            )
+                            .clamp_range(0..=1000),
+                        )
+                        .on_hover_text(self.localizer.t("workspace_removal_typed_threshold_hint"))
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-        let repos_count = node.repositories.len();
-        for (repo_index, (original_idx, _)) in node.repositories.iter().enumerate() {
-            if let Some(repo) = workspace.get(*original_idx) {
-                let indent = ((depth + 1) as f32) * 20.0;
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("branch_list_limit"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.branch_list_limit)
+                                .clamp_range(10..=5000),
+                        )
+                        .changed()
+                    {
+                        set_branch_list_limit(self.config.branch_list_limit);
+                        self.mark_config_dirty();
+                    }
+                });
 
                 ui.horizontal(|ui| {
-                    ui.add_space(indent);
+                    ui.label(self.localizer.t("log_capacity"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.log_capacity)
+                                .clamp_range(100..=100_000),
+                        )
+                        .on_hover_text(self.localizer.t("log_capacity_hint"))
+                        .changed()
+                    {
+                        self.logger.set_capacity(self.config.log_capacity);
+                        self.mark_config_dirty();
+                    }
+                });
 
-                    let available_width = ui.available_width();
-                    let fetch_button_width = 30.0;
-                    let menu_width = 35.0;
-                    let status_width = 130.0;
-                    let branch_width = f32::min(180.0, f32::max(100.0, available_width * 0.2));
+                ui.horizontal(|ui| {
+                    let mut shallow = self.config.default_fetch_depth.is_some();
+                    let mut changed = ui
+                        .checkbox(&mut shallow, self.localizer.t("default_fetch_depth"))
+                        .on_hover_text(self.localizer.t("default_fetch_depth_hint"))
+                        .changed();
+                    let mut depth = self.config.default_fetch_depth.unwrap_or(50);
+                    changed |= ui
+                        .add_enabled(
+                            shallow,
+                            egui::DragValue::new(&mut depth).clamp_range(1..=100_000),
+                        )
+                        .changed();
+                    if changed {
+                        self.config.default_fetch_depth = shallow.then_some(depth);
+                        set_fetch_depth(self.config.default_fetch_depth);
+                        self.mark_config_dirty();
+                    }
+                });
 
-                    let buttons_width = fetch_button_width + menu_width + 10.0;
-                    let min_repo_width = 100.0;
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("git_executable"))
+                        .on_hover_text(self.localizer.t("git_executable_hint"));
+                    let response = ui
+                        .text_edit_singleline(&mut self.git_executable_input)
+                        .on_hover_text(self.localizer.t("git_executable_hint"));
+                    // Путь применяется целиком: по Enter или уходу из поля,
+                    // а не на каждую набранную букву
+                    if response.lost_focus() {
+                        self.set_git_executable_input();
+                        self.check_git_environment();
+                    }
+                });
 
-                    let repo_width = f32::max(
-                        min_repo_width,
-                        available_width - branch_width - status_width - buttons_width,
-                    );
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("difftool_command"))
+                        .on_hover_text(self.localizer.t("difftool_command_hint"));
+                    if ui
+                        .text_edit_singleline(&mut self.config.difftool_command)
+                        .on_hover_text(self.localizer.t("difftool_command_hint"))
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-                    ui.allocate_ui_with_layout(
-                        egui::Vec2::new(repo_width, 25.0),
-                        egui::Layout::left_to_right(egui::Align::Center),
-                        |ui| {
-                            ui.set_min_size(egui::Vec2::new(repo_width, 25.0));
-                            if ui.button(&repo.name).clicked() {
-                                opener::open(&repo.path).ok();
-                            }
-                        },
-                    );
+                if ConfigManager::is_portable() {
+                    ui.horizontal(|ui| {
+                        ui.label(self.localizer.t("path_root"))
+                            .on_hover_text(self.localizer.t("path_root_hint"));
+                        if ui
+                            .text_edit_singleline(&mut self.config.path_root)
+                            .on_hover_text(self.localizer.t("path_root_hint"))
+                            .changed()
+                        {
+                            self.mark_config_dirty();
+                        }
+                    });
+                }
 
-                    ui.allocate_ui_with_layout(
-                        egui::Vec2::new(branch_width, 25.0),
-                        egui::Layout::left_to_right(egui::Align::Center),
-                        |ui| {
-                            ui.set_min_size(egui::Vec2::new(branch_width, 25.0));
-                            ui.set_max_size(egui::Vec2::new(branch_width, 25.0));
-
-                            let current_branch =
-                                repo.git_info.current_branch.as_deref().unwrap_or("...");
-                            let display_branch = if current_branch.len() > 15 {
-                                format!("{}...", &current_branch[..12])
-                            } else {
-                                current_branch.to_string()
-                            };
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("catch_up_minutes"))
+                        .on_hover_text(self.localizer.t("catch_up_minutes_hint"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.catch_up_minutes)
+                                .clamp_range(1..=1440),
+                        )
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-                            egui::ComboBox::from_id_source(&repo.path)
-                                .selected_text(display_branch)
-                                .width(branch_width - 10.0)
-                                .show_ui(ui, |ui| {
-                                    for branch in &repo.git_info.branches {
-                                        let label = ui
-                                            .selectable_label(false, branch)
-                                            .on_hover_text(branch);
-
-                                        if label.clicked() {
-                                            if let Err(e) = switch_branch(&repo.path, branch) {
-                                                self.logger.error(self.localizer.tf(
-                                                    "branch_switch_error",
-                                                    &[&repo.name, &e.to_string()],
-                                                ));
-                                            } else {
-                                                if let Some(tx) = &self.app_sender {
-                                                    refresh_repo_status_async::<AppMessage>(
-                                                        repo.path.clone(),
-                                                        tx.clone(),
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
-                                });
-                        },
-                    );
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("shutdown_wait_secs"))
+                        .on_hover_text(self.localizer.t("shutdown_wait_secs_hint"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.shutdown_wait_secs)
+                                .clamp_range(5..=3600),
+                        )
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-                    ui.allocate_ui_with_layout(
-                        egui::Vec2::new(status_width, 25.0),
-                        egui::Layout::left_to_right(egui::Align::Center),
-                        |ui| {
-                            ui.set_min_size(egui::Vec2::new(status_width, 25.0));
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("tooltip_delay_ms"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.tooltip_delay_ms)
+                                .clamp_range(0..=5000)
+                                .speed(10),
+                        )
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-                            if self.syncing_repos.contains(&repo.path) {
-                                ui.spinner();
-                            }
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("tooltip_on_hover_threshold_px"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.tooltip_on_hover_threshold_px)
+                                .clamp_range(0.0..=50.0)
+                                .speed(0.1),
+                        )
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-                            if repo.git_info.behind > 0 {
-                                let pull_button = Button::icon_text(
-                                    IconType::Pull,
-                                    format!("{}", repo.git_info.behind),
-                                )
-                                .show(ui, &mut self.icon_manager);
-                                if pull_button.clicked() {
-                                    self.logger
-                                        .info(self.localizer.tf("starting_pull", &[&repo.name]));
-                                    self.syncing_repos.insert(repo.path.clone());
-                                    if let Some(tx) = &self.app_sender {
-                                        git_pull_fast_async::<AppMessage>(
-                                            repo.path.clone(),
-                                            tx.clone(),
-                                        );
-                                    }
-                                }
-                                pull_button.on_hover_text(
-                                    self.localizer
-                                        .tf("pull_commits", &[&repo.git_info.behind.to_string()]),
-                                );
-                            }
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("recent_days"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.recent_days).clamp_range(1..=365),
+                        )
+                        .changed()
+                    {
+                        self.mark_config_dirty();
+                    }
+                });
 
-                            if repo.git_info.ahead > 0 {
-                                let push_button = Button::icon_text(
-                                    IconType::Push,
-                                    format!("{}", repo.git_info.ahead),
-                                )
-                                .show(ui, &mut self.icon_manager);
-                                if push_button.clicked() {
-                                    self.logger
-                                        .info(self.localizer.tf("starting_push", &[&repo.name]));
-                                    self.syncing_repos.insert(repo.path.clone());
-                                    if let Some(tx) = &self.app_sender {
-                                        git_push_fast_async::<AppMessage>(
-                                            repo.path.clone(),
-                                            tx.clone(),
-                                        );
-                                    }
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("max_tree_depth"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.max_tree_depth)
+                                .clamp_range(1..=32),
+                        )
+                        .changed()
+                    {
+                        self.logged_tree_truncations.clear();
+                        self.mark_config_dirty();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("row_density"));
+                    for density in RowDensity::ALL {
+                        if ui
+                            .selectable_label(
+                                self.config.row_density == density,
+                                self.localizer.t(density.label_key()),
+                            )
+                            .clicked()
+                            && self.config.row_density != density
+                        {
+                            self.config.row_density = density;
+                            self.save_config();
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("branch_switch_confirmation"));
+                    let current = self.config.branch_switch_confirmation;
+                    egui::ComboBox::from_id_source("branch_switch_confirmation")
+                        .selected_text(self.localizer.t(current.label_key()))
+                        .show_ui(ui, |ui| {
+                            for mode in BranchSwitchConfirmation::ALL {
+                                if ui
+                                    .selectable_label(
+                                        current == mode,
+                                        self.localizer.t(mode.label_key()),
+                                    )
+                                    .clicked()
+                                    && current != mode
+                                {
+                                    self.config.branch_switch_confirmation = mode;
+                                    self.save_config();
                                 }
-                                push_button.on_hover_text(
-                                    self.localizer
-                                        .tf("push_commits", &[&repo.git_info.ahead.to_string()]),
-                                );
                             }
+                        });
+                });
 
-                            if self.error_repos.contains(&repo.path) {
-                                let error_indicator = ui.colored_label(egui::Color32::RED, "!");
-                                error_indicator.on_hover_text(&self.localizer.t("error_loading"));
-                            }
+                if ui
+                    .checkbox(
+                        &mut self.config.confirm_bulk_actions,
+                        self.localizer.t("confirm_bulk_actions"),
+                    )
+                    .changed()
+                {
+                    self.mark_config_dirty();
+                }
 
-                            if !self.error_repos.contains(&repo.path) && repo.git_info.has_changes {
-                                let changes_indicator =
-                                    ui.colored_label(egui::Color32::YELLOW, "!");
-                                changes_indicator.on_hover_text(&self.localizer.t("has_changes"));
-                            }
-                        },
-                    );
+                if ui
+                    .checkbox(
+                        &mut self.config.auto_show_digest,
+                        self.localizer.t("auto_show_digest"),
+                    )
+                    .changed()
+                {
+                    self.mark_config_dirty();
+                }
 
-                    if Button::icon(IconType::Refresh)
-                        .show(ui, &mut self.icon_manager)
-                        .on_hover_text(&self.localizer.t("fetch"))
-                        .clicked()
-                    {
-                        self.logger
-                            .info(self.localizer.tf("starting_fetch", &[&repo.name]));
-                        self.syncing_repos.insert(repo.path.clone());
-                        if let Some(tx) = &self.app_sender {
-                            git_fetch_fast_async::<AppMessage>(repo.path.clone(), tx.clone());
-                        }
-                    }
+                if ui
+                    .checkbox(
+                        &mut self.config.show_activity_sparkline,
+                        self.localizer.t("show_activity_sparkline"),
+                    )
+                    .changed()
+                {
+                    self.mark_config_dirty();
+                }
 
-                    ui.menu_button("»", |ui| {
-                        if Button::icon_text(IconType::Refresh, &self.localizer.t("fetch"))
+                if ui
+                    .checkbox(
+                        &mut self.config.fetch_submodules_with_parent,
+                        self.localizer.t("fetch_submodules_with_parent"),
+                    )
+                    .changed()
+                {
+                    self.mark_config_dirty();
+                }
+
+                let tray_available = matches!(self.tray, Some(Some(_)));
+                if ui
+                    .add_enabled(
+                        tray_available,
+                        egui::Checkbox::new(
+                            &mut self.config.close_to_tray,
+                            self.localizer.t("close_to_tray"),
+                        ),
+                    )
+                    .on_disabled_hover_text(self.localizer.t("close_to_tray_unavailable"))
+                    .changed()
+                {
+                    self.mark_config_dirty();
+                }
+
+                if ui
+                    .checkbox(
+                        &mut self.config.force_push_require_reason,
+                        self.localizer.t("force_push_require_reason"),
+                    )
+                    .changed()
+                {
+                    self.mark_config_dirty();
+                }
+
+                egui::CollapsingHeader::new(self.localizer.t("force_push_history"))
+                    .id_source("force_push_history")
+                    .show(ui, |ui| {
+                        if self.config.force_push_history.is_empty() {
+                            ui.weak(self.localizer.t("force_push_history_empty"));
+                        }
+                        egui::ScrollArea::vertical()
+                            .id_source("force_push_history_scroll")
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                // Новые записи сверху
+                                for record in self.config.force_push_history.iter().rev() {
+                                    let pushed_at = std::time::UNIX_EPOCH
+                                        + std::time::Duration::from_secs(record.timestamp);
+                                    let short =
+                                        |hash: &str| hash.chars().take(8).collect::<String>();
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "{} ({}) — {}",
+                                            record.repo_name,
+                                            record.branch,
+                                            format_relative_time(pushed_at)
+                                        ))
+                                        .strong(),
+                                    );
+                                    ui.label(&record.reason);
+                                    ui.monospace(format!(
+                                        "{} → {}",
+                                        record
+                                            .commit_before
+                                            .as_deref()
+                                            .map_or_else(|| "-".to_string(), short),
+                                        short(&record.commit_after)
+                                    ));
+                                    ui.separator();
+                                }
+                            });
+                    });
+
+                egui::CollapsingHeader::new(self.localizer.t("row_elements"))
+                    .id_source("row_elements")
+                    .show(ui, |ui| {
+                        let mut row = self.config.row_elements.clone();
+                        let mut changed = false;
+                        let last = row.len().saturating_sub(1);
+
+                        for idx in 0..row.len() {
+                            ui.horizontal(|ui| {
+                                ui.label(self.localizer.t(row[idx].label_key()));
+                                if ui.add_enabled(idx > 0, egui::Button::new("⬆")).clicked() {
+                                    row.swap(idx, idx - 1);
+                                    changed = true;
+                                }
+                                if ui.add_enabled(idx < last, egui::Button::new("⬇")).clicked() {
+                                    row.swap(idx, idx + 1);
+                                    changed = true;
+                                }
+                                if ui.small_button(self.localizer.t("hide")).clicked() {
+                                    row.remove(idx);
+                                    changed = true;
+                                }
+                            });
+                            if changed {
+                                break;
+                            }
+                        }
+
+                        let hidden = RowElement::hidden(&row);
+                        if !hidden.is_empty() {
+                            ui.separator();
+                            for element in hidden {
+                                ui.horizontal(|ui| {
+                                    ui.colored_label(
+                                        egui::Color32::DARK_GRAY,
+                                        self.localizer.t(element.label_key()),
+                                    );
+                                    if ui.small_button(self.localizer.t("show")).clicked() {
+                                        row.push(element);
+                                        changed = true;
+                                    }
+                                });
+                            }
+                        }
+
+                        if changed {
+                            self.config.row_elements = row;
+                            self.save_config();
+                        }
+                    });
+
+                egui::CollapsingHeader::new(self.localizer.t("credentials"))
+                    .id_source("credentials")
+                    .show(ui, |ui| {
+                        let mut changed = false;
+                        let current = self.config.credential_helper.clone();
+
+                        egui::ComboBox::from_id_source("credential_helper")
+                            .selected_text(self.localizer.t(current.label_key()))
+                            .show_ui(ui, |ui| {
+                                for preset in CredentialHelper::presets() {
+                                    let selected = current.same_kind(&preset);
+                                    if ui
+                                        .selectable_label(
+                                            selected,
+                                            self.localizer.t(preset.label_key()),
+                                        )
+                                        .clicked()
+                                        && !selected
+                                    {
+                                        self.config.credential_helper = preset;
+                                        changed = true;
+                                    }
+                                }
+                            });
+
+                        match &mut self.config.credential_helper {
+                            CredentialHelper::Cache { timeout_secs } => {
+                                ui.horizontal(|ui| {
+                                    ui.label(self.localizer.t("cache_timeout"));
+                                    changed |= ui
+                                        .add(egui::DragValue::new(timeout_secs).suffix(" s"))
+                                        .changed();
+                                });
+                            }
+                            CredentialHelper::Custom(value) => {
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(value)
+                                            .hint_text(self.localizer.t("custom_helper_hint")),
+                                    )
+                                    .changed();
+                            }
+                            CredentialHelper::Store => {
+                                ui.colored_label(
+                                    egui::Color32::YELLOW,
+                                    self.localizer.t("credential_store_warning"),
+                                );
+                            }
+                            _ => {}
+                        }
+
+                        if self.config.credential_helper == CredentialHelper::Default
+                            && self.global_credential_helper().is_none()
+                        {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                self.localizer.t("no_credential_helper"),
+                            );
+                        }
+
+                        if ui.button(self.localizer.t("apply_to_all_repos")).clicked() {
+                            self.apply_credential_helper();
+                        }
+
+                        if changed {
+                            self.mark_config_dirty();
+                        }
+                    });
+
+                egui::CollapsingHeader::new(self.localizer.t("settings_transfer"))
+                    .id_source("settings_transfer")
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(self.localizer.t("file"));
+                            ui.text_edit_singleline(&mut self.settings_transfer_path);
+                        });
+
+                        ui.horizontal(|ui| {
+                            if ui.button(self.localizer.t("export_settings")).clicked() {
+                                self.export_settings();
+                            }
+                            if ui.button(self.localizer.t("import_settings")).clicked() {
+                                self.preview_settings_import();
+                            }
+                        });
+
+                        let mut apply_import = false;
+                        let mut cancel_import = false;
+                        if let Some(preview) = &self.import_preview {
+                            ui.separator();
+                            ui.label(self.localizer.tf(
+                                "import_preview",
+                                &[
+                                    &preview.workspaces_added.to_string(),
+                                    &preview.workspaces_merged.to_string(),
+                                    &preview.settings_overwritten.to_string(),
+                                ],
+                            ));
+                            ui.horizontal(|ui| {
+                                apply_import = ui.button(self.localizer.t("apply")).clicked();
+                                cancel_import = ui.button(self.localizer.t("cancel")).clicked();
+                            });
+                        }
+
+                        if apply_import {
+                            self.apply_settings_import();
+                        } else if cancel_import {
+                            self.import_preview = None;
+                        }
+                    });
+
+                if ui
+                    .button(self.localizer.tf(
+                        "duplicates_report",
+                        &[&self.repo_duplicates.len().to_string()],
+                    ))
+                    .clicked()
+                {
+                    self.show_duplicates_report = true;
+                }
+
+                egui::CollapsingHeader::new(header)
+                    .id_source("translation_coverage")
+                    .show(ui, |ui| {
+                        for key in &missing_keys {
+                            ui.monospace(key);
+                        }
+
+                        if ui
+                            .add_enabled(
+                                !missing_keys.is_empty(),
+                                egui::Button::new(self.localizer.t("copy_missing_keys")),
+                            )
+                            .clicked()
+                        {
+                            let skeleton = self.localizer.missing_keys_skeleton();
+                            ui.output_mut(|o| o.copied_text = skeleton);
+                        }
+                    });
+            });
+
+        self.show_settings = open;
+    }
+
+    fn environment_text(&self, environment: &EnvironmentLabel) -> String {
+        match environment {
+            EnvironmentLabel::Custom(text) => text.clone(),
+            other => self.localizer.t(other.label_key()),
+        }
+    }
+
+    fn render_confirmation_window(&mut self, ctx: &egui::Context) {
+        let Some(action) = &self.pending_confirmation else {
+            return;
+        };
+        let args = action.message_args();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let message = self.localizer.tf(action.message_key(), &args);
+        let items = action.items().to_vec();
+        let required_phrase = action.required_phrase().map(str::to_string);
+        let shift_unlocks = action.shift_unlocks();
+
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new(self.localizer.t("confirm_title"))
+            .id(egui::Id::new("confirm_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(message);
+                if !items.is_empty() {
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for item in &items {
+                                ui.monospace(item);
+                            }
+                        });
+                }
+                if let Some(phrase) = &required_phrase {
+                    ui.label(self.localizer.tf("type_to_confirm", &[phrase]));
+                    ui.text_edit_singleline(&mut self.confirmation_input);
+                    if shift_unlocks {
+                        ui.weak(self.localizer.t("hold_shift_to_confirm"));
+                    }
+                }
+                let unlocked = match &required_phrase {
+                    Some(phrase) => {
+                        self.confirmation_input.trim() == phrase
+                            || (shift_unlocks && ui.input(|i| i.modifiers.shift))
+                    }
+                    None => true,
+                };
+                ui.horizontal(|ui| {
+                    confirm = ui
+                        .add_enabled(
+                            unlocked,
+                            egui::Button::new(
+                                egui::RichText::new(self.localizer.t("confirm"))
+                                    .color(egui::Color32::LIGHT_RED),
+                            ),
+                        )
+                        .clicked();
+                    cancel = ui.button(self.localizer.t("cancel")).clicked();
+                });
+            });
+
+        if confirm {
+            self.confirmation_input.clear();
+            self.confirm_pending_action();
+        } else if cancel {
+            self.confirmation_input.clear();
+            self.pending_confirmation = None;
+        }
+    }
+
+    /// "Workspace removed — Undo" toast, shown while the removal can still be
+    /// undone.
+    fn render_workspace_undo_toast(&mut self, ctx: &egui::Context) {
+        let Some(removed) = &self.removed_workspace else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        if !removed.can_undo(now) {
+            self.removed_workspace = None;
+            return;
+        }
+        let message = self
+            .localizer
+            .tf("workspace_removed", &[&removed.workspace.name]);
+
+        let mut undo = false;
+        egui::Area::new(egui::Id::new("workspace_undo_toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(message);
+                        undo = ui.button(self.localizer.t("undo")).clicked();
+                    });
+                });
+            });
+
+        if undo {
+            self.undo_workspace_removal();
+        } else {
+            // Тост должен исчезнуть и без движения мыши
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+    }
+
+    fn render_shutdown_window(&mut self, ctx: &egui::Context) {
+        let operations = self.in_flight_operations();
+        let remaining = self.shutdown.remaining(std::time::Instant::now());
+
+        let mut choice = None;
+        egui::Window::new(self.localizer.t("shutdown_title"))
+            .id(egui::Id::new("shutdown_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                match remaining {
+                    Some(remaining) => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(self.localizer.tf(
+                                "shutdown_waiting",
+                                &[
+                                    &operations.len().to_string(),
+                                    &remaining.as_secs().to_string(),
+                                ],
+                            ));
+                        });
+                    }
+                    None => {
+                        ui.label(
+                            self.localizer
+                                .tf("shutdown_in_flight", &[&operations.len().to_string()]),
+                        );
+                    }
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for (repo_name, state_key) in &operations {
+                            ui.horizontal(|ui| {
+                                ui.strong(repo_name);
+                                ui.label(self.localizer.t(state_key));
+                            });
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if remaining.is_none() {
+                        if ui.button(self.localizer.t("shutdown_wait")).clicked() {
+                            choice = Some(ShutdownChoice::WaitAndClose);
+                        }
+                        if ui.button(self.localizer.t("shutdown_cancel_ops")).clicked() {
+                            choice = Some(ShutdownChoice::CancelAndClose);
+                        }
+                    }
+                    if ui
+                        .button(
+                            egui::RichText::new(self.localizer.t("shutdown_close_anyway"))
+                                .color(egui::Color32::LIGHT_RED),
+                        )
+                        .clicked()
+                    {
+                        choice = Some(ShutdownChoice::CloseAnyway);
+                    }
+                    if remaining.is_none() && ui.button(self.localizer.t("cancel")).clicked() {
+                        choice = Some(ShutdownChoice::Stay);
+                    }
+                });
+            });
+
+        match choice {
+            Some(ShutdownChoice::CloseAnyway) if remaining.is_some() => {
+                // Ожидание уже идёт: повторный запрос закрытия пропускается
+                self.shutdown.close_requested(true);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            Some(ShutdownChoice::CloseAnyway) => {
+                self.choose_shutdown(ShutdownChoice::CloseAnyway);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            Some(choice) => self.choose_shutdown(choice),
+            None => {}
+        }
+        // Обратный отсчёт и проверка завершения без ввода пользователя
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+    }
+
+    fn render_archive_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.archive_dialog else {
+            return;
+        };
+
+        let (title, action) = match dialog.workspace_idx {
+            Some(_) => ("archive_workspace", "archive"),
+            None => ("restore_from_archive", "restore"),
+        };
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+        egui::Window::new(self.localizer.t(title))
+            .id(egui::Id::new("archive_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if dialog.workspace_idx.is_some() {
+                    ui.label(self.localizer.t("archive_workspace_hint"));
+                }
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("file"));
+                    ui.text_edit_singleline(&mut dialog.path);
+                });
+                ui.horizontal(|ui| {
+                    apply = ui.button(self.localizer.t(action)).clicked();
+                    cancel = ui.button(self.localizer.t("cancel")).clicked();
+                });
+            });
+
+        if apply {
+            self.apply_archive_dialog();
+        } else if cancel || !open {
+            self.archive_dialog = None;
+        }
+    }
+
+    /// Creates the tray icon on the first frame, keeps its status line
+    /// current and runs the menu commands.
+    fn poll_tray(&mut self, ctx: &egui::Context) {
+        if self.tray.is_none() {
+            let labels = TrayLabels {
+                fetch_all: self.localizer.t("fetch_all"),
+                open_window: self.localizer.t("tray_open_window"),
+                quit: self.localizer.t("tray_quit"),
+            };
+            self.tray = Some(Tray::new(ctx, &labels));
+        }
+
+        let status = TrayStatus::of(
+            self.get_active_workspace()
+                .map(|workspace| workspace.repositories.as_slice())
+                .unwrap_or_default(),
+        );
+        let text = if status.is_clean() {
+            self.localizer.t("tray_status_clean")
+        } else {
+            self.localizer.tf(
+                "tray_status",
+                &[&status.behind.to_string(), &status.dirty.to_string()],
+            )
+        };
+        let Some(Some(tray)) = &mut self.tray else {
+            return;
+        };
+        if self.tray_status != Some(status) {
+            tray.set_status(&text);
+            self.tray_status = Some(status);
+        }
+
+        let Some(command) = tray.poll() else {
+            return;
+        };
+        match command {
+            TrayCommand::FetchAll => self.request_fetch_all(),
+            TrayCommand::OpenWindow => self.show_from_tray(ctx),
+            TrayCommand::Quit => {
+                self.quitting_from_tray = true;
+                // Окно должно быть видно, если выход спросит о незавершённых операциях
+                self.show_from_tray(ctx);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+    }
+
+    fn show_from_tray(&mut self, ctx: &egui::Context) {
+        if self.hidden_to_tray {
+            self.hidden_to_tray = false;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    }
+
+    fn render_force_push_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.force_push_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new(self.localizer.t("force_push"))
+            .id(egui::Id::new("force_push_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    self.localizer
+                        .tf("force_push_target", &[&dialog.repo_name, &dialog.branch]),
+                );
+                ui.label(self.localizer.t("force_push_reason"));
+                let response = ui.text_edit_singleline(&mut dialog.reason);
+                let has_reason = !dialog.reason.trim().is_empty();
+                let submitted = response.lost_focus()
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                    && has_reason;
+                ui.horizontal(|ui| {
+                    confirm = ui
+                        .add_enabled(
+                            has_reason,
+                            egui::Button::new(self.localizer.t("force_push_confirm")),
+                        )
+                        .clicked()
+                        || submitted;
+                    cancel = ui.button(self.localizer.t("cancel")).clicked();
+                });
+            });
+
+        if confirm {
+            if let Some(dialog) = self.force_push_dialog.take() {
+                self.start_force_push(
+                    dialog.repo_path,
+                    dialog.repo_name,
+                    dialog.branch,
+                    dialog.reason.trim().to_string(),
+                );
+            }
+        } else if cancel || !open {
+            self.force_push_dialog = None;
+        }
+    }
+
+    fn render_branch_switch_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.branch_switch_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut changed = false;
+        let mut run = false;
+        let mut cancel = false;
+        let localizer = &self.localizer;
+        egui::Window::new(localizer.t("switch_branch_across_title"))
+            .id(egui::Id::new("branch_switch_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(localizer.t("branch_switch_target"));
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut dialog.target)
+                                .hint_text("release/2024.06"),
+                        )
+                        .changed();
+                });
+                changed |= ui
+                    .checkbox(
+                        &mut dialog.create_missing,
+                        localizer.t("branch_switch_create_missing"),
+                    )
+                    .changed();
+                ui.separator();
+
+                if dialog.rows.is_empty() {
+                    ui.weak(localizer.t("branch_switch_enter_name"));
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("branch_switch_plan")
+                            .num_columns(3)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for row in &mut dialog.rows {
+                                    ui.label(&row.repo_name)
+                                        .on_hover_text(row.repo_path.display().to_string());
+                                    let color = if row.plan.runs() {
+                                        ui.visuals().text_color()
+                                    } else {
+                                        egui::Color32::GRAY
+                                    };
+                                    ui.colored_label(color, localizer.t(row.plan.label_key()));
+                                    // Грязный репозиторий переключается только по явной галочке
+                                    if row.plan == SwitchPlan::SkipDirty || row.override_dirty {
+                                        changed |= ui
+                                            .checkbox(
+                                                &mut row.override_dirty,
+                                                localizer.t("branch_switch_override_dirty"),
+                                            )
+                                            .changed();
+                                    } else {
+                                        ui.label("");
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+                let runnable = dialog.rows.iter().filter(|row| row.plan.runs()).count();
+                let loading = dialog
+                    .rows
+                    .iter()
+                    .any(|row| row.plan == SwitchPlan::LoadingBranches);
+                ui.horizontal(|ui| {
+                    run = ui
+                        .add_enabled(
+                            runnable > 0 && !loading,
+                            egui::Button::new(
+                                localizer.tf("branch_switch_run", &[&runnable.to_string()]),
+                            ),
+                        )
+                        .clicked();
+                    cancel = ui.button(localizer.t("cancel")).clicked();
+                });
+            });
+
+        if changed {
+            self.refresh_branch_switch_plan();
+        }
+        if run {
+            self.request_bulk(app::BulkRequest::SwitchBranch);
+        } else if cancel || !open {
+            self.branch_switch_dialog = None;
+        }
+    }
+
+    /// Tooltip delay and movement threshold from the config, for every
+    /// tooltip in the app.
+    fn apply_tooltip_style(&self, ctx: &egui::Context) {
+        let delay = self.config.tooltip_delay_ms as f32 / 1000.0;
+        let moving =
+            ctx.input(|i| i.pointer.delta().length()) > self.config.tooltip_on_hover_threshold_px;
+        let style = ctx.style();
+        let interaction = &style.interaction;
+        if interaction.tooltip_delay != delay || interaction.show_tooltips_only_when_still != moving
+        {
+            ctx.style_mut(|style| {
+                style.interaction.tooltip_delay = delay;
+                style.interaction.show_tooltips_only_when_still = moving;
+            });
+        }
+    }
+
+    /// Toggle chips under the search row, each with the number of
+    /// repositories of the active workspace it would keep on its own.
+    fn render_quick_filter_chips(&mut self, ui: &mut egui::Ui) {
+        if self.show_recent {
+            return;
+        }
+        let Some(workspace) = self.get_active_workspace() else {
+            return;
+        };
+        let counts: Vec<(RepoPredicate, usize)> = RepoPredicate::ALL
+            .into_iter()
+            .map(|chip| {
+                (
+                    chip,
+                    chip.count(&workspace.repositories, self.runtime.errors()),
+                )
+            })
+            .collect();
+        let active = self.active_quick_filters();
+
+        let mut toggled = None;
+        ui.horizontal_wrapped(|ui| {
+            for (chip, count) in counts {
+                let label = format!("{} ({})", self.localizer.t(chip.label_key()), count);
+                let text = if count == 0 && !active.contains(&chip) {
+                    egui::RichText::new(label).weak()
+                } else {
+                    egui::RichText::new(label)
+                };
+                if ui.selectable_label(active.contains(&chip), text).clicked() {
+                    toggled = Some(chip);
+                }
+            }
+        });
+
+        if let Some(chip) = toggled {
+            self.toggle_quick_filter(chip);
+        }
+    }
+
+    fn render_duplicates_window(&mut self, ctx: &egui::Context) {
+        let mut open = true;
+        let mut keep_only: Option<(PathBuf, WorkspaceId)> = None;
+        let mut keep_everywhere: Option<PathBuf> = None;
+        let localizer = &self.localizer;
+        egui::Window::new(localizer.tf(
+            "duplicates_report",
+            &[&self.repo_duplicates.len().to_string()],
+        ))
+        .id(egui::Id::new("duplicates_window"))
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if self.repo_duplicates.is_empty() {
+                ui.weak(localizer.t("duplicates_none"));
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    for duplicate in &self.repo_duplicates {
+                        ui.strong(duplicate.key.display().to_string());
+                        for location in &duplicate.locations {
+                            ui.horizontal(|ui| {
+                                ui.label(&location.workspace_name)
+                                    .on_hover_text(location.path.display().to_string());
+                                if ui
+                                    .small_button(localizer.t("duplicates_keep_only_here"))
+                                    .clicked()
+                                {
+                                    keep_only =
+                                        Some((duplicate.key.clone(), location.workspace_id));
+                                }
+                            });
+                        }
+                        if ui
+                            .small_button(localizer.t("duplicates_keep_everywhere"))
+                            .clicked()
+                        {
+                            keep_everywhere = Some(duplicate.key.clone());
+                        }
+                        ui.separator();
+                    }
+                });
+        });
+
+        if let Some((key, workspace_id)) = keep_only {
+            self.keep_duplicate_only_in(&key, workspace_id);
+        }
+        if let Some(key) = keep_everywhere {
+            self.keep_duplicate_everywhere(key);
+        }
+        self.show_duplicates_report = open;
+    }
+
+    fn render_dashboard_window(&mut self, ctx: &egui::Context) {
+        let Some(stats) = &self.dashboard else {
+            return;
+        };
+
+        let mut open = true;
+        let mut refresh = false;
+        let mut fetch_all = false;
+        let localizer = &self.localizer;
+        let repo_name = |path: &PathBuf| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string())
+        };
+        egui::Window::new(localizer.t("dashboard"))
+            .id(egui::Id::new("dashboard_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("dashboard_stats")
+                    .num_columns(2)
+                    .spacing([24.0, 4.0])
+                    .show(ui, |ui| {
+                        let rows = [
+                            ("dashboard_total_repos", stats.total_repos),
+                            ("dashboard_total_behind", stats.total_behind),
+                            ("dashboard_total_ahead", stats.total_ahead),
+                            ("dashboard_with_conflicts", stats.with_conflicts),
+                            ("dashboard_with_errors", stats.with_errors),
+                            ("dashboard_without_upstream", stats.without_upstream),
+                        ];
+                        for (key, value) in rows {
+                            ui.label(localizer.t(key));
+                            ui.strong(value.to_string());
+                            ui.end_row();
+                        }
+
+                        ui.label(localizer.t("dashboard_most_stale"));
+                        match &stats.most_stale {
+                            Some((path, time)) => {
+                                let when = std::time::UNIX_EPOCH
+                                    + std::time::Duration::from_secs((*time).max(0) as u64);
+                                ui.label(format!(
+                                    "{} ({})",
+                                    repo_name(path),
+                                    format_relative_time(when)
+                                ))
+                                .on_hover_text(path.display().to_string());
+                            }
+                            None => {
+                                ui.weak("—");
+                            }
+                        }
+                        ui.end_row();
+
+                        ui.label(localizer.t("dashboard_most_active"));
+                        match &stats.most_active {
+                            Some((path, commits)) => {
+                                ui.label(localizer.tf(
+                                    "dashboard_most_active_value",
+                                    &[&repo_name(path), &commits.to_string()],
+                                ))
+                                .on_hover_text(path.display().to_string());
+                            }
+                            None => {
+                                ui.weak("—");
+                            }
+                        }
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.label(localizer.t("dashboard_workspace_sizes"));
+                let largest = stats.largest_workspace().max(1) as f32;
+                for size in &stats.workspace_sizes {
+                    ui.horizontal(|ui| {
+                        ui.add_sized([120.0, 18.0], egui::Label::new(&size.name).truncate(true));
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(200.0, 14.0), egui::Sense::hover());
+                        let width = rect.width() * size.repos as f32 / largest;
+                        let bar =
+                            egui::Rect::from_min_size(rect.min, egui::vec2(width, rect.height()));
+                        let color = size
+                            .color
+                            .map(|[r, g, b]| egui::Color32::from_rgb(r, g, b))
+                            .unwrap_or(egui::Color32::from_rgb(80, 140, 220));
+                        ui.painter()
+                            .rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+                        ui.painter().rect_filled(bar, 2.0, color);
+                        ui.label(size.repos.to_string());
+                    });
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(localizer.t("dashboard_fetch_all")).clicked() {
+                        fetch_all = true;
+                    }
+                    if ui.button(localizer.t("refresh")).clicked() {
+                        refresh = true;
+                    }
+                });
+            });
+
+        if !open {
+            self.dashboard = None;
+        } else if fetch_all {
+            self.request_bulk(app::BulkRequest::FetchAllWorkspaces);
+        } else if refresh {
+            self.open_dashboard();
+        }
+    }
+
+    fn render_prune_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.prune_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut run = false;
+        let mut cancel = false;
+        let localizer = &self.localizer;
+        egui::Window::new(localizer.t("prune_merged_title"))
+            .id(egui::Id::new("prune_merged_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                if dialog.is_scanning() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(localizer.t("prune_scanning"));
+                    });
+                } else if dialog.repos.is_empty() {
+                    ui.weak(localizer.t("prune_nothing_found"));
+                }
+                if dialog.skipped_dirty > 0 {
+                    ui.weak(
+                        localizer.tf("prune_skipped_dirty", &[&dialog.skipped_dirty.to_string()]),
+                    );
+                }
+                if !dialog.scan_failures.is_empty() {
+                    ui.colored_label(
+                        egui::Color32::LIGHT_RED,
+                        localizer.tf(
+                            "prune_scan_failures",
+                            &[&dialog.scan_failures.len().to_string()],
+                        ),
+                    );
+                    for (repo_name, error) in &dialog.scan_failures {
+                        ui.label(format!("{}: {}", repo_name, error.trim()));
+                    }
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for repo in &mut dialog.repos {
+                            egui::CollapsingHeader::new(localizer.tf(
+                                "prune_repo_header",
+                                &[
+                                    &repo.repo_name,
+                                    &repo.branches.len().to_string(),
+                                    &repo.default_branch,
+                                ],
+                            ))
+                            .id_source(&repo.repo_path)
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for branch in &mut repo.branches {
+                                    ui.checkbox(&mut branch.selected, &branch.name);
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+                let selected = dialog.selected_count();
+                ui.horizontal(|ui| {
+                    run = ui
+                        .add_enabled(
+                            selected > 0 && !dialog.is_scanning(),
+                            egui::Button::new(localizer.tf("prune_run", &[&selected.to_string()])),
+                        )
+                        .clicked();
+                    cancel = ui.button(localizer.t("cancel")).clicked();
+                });
+            });
+
+        if run {
+            self.run_branch_prune();
+        } else if cancel || !open {
+            self.prune_dialog = None;
+        }
+    }
+
+    fn render_scan_split_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &self.scan_split_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut add_all = false;
+        let mut split = false;
+        egui::Window::new(self.localizer.t("scan_split_title"))
+            .id(egui::Id::new("scan_split_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(self.localizer.tf(
+                    "scan_split_hint",
+                    &[
+                        &dialog.repos.len().to_string(),
+                        &dialog.groups.len().to_string(),
+                    ],
+                ));
+                ui.add_space(4.0);
+                add_all = ui.button(self.localizer.t("scan_add_all")).clicked();
+                split = ui
+                    .button(
+                        self.localizer
+                            .tf("scan_split_per_folder", &[&dialog.summary()]),
+                    )
+                    .clicked();
+            });
+
+        if split {
+            if let Some(dialog) = self.scan_split_dialog.take() {
+                self.split_into_workspaces(dialog.groups);
+            }
+        } else if add_all || !open {
+            // Закрытие окна — то же, что прежнее поведение без вопроса:
+            // большой результат сначала попадает в список для проверки
+            if let Some(dialog) = self.scan_split_dialog.take() {
+                self.review_found_repositories(dialog.repos);
+            }
+        }
+    }
+
+    fn render_scan_preview_window(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &mut self.scan_preview else {
+            return;
+        };
+
+        let mut open = true;
+        let mut add = false;
+        let mut cancel = false;
+        let localizer = &self.localizer;
+        egui::Window::new(
+            localizer.tf("scan_preview_title", &[&preview.entries.len().to_string()]),
+        )
+        .id(egui::Id::new("scan_preview_window"))
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut preview.filter)
+                        .hint_text(localizer.t("scan_preview_filter")),
+                );
+                if ui.button(localizer.t("scan_preview_select_all")).clicked() {
+                    preview.select_visible(true);
+                }
+                if ui.button(localizer.t("scan_preview_select_none")).clicked() {
+                    preview.select_visible(false);
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    for idx in 0..preview.entries.len() {
+                        if !preview.is_visible(&preview.entries[idx]) {
+                            continue;
+                        }
+                        let entry = &mut preview.entries[idx];
+                        ui.horizontal(|ui| {
+                            let path = entry.path.display().to_string();
+                            if entry.already_added {
+                                ui.add_enabled(
+                                    false,
+                                    egui::Checkbox::new(&mut entry.selected, path),
+                                );
+                                ui.weak(localizer.t("scan_preview_already_added"));
+                            } else {
+                                ui.checkbox(&mut entry.selected, path);
+                            }
+                            if let Some(branch) = &entry.branch {
+                                ui.label(
+                                    egui::RichText::new(branch)
+                                        .small()
+                                        .color(egui::Color32::GRAY),
+                                );
+                            }
+                        });
+                    }
+                });
+
+            ui.separator();
+            let selected = preview.selected_count();
+            ui.horizontal(|ui| {
+                add = ui
+                    .add_enabled(
+                        selected > 0,
+                        egui::Button::new(
+                            localizer.tf("scan_preview_add", &[&selected.to_string()]),
+                        ),
+                    )
+                    .clicked();
+                cancel = ui.button(localizer.t("cancel")).clicked();
+            });
+        });
+
+        if add {
+            if let Some(preview) = self.scan_preview.take() {
+                self.add_found_repositories(preview.selected_paths());
+            }
+        } else if cancel || !open {
+            self.scan_preview = None;
+        }
+    }
+
+    fn render_repath_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.repath_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+        egui::Window::new(self.localizer.t("repath_folder"))
+            .id(egui::Id::new("repath_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(self.localizer.tf(
+                    "repath_old_location",
+                    &[&dialog.old_prefix.display().to_string()],
+                ));
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("repath_new_location"));
+                    ui.text_edit_singleline(&mut dialog.new_prefix);
+                });
+                ui.horizontal(|ui| {
+                    apply = ui.button(self.localizer.t("apply")).clicked();
+                    cancel = ui.button(self.localizer.t("cancel")).clicked();
+                });
+            });
+
+        if apply {
+            self.apply_repath();
+        } else if cancel || !open {
+            self.repath_dialog = None;
+        }
+    }
+
+    fn render_row_element(
+        &mut self,
+        ui: &mut egui::Ui,
+        repo: &RepositoryState,
+        element: RowElement,
+    ) {
+        match element {
+            RowElement::SyncSpinner => {
+                if self.runtime.is_syncing(&repo.path) {
+                    if is_queued(&repo.path) {
+                        // Ждёт свободного слота пула: можно отменить до старта
+                        if ui
+                            .small_button("⏳")
+                            .on_hover_text(self.localizer.t("operation_queued"))
+                            .clicked()
+                        {
+                            cancel_queued(&repo.path);
+                        }
+                        ui.ctx()
+                            .request_repaint_after(std::time::Duration::from_millis(250));
+                    } else {
+                        let spinner = ui.spinner();
+                        if self.runtime.is_pushing(&repo.path) && repo.git_info.has_push_hooks {
+                            spinner.on_hover_text(self.localizer.t("running_pre_push_hooks"));
+                        }
+                    }
+                }
+            }
+            RowElement::PullBadge => {
+                if repo.git_info.behind > 0 {
+                    let pull_button =
+                        Button::icon_text(IconType::Pull, format!("{}", repo.git_info.behind))
+                            .show(ui, &mut self.icon_manager);
+                    if pull_button.clicked() {
+                        self.request_bulk(app::BulkRequest::Pull {
+                            repo_path: repo.path.clone(),
+                            repo_name: repo.name.clone(),
+                        });
+                    }
+                    pull_button.on_hover_text(
+                        self.localizer
+                            .tf("pull_commits", &[&repo.git_info.behind.to_string()]),
+                    );
+                }
+            }
+            RowElement::PushBadge => {
+                let pr_url = self
+                    .pr_links
+                    .get(&repo.path)
+                    .filter(|(branch, _)| repo.git_info.current_branch.as_ref() == Some(branch))
+                    .map(|(_, url)| url.clone());
+                if let Some(url) = pr_url.filter(|_| repo.git_info.ahead == 0) {
+                    if ui
+                        .small_button(self.localizer.t("open_pr"))
+                        .on_hover_text(&url)
+                        .clicked()
+                    {
+                        opener::open(&url).ok();
+                    }
+                } else if repo.git_info.ahead > 0 {
+                    let push_button =
+                        Button::icon_text(IconType::Push, format!("{}", repo.git_info.ahead))
+                            .show(ui, &mut self.icon_manager);
+                    if push_button.clicked() {
+                        self.start_push(repo.path.clone(), &repo.name, false);
+                    }
+                    push_button.on_hover_text(
+                        self.localizer
+                            .tf("push_commits", &[&repo.git_info.ahead.to_string()]),
+                    );
+                }
+            }
+            RowElement::ErrorIndicator => {
+                if let Some(error) = self.runtime.errors().get(&repo.path).cloned() {
+                    self.render_error_indicator(ui, repo, &error);
+                }
+            }
+            RowElement::ChangesIndicator => {
+                if !self.runtime.errors().contains(&repo.path) && repo.git_info.has_changes {
+                    let changes_indicator = ui.add(
+                        egui::Label::new(egui::RichText::new("!").color(egui::Color32::YELLOW))
+                            .sense(egui::Sense::click()),
+                    );
+                    if changes_indicator.hovered() {
+                        self.request_dirty_noise(&repo.path);
+                    }
+                    let noise = self.dirty_noise.get(&repo.path).cloned().flatten();
+                    let mut tooltip = match &noise {
+                        Some(DirtyNoise::LineEndings(_)) => self.localizer.t("line_ending_noise"),
+                        Some(DirtyNoise::CaseCollisions(_)) => {
+                            self.localizer.t("case_collision_noise")
+                        }
+                        _ => self.localizer.t("has_changes"),
+                    };
+                    if repo.git_info.ignored_count > 0 {
+                        tooltip.push('\n');
+                        tooltip.push_str(
+                            &self
+                                .localizer
+                                .tf("ignored_count", &[&repo.git_info.ignored_count.to_string()]),
+                        );
+                    }
+                    let changes_indicator = changes_indicator.on_hover_text(tooltip);
+                    if matches!(
+                        noise,
+                        Some(DirtyNoise::LineEndings(_) | DirtyNoise::CaseCollisions(_))
+                    ) {
+                        changes_indicator.context_menu(|ui| {
+                            if ui.button(self.localizer.t("show_affected_files")).clicked() {
+                                self.open_dirty_noise(&repo.path, repo.name.clone());
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                }
+            }
+            RowElement::BranchDelta => {
+                if repo.git_info.upstream_gone {
+                    egui::Frame::none()
+                        .fill(egui::Color32::from_gray(60))
+                        .rounding(8.0)
+                        .inner_margin(egui::Margin::symmetric(6.0, 1.0))
+                        .show(ui, |ui| {
+                            ui.label(
+                                egui::RichText::new(self.localizer.t("upstream_gone_chip"))
+                                    .small()
+                                    .color(egui::Color32::LIGHT_GRAY),
+                            );
+                        })
+                        .response
+                        .on_hover_text(self.localizer.t("upstream_gone_tooltip"));
+                } else if repo.git_info.has_upstream {
+                    let fetched = match repo.last_fetch {
+                        Some(time) => self
+                            .localizer
+                            .tf("fetched_ago", &[&format_relative_time(time)]),
+                        None => self.localizer.t("never_fetched"),
+                    };
+                    ui.colored_label(
+                        egui::Color32::GRAY,
+                        format!("↑{} ↓{}", repo.git_info.ahead, repo.git_info.behind),
+                    )
+                    .on_hover_text(egui::RichText::new(fetched).weak());
+                }
+            }
+            RowElement::LastCommitAge => {
+                if let Some(time) = repo.git_info.last_commit_time {
+                    ui.colored_label(egui::Color32::DARK_GRAY, commit_age(time));
+                }
+            }
+            RowElement::LatestTag => {
+                let describe = repo.git_info.describe_string.as_deref();
+                match (repo.git_info.tags.first(), describe) {
+                    (Some(tag), describe) => {
+                        let badge = egui::Frame::none()
+                            .fill(egui::Color32::from_gray(50))
+                            .rounding(8.0)
+                            .inner_margin(egui::Margin::symmetric(6.0, 1.0))
+                            .show(ui, |ui| {
+                                ui.label(egui::RichText::new(tag).small().monospace());
+                            })
+                            .response;
+                        if let Some(describe) = describe {
+                            badge.on_hover_text(describe);
+                        }
+                    }
+                    // Тегов нет: describe — это просто короткий хеш
+                    (None, Some(hash)) => {
+                        ui.label(
+                            egui::RichText::new(hash)
+                                .small()
+                                .monospace()
+                                .color(egui::Color32::GRAY),
+                        )
+                        .on_hover_text(self.localizer.t("describe_no_tags"));
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    fn render_error_indicator(
+        &mut self,
+        ui: &mut egui::Ui,
+        repo: &RepositoryState,
+        error: &app::RepoError,
+    ) {
+        let summary = error.summary(200);
+        let dubious_ownership = error.kind == GitErrorKind::DubiousOwnership;
+        let (badge, color, hover) = if dubious_ownership {
+            (
+                "🛡",
+                egui::Color32::YELLOW,
+                self.localizer.t("dubious_ownership_tooltip"),
+            )
+        } else if error.kind == GitErrorKind::ProtectedBranch {
+            (
+                "!",
+                egui::Color32::RED,
+                self.localizer.t("protected_branch_explanation"),
+            )
+        } else {
+            ("!", egui::Color32::RED, summary.clone())
+        };
+        let indicator = ui
+            .add(
+                egui::Label::new(egui::RichText::new(badge).color(color))
+                    .sense(egui::Sense::click()),
+            )
+            .on_hover_text(hover);
+
+        let popup_id = ui.make_persistent_id(("repo_error_popup", &repo.path));
+        if indicator.clicked() {
+            ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+        }
+
+        egui::popup::popup_below_widget(ui, popup_id, &indicator, |ui| {
+            ui.set_min_width(300.0);
+            if error.kind == GitErrorKind::ProtectedBranch {
+                ui.label(
+                    egui::RichText::new(self.localizer.t("protected_branch_explanation"))
+                        .color(egui::Color32::LIGHT_RED),
+                );
+                let pr_url = repo
+                    .git_info
+                    .remote_url
+                    .as_deref()
+                    .and_then(parse_remote_url)
+                    .zip(repo.git_info.current_branch.as_deref())
+                    .map(|(hosted, branch)| hosted.new_pull_request_url(branch));
+                if let Some(url) = pr_url {
+                    if ui.button(self.localizer.t("open_pr_page")).clicked() {
+                        opener::open(&url).ok();
+                    }
+                }
+                ui.separator();
+            }
+            ui.label(egui::RichText::new(&summary).color(egui::Color32::LIGHT_RED));
+
+            if let Ok(duration) = error.occurred_at.elapsed() {
+                ui.colored_label(
+                    egui::Color32::DARK_GRAY,
+                    self.localizer
+                        .tf("error_occurred_ago", &[&format_elapsed(duration)]),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(self.localizer.t("copy_full_error")).clicked() {
+                    ui.output_mut(|o| o.copied_text = error.message.clone());
+                }
+
+                if error.retrying {
+                    ui.spinner();
+                    ui.label(self.localizer.t("retrying"));
+                } else if dubious_ownership {
+                    if ui.button(self.localizer.t("mark_safe_directory")).clicked() {
+                        self.pending_confirmation = Some(app::ConfirmAction::MarkSafeDirectory {
+                            repo_path: repo.path.clone(),
+                            repo_name: repo.name.clone(),
+                        });
+                        ui.memory_mut(|mem| mem.close_popup());
+                    }
+                } else if ui.button(self.localizer.t("retry")).clicked() {
+                    self.runtime.begin_retry(&repo.path);
+                    if let Some(tx) = &self.app_sender {
+                        refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+                    }
+                }
+            });
+        });
+    }
+
+    fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
+        // Пока открыт переключатель, клавиши принадлежат только ему
+        if self.show_workspace_switcher {
+            self.handle_workspace_switcher_keys(ctx);
+            return;
+        }
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab))
+            && !self.config.workspaces.is_empty()
+        {
+            self.workspace_switcher_selection =
+                (self.active_workspace_idx() + 1) % self.config.workspaces.len();
+            self.show_workspace_switcher = true;
+        }
+    }
+
+    fn handle_workspace_switcher_keys(&mut self, ctx: &egui::Context) {
+        let count = self.config.workspaces.len();
+        if count == 0 {
+            self.show_workspace_switcher = false;
+            return;
+        }
+
+        let (down, up, confirm, close) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)
+                    || i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+
+        let selection = self.workspace_switcher_selection.min(count - 1);
+        if down {
+            self.workspace_switcher_selection = (selection + 1) % count;
+        } else if up {
+            self.workspace_switcher_selection = (selection + count - 1) % count;
+        }
+
+        if confirm {
+            self.show_workspace_switcher = false;
+            if selection != self.active_workspace_idx() {
+                self.switch_to_workspace(selection);
+            }
+        } else if close {
+            self.show_workspace_switcher = false;
+        }
+    }
+
+    fn render_workspace_switcher(&mut self, ctx: &egui::Context) {
+        let mut switch_to = None;
+        let response = egui::Window::new(self.localizer.t("quick_switch"))
+            .id(egui::Id::new("workspace_switcher"))
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .min_width(280.0)
+            .show(ctx, |ui| {
+                for (idx, workspace) in self.config.workspaces.iter().enumerate() {
+                    let (dirty, behind) = workspace.status_counts();
+                    ui.horizontal(|ui| {
+                        let label = ui.selectable_label(
+                            idx == self.workspace_switcher_selection,
+                            format!("{} ({})", workspace.name, workspace.repository_count()),
+                        );
+                        if idx == self.workspace_switcher_selection {
+                            label.scroll_to_me(None);
+                        }
+                        if label.clicked() {
+                            switch_to = Some(idx);
+                        }
+
+                        if dirty > 0 {
+                            ui.colored_label(egui::Color32::YELLOW, format!("!{}", dirty))
+                                .on_hover_text(
+                                    self.localizer.tf("switcher_dirty", &[&dirty.to_string()]),
+                                );
+                        }
+                        if behind > 0 {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(100, 150, 255),
+                                format!("↓{}", behind),
+                            )
+                            .on_hover_text(
+                                self.localizer.tf("switcher_behind", &[&behind.to_string()]),
+                            );
+                        }
+                    });
+                }
+            });
+
+        if let Some(idx) = switch_to {
+            self.show_workspace_switcher = false;
+            if idx != self.active_workspace_idx() {
+                self.switch_to_workspace(idx);
+            }
+            return;
+        }
+
+        // Клик мимо окна закрывает переключатель
+        let clicked_outside = response.is_some_and(|inner| {
+            ctx.input(|i| {
+                i.pointer.any_click()
+                    && i.pointer
+                        .interact_pos()
+                        .is_some_and(|pos| !inner.response.rect.contains(pos))
+            })
+        });
+        if clicked_outside {
+            self.show_workspace_switcher = false;
+        }
+    }
+
+    fn render_patch_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.patch_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply = false;
+        let mut resolve_am = None;
+        egui::Window::new(self.localizer.tf("apply_patch_to", &[&dialog.repo_name]))
+            .id(egui::Id::new("patch_window"))
+            .open(&mut open)
+            .resizable(false)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                if dialog.am_stopped {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 180, 0),
+                        self.localizer.t("am_stopped"),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button(self.localizer.t("am_continue")).clicked() {
+                            resolve_am = Some(true);
+                        }
+                        if ui.button(self.localizer.t("am_abort")).clicked() {
+                            resolve_am = Some(false);
+                        }
+                    });
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("file"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut dialog.patch_path)
+                            .hint_text(self.localizer.t("patch_file_hint"))
+                            .desired_width(260.0),
+                    );
+                });
+                ui.radio_value(
+                    &mut dialog.create_commits,
+                    false,
+                    self.localizer.t("patch_mode_apply"),
+                );
+                ui.radio_value(
+                    &mut dialog.create_commits,
+                    true,
+                    self.localizer.t("patch_mode_am"),
+                );
+
+                ui.separator();
+                let ready = std::path::Path::new(dialog.patch_path.trim()).is_file();
+                if ui
+                    .add_enabled(ready, egui::Button::new(self.localizer.t("apply")))
+                    .clicked()
+                {
+                    apply = true;
+                }
+            });
+
+        if apply {
+            self.apply_patch();
+        } else if let Some(resume) = resolve_am {
+            self.resolve_patch_am(resume);
+        } else if !open {
+            self.patch_dialog = None;
+        }
+    }
+
+    fn render_stale_refs_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.stale_refs else {
+            return;
+        };
+        let repo_path = view.repo_path.clone();
+        let repo_name = view.repo_name.clone();
+        let refs = view.refs.clone();
+
+        let mut open = true;
+        let mut prune = false;
+        egui::Window::new(self.localizer.tf("stale_refs_of", &[&repo_name]))
+            .id(egui::Id::new("stale_refs_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if refs.is_empty() {
+                    ui.label(self.localizer.t("no_stale_refs"));
+                    return;
+                }
+
+                ui.label(
+                    self.localizer
+                        .tf("stale_refs_count", &[&refs.len().to_string()]),
+                );
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for stale_ref in &refs {
+                            ui.monospace(stale_ref);
+                        }
+                    });
+
+                ui.separator();
+                if ui.button(self.localizer.t("prune_now")).clicked() {
+                    prune = true;
+                }
+            });
+
+        if prune {
+            match git_remote_prune(&repo_path) {
+                Ok(_) => {
+                    self.logger.info(
+                        self.localizer
+                            .tf("prune_success", &[&refs.len().to_string(), &repo_name]),
+                    );
+                    if let Some(tx) = &self.app_sender {
+                        refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                    }
+                }
+                Err(e) => self.logger.error(
+                    self.localizer
+                        .tf("prune_error", &[&repo_name, &e.to_string()]),
+                ),
+            }
+            open = false;
+        }
+
+        if !open {
+            self.stale_refs = None;
+        }
+    }
+
+    fn render_terminal_panel(&mut self, ctx: &egui::Context) {
+        let Some(terminal) = &mut self.terminal_pane else {
+            return;
+        };
+        terminal.poll();
+        let exited = terminal.has_exited();
+        let cwd = terminal.cwd.clone();
+
+        let mut close = false;
+        let mut restart = false;
+        egui::TopBottomPanel::bottom("terminal_panel")
+            .resizable(true)
+            .default_height(220.0)
+            .height_range(100.0..=600.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(self.localizer.t("terminal"));
+                    ui.label(egui::RichText::new(cwd.display().to_string()).weak());
+                    if exited {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            self.localizer.t("terminal_exited"),
+                        );
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if Button::icon(IconType::Cross)
+                            .show(ui, &mut self.icon_manager)
+                            .clicked()
+                        {
+                            close = true;
+                        }
+                        if ui.button(self.localizer.t("terminal_restart")).clicked() {
+                            restart = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                let Some(terminal) = &mut self.terminal_pane else {
+                    return;
+                };
+
+                let font = egui::FontId::monospace(12.0);
+                let (char_width, row_height) =
+                    ui.fonts(|f| (f.glyph_width(&font, 'M'), f.row_height(&font)));
+                let area = ui.available_size();
+                terminal.resize(
+                    (area.y / row_height) as u16,
+                    (area.x / char_width.max(1.0)) as u16,
+                );
+
+                let terminal_id = ui.make_persistent_id("terminal_input");
+                let scroll = egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(terminal.output()).font(font.clone()),
+                            )
+                            .wrap(true),
+                        );
+                    });
+
+                // Клик по области передаёт клавиатуру оболочке
+                let response = ui.interact(scroll.inner_rect, terminal_id, egui::Sense::click());
+                if response.clicked() {
+                    response.request_focus();
+                }
+                if response.has_focus() {
+                    ui.memory_mut(|mem| {
+                        mem.set_focus_lock_filter(
+                            terminal_id,
+                            egui::EventFilter {
+                                tab: true,
+                                horizontal_arrows: true,
+                                vertical_arrows: true,
+                                escape: true,
+                            },
+                        )
+                    });
+                    ui.painter()
+                        .rect_stroke(scroll.inner_rect, 2.0, ui.visuals().selection.stroke);
+
+                    let events = ui.input(|i| i.events.clone());
+                    for event in &events {
+                        if let Some(bytes) = event_bytes(event) {
+                            terminal.send_input(&bytes);
+                        }
+                    }
+                }
+            });
+
+        if close {
+            self.terminal_pane = None;
+        } else if restart {
+            self.open_terminal(&cwd, ctx);
+        }
+    }
+
+    fn render_audit_history_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.audit_history else {
+            return;
+        };
+
+        let mut open = true;
+        let mut export = false;
+        let all_repos = self.localizer.t("audit_all_repos");
+        let all_operations = self.localizer.t("audit_all_operations");
+        egui::Window::new(self.localizer.t("operation_history"))
+            .id(egui::Id::new("audit_history_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("audit_repo_filter")
+                        .selected_text(
+                            view.repo_filter
+                                .as_ref()
+                                .map(|p| p.display().to_string())
+                                .unwrap_or_else(|| all_repos.clone()),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut view.repo_filter, None, &all_repos);
+                            for path in view.repo_paths() {
+                                let label = path.display().to_string();
+                                ui.selectable_value(&mut view.repo_filter, Some(path), label);
+                            }
+                        });
+
+                    egui::ComboBox::from_id_source("audit_operation_filter")
+                        .selected_text(
+                            view.operation_filter
+                                .map(|op| self.localizer.t(op.label_key()))
+                                .unwrap_or_else(|| all_operations.clone()),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut view.operation_filter, None, &all_operations);
+                            for op in AuditOperation::ALL {
+                                ui.selectable_value(
+                                    &mut view.operation_filter,
+                                    Some(op),
+                                    self.localizer.t(op.label_key()),
+                                );
+                            }
+                        });
+                });
+
+                let entries = view.filtered();
+                if entries.is_empty() {
+                    ui.label(self.localizer.t("audit_empty"));
+                } else {
+                    egui::ScrollArea::vertical()
+                        .max_height(400.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("audit_history_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for entry in entries {
+                                        let time = std::time::UNIX_EPOCH
+                                            + std::time::Duration::from_secs(entry.timestamp);
+                                        ui.label(format_relative_time(time))
+                                            .on_hover_text(entry.timestamp.to_string());
+                                        ui.label(self.localizer.t(entry.operation.label_key()));
+                                        ui.label(
+                                            entry
+                                                .repo_path
+                                                .file_name()
+                                                .unwrap_or_default()
+                                                .to_string_lossy(),
+                                        )
+                                        .on_hover_text(entry.repo_path.display().to_string());
+                                        ui.monospace(entry.branch.as_deref().unwrap_or("-"));
+                                        match &entry.error {
+                                            None => {
+                                                ui.colored_label(egui::Color32::LIGHT_GREEN, "✔");
+                                            }
+                                            Some(error) => {
+                                                ui.colored_label(egui::Color32::LIGHT_RED, "✖")
+                                                    .on_hover_text(error);
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut view.export_path);
+                    if ui.button(self.localizer.t("audit_export")).clicked() {
+                        export = true;
+                    }
+                });
+            });
+
+        if export {
+            self.export_audit_history();
+        }
+        if !open {
+            self.audit_history = None;
+        }
+    }
+
+    fn render_branches_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.branches_view else {
+            return;
+        };
+
+        let mut open = true;
+        let mut switch_to = None;
+        let mut delete = None;
+        let mut delete_merged = false;
+        let mut protected_changed = false;
+        egui::Window::new(self.localizer.tf("branches_of", &[&view.repo_name]))
+            .id(egui::Id::new("branches_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("branches_grid")
+                            .num_columns(6)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for branch in &view.branches {
+                                    if branch.is_current {
+                                        ui.colored_label(egui::Color32::LIGHT_GREEN, "●")
+                                            .on_hover_text(self.localizer.t("current_branch"));
+                                    } else {
+                                        ui.label("");
+                                    }
+                                    ui.label(egui::RichText::new(&branch.name).monospace());
+
+                                    match &branch.upstream {
+                                        Some(upstream) if branch.upstream_gone => {
+                                            ui.colored_label(egui::Color32::LIGHT_RED, upstream)
+                                                .on_hover_text(self.localizer.t("upstream_gone"));
+                                        }
+                                        Some(upstream) => {
+                                            ui.label(upstream);
+                                        }
+                                        None => {
+                                            ui.colored_label(
+                                                egui::Color32::GRAY,
+                                                self.localizer.t("no_upstream"),
+                                            );
+                                        }
+                                    }
+
+                                    let tracking = format!("↑{} ↓{}", branch.ahead, branch.behind);
+                                    if branch.ahead > 0 {
+                                        ui.colored_label(egui::Color32::YELLOW, tracking)
+                                            .on_hover_text(self.localizer.t("branch_unpushed"));
+                                    } else {
+                                        ui.label(tracking);
+                                    }
+
+                                    ui.label(
+                                        branch
+                                            .last_commit_time
+                                            .map(|secs| {
+                                                format_relative_time(
+                                                    std::time::UNIX_EPOCH
+                                                        + std::time::Duration::from_secs(
+                                                            secs.max(0) as u64,
+                                                        ),
+                                                )
+                                            })
+                                            .unwrap_or_default(),
+                                    );
+
+                                    ui.horizontal(|ui| {
+                                        ui.add_enabled_ui(!branch.is_current, |ui| {
+                                            if ui.small_button(self.localizer.t("switch")).clicked()
+                                            {
+                                                switch_to = Some(branch.name.clone());
+                                            }
+                                            if ui.small_button(self.localizer.t("delete")).clicked()
+                                            {
+                                                delete = Some(branch.name.clone());
+                                            }
+                                        });
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(self.localizer.t("protected_branches"));
+                    protected_changed = ui
+                        .text_edit_singleline(&mut view.protected_input)
+                        .lost_focus();
+                });
+                let default_branch = view.default_branch.clone().unwrap_or_default();
+                if ui
+                    .add_enabled(
+                        view.default_branch.is_some(),
+                        egui::Button::new(
+                            self.localizer
+                                .tf("delete_merged_branches", &[&default_branch]),
+                        ),
+                    )
+                    .clicked()
+                {
+                    delete_merged = true;
+                }
+            });
+
+        let repo_path = view.repo_path.clone();
+        if protected_changed {
+            self.apply_protected_branches_input();
+        }
+        if let Some(branch) = switch_to {
+            self.start_branch_switch(repo_path, branch);
+        } else if let Some(branch) = delete {
+            self.request_delete_branch(branch);
+        } else if delete_merged {
+            self.request_delete_merged_branches();
+        }
+        if !open {
+            self.branches_view = None;
+        }
+    }
+
+    fn render_remotes_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.remotes else {
+            return;
+        };
+
+        let mut open = true;
+        let mut confirm = false;
+        let mut mirror_to = None;
+        egui::Window::new(self.localizer.tf("remotes_of", &[&view.repo_name]))
+            .id(egui::Id::new("remotes_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                if view.remotes.is_empty() {
+                    ui.label(self.localizer.t("no_remotes"));
+                    return;
+                }
+
+                egui::Grid::new("remotes_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (index, remote) in view.remotes.iter().enumerate() {
+                            match &mut view.editing {
+                                Some((editing, name)) if *editing == index => {
+                                    let response = ui
+                                        .add(egui::TextEdit::singleline(name).desired_width(140.0));
+                                    if response.lost_focus() {
+                                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                            confirm = true;
+                                        } else {
+                                            view.editing = None;
+                                        }
+                                    } else {
+                                        response.request_focus();
+                                    }
+                                }
+                                _ => {
+                                    if ui
+                                        .add(
+                                            egui::Label::new(
+                                                egui::RichText::new(&remote.name).strong(),
+                                            )
+                                            .sense(egui::Sense::click()),
+                                        )
+                                        .on_hover_text(self.localizer.t("click_to_rename"))
+                                        .clicked()
+                                    {
+                                        view.editing = Some((index, remote.name.clone()));
+                                    }
+                                }
+                            }
+                            ui.label(egui::RichText::new(&remote.url).monospace());
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                egui::CollapsingHeader::new(self.localizer.t("remotes_advanced"))
+                    .id_source("remotes_advanced")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.colored_label(
+                            egui::Color32::LIGHT_RED,
+                            self.localizer.t("mirror_push_warning"),
+                        );
+                        for remote in &view.remotes {
+                            if ui
+                                .button(self.localizer.tf("mirror_push_to", &[&remote.name]))
+                                .clicked()
+                            {
+                                mirror_to = Some(remote.name.clone());
+                            }
+                        }
+                    });
+            });
+
+        if confirm {
+            self.confirm_remote_rename();
+        }
+        if let (Some(remote), Some(view)) = (mirror_to, &self.remotes) {
+            self.confirmation_input.clear();
+            self.pending_confirmation = Some(app::ConfirmAction::PushMirror {
+                repo_path: view.repo_path.clone(),
+                repo_name: view.repo_name.clone(),
+                remote,
+            });
+        }
+        if !open {
+            self.remotes = None;
+        }
+    }
+
+    fn render_monorepo_services_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.monorepo_services else {
+            return;
+        };
+
+        let mut open = true;
+        let mut add = false;
+        egui::Window::new(
+            self.localizer
+                .tf("monorepo_services_of", &[&view.repo_name]),
+        )
+        .id(egui::Id::new("monorepo_services_window"))
+        .open(&mut open)
+        .resizable(true)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.label(self.localizer.t("monorepo_services_hint"));
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for (service, checked) in &mut view.services {
+                        let relative = service.strip_prefix(&view.repo_path).unwrap_or(service);
+                        ui.checkbox(checked, relative.display().to_string());
+                    }
+                });
+            ui.separator();
+            let any_checked = view.services.iter().any(|(_, checked)| *checked);
+            if ui
+                .add_enabled(
+                    any_checked,
+                    egui::Button::new(self.localizer.t("add_monorepo_services")),
+                )
+                .clicked()
+            {
+                add = true;
+            }
+        });
+
+        if add {
+            self.add_monorepo_services();
+        } else if !open {
+            self.monorepo_services = None;
+        }
+    }
+
+    fn render_ignored_files_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.ignored_files else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(self.localizer.tf("ignored_files_of", &[&view.repo_name]))
+            .id(egui::Id::new("ignored_files_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                if view.files.is_empty() {
+                    ui.label(self.localizer.t("no_ignored_files"));
+                    return;
+                }
+                ui.label(
+                    self.localizer
+                        .tf("ignored_count", &[&view.files.len().to_string()]),
+                );
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("ignored_files_grid")
+                            .striped(true)
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                ui.strong(self.localizer.t("ignored_path"));
+                                ui.strong(self.localizer.t("gitignore_rule"));
+                                ui.end_row();
+
+                                for file in &view.files {
+                                    ui.monospace(&file.path);
+                                    match &file.rule {
+                                        Some(rule) => ui.monospace(rule),
+                                        None => ui.weak("—"),
+                                    };
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if !open {
+            self.ignored_files = None;
+        }
+    }
+
+    fn render_dirty_noise_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.dirty_noise_view else {
+            return;
+        };
+        let (hint, files) = match &view.noise {
+            DirtyNoise::LineEndings(files) => ("line_ending_noise", files.as_slice()),
+            DirtyNoise::CaseCollisions(files) => ("case_collision_noise", files.as_slice()),
+            DirtyNoise::Genuine => ("has_changes", [].as_slice()),
+        };
+
+        let mut open = true;
+        egui::Window::new(self.localizer.tf("affected_files_of", &[&view.repo_name]))
+            .id(egui::Id::new("dirty_noise_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.label(self.localizer.t(hint));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for file in files {
+                            ui.monospace(file);
+                        }
+                    });
+            });
+
+        if !open {
+            self.dirty_noise_view = None;
+        }
+    }
+
+    fn render_contributors_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.contributors else {
+            return;
+        };
+        let key = (view.repo_path.clone(), contributor_since(view.days));
+        let contributors = self.contributor_cache.get(&key);
+
+        let mut open = true;
+        let mut range = None;
+        egui::Window::new(self.localizer.tf("contributors_of", &[&view.repo_name]))
+            .id(egui::Id::new("contributors_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for days in CONTRIBUTOR_RANGES {
+                        let label = self.localizer.tf("last_days", &[&days.to_string()]);
+                        if ui.selectable_label(view.days == days, label).clicked()
+                            && view.days != days
+                        {
+                            range = Some(days);
+                        }
+                    }
+                });
+                ui.separator();
+
+                let Some(contributors) = contributors else {
+                    ui.spinner();
+                    return;
+                };
+                if contributors.is_empty() {
+                    ui.label(self.localizer.t("no_contributors"));
+                    return;
+                }
+
+                let max = contributors
+                    .iter()
+                    .map(|entry| entry.commit_count)
+                    .max()
+                    .unwrap_or(1)
+                    .max(1) as f32;
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("contributors_grid")
+                            .striped(true)
+                            .num_columns(3)
+                            .show(ui, |ui| {
+                                for entry in contributors {
+                                    ui.label(&entry.author);
+                                    let (rect, _) = ui.allocate_exact_size(
+                                        egui::vec2(200.0, 12.0),
+                                        egui::Sense::hover(),
+                                    );
+                                    let width = rect.width() * entry.commit_count as f32 / max;
+                                    ui.painter().rect_filled(
+                                        egui::Rect::from_min_size(
+                                            rect.min,
+                                            egui::vec2(width.max(1.0), rect.height()),
+                                        ),
+                                        2.0,
+                                        egui::Color32::from_rgb(100, 150, 255),
+                                    );
+                                    ui.monospace(entry.commit_count.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(days) = range {
+            self.set_contributors_range(days);
+        }
+        if !open {
+            self.contributors = None;
+        }
+    }
+
+    fn render_env_overrides_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.env_overrides else {
+            return;
+        };
+
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new(self.localizer.tf("env_overrides_of", &[&view.repo_name]))
+            .id(egui::Id::new("env_overrides_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label(self.localizer.t("env_overrides_hint"));
+                ui.separator();
+
+                let mut remove = None;
+                egui::Grid::new("env_overrides_grid")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        ui.strong(self.localizer.t("env_name"));
+                        ui.strong(self.localizer.t("env_value"));
+                        ui.end_row();
+
+                        for (idx, (name, value)) in view.rows.iter_mut().enumerate() {
+                            ui.add(egui::TextEdit::singleline(name).desired_width(160.0));
+                            ui.add(
+                                egui::TextEdit::singleline(value)
+                                    .password(is_sensitive_env_name(name))
+                                    .desired_width(220.0),
+                            );
+                            if ui
+                                .small_button("🗑")
+                                .on_hover_text(self.localizer.t("remove"))
+                                .clicked()
+                            {
+                                remove = Some(idx);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(idx) = remove {
+                    view.rows.remove(idx);
+                }
+
+                if ui.button(self.localizer.t("env_add_variable")).clicked() {
+                    view.rows.push((String::new(), String::new()));
+                }
+                ui.separator();
+                if ui.button(self.localizer.t("save")).clicked() {
+                    save = true;
+                }
+            });
+
+        if save {
+            self.save_env_overrides();
+        } else if !open {
+            self.env_overrides = None;
+        }
+    }
+
+    fn render_snapshot_diff_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.snapshot_diff else {
+            return;
+        };
+
+        let taken_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(view.taken_at);
+        let short = |hash: &Option<String>| -> String {
+            hash.as_deref()
+                .map(|h| h.chars().take(8).collect())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let branch = |branch: &Option<String>| branch.clone().unwrap_or_else(|| "-".to_string());
+
+        let mut open = true;
+        egui::Window::new(
+            self.localizer
+                .tf("snapshot_diff_title", &[&format_relative_time(taken_at)]),
+        )
+        .id(egui::Id::new("snapshot_diff_window"))
+        .open(&mut open)
+        .resizable(true)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            let Some(changes) = &view.changes else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(self.localizer.t("snapshot_comparing"));
+                });
+                return;
+            };
+            if changes.is_empty() {
+                ui.label(self.localizer.t("snapshot_no_changes"));
+                return;
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("snapshot_diff_grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for change in changes {
+                            ui.strong(&change.repo_name)
+                                .on_hover_text(change.repo_path.display().to_string());
+                            ui.label(format!(
+                                "{} → {}",
+                                branch(&change.old_branch),
+                                branch(&change.new_branch)
+                            ));
+                            ui.monospace(format!(
+                                "{} → {}",
+                                short(&change.old_head),
+                                short(&change.new_head)
+                            ));
+                            match change.kind {
+                                SnapshotChangeKind::Added => {
+                                    ui.colored_label(
+                                        egui::Color32::LIGHT_GREEN,
+                                        self.localizer.t("snapshot_repo_added"),
+                                    );
+                                }
+                                SnapshotChangeKind::Removed => {
+                                    ui.colored_label(
+                                        egui::Color32::GRAY,
+                                        self.localizer.t("snapshot_repo_removed"),
+                                    );
+                                }
+                                SnapshotChangeKind::Moved if change.history_rewritten => {
+                                    ui.colored_label(
+                                        egui::Color32::LIGHT_RED,
+                                        self.localizer.t("snapshot_history_rewritten"),
+                                    );
+                                }
+                                SnapshotChangeKind::Moved => {
+                                    ui.label(self.localizer.tf(
+                                        "snapshot_commits",
+                                        &[&change.commits.unwrap_or(0).to_string()],
+                                    ));
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+        if !open {
+            self.snapshot_diff = None;
+        }
+    }
+
+    fn render_conflict_windows(&mut self, ctx: &egui::Context) {
+        let mut closed = Vec::new();
+        let mut open_file = None;
+        let mut recheck = None;
+        let mut finish = None;
+
+        for panel in self.conflict_panels.values() {
+            let repo_path = &panel.repo_path;
+            let mut open = true;
+            egui::Window::new(self.localizer.tf("conflicts_in", &[&panel.repo_name]))
+                .id(egui::Id::new(("conflict_window", repo_path)))
+                .open(&mut open)
+                .resizable(true)
+                .default_width(420.0)
+                .show(ctx, |ui| {
+                    if panel.files.is_empty() {
+                        ui.label(self.localizer.t("conflicts_resolved"));
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for file in &panel.files {
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(file).monospace());
+                                    if ui
+                                        .add_enabled(
+                                            panel.is_enabled(ConflictAction::OpenFile),
+                                            egui::Button::new(self.localizer.t("open_in_editor")),
+                                        )
+                                        .clicked()
+                                    {
+                                        open_file = Some(repo_path.join(file));
+                                    }
+                                });
+                            }
+                        });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                panel.is_enabled(ConflictAction::Recheck),
+                                egui::Button::new(self.localizer.t("recheck_conflicts")),
+                            )
+                            .clicked()
+                        {
+                            recheck = Some(repo_path.clone());
+                        }
+                        if ui
+                            .add_enabled(
+                                panel.is_enabled(ConflictAction::Abort),
+                                egui::Button::new(self.localizer.t("abort_merge")),
+                            )
+                            .clicked()
+                        {
+                            finish = Some((repo_path.clone(), true));
+                        }
+                        if ui
+                            .add_enabled(
+                                panel.is_enabled(ConflictAction::Continue),
+                                egui::Button::new(self.localizer.t("mark_resolved_continue")),
+                            )
+                            .on_disabled_hover_text(self.localizer.t("recheck_before_continue"))
+                            .clicked()
+                        {
+                            finish = Some((repo_path.clone(), false));
+                        }
+                        if matches!(
+                            panel.stage,
+                            ConflictStage::Checking | ConflictStage::Finishing
+                        ) {
+                            ui.spinner();
+                        }
+                    });
+                });
+            if !open {
+                closed.push(repo_path.clone());
+            }
+        }
+
+        if let Some(path) = open_file {
+            opener::open(&path).ok();
+        }
+        if let Some(repo_path) = recheck {
+            self.recheck_conflicts(&repo_path);
+        }
+        if let Some((repo_path, abort)) = finish {
+            self.finish_merge(&repo_path, abort);
+        }
+        for repo_path in closed {
+            self.conflict_panels.remove(&repo_path);
+        }
+    }
+
+    fn render_cherry_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.cherry else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(self.localizer.tf("unique_commits_of", &[&view.repo_name]))
+            .id(egui::Id::new("cherry_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                if view.entries.is_empty() {
+                    ui.label(self.localizer.t("no_unique_commits"));
+                    return;
+                }
+
+                let pending = view.entries.iter().filter(|e| e.sign == '+').count();
+                ui.label(self.localizer.tf(
+                    "unique_commits_summary",
+                    &[
+                        &pending.to_string(),
+                        &(view.entries.len() - pending).to_string(),
+                    ],
+                ));
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for entry in &view.entries {
+                            let short_hash: String = entry.hash.chars().take(8).collect();
+                            let text = format!("{} {} {}", entry.sign, short_hash, entry.subject);
+                            if entry.sign == '+' {
+                                ui.label(
+                                    egui::RichText::new(text)
+                                        .monospace()
+                                        .color(egui::Color32::YELLOW),
+                                )
+                                .on_hover_text(self.localizer.t("cherry_pending"));
+                            } else {
+                                ui.label(
+                                    egui::RichText::new(text)
+                                        .monospace()
+                                        .color(egui::Color32::GRAY),
+                                )
+                                .on_hover_text(self.localizer.t("cherry_equivalent"));
+                            }
+                        }
+                    });
+            });
+
+        if !open {
+            self.cherry = None;
+        }
+    }
+
+    fn render_file_at_commit_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.file_at_commit else {
+            return;
+        };
+
+        let mut open = true;
+        let mut save = false;
+        let localizer = &self.localizer;
+        egui::Window::new(localizer.tf(
+            "file_at_commit_title",
+            &[view.file_name(), view.short_hash()],
+        ))
+        .id(egui::Id::new("file_at_commit_window"))
+        .open(&mut open)
+        .resizable(true)
+        .default_size([700.0, 500.0])
+        .show(ctx, |ui| {
+            match &view.content {
+                None => {
+                    ui.spinner();
+                    return;
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(egui::Color32::LIGHT_RED, error);
+                    return;
+                }
+                Some(Ok(_)) => {}
+            }
+            let content = &view.text;
+
+            ui.horizontal(|ui| {
+                if ui.button(localizer.t("copy_content")).clicked() {
+                    ui.output_mut(|o| o.copied_text = content.clone());
+                }
+                if ui.button(localizer.t("save_to_file")).clicked() {
+                    save = true;
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::both()
+                .id_source("file_at_commit_scroll")
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    // &str — TextEdit только для чтения
+                    ui.add(
+                        egui::TextEdit::multiline(&mut content.as_str())
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+        });
+
+        if save {
+            self.save_file_at_commit();
+        }
+        if !open {
+            self.file_at_commit = None;
+        }
+    }
+
+    fn render_commit_log_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.commit_log else {
+            return;
+        };
+        let repo_path = view.repo_path.clone();
+        let commits = view.commits.clone();
+        let graph = view.graph.clone();
+        let show_graph = view.show_graph;
+        let selected = view.selected.clone();
+        let diff = selected.as_ref().and_then(|hash| {
+            match (self.commit_diff_cache.get(hash), view.diff_errors.get(hash)) {
+                (Some(diff), _) => Some(Ok(diff.clone())),
+                (None, Some(error)) => Some(Err(error.clone())),
+                (None, None) => None,
+            }
+        });
+        let noted: HashSet<String> = view
+            .notes
+            .iter()
+            .map(|note| note.object_hash.clone())
+            .collect();
+
+        let mut open = true;
+        let mut clicked_commit = None;
+        let mut edit_note = None;
+        let mut note_action = None;
+        let mut toggle_graph = false;
+        let mut cat_file_path = view.cat_file_path.clone();
+        let mut view_file = None;
+        egui::Window::new(self.localizer.tf("commit_log_of", &[&view.repo_name]))
+            .id(egui::Id::new(("commit_log_window", &repo_path)))
+            .open(&mut open)
+            .resizable(true)
+            .default_size([900.0, 500.0])
+            .show(ctx, |ui| {
+                egui::SidePanel::left("commit_log_list")
+                    .resizable(true)
+                    .default_width(340.0)
+                    .show_inside(ui, |ui| {
+                        let toggle_key = if show_graph {
+                            "commit_log_list_view"
+                        } else {
+                            "commit_log_graph_view"
+                        };
+                        if ui.button(self.localizer.t(toggle_key)).clicked() {
+                            toggle_graph = true;
+                        }
+                        ui.separator();
+
+                        if show_graph {
+                            let graph = match &graph {
+                                None => {
+                                    ui.spinner();
+                                    return;
+                                }
+                                Some(Err(error)) => {
+                                    ui.colored_label(egui::Color32::LIGHT_RED, error);
+                                    return;
+                                }
+                                Some(Ok(graph)) => graph,
+                            };
+                            egui::ScrollArea::vertical()
+                                .id_source("commit_graph_scroll")
+                                .auto_shrink([false, false])
+                                .show(ui, |ui| {
+                                    ui.spacing_mut().item_spacing.y = 0.0;
+                                    for line in graph {
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                egui::RichText::new(&line.graph_prefix)
+                                                    .monospace()
+                                                    .color(egui::Color32::GRAY),
+                                            );
+                                            if !line.is_commit() {
+                                                return;
+                                            }
+                                            let is_selected = selected.as_ref() == Some(&line.hash);
+                                            let row = ui
+                                                .selectable_label(
+                                                    is_selected,
+                                                    format!(
+                                                        "{}  {}",
+                                                        line.short_hash(),
+                                                        line.subject
+                                                    ),
+                                                )
+                                                .on_hover_text(format!(
+                                                    "{}, {}\n{}",
+                                                    line.author, line.date, line.hash
+                                                ));
+                                            if row.clicked() && !is_selected {
+                                                clicked_commit = Some(line.hash.clone());
+                                            }
+                                        });
+                                    }
+                                });
+                            return;
+                        }
+
+                        let commits = match &commits {
+                            None => {
+                                ui.spinner();
+                                return;
+                            }
+                            Some(Err(error)) => {
+                                ui.colored_label(egui::Color32::LIGHT_RED, error);
+                                return;
+                            }
+                            Some(Ok(commits)) => commits,
+                        };
+
+                        egui::ScrollArea::vertical()
+                            .id_source("commit_log_list_scroll")
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| {
+                                for commit in commits {
+                                    let is_selected = selected.as_ref() == Some(&commit.hash);
+                                    let row = ui.horizontal(|ui| {
+                                        if noted.contains(&commit.hash)
+                                            && ui
+                                                .small_button("📝")
+                                                .on_hover_text(self.localizer.t("edit_note"))
+                                                .clicked()
+                                        {
+                                            edit_note = Some(commit.hash.clone());
+                                        }
+                                        ui.selectable_label(
+                                            is_selected,
+                                            format!("{}  {}", commit.short_hash, commit.subject),
+                                        )
+                                        .on_hover_text(
+                                            format!(
+                                                "{}, {}\n{}",
+                                                commit.author,
+                                                commit_age(commit.time),
+                                                commit.hash
+                                            ),
+                                        )
+                                    });
+                                    let row = row.inner;
+                                    if row.clicked() && !is_selected {
+                                        clicked_commit = Some(commit.hash.clone());
+                                    }
+                                    row.context_menu(|ui| {
+                                        let key = if noted.contains(&commit.hash) {
+                                            "edit_note"
+                                        } else {
+                                            "add_note"
+                                        };
+                                        if ui.button(self.localizer.t(key)).clicked() {
+                                            edit_note = Some(commit.hash.clone());
+                                            ui.close_menu();
+                                        }
+                                        ui.menu_button(
+                                            self.localizer.t("view_file_at_commit"),
+                                            |ui| {
+                                                let input = ui.add(
+                                                    egui::TextEdit::singleline(&mut cat_file_path)
+                                                        .hint_text(
+                                                            self.localizer.t("file_path_hint"),
+                                                        ),
+                                                );
+                                                let submitted = input.lost_focus()
+                                                    && ui
+                                                        .input(|i| i.key_pressed(egui::Key::Enter));
+                                                let path = cat_file_path.trim();
+                                                if (ui
+                                                    .add_enabled(
+                                                        !path.is_empty(),
+                                                        egui::Button::new(
+                                                            self.localizer.t("view_file_open"),
+                                                        ),
+                                                    )
+                                                    .clicked()
+                                                    || submitted)
+                                                    && !path.is_empty()
+                                                {
+                                                    view_file = Some((
+                                                        commit.hash.clone(),
+                                                        path.to_string(),
+                                                    ));
+                                                    ui.close_menu();
+                                                }
+                                            },
+                                        );
+                                    });
+
+                                    let Some(view) = &mut self.commit_log else {
+                                        continue;
+                                    };
+                                    let Some((note_hash, text)) = &mut view.editing_note else {
+                                        continue;
+                                    };
+                                    if *note_hash != commit.hash {
+                                        continue;
+                                    }
+                                    ui.add(
+                                        egui::TextEdit::multiline(text)
+                                            .desired_rows(3)
+                                            .desired_width(f32::INFINITY)
+                                            .hint_text(self.localizer.t("note_hint")),
+                                    );
+                                    ui.horizontal(|ui| {
+                                        if ui.button(self.localizer.t("save")).clicked() {
+                                            note_action = Some(false);
+                                        }
+                                        if noted.contains(&commit.hash)
+                                            && ui.button(self.localizer.t("remove_note")).clicked()
+                                        {
+                                            note_action = Some(true);
+                                        }
+                                        if ui.button(self.localizer.t("cancel")).clicked() {
+                                            view.editing_note = None;
+                                        }
+                                    });
+                                }
+                            });
+                    });
+
+                egui::CentralPanel::default().show_inside(ui, |ui| match (&selected, &diff) {
+                    (None, _) => {
+                        ui.label(self.localizer.t("select_commit"));
+                    }
+                    (Some(_), None) => {
+                        ui.spinner();
+                    }
+                    (Some(_), Some(Err(error))) => {
+                        ui.colored_label(egui::Color32::LIGHT_RED, error);
+                    }
+                    (Some(_), Some(Ok(diff))) => {
+                        if ui.button(self.localizer.t("copy_diff")).clicked() {
+                            ui.output_mut(|o| o.copied_text = diff.clone());
+                        }
+                        ui.separator();
+                        render_diff(ui, diff, "commit_diff_scroll");
+                    }
+                });
+            });
+
+        if let Some(view) = &mut self.commit_log {
+            view.cat_file_path = cat_file_path;
+        }
+        if let Some((hash, file_path)) = view_file {
+            self.view_file_at_commit(hash, file_path);
+        }
+        if toggle_graph {
+            self.toggle_commit_graph();
+        }
+        if let Some(hash) = clicked_commit {
+            self.select_commit(hash);
+        }
+        if let Some(hash) = edit_note {
+            self.edit_commit_note(hash);
+        }
+        if let Some(remove) = note_action {
+            self.save_commit_note(remove);
+        }
+        if !open {
+            self.commit_log = None;
+        }
+    }
+
+    fn render_stash_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &self.stash_window else {
+            return;
+        };
+        let repo_path = window.repo_path.clone();
+        let entries = window.entries.clone();
+        let selected = window.selected;
+        let diff = selected.and_then(|index| window.diffs.get(&index)).cloned();
+
+        let mut open = true;
+        let mut hovered = None;
+        let mut clicked = None;
+        let mut action = None;
+        egui::Window::new(self.localizer.tf("stashes_of", &[&window.repo_name]))
+            .id(egui::Id::new(("stash_window", &repo_path)))
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 450.0])
+            .show(ctx, |ui| {
+                egui::SidePanel::left("stash_list")
+                    .resizable(true)
+                    .default_width(320.0)
+                    .show_inside(ui, |ui| {
+                        if entries.is_empty() {
+                            ui.label(self.localizer.t("no_stashes"));
+                            return;
+                        }
+
+                        egui::ScrollArea::vertical()
+                            .id_source("stash_list_scroll")
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| {
+                                for entry in &entries {
+                                    let row = ui.selectable_label(
+                                        selected == Some(entry.index),
+                                        format!("stash@{{{}}}  {}", entry.index, entry.message),
+                                    );
+                                    if row.hovered() {
+                                        hovered = Some(entry.index);
+                                    }
+                                    if row.clicked() {
+                                        clicked = Some(entry.index);
+                                    }
+
+                                    ui.horizontal(|ui| {
+                                        for stash_action in StashAction::ALL {
+                                            if ui
+                                                .small_button(
+                                                    self.localizer.t(stash_action.label_key()),
+                                                )
+                                                .clicked()
+                                            {
+                                                action = Some((stash_action, entry.index));
+                                            }
+                                        }
+                                    });
+                                    ui.separator();
+                                }
+                            });
+                    });
+
+                egui::CentralPanel::default().show_inside(ui, |ui| match (selected, &diff) {
+                    (None, _) => {
+                        ui.label(self.localizer.t("select_stash"));
+                    }
+                    (Some(_), None) => {
+                        ui.spinner();
+                    }
+                    (Some(_), Some(Err(error))) => {
+                        ui.colored_label(egui::Color32::LIGHT_RED, error);
+                    }
+                    (Some(_), Some(Ok(diff))) => render_diff(ui, diff, "stash_diff_scroll"),
+                });
+            });
+
+        if let Some(index) = hovered {
+            self.load_stash_diff(index);
+        }
+        if let Some(index) = clicked {
+            self.load_stash_diff(index);
+            if let Some(window) = &mut self.stash_window {
+                window.selected = Some(index);
+            }
+        }
+        if let Some((stash_action, index)) = action {
+            self.request_stash_action(stash_action, index);
+        }
+        if !open {
+            self.stash_window = None;
+        }
+    }
+
+    fn render_digest_window(&mut self, ctx: &egui::Context) {
+        let Some(deltas) = &self.digest else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(self.localizer.t("daily_digest"))
+            .id(egui::Id::new("digest_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                if deltas.is_empty() {
+                    ui.label(self.localizer.t("digest_no_changes"));
+                    return;
+                }
+
+                if ui.button(self.localizer.t("copy_as_markdown")).clicked() {
+                    let markdown = digest_to_markdown(
+                        deltas,
+                        &self.localizer.t("daily_digest"),
+                        &self.localizer.t("history_rewritten"),
+                    );
+                    ui.output_mut(|o| o.copied_text = markdown);
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for delta in deltas {
+                            ui.strong(&delta.repo_name);
+                            if delta.history_rewritten {
+                                ui.colored_label(
+                                    egui::Color32::YELLOW,
+                                    self.localizer.t("history_rewritten"),
+                                );
+                            }
+                            for (author, commits) in group_by_author(&delta.commits) {
+                                ui.indent(("digest_author", &delta.repo_path, author), |ui| {
+                                    ui.colored_label(egui::Color32::LIGHT_BLUE, author);
+                                    for commit in commits {
+                                        ui.horizontal(|ui| {
+                                            ui.monospace(&commit.short_hash);
+                                            ui.label(&commit.subject);
+                                        });
+                                    }
+                                });
+                            }
+                            ui.add_space(6.0);
+                        }
+                    });
+            });
+
+        if !open {
+            self.digest = None;
+        }
+    }
+
+    fn render_tags_window(&mut self, ctx: &egui::Context) {
+        let Some(repo_path) = self.tag_window_repo.clone() else {
+            return;
+        };
+        let Some(repo) = self
+            .get_active_workspace()
+            .and_then(|w| w.repositories.iter().find(|r| r.path == repo_path))
+            .cloned()
+        else {
+            self.tag_window_repo = None;
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(self.localizer.tf("tags_of", &[&repo.name]))
+            .id(egui::Id::new("tags_window"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if let Some(describe) = &repo.git_info.describe_string {
+                        ui.monospace(describe);
+                    }
+                    if ui.button(self.localizer.t("copy_describe")).clicked() {
+                        match git_describe_for_ci(&repo.path) {
+                            Ok(describe) => ui.output_mut(|o| o.copied_text = describe),
+                            Err(e) => self.logger.error(
+                                self.localizer
+                                    .tf("describe_failed", &[&repo.name, &e.to_string()]),
+                            ),
+                        }
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for tag in &repo.git_info.tags {
+                            ui.horizontal(|ui| {
+                                ui.monospace(tag);
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui
+                                            .add_enabled(
+                                                repo.git_info.has_upstream,
+                                                egui::Button::new(self.localizer.t("push")),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.logger.info(
+                                                self.localizer
+                                                    .tf("starting_push_tag", &[tag, &repo.name]),
+                                            );
+                                            self.runtime.start_op(repo.path.clone(), RepoOp::Sync);
+                                            if let Some(tx) = &self.app_sender {
+                                                git_push_tags_async::<AppMessage>(
+                                                    repo.path.clone(),
+                                                    Some(tag.clone()),
+                                                    tx.clone(),
+                                                );
+                                            }
+                                        }
+                                    },
+                                );
+                            });
+                        }
+                    });
+            });
+
+        if !open {
+            self.tag_window_repo = None;
+        }
+    }
+
+    /// Read-only tree of repositories from all workspaces that were active
+    /// within `Config::recent_days`.
+    fn render_recent_workspace(&mut self, ui: &mut egui::Ui) {
+        let repos = self.recent_repositories();
+        if repos.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label(self.localizer.tf(
+                    "recent_workspace_empty",
+                    &[&self.config.recent_days.to_string()],
+                ));
+            });
+            return;
+        }
+
+        if self.config.view_mode == ViewMode::List {
+            self.render_repo_list(ui, &repos, SortMode::LastCommit, None);
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                let chips = self.active_quick_filters();
+                let tree = TreeBuilder::build_tree(
+                    &repos,
+                    RepoFilter::new(&self.search_query, self.attention_filter, &chips),
+                    SortMode::LastCommit,
+                    self.config.max_tree_depth as usize,
+                    self.config.group_mode,
+                    self.runtime.errors(),
+                );
+                self.render_tree_node(ui, &tree, &repos, 0, None);
+            });
+    }
+
+    /// Flat table of the filtered repositories with a sortable header. Rows
+    /// have a fixed height, so only the visible ones are laid out.
+    fn render_repo_list(
+        &mut self,
+        ui: &mut egui::Ui,
+        repos: &[RepositoryState],
+        sort_mode: SortMode,
+        to_remove: Option<&std::cell::RefCell<Option<usize>>>,
+    ) {
+        let chips = self.active_quick_filters();
+        let order = list_order(
+            repos,
+            RepoFilter::new(&self.search_query, self.attention_filter, &chips),
+            self.runtime.errors(),
+            sort_mode,
+            self.list_sort,
+        );
+
+        ui.horizontal(|ui| {
+            ui.add_space(LIST_ROW_INDENT);
+            let (repo_width, branch_width, status_width) = repo_row_widths(ui.available_width());
+            let mut clicked = None;
+
+            ui.allocate_ui_with_layout(
+                egui::Vec2::new(repo_width, 20.0),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    ui.set_min_size(egui::Vec2::new(repo_width, 20.0));
+                    if self.list_header(ui, ListColumn::Name) {
+                        clicked = Some(ListColumn::Name);
+                    }
+                },
+            );
+            ui.allocate_ui_with_layout(
+                egui::Vec2::new(branch_width, 20.0),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    ui.set_min_size(egui::Vec2::new(branch_width, 20.0));
+                    ui.strong(self.localizer.t("list_column_branch"));
+                },
+            );
+            ui.allocate_ui_with_layout(
+                egui::Vec2::new(status_width, 20.0),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    ui.set_min_size(egui::Vec2::new(status_width, 20.0));
+                    for column in [ListColumn::Behind, ListColumn::Ahead] {
+                        if self.list_header(ui, column) {
+                            clicked = Some(column);
+                        }
+                    }
+                },
+            );
+            ui.strong(self.localizer.t("list_column_actions"));
+
+            if let Some(column) = clicked {
+                self.list_sort = ListSort::clicked(self.list_sort, column);
+            }
+        });
+        ui.separator();
+
+        let scroll = egui::ScrollArea::vertical().auto_shrink([false, true]);
+        if self.config.row_density == RowDensity::Compact {
+            // Все строки одной высоты: рисуем только видимые
+            scroll.show_rows(ui, 25.0, order.len(), |ui, rows| {
+                for idx in &order[rows] {
+                    self.render_repo_row(ui, &repos[*idx], *idx, LIST_ROW_INDENT, to_remove);
+                }
+            });
+        } else {
+            // Строка описания есть не у каждого репозитория
+            scroll.show(ui, |ui| {
+                for idx in &order {
+                    self.render_repo_row(ui, &repos[*idx], *idx, LIST_ROW_INDENT, to_remove);
+                }
+            });
+        }
+    }
+
+    /// Clickable header label with an arrow on the active sort column.
+    fn list_header(&self, ui: &mut egui::Ui, column: ListColumn) -> bool {
+        let active = self.list_sort.filter(|sort| sort.column == column);
+        let arrow = match active {
+            Some(sort) if sort.descending => " ▼",
+            Some(_) => " ▲",
+            None => "",
+        };
+        ui.selectable_label(
+            active.is_some(),
+            format!("{}{}", self.localizer.t(column.label_key()), arrow),
+        )
+        .clicked()
+    }
+
+    /// One repository row: name, branch, status cell and actions. Shared by
+    /// the tree and the flat list so the two views cannot drift apart.
+    fn render_repo_row(
+        &mut self,
+        ui: &mut egui::Ui,
+        repo: &RepositoryState,
+        original_idx: usize,
+        indent: f32,
+        to_remove: Option<&std::cell::RefCell<Option<usize>>>,
+    ) {
+        let identity_mismatch = find_identity_mismatch(
+            &self.config.expected_identities,
+            &repo.path,
+            repo.git_info.identity.as_ref(),
+            home_dir().as_deref(),
+        );
+
+        ui.horizontal(|ui| {
+            ui.add_space(indent);
+
+            let (repo_width, branch_width, status_width) = repo_row_widths(ui.available_width());
+
+            ui.allocate_ui_with_layout(
+                egui::Vec2::new(repo_width, 25.0),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    ui.set_min_size(egui::Vec2::new(repo_width, 25.0));
+
+                    let identity_text = match &repo.git_info.identity {
+                        Some((name, email)) => {
+                            self.localizer.tf("identity_tooltip", &[name, email])
+                        }
+                        None => self.localizer.t("identity_unset"),
+                    };
+
+                    if let Some(url) = &repo.git_info.remote_url {
+                        let icon = IconType::for_provider(remote_provider(url));
+                        Icon::show(ui, &mut self.icon_manager, icon, Some(14.0)).on_hover_text(url);
+                    }
+
+                    let name_button = ui
+                        .push_id(("repo_name", &repo.path), |ui| ui.button(&repo.name))
+                        .inner;
+                    if name_button.clicked() {
+                        opener::open(&repo.path).ok();
+                    }
+                    name_button.on_hover_text(identity_text);
+                    if self.config.bookmarks.contains(&repo.path) {
+                        ui.label("★").on_hover_text(self.localizer.t("bookmarked"));
+                    }
+                    if repo.is_symlink {
+                        let target = repo
+                            .canonical_path
+                            .as_ref()
+                            .map(|path| path.display().to_string())
+                            .unwrap_or_default();
+                        ui.label("⟳")
+                            .on_hover_text(self.localizer.tf("symlink_tooltip", &[&target]));
+                    }
+                    if !repo.git_dir_check.is_valid() {
+                        let color = if repo.git_dir_check.is_invalid() {
+                            egui::Color32::from_rgb(220, 80, 80)
+                        } else {
+                            egui::Color32::GRAY
+                        };
+                        ui.label(egui::RichText::new("⚠").color(color))
+                            .on_hover_text(self.git_dir_tooltip(&repo.git_dir_check));
+                    }
+                    if let Some(id) = self.active_workspace_id {
+                        if let Some(duplicate) = self.duplicate_of(id, &repo.path) {
+                            let others = duplicate.other_workspaces(id).join(", ");
+                            ui.label(egui::RichText::new("⧉").small().weak())
+                                .on_hover_text(self.localizer.tf("duplicate_also_in", &[&others]));
+                        }
+                    }
+
+                    if self.config.show_activity_sparkline {
+                        self.request_commit_activity(&repo.path, unix_now());
+                        let activity = self.status_cache.activity.get(&repo.path);
+                        let cell = activity_sparkline(ui, activity);
+                        if let Some(activity) = activity {
+                            cell.on_hover_text(self.localizer.tf(
+                                "activity_tooltip",
+                                &[&activity.total().to_string(), &ACTIVITY_DAYS.to_string()],
+                            ));
+                        }
+                    }
+
+                    if let Some(root) = &repo.monorepo_root {
+                        ui.label(egui::RichText::new("⤷").small().weak())
+                            .on_hover_text(
+                                self.localizer
+                                    .tf("monorepo_service_of", &[&root.display().to_string()]),
+                            );
+                    }
+
+                    if let Some(project_type) = repo.project_type {
+                        if let Some(badge) = project_type.badge() {
+                            ui.label(
+                                egui::RichText::new(badge)
+                                    .small()
+                                    .color(project_type.color()),
+                            )
+                            .on_hover_text(project_type.label());
+                        }
+                    }
+
+                    if let Some(mismatch) = &identity_mismatch {
+                        ui.colored_label(egui::Color32::from_rgb(170, 90, 220), "●")
+                            .on_hover_text(self.localizer.tf(
+                                "identity_mismatch",
+                                &[
+                                    &mismatch.rule.email,
+                                    &mismatch.rule.path_prefix,
+                                    &mismatch.actual_email,
+                                ],
+                            ));
+                    }
+                },
+            );
+
+            // Во время bisect вместо списка веток показываем его состояние
+            let bisect_state = self.bisect_states.get(&repo.path).cloned();
+            let branch_width = if bisect_state.is_some() {
+                branch_width + 150.0
+            } else {
+                branch_width
+            };
+            ui.allocate_ui_with_layout(
+                egui::Vec2::new(branch_width, 25.0),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    ui.set_min_size(egui::Vec2::new(branch_width, 25.0));
+                    ui.set_max_size(egui::Vec2::new(branch_width, 25.0));
+
+                    if let Some(state) = bisect_state {
+                        let short_hash: String = state.current_hash.chars().take(7).collect();
+                        ui.colored_label(
+                            egui::Color32::from_rgb(230, 160, 60),
+                            self.localizer.tf(
+                                "bisect_status",
+                                &[&state.remaining_steps.to_string(), &short_hash],
+                            ),
+                        )
+                        .on_hover_text(&state.current_hash);
+                        for verdict in [BisectVerdict::Good, BisectVerdict::Bad] {
+                            if ui
+                                .small_button(self.localizer.t(verdict.label_key()))
+                                .clicked()
+                            {
+                                self.mark_bisect(repo.path.clone(), verdict);
+                            }
+                        }
+                        if ui.small_button(self.localizer.t("bisect_reset")).clicked() {
+                            self.reset_bisect(repo.path.clone());
+                        }
+                        return;
+                    }
+
+                    let current_branch = repo.git_info.current_branch.as_deref().unwrap_or("...");
+                    // Место под стрелку выпадающего списка
+                    let display_branch =
+                        ellipsize_to_width(ui, current_branch, branch_width - 30.0);
+
+                    let full_list = self.all_branches.get(&repo.path).cloned();
+                    let mut load_all = false;
+                    let needs_confirmation = self
+                        .config
+                        .branch_switch_confirmation
+                        .requires_confirmation(repo.git_info.has_changes);
+                    let pending = self
+                        .pending_branch_switch
+                        .as_ref()
+                        .filter(|(path, _)| *path == repo.path)
+                        .map(|(_, branch)| branch.clone());
+                    let mut picked = None;
+                    let mut confirmed = None;
+                    let mut cancelled = false;
+                    let popup_id = ui.make_persistent_id(("branch", &repo.path)).with("popup");
+                    egui::ComboBox::from_id_source(("branch", &repo.path))
+                        .selected_text(display_branch)
+                        .width(branch_width - 10.0)
+                        .show_ui(ui, |ui| {
+                            let shown = repo.git_info.branches.len();
+                            if full_list.is_none() && repo.git_info.branch_count_total > shown {
+                                ui.colored_label(
+                                    egui::Color32::GRAY,
+                                    self.localizer.tf(
+                                        "branches_shown_of",
+                                        &[
+                                            &shown.to_string(),
+                                            &repo.git_info.branch_count_total.to_string(),
+                                        ],
+                                    ),
+                                );
+                                if self.loading_branches.contains(&repo.path) {
+                                    ui.spinner();
+                                } else if ui.button(self.localizer.t("load_all_branches")).clicked()
+                                {
+                                    load_all = true;
+                                }
+                                ui.separator();
+                            }
+
+                            let branches = full_list.as_ref().unwrap_or(&repo.git_info.branches);
+                            for branch in branches {
+                                let label = ui
+                                    .selectable_label(pending.as_ref() == Some(branch), branch)
+                                    .on_hover_text(branch);
+
+                                if label.clicked() {
+                                    picked = Some(branch.clone());
+                                }
+                            }
+
+                            if let Some(branch) = &pending {
+                                ui.separator();
+                                ui.label(self.localizer.tf("switch_to_branch_question", &[branch]));
+                                ui.horizontal(|ui| {
+                                    if ui.button(self.localizer.t("switch")).clicked() {
+                                        confirmed = Some(branch.clone());
+                                    }
+                                    if ui.button(self.localizer.t("cancel")).clicked() {
+                                        cancelled = true;
+                                    }
+                                });
+                            }
+                        });
+
+                    if load_all {
+                        self.load_all_branches(repo.path.clone());
+                    }
+                    if let Some(branch) = picked {
+                        if needs_confirmation {
+                            self.pending_branch_switch = Some((repo.path.clone(), branch));
+                            // Клик по ветке закрывает список, держим его открытым
+                            ui.memory_mut(|mem| mem.open_popup(popup_id));
+                        } else {
+                            self.start_branch_switch(repo.path.clone(), branch);
+                        }
+                    } else if let Some(branch) = confirmed {
+                        self.start_branch_switch(repo.path.clone(), branch);
+                    } else if cancelled
+                        || (pending.is_some() && !ui.memory(|mem| mem.is_popup_open(popup_id)))
+                    {
+                        self.pending_branch_switch = None;
+                    }
+                },
+            );
+
+            ui.allocate_ui_with_layout(
+                egui::Vec2::new(status_width, 25.0),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    ui.set_min_size(egui::Vec2::new(status_width, 25.0));
+
+                    for element in self.config.row_elements.clone() {
+                        self.render_row_element(ui, repo, element);
+                    }
+                    if let Some(depth) = repo.git_info.shallow_depth {
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            self.localizer.tf("shallow_badge", &[&depth.to_string()]),
+                        )
+                        .on_hover_text(self.localizer.t("shallow_tooltip"));
+                    }
+                },
+            );
+
+            if Button::icon(IconType::Refresh)
+                .show(ui, &mut self.icon_manager)
+                .on_hover_text(&self.localizer.t("fetch"))
+                .clicked()
+            {
+                self.logger
+                    .info(self.localizer.tf("starting_fetch", &[&repo.name]));
+                self.runtime.start_op(repo.path.clone(), RepoOp::Fetch);
+                if let Some(tx) = &self.app_sender {
+                    git_fetch_fast_async::<AppMessage>(repo.path.clone(), tx.clone());
+                }
+            }
+
+            ui.menu_button("»", |ui| {
+                if repo.git_info.remotes.len() > 1 {
+                    // Some(None) — все remotes, Some(Some(name)) — один
+                    let mut fetch_remote: Option<Option<String>> = None;
+                    ui.menu_button(self.localizer.t("fetch"), |ui| {
+                        if Button::icon_text(
+                            IconType::Refresh,
+                            self.localizer.t("fetch_all_remotes"),
+                        )
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                        {
+                            fetch_remote = Some(None);
+                        }
+                        ui.separator();
+                        for remote in &repo.git_info.remotes {
+                            if Button::icon_text(IconType::Refresh, remote)
+                                .full_width()
+                                .show(ui, &mut self.icon_manager)
+                                .clicked()
+                            {
+                                fetch_remote = Some(Some(remote.clone()));
+                            }
+                        }
+                    });
+                    if let Some(remote) = fetch_remote {
+                        let target = remote.as_deref().unwrap_or("--all");
+                        self.logger.info(
+                            self.localizer
+                                .tf("starting_fetch_remote", &[target, &repo.name]),
+                        );
+                        self.runtime.start_op(repo.path.clone(), RepoOp::Fetch);
+                        if let Some(tx) = &self.app_sender {
+                            git_fetch_remote_async::<AppMessage>(
+                                repo.path.clone(),
+                                remote,
+                                tx.clone(),
+                            );
+                        }
+                        ui.close_menu();
+                    }
+                } else if Button::icon_text(IconType::Refresh, &self.localizer.t("fetch"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.logger
+                        .info(self.localizer.tf("starting_fetch", &[&repo.name]));
+                    self.runtime.start_op(repo.path.clone(), RepoOp::Fetch);
+                    if let Some(tx) = &self.app_sender {
+                        git_fetch_fast_async::<AppMessage>(repo.path.clone(), tx.clone());
+                    }
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Refresh, &self.localizer.t("fetch_rebase"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    println!("Fetch with rebase for {:?}", repo.path);
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Refresh, &self.localizer.t("refresh"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.runtime.begin_retry(&repo.path);
+                    if let Some(tx) = &self.app_sender {
+                        refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+                    }
+                    ui.close_menu();
+                }
+                if repo.git_info.has_push_hooks
+                    && Button::icon_text(IconType::Push, self.localizer.t("push_no_verify"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    self.pending_confirmation = Some(app::ConfirmAction::PushNoVerify {
+                        repo_path: repo.path.clone(),
+                        repo_name: repo.name.clone(),
+                    });
+                    ui.close_menu();
+                }
+                if let Some(branch) = repo
+                    .git_info
+                    .current_branch
+                    .as_ref()
+                    .filter(|_| repo.git_info.has_upstream)
+                {
+                    if Button::icon_text(IconType::Push, self.localizer.t("force_push"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                    {
+                        self.request_force_push(
+                            repo.path.clone(),
+                            repo.name.clone(),
+                            branch.clone(),
+                        );
+                        ui.close_menu();
+                    }
+                }
+                if !repo.git_info.tags.is_empty()
+                    && repo.git_info.has_upstream
+                    && Button::icon_text(IconType::Push, self.localizer.t("push_tags"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    self.logger
+                        .info(self.localizer.tf("starting_push_tags", &[&repo.name]));
+                    self.runtime.start_op(repo.path.clone(), RepoOp::Sync);
+                    if let Some(tx) = &self.app_sender {
+                        git_push_tags_async::<AppMessage>(repo.path.clone(), None, tx.clone());
+                    }
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Info, self.localizer.t("commit_log"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_commit_log(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if self
+                    .runtime
+                    .errors()
+                    .get(&repo.path)
+                    .is_some_and(|e| e.kind == GitErrorKind::DubiousOwnership)
+                    && Button::icon_text(IconType::Check, self.localizer.t("mark_safe_directory"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    self.pending_confirmation = Some(app::ConfirmAction::MarkSafeDirectory {
+                        repo_path: repo.path.clone(),
+                        repo_name: repo.name.clone(),
+                    });
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Info, self.localizer.t("stashes"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_stash_window(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Info, self.localizer.t("branches_overview"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_branches_view(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Edit, self.localizer.t("remotes"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_remotes_window(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Info, self.localizer.t("contributors"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_contributors(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Folder, self.localizer.t("show_ignored_files"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_ignored_files(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Edit, self.localizer.t("env_overrides"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_env_overrides(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if repo.monorepo_root.is_none()
+                    && Button::icon_text(
+                        IconType::Folder,
+                        self.localizer.t("detect_monorepo_services"),
+                    )
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_monorepo_services(
+                        repo.infer_project_root().to_path_buf(),
+                        repo.name.clone(),
+                    );
+                    ui.close_menu();
+                }
+                if repo.git_info.shallow_depth.is_some() {
+                    // None — полная история (--unshallow)
+                    let mut deepen: Option<Option<u32>> = None;
+                    ui.menu_button(self.localizer.t("deepen_history"), |ui| {
+                        for n in [10, 100, 1000] {
+                            if ui
+                                .button(self.localizer.tf("deepen_by", &[&n.to_string()]))
+                                .clicked()
+                            {
+                                deepen = Some(Some(n));
+                            }
+                        }
+                    });
+                    if Button::icon_text(IconType::Pull, self.localizer.t("unshallow"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                    {
+                        deepen = Some(None);
+                    }
+                    if let Some(deepen) = deepen {
+                        self.logger
+                            .info(self.localizer.tf("starting_fetch", &[&repo.name]));
+                        self.runtime.start_op(repo.path.clone(), RepoOp::Fetch);
+                        if let Some(tx) = &self.app_sender {
+                            git_deepen_async::<AppMessage>(repo.path.clone(), deepen, tx.clone());
+                        }
+                        ui.close_menu();
+                    }
+                }
+                if repo.git_info.upstream_gone {
+                    ui.separator();
+                    if Button::icon_text(IconType::Edit, self.localizer.t("unset_upstream"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                    {
+                        self.unset_upstream(repo.path.clone(), &repo.name);
+                        ui.close_menu();
+                    }
+                    if Button::icon_text(
+                        IconType::Edit,
+                        self.localizer.t("repoint_upstream_to_default"),
+                    )
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                    {
+                        self.repoint_upstream(repo.path.clone(), &repo.name);
+                        ui.close_menu();
+                    }
+                    let clean = !repo.git_info.has_changes;
+                    let delete_clicked = ui
+                        .add_enabled_ui(clean, |ui| {
+                            Button::icon_text(
+                                IconType::Trash,
+                                self.localizer.t("delete_local_branch"),
+                            )
                             .full_width()
                             .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            self.logger
-                                .info(self.localizer.tf("starting_fetch", &[&repo.name]));
-                            self.syncing_repos.insert(repo.path.clone());
-                            if let Some(tx) = &self.app_sender {
-                                git_fetch_fast_async::<AppMessage>(repo.path.clone(), tx.clone());
+                        })
+                        .inner
+                        .on_disabled_hover_text(self.localizer.t("has_changes"))
+                        .clicked();
+                    if delete_clicked {
+                        self.request_delete_gone_branch(repo);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                }
+                if Button::icon_text(IconType::Edit, self.localizer.t("apply_patch"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_patch_dialog(repo.path.clone(), repo.name.clone());
+                    ui.close_menu();
+                }
+                if Button::icon_text(IconType::Info, self.localizer.t("show_stale_refs"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    match git_remote_prune_dryrun(&repo.path) {
+                        Ok(refs) => {
+                            self.stale_refs = Some(app::StaleRefsView {
+                                repo_path: repo.path.clone(),
+                                repo_name: repo.name.clone(),
+                                refs,
+                            });
+                        }
+                        Err(e) => self.logger.error(
+                            self.localizer
+                                .tf("prune_error", &[&repo.name, &e.to_string()]),
+                        ),
+                    }
+                    ui.close_menu();
+                }
+                if repo.git_info.has_upstream
+                    && Button::icon_text(IconType::Push, self.localizer.t("show_unique_commits"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    match git_cherry(&repo.path, "@{u}") {
+                        Ok(entries) => {
+                            self.cherry = Some(app::CherryView {
+                                repo_name: repo.name.clone(),
+                                entries,
+                            });
+                        }
+                        Err(e) => self.logger.error(
+                            self.localizer
+                                .tf("cherry_error", &[&repo.name, &e.to_string()]),
+                        ),
+                    }
+                    ui.close_menu();
+                }
+                if !repo.git_info.tags.is_empty()
+                    && Button::icon_text(IconType::Info, self.localizer.t("tags"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    self.tag_window_repo = Some(repo.path.clone());
+                    ui.close_menu();
+                }
+                if let Some(mismatch) = &identity_mismatch {
+                    if Button::icon_text(IconType::Edit, self.localizer.t("set_local_identity"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                    {
+                        match git_set_local_identity(
+                            &repo.path,
+                            mismatch.rule.name.as_deref(),
+                            &mismatch.rule.email,
+                        ) {
+                            Ok(_) => {
+                                self.logger.info(self.localizer.tf(
+                                    "identity_set_success",
+                                    &[&mismatch.rule.email, &repo.name],
+                                ));
+                                if let Some(tx) = &self.app_sender {
+                                    refresh_repo_status_async::<AppMessage>(
+                                        repo.path.clone(),
+                                        tx.clone(),
+                                    );
+                                }
+                            }
+                            Err(e) => self.logger.error(
+                                self.localizer
+                                    .tf("identity_set_error", &[&repo.name, &e.to_string()]),
+                            ),
+                        }
+                        ui.close_menu();
+                    }
+                }
+                if Button::icon_text(IconType::Folder, self.localizer.t("open_terminal_here"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.open_terminal(&repo.path, ui.ctx());
+                    ui.close_menu();
+                }
+                if repo.git_info.has_changes
+                    && Button::icon_text(IconType::Info, self.localizer.t("open_difftool"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    self.open_difftool(&repo.path, &repo.name, false);
+                    ui.close_menu();
+                }
+                if repo.git_info.behind > 0
+                    && Button::icon_text(IconType::Info, self.localizer.t("open_difftool_upstream"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                {
+                    self.open_difftool(&repo.path, &repo.name, true);
+                    ui.close_menu();
+                }
+                ui.separator();
+                if Button::icon_text(IconType::Cross, &self.localizer.t("reset_changes"))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    if let Err(e) = git_reset_hard(&repo.path) {
+                        self.logger.error(
+                            self.localizer
+                                .tf("reset_error", &[&repo.name, &e.to_string()]),
+                        );
+                    } else {
+                        self.logger
+                            .info(self.localizer.tf("reset_success", &[&repo.name]));
+                        self.mark_workspace_modified(&repo.path);
+                        if let Some(tx) = &self.app_sender {
+                            refresh_repo_status_async::<AppMessage>(repo.path.clone(), tx.clone());
+                        }
+                    }
+                    ui.close_menu();
+                }
+                let bookmark_key = if self.config.bookmarks.contains(&repo.path) {
+                    "remove_bookmark"
+                } else {
+                    "bookmark_repo"
+                };
+                if Button::icon_text(IconType::Info, self.localizer.t(bookmark_key))
+                    .full_width()
+                    .show(ui, &mut self.icon_manager)
+                    .clicked()
+                {
+                    self.toggle_bookmark(&repo.path);
+                    ui.close_menu();
+                }
+                if let Some(to_remove) = to_remove {
+                    ui.separator();
+                    if Button::icon_text(IconType::Trash, &self.localizer.t("remove_repo"))
+                        .full_width()
+                        .show(ui, &mut self.icon_manager)
+                        .clicked()
+                    {
+                        *to_remove.borrow_mut() = Some(original_idx);
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+
+        if self.config.row_density == RowDensity::Comfortable {
+            if let Some(description) = &repo.description {
+                ui.horizontal(|ui| {
+                    ui.add_space(indent + 24.0);
+                    ui.label(egui::RichText::new(description).small().weak());
+                });
+            }
+        }
+    }
+
+    /// "Bookmarks" section above the repositories of every workspace; a
+    /// double click opens the repository folder.
+    fn render_bookmarks(&mut self, ui: &mut egui::Ui) {
+        let bookmarks = self.bookmarked_repositories();
+        if bookmarks.is_empty() {
+            return;
+        }
+
+        // Ключ не совпадает ни с одним путём папки в дереве
+        let section_key = "\0bookmarks".to_string();
+        let is_collapsed = self.collapsed_paths.contains(&section_key);
+        ui.horizontal(|ui| {
+            let expand_symbol = if is_collapsed { "+" } else { "-" };
+            if ui
+                .button(format!(
+                    "{} {}",
+                    expand_symbol,
+                    self.localizer.t("bookmarks")
+                ))
+                .clicked()
+            {
+                if is_collapsed {
+                    self.collapsed_paths.remove(&section_key);
+                } else {
+                    self.collapsed_paths.insert(section_key.clone());
+                }
+            }
+            ui.colored_label(
+                egui::Color32::DARK_GRAY,
+                self.localizer
+                    .tf("elements_count", &[&bookmarks.len().to_string()]),
+            );
+        });
+
+        if !is_collapsed {
+            for repo in &bookmarks {
+                ui.horizontal(|ui| {
+                    ui.add_space(20.0);
+                    let row = ui
+                        .push_id(("bookmark", &repo.path), |ui| {
+                            ui.selectable_label(false, format!("{} ★", repo.name))
+                        })
+                        .inner
+                        .on_hover_text(repo.path.display().to_string());
+                    if row.double_clicked() {
+                        opener::open(&repo.path).ok();
+                    }
+                    row.context_menu(|ui| {
+                        if ui.button(self.localizer.t("remove_bookmark")).clicked() {
+                            self.toggle_bookmark(&repo.path);
+                            ui.close_menu();
+                        }
+                    });
+                    if let Some(branch) = &repo.git_info.current_branch {
+                        ui.colored_label(egui::Color32::GRAY, branch);
+                    }
+                });
+            }
+        }
+        ui.separator();
+    }
+
+    fn render_tree_node(
+        &mut self,
+        ui: &mut egui::Ui,
+        node: &app::TreeNode,
+        workspace: &[RepositoryState],
+        depth: usize,
+        to_remove: Option<&std::cell::RefCell<Option<usize>>>,
+    ) {
+        if depth > 0 {
+            let indent = (depth as f32) * 20.0;
+            ui.horizontal(|ui| {
+                ui.add_space(indent - 20.0);
+
+                let has_children = !node.children.is_empty();
+                let has_repos = !node.repositories.is_empty();
+
+                if has_children || has_repos {
+                    let node_path = node.path.to_string_lossy().to_string();
+                    let is_collapsed = self.collapsed_paths.contains(&node_path);
+                    let expand_symbol = if is_collapsed { "+" } else { "-" };
+
+                    let node_name = if node.is_group {
+                        self.localizer.t(&node.name)
+                    } else {
+                        node.name.clone()
+                    };
+                    let folder_button = ui.button(format!("{} {}", expand_symbol, node_name));
+                    folder_button.context_menu(|ui| {
+                        for action in app::BulkAction::ALL {
+                            if ui
+                                .button(self.localizer.t(action.folder_menu_key()))
+                                .clicked()
+                            {
+                                self.request_bulk(app::BulkRequest::Folder {
+                                    folder: node.path.display().to_string(),
+                                    repos: node.all_repository_paths(),
+                                    action,
+                                });
+                                ui.close_menu();
                             }
-                            ui.close_menu();
-                        }
-                        if Button::icon_text(IconType::Refresh, &self.localizer.t("fetch_rebase"))
-                            .full_width()
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            println!("Fetch with rebase for {:?}", repo.path);
-                            ui.close_menu();
                         }
-                        if Button::icon_text(IconType::Refresh, &self.localizer.t("refresh"))
-                            .full_width()
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            self.error_repos.remove(&repo.path);
-                            if let Some(tx) = &self.app_sender {
-                                refresh_repo_status_async::<AppMessage>(
-                                    repo.path.clone(),
-                                    tx.clone(),
-                                );
-                            }
-                            ui.close_menu();
+                        if node.is_group {
+                            return;
                         }
                         ui.separator();
-                        if Button::icon_text(IconType::Cross, &self.localizer.t("reset_changes"))
-                            .full_width()
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            if let Err(e) = git_reset_hard(&repo.path) {
-                                self.logger.error(
-                                    self.localizer
-                                        .tf("reset_error", &[&repo.name, &e.to_string()]),
-                                );
-                            } else {
-                                self.logger
-                                    .info(self.localizer.tf("reset_success", &[&repo.name]));
-                                if let Some(tx) = &self.app_sender {
-                                    refresh_repo_status_async::<AppMessage>(
-                                        repo.path.clone(),
-                                        tx.clone(),
-                                    );
-                                }
+                        if ui.button(self.localizer.t("repath_folder")).clicked() {
+                            if let Some(folder) = node.folder_path() {
+                                self.open_repath_dialog(folder);
                             }
                             ui.close_menu();
                         }
-                        ui.separator();
-                        if Button::icon_text(IconType::Trash, &self.localizer.t("remove_repo"))
-                            .full_width()
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            *to_remove.borrow_mut() = Some(*original_idx);
-                            ui.close_menu();
+                    });
+                    if folder_button.clicked() {
+                        if is_collapsed {
+                            self.collapsed_paths.remove(&node_path);
+                        } else {
+                            self.collapsed_paths.insert(node_path.clone());
                         }
+                    }
+
+                    let total_items = node.children.len() + node.repositories.len();
+                    if total_items > 0 {
+                        ui.colored_label(
+                            egui::Color32::DARK_GRAY,
+                            self.localizer
+                                .tf("elements_count", &[&total_items.to_string()]),
+                        );
+                    }
+                } else {
+                    ui.horizontal(|ui| {
+                        Button::icon(IconType::Folder).show(ui, &mut self.icon_manager);
+                        ui.label(&node.name);
                     });
+                }
+            });
+
+            let node_path = node.path.to_string_lossy().to_string();
+            if self.collapsed_paths.contains(&node_path) && depth > 0 {
+                return;
+            }
+        }
+
+        for child in &node.children {
+            if child.is_group {
+                // Репозиторий может попасть в несколько групп — у каждой
+                // свои id виджетов строк
+                ui.push_id(&child.path, |ui| {
+                    self.render_tree_node(ui, child, workspace, depth + 1, to_remove);
                 });
+            } else {
+                self.render_tree_node(ui, child, workspace, depth + 1, to_remove);
+            }
+        }
+
+        let repos_count = node.repositories.len();
+        for (repo_index, (original_idx, _)) in node.repositories.iter().enumerate() {
+            if let Some(repo) = workspace.get(*original_idx) {
+                let indent = ((depth + 1) as f32) * 20.0;
+                self.render_repo_row(ui, repo, *original_idx, indent, to_remove);
 
                 if repo_index < repos_count - 1 {
                     ui.add_space(0.0);
@@ -387,17 +4859,98 @@ impl MyApp {
             }
         }
     }
+
+    /// Fresh git status of one repository, after a load or an operation.
+    fn apply_repo_status(
+        &mut self,
+        repo_path: PathBuf,
+        git_info: Box<GitInfo>,
+        pending_logs: &mut Vec<(LogLevel, String, Option<PathBuf>)>,
+    ) {
+        // Завершилась операция пользователя (pull/push/fetch)
+        let finished = self.runtime.finish_op(&repo_path);
+        if finished.was_syncing {
+            self.mark_workspace_modified(&repo_path);
+        }
+        if finished.was_pushing {
+            self.offer_pull_request(&repo_path, &git_info);
+        }
+        if finished.was_fetching {
+            self.record_fetch(&repo_path);
+        }
+        self.refresh_description(&repo_path);
+        self.runtime.resolve_error(&repo_path);
+        self.dirty_noise.remove(&repo_path);
+        self.reload_branches_view(&repo_path);
+        self.conflict_operation_done(&repo_path, true);
+        if git_info.bisecting {
+            self.watch_bisect(&repo_path);
+        }
+
+        if self.pending_git_loads > 0 {
+            self.pending_git_loads -= 1;
+        }
+        // Сводка пересчитывается после массовой операции или загрузки
+        if self.runtime.is_idle() && (finished.was_syncing || self.pending_git_loads == 0) {
+            self.refresh_dashboard();
+        }
+
+        if let Some(repo_name) = repo_path.file_name() {
+            if self.pending_git_loads == 0 {
+                pending_logs.push((
+                    LogLevel::Info,
+                    self.localizer
+                        .tf("repo_loaded_last", &[&repo_name.to_string_lossy()]),
+                    Some(repo_path.clone()),
+                ));
+            } else {
+                pending_logs.push((
+                    LogLevel::Info,
+                    self.localizer.tf(
+                        "repo_loaded_remaining",
+                        &[
+                            &repo_name.to_string_lossy(),
+                            &self.pending_git_loads.to_string(),
+                        ],
+                    ),
+                    Some(repo_path.clone()),
+                ));
+            }
+        }
+
+        // Полный список веток устарел, если их число изменилось
+        if self
+            .all_branches
+            .get(&repo_path)
+            .is_some_and(|all| all.len() != git_info.branch_count_total)
+        {
+            self.all_branches.remove(&repo_path);
+        }
+
+        let mut needs_project_type = false;
+        if let Some(repo) = find_workspace_containing(&mut self.config.workspaces, &repo_path)
+            .and_then(|workspace| workspace.find_repository_mut(&repo_path))
+        {
+            repo.update_git_info(*git_info);
+            needs_project_type = repo.project_type.is_none();
+        }
+        if needs_project_type {
+            self.request_project_type(repo_path);
+        }
+    }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.apply_tooltip_style(ctx);
+
         if self.first_startup {
             self.first_startup = false;
 
             if !self.config.workspaces.is_empty() {
-                self.load_workspace(self.active_workspace_idx);
+                self.start_prioritized_load();
 
-                if let Some(workspace) = self.config.workspaces.get(self.active_workspace_idx) {
+                if let Some(workspace) = self.get_active_workspace() {
                     self.logger.info(self.localizer.tf(
                         "loading_workspace",
                         &[&workspace.name, &workspace.repositories.len().to_string()],
@@ -406,6 +4959,46 @@ impl eframe::App for MyApp {
             }
         }
 
+        self.handle_keyboard_shortcuts(ctx);
+
+        self.poll_tray(ctx);
+
+        let (focused, minimized) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (
+                viewport.focused.unwrap_or(true),
+                viewport.minimized.unwrap_or(false),
+            )
+        });
+        // Окно в трее считается свёрнутым: при возврате сработает догоняющее обновление
+        let low_power_heartbeat = self.update_power_state(
+            focused && !self.hidden_to_tray,
+            minimized || self.hidden_to_tray,
+        );
+
+        // Закрытие окна во время git-операций сначала спрашивает пользователя
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        let tray_available = matches!(self.tray, Some(Some(_)));
+        if close_requested
+            && close_action(
+                self.config.close_to_tray,
+                tray_available,
+                self.quitting_from_tray,
+            ) == CloseAction::HideToTray
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+            self.hidden_to_tray = true;
+        } else if close_requested && !self.shutdown.close_requested(self.is_busy()) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        }
+        if self
+            .shutdown
+            .tick(self.is_busy(), std::time::Instant::now())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+
         let size = ctx.input(|i| i.screen_rect().size());
         if size.x > 0.0 && size.y > 0.0 {
             let current_width = self.config.window_width.unwrap_or(0.0);
@@ -413,13 +5006,7 @@ impl eframe::App for MyApp {
             if (size.x - current_width).abs() > 1.0 || (size.y - current_height).abs() > 1.0 {
                 self.config.window_width = Some(size.x);
                 self.config.window_height = Some(size.y);
-
-                if self.search_status_timer.is_none()
-                    || self.search_status_timer.unwrap().elapsed()
-                        > std::time::Duration::from_secs(1)
-                {
-                    self.save_config();
-                }
+                self.mark_config_dirty();
             }
         }
 
@@ -440,144 +5027,285 @@ impl eframe::App for MyApp {
         }
 
         for msg in messages {
+            if let AppMessage::Git(git_msg) = &msg {
+                self.advance_load_scheduler(git_msg);
+                self.advance_folder_operation(git_msg);
+                self.advance_refresh_after_job(git_msg);
+                self.advance_branch_switch(git_msg);
+                self.advance_branch_prune(git_msg);
+            }
+
             match msg {
                 AppMessage::Git(GitMessage::RepoStatusUpdated {
                     repo_path,
                     git_info,
                 }) => {
-                    self.syncing_repos.remove(&repo_path);
-                    self.error_repos.remove(&repo_path);
-
-                    if self.pending_git_loads > 0 {
-                        self.pending_git_loads -= 1;
-                    }
-
-                    if let Some(repo_name) = repo_path.file_name() {
-                        if self.pending_git_loads == 0 {
-                            pending_logs.push((
-                                LogLevel::Info,
-                                self.localizer
-                                    .tf("repo_loaded_last", &[&repo_name.to_string_lossy()]),
-                            ));
-                        } else {
-                            pending_logs.push((
-                                LogLevel::Info,
-                                self.localizer.tf(
-                                    "repo_loaded_remaining",
-                                    &[
-                                        &repo_name.to_string_lossy(),
-                                        &self.pending_git_loads.to_string(),
-                                    ],
-                                ),
-                            ));
-                        }
+                    self.apply_repo_status(repo_path, git_info, &mut pending_logs);
+                }
+                AppMessage::Git(GitMessage::FetchComplete {
+                    repo_path,
+                    git_info,
+                    stats,
+                }) => {
+                    if let Some(stats) = stats {
+                        let repo_name = repo_path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        pending_logs.push((
+                            LogLevel::Info,
+                            self.localizer
+                                .tf("fetch_stats", &[&repo_name, &stats.summary()]),
+                            Some(repo_path.clone()),
+                        ));
                     }
-
+                    self.apply_repo_status(repo_path, git_info, &mut pending_logs);
+                }
+                AppMessage::ProjectTypeDetected {
+                    repo_path,
+                    project_type,
+                } => {
                     for workspace in &mut self.config.workspaces {
                         if let Some(repo) = workspace.find_repository_mut(&repo_path) {
-                            repo.update_git_info(git_info.clone());
-
-                            if self.is_loading_on_startup {
-                                self.startup_loaded_repos += 1;
-                                let total_repos: usize = self
-                                    .config
-                                    .workspaces
-                                    .iter()
-                                    .map(|w| w.repositories.len())
-                                    .sum();
-
-                                if self.startup_loaded_repos >= total_repos {
-                                    self.is_loading_on_startup = false;
-                                    self.search_status = Some(self.localizer.t("all_repos_loaded"));
-                                    self.search_status_timer = Some(std::time::Instant::now());
-                                } else {
-                                    self.search_status = Some(self.localizer.tf(
-                                        "loaded_count",
-                                        &[
-                                            &self.startup_loaded_repos.to_string(),
-                                            &total_repos.to_string(),
-                                        ],
-                                    ));
-                                }
-                            }
-                            break;
+                            repo.project_type = Some(project_type);
                         }
                     }
                 }
-                AppMessage::Git(GitMessage::Error(err)) => {
-                    pending_logs.push((LogLevel::Error, format!("Git error: {}", err)));
-
-                    if let Some(start) = err.find('"') {
-                        if let Some(end) = err[start + 1..].find('"') {
-                            let path_str = &err[start + 1..start + 1 + end];
-                            let path = PathBuf::from(path_str);
-                            self.syncing_repos.remove(&path);
-                            self.error_repos.insert(path);
-                        }
-                    }
-
-                    if self.is_loading_on_startup {
-                        self.startup_loaded_repos += 1;
-                        let total_repos: usize = self
-                            .config
-                            .workspaces
-                            .iter()
-                            .map(|w| w.repositories.len())
-                            .sum();
-
-                        if self.startup_loaded_repos >= total_repos {
-                            self.is_loading_on_startup = false;
-                            self.search_status = Some(self.localizer.t("loading_complete_errors"));
-                            self.search_status_timer = Some(std::time::Instant::now());
-                        } else {
-                            self.search_status = Some(self.localizer.tf(
-                                "loaded_count",
-                                &[
-                                    &self.startup_loaded_repos.to_string(),
-                                    &total_repos.to_string(),
-                                ],
-                            ));
+                AppMessage::Git(GitMessage::PullConflicts { repo_path, files }) => {
+                    // Конфликт случился уже после fetch
+                    if self.runtime.finish_op(&repo_path).was_fetching {
+                        self.record_fetch(&repo_path);
+                    }
+                    self.show_pull_conflicts(repo_path.clone(), files);
+                    if let Some(tx) = &self.app_sender {
+                        refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
+                    }
+                }
+                AppMessage::Git(GitMessage::ConflictsChecked { repo_path, files }) => {
+                    if let Some(panel) = self.conflict_panels.get_mut(&repo_path) {
+                        panel.checked(files);
+                    }
+                }
+                AppMessage::Git(GitMessage::OperationCancelled { repo_path }) => {
+                    self.runtime.finish_op(&repo_path);
+                    let repo_name = repo_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    pending_logs.push((
+                        LogLevel::Info,
+                        self.localizer.tf("operation_cancelled", &[&repo_name]),
+                        Some(repo_path),
+                    ));
+                }
+                AppMessage::Git(GitMessage::OperationOutput { repo_path, line }) => {
+                    // Имя репозитория показывает бейдж контекста
+                    pending_logs.push((LogLevel::Info, line, Some(repo_path)));
+                }
+                AppMessage::Git(GitMessage::CommitLogLoaded {
+                    repo_path,
+                    commits,
+                    notes,
+                }) => {
+                    if let Some(view) = &mut self.commit_log {
+                        if view.repo_path == repo_path {
+                            view.commits = Some(commits);
+                            view.notes = notes;
                         }
                     }
                 }
-                AppMessage::ReposFound { repos } => {
-                    self.is_searching = false;
-
-                    let mut added_count = 0;
-                    let mut repos_to_refresh = Vec::new();
-
-                    if let Some(workspace) = self.get_active_workspace_mut() {
-                        for repo_path in repos {
-                            if workspace.add_repository(repo_path.clone()) {
-                                added_count += 1;
-                                repos_to_refresh.push(repo_path);
+                AppMessage::Git(GitMessage::CommitGraphLoaded { repo_path, lines }) => {
+                    if let Some(view) = &mut self.commit_log {
+                        if view.repo_path == repo_path {
+                            view.graph = Some(lines);
+                        }
+                    }
+                }
+                AppMessage::Git(GitMessage::CommitDiffLoaded {
+                    repo_path,
+                    hash,
+                    diff,
+                }) => {
+                    // Окно могли закрыть, пока diff загружался
+                    if let Some(view) = self
+                        .commit_log
+                        .as_mut()
+                        .filter(|view| view.repo_path == repo_path)
+                    {
+                        match diff {
+                            Ok(diff_text) => self.cache_commit_diff(hash, diff_text),
+                            Err(error) => {
+                                view.diff_errors.insert(hash, error);
                             }
                         }
                     }
-
-                    if let Some(tx) = &self.app_sender {
-                        for repo_path in repos_to_refresh {
+                }
+                AppMessage::Git(GitMessage::FileAtCommitLoaded {
+                    repo_path,
+                    hash,
+                    file_path,
+                    content,
+                }) => {
+                    if let Some(view) = &mut self.file_at_commit {
+                        if view.repo_path == repo_path
+                            && view.hash == hash
+                            && view.file_path == file_path
+                        {
+                            if let Ok(bytes) = &content {
+                                view.text = String::from_utf8_lossy(bytes).into_owned();
+                            }
+                            view.content = Some(content);
+                        }
+                    }
+                }
+                AppMessage::Git(GitMessage::StashDiffLoaded {
+                    repo_path,
+                    index,
+                    diff,
+                }) => {
+                    if let Some(window) = &mut self.stash_window {
+                        if window.repo_path == repo_path {
+                            window.loading.remove(&index);
+                            window.diffs.insert(index, diff);
+                        }
+                    }
+                }
+                AppMessage::Git(GitMessage::BisectStateChanged {
+                    repo_path,
+                    current_hash,
+                    remaining_steps,
+                }) => {
+                    self.bisect_states.insert(
+                        repo_path,
+                        BisectState {
+                            current_hash,
+                            remaining_steps,
+                        },
+                    );
+                }
+                AppMessage::Git(GitMessage::BisectEnded { repo_path }) => {
+                    self.bisect_watchers.remove(&repo_path);
+                    if self.bisect_states.remove(&repo_path).is_some() {
+                        if let Some(tx) = &self.app_sender {
                             refresh_repo_status_async::<AppMessage>(repo_path, tx.clone());
                         }
                     }
-
-                    if added_count > 0 {
-                        self.save_config();
+                }
+                AppMessage::Git(GitMessage::BranchesLoaded {
+                    repo_path,
+                    branches,
+                }) => {
+                    self.loading_branches.remove(&repo_path);
+                    self.all_branches.insert(repo_path, branches);
+                    if self.branch_switch_dialog.is_some() {
+                        self.refresh_branch_switch_plan();
+                    }
+                }
+                AppMessage::Git(GitMessage::DigestReady { tips, deltas, auto }) => {
+                    self.apply_digest(tips, deltas, auto);
+                }
+                AppMessage::Git(GitMessage::ForcePushed {
+                    repo_path,
+                    commit_before,
+                    commit_after,
+                }) => {
+                    self.finish_force_push(&repo_path, commit_before, commit_after);
+                }
+                AppMessage::Git(GitMessage::MirrorPushed { repo_path, remote }) => {
+                    let repo_name = repo_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    pending_logs.push((
+                        LogLevel::Warning,
+                        self.localizer
+                            .tf("mirror_push_finished", &[&repo_name, &remote]),
+                        Some(repo_path),
+                    ));
+                }
+                AppMessage::Git(GitMessage::CommitActivityLoaded {
+                    repo_path,
+                    activity,
+                }) => {
+                    self.commit_activity_loaded(repo_path, activity);
+                }
+                AppMessage::Git(GitMessage::DirtyNoiseChecked { repo_path, noise }) => {
+                    // Статус успел обновиться, пока шла проверка
+                    if let Some(entry) = self.dirty_noise.get_mut(&repo_path) {
+                        *entry = Some(noise);
+                    }
+                }
+                AppMessage::Git(GitMessage::ContributorsLoaded {
+                    repo_path,
+                    since,
+                    contributors,
+                }) => {
+                    self.contributor_cache
+                        .insert((repo_path, since), contributors);
+                }
+                AppMessage::Git(GitMessage::SnapshotTaken { snapshot }) => {
+                    self.snapshot_taken(snapshot);
+                }
+                AppMessage::Git(GitMessage::SnapshotCompared { taken_at, changes }) => {
+                    self.snapshot_compared(taken_at, changes);
+                }
+                AppMessage::Git(GitMessage::TagsPushed { repo_path, count }) => {
+                    let repo_name = repo_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    pending_logs.push((
+                        LogLevel::Info,
+                        self.localizer
+                            .tf("tags_pushed", &[&count.to_string(), &repo_name]),
+                        Some(repo_path),
+                    ));
+                }
+                // Уже обработаны в advance_branch_prune
+                AppMessage::Git(
+                    GitMessage::MergedBranchesFound { .. }
+                    | GitMessage::MergedBranchesScanFailed { .. }
+                    | GitMessage::BranchesPruned { .. },
+                ) => {}
+                AppMessage::Git(GitMessage::Error(err)) => {
+                    // Чужой владелец репозитория: каждая команда падает одинаково,
+                    // в лог пишем только первый раз
+                    let repeated_ownership_error = classify_git_error(&err)
+                        == GitErrorKind::DubiousOwnership
+                        && error_repo_path(&err)
+                            .and_then(|path| self.runtime.errors().get(&path))
+                            .is_some_and(|e| e.kind == GitErrorKind::DubiousOwnership);
+                    if !repeated_ownership_error {
                         pending_logs.push((
-                            LogLevel::Info,
-                            self.localizer
-                                .tf("added_repos_log", &[&added_count.to_string()]),
+                            LogLevel::Error,
+                            format!("Git error: {}", err),
+                            error_repo_path(&err),
                         ));
-                        self.search_status = Some(
-                            self.localizer
-                                .tf("added_repos", &[&added_count.to_string()]),
-                        );
-                    } else {
-                        pending_logs
-                            .push((LogLevel::Warning, self.localizer.t("no_new_repos_log")));
-                        self.search_status = Some(self.localizer.t("no_repos_found"));
                     }
-                    self.search_status_timer = Some(std::time::Instant::now());
+
+                    if let Some(path) = error_repo_path(&err) {
+                        self.conflict_operation_done(&path, false);
+                        self.runtime.finish_op(&path);
+                        // Ошибка удалённого репозитория не должна висеть в состоянии
+                        if self.is_known_repo(&path) {
+                            self.runtime.set_error(path, err.clone());
+                        }
+                    }
+                }
+                AppMessage::ReposFound {
+                    root,
+                    repos,
+                    ms_per_dir,
+                } => {
+                    self.is_searching = false;
+                    if self.config.actual_ms_per_dir.is_none() && ms_per_dir.is_some() {
+                        self.config.actual_ms_per_dir = ms_per_dir;
+                        self.mark_config_dirty();
+                    }
+                    self.handle_found_repositories(root, repos);
                 }
                 AppMessage::SearchComplete { total_found } => {
                     self.is_searching = false;
@@ -590,11 +5318,20 @@ impl eframe::App for MyApp {
             }
         }
 
-        for (level, message) in pending_logs {
-            match level {
-                LogLevel::Info => self.logger.info(message),
-                LogLevel::Warning => self.logger.warning(message),
-                LogLevel::Error => self.logger.error(message),
+        for warning in take_audit_warnings() {
+            pending_logs.push((
+                LogLevel::Warning,
+                self.localizer.tf("audit_write_failed", &[&warning]),
+                None,
+            ));
+        }
+
+        for (level, message, context) in pending_logs {
+            match (level, context) {
+                (level, Some(repo)) => self.logger.with_structured_context(level, message, &repo),
+                (LogLevel::Info, None) => self.logger.info(message),
+                (LogLevel::Warning, None) => self.logger.warning(message),
+                (LogLevel::Error, None) => self.logger.error(message),
             }
         }
 
@@ -602,12 +5339,23 @@ impl eframe::App for MyApp {
             if !i.raw.dropped_files.is_empty() {
                 for file in &i.raw.dropped_files {
                     if let Some(path) = &file.path {
+                        // Брошенный .patch/.diff при открытом диалоге идёт в него
+                        if let Some(dialog) = &mut self.patch_dialog {
+                            let is_patch = path.extension().is_some_and(|ext| {
+                                ext.eq_ignore_ascii_case("patch")
+                                    || ext.eq_ignore_ascii_case("diff")
+                            });
+                            if is_patch {
+                                dialog.patch_path = path.display().to_string();
+                                continue;
+                            }
+                        }
                         if path.is_dir() {
                             if self.config.workspaces.is_empty() {
                                 self.config
                                     .workspaces
                                     .push(Workspace::new("Default Workspace"));
-                                self.active_workspace_idx = 0;
+                                self.set_active_workspace(0);
                             }
                             self.add_repository(path.clone());
                         }
@@ -632,147 +5380,187 @@ impl eframe::App for MyApp {
             let new_width = ui.available_width();
             if !is_editing && (self.config.sidebar_width - new_width).abs() > 1.0 {
                 self.config.sidebar_width = new_width;
+                self.mark_config_dirty();
+            }
+
+            ui.set_max_width(self.config.sidebar_width);
+
+            ui.heading(&self.localizer.t("workspaces"));
+
+            let recent_count = self.recent_repositories().len();
+            if ui
+                .selectable_label(
+                    self.show_recent,
+                    self.localizer
+                        .tf("recent_workspace_count", &[&recent_count.to_string()]),
+                )
+                .on_hover_text(self.localizer.tf(
+                    "recent_workspace_hint",
+                    &[&self.config.recent_days.to_string()],
+                ))
+                .clicked()
+            {
+                self.show_recent_workspace();
+            }
+            ui.separator();
+
+            // Статус поиска закреплён под списком и его кнопками
+            if let Some(status) = &self.search_status {
+                egui::TopBottomPanel::bottom("workspaces_status")
+                    .show_separator_line(false)
+                    .show_inside(ui, |ui| {
+                        ui.separator();
+                        if self.is_searching
+                            || self.load_scheduler.is_running()
+                            || self.folder_operation.is_some()
+                        {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                let color = if self.load_scheduler.is_running() {
+                                    egui::Color32::from_rgb(100, 150, 255)
+                                } else {
+                                    egui::Color32::from_rgb(100, 150, 200)
+                                };
+                                ui.colored_label(color, status);
+                            });
+                        } else {
+                            ui.colored_label(egui::Color32::from_rgb(100, 150, 100), status);
+                        }
+                    });
+            }
+
+            let mut state = WorkspacePanelState {
+                workspaces: &self.config.workspaces,
+                active_idx: self.active_workspace_idx(),
+                show_recent: self.show_recent,
+                editing: &mut self.editing_workspace,
+                new_name: &mut self.new_workspace_name,
+                localizer: &self.localizer,
+                icon_manager: &mut self.icon_manager,
+            };
+            if let Some(action) = self.workspace_panel.show(ui, &mut state) {
+                self.apply_workspace_action(action);
             }
+        });
+
+        if self.show_settings {
+            self.render_settings_window(ctx);
+        }
+
+        if self.tag_window_repo.is_some() {
+            self.render_tags_window(ctx);
+        }
+
+        if self.commit_log.is_some() {
+            self.render_commit_log_window(ctx);
+        }
+        if self.file_at_commit.is_some() {
+            self.render_file_at_commit_window(ctx);
+        }
 
-            ui.set_max_width(self.config.sidebar_width);
+        if self.stale_refs.is_some() {
+            self.render_stale_refs_window(ctx);
+        }
 
-            ui.heading(&self.localizer.t("workspaces"));
+        if self.remotes.is_some() {
+            self.render_remotes_window(ctx);
+        }
+        if self.env_overrides.is_some() {
+            self.render_env_overrides_window(ctx);
+        }
+        if self.dirty_noise_view.is_some() {
+            self.render_dirty_noise_window(ctx);
+        }
 
-            let mut to_remove = None;
-            let mut to_rename = None;
-            let mut should_add_workspace = false;
-            let mut switch_to_workspace_idx: Option<usize> = None;
+        if self.contributors.is_some() {
+            self.render_contributors_window(ctx);
+        }
 
-            for (idx, workspace) in self.config.workspaces.iter().enumerate() {
-                ui.horizontal(|ui| {
-                    if self.editing_workspace == Some(idx) {
-                        let available_width = ui.available_width();
-                        let button_width = 50.0;
-                        let input_width = available_width - button_width - 15.0;
-
-                        ui.scope(|ui| {
-                            ui.spacing_mut().item_spacing.x = 0.0;
-                            ui.style_mut().spacing.indent = 0.0;
-
-                            ui.set_max_width(input_width);
-                            ui.set_min_width(input_width);
-
-                            let response = ui.add_sized(
-                                [input_width, 20.0],
-                                egui::TextEdit::singleline(&mut self.new_workspace_name)
-                                    .desired_width(input_width)
-                                    .clip_text(true),
-                            );
+        if self.ignored_files.is_some() {
+            self.render_ignored_files_window(ctx);
+        }
+        if self.monorepo_services.is_some() {
+            self.render_monorepo_services_window(ctx);
+        }
+        if self.snapshot_diff.is_some() {
+            self.render_snapshot_diff_window(ctx);
+        }
+        if !self.conflict_panels.is_empty() {
+            self.render_conflict_windows(ctx);
+        }
+        if self.branches_view.is_some() {
+            self.render_branches_window(ctx);
+        }
+        if self.cherry.is_some() {
+            self.render_cherry_window(ctx);
+        }
 
-                            if response.lost_focus()
-                                && ui.input(|i| i.key_pressed(egui::Key::Enter))
-                            {
-                                to_rename = Some((idx, self.new_workspace_name.clone()));
-                            }
-                        });
+        if self.audit_history.is_some() {
+            self.render_audit_history_window(ctx);
+        }
 
-                        if Button::icon(IconType::Check)
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            to_rename = Some((idx, self.new_workspace_name.clone()));
-                        }
-                        if Button::icon(IconType::Cross)
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            self.editing_workspace = None;
-                        }
-                    } else {
-                        let available_width = ui.available_width();
-                        let button_width = 50.0;
-                        let name_width = available_width - button_width;
-
-                        ui.allocate_ui_with_layout(
-                            egui::Vec2::new(name_width, 25.0),
-                            egui::Layout::left_to_right(egui::Align::Center),
-                            |ui| {
-                                let mut temp_active_idx = self.active_workspace_idx;
-                                if ui
-                                    .selectable_value(&mut temp_active_idx, idx, &workspace.name)
-                                    .clicked()
-                                {
-                                    if temp_active_idx != self.active_workspace_idx {
-                                        switch_to_workspace_idx = Some(temp_active_idx);
-                                    }
-                                }
-                            },
-                        );
+        if self.patch_dialog.is_some() {
+            self.render_patch_window(ctx);
+        }
 
-                        if Button::icon(IconType::Edit)
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            self.editing_workspace = Some(idx);
-                            self.new_workspace_name = workspace.name.clone();
-                        }
-                        if Button::icon(IconType::Trash)
-                            .show(ui, &mut self.icon_manager)
-                            .clicked()
-                        {
-                            to_remove = Some(idx);
-                        }
-                    }
-                });
-            }
+        if self.stash_window.is_some() {
+            self.render_stash_window(ctx);
+        }
 
-            if ui.button(&self.localizer.t("new_workspace")).clicked() {
-                should_add_workspace = true;
-            }
+        if self.show_workspace_switcher {
+            self.render_workspace_switcher(ctx);
+        }
 
-            ui.separator();
+        if self.digest.is_some() {
+            self.render_digest_window(ctx);
+        }
 
-            if let Some((idx, new_name)) = to_rename {
-                if let Some(ws) = self.config.workspaces.get_mut(idx) {
-                    ws.name = new_name;
-                    self.save_config();
-                }
-                self.editing_workspace = None;
-            }
+        if self.repath_dialog.is_some() {
+            self.render_repath_window(ctx);
+        }
 
-            if let Some(idx) = to_remove {
-                self.config.workspaces.remove(idx);
-                if self.active_workspace_idx >= self.config.workspaces.len() {
-                    self.active_workspace_idx = self.config.workspaces.len().saturating_sub(1);
-                }
-                self.save_config();
-            }
+        if self.archive_dialog.is_some() {
+            self.render_archive_window(ctx);
+        }
 
-            if should_add_workspace {
-                let new_workspace =
-                    Workspace::new(format!("Workspace {}", self.config.workspaces.len() + 1));
-                self.config.workspaces.push(new_workspace);
-                self.save_config();
-            }
+        if self.force_push_dialog.is_some() {
+            self.render_force_push_window(ctx);
+        }
+        if self.branch_switch_dialog.is_some() {
+            self.render_branch_switch_window(ctx);
+        }
+        if self.prune_dialog.is_some() {
+            self.render_prune_window(ctx);
+        }
+        if self.scan_split_dialog.is_some() {
+            self.render_scan_split_window(ctx);
+        }
+        if self.scan_preview.is_some() {
+            self.render_scan_preview_window(ctx);
+        }
+        if self.dashboard.is_some() {
+            self.render_dashboard_window(ctx);
+        }
+        if self.show_duplicates_report {
+            self.render_duplicates_window(ctx);
+        }
 
-            if let Some(idx) = switch_to_workspace_idx {
-                self.logger
-                    .info(self.localizer.tf("switch_workspace", &[&idx.to_string()]));
-                self.switch_to_workspace(idx);
-            }
+        if self.pending_confirmation.is_some() {
+            self.render_confirmation_window(ctx);
+        }
+        if self.removed_workspace.is_some() {
+            self.render_workspace_undo_toast(ctx);
+        }
+        if matches!(
+            self.shutdown,
+            AppShutdownState::Confirming | AppShutdownState::Waiting { .. }
+        ) {
+            self.render_shutdown_window(ctx);
+        }
 
-            if let Some(status) = &self.search_status {
-                ui.separator();
-                if self.is_searching || self.is_loading_on_startup {
-                    ui.horizontal(|ui| {
-                        ui.spinner();
-                        let color = if self.is_loading_on_startup {
-                            egui::Color32::from_rgb(100, 150, 255)
-                        } else {
-                            egui::Color32::from_rgb(100, 150, 200)
-                        };
-                        ui.colored_label(color, status);
-                    });
-                } else {
-                    ui.colored_label(egui::Color32::from_rgb(100, 150, 100), status);
-                }
-            }
-        });
+        if self.terminal_pane.is_some() {
+            self.render_terminal_panel(ctx);
+        }
 
         if self.show_logs {
             egui::TopBottomPanel::bottom("logs_panel")
@@ -783,6 +5571,36 @@ impl eframe::App for MyApp {
                     ui.horizontal(|ui| {
                         ui.heading(&self.localizer.t("logs"));
 
+                        let all_repos = self.localizer.t("log_all_repos");
+                        let contexts = self.logger.contexts();
+                        // Репозиторий мог пропасть из лога после очистки или вытеснения
+                        if self
+                            .log_repo_filter
+                            .as_ref()
+                            .is_some_and(|filter| !contexts.contains(filter))
+                        {
+                            self.log_repo_filter = None;
+                        }
+                        ui.label(self.localizer.t("log_filter_by_repo"));
+                        egui::ComboBox::from_id_source("log_repo_filter")
+                            .selected_text(
+                                self.log_repo_filter
+                                    .as_ref()
+                                    .map(|p| p.display().to_string())
+                                    .unwrap_or_else(|| all_repos.clone()),
+                            )
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.log_repo_filter, None, &all_repos);
+                                for path in contexts {
+                                    let label = path.display().to_string();
+                                    ui.selectable_value(
+                                        &mut self.log_repo_filter,
+                                        Some(path),
+                                        label,
+                                    );
+                                }
+                            });
+
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             if ui.button(&self.localizer.t("clear")).clicked() {
                                 self.logger.clear();
@@ -796,7 +5614,27 @@ impl eframe::App for MyApp {
                         .auto_shrink([false, true])
                         .stick_to_bottom(true)
                         .show(ui, |ui| {
-                            for log_entry in self.logger.logs() {
+                            let filter = self.log_repo_filter.as_deref();
+                            for (idx, log_entry) in self
+                                .logger
+                                .logs()
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, log_entry)| log_entry.matches_context(filter))
+                            {
+                                // Кликабельная подложка строки: регистрируется раньше
+                                // содержимого, чтобы ссылки оставались кликабельными
+                                let row_rect = egui::Rect::from_min_size(
+                                    ui.cursor().min,
+                                    egui::vec2(ui.available_width(), ui.spacing().interact_size.y),
+                                );
+                                let row = ui.interact(
+                                    row_rect,
+                                    ui.id().with(("log_row", idx)),
+                                    egui::Sense::click(),
+                                );
+                                let row_hovered = ui.rect_contains_pointer(row_rect);
+
                                 ui.horizontal(|ui| {
                                     ui.colored_label(
                                         log_entry.level.color(),
@@ -804,21 +5642,64 @@ impl eframe::App for MyApp {
                                     );
 
                                     if let Ok(duration) = log_entry.timestamp.elapsed() {
-                                        let seconds = duration.as_secs();
-                                        let time_text = if seconds < 60 {
-                                            format!("{}s", seconds)
-                                        } else if seconds < 3600 {
-                                            format!("{}m", seconds / 60)
-                                        } else {
-                                            format!("{}h", seconds / 3600)
-                                        };
                                         ui.colored_label(
                                             egui::Color32::DARK_GRAY,
-                                            format!("[{}]", time_text),
+                                            format!("[{}]", format_elapsed(duration)),
                                         );
                                     }
 
+                                    if let Some(repo_name) = log_entry.context_name() {
+                                        egui::Frame::none()
+                                            .fill(ui.visuals().faint_bg_color)
+                                            .rounding(3.0)
+                                            .inner_margin(egui::Margin::symmetric(4.0, 0.0))
+                                            .show(ui, |ui| {
+                                                ui.label(egui::RichText::new(repo_name).small());
+                                            });
+                                    }
+
                                     ui.colored_label(log_entry.level.color(), &log_entry.message);
+                                    if let Some(link) = &log_entry.link {
+                                        ui.hyperlink_to(link, link);
+                                    }
+                                    if row_hovered {
+                                        let copy = ui
+                                            .add(
+                                                egui::Label::new(
+                                                    egui::RichText::new("📋")
+                                                        .color(egui::Color32::DARK_GRAY),
+                                                )
+                                                .sense(egui::Sense::click()),
+                                            )
+                                            .on_hover_text(self.localizer.t("log_copy_hint"));
+                                        if copy.clicked() {
+                                            ui.output_mut(|o| {
+                                                o.copied_text = log_entry.message.clone()
+                                            });
+                                        }
+                                    }
+                                });
+
+                                row.context_menu(|ui| {
+                                    if ui.button(self.localizer.t("log_copy_message")).clicked() {
+                                        ui.output_mut(|o| {
+                                            o.copied_text = log_entry.message.clone()
+                                        });
+                                        ui.close_menu();
+                                    }
+                                    if ui
+                                        .button(self.localizer.t("log_copy_with_timestamp"))
+                                        .clicked()
+                                    {
+                                        ui.output_mut(|o| {
+                                            o.copied_text = log_entry.with_timestamp()
+                                        });
+                                        ui.close_menu();
+                                    }
+                                    if ui.button(self.localizer.t("log_search_online")).clicked() {
+                                        opener::open(log_entry.search_url()).ok();
+                                        ui.close_menu();
+                                    }
                                 });
                             }
                         });
@@ -835,24 +5716,145 @@ impl eframe::App for MyApp {
 
             let mut should_fetch_all = false;
 
-            if self.active_workspace_idx >= self.config.workspaces.len() {
-                self.active_workspace_idx = self.config.workspaces.len().saturating_sub(1);
-            }
+            let workspace_name = if self.show_recent {
+                self.localizer.t("recent_workspace")
+            } else {
+                self.get_active_workspace()
+                    .map(|w| w.name.clone())
+                    .unwrap_or_default()
+            };
+
+            let mut should_refresh_all = false;
 
-            let workspace_name = self
+            let (workspace_color, workspace_environment) = self
                 .get_active_workspace()
-                .map(|w| w.name.clone())
+                .filter(|_| !self.show_recent)
+                .map(|w| (w.color, w.environment.clone()))
                 .unwrap_or_default();
 
-            let mut should_refresh_all = false;
-
             ui.horizontal(|ui| {
-                ui.heading(&workspace_name);
-                if ui.button(&self.localizer.t("fetch_all")).clicked() {
-                    should_fetch_all = true;
+                let tint = workspace_color
+                    .map(|[r, g, b]| egui::Color32::from_rgba_unmultiplied(r, g, b, 60))
+                    .unwrap_or(egui::Color32::TRANSPARENT);
+                egui::Frame::none()
+                    .fill(tint)
+                    .rounding(4.0)
+                    .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.heading(&workspace_name);
+                            if workspace_environment != EnvironmentLabel::None {
+                                let label = self.environment_text(&workspace_environment);
+                                let color = if workspace_environment.is_production() {
+                                    egui::Color32::LIGHT_RED
+                                } else {
+                                    egui::Color32::LIGHT_GRAY
+                                };
+                                ui.label(egui::RichText::new(label).strong().color(color));
+                            }
+                        });
+                    });
+                if !self.show_recent {
+                    if ui.button(&self.localizer.t("fetch_all")).clicked() {
+                        should_fetch_all = true;
+                    }
+                    if ui.button(&self.localizer.t("refresh_all")).clicked() {
+                        should_refresh_all = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.branch_switch_batch.is_none(),
+                            egui::Button::new(self.localizer.t("switch_branch_across")),
+                        )
+                        .clicked()
+                    {
+                        self.open_branch_switch_dialog();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.prune_batch.is_none(),
+                            egui::Button::new(self.localizer.t("prune_merged_across")),
+                        )
+                        .clicked()
+                    {
+                        self.open_prune_dialog();
+                    }
+                }
+                if ui
+                    .add_enabled(
+                        !self.digest_loading,
+                        egui::Button::new(self.localizer.t("daily_digest")),
+                    )
+                    .clicked()
+                {
+                    self.generate_digest(false);
+                }
+                if !self.show_recent {
+                    if ui
+                        .add_enabled(
+                            !self.snapshot_loading,
+                            egui::Button::new(self.localizer.t("take_snapshot")),
+                        )
+                        .clicked()
+                    {
+                        self.take_snapshot();
+                    }
+                    let mut compare_with = None;
+                    ui.menu_button(self.localizer.t("compare_with_snapshot"), |ui| {
+                        let snapshots = self.stored_snapshots();
+                        if snapshots.is_empty() {
+                            ui.label(self.localizer.t("no_snapshots"));
+                        }
+                        for snapshot in snapshots {
+                            let taken_at = std::time::UNIX_EPOCH
+                                + std::time::Duration::from_secs(snapshot.taken_at);
+                            let label = self.localizer.tf(
+                                "snapshot_entry",
+                                &[
+                                    &format_relative_time(taken_at),
+                                    &snapshot.repos.len().to_string(),
+                                ],
+                            );
+                            if ui.button(label).clicked() {
+                                compare_with = Some(snapshot);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    if let Some(snapshot) = compare_with {
+                        self.compare_with_snapshot(snapshot);
+                    }
+                }
+
+                let global_label = self.localizer.tf(
+                    "sort_use_global",
+                    &[&self.localizer.t(self.config.sort_mode.label_key())],
+                );
+                let current_sort = self.get_active_workspace().and_then(|w| w.sort_mode);
+                let mut workspace_sort = current_sort;
+                if !self.show_recent {
+                    egui::ComboBox::from_id_source("workspace_sort_mode")
+                        .selected_text(match workspace_sort {
+                            Some(mode) => self.localizer.t(mode.label_key()),
+                            None => global_label.clone(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut workspace_sort, None, global_label);
+                            for mode in SortMode::ALL {
+                                ui.selectable_value(
+                                    &mut workspace_sort,
+                                    Some(mode),
+                                    self.localizer.t(mode.label_key()),
+                                );
+                            }
+                        });
                 }
-                if ui.button(&self.localizer.t("refresh_all")).clicked() {
-                    should_refresh_all = true;
+
+                if workspace_sort != current_sort {
+                    if let Some(workspace) = self.get_active_workspace_mut() {
+                        workspace.sort_mode = workspace_sort;
+                    }
+                    self.save_config();
                 }
 
                 ui.separator();
@@ -881,6 +5883,9 @@ impl eframe::App for MyApp {
                                 {
                                     self.localizer.set_language(&lang_code);
                                     self.config.language = lang_code.to_string();
+                                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::Title(
+                                        self.window_title(),
+                                    ));
                                     self.save_config();
                                 }
                             }
@@ -888,6 +5893,35 @@ impl eframe::App for MyApp {
 
                     ui.separator();
 
+                    if ui.button(self.localizer.t("settings")).clicked() {
+                        self.show_settings = !self.show_settings;
+                    }
+
+                    if ui.button(self.localizer.t("operation_history")).clicked() {
+                        self.open_audit_history();
+                    }
+
+                    if ui
+                        .selectable_label(self.dashboard.is_some(), self.localizer.t("dashboard"))
+                        .clicked()
+                    {
+                        if self.dashboard.is_some() {
+                            self.dashboard = None;
+                        } else {
+                            self.open_dashboard();
+                        }
+                    }
+
+                    if ui
+                        .selectable_label(
+                            self.terminal_pane.is_some(),
+                            self.localizer.t("terminal"),
+                        )
+                        .clicked()
+                    {
+                        self.toggle_terminal(ui.ctx());
+                    }
+
                     let logs_button_text = if self.show_logs {
                         self.localizer.t("hide_logs")
                     } else {
@@ -905,7 +5939,18 @@ impl eframe::App for MyApp {
                         );
                     }
 
-                    if !self.logger.logs().is_empty() {
+                    let (running, queued) = pool_status();
+                    if running > 0 || queued > 0 {
+                        ui.colored_label(
+                            egui::Color32::LIGHT_BLUE,
+                            self.localizer.tf(
+                                "pool_running_queued",
+                                &[&running.to_string(), &queued.to_string()],
+                            ),
+                        );
+                    }
+
+                    if self.logger.total_count() > 0 {
                         let error_count = self.logger.error_count();
                         let warning_count = self.logger.warning_count();
 
@@ -933,36 +5978,55 @@ impl eframe::App for MyApp {
                 });
             });
 
-            if should_fetch_all {
-                if let Some(workspace) = self.get_active_workspace() {
-                    let repo_count = workspace.repository_count();
-                    let repos: Vec<_> = workspace
-                        .repositories
-                        .iter()
-                        .map(|r| r.path.clone())
-                        .collect();
+            let stale_count = self
+                .get_active_workspace()
+                .map(|workspace| {
+                    stale_fetch_count(&workspace.repositories, std::time::SystemTime::now())
+                })
+                .unwrap_or(0);
+            if stale_count > 0 && self.attention_filter != AttentionFilter::NotFetched {
+                let hint = ui
+                    .add(
+                        egui::Label::new(
+                            egui::RichText::new(
+                                self.localizer
+                                    .tf("repos_not_fetched", &[&stale_count.to_string()]),
+                            )
+                            .color(egui::Color32::GRAY),
+                        )
+                        .sense(egui::Sense::click()),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text(self.localizer.t("repos_not_fetched_hint"));
+                if hint.clicked() {
+                    self.attention_filter = AttentionFilter::NotFetched;
+                }
+            }
 
-                    self.logger.info(
-                        self.localizer
-                            .tf("starting_fetch_all", &[&repo_count.to_string()]),
+            let mut repath_prefix = None;
+            for missing in &self.missing_prefixes {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        self.localizer.tf(
+                            "repos_missing_under",
+                            &[
+                                &missing.repo_count.to_string(),
+                                &missing.prefix.display().to_string(),
+                            ],
+                        ),
                     );
-
-                    for (index, repo_path) in repos.into_iter().enumerate() {
-                        self.syncing_repos.insert(repo_path.clone());
-
-                        let delay_ms = index as u64 * 200;
-
-                        if let Some(tx) = &self.app_sender {
-                            let tx_clone = tx.clone();
-                            std::thread::spawn(move || {
-                                if delay_ms > 0 {
-                                    std::thread::sleep(std::time::Duration::from_millis(delay_ms));
-                                }
-                                git_fetch_fast_async_with_retry::<AppMessage>(repo_path, tx_clone);
-                            });
-                        }
+                    if ui.button(self.localizer.t("repath_folder")).clicked() {
+                        repath_prefix = Some(missing.prefix.clone());
                     }
-                }
+                });
+            }
+            if let Some(prefix) = repath_prefix {
+                self.open_repath_dialog(prefix);
+            }
+
+            if should_fetch_all {
+                self.request_fetch_all();
             }
 
             if should_refresh_all {
@@ -975,21 +6039,79 @@ impl eframe::App for MyApp {
                 ui.label(&self.localizer.t("search"));
                 ui.text_edit_singleline(&mut self.search_query);
 
+                egui::ComboBox::from_id_source("attention_filter")
+                    .selected_text(self.localizer.t(self.attention_filter.label_key()))
+                    .show_ui(ui, |ui| {
+                        for filter in AttentionFilter::ALL {
+                            ui.selectable_value(
+                                &mut self.attention_filter,
+                                filter,
+                                self.localizer.t(filter.label_key()),
+                            );
+                        }
+                    });
+
                 ui.separator();
 
-                if ui
-                    .checkbox(
-                        &mut self.config.sort_by_name,
-                        &self.localizer.t("sort_by_name"),
-                    )
-                    .changed()
+                for mode in ViewMode::ALL {
+                    if ui
+                        .selectable_label(
+                            self.config.view_mode == mode,
+                            self.localizer.t(mode.label_key()),
+                        )
+                        .clicked()
+                        && self.config.view_mode != mode
+                    {
+                        self.config.view_mode = mode;
+                        self.save_config();
+                    }
+                }
+
+                let group_button = ui.add_enabled(
+                    self.config.view_mode == ViewMode::Tree,
+                    egui::Button::new(self.localizer.t(self.config.group_mode.label_key())),
+                );
+                if group_button
+                    .on_hover_text(self.localizer.t("group_mode_hint"))
+                    .clicked()
                 {
+                    self.config.group_mode = self.config.group_mode.next();
                     self.save_config();
                 }
+
+                ui.separator();
+
+                ui.label(self.localizer.t("sort_mode"));
+                let mut global_sort = self.config.sort_mode;
+                egui::ComboBox::from_id_source("global_sort_mode")
+                    .selected_text(self.localizer.t(global_sort.label_key()))
+                    .show_ui(ui, |ui| {
+                        for mode in SortMode::ALL {
+                            ui.selectable_value(
+                                &mut global_sort,
+                                mode,
+                                self.localizer.t(mode.label_key()),
+                            );
+                        }
+                    });
+
+                if global_sort != self.config.sort_mode {
+                    self.config.sort_mode = global_sort;
+                    self.mark_config_dirty();
+                }
             });
 
+            self.render_quick_filter_chips(ui);
+
             ui.separator();
 
+            self.render_bookmarks(ui);
+
+            if self.show_recent {
+                self.render_recent_workspace(ui);
+                return;
+            }
+
             if self.get_active_workspace().map_or(true, |w| w.is_empty()) {
                 ui.centered_and_justified(|ui| {
                     ui.label(&self.localizer.t("drag_folders"));
@@ -998,27 +6120,73 @@ impl eframe::App for MyApp {
             }
 
             let to_remove = std::cell::RefCell::new(None);
-            egui::ScrollArea::vertical()
-                .auto_shrink([false, true])
-                .show(ui, |ui| {
-                    if let Some(workspace) = self.get_active_workspace() {
-                        let tree = TreeBuilder::build_tree(
-                            &workspace.repositories,
-                            &self.search_query,
-                            self.config.sort_by_name,
-                        );
-                        let repos = workspace.repositories.clone();
+            if self.config.view_mode == ViewMode::List {
+                if let Some(workspace) = self.get_active_workspace() {
+                    let sort_mode = workspace.sort_mode.unwrap_or(self.config.sort_mode);
+                    let repos = workspace.repositories.clone();
+                    self.render_repo_list(ui, &repos, sort_mode, Some(&to_remove));
+                }
+            } else {
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        let chips = self.active_quick_filters();
+                        if let Some(workspace) = self.get_active_workspace() {
+                            let tree = TreeBuilder::build_tree(
+                                &workspace.repositories,
+                                RepoFilter::new(&self.search_query, self.attention_filter, &chips),
+                                workspace.sort_mode.unwrap_or(self.config.sort_mode),
+                                self.config.max_tree_depth as usize,
+                                self.config.group_mode,
+                                self.runtime.errors(),
+                            );
+                            let repos = workspace.repositories.clone();
 
-                        self.render_tree_node(ui, &tree, &repos, 0, &to_remove);
-                    }
-                });
+                            for path in tree.truncated_paths() {
+                                if self.logged_tree_truncations.insert(path.clone()) {
+                                    self.logger.warning(format!(
+                                        "Tree truncated at depth {} for path {}",
+                                        self.config.max_tree_depth,
+                                        path.display()
+                                    ));
+                                }
+                            }
+
+                            self.render_tree_node(ui, &tree, &repos, 0, Some(&to_remove));
+                        }
+                    });
+            }
 
             if let Some(idx) = to_remove.into_inner() {
-                if let Some(workspace) = self.get_active_workspace_mut() {
-                    workspace.remove_repository(idx);
+                let removed = self
+                    .get_active_workspace_mut()
+                    .and_then(|workspace| workspace.remove_repository(idx));
+                if let Some(removed) = removed {
+                    if !self.is_known_repo(&removed.path) {
+                        self.runtime.clear_for_removed_repo(&removed.path);
+                    }
+                    self.refresh_repo_duplicates();
                     self.save_config();
                 }
             }
         });
+
+        // В режиме энергосбережения откладываем необязательную работу
+        if let Some(heartbeat) = low_power_heartbeat {
+            ctx.request_repaint_after(heartbeat);
+        } else {
+            self.maybe_start_auto_digest();
+
+            #[cfg(debug_assertions)]
+            self.hot_reload_locales();
+        }
+
+        if !self.config_saver.maybe_save(&self.config) && self.config_saver.is_dirty() {
+            ctx.request_repaint_after(self.config_saver.interval());
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.config_saver.flush(&self.config);
     }
 }
@@ -110,7 +110,13 @@ impl ButtonBuilder {
     pub fn show(self, ui: &mut egui::Ui, icon_manager: &mut IconManager) -> egui::Response {
         let actual_size = self.size.unwrap_or_else(|| self.calculate_size(ui));
         let button_rect = egui::Rect::from_min_size(ui.cursor().min, actual_size);
-        let response = ui.allocate_rect(button_rect, egui::Sense::click());
+        // Стабильный Id: egui помнит состояние взаимодействия между кадрами
+        ui.advance_cursor_after_rect(button_rect);
+        let response = ui.interact(
+            button_rect,
+            egui::Id::new(format!("{:?}_{:?}", self.content, button_rect.min)),
+            egui::Sense::click(),
+        );
 
         let mut visuals = ui.style().interact(&response).clone();
 
@@ -302,11 +308,11 @@ impl Icon {
         icon_manager: &mut IconManager,
         icon_type: IconType,
         size: Option<f32>,
-    ) {
+    ) -> egui::Response {
         let icon_size = size.unwrap_or(UiSize::default().small);
         let texture = icon_manager.get_icon(ui.ctx(), icon_type, icon_size);
         let image = egui::Image::new(&texture).max_size(egui::Vec2::splat(icon_size));
-        ui.add(image);
+        ui.add(image)
     }
 }
 
@@ -338,3 +344,39 @@ pub fn text_button<T: Into<String>>(
 pub fn icon_image(ui: &mut egui::Ui, icon_manager: &mut IconManager, icon: IconType) {
     Icon::show(ui, icon_manager, icon, None);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn show_button(ctx: &egui::Context, icon_manager: &mut IconManager) -> egui::Response {
+        let mut response = None;
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                response = Some(Button::text("Fetch").show(ui, icon_manager));
+            });
+        });
+        response.unwrap()
+    }
+
+    #[test]
+    fn button_has_stable_id_and_no_click_without_input() {
+        let ctx = egui::Context::default();
+        let mut icon_manager = IconManager::default();
+
+        let first = show_button(&ctx, &mut icon_manager);
+        let second = show_button(&ctx, &mut icon_manager);
+
+        assert!(!first.clicked());
+        assert!(!second.clicked());
+        assert_eq!(first.id, second.id);
+        assert_eq!(
+            first.id,
+            egui::Id::new(format!(
+                "{:?}_{:?}",
+                ButtonContent::Text("Fetch".to_string()),
+                first.rect.min
+            ))
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::git::HostingProvider;
 use std::collections::HashMap;
 
 const PULL_PNG: &[u8] = include_bytes!("../../assets/png/pull.png");
@@ -9,6 +10,11 @@ const REFRESH_PNG: &[u8] = include_bytes!("../../assets/png/refresh.png");
 const CHECK_PNG: &[u8] = include_bytes!("../../assets/png/check.png");
 const CROSS_PNG: &[u8] = include_bytes!("../../assets/png/cross.png");
 const INFO_PNG: &[u8] = include_bytes!("../../assets/png/info.png");
+const GITHUB_PNG: &[u8] = include_bytes!("../../assets/png/github.png");
+const GITLAB_PNG: &[u8] = include_bytes!("../../assets/png/gitlab.png");
+const BITBUCKET_PNG: &[u8] = include_bytes!("../../assets/png/bitbucket.png");
+const GITEA_PNG: &[u8] = include_bytes!("../../assets/png/gitea.png");
+const GLOBE_PNG: &[u8] = include_bytes!("../../assets/png/globe.png");
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IconType {
@@ -21,6 +27,12 @@ pub enum IconType {
     Check,
     Cross,
     Info,
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+    /// A remote on a host no provider is known for.
+    Globe,
 }
 
 impl IconType {
@@ -35,6 +47,22 @@ impl IconType {
             IconType::Check => CHECK_PNG,
             IconType::Cross => CROSS_PNG,
             IconType::Info => INFO_PNG,
+            IconType::GitHub => GITHUB_PNG,
+            IconType::GitLab => GITLAB_PNG,
+            IconType::Bitbucket => BITBUCKET_PNG,
+            IconType::Gitea => GITEA_PNG,
+            IconType::Globe => GLOBE_PNG,
+        }
+    }
+
+    /// Icon shown before the name of a repository with a remote.
+    pub fn for_provider(provider: Option<HostingProvider>) -> IconType {
+        match provider {
+            Some(HostingProvider::GitHub) => IconType::GitHub,
+            Some(HostingProvider::GitLab) => IconType::GitLab,
+            Some(HostingProvider::Bitbucket) => IconType::Bitbucket,
+            Some(HostingProvider::Gitea) => IconType::Gitea,
+            None => IconType::Globe,
         }
     }
 }
@@ -106,9 +134,21 @@ impl IconManager {
         size: f32,
         icon_type: IconType,
     ) -> egui::TextureHandle {
-        let color = [80, 80, 80, 255];
-
         let size_usize = size as usize;
+        let rgba_data = self.fallback_pixels(size_usize, icon_type);
+
+        let color_image =
+            egui::ColorImage::from_rgba_unmultiplied([size_usize, size_usize], &rgba_data);
+        ctx.load_texture(
+            format!("{:?}_fallback", icon_type),
+            color_image,
+            egui::TextureOptions::default(),
+        )
+    }
+
+    /// Pixel-art stand-in for an icon whose PNG did not decode.
+    fn fallback_pixels(&self, size_usize: usize, icon_type: IconType) -> Vec<u8> {
+        let color = [80, 80, 80, 255];
         let mut rgba_data = vec![0u8; size_usize * size_usize * 4];
 
         match icon_type {
@@ -121,15 +161,19 @@ impl IconManager {
             IconType::Check => self.draw_check_icon(&mut rgba_data, size_usize, color),
             IconType::Cross => self.draw_cross_icon(&mut rgba_data, size_usize, color),
             IconType::Info => self.draw_info_icon(&mut rgba_data, size_usize, color),
+            // У значков хостингов свой цвет, чтобы их можно было различить
+            IconType::GitHub => self.draw_badge_icon(&mut rgba_data, size_usize, [36, 41, 46, 255]),
+            IconType::GitLab => {
+                self.draw_gitlab_icon(&mut rgba_data, size_usize, [226, 67, 41, 255])
+            }
+            IconType::Bitbucket => {
+                self.draw_bitbucket_icon(&mut rgba_data, size_usize, [38, 132, 255, 255])
+            }
+            IconType::Gitea => self.draw_badge_icon(&mut rgba_data, size_usize, [96, 153, 38, 255]),
+            IconType::Globe => self.draw_globe_icon(&mut rgba_data, size_usize, color),
         }
 
-        let color_image =
-            egui::ColorImage::from_rgba_unmultiplied([size_usize, size_usize], &rgba_data);
-        ctx.load_texture(
-            format!("{:?}_fallback", icon_type),
-            color_image,
-            egui::TextureOptions::default(),
-        )
+        rgba_data
     }
 
     fn draw_trash_icon(&self, rgba_data: &mut [u8], size: usize, color: [u8; 4]) {
@@ -289,6 +333,86 @@ impl IconManager {
         }
     }
 
+    fn draw_badge_icon(&self, rgba_data: &mut [u8], size: usize, color: [u8; 4]) {
+        let center = size as f32 / 2.0 - 0.5;
+        let radius = size as f32 / 2.0 - 1.0;
+        for y in 0..size {
+            for x in 0..size {
+                let idx = (y * size + x) * 4;
+                let dx = x as f32 - center;
+                let dy = y as f32 - center;
+
+                if dx * dx + dy * dy <= radius * radius {
+                    rgba_data[idx] = color[0];
+                    rgba_data[idx + 1] = color[1];
+                    rgba_data[idx + 2] = color[2];
+                    rgba_data[idx + 3] = color[3];
+                }
+            }
+        }
+    }
+
+    fn draw_gitlab_icon(&self, rgba_data: &mut [u8], size: usize, color: [u8; 4]) {
+        let center = size / 2;
+        for y in 0..size {
+            for x in 0..size {
+                let idx = (y * size + x) * 4;
+                // Перевёрнутый треугольник с двумя «ушами» сверху
+                let should_draw = (y >= size / 3 && x.abs_diff(center) + y <= size - 2)
+                    || (y >= 2 && y < size / 3 && (x <= 2 || x >= size - 3));
+
+                if should_draw {
+                    rgba_data[idx] = color[0];
+                    rgba_data[idx + 1] = color[1];
+                    rgba_data[idx + 2] = color[2];
+                    rgba_data[idx + 3] = color[3];
+                }
+            }
+        }
+    }
+
+    fn draw_bitbucket_icon(&self, rgba_data: &mut [u8], size: usize, color: [u8; 4]) {
+        for y in 0..size {
+            for x in 0..size {
+                let idx = (y * size + x) * 4;
+                let inset = 1 + y / 4;
+                let should_draw = y >= 2 && y < size - 1 && x >= inset && x < size - inset;
+
+                if should_draw {
+                    rgba_data[idx] = color[0];
+                    rgba_data[idx + 1] = color[1];
+                    rgba_data[idx + 2] = color[2];
+                    rgba_data[idx + 3] = color[3];
+                }
+            }
+        }
+    }
+
+    fn draw_globe_icon(&self, rgba_data: &mut [u8], size: usize, color: [u8; 4]) {
+        let center = size / 2;
+        let radius = size / 2 - 1;
+
+        for y in 0..size {
+            for x in 0..size {
+                let idx = (y * size + x) * 4;
+                let dx = x as i32 - center as i32;
+                let dy = y as i32 - center as i32;
+                let dist = ((dx * dx + dy * dy) as f32).sqrt();
+
+                let inside = dist <= radius as f32;
+                let should_draw =
+                    inside && (dist >= radius as f32 - 1.0 || x == center || y == center);
+
+                if should_draw {
+                    rgba_data[idx] = color[0];
+                    rgba_data[idx + 1] = color[1];
+                    rgba_data[idx + 2] = color[2];
+                    rgba_data[idx + 3] = color[3];
+                }
+            }
+        }
+    }
+
     fn draw_info_icon(&self, rgba_data: &mut [u8], size: usize, color: [u8; 4]) {
         let center = size / 2;
         let radius = size / 3;
@@ -314,3 +438,48 @@ impl IconManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROVIDER_ICONS: [IconType; 5] = [
+        IconType::GitHub,
+        IconType::GitLab,
+        IconType::Bitbucket,
+        IconType::Gitea,
+        IconType::Globe,
+    ];
+
+    #[test]
+    fn provider_icons_are_bundled() {
+        for icon in PROVIDER_ICONS {
+            assert!(
+                image::load_from_memory(icon.png_data()).is_ok(),
+                "{:?} does not decode",
+                icon
+            );
+        }
+    }
+
+    #[test]
+    fn provider_icons_have_a_visible_fallback() {
+        let manager = IconManager::new();
+
+        for icon in PROVIDER_ICONS {
+            let pixels = manager.fallback_pixels(16, icon);
+            assert_eq!(pixels.len(), 16 * 16 * 4);
+            let opaque = pixels.chunks(4).filter(|pixel| pixel[3] > 0).count();
+            assert!(opaque > 16, "{:?} fallback is almost empty", icon);
+        }
+    }
+
+    #[test]
+    fn unknown_hosts_get_the_globe() {
+        assert_eq!(IconType::for_provider(None), IconType::Globe);
+        assert_eq!(
+            IconType::for_provider(Some(HostingProvider::Gitea)),
+            IconType::Gitea
+        );
+    }
+}
@@ -1,5 +1,13 @@
 pub mod components;
 pub mod icons;
+pub mod terminal;
+pub mod text;
+pub mod tray;
+pub mod workspace_panel;
 
 pub use components::*;
 pub use icons::*;
+pub use terminal::*;
+pub use text::*;
+pub use tray::*;
+pub use workspace_panel::*;
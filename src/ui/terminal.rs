@@ -0,0 +1,299 @@
+use crossbeam_channel::{unbounded, Receiver};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Older output is dropped once the scrollback grows past this many bytes.
+const MAX_SCROLLBACK: usize = 256 * 1024;
+
+/// A shell running in a pseudo-terminal, shown in the bottom panel.
+pub struct Terminal {
+    pub cwd: PathBuf,
+    output: String,
+    pending_utf8: Vec<u8>,
+    rx: Receiver<Vec<u8>>,
+    writer: Box<dyn Write + Send>,
+    master: Box<dyn MasterPty + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    size: (u16, u16),
+}
+
+impl Terminal {
+    /// Starts the user's default shell (`$SHELL`, `cmd.exe` on Windows) in
+    /// `cwd`. `ctx` is repainted whenever the shell prints something.
+    pub fn spawn(cwd: &Path, ctx: egui::Context) -> Result<Self, Box<dyn std::error::Error>> {
+        let size = (24, 80);
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: size.0,
+                cols: size.1,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| e.to_string())?;
+
+        let mut cmd = CommandBuilder::new_default_prog();
+        cmd.cwd(cwd);
+        cmd.env("TERM", "dumb");
+        let child = pair.slave.spawn_command(cmd).map_err(|e| e.to_string())?;
+        drop(pair.slave);
+
+        let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
+        let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
+
+        let (tx, rx) = unbounded();
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if tx.send(buffer[..n].to_vec()).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                }
+            }
+            ctx.request_repaint();
+        });
+
+        Ok(Self {
+            cwd: cwd.to_path_buf(),
+            output: String::new(),
+            pending_utf8: Vec::new(),
+            rx,
+            writer,
+            master: pair.master,
+            child,
+            size,
+        })
+    }
+
+    /// Moves everything the shell printed since the last frame into the
+    /// scrollback.
+    pub fn poll(&mut self) {
+        while let Ok(chunk) = self.rx.try_recv() {
+            let text = decode_utf8(&mut self.pending_utf8, &chunk);
+            append_output(&mut self.output, &text);
+        }
+        if self.output.len() > MAX_SCROLLBACK {
+            let mut cut = self.output.len() - MAX_SCROLLBACK;
+            while !self.output.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.output.drain(..cut);
+        }
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn send_input(&mut self, bytes: &[u8]) {
+        let _ = self.writer.write_all(bytes);
+        let _ = self.writer.flush();
+    }
+
+    /// Keeps the pty size in line with the pane so the shell wraps lines
+    /// where the pane does.
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        let size = (rows.max(1), cols.max(1));
+        if size != self.size {
+            self.size = size;
+            let _ = self.master.resize(PtySize {
+                rows: size.0,
+                cols: size.1,
+                pixel_width: 0,
+                pixel_height: 0,
+            });
+        }
+    }
+
+    pub fn has_exited(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(Some(_)))
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+/// Decodes `chunk`, keeping a multi-byte character split across reads in
+/// `pending` until the rest arrives.
+pub fn decode_utf8(pending: &mut Vec<u8>, chunk: &[u8]) -> String {
+    pending.extend_from_slice(chunk);
+    match std::str::from_utf8(pending) {
+        Ok(text) => {
+            let text = text.to_string();
+            pending.clear();
+            text
+        }
+        Err(e) if e.error_len().is_none() => {
+            let valid = e.valid_up_to();
+            let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+            text
+        }
+        Err(_) => String::from_utf8_lossy(&std::mem::take(pending)).into_owned(),
+    }
+}
+
+/// Appends shell output to the scrollback. Escape sequences (colors, cursor
+/// movement, window titles) are dropped, backspace erases the previous
+/// character and a bare `\r` restarts the current line.
+pub fn append_output(buffer: &mut String, text: &str) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: ESC [ параметры финальный-байт
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: ESC ] ... BEL или ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {
+                if chars.peek() != Some(&'\n') {
+                    let line_start = buffer.rfind('\n').map_or(0, |i| i + 1);
+                    buffer.truncate(line_start);
+                }
+            }
+            '\x08' => {
+                if !buffer.ends_with('\n') {
+                    buffer.pop();
+                }
+            }
+            '\n' | '\t' => buffer.push(c),
+            c if c.is_control() => {}
+            c => buffer.push(c),
+        }
+    }
+}
+
+/// Bytes a terminal would send for a keyboard event: typed text, Enter,
+/// Backspace, arrows and Ctrl+letter control codes.
+pub fn event_bytes(event: &egui::Event) -> Option<Vec<u8>> {
+    match event {
+        egui::Event::Text(text) | egui::Event::Paste(text) => Some(text.as_bytes().to_vec()),
+        // egui-winit превращает Ctrl+C / Ctrl+X в Copy / Cut вместо Key
+        egui::Event::Copy => Some(vec![0x03]),
+        egui::Event::Cut => Some(vec![0x18]),
+        egui::Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => {
+            if modifiers.ctrl || modifiers.command {
+                let name = key.name();
+                return match name.as_bytes() {
+                    [letter] if letter.is_ascii_alphabetic() => {
+                        Some(vec![letter.to_ascii_uppercase() - b'A' + 1])
+                    }
+                    _ => None,
+                };
+            }
+
+            let sequence: &[u8] = match key {
+                egui::Key::Enter => b"\r",
+                egui::Key::Backspace => b"\x7f",
+                egui::Key::Tab => b"\t",
+                egui::Key::Escape => b"\x1b",
+                egui::Key::ArrowUp => b"\x1b[A",
+                egui::Key::ArrowDown => b"\x1b[B",
+                egui::Key::ArrowRight => b"\x1b[C",
+                egui::Key::ArrowLeft => b"\x1b[D",
+                egui::Key::Home => b"\x1b[H",
+                egui::Key::End => b"\x1b[F",
+                egui::Key::Delete => b"\x1b[3~",
+                _ => return None,
+            };
+            Some(sequence.to_vec())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: egui::Key, modifiers: egui::Modifiers) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn escape_sequences_are_stripped() {
+        let mut buffer = String::new();
+        append_output(
+            &mut buffer,
+            "\x1b]0;title\x07\x1b[1;32mmain\x1b[0m $ git status\r\n",
+        );
+
+        assert_eq!(buffer, "main $ git status\n");
+    }
+
+    #[test]
+    fn carriage_return_and_backspace_edit_the_current_line() {
+        let mut buffer = "done\n".to_string();
+        append_output(&mut buffer, "Receiving 10%\rReceiving 100%\nab\x08c");
+
+        assert_eq!(buffer, "done\nReceiving 100%\nac");
+    }
+
+    #[test]
+    fn split_multibyte_character_waits_for_its_tail() {
+        let bytes = "ёж".as_bytes();
+        let mut pending = Vec::new();
+
+        assert_eq!(decode_utf8(&mut pending, &bytes[..1]), "");
+        assert_eq!(decode_utf8(&mut pending, &bytes[1..3]), "ё");
+        assert_eq!(decode_utf8(&mut pending, &bytes[3..]), "ж");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn keys_map_to_terminal_bytes() {
+        let none = egui::Modifiers::NONE;
+        assert_eq!(
+            event_bytes(&egui::Event::Text("ls".into())),
+            Some(b"ls".to_vec())
+        );
+        assert_eq!(
+            event_bytes(&key(egui::Key::Enter, none)),
+            Some(b"\r".to_vec())
+        );
+        assert_eq!(
+            event_bytes(&key(egui::Key::ArrowUp, none)),
+            Some(b"\x1b[A".to_vec())
+        );
+        assert_eq!(
+            event_bytes(&key(egui::Key::C, egui::Modifiers::CTRL)),
+            Some(vec![3])
+        );
+        assert_eq!(event_bytes(&egui::Event::Copy), Some(vec![0x03]));
+        assert_eq!(event_bytes(&egui::Event::Cut), Some(vec![0x18]));
+        assert_eq!(event_bytes(&key(egui::Key::F5, none)), None);
+    }
+}
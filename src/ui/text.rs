@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+const ELLIPSIS: &str = "...";
+
+/// Characters that attach to the one before them: combining marks, variation
+/// selectors, emoji skin tones and the zero-width joiner. Cutting right
+/// before one of them would split a visible character.
+fn attaches_to_previous(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+/// The first `max_chars` characters of `s` followed by `...`, or `s` itself
+/// when it is not longer. Cuts on char boundaries and never between a
+/// character and the marks or joiners attached to it.
+pub fn ellipsize(s: &str, max_chars: usize) -> Cow<'_, str> {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    if chars.len() <= max_chars {
+        return Cow::Borrowed(s);
+    }
+
+    let mut cut = max_chars;
+    while cut > 0 && (attaches_to_previous(chars[cut].1) || chars[cut - 1].1 == '\u{200D}') {
+        cut -= 1;
+    }
+
+    Cow::Owned(format!("{}{}", &s[..chars[cut].0], ELLIPSIS))
+}
+
+/// `ellipsize` with as many characters as fit into `max_width` points in
+/// the body font.
+pub fn ellipsize_to_width(ui: &egui::Ui, s: &str, max_width: f32) -> String {
+    let font = egui::TextStyle::Body.resolve(ui.style());
+    let width_of = |text: &str| {
+        ui.fonts(|fonts| {
+            fonts
+                .layout_no_wrap(text.to_string(), font.clone(), egui::Color32::WHITE)
+                .size()
+                .x
+        })
+    };
+
+    if width_of(s) <= max_width {
+        return s.to_string();
+    }
+
+    // Двоичный поиск по числу символов: ширина растёт вместе с ним
+    let (mut fits, mut too_long) = (0, s.chars().count());
+    while too_long - fits > 1 {
+        let middle = (fits + too_long) / 2;
+        if width_of(&ellipsize(s, middle)) <= max_width {
+            fits = middle;
+        } else {
+            too_long = middle;
+        }
+    }
+    ellipsize(s, fits).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_and_exact_length_strings_are_borrowed() {
+        assert!(matches!(ellipsize("main", 4), Cow::Borrowed("main")));
+        assert!(matches!(ellipsize("main", 10), Cow::Borrowed("main")));
+        assert_eq!(ellipsize("", 0), "");
+    }
+
+    #[test]
+    fn multi_byte_strings_are_cut_on_char_boundaries() {
+        assert_eq!(ellipsize("feature/исправление", 12), "feature/испр...");
+        assert_eq!(ellipsize("🚀🚀🚀🚀", 2), "🚀🚀...");
+        assert_eq!(ellipsize("main", 3), "mai...");
+    }
+
+    #[test]
+    fn attached_marks_and_joined_emoji_stay_whole() {
+        // "e" + комбинируемый акут не разделяются
+        assert_eq!(ellipsize("cafe\u{301}-menu", 4), "caf...");
+        // Семья из эмодзи, склеенных ZWJ
+        let family = "x👨\u{200D}👩\u{200D}👧";
+        assert_eq!(ellipsize(family, 3), "x...");
+        assert_eq!(ellipsize("👍\u{1F3FD}👍", 1), "...");
+    }
+}
@@ -0,0 +1,123 @@
+use crate::app::TrayCommand;
+
+/// Menu texts, already localized.
+#[cfg_attr(
+    not(all(feature = "tray", any(windows, target_os = "macos"))),
+    allow(dead_code)
+)]
+pub struct TrayLabels {
+    pub fetch_all: String,
+    pub open_window: String,
+    pub quit: String,
+}
+
+#[cfg(all(feature = "tray", any(windows, target_os = "macos")))]
+mod native {
+    use super::{TrayCommand, TrayLabels};
+    use crate::ui::IconType;
+    use crossbeam_channel::Receiver;
+    use tray_icon::menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem};
+    use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+    /// Tray icon with the workspace status and quick actions. Built only
+    /// with the `tray` feature on Windows and macOS.
+    pub struct Tray {
+        icon: TrayIcon,
+        status: MenuItem,
+        fetch_all: MenuId,
+        open_window: MenuId,
+        quit: MenuId,
+        events: Receiver<MenuEvent>,
+    }
+
+    impl Tray {
+        pub fn new(ctx: &egui::Context, labels: &TrayLabels) -> Option<Self> {
+            let status = MenuItem::new("", false, None);
+            let fetch_all = MenuItem::new(&labels.fetch_all, true, None);
+            let open_window = MenuItem::new(&labels.open_window, true, None);
+            let quit = MenuItem::new(&labels.quit, true, None);
+
+            let menu = Menu::new();
+            menu.append_items(&[
+                &status,
+                &PredefinedMenuItem::separator(),
+                &fetch_all,
+                &open_window,
+                &PredefinedMenuItem::separator(),
+                &quit,
+            ])
+            .ok()?;
+
+            let image = image::load_from_memory(IconType::Folder.png_data())
+                .ok()?
+                .into_rgba8();
+            let (width, height) = image.dimensions();
+            let icon = Icon::from_rgba(image.into_raw(), width, height).ok()?;
+
+            let icon = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_icon(icon)
+                .with_tooltip("Repo Manager")
+                .build()
+                .ok()?;
+
+            // Скрытое окно не перерисовывается само, будим его на каждый клик
+            let (tx, events) = crossbeam_channel::unbounded();
+            let ctx = ctx.clone();
+            MenuEvent::set_event_handler(Some(move |event| {
+                let _ = tx.send(event);
+                ctx.request_repaint();
+            }));
+
+            Some(Self {
+                icon,
+                status,
+                fetch_all: fetch_all.id().clone(),
+                open_window: open_window.id().clone(),
+                quit: quit.id().clone(),
+                events,
+            })
+        }
+
+        pub fn set_status(&mut self, text: &str) {
+            self.status.set_text(text);
+            let _ = self.icon.set_tooltip(Some(text));
+        }
+
+        pub fn poll(&mut self) -> Option<TrayCommand> {
+            while let Ok(event) = self.events.try_recv() {
+                if event.id == self.fetch_all {
+                    return Some(TrayCommand::FetchAll);
+                }
+                if event.id == self.open_window {
+                    return Some(TrayCommand::OpenWindow);
+                }
+                if event.id == self.quit {
+                    return Some(TrayCommand::Quit);
+                }
+            }
+            None
+        }
+    }
+}
+
+#[cfg(all(feature = "tray", any(windows, target_os = "macos")))]
+pub use native::Tray;
+
+/// Stand-in without tray support: `Tray::new` returns `None` and closing the
+/// window exits as usual.
+#[cfg(not(all(feature = "tray", any(windows, target_os = "macos"))))]
+pub struct Tray;
+
+#[cfg(not(all(feature = "tray", any(windows, target_os = "macos"))))]
+impl Tray {
+    pub fn new(_ctx: &egui::Context, _labels: &TrayLabels) -> Option<Self> {
+        None
+    }
+
+    pub fn set_status(&mut self, _text: &str) {}
+
+    pub fn poll(&mut self) -> Option<TrayCommand> {
+        None
+    }
+}
@@ -0,0 +1,447 @@
+use super::{Button, IconManager, IconType};
+use crate::localization::Localizer;
+use crate::workspace::{
+    filter_workspaces, format_relative_time, EnvironmentLabel, Workspace, WorkspaceId,
+    WORKSPACE_COLORS,
+};
+
+/// What the user did in the workspace list; `MyApp` applies it after the
+/// panel has been drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkspacePanelAction {
+    Switch(usize),
+    /// By id: the edit field stays on its workspace while the list changes.
+    Rename(WorkspaceId, String),
+    Remove(usize),
+    Add,
+    Duplicate(usize),
+    Archive(usize),
+    RemoveDuplicates(usize),
+    RestoreFromArchive,
+    Reorder {
+        from: usize,
+        to: usize,
+    },
+    SetStyle(usize, Option<[u8; 3]>, EnvironmentLabel),
+    /// Author name and email; `None` falls back to `.gitconfig`.
+    SetIdentity(usize, Option<String>, Option<String>),
+}
+
+/// App state the panel reads, and the bits of it edited in place.
+pub struct WorkspacePanelState<'a> {
+    pub workspaces: &'a [Workspace],
+    pub active_idx: usize,
+    /// The "Recent" view is shown, so no workspace is highlighted.
+    pub show_recent: bool,
+    pub editing: &'a mut Option<WorkspaceId>,
+    pub new_name: &'a mut String,
+    pub localizer: &'a Localizer,
+    pub icon_manager: &'a mut IconManager,
+}
+
+/// Workspace list of the sidebar: name filter, scrollable rows with drag
+/// reordering, and the add/restore buttons pinned below them.
+#[derive(Default)]
+pub struct WorkspacePanel {
+    pub filter: String,
+    /// Row being dragged by its handle.
+    dragging: Option<usize>,
+}
+
+impl WorkspacePanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        state: &mut WorkspacePanelState,
+    ) -> Option<WorkspacePanelAction> {
+        let mut action = None;
+
+        egui::TopBottomPanel::bottom("workspaces_buttons")
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(state.localizer.t("new_workspace")).clicked() {
+                        action = Some(WorkspacePanelAction::Add);
+                    }
+                    if ui
+                        .button(state.localizer.t("restore_from_archive"))
+                        .clicked()
+                    {
+                        action = Some(WorkspacePanelAction::RestoreFromArchive);
+                    }
+                });
+            });
+
+        ui.add(
+            egui::TextEdit::singleline(&mut self.filter)
+                .hint_text(state.localizer.t("filter_workspaces"))
+                .desired_width(f32::INFINITY),
+        );
+        let filtering = !self.filter.trim().is_empty();
+        let visible = filter_workspaces(state.workspaces, &self.filter);
+        if visible.is_empty() {
+            ui.weak(state.localizer.t("no_workspaces_match"));
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("workspace_list")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let mut row_rects = Vec::with_capacity(visible.len());
+                let mut drag_released_at = None;
+
+                for &idx in &visible {
+                    let row = ui.horizontal(|ui| {
+                        if *state.editing == Some(state.workspaces[idx].id) {
+                            Self::show_rename_row(ui, state, idx, &mut action);
+                        } else {
+                            // При фильтре позиции строк не совпадают с порядком списка
+                            if !filtering {
+                                let handle = ui
+                                    .add(egui::Label::new("⠿").sense(egui::Sense::drag()))
+                                    .on_hover_cursor(egui::CursorIcon::Grab);
+                                if handle.drag_started() {
+                                    self.dragging = Some(idx);
+                                }
+                                if handle.drag_stopped() {
+                                    drag_released_at =
+                                        ui.ctx().pointer_interact_pos().map(|pos| pos.y);
+                                }
+                            }
+                            Self::show_workspace_row(ui, state, idx, &mut action);
+                        }
+                    });
+                    row_rects.push(row.response.rect);
+                }
+
+                if let Some(from) = self.dragging {
+                    let pointer_y =
+                        drag_released_at.or_else(|| ui.ctx().pointer_latest_pos().map(|pos| pos.y));
+                    let target = pointer_y.and_then(|y| workspace_drop_index(&row_rects, y));
+
+                    if let Some(to) = target.filter(|to| *to != from) {
+                        if drag_released_at.is_none() {
+                            let target_rect = row_rects[to];
+                            let line_y = if to < from {
+                                target_rect.top()
+                            } else {
+                                target_rect.bottom()
+                            };
+                            ui.painter().hline(
+                                target_rect.x_range(),
+                                line_y,
+                                egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 150, 255)),
+                            );
+                        }
+                    }
+
+                    if drag_released_at.is_some() {
+                        self.dragging = None;
+                        if let Some(to) = target.filter(|to| *to != from) {
+                            action = Some(WorkspacePanelAction::Reorder { from, to });
+                        }
+                    }
+                }
+            });
+
+        action
+    }
+
+    fn show_rename_row(
+        ui: &mut egui::Ui,
+        state: &mut WorkspacePanelState,
+        idx: usize,
+        action: &mut Option<WorkspacePanelAction>,
+    ) {
+        let id = state.workspaces[idx].id;
+        let available_width = ui.available_width();
+        let button_width = 50.0;
+        let input_width = available_width - button_width - 15.0;
+
+        ui.scope(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            ui.style_mut().spacing.indent = 0.0;
+
+            ui.set_max_width(input_width);
+            ui.set_min_width(input_width);
+
+            let response = ui.add_sized(
+                [input_width, 20.0],
+                egui::TextEdit::singleline(state.new_name)
+                    .desired_width(input_width)
+                    .clip_text(true),
+            );
+
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                *action = Some(WorkspacePanelAction::Rename(id, state.new_name.clone()));
+            }
+        });
+
+        if Button::icon(IconType::Check)
+            .show(ui, state.icon_manager)
+            .clicked()
+        {
+            *action = Some(WorkspacePanelAction::Rename(id, state.new_name.clone()));
+        }
+        if Button::icon(IconType::Cross)
+            .show(ui, state.icon_manager)
+            .clicked()
+        {
+            *state.editing = None;
+        }
+    }
+
+    fn show_workspace_row(
+        ui: &mut egui::Ui,
+        state: &mut WorkspacePanelState,
+        idx: usize,
+        action: &mut Option<WorkspacePanelAction>,
+    ) {
+        let workspace = &state.workspaces[idx];
+        let localizer = state.localizer;
+        let available_width = ui.available_width();
+        let button_width = 50.0;
+        let name_width = available_width - button_width;
+
+        ui.allocate_ui_with_layout(
+            egui::Vec2::new(name_width, 25.0),
+            egui::Layout::left_to_right(egui::Align::Center),
+            |ui| {
+                if let Some([r, g, b]) = workspace.color {
+                    ui.colored_label(egui::Color32::from_rgb(r, g, b), "●");
+                }
+
+                let is_selected = !state.show_recent && state.active_idx == idx;
+                let name_response = ui
+                    .push_id(("workspace", workspace.id), |ui| {
+                        ui.selectable_label(is_selected, &workspace.name)
+                    })
+                    .inner;
+                if name_response.clicked() && !is_selected {
+                    *action = Some(WorkspacePanelAction::Switch(idx));
+                }
+                if let Some(identity) = workspace.git_env() {
+                    let who = [identity.author_name, identity.author_email]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    ui.label("👤")
+                        .on_hover_text(localizer.tf("workspace_custom_identity", &[&who]));
+                }
+
+                name_response.context_menu(|ui| {
+                    if let Some((color, environment)) =
+                        workspace_style_menu(localizer, ui, workspace)
+                    {
+                        *action = Some(WorkspacePanelAction::SetStyle(idx, color, environment));
+                    }
+                    ui.separator();
+                    if let Some((name, email)) = workspace_identity_menu(localizer, ui, workspace) {
+                        *action = Some(WorkspacePanelAction::SetIdentity(idx, name, email));
+                    }
+                    ui.separator();
+                    if ui.button(localizer.t("duplicate_workspace")).clicked() {
+                        *action = Some(WorkspacePanelAction::Duplicate(idx));
+                        ui.close_menu();
+                    }
+                    if ui.button(localizer.t("archive_workspace")).clicked() {
+                        *action = Some(WorkspacePanelAction::Archive(idx));
+                        ui.close_menu();
+                    }
+                    if ui.button(localizer.t("remove_duplicates")).clicked() {
+                        *action = Some(WorkspacePanelAction::RemoveDuplicates(idx));
+                        ui.close_menu();
+                    }
+                });
+
+                if let Some(last_modified) = workspace.last_modified {
+                    ui.label(
+                        egui::RichText::new(format_relative_time(last_modified))
+                            .small()
+                            .color(egui::Color32::GRAY),
+                    )
+                    .on_hover_text(localizer.t("workspace_last_active"));
+                }
+            },
+        );
+
+        if Button::icon(IconType::Edit)
+            .show(ui, state.icon_manager)
+            .clicked()
+        {
+            *state.editing = Some(workspace.id);
+            *state.new_name = workspace.name.clone();
+        }
+        if Button::icon(IconType::Trash)
+            .show(ui, state.icon_manager)
+            .clicked()
+        {
+            *action = Some(WorkspacePanelAction::Remove(idx));
+        }
+    }
+}
+
+/// Color and environment editor for the workspace context menu. Returns
+/// the new values when the user changed something.
+fn workspace_style_menu(
+    localizer: &Localizer,
+    ui: &mut egui::Ui,
+    workspace: &Workspace,
+) -> Option<(Option<[u8; 3]>, EnvironmentLabel)> {
+    let mut color = workspace.color;
+    let mut environment = workspace.environment.clone();
+
+    ui.label(localizer.t("workspace_color"));
+    ui.horizontal(|ui| {
+        if ui
+            .selectable_label(color.is_none(), localizer.t("env_none"))
+            .clicked()
+        {
+            color = None;
+        }
+        for rgb in WORKSPACE_COLORS {
+            let [r, g, b] = rgb;
+            let text = egui::RichText::new("●").color(egui::Color32::from_rgb(r, g, b));
+            if ui.selectable_label(color == Some(rgb), text).clicked() {
+                color = Some(rgb);
+            }
+        }
+    });
+
+    ui.separator();
+    ui.label(localizer.t("workspace_environment"));
+    for preset in EnvironmentLabel::presets() {
+        let selected = environment.same_kind(&preset);
+        if ui
+            .selectable_label(selected, localizer.t(preset.label_key()))
+            .clicked()
+            && !selected
+        {
+            environment = preset;
+        }
+    }
+    if let EnvironmentLabel::Custom(text) = &mut environment {
+        ui.text_edit_singleline(text);
+    }
+
+    (color != workspace.color || environment != workspace.environment)
+        .then_some((color, environment))
+}
+
+/// Commit identity editor for the workspace context menu. Returns the new
+/// values, empty fields as `None`, when the user changed something.
+fn workspace_identity_menu(
+    localizer: &Localizer,
+    ui: &mut egui::Ui,
+    workspace: &Workspace,
+) -> Option<(Option<String>, Option<String>)> {
+    // Набранное держится в памяти egui и применяется по Enter или уходу
+    // из поля, а не на каждую букву
+    let draft_id = ui.make_persistent_id(("workspace_identity_draft", workspace.id));
+    let (mut name, mut email) = ui
+        .data_mut(|data| data.get_temp::<(String, String)>(draft_id))
+        .unwrap_or_else(|| {
+            (
+                workspace.git_author_name.clone().unwrap_or_default(),
+                workspace.git_author_email.clone().unwrap_or_default(),
+            )
+        });
+
+    ui.label(localizer.t("workspace_identity"));
+    let mut committed = false;
+    egui::Grid::new(("workspace_identity", workspace.id))
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(localizer.t("git_author_name"));
+            committed |= ui.text_edit_singleline(&mut name).lost_focus();
+            ui.end_row();
+            ui.label(localizer.t("git_author_email"));
+            committed |= ui.text_edit_singleline(&mut email).lost_focus();
+            ui.end_row();
+        });
+
+    if !committed {
+        ui.data_mut(|data| data.insert_temp(draft_id, (name, email)));
+        return None;
+    }
+    ui.data_mut(|data| data.remove::<(String, String)>(draft_id));
+    let non_empty = |value: String| (!value.trim().is_empty()).then_some(value);
+    let name = non_empty(name);
+    let email = non_empty(email);
+    (name != workspace.git_author_name || email != workspace.git_author_email)
+        .then_some((name, email))
+}
+
+/// Row a dragged workspace is dropped on: the first row above the list,
+/// the last one below it.
+fn workspace_drop_index(row_rects: &[egui::Rect], y: f32) -> Option<usize> {
+    let first = row_rects.first()?;
+    if y < first.top() {
+        return Some(0);
+    }
+
+    row_rects
+        .iter()
+        .position(|rect| y <= rect.bottom())
+        .or(Some(row_rects.len() - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<egui::Rect> {
+        (0..3)
+            .map(|i| {
+                let top = 100.0 + i as f32 * 25.0;
+                egui::Rect::from_min_max(egui::pos2(0.0, top), egui::pos2(200.0, top + 25.0))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drop_index_clamps_to_the_list() {
+        let rows = rows();
+
+        assert_eq!(workspace_drop_index(&rows, 50.0), Some(0));
+        assert_eq!(workspace_drop_index(&rows, 130.0), Some(1));
+        assert_eq!(workspace_drop_index(&rows, 500.0), Some(2));
+        assert_eq!(workspace_drop_index(&[], 130.0), None);
+    }
+
+    #[test]
+    fn idle_frame_produces_no_action() {
+        let ctx = egui::Context::default();
+        let workspaces = vec![Workspace::new("Work"), Workspace::new("Home")];
+        let localizer = Localizer::new("en");
+        let mut icon_manager = IconManager::new();
+        let mut editing = Some(workspaces[1].id);
+        let mut new_name = "Home".to_string();
+        let mut panel = WorkspacePanel {
+            filter: "ho".to_string(),
+            ..Default::default()
+        };
+
+        let mut action = Some(WorkspacePanelAction::Add);
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let mut state = WorkspacePanelState {
+                    workspaces: &workspaces,
+                    active_idx: 0,
+                    show_recent: false,
+                    editing: &mut editing,
+                    new_name: &mut new_name,
+                    localizer: &localizer,
+                    icon_manager: &mut icon_manager,
+                };
+                action = panel.show(ui, &mut state);
+            });
+        });
+
+        assert_eq!(action, None);
+        assert_eq!(editing, Some(workspaces[1].id));
+        assert_eq!(new_name, "Home");
+    }
+}
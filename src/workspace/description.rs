@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Per-repository settings file teams can commit to the repository root.
+pub const REPO_MANAGER_FILE: &str = "repo-manager.toml";
+
+/// `repo-manager.toml`; unknown keys are left for later settings.
+#[derive(serde::Deserialize, Default, Debug, PartialEq)]
+struct RepoManagerFile {
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// The text `git init` writes into `.git/description`.
+pub fn is_placeholder_description(text: &str) -> bool {
+    text.trim().starts_with("Unnamed repository;")
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// `description` from a `repo-manager.toml`; `None` for a file that does
+/// not parse.
+pub fn parse_repo_manager_description(content: &str) -> Option<String> {
+    toml::from_str::<RepoManagerFile>(content)
+        .ok()?
+        .description
+        .as_deref()
+        .and_then(non_empty)
+}
+
+/// The files a description is read from, in order of preference.
+/// `git_root` differs from `repo_path` for monorepo services.
+pub fn description_sources(repo_path: &Path, git_root: &Path) -> [PathBuf; 2] {
+    [
+        git_root.join(".git").join("description"),
+        repo_path.join(REPO_MANAGER_FILE),
+    ]
+}
+
+/// Modification times of the description sources; a missing file is `None`.
+pub fn description_mtimes(sources: &[PathBuf; 2]) -> [Option<SystemTime>; 2] {
+    sources.clone().map(|path| {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    })
+}
+
+/// `.git/description` unless it is the `git init` placeholder, then the
+/// `repo-manager.toml` description. Unreadable files count as absent.
+pub fn read_description(sources: &[PathBuf; 2]) -> Option<String> {
+    let [git_description, repo_manager_file] = sources;
+    std::fs::read_to_string(git_description)
+        .ok()
+        .filter(|text| !is_placeholder_description(text))
+        .and_then(|text| non_empty(&text))
+        .or_else(|| {
+            std::fs::read_to_string(repo_manager_file)
+                .ok()
+                .and_then(|content| parse_repo_manager_description(&content))
+        })
+}
+
+/// A description remembered in the status cache with the mtimes it was read
+/// at; it is read again once either file changes.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CachedDescription {
+    pub description: Option<String>,
+    pub mtimes: [Option<SystemTime>; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_init_placeholder_is_not_a_description() {
+        assert!(is_placeholder_description(
+            "Unnamed repository; edit this file 'description' to name the repository.\n"
+        ));
+        assert!(!is_placeholder_description("Payments API gateway\n"));
+    }
+
+    #[test]
+    fn repo_manager_file_description_is_parsed() {
+        assert_eq!(
+            parse_repo_manager_description(
+                "description = \"Billing service\"\n\n[future]\nkey = 1\n"
+            ),
+            Some("Billing service".to_string())
+        );
+        assert_eq!(parse_repo_manager_description("description = \"  \""), None);
+        assert_eq!(parse_repo_manager_description("other = 1"), None);
+        // Битый файл — просто нет описания
+        assert_eq!(parse_repo_manager_description("description = "), None);
+    }
+
+    #[test]
+    fn git_description_wins_and_placeholders_fall_through() {
+        let root = std::env::temp_dir().join("repo_manager_description");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        let sources = description_sources(&root, &root);
+
+        assert_eq!(read_description(&sources), None);
+
+        std::fs::write(&sources[1], "description = \"From toml\"").unwrap();
+        std::fs::write(
+            &sources[0],
+            "Unnamed repository; edit this file 'description' to name the repository.",
+        )
+        .unwrap();
+        assert_eq!(read_description(&sources), Some("From toml".to_string()));
+
+        std::fs::write(&sources[0], "From git\n").unwrap();
+        let description = read_description(&sources);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(description, Some("From git".to_string()));
+    }
+}
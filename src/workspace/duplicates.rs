@@ -0,0 +1,123 @@
+use super::{RepositoryState, Workspace, WorkspaceId};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// One registration of a repository that other workspaces list too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateLocation {
+    pub workspace_id: WorkspaceId,
+    pub workspace_name: String,
+    /// Path as registered in this workspace; it may differ from the others
+    /// by a trailing slash or a symlink.
+    pub path: PathBuf,
+}
+
+/// A repository registered in more than one workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRepo {
+    /// Shared by every location, see `duplicate_key`.
+    pub key: PathBuf,
+    pub locations: Vec<DuplicateLocation>,
+}
+
+impl DuplicateRepo {
+    pub fn is_at(&self, workspace_id: WorkspaceId, path: &Path) -> bool {
+        self.locations
+            .iter()
+            .any(|location| location.workspace_id == workspace_id && location.path == path)
+    }
+
+    /// Names of the workspaces other than `workspace_id` that list it.
+    pub fn other_workspaces(&self, workspace_id: WorkspaceId) -> Vec<&str> {
+        self.locations
+            .iter()
+            .filter(|location| location.workspace_id != workspace_id)
+            .map(|location| location.workspace_name.as_str())
+            .collect()
+    }
+}
+
+/// Repositories that appear in two or more workspaces, grouped by `key`,
+/// sorted by key. Repeats inside a single workspace are left to
+/// `Workspace::remove_duplicates`.
+pub fn find_duplicate_registrations(
+    workspaces: &[Workspace],
+    key: impl Fn(&RepositoryState) -> PathBuf,
+) -> Vec<DuplicateRepo> {
+    let mut by_key: BTreeMap<PathBuf, Vec<DuplicateLocation>> = BTreeMap::new();
+    for workspace in workspaces {
+        for repo in &workspace.repositories {
+            by_key
+                .entry(key(repo))
+                .or_default()
+                .push(DuplicateLocation {
+                    workspace_id: workspace.id,
+                    workspace_name: workspace.name.clone(),
+                    path: repo.path.clone(),
+                });
+        }
+    }
+
+    by_key
+        .into_iter()
+        .filter(|(_, locations)| {
+            locations
+                .iter()
+                .any(|location| location.workspace_id != locations[0].workspace_id)
+        })
+        .map(|(key, locations)| DuplicateRepo { key, locations })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str, repos: &[&str]) -> Workspace {
+        let mut workspace = Workspace::new(name);
+        for repo in repos {
+            workspace.add_repository(PathBuf::from(repo));
+        }
+        workspace
+    }
+
+    fn normalized(repo: &RepositoryState) -> PathBuf {
+        repo.path.components().collect()
+    }
+
+    #[test]
+    fn repositories_in_several_workspaces_are_reported() {
+        let workspaces = [
+            workspace("Work", &["/src/api", "/src/web"]),
+            workspace("Platform", &["/src/api/", "/src/infra"]),
+            workspace("Experiments", &["/src/api", "/src/infra"]),
+        ];
+
+        let duplicates = find_duplicate_registrations(&workspaces, normalized);
+
+        assert_eq!(
+            duplicates.iter().map(|d| &d.key).collect::<Vec<_>>(),
+            [Path::new("/src/api"), Path::new("/src/infra")]
+        );
+        let api = &duplicates[0];
+        assert_eq!(api.locations.len(), 3);
+        assert_eq!(api.locations[1].path, PathBuf::from("/src/api/"));
+        assert_eq!(
+            api.other_workspaces(workspaces[0].id),
+            ["Platform", "Experiments"]
+        );
+        assert!(api.is_at(workspaces[1].id, Path::new("/src/api/")));
+        assert!(!api.is_at(workspaces[1].id, Path::new("/src/web")));
+    }
+
+    #[test]
+    fn repeats_inside_one_workspace_are_not_cross_workspace_duplicates() {
+        let mut single = Workspace::new("Work");
+        single.repositories = ["/src/api", "/src/api/"]
+            .iter()
+            .map(|path| RepositoryState::new(PathBuf::from(path)))
+            .collect();
+
+        assert!(find_duplicate_registrations(&[single], normalized).is_empty());
+    }
+}
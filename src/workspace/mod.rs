@@ -1,12 +1,191 @@
-use crate::git::GitInfo;
-use std::path::PathBuf;
+pub mod description;
+pub mod duplicates;
+pub mod project;
+pub mod repath;
+
+pub use description::*;
+pub use duplicates::*;
+pub use project::*;
+pub use repath::*;
+
+use crate::config::SortMode;
+use crate::git::{check_git_dir, GitDirCheck, GitInfo, WorkspaceGitEnv};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Palette offered in the workspace context menu.
+pub const WORKSPACE_COLORS: [[u8; 3]; 6] = [
+    [220, 80, 80],
+    [230, 160, 60],
+    [210, 200, 70],
+    [90, 180, 100],
+    [80, 140, 220],
+    [160, 100, 210],
+];
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug, PartialEq, Eq)]
+pub enum EnvironmentLabel {
+    #[default]
+    None,
+    Dev,
+    Staging,
+    Prod,
+    Custom(String),
+}
+
+impl EnvironmentLabel {
+    pub fn presets() -> [EnvironmentLabel; 5] {
+        [
+            EnvironmentLabel::None,
+            EnvironmentLabel::Dev,
+            EnvironmentLabel::Staging,
+            EnvironmentLabel::Prod,
+            EnvironmentLabel::Custom(String::new()),
+        ]
+    }
+
+    pub fn same_kind(&self, other: &EnvironmentLabel) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            EnvironmentLabel::None => "env_none",
+            EnvironmentLabel::Dev => "env_dev",
+            EnvironmentLabel::Staging => "env_staging",
+            EnvironmentLabel::Prod => "env_prod",
+            EnvironmentLabel::Custom(_) => "env_custom",
+        }
+    }
+
+    /// Free-text labels such as "production" count as Prod as well.
+    pub fn is_production(&self) -> bool {
+        match self {
+            EnvironmentLabel::Prod => true,
+            EnvironmentLabel::Custom(text) => {
+                matches!(text.trim().to_lowercase().as_str(), "prod" | "production")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Bulk actions that change many repositories at once must be confirmed if
+/// the user asked for it globally, and always on production workspaces.
+pub fn requires_bulk_confirmation(
+    environment: &EnvironmentLabel,
+    confirm_bulk_actions: bool,
+) -> bool {
+    confirm_bulk_actions || environment.is_production()
+}
+
+/// Stable identity of a workspace: unlike its position in the list it
+/// survives reordering, filtering and removal of other workspaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct WorkspaceId(pub u64);
+
+static NEXT_WORKSPACE_ID: AtomicU64 = AtomicU64::new(0);
+
+impl WorkspaceId {
+    /// Counts up from the clock of the first call, so ids made in a later
+    /// session never meet the ones already saved.
+    pub fn fresh() -> Self {
+        if NEXT_WORKSPACE_ID.load(Ordering::Relaxed) == 0 {
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(1)
+                .max(1);
+            let _ =
+                NEXT_WORKSPACE_ID.compare_exchange(0, seed, Ordering::Relaxed, Ordering::Relaxed);
+        }
+        WorkspaceId(NEXT_WORKSPACE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for WorkspaceId {
+    fn default() -> Self {
+        Self::fresh()
+    }
+}
+
+/// Position of the workspace with `id`, wherever it has moved to.
+pub fn workspace_index(workspaces: &[Workspace], id: WorkspaceId) -> Option<usize> {
+    workspaces.iter().position(|workspace| workspace.id == id)
+}
+
+/// Indices of the workspaces whose name contains `query`, ignoring case;
+/// all of them for an empty query.
+pub fn filter_workspaces(workspaces: &[Workspace], query: &str) -> Vec<usize> {
+    let query = query.trim().to_lowercase();
+    workspaces
+        .iter()
+        .enumerate()
+        .filter(|(_, workspace)| query.is_empty() || workspace.name.to_lowercase().contains(&query))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Gives copies of a workspace (e.g. from a hand-edited config) ids of
+/// their own.
+pub fn ensure_unique_workspace_ids(workspaces: &mut [Workspace]) {
+    let mut seen = std::collections::HashSet::new();
+    for workspace in workspaces {
+        while !seen.insert(workspace.id) {
+            workspace.id = WorkspaceId::fresh();
+        }
+    }
+}
 
 #[derive(serde::Deserialize, serde::Serialize, Default, Clone)]
 pub struct Workspace {
+    /// Missing in configs written before ids existed; a fresh one is made.
+    #[serde(default)]
+    pub id: WorkspaceId,
     pub name: String,
     pub repositories: Vec<RepositoryState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_mode: Option<SortMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[u8; 3]>,
+    #[serde(default)]
+    pub environment: EnvironmentLabel,
+    /// Commit identity for this workspace's repositories, instead of an
+    /// `includeIf` in `.gitconfig`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_author_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_author_email: Option<String>,
+    /// When a pull, push, fetch, reset or branch switch last finished in
+    /// one of the workspace's repositories.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "unix_timestamp"
+    )]
+    pub last_modified: Option<SystemTime>,
     #[serde(skip)] // Не сохраняем состояние загрузки в файл
     pub is_loaded: bool,
+    /// Came from the `--config-extra` file and is not written to the main
+    /// config.
+    #[serde(skip)]
+    pub from_extra: bool,
+}
+
+/// How many folder levels below a repository are searched for services.
+pub const MONOREPO_SCAN_DEPTH: usize = 3;
+
+/// Folders with build output or dependencies, never service roots.
+const MONOREPO_SKIPPED_DIRS: [&str; 5] = ["node_modules", "target", "vendor", "dist", "build"];
+
+/// Sidecar file written when a finished workspace is archived.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct WorkspaceArchive {
+    name: String,
+    repositories: Vec<PathBuf>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone)]
@@ -16,6 +195,35 @@ pub struct RepositoryState {
     pub name: String,
     #[serde(skip)]
     pub git_info: GitInfo,
+    /// `None` until `MyApp::request_project_type` reports back.
+    #[serde(skip)]
+    pub project_type: Option<ProjectType>,
+    /// Set for a service inside a monorepo: the folder holding the shared
+    /// `.git`, while `path` is the service's own subfolder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monorepo_root: Option<PathBuf>,
+    /// Extra environment for every git command run in this repository,
+    /// e.g. `GIT_SSH_COMMAND` pointing at a deploy key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_overrides: Vec<(String, String)>,
+    /// `path` itself is a symbolic link; filled by `detect_symlink`.
+    #[serde(skip)]
+    pub is_symlink: bool,
+    #[serde(skip)]
+    pub canonical_path: Option<PathBuf>,
+    /// Last successful fetch or pull, from the status cache.
+    #[serde(skip)]
+    pub last_fetch: Option<SystemTime>,
+    /// From `.git/description` or `repo-manager.toml`, via the status cache.
+    #[serde(skip)]
+    pub description: Option<String>,
+    /// Filled by `validate_git_dir`; invalid entries are not refreshed.
+    #[serde(skip)]
+    pub git_dir_check: GitDirCheck,
+    /// Came from the `--config-extra` file and is not written to the main
+    /// config.
+    #[serde(skip)]
+    pub from_extra: bool,
 }
 
 impl Default for RepositoryState {
@@ -24,6 +232,15 @@ impl Default for RepositoryState {
             path: PathBuf::new(),
             name: String::new(),
             git_info: GitInfo::default(),
+            project_type: None,
+            monorepo_root: None,
+            env_overrides: Vec::new(),
+            is_symlink: false,
+            canonical_path: None,
+            last_fetch: None,
+            description: None,
+            git_dir_check: GitDirCheck::Valid,
+            from_extra: false,
         }
     }
 }
@@ -36,11 +253,47 @@ impl RepositoryState {
             .to_string_lossy()
             .to_string();
 
-        Self {
+        let mut state = Self {
             path,
             name,
-            git_info: GitInfo::default(),
-        }
+            ..Self::default()
+        };
+        state.detect_symlink();
+        state.validate_git_dir();
+        state
+    }
+
+    /// Checks whether `path` is a symbolic link and where it resolves to.
+    /// Entries loaded from the config are not built through `new`, so this
+    /// runs again after loading.
+    pub fn detect_symlink(&mut self) {
+        self.is_symlink = std::fs::symlink_metadata(&self.path)
+            .map(|meta| meta.file_type().is_symlink())
+            .unwrap_or(false);
+        self.canonical_path = std::fs::canonicalize(&self.path).ok();
+    }
+
+    /// `duplicate_key` from the `canonical_path` cached by `detect_symlink`,
+    /// without touching the disk.
+    pub fn duplicate_key(&self) -> PathBuf {
+        let key = self
+            .canonical_path
+            .clone()
+            .unwrap_or_else(|| self.path.components().collect());
+        fold_duplicate_key(key)
+    }
+
+    /// Folder holding the `.git` this entry belongs to: the monorepo root
+    /// for a service entry, `path` otherwise.
+    pub fn infer_project_root(&self) -> &Path {
+        self.monorepo_root.as_deref().unwrap_or(&self.path)
+    }
+
+    /// Checks the `.git` of the entry, so a broken clone is flagged once
+    /// instead of failing every git command. Like `detect_symlink`, runs
+    /// again after loading the config.
+    pub fn validate_git_dir(&mut self) {
+        self.git_dir_check = check_git_dir(self.infer_project_root());
     }
 
     pub fn update_git_info(&mut self, git_info: GitInfo) {
@@ -51,9 +304,37 @@ impl RepositoryState {
 impl Workspace {
     pub fn new<T: Into<String>>(name: T) -> Self {
         Self {
+            id: WorkspaceId::fresh(),
             name: name.into(),
             repositories: Vec::new(),
+            sort_mode: None,
+            color: None,
+            environment: EnvironmentLabel::None,
+            git_author_name: None,
+            git_author_email: None,
+            last_modified: None,
+            is_loaded: false,
+            from_extra: false,
+        }
+    }
+
+    /// The identity override, when the workspace sets one.
+    pub fn git_env(&self) -> Option<WorkspaceGitEnv> {
+        let env = WorkspaceGitEnv {
+            author_name: self.git_author_name.clone(),
+            author_email: self.git_author_email.clone(),
+        };
+        (env != WorkspaceGitEnv::default()).then_some(env)
+    }
+
+    /// Copy with its own id under `name`; repositories are loaded again
+    /// when the copy is opened.
+    pub fn duplicate<T: Into<String>>(&self, name: T) -> Self {
+        Self {
+            id: WorkspaceId::fresh(),
+            name: name.into(),
             is_loaded: false,
+            ..self.clone()
         }
     }
 
@@ -67,6 +348,63 @@ impl Workspace {
         true
     }
 
+    /// Adds `service` as a virtual entry of the monorepo at `root`.
+    pub fn add_monorepo_service(&mut self, root: &Path, service: PathBuf) -> bool {
+        if self.repositories.iter().any(|r| r.path == service) {
+            return false;
+        }
+
+        let mut repo_state = RepositoryState::new(service);
+        repo_state.monorepo_root = Some(root.to_path_buf());
+        self.repositories.push(repo_state);
+        true
+    }
+
+    /// Subfolders of `repo_path` with their own build file, e.g. the services
+    /// of a monorepo that shares one `.git`. Hidden and dependency folders
+    /// and nested repositories are skipped, and a found service is not
+    /// searched further.
+    pub fn detect_monorepo_services(repo_path: &Path) -> Vec<PathBuf> {
+        fn scan(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !path.is_dir()
+                    || name.starts_with('.')
+                    || MONOREPO_SKIPPED_DIRS.contains(&name.as_str())
+                    || path.join(".git").exists()
+                {
+                    continue;
+                }
+
+                if has_project_marker(&path) {
+                    found.push(path);
+                } else if depth < MONOREPO_SCAN_DEPTH {
+                    scan(&path, depth + 1, found);
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        scan(repo_path, 1, &mut found);
+        found.sort();
+        found
+    }
+
+    /// Drops entries pointing at the same folder as an earlier one, e.g. the
+    /// same drop with and without a trailing slash or through a symlink.
+    /// Returns how many were removed.
+    pub fn remove_duplicates(&mut self) -> usize {
+        let before = self.repositories.len();
+        let mut seen = HashSet::new();
+        self.repositories
+            .retain(|repo| seen.insert(repo.duplicate_key()));
+        before - self.repositories.len()
+    }
+
     pub fn remove_repository(&mut self, index: usize) -> Option<RepositoryState> {
         if index < self.repositories.len() {
             Some(self.repositories.remove(index))
@@ -83,10 +421,79 @@ impl Workspace {
         self.repositories.len()
     }
 
+    /// `(with local changes, behind upstream)` repository counts.
+    pub fn status_counts(&self) -> (usize, usize) {
+        let dirty = self
+            .repositories
+            .iter()
+            .filter(|r| r.git_info.has_changes)
+            .count();
+        let behind = self
+            .repositories
+            .iter()
+            .filter(|r| r.git_info.behind > 0)
+            .count();
+        (dirty, behind)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.repositories.is_empty()
     }
 
+    /// Deepest folder containing every repository of the workspace.
+    pub fn root_path(&self) -> Option<PathBuf> {
+        let mut repos = self.repositories.iter();
+        let mut root = repos.next()?.path.clone();
+        for repo in repos {
+            while !repo.path.starts_with(&root) {
+                if !root.pop() {
+                    return None;
+                }
+            }
+        }
+        Some(root)
+    }
+
+    /// Writes the name and repository paths to `path` so the workspace can
+    /// be removed and brought back later with `restore_from_archive`.
+    pub fn archive_to_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let archive = WorkspaceArchive {
+            name: self.name.clone(),
+            repositories: self.repositories.iter().map(|r| r.path.clone()).collect(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&archive)?)?;
+        Ok(())
+    }
+
+    pub fn restore_from_archive(path: &Path) -> Result<Workspace, Box<dyn std::error::Error>> {
+        let archive: WorkspaceArchive = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let mut workspace = Workspace::new(archive.name);
+        for repo_path in archive.repositories {
+            workspace.add_repository(repo_path);
+        }
+        Ok(workspace)
+    }
+
+    pub fn find_by_name<'a>(
+        workspaces: &'a [Workspace],
+        name: &str,
+    ) -> Option<(usize, &'a Workspace)> {
+        workspaces
+            .iter()
+            .enumerate()
+            .find(|(_, workspace)| workspace.name == name)
+    }
+
+    pub fn find_by_name_mut<'a>(
+        workspaces: &'a mut [Workspace],
+        name: &str,
+    ) -> Option<(usize, &'a mut Workspace)> {
+        workspaces
+            .iter_mut()
+            .enumerate()
+            .find(|(_, workspace)| workspace.name == name)
+    }
+
     pub fn mark_as_loaded(&mut self) {
         self.is_loaded = true;
     }
@@ -95,3 +502,403 @@ impl Workspace {
         self.is_loaded = false;
     }
 }
+
+/// What two entries must share to be duplicates: the canonical path when the
+/// folder exists, otherwise the path with `.` and trailing separators gone.
+/// Case is ignored where the file system usually ignores it.
+pub fn duplicate_key(path: &Path) -> PathBuf {
+    let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.components().collect());
+    fold_duplicate_key(key)
+}
+
+fn fold_duplicate_key(key: PathBuf) -> PathBuf {
+    if cfg!(any(windows, target_os = "macos")) {
+        PathBuf::from(key.to_string_lossy().to_lowercase())
+    } else {
+        key
+    }
+}
+
+/// First workspace holding the repository at `path`.
+pub fn find_workspace_containing<'a>(
+    workspaces: &'a mut [Workspace],
+    path: &Path,
+) -> Option<&'a mut Workspace> {
+    workspaces
+        .iter_mut()
+        .find(|workspace| workspace.repositories.iter().any(|r| r.path == path))
+}
+
+/// `SystemTime` stored as whole seconds since the Unix epoch.
+mod unix_timestamp {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        time.map(|time| {
+            time.duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0)
+        })
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?
+            .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds)))
+    }
+}
+
+/// Coarse age for the sidebar: `just now`, `5m ago`, `2h ago`, `3d ago`.
+pub fn format_relative_time(t: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(t)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    if seconds < 60 {
+        "just now".to_string()
+    } else if seconds < 3600 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 86400 {
+        format!("{}h ago", seconds / 3600)
+    } else {
+        format!("{}d ago", seconds / 86400)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(names: &[&str]) -> Vec<Workspace> {
+        names.iter().map(|name| Workspace::new(*name)).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_repository_knows_its_target() {
+        let target = std::env::temp_dir().join("repo_manager_symlink_target");
+        let link = std::env::temp_dir().join("repo_manager_symlink_link");
+        let _ = std::fs::remove_file(&link);
+        std::fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let linked = RepositoryState::new(link.clone());
+        let direct = RepositoryState::new(target.clone());
+        let _ = std::fs::remove_file(&link);
+        let _ = std::fs::remove_dir_all(&target);
+
+        assert!(linked.is_symlink);
+        assert_eq!(linked.canonical_path, direct.canonical_path);
+        assert!(!direct.is_symlink);
+    }
+
+    #[test]
+    fn duplicates_differing_in_trailing_slash_keep_the_first() {
+        // Как в загруженном конфиге: `add_repository` такие пути не пропустил бы
+        let mut workspace = Workspace::new("Client");
+        workspace.repositories = [
+            "/missing/work/api",
+            "/missing/work/web",
+            "/missing/work/api/",
+            "/missing/work/./web",
+        ]
+        .into_iter()
+        .map(|path| RepositoryState::new(PathBuf::from(path)))
+        .collect();
+
+        assert_eq!(workspace.remove_duplicates(), 2);
+        assert_eq!(
+            workspace
+                .repositories
+                .iter()
+                .map(|r| r.path.clone())
+                .collect::<Vec<_>>(),
+            [
+                PathBuf::from("/missing/work/api"),
+                PathBuf::from("/missing/work/web")
+            ]
+        );
+        assert_eq!(workspace.remove_duplicates(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_to_a_listed_repository_is_a_duplicate() {
+        let target = std::env::temp_dir().join("repo_manager_dedup_target");
+        let link = std::env::temp_dir().join("repo_manager_dedup_link");
+        let _ = std::fs::remove_file(&link);
+        std::fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let mut workspace = Workspace::new("Client");
+        workspace.add_repository(target.clone());
+        workspace.add_repository(link.clone());
+        let removed = workspace.remove_duplicates();
+        let _ = std::fs::remove_file(&link);
+        let _ = std::fs::remove_dir_all(&target);
+
+        assert_eq!(removed, 1);
+        assert_eq!(workspace.repositories[0].path, target);
+    }
+
+    #[cfg(any(windows, target_os = "macos"))]
+    #[test]
+    fn case_only_differences_are_duplicates_on_case_insensitive_systems() {
+        let dir = std::env::temp_dir().join("repo_manager_dedup_case");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut workspace = Workspace::new("Client");
+        workspace.add_repository(dir.clone());
+        workspace.add_repository(PathBuf::from(dir.to_string_lossy().to_uppercase()));
+        assert_eq!(workspace.repositories.len(), 2);
+        let removed = workspace.remove_duplicates();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(removed, 1);
+    }
+
+    #[test]
+    fn duplicate_gets_its_own_id_and_keeps_repositories() {
+        let mut original = Workspace::new("Client");
+        original.add_repository(PathBuf::from("/work/api"));
+        original.color = Some(WORKSPACE_COLORS[0]);
+        original.is_loaded = true;
+
+        let copy = original.duplicate("Client (copy)");
+
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.name, "Client (copy)");
+        assert_eq!(copy.repositories.len(), 1);
+        assert_eq!(copy.color, original.color);
+        assert!(!copy.is_loaded);
+    }
+
+    #[test]
+    fn workspace_filter_matches_names_ignoring_case() {
+        let workspaces = named(&["Client Alpha", "internal", "ALPHA tools", "Beta"]);
+
+        assert_eq!(filter_workspaces(&workspaces, ""), vec![0, 1, 2, 3]);
+        assert_eq!(filter_workspaces(&workspaces, "alpha"), vec![0, 2]);
+        assert_eq!(filter_workspaces(&workspaces, "  BETA "), vec![3]);
+        assert!(filter_workspaces(&workspaces, "gamma").is_empty());
+    }
+
+    #[test]
+    fn id_lookup_survives_reordering_and_removal() {
+        let mut workspaces = named(&["One", "Two", "Three"]);
+        let two = workspaces[1].id;
+        let three = workspaces[2].id;
+
+        workspaces.swap(0, 2);
+        assert_eq!(workspace_index(&workspaces, two), Some(1));
+        assert_eq!(workspace_index(&workspaces, three), Some(0));
+
+        workspaces.remove(0);
+        assert_eq!(workspace_index(&workspaces, two), Some(0));
+        assert_eq!(workspace_index(&workspaces, three), None);
+
+        workspaces.insert(0, Workspace::new("Zero"));
+        assert_eq!(workspace_index(&workspaces, two), Some(1));
+    }
+
+    #[test]
+    fn ids_are_unique_and_survive_serialization() {
+        let mut workspaces = named(&["One", "Two"]);
+        assert_ne!(workspaces[0].id, workspaces[1].id);
+
+        let json = serde_json::to_string(&workspaces[0]).unwrap();
+        let restored: Workspace = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.id, workspaces[0].id);
+
+        let legacy: Workspace =
+            serde_json::from_str(r#"{"name":"Old","repositories":[]}"#).unwrap();
+        assert_ne!(legacy.id, restored.id);
+
+        workspaces[1].id = workspaces[0].id;
+        ensure_unique_workspace_ids(&mut workspaces);
+        assert_ne!(workspaces[0].id, workspaces[1].id);
+    }
+
+    #[test]
+    fn archived_workspace_restores_name_and_repositories() {
+        let path = std::env::temp_dir().join("repo_manager_workspace_archive.json");
+        let mut workspace = Workspace::new("Client X");
+        workspace.add_repository(PathBuf::from("/r/x/api"));
+        workspace.add_repository(PathBuf::from("/r/x/web"));
+        workspace.color = Some([220, 80, 80]);
+
+        workspace.archive_to_file(&path).unwrap();
+        let restored = Workspace::restore_from_archive(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(restored.name, "Client X");
+        let paths: Vec<_> = restored.repositories.iter().map(|r| &r.path).collect();
+        assert_eq!(
+            paths,
+            vec![&PathBuf::from("/r/x/api"), &PathBuf::from("/r/x/web")]
+        );
+        assert_eq!(restored.repositories[0].name, "api");
+        assert!(!restored.is_loaded);
+    }
+
+    fn workspaces() -> Vec<Workspace> {
+        let mut work = Workspace::new("Work");
+        work.add_repository(PathBuf::from("/r/work/api"));
+        let mut personal = Workspace::new("Personal");
+        personal.add_repository(PathBuf::from("/r/home/blog"));
+        vec![work, personal]
+    }
+
+    #[test]
+    fn workspaces_are_found_by_exact_name() {
+        let mut workspaces = workspaces();
+
+        let (index, found) = Workspace::find_by_name(&workspaces, "Personal").unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(found.name, "Personal");
+        assert!(Workspace::find_by_name(&workspaces, "personal").is_none());
+
+        let (index, found) = Workspace::find_by_name_mut(&mut workspaces, "Work").unwrap();
+        found.color = Some([90, 180, 100]);
+        assert_eq!(index, 0);
+        assert_eq!(workspaces[0].color, Some([90, 180, 100]));
+    }
+
+    #[test]
+    fn workspace_is_found_by_repository_path() {
+        let mut workspaces = workspaces();
+
+        let found = find_workspace_containing(&mut workspaces, Path::new("/r/home/blog"));
+        assert_eq!(found.map(|w| w.name.clone()), Some("Personal".to_string()));
+        assert!(find_workspace_containing(&mut workspaces, Path::new("/r/home")).is_none());
+    }
+
+    #[test]
+    fn prod_workspaces_always_require_confirmation() {
+        assert!(requires_bulk_confirmation(&EnvironmentLabel::Prod, false));
+        assert!(requires_bulk_confirmation(&EnvironmentLabel::Prod, true));
+        assert!(requires_bulk_confirmation(
+            &EnvironmentLabel::Custom(" Production ".to_string()),
+            false
+        ));
+    }
+
+    #[test]
+    fn other_workspaces_follow_the_global_setting() {
+        for label in [
+            EnvironmentLabel::None,
+            EnvironmentLabel::Dev,
+            EnvironmentLabel::Staging,
+            EnvironmentLabel::Custom("mirrors".to_string()),
+        ] {
+            assert!(!requires_bulk_confirmation(&label, false));
+            assert!(requires_bulk_confirmation(&label, true));
+        }
+    }
+
+    #[test]
+    fn old_workspaces_load_without_label_or_color() {
+        let workspace: Workspace =
+            serde_json::from_str(r#"{"name": "Work", "repositories": []}"#).unwrap();
+
+        assert_eq!(workspace.environment, EnvironmentLabel::None);
+        assert_eq!(workspace.color, None);
+    }
+
+    #[test]
+    fn root_path_is_the_common_ancestor_of_all_repositories() {
+        let mut workspace = Workspace::new("Work");
+        assert_eq!(workspace.root_path(), None);
+
+        workspace.add_repository(PathBuf::from("/home/dev/work/api"));
+        assert_eq!(
+            workspace.root_path(),
+            Some(PathBuf::from("/home/dev/work/api"))
+        );
+
+        workspace.add_repository(PathBuf::from("/home/dev/work/libs/core"));
+        workspace.add_repository(PathBuf::from("/home/dev/work/apiserver"));
+        assert_eq!(workspace.root_path(), Some(PathBuf::from("/home/dev/work")));
+    }
+
+    #[test]
+    fn last_modified_round_trips_as_unix_seconds() {
+        let mut workspace = Workspace::new("Work");
+        workspace.last_modified = Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+        let json = serde_json::to_string(&workspace).unwrap();
+        assert!(json.contains(r#""last_modified":1700000000"#));
+
+        let restored: Workspace = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.last_modified, workspace.last_modified);
+    }
+
+    #[test]
+    fn relative_time_uses_the_largest_unit() {
+        let ago = |seconds| format_relative_time(SystemTime::now() - Duration::from_secs(seconds));
+
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(5 * 60), "5m ago");
+        assert_eq!(ago(2 * 3600 + 59), "2h ago");
+        assert_eq!(ago(3 * 86400), "3d ago");
+        assert_eq!(
+            format_relative_time(SystemTime::now() + Duration::from_secs(60)),
+            "just now"
+        );
+    }
+
+    #[test]
+    fn monorepo_services_are_found_below_the_root() {
+        let root = std::env::temp_dir().join("repo_manager_monorepo_scan");
+        let _ = std::fs::remove_dir_all(&root);
+        for (dir, file) in [
+            ("services/api", "Cargo.toml"),
+            ("services/api/examples/demo", "Cargo.toml"),
+            ("web", "package.json"),
+            ("web/node_modules/left-pad", "package.json"),
+            ("tools/gen/deep/deeper", "go.mod"),
+            (".github/actions/x", "package.json"),
+            ("vendored", "go.mod"),
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join(file), "").unwrap();
+        }
+        std::fs::create_dir_all(root.join("vendored").join(".git")).unwrap();
+
+        let services = Workspace::detect_monorepo_services(&root);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(services, vec![root.join("services/api"), root.join("web")]);
+    }
+
+    #[test]
+    fn monorepo_service_entries_point_at_the_shared_root() {
+        let mut workspace = Workspace::new("Mono");
+        workspace.add_repository(PathBuf::from("/r/mono"));
+
+        assert!(workspace.add_monorepo_service(Path::new("/r/mono"), PathBuf::from("/r/mono/api")));
+        assert!(!workspace.add_monorepo_service(Path::new("/r/mono"), PathBuf::from("/r/mono/api")));
+
+        let service = &workspace.repositories[1];
+        assert_eq!(service.name, "api");
+        assert_eq!(service.infer_project_root(), Path::new("/r/mono"));
+        assert_eq!(
+            workspace.repositories[0].infer_project_root(),
+            Path::new("/r/mono")
+        );
+
+        let json = serde_json::to_string(&workspace).unwrap();
+        let restored: Workspace = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.repositories[0].monorepo_root, None);
+        assert_eq!(
+            restored.repositories[1].monorepo_root,
+            Some(PathBuf::from("/r/mono"))
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectType {
+    Rust,
+    JavaScript,
+    Python,
+    Java,
+    Go,
+    Cpp,
+    Other,
+}
+
+/// Characteristic files in the repository root, checked in order.
+const MARKERS: [(&str, ProjectType); 9] = [
+    ("Cargo.toml", ProjectType::Rust),
+    ("package.json", ProjectType::JavaScript),
+    ("requirements.txt", ProjectType::Python),
+    ("pyproject.toml", ProjectType::Python),
+    ("pom.xml", ProjectType::Java),
+    ("build.gradle", ProjectType::Java),
+    ("go.mod", ProjectType::Go),
+    ("CMakeLists.txt", ProjectType::Cpp),
+    ("meson.build", ProjectType::Cpp),
+];
+
+impl ProjectType {
+    /// Badge shown next to the repository name; `None` for `Other`.
+    pub fn badge(self) -> Option<&'static str> {
+        match self {
+            ProjectType::Rust => Some("🦀"),
+            ProjectType::JavaScript => Some("🟨"),
+            ProjectType::Python => Some("🐍"),
+            ProjectType::Java => Some("☕"),
+            ProjectType::Go => Some("🐹"),
+            ProjectType::Cpp => Some("C++"),
+            ProjectType::Other => None,
+        }
+    }
+
+    pub fn color(self) -> egui::Color32 {
+        match self {
+            ProjectType::Rust => egui::Color32::from_rgb(222, 165, 132),
+            ProjectType::JavaScript => egui::Color32::from_rgb(240, 219, 79),
+            ProjectType::Python => egui::Color32::from_rgb(75, 139, 190),
+            ProjectType::Java => egui::Color32::from_rgb(176, 114, 25),
+            ProjectType::Go => egui::Color32::from_rgb(0, 173, 216),
+            ProjectType::Cpp => egui::Color32::from_rgb(100, 154, 210),
+            ProjectType::Other => egui::Color32::GRAY,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProjectType::Rust => "Rust",
+            ProjectType::JavaScript => "JavaScript",
+            ProjectType::Python => "Python",
+            ProjectType::Java => "Java",
+            ProjectType::Go => "Go",
+            ProjectType::Cpp => "C++",
+            ProjectType::Other => "Other",
+        }
+    }
+}
+
+/// `dir` holds one of the build files that mark a project root.
+pub fn has_project_marker(dir: &Path) -> bool {
+    MARKERS.iter().any(|(file, _)| dir.join(file).is_file())
+}
+
+pub fn detect_project_type(repo_path: &Path) -> ProjectType {
+    MARKERS
+        .iter()
+        .find(|(file, _)| repo_path.join(file).is_file())
+        .map(|(_, project_type)| *project_type)
+        .unwrap_or(ProjectType::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect_with(name: &str, files: &[&str]) -> ProjectType {
+        let root = std::env::temp_dir().join(format!("repo_manager_project_{}", name));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        for file in files {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let project_type = detect_project_type(&root);
+        let _ = std::fs::remove_dir_all(&root);
+        project_type
+    }
+
+    #[test]
+    fn marker_files_decide_the_type() {
+        assert_eq!(detect_with("rust", &["Cargo.toml"]), ProjectType::Rust);
+        assert_eq!(
+            detect_with("python", &["pyproject.toml"]),
+            ProjectType::Python
+        );
+        assert_eq!(detect_with("java", &["build.gradle"]), ProjectType::Java);
+        assert_eq!(detect_with("docs", &["README.md"]), ProjectType::Other);
+    }
+
+    #[test]
+    fn first_marker_wins_in_mixed_repositories() {
+        assert_eq!(
+            detect_with("mixed", &["package.json", "Cargo.toml"]),
+            ProjectType::Rust
+        );
+    }
+}
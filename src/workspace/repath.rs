@@ -0,0 +1,201 @@
+use super::Workspace;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Minimum number of missing repositories under one vanished folder before
+/// the recovery banner suggests repathing it.
+pub const MISSING_PREFIX_THRESHOLD: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingPrefix {
+    pub prefix: PathBuf,
+    pub repo_count: usize,
+}
+
+/// Rewrites every repository path under `old_prefix` to the same relative
+/// location under `new_prefix`, in all workspaces. Only the path changes, the
+/// rest of the repository state is kept. Returns the number of rewritten entries.
+pub fn rewrite_repo_paths(
+    workspaces: &mut [Workspace],
+    old_prefix: &Path,
+    new_prefix: &Path,
+) -> usize {
+    let mut rewritten = 0;
+
+    for workspace in workspaces.iter_mut() {
+        for repo in &mut workspace.repositories {
+            if let Ok(rest) = repo.path.strip_prefix(old_prefix) {
+                repo.path = new_prefix.join(rest);
+                repo.detect_symlink();
+                rewritten += 1;
+            }
+        }
+
+        // Если репозиторий уже был добавлен по новому пути, оставляем одну запись
+        let mut seen = std::collections::HashSet::new();
+        workspace
+            .repositories
+            .retain(|repo| seen.insert(repo.path.clone()));
+    }
+
+    rewritten
+}
+
+/// Splits the repositories under `old_prefix` into those found under
+/// `new_prefix` and those that are not there.
+pub fn verify_repath(
+    repo_paths: &[PathBuf],
+    old_prefix: &Path,
+    new_prefix: &Path,
+    exists: impl Fn(&Path) -> bool,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    repo_paths
+        .iter()
+        .filter_map(|path| path.strip_prefix(old_prefix).ok())
+        .map(|rest| new_prefix.join(rest))
+        .partition(|path| exists(path))
+}
+
+/// Groups missing repositories by the topmost ancestor that no longer
+/// exists, which is the folder that was most likely renamed or moved.
+/// Groups with fewer than `threshold` repositories are ignored.
+pub fn find_missing_prefixes(
+    repo_paths: &[PathBuf],
+    exists: impl Fn(&Path) -> bool,
+    threshold: usize,
+) -> Vec<MissingPrefix> {
+    let mut groups: BTreeMap<PathBuf, usize> = BTreeMap::new();
+
+    for path in repo_paths {
+        if exists(path) {
+            continue;
+        }
+
+        let vanished = path
+            .ancestors()
+            .take_while(|ancestor| {
+                ancestor.parent().is_some() && !ancestor.as_os_str().is_empty() && !exists(ancestor)
+            })
+            .last();
+
+        if let Some(vanished) = vanished {
+            if vanished != path.as_path() {
+                *groups.entry(vanished.to_path_buf()).or_default() += 1;
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, count)| *count >= threshold)
+        .map(|(prefix, repo_count)| MissingPrefix { prefix, repo_count })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn paths(items: &[&str]) -> Vec<PathBuf> {
+        items.iter().map(PathBuf::from).collect()
+    }
+
+    fn fs(existing: &[&str]) -> impl Fn(&Path) -> bool {
+        let existing: HashSet<PathBuf> = existing.iter().map(PathBuf::from).collect();
+        move |path: &Path| existing.iter().any(|e| e == path || e.starts_with(path))
+    }
+
+    #[test]
+    fn three_missing_repos_under_renamed_folder_are_detected() {
+        let repos = paths(&[
+            "/home/u/projects/a",
+            "/home/u/projects/b",
+            "/home/u/projects/nested/c",
+            "/home/u/other/d",
+        ]);
+        let exists = fs(&["/home/u/src/a", "/home/u/other/d"]);
+
+        let missing = find_missing_prefixes(&repos, exists, MISSING_PREFIX_THRESHOLD);
+
+        assert_eq!(
+            missing,
+            vec![MissingPrefix {
+                prefix: PathBuf::from("/home/u/projects"),
+                repo_count: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn fewer_than_threshold_missing_repos_are_ignored() {
+        let repos = paths(&["/home/u/projects/a", "/home/u/projects/b"]);
+        let exists = fs(&["/home/u/other"]);
+
+        assert!(find_missing_prefixes(&repos, exists, MISSING_PREFIX_THRESHOLD).is_empty());
+    }
+
+    #[test]
+    fn individually_deleted_repos_do_not_form_a_prefix() {
+        let repos = paths(&[
+            "/home/u/projects/a",
+            "/home/u/projects/b",
+            "/home/u/projects/c",
+        ]);
+        let exists = fs(&["/home/u/projects/keep"]);
+
+        assert!(find_missing_prefixes(&repos, exists, MISSING_PREFIX_THRESHOLD).is_empty());
+    }
+
+    #[test]
+    fn separate_vanished_folders_are_reported_separately() {
+        let repos = paths(&[
+            "/w/one/a", "/w/one/b", "/w/one/c", "/w/two/a", "/w/two/b", "/w/two/c",
+        ]);
+        let exists = fs(&["/w/three"]);
+
+        let missing = find_missing_prefixes(&repos, exists, MISSING_PREFIX_THRESHOLD);
+
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].prefix, PathBuf::from("/w/one"));
+        assert_eq!(missing[1].prefix, PathBuf::from("/w/two"));
+    }
+
+    #[test]
+    fn rewrite_updates_repos_in_every_workspace() {
+        let mut work = Workspace::new("Work");
+        work.add_repository(PathBuf::from("/home/u/projects/a"));
+        work.add_repository(PathBuf::from("/srv/b"));
+        let mut home = Workspace::new("Home");
+        home.add_repository(PathBuf::from("/home/u/projects/a"));
+        let mut workspaces = vec![work, home];
+
+        let rewritten = rewrite_repo_paths(
+            &mut workspaces,
+            Path::new("/home/u/projects"),
+            Path::new("/home/u/src"),
+        );
+
+        assert_eq!(rewritten, 2);
+        assert_eq!(
+            workspaces[0].repositories[0].path,
+            PathBuf::from("/home/u/src/a")
+        );
+        assert_eq!(workspaces[0].repositories[1].path, PathBuf::from("/srv/b"));
+        assert_eq!(
+            workspaces[1].repositories[0].path,
+            PathBuf::from("/home/u/src/a")
+        );
+    }
+
+    #[test]
+    fn verify_reports_repos_absent_from_new_location() {
+        let repos = paths(&["/old/a", "/old/b", "/elsewhere/c"]);
+        let exists = fs(&["/new/a"]);
+
+        let (found, absent) = verify_repath(&repos, Path::new("/old"), Path::new("/new"), exists);
+
+        assert_eq!(found, paths(&["/new/a"]));
+        assert_eq!(absent, paths(&["/new/b"]));
+    }
+}
@@ -0,0 +1,246 @@
+mod harness;
+
+use harness::{FixtureRemote, FixtureRepo, TempDir};
+use repo_manager::git::{
+    get_git_info, git_abort_pending, git_cat_file, git_check_dirty_noise, git_continue_pending,
+    git_fetch, git_local_only_commits, git_pull, pending_operation, switch_branch, DirtyNoise,
+    PendingOperation,
+};
+use std::process::Command;
+
+#[test]
+fn git_info_reports_branch_changes_and_upstream() {
+    let Some(tmp) = TempDir::new("git_info") else {
+        return;
+    };
+    let remote = FixtureRemote::new(tmp.join("origin.git"));
+    remote.advance(1);
+    let repo = FixtureRepo::clone_of(&remote, tmp.join("work"))
+        .commit(2)
+        .dirty_file("notes.txt");
+
+    let info = get_git_info(repo.path()).unwrap();
+
+    assert_eq!(info.current_branch.as_deref(), Some("main"));
+    assert_eq!(info.default_branch.as_deref(), Some("main"));
+    assert_eq!((info.ahead, info.behind), (2, 0));
+    assert!(info.has_changes);
+    assert!(info.has_upstream);
+    assert_eq!(info.remotes, ["origin"]);
+    assert_eq!(info.remote_url, Some(remote.url()));
+
+    // Новая ветка без upstream
+    let repo = repo.branch("feature");
+    let info = get_git_info(repo.path()).unwrap();
+
+    assert_eq!(info.current_branch.as_deref(), Some("feature"));
+    assert!(!info.has_upstream);
+    assert!(info.branches.iter().any(|branch| branch == "feature"));
+}
+
+#[test]
+fn fetch_then_pull_catches_up_with_the_remote() {
+    let Some(tmp) = TempDir::new("fetch_pull") else {
+        return;
+    };
+    let remote = FixtureRemote::new(tmp.join("origin.git"));
+    remote.advance(1);
+    let repo = FixtureRepo::clone_of(&remote, tmp.join("work"));
+    remote.advance(3);
+
+    // До fetch локальный репозиторий ничего не знает о новых коммитах
+    assert_eq!(get_git_info(repo.path()).unwrap().behind, 0);
+
+    git_fetch(repo.path()).unwrap();
+    let info = get_git_info(repo.path()).unwrap();
+    assert_eq!((info.ahead, info.behind), (0, 3));
+
+    git_pull(repo.path()).unwrap();
+    let info = get_git_info(repo.path()).unwrap();
+    assert_eq!((info.ahead, info.behind), (0, 0));
+    assert_eq!(repo.head(), repo.git(&["rev-parse", "origin/main"]));
+}
+
+#[test]
+fn pull_merges_local_and_remote_commits() {
+    let Some(tmp) = TempDir::new("pull_merge") else {
+        return;
+    };
+    let remote = FixtureRemote::new(tmp.join("origin.git"));
+    remote.advance(1);
+    let repo = FixtureRepo::clone_of(&remote, tmp.join("work")).commit(1);
+    remote.advance(2);
+
+    git_pull(repo.path()).unwrap();
+    let info = get_git_info(repo.path()).unwrap();
+
+    // Локальный коммит и коммит слияния ещё не отправлены
+    assert_eq!((info.ahead, info.behind), (2, 0));
+
+    let repo = repo.push();
+    assert_eq!(get_git_info(repo.path()).unwrap().ahead, 0);
+}
+
+#[test]
+fn switch_branch_checks_out_local_and_remote_branches() {
+    let Some(tmp) = TempDir::new("switch_branch") else {
+        return;
+    };
+    let remote = FixtureRemote::new(tmp.join("origin.git"));
+    remote.advance(1);
+    remote.advance_branch("feature/login", 2);
+    let repo = FixtureRepo::clone_of(&remote, tmp.join("work")).branch("local-only");
+
+    switch_branch(repo.path(), "main").unwrap();
+    assert_eq!(repo.current_branch(), "main");
+
+    // remotes/origin/<name> создаёт отслеживающую локальную ветку
+    switch_branch(repo.path(), "remotes/origin/feature/login").unwrap();
+    let info = get_git_info(repo.path()).unwrap();
+    assert_eq!(info.current_branch.as_deref(), Some("feature/login"));
+    assert!(info.has_upstream);
+    assert_eq!(
+        repo.head(),
+        repo.git(&["rev-parse", "origin/feature/login"])
+    );
+
+    // Повторный переход берёт уже существующую локальную ветку
+    let repo = repo.commit(1);
+    switch_branch(repo.path(), "main").unwrap();
+    switch_branch(repo.path(), "remotes/origin/feature/login").unwrap();
+    assert_eq!(repo.current_branch(), "feature/login");
+    assert_eq!(get_git_info(repo.path()).unwrap().ahead, 1);
+
+    assert!(switch_branch(repo.path(), "remotes/origin").is_err());
+    assert!(switch_branch(repo.path(), "missing").is_err());
+}
+
+#[test]
+fn cat_file_reads_a_file_as_of_an_older_commit() {
+    let Some(tmp) = TempDir::new("cat_file") else {
+        return;
+    };
+    let repo = FixtureRepo::new(tmp.join("work")).commit_file("src/lib.rs", "fn v1() {}\n");
+    let first = repo.head();
+    let repo = repo.commit_file("src/lib.rs", "fn v2() {}\n");
+
+    assert_eq!(
+        git_cat_file(repo.path(), &first, "src/lib.rs").unwrap(),
+        b"fn v1() {}\n"
+    );
+    assert_eq!(
+        git_cat_file(repo.path(), "HEAD", "src/lib.rs").unwrap(),
+        b"fn v2() {}\n"
+    );
+    // Не-UTF-8 содержимое возвращается байт в байт
+    let repo = repo.commit_file("latin1.txt", b"caf\xe9\n");
+    assert_eq!(
+        git_cat_file(repo.path(), "HEAD", "latin1.txt").unwrap(),
+        b"caf\xe9\n"
+    );
+    assert!(git_cat_file(repo.path(), &first, "missing.rs").is_err());
+}
+
+#[test]
+fn local_only_commits_survive_a_gone_upstream() {
+    let Some(tmp) = TempDir::new("gone_branch") else {
+        return;
+    };
+    let remote = FixtureRemote::new(tmp.join("origin.git"));
+    remote.advance(1);
+    let repo = FixtureRepo::clone_of(&remote, tmp.join("work"))
+        .branch("feature")
+        .commit(2)
+        .push();
+
+    // Ветку удалили на сервере после слияния
+    repo.git(&["push", "--quiet", "origin", "--delete", "feature"]);
+    repo.git(&["fetch", "--quiet", "--prune"]);
+    assert!(get_git_info(repo.path()).unwrap().upstream_gone);
+
+    // Коммиты ещё есть в origin/main, если его слили туда
+    repo.git(&["push", "--quiet", "origin", "feature:main"]);
+    repo.git(&["fetch", "--quiet"]);
+    assert_eq!(
+        git_local_only_commits(repo.path(), "feature", "main").unwrap(),
+        0
+    );
+
+    // Неотправленный коммит после удаления upstream
+    let repo = repo.commit(1);
+    assert_eq!(
+        git_local_only_commits(repo.path(), "feature", "main").unwrap(),
+        1
+    );
+    assert!(git_local_only_commits(repo.path(), "missing", "main").is_err());
+}
+
+#[test]
+fn crlf_rewrite_is_reported_as_line_ending_noise() {
+    let Some(tmp) = TempDir::new("dirty_noise") else {
+        return;
+    };
+    let repo = FixtureRepo::new(tmp.join("work"))
+        .commit_file("a.txt", "one\ntwo\n")
+        .commit_file("b.txt", "three\n");
+    repo.git(&["config", "core.autocrlf", "false"]);
+
+    std::fs::write(repo.path().join("a.txt"), "one\r\ntwo\r\n").unwrap();
+    assert_eq!(
+        git_check_dirty_noise(repo.path()).unwrap(),
+        DirtyNoise::LineEndings(vec!["a.txt".to_string()])
+    );
+
+    std::fs::write(repo.path().join("b.txt"), "changed\n").unwrap();
+    assert_eq!(
+        git_check_dirty_noise(repo.path()).unwrap(),
+        DirtyNoise::Genuine
+    );
+}
+
+#[test]
+fn conflicted_rebase_and_cherry_pick_are_aborted_or_continued() {
+    let Some(tmp) = TempDir::new("pending_operation") else {
+        return;
+    };
+    let repo = FixtureRepo::new(tmp.join("work"))
+        .commit_file("a.txt", "base\n")
+        .branch("feature")
+        .commit_file("a.txt", "feature\n")
+        .checkout("main")
+        .commit_file("a.txt", "main\n");
+    let path = repo.path().clone();
+    let stopped = |args: &[&str]| {
+        let status = Command::new("git")
+            .current_dir(&path)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(!status.success(), "git {} did not stop", args.join(" "));
+    };
+
+    stopped(&["cherry-pick", "feature"]);
+    assert_eq!(
+        pending_operation(repo.path()),
+        Some(PendingOperation::CherryPick)
+    );
+    git_abort_pending(repo.path()).unwrap();
+    assert_eq!(pending_operation(repo.path()), None);
+    assert!(git_abort_pending(repo.path()).is_err());
+
+    let repo = repo.checkout("feature");
+    stopped(&["rebase", "main"]);
+    assert_eq!(
+        pending_operation(repo.path()),
+        Some(PendingOperation::Rebase)
+    );
+    std::fs::write(repo.path().join("a.txt"), "resolved\n").unwrap();
+    git_continue_pending(repo.path()).unwrap();
+    assert_eq!(pending_operation(repo.path()), None);
+    assert_eq!(repo.current_branch(), "feature");
+    assert_eq!(
+        repo.git(&["rev-parse", "HEAD~1"]),
+        repo.git(&["rev-parse", "main"])
+    );
+}
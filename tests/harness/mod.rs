@@ -0,0 +1,267 @@
+//! Fixture git repositories for integration tests.
+//!
+//! Fixtures drive the system `git` binary in a fresh folder under the temp
+//! dir, so the code under test works on real repositories:
+//!
+//! ```ignore
+//! mod harness;
+//! use harness::{FixtureRemote, FixtureRepo, TempDir};
+//!
+//! let Some(tmp) = TempDir::new("pull") else {
+//!     return;
+//! };
+//! let remote = FixtureRemote::new(tmp.join("origin.git"));
+//! remote.advance(1);
+//! let repo = FixtureRepo::clone_of(&remote, tmp.join("work"))
+//!     .commit(2)
+//!     .branch("feature")
+//!     .set_upstream("origin/main")
+//!     .dirty_file("a.txt");
+//! ```
+//!
+//! `TempDir::new` returns `None` when git is not installed, so the test
+//! returns early instead of failing. A `FixtureRemote` is a bare repository
+//! used as a `file://` origin: fetch, pull and push run without network.
+//! Every commit adds a file with a unique name, so commits made in
+//! different clones never conflict.
+
+// Каждый файл в tests/ собирает модуль заново и использует только часть
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+pub fn git_available() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Runs git in `dir`; panics with its stderr when it fails.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success(),
+        "git {} failed in {}: {}",
+        args.join(" "),
+        dir.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Identity and settings independent of the user's global git config.
+fn configure(path: &Path) {
+    for (key, value) in [
+        ("user.name", "Fixture"),
+        ("user.email", "fixture@example.com"),
+        ("commit.gpgsign", "false"),
+        ("pull.rebase", "false"),
+    ] {
+        git(path, &["config", key, value]);
+    }
+}
+
+/// Folder for one test, removed on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `None`, with a note on stderr, when git is not installed.
+    pub fn new(label: &str) -> Option<Self> {
+        if !git_available() {
+            eprintln!("git not found, skipping {}", label);
+            return None;
+        }
+        let path = std::env::temp_dir().join(format!(
+            "repo_manager_it_{}_{}_{}",
+            label,
+            std::process::id(),
+            next_id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Some(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Working repository built step by step; every step runs git right away.
+pub struct FixtureRepo {
+    path: PathBuf,
+}
+
+impl FixtureRepo {
+    /// Empty repository on `main`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        std::fs::create_dir_all(&path).unwrap();
+        git(&path, &["init", "--quiet"]);
+        git(&path, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        configure(&path);
+        Self { path }
+    }
+
+    /// Clone of `remote`, tracking `origin/main` once the remote has commits.
+    pub fn clone_of(remote: &FixtureRemote, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let parent = path.parent().expect("fixture path has a parent");
+        std::fs::create_dir_all(parent).unwrap();
+        git(
+            parent,
+            &["clone", "--quiet", &remote.url(), &path.to_string_lossy()],
+        );
+        configure(&path);
+        Self { path }
+    }
+
+    /// Path in the form the crate's git functions take.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn git(&self, args: &[&str]) -> String {
+        git(&self.path, args)
+    }
+
+    /// `n` commits, each adding one new file.
+    pub fn commit(self, n: usize) -> Self {
+        for _ in 0..n {
+            let name = format!("file{}.txt", next_id());
+            std::fs::write(self.path.join(&name), &name).unwrap();
+            self.git(&["add", &name]);
+            self.git(&["commit", "--quiet", "-m", &format!("Add {}", name)]);
+        }
+        self
+    }
+
+    /// Commits `contents` as `name`, creating folders as needed.
+    pub fn commit_file(self, name: &str, contents: impl AsRef<[u8]>) -> Self {
+        let path = self.path.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        self.git(&["add", name]);
+        self.git(&["commit", "--quiet", "-m", &format!("Update {}", name)]);
+        self
+    }
+
+    /// Creates `name` at HEAD and checks it out.
+    pub fn branch(self, name: &str) -> Self {
+        self.git(&["checkout", "--quiet", "-b", name]);
+        self
+    }
+
+    pub fn checkout(self, name: &str) -> Self {
+        self.git(&["checkout", "--quiet", name]);
+        self
+    }
+
+    /// Makes the current branch track `upstream`, e.g. `origin/main`.
+    pub fn set_upstream(self, upstream: &str) -> Self {
+        self.git(&["branch", "--quiet", "--set-upstream-to", upstream]);
+        self
+    }
+
+    /// Uncommitted change: a new file, or an edit when `name` is tracked.
+    pub fn dirty_file(self, name: &str) -> Self {
+        std::fs::write(self.path.join(name), "uncommitted\n").unwrap();
+        self
+    }
+
+    /// Pushes the current branch to `origin` and tracks it.
+    pub fn push(self) -> Self {
+        self.git(&["push", "--quiet", "-u", "origin", "HEAD"]);
+        self
+    }
+
+    pub fn head(&self) -> String {
+        self.git(&["rev-parse", "HEAD"])
+    }
+
+    pub fn current_branch(&self) -> String {
+        self.git(&["branch", "--show-current"])
+    }
+}
+
+/// Bare repository serving as a `file://` origin. Commits are added through
+/// throwaway clones, as if another developer pushed them.
+pub struct FixtureRemote {
+    path: PathBuf,
+}
+
+impl FixtureRemote {
+    /// Empty bare repository whose default branch is `main`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        std::fs::create_dir_all(&path).unwrap();
+        git(&path, &["init", "--quiet", "--bare"]);
+        git(&path, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn url(&self) -> String {
+        let path = self.path.to_string_lossy().replace('\\', "/");
+        if path.starts_with('/') {
+            format!("file://{}", path)
+        } else {
+            // C:/... на Windows
+            format!("file:///{}", path)
+        }
+    }
+
+    /// Pushes `n` new commits to `main`.
+    pub fn advance(&self, n: usize) {
+        self.advance_branch("main", n);
+    }
+
+    /// Pushes `n` new commits to `branch`, creating it from `main` (or from
+    /// nothing in an empty remote) when it does not exist yet.
+    pub fn advance_branch(&self, branch: &str, n: usize) {
+        let writer_path = self.path.with_file_name(format!("writer{}", next_id()));
+        let writer = FixtureRepo::clone_of(self, &writer_path);
+        let tracking = format!("origin/{}", branch);
+        if !writer
+            .git(&["ls-remote", "--heads", "origin", branch])
+            .is_empty()
+        {
+            writer.git(&["checkout", "--quiet", "-B", branch, &tracking]);
+        } else if writer.current_branch() != branch {
+            writer.git(&["checkout", "--quiet", "-b", branch]);
+        }
+        writer.commit(n).git(&[
+            "push",
+            "--quiet",
+            "origin",
+            &format!("HEAD:refs/heads/{}", branch),
+        ]);
+        let _ = std::fs::remove_dir_all(&writer_path);
+    }
+}